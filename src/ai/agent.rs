@@ -1,10 +1,18 @@
 //! Agent orchestration for AI-driven document generation
 
-use super::client::{ChatMessage, OpenRouterClient};
+use super::cancel::CancellationToken;
+use super::client::{ChatMessage, ClientError, OpenRouterClient, TokenUsage};
 use super::prompts::{
-    generate_error_recovery_prompt, generate_system_prompt, generate_user_prompt, PromptTemplate,
+    generate_error_recovery_prompt, generate_system_prompt, generate_tool_editing_prompt,
+    generate_user_prompt, generate_verification_retry_prompt, PromptTemplate,
 };
-use super::verify::{verify_change, VerificationResult};
+use super::tools::{all_tool_definitions, dispatch_tool_call, ReadJsonTool, ToolContext};
+use super::verify::{
+    retry_feedback, verify_change, verify_change_with_vision, VerificationResult,
+    DEFAULT_CONFIDENCE_THRESHOLD,
+};
+use crate::data::SlickSheetData;
+use crate::template::TemplateEngine;
 
 /// Configuration for the agent loop
 #[derive(Debug, Clone)]
@@ -13,9 +21,14 @@ pub struct AgentConfig {
     pub max_iterations: usize,
     /// Model to use for generation
     pub model: String,
-    /// Whether to use visual verification (planned feature)
-    #[allow(dead_code)]
+    /// Whether to check each compiled result with a vision model
     pub enable_visual_verification: bool,
+    /// Minimum vision-model confidence to accept a result
+    pub confidence_threshold: f64,
+    /// Maximum model turns in tool-calling mode
+    pub max_tool_rounds: usize,
+    /// Models to try in order when `model` is unavailable
+    pub fallback_models: Vec<String>,
 }
 
 impl Default for AgentConfig {
@@ -24,6 +37,9 @@ impl Default for AgentConfig {
             max_iterations: 3,
             model: "google/gemini-3-flash-preview".to_string(),
             enable_visual_verification: false,
+            confidence_threshold: DEFAULT_CONFIDENCE_THRESHOLD,
+            max_tool_rounds: 10,
+            fallback_models: Vec::new(),
         }
     }
 }
@@ -41,6 +57,8 @@ pub struct AgentState {
     pub last_svg: Option<String>,
     /// Last generated Typst code
     pub last_code: Option<String>,
+    /// Feedback from a rejected visual verification, if any
+    pub last_feedback: Option<String>,
 }
 
 impl AgentState {
@@ -52,6 +70,7 @@ impl AgentState {
             last_error: None,
             last_svg: None,
             last_code: None,
+            last_feedback: None,
         }
     }
 
@@ -85,6 +104,20 @@ pub enum AgentResult {
         code: String,
         svg: String,
         iterations: usize,
+        /// Model that produced the final code
+        model: String,
+    },
+    /// Tool-calling run finished with validated data and template
+    ToolSuccess {
+        data: Box<SlickSheetData>,
+        template: String,
+        code: String,
+        svg: String,
+        iterations: usize,
+        /// Model that produced the final response
+        model: String,
+        /// Token usage summed over the run's requests, if the API reported any
+        usage: Option<TokenUsage>,
     },
     /// Generation failed after max iterations
     MaxIterationsReached {
@@ -100,18 +133,35 @@ pub struct AgentLoop {
     client: OpenRouterClient,
     config: AgentConfig,
     state: AgentState,
+    /// Messages sent between the system prompt and the request
+    context: Vec<ChatMessage>,
 }
 
 impl AgentLoop {
     /// Create a new agent loop
     pub fn new(client: OpenRouterClient, config: AgentConfig) -> Self {
+        let client = if config.fallback_models.is_empty() {
+            client
+        } else {
+            client.with_fallback_models(config.fallback_models.clone())
+        };
         Self {
             client,
             config,
             state: AgentState::new(),
+            context: Vec::new(),
         }
     }
 
+    /// Builder method to send extra context ahead of the request
+    ///
+    /// Used by tool-calling runs for earlier accepted exchanges and for
+    /// notes such as the images available to the document.
+    pub fn with_context(mut self, context: Vec<ChatMessage>) -> Self {
+        self.context = context;
+        self
+    }
+
     /// Get the current state (public API for monitoring)
     #[allow(dead_code)]
     pub fn state(&self) -> &AgentState {
@@ -147,7 +197,14 @@ impl AgentLoop {
             self.state.increment_iteration();
 
             // Determine which prompt to use
-            let (system_prompt, user_prompt) = if let Some(error) = &self.state.last_error {
+            let (system_prompt, user_prompt) = if let Some(feedback) = &self.state.last_feedback {
+                // Revise after visual verification rejected the output
+                let code = self.state.last_code.as_deref().unwrap_or("");
+                (
+                    generate_system_prompt(PromptTemplate::TypstGeneration),
+                    generate_verification_retry_prompt(request, code, feedback),
+                )
+            } else if let Some(error) = &self.state.last_error {
                 // Error recovery mode
                 let code = self.state.last_code.as_deref().unwrap_or("");
                 (
@@ -169,8 +226,9 @@ impl AgentLoop {
             ];
 
             // Call the LLM
-            let generated_code = match self.client.chat(&self.config.model, messages).await {
-                Ok(code) => code,
+            let (model, generated_code) = match self.client.chat(&self.config.model, messages).await
+            {
+                Ok(response) => (response.served_model().to_string(), response.content),
                 Err(e) => return AgentResult::Error(format!("LLM request failed: {}", e)),
             };
 
@@ -179,6 +237,7 @@ impl AgentLoop {
 
             // Store the code
             self.state.last_code = Some(cleaned_code.clone());
+            self.state.last_feedback = None;
             self.state.add_to_history(cleaned_code.clone());
 
             // Try to compile
@@ -192,10 +251,28 @@ impl AgentLoop {
 
                     match verification {
                         VerificationResult::Success { .. } => {
+                            // Only worth asking when another attempt is possible
+                            if self.config.enable_visual_verification
+                                && self.state.should_continue(&self.config)
+                            {
+                                let visual = verify_change_with_vision(
+                                    &self.client,
+                                    &self.config.model,
+                                    request,
+                                    &svg,
+                                    self.config.confidence_threshold,
+                                )
+                                .await;
+                                if let Some(feedback) = retry_feedback(&visual) {
+                                    self.state.last_feedback = Some(feedback);
+                                    continue;
+                                }
+                            }
                             return AgentResult::Success {
                                 code: cleaned_code,
                                 svg,
                                 iterations: self.state.iteration,
+                                model,
                             };
                         }
                         VerificationResult::NeedsRetry { reason, .. } => {
@@ -221,6 +298,118 @@ impl AgentLoop {
             last_error: self.state.last_error.clone(),
         }
     }
+
+    /// Run the agent loop using function calling over the data/template tools
+    ///
+    /// The model is offered the `AiTool` definitions; each tool call is
+    /// dispatched and its result fed back as a tool message until the model
+    /// replies without calling tools. The final data is then rendered and
+    /// compiled; a failure is reported back and the loop continues. Firing
+    /// `cancel` ends the run with `AgentResult::Error`.
+    pub async fn run_with_tools<F>(
+        &mut self,
+        request: &str,
+        data: SlickSheetData,
+        template: &str,
+        mut compile_fn: F,
+        cancel: &CancellationToken,
+    ) -> AgentResult
+    where
+        F: FnMut(&str) -> Result<String, String>,
+    {
+        self.reset();
+
+        let api_key = self.client.config().api_key.clone();
+        let mut ctx = ToolContext::new(data, template).with_api_key(api_key);
+        let tools = all_tool_definitions();
+        let current_json = ReadJsonTool::execute(&ctx.data).message().to_string();
+        let mut messages = vec![ChatMessage::system(generate_system_prompt(
+            PromptTemplate::ToolBasedEditing,
+        ))];
+        messages.extend(self.context.iter().cloned());
+        messages.push(ChatMessage::user(generate_tool_editing_prompt(
+            request,
+            &current_json,
+            &ctx.template,
+        )));
+
+        let mut usage: Option<TokenUsage> = None;
+        while self.state.iteration < self.config.max_tool_rounds {
+            self.state.increment_iteration();
+
+            let response = match self
+                .client
+                .chat_with_tools(&self.config.model, &messages, &tools, cancel)
+                .await
+            {
+                Ok(response) => response,
+                Err(e) => return AgentResult::Error(request_failure(&e)),
+            };
+            usage = match (usage, response.usage) {
+                (Some(a), Some(b)) => Some(a + b),
+                (a, b) => a.or(b),
+            };
+
+            if !response.tool_calls.is_empty() {
+                messages.push(ChatMessage::assistant_tool_calls(
+                    response.content,
+                    response.tool_calls.clone(),
+                ));
+                for call in &response.tool_calls {
+                    let result = dispatch_tool_call(call, &mut ctx, &mut compile_fn).await;
+                    if !result.is_success() {
+                        self.state.last_error = Some(result.message().to_string());
+                    }
+                    messages.push(
+                        ChatMessage::tool(call.id.clone(), result.message().to_string())
+                            .with_name(call.function.name.clone()),
+                    );
+                }
+                continue;
+            }
+
+            // The model stopped calling tools - check the final document
+            let model = response.served_model().to_string();
+            messages.push(ChatMessage::assistant(response.content));
+            let compiled = TemplateEngine::render(&ctx.template, &ctx.data)
+                .map_err(|errors| format!("Template render failed: {}", errors.join(", ")))
+                .and_then(|code| compile_fn(&code).map(|svg| (code, svg)));
+
+            match compiled {
+                Ok((code, svg)) => {
+                    return AgentResult::ToolSuccess {
+                        data: Box::new(ctx.data),
+                        template: ctx.template,
+                        code,
+                        svg,
+                        iterations: self.state.iteration,
+                        model,
+                        usage,
+                    };
+                }
+                Err(error) => {
+                    messages.push(ChatMessage::user(format!(
+                        "The document does not compile: {}\nUse the tools to fix it.",
+                        error
+                    )));
+                    self.state.last_error = Some(error);
+                }
+            }
+        }
+
+        AgentResult::MaxIterationsReached {
+            last_code: TemplateEngine::render(&ctx.template, &ctx.data).ok(),
+            last_error: self.state.last_error.clone(),
+        }
+    }
+}
+
+/// Describe a failed LLM request, with a hint on fixing it when there is one
+fn request_failure(error: &ClientError) -> String {
+    match error.guidance() {
+        Some(hint) => format!("LLM request failed: {}\n{}", error, hint),
+        None => format!("LLM request failed: {}", error),
+    }
 }
 
 /// Clean generated code by removing markdown code fences
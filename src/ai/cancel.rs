@@ -0,0 +1,104 @@
+//! Cancellation tokens for in-flight AI requests
+//!
+//! A `CancellationToken` is shared between the code that starts a request
+//! and the UI that may want to stop it. On WASM the token also owns an
+//! `AbortController` so the underlying `fetch` is aborted, not just ignored.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+#[derive(Debug, Default)]
+struct TokenState {
+    cancelled: bool,
+    wakers: Vec<Waker>,
+}
+
+/// Token used to cancel an in-flight request
+///
+/// Clones share the same state, so cancelling any clone cancels them all.
+#[derive(Debug, Clone)]
+pub struct CancellationToken {
+    state: Arc<Mutex<TokenState>>,
+    #[cfg(target_arch = "wasm32")]
+    controller: Option<web_sys::AbortController>,
+}
+
+impl Default for CancellationToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CancellationToken {
+    /// Create a new, uncancelled token
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(TokenState::default())),
+            #[cfg(target_arch = "wasm32")]
+            controller: web_sys::AbortController::new().ok(),
+        }
+    }
+
+    /// Cancel the token, waking anything waiting on it
+    pub fn cancel(&self) {
+        let wakers = {
+            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            if state.cancelled {
+                return;
+            }
+            state.cancelled = true;
+            std::mem::take(&mut state.wakers)
+        };
+
+        #[cfg(target_arch = "wasm32")]
+        if let Some(controller) = &self.controller {
+            controller.abort();
+        }
+
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+
+    /// Check whether the token has been cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.state
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .cancelled
+    }
+
+    /// Future that resolves once the token is cancelled
+    pub fn cancelled(&self) -> Cancelled<'_> {
+        Cancelled { token: self }
+    }
+
+    /// Abort signal to attach to a `fetch` request
+    #[cfg(target_arch = "wasm32")]
+    pub fn abort_signal(&self) -> Option<web_sys::AbortSignal> {
+        self.controller.as_ref().map(|c| c.signal())
+    }
+}
+
+/// Future returned by [`CancellationToken::cancelled`]
+#[derive(Debug)]
+pub struct Cancelled<'a> {
+    token: &'a CancellationToken,
+}
+
+impl Future for Cancelled<'_> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let mut state = self.token.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.cancelled {
+            return Poll::Ready(());
+        }
+        if !state.wakers.iter().any(|w| w.will_wake(cx.waker())) {
+            state.wakers.push(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+}
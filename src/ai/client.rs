@@ -1,6 +1,19 @@
 //! OpenRouter API client for LLM interactions
 
+use super::cancel::CancellationToken;
+use futures::future::{self, Either};
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use thiserror::Error;
+
+/// HTTP status codes worth retrying: rate limits and transient server errors
+const RETRYABLE_STATUS: [u16; 5] = [429, 500, 502, 503, 504];
+
+/// HTTP status codes meaning the model itself is out of capacity
+const MODEL_UNAVAILABLE_STATUS: [u16; 2] = [502, 503];
+
+/// Upper bound for a single backoff delay
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
 
 /// Message roles in a chat conversation
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -9,6 +22,7 @@ pub enum Role {
     System,
     User,
     Assistant,
+    Tool,
 }
 
 /// A chat message with role and content
@@ -16,12 +30,31 @@ pub enum Role {
 pub struct ChatMessage {
     pub role: Role,
     pub content: String,
+    /// Tool calls requested by the assistant
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCall>,
+    /// ID of the tool call a tool message answers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+    /// Name of the function a tool message reports on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Image URLs (usually data URLs) attached to a user message
+    #[serde(skip)]
+    pub images: Vec<String>,
 }
 
 impl ChatMessage {
     /// Create a new chat message
     pub fn new(role: Role, content: String) -> Self {
-        Self { role, content }
+        Self {
+            role,
+            content,
+            tool_calls: Vec::new(),
+            tool_call_id: None,
+            name: None,
+            images: Vec::new(),
+        }
     }
 
     /// Create a system message
@@ -39,6 +72,89 @@ impl ChatMessage {
     pub fn assistant(content: String) -> Self {
         Self::new(Role::Assistant, content)
     }
+
+    /// Create an assistant message carrying tool calls
+    pub fn assistant_tool_calls(content: String, tool_calls: Vec<ToolCall>) -> Self {
+        Self {
+            tool_calls,
+            ..Self::new(Role::Assistant, content)
+        }
+    }
+
+    /// Create a tool result message answering a tool call
+    pub fn tool(tool_call_id: String, content: String) -> Self {
+        Self {
+            tool_call_id: Some(tool_call_id),
+            ..Self::new(Role::Tool, content)
+        }
+    }
+
+    /// Builder method to attach an image URL to the message
+    pub fn with_image(mut self, url: impl Into<String>) -> Self {
+        self.images.push(url.into());
+        self
+    }
+
+    /// Builder method to set the function name on a tool result message
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+}
+
+/// A tool the model may call, in OpenAI function-calling format
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ToolDefinition {
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub function: FunctionDefinition,
+}
+
+/// Name, description and JSON Schema parameters of a callable function
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FunctionDefinition {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub parameters: serde_json::Value,
+}
+
+impl ToolDefinition {
+    /// Create a function tool definition
+    pub fn function(
+        name: &'static str,
+        description: &'static str,
+        parameters: serde_json::Value,
+    ) -> Self {
+        Self {
+            kind: "function",
+            function: FunctionDefinition {
+                name,
+                description,
+                parameters,
+            },
+        }
+    }
+}
+
+/// A tool call requested by the model
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolCall {
+    pub id: String,
+    #[serde(rename = "type", default = "function_kind")]
+    pub kind: String,
+    pub function: FunctionCall,
+}
+
+/// Function name and JSON-encoded arguments of a tool call
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FunctionCall {
+    pub name: String,
+    #[serde(default)]
+    pub arguments: String,
+}
+
+fn function_kind() -> String {
+    "function".to_string()
 }
 
 /// Configuration for the OpenRouter client
@@ -52,6 +168,18 @@ pub struct OpenRouterConfig {
     pub http_referer: String,
     /// App title for OpenRouter attribution
     pub x_title: String,
+    /// Total attempts per request, including the first
+    pub max_attempts: u32,
+    /// Base delay for exponential backoff between attempts
+    pub retry_base_delay: Duration,
+    /// Models to try in order when the requested model is unavailable
+    pub fallback_models: Vec<String>,
+    /// Sampling temperature, or the provider default when unset
+    pub temperature: Option<f32>,
+    /// Nucleus sampling cutoff, or the provider default when unset
+    pub top_p: Option<f32>,
+    /// Maximum tokens to generate, or the provider default when unset
+    pub max_tokens: Option<u32>,
 }
 
 impl Default for OpenRouterConfig {
@@ -61,6 +189,12 @@ impl Default for OpenRouterConfig {
             base_url: "https://openrouter.ai/api/v1".to_string(),
             http_referer: "https://slicksheetstudio.app".to_string(),
             x_title: "Slick Sheet Studio".to_string(),
+            max_attempts: 3,
+            retry_base_delay: Duration::from_millis(500),
+            fallback_models: Vec::new(),
+            temperature: None,
+            top_p: None,
+            max_tokens: None,
         }
     }
 }
@@ -73,6 +207,170 @@ impl OpenRouterConfig {
             ..Default::default()
         }
     }
+
+    /// Builder method to set the referer OpenRouter attributes usage to
+    pub fn with_referer(mut self, referer: impl Into<String>) -> Self {
+        self.http_referer = referer.into();
+        self
+    }
+
+    /// Builder method to set the app title OpenRouter attributes usage to
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.x_title = title.into();
+        self
+    }
+
+    /// Headers sent with every OpenRouter request, including attribution
+    pub fn headers(&self) -> [(&'static str, String); 4] {
+        [
+            ("Content-Type", "application/json".to_string()),
+            ("Authorization", format!("Bearer {}", self.api_key)),
+            ("HTTP-Referer", self.http_referer.clone()),
+            ("X-Title", self.x_title.clone()),
+        ]
+    }
+}
+
+/// Token counts reported by the API for a single request
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+pub struct TokenUsage {
+    /// Tokens in the prompt
+    #[serde(rename = "prompt_tokens", default)]
+    pub prompt: u32,
+    /// Tokens in the completion
+    #[serde(rename = "completion_tokens", default)]
+    pub completion: u32,
+    /// Total tokens billed
+    #[serde(rename = "total_tokens", default)]
+    pub total: u32,
+    /// Cost in credits, when OpenRouter reports it
+    #[serde(default)]
+    pub cost: Option<f64>,
+}
+
+impl std::ops::Add for TokenUsage {
+    type Output = Self;
+
+    /// Totals for two requests; cost is summed over the requests that report it
+    fn add(self, other: Self) -> Self {
+        Self {
+            prompt: self.prompt + other.prompt,
+            completion: self.completion + other.completion,
+            total: self.total + other.total,
+            cost: match (self.cost, other.cost) {
+                (Some(a), Some(b)) => Some(a + b),
+                (a, b) => a.or(b),
+            },
+        }
+    }
+}
+
+/// A completed chat response
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChatResponse {
+    /// Message content from the first choice
+    pub content: String,
+    /// Token usage, if the API reported it
+    pub usage: Option<TokenUsage>,
+    /// Tool calls requested by the model, empty for plain replies
+    pub tool_calls: Vec<ToolCall>,
+    /// Model the API reports produced the response (empty if unreported)
+    pub model: String,
+    /// Model that was requested, which may be a fallback from the chain
+    pub requested_model: String,
+}
+
+impl ChatResponse {
+    /// The reported model, or the requested one when the API did not say
+    pub fn served_model(&self) -> &str {
+        if self.model.is_empty() {
+            &self.requested_model
+        } else {
+            &self.model
+        }
+    }
+}
+
+/// Errors returned by chat completion requests
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum ClientError {
+    /// The request was cancelled before it completed
+    #[error("Request cancelled")]
+    Cancelled,
+
+    /// The API rejected the API key (HTTP 401)
+    #[error("Authentication failed: {0}")]
+    Unauthorized(String),
+
+    /// Too many requests (HTTP 429)
+    #[error("Rate limited{}", .retry_after.map(|d| format!(" (retry after {}s)", d.as_secs())).unwrap_or_default())]
+    RateLimited { retry_after: Option<Duration> },
+
+    /// The model is overloaded or offline (HTTP 502/503)
+    #[error("Model unavailable: {0}")]
+    ModelUnavailable(String),
+
+    /// Any other server-side HTTP error
+    #[error("HTTP {status}: {message}")]
+    Server { status: u16, message: String },
+
+    /// The request could not be sent or its response could not be read
+    #[error("Network error: {0}")]
+    Network(String),
+
+    /// The API responded with an error or an unusable body
+    #[error("{0}")]
+    BadResponse(String),
+}
+
+impl ClientError {
+    /// Map an unsuccessful HTTP status and its error message onto a variant
+    pub fn from_status(status: u16, retry_after: Option<Duration>, message: String) -> Self {
+        match status {
+            401 => Self::Unauthorized(message),
+            429 => Self::RateLimited { retry_after },
+            _ if MODEL_UNAVAILABLE_STATUS.contains(&status) => Self::ModelUnavailable(message),
+            _ => Self::Server { status, message },
+        }
+    }
+
+    /// Whether the request may succeed if sent again
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::RateLimited { .. } | Self::ModelUnavailable(_) => true,
+            Self::Server { status, .. } => RETRYABLE_STATUS.contains(status),
+            _ => false,
+        }
+    }
+
+    /// Whether the model is overloaded or unavailable, so another may work
+    pub fn is_model_unavailable(&self) -> bool {
+        matches!(self, Self::RateLimited { .. } | Self::ModelUnavailable(_))
+    }
+
+    /// How long the server asked us to wait before retrying, if it said
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::RateLimited { retry_after } => *retry_after,
+            _ => None,
+        }
+    }
+
+    /// A hint on what the user can do about this error, for display
+    pub fn guidance(&self) -> Option<&'static str> {
+        match self {
+            Self::Unauthorized(_) => Some(
+                "Check your API key in Settings (expected format: sk-or-v1-..., \
+                get one at openrouter.ai/keys).",
+            ),
+            Self::RateLimited { .. } => Some("Too many requests - wait a moment and try again."),
+            Self::ModelUnavailable(_) => {
+                Some("The model is busy or offline - try again later or pick another model.")
+            }
+            Self::Network(_) => Some("Check your internet connection and try again."),
+            _ => None,
+        }
+    }
 }
 
 /// OpenRouter API client
@@ -85,14 +383,51 @@ pub struct OpenRouterClient {
 #[derive(Debug, Serialize)]
 struct ChatCompletionRequest<'a> {
     model: &'a str,
+    #[serde(serialize_with = "serialize_messages")]
     messages: &'a [ChatMessage],
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    tools: &'a [ToolDefinition],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_tokens: Option<u32>,
+}
+
+/// Serialize messages, sending content as text and image parts when a
+/// message carries images
+pub(super) fn serialize_messages<S>(
+    messages: &&[ChatMessage],
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    use serde::ser::{Error, SerializeSeq};
+
+    let mut seq = serializer.serialize_seq(Some(messages.len()))?;
+    for message in messages.iter() {
+        let mut value = serde_json::to_value(message).map_err(S::Error::custom)?;
+        if !message.images.is_empty() {
+            let mut parts = vec![serde_json::json!({ "type": "text", "text": message.content })];
+            parts.extend(message.images.iter().map(
+                |url| serde_json::json!({ "type": "image_url", "image_url": { "url": url } }),
+            ));
+            value["content"] = serde_json::Value::Array(parts);
+        }
+        seq.serialize_element(&value)?;
+    }
+    seq.end()
 }
 
 /// Response from chat completions
 #[derive(Debug, Deserialize)]
 struct ChatCompletionResponse {
+    model: Option<String>,
     choices: Option<Vec<Choice>>,
     error: Option<ApiError>,
+    usage: Option<TokenUsage>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -102,7 +437,10 @@ struct Choice {
 
 #[derive(Debug, Deserialize)]
 struct MessageContent {
-    content: String,
+    #[serde(default)]
+    content: Option<String>,
+    #[serde(default)]
+    tool_calls: Option<Vec<ToolCall>>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -116,6 +454,12 @@ impl OpenRouterClient {
         Self { config }
     }
 
+    /// Replace the fallback models tried when the requested model is unavailable
+    pub fn with_fallback_models(mut self, models: Vec<String>) -> Self {
+        self.config.fallback_models = models;
+        self
+    }
+
     /// Get the client configuration (public API)
     #[allow(dead_code)]
     pub fn config(&self) -> &OpenRouterConfig {
@@ -124,12 +468,29 @@ impl OpenRouterClient {
 
     /// Build the request body JSON for a chat completion
     pub fn build_request_body(&self, model: &str, messages: &[ChatMessage]) -> String {
-        let request = ChatCompletionRequest { model, messages };
+        self.build_request_body_with_tools(model, messages, &[])
+    }
+
+    /// Build the request body JSON, advertising the given tools
+    pub fn build_request_body_with_tools(
+        &self,
+        model: &str,
+        messages: &[ChatMessage],
+        tools: &[ToolDefinition],
+    ) -> String {
+        let request = ChatCompletionRequest {
+            model,
+            messages,
+            tools,
+            temperature: self.config.temperature,
+            top_p: self.config.top_p,
+            max_tokens: self.config.max_tokens,
+        };
         serde_json::to_string(&request).unwrap_or_default()
     }
 
-    /// Parse the API response and extract the content
-    pub fn parse_response(response: &str) -> Result<String, String> {
+    /// Parse the API response and extract the content and usage
+    pub fn parse_response(response: &str) -> Result<ChatResponse, String> {
         let parsed: ChatCompletionResponse = serde_json::from_str(response)
             .map_err(|e| format!("Failed to parse response: {}", e))?;
 
@@ -147,16 +508,186 @@ impl OpenRouterClient {
             return Err("Empty choices array".to_string());
         }
 
-        Ok(choices[0].message.content.clone())
+        let message = &choices[0].message;
+        Ok(ChatResponse {
+            content: message.content.clone().unwrap_or_default(),
+            usage: parsed.usage,
+            tool_calls: message.tool_calls.clone().unwrap_or_default(),
+            model: parsed.model.unwrap_or_default(),
+            requested_model: String::new(),
+        })
+    }
+
+    /// Send a chat completion request
+    pub async fn chat(
+        &self,
+        model: &str,
+        messages: Vec<ChatMessage>,
+    ) -> Result<ChatResponse, ClientError> {
+        self.chat_cancellable(model, messages, &CancellationToken::new())
+            .await
+    }
+
+    /// Send a chat completion request that stops early when `cancel` fires
+    pub async fn chat_cancellable(
+        &self,
+        model: &str,
+        messages: Vec<ChatMessage>,
+        cancel: &CancellationToken,
+    ) -> Result<ChatResponse, ClientError> {
+        self.send_with_fallback(model, &messages, &[], cancel).await
+    }
+
+    /// Send a chat completion request advertising tools the model may call
+    ///
+    /// Stops early with `ClientError::Cancelled` when `cancel` fires.
+    pub async fn chat_with_tools(
+        &self,
+        model: &str,
+        messages: &[ChatMessage],
+        tools: &[ToolDefinition],
+        cancel: &CancellationToken,
+    ) -> Result<ChatResponse, ClientError> {
+        self.send_with_fallback(model, messages, tools, cancel)
+            .await
+    }
+
+    /// Send to `model`, moving down the fallback chain while models are unavailable
+    async fn send_with_fallback(
+        &self,
+        model: &str,
+        messages: &[ChatMessage],
+        tools: &[ToolDefinition],
+        cancel: &CancellationToken,
+    ) -> Result<ChatResponse, ClientError> {
+        let candidates: Vec<&str> = std::iter::once(model)
+            .chain(self.config.fallback_models.iter().map(String::as_str))
+            .collect();
+
+        let mut result = Err(ClientError::BadResponse("No model to try".to_string()));
+        for candidate in candidates {
+            crate::log::debug(&format!("OpenRouter request: model={}", candidate));
+            let body = self.build_request_body_with_tools(candidate, messages, tools);
+            result = self.send_with_retry(&body, cancel).await;
+            match &mut result {
+                Ok(response) => {
+                    response.requested_model = candidate.to_string();
+                    break;
+                }
+                Err(err) if err.is_model_unavailable() => continue,
+                Err(_) => break,
+            }
+        }
+        result
+    }
+
+    /// Send a request body, retrying transient errors with backoff
+    async fn send_with_retry(
+        &self,
+        body: &str,
+        cancel: &CancellationToken,
+    ) -> Result<ChatResponse, ClientError> {
+        let mut attempt = 1;
+        loop {
+            let result = until_cancelled(cancel, self.send_chat(body, cancel)).await;
+            match result {
+                Err(err) if err.is_retryable() && attempt < self.config.max_attempts => {
+                    let delay = err
+                        .retry_after()
+                        .unwrap_or_else(|| backoff_delay(self.config.retry_base_delay, attempt))
+                        .min(MAX_RETRY_DELAY);
+                    until_cancelled(cancel, async {
+                        sleep(delay).await;
+                        Ok(())
+                    })
+                    .await?;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Turn a raw HTTP response into a chat response or a typed error
+    pub(crate) fn handle_response(
+        status: u16,
+        retry_after: Option<Duration>,
+        text: &str,
+    ) -> Result<ChatResponse, ClientError> {
+        if status == 401 || RETRYABLE_STATUS.contains(&status) {
+            return Err(ClientError::from_status(
+                status,
+                retry_after,
+                error_message(text),
+            ));
+        }
+
+        Self::parse_response(text).map_err(ClientError::BadResponse)
+    }
+
+    /// Check the API key with a cheap authenticated request
+    ///
+    /// Queries the key's own info endpoint, so no tokens are spent.
+    pub async fn validate_key(&self) -> Result<(), ClientError> {
+        if self.config.api_key.trim().is_empty() {
+            return Err(ClientError::Unauthorized("No API key entered".to_string()));
+        }
+
+        let (status, text) = self.send_get("key").await?;
+        if (200..300).contains(&status) {
+            Ok(())
+        } else {
+            Err(ClientError::from_status(status, None, error_message(&text)))
+        }
+    }
+
+    /// Send an authenticated GET to `path` under the base URL (async, for WASM)
+    #[cfg(target_arch = "wasm32")]
+    async fn send_get(&self, path: &str) -> Result<(u16, String), ClientError> {
+        use gloo_net::http::Request;
+
+        let url = format!("{}/{}", self.config.base_url, path);
+        let response = Request::get(&url)
+            .header("Authorization", &format!("Bearer {}", self.config.api_key))
+            .send()
+            .await
+            .map_err(|e| ClientError::Network(format!("Request failed: {:?}", e)))?;
+        let status = response.status();
+        let text = response
+            .text()
+            .await
+            .map_err(|e| ClientError::Network(format!("Failed to read response: {:?}", e)))?;
+        Ok((status, text))
+    }
+
+    /// Send an authenticated GET to `path` under the base URL (async, for native builds)
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn send_get(&self, path: &str) -> Result<(u16, String), ClientError> {
+        let url = format!("{}/{}", self.config.base_url, path);
+        let response = reqwest::Client::new()
+            .get(&url)
+            .header("Authorization", format!("Bearer {}", self.config.api_key))
+            .send()
+            .await
+            .map_err(|e| ClientError::Network(format!("Request failed: {}", e)))?;
+        let status = response.status().as_u16();
+        let text = response
+            .text()
+            .await
+            .map_err(|e| ClientError::Network(format!("Failed to read response: {}", e)))?;
+        Ok((status, text))
     }
 
     /// Send a chat completion request (async, for WASM)
     #[cfg(target_arch = "wasm32")]
-    pub async fn chat(&self, model: &str, messages: Vec<ChatMessage>) -> Result<String, String> {
+    async fn send_chat(
+        &self,
+        body: &str,
+        cancel: &CancellationToken,
+    ) -> Result<ChatResponse, ClientError> {
         use gloo_net::http::Request;
-        use wasm_bindgen::JsValue;
 
-        let body = self.build_request_body(model, &messages);
+        let body = body.to_string();
         let url = format!("{}/chat/completions", self.config.base_url);
 
         // Log for debugging - show key prefix to help identify issues
@@ -169,34 +700,37 @@ impl OpenRouterClient {
         } else {
             "[too short]".to_string()
         };
-        web_sys::console::log_1(&JsValue::from_str(&format!(
-            "OpenRouter request: url={}, model={}, key={} (len={})",
+        crate::log::debug(&format!(
+            "OpenRouter request: url={}, key={} (len={})",
             url,
-            model,
             key_preview,
             self.config.api_key.len()
-        )));
+        ));
 
         // Build request with headers and body
-        let response = Request::post(&url)
-            .header("Content-Type", "application/json")
-            .header("Authorization", &format!("Bearer {}", self.config.api_key))
-            .header("HTTP-Referer", &self.config.http_referer)
-            .header("X-Title", &self.config.x_title)
+        let response = self
+            .config
+            .headers()
+            .iter()
+            .fold(Request::post(&url), |request, (name, value)| {
+                request.header(name, value)
+            })
+            .abort_signal(cancel.abort_signal().as_ref())
             .body(body)
-            .map_err(|e| format!("Failed to build request: {:?}", e))?
+            .map_err(|e| ClientError::Network(format!("Failed to build request: {:?}", e)))?
             .send()
             .await
-            .map_err(|e| format!("Request failed: {:?}", e))?;
+            .map_err(|e| ClientError::Network(format!("Request failed: {:?}", e)))?;
 
         let status = response.status();
+        let retry_after = parse_retry_after(response.headers().get("Retry-After").as_deref());
         let text = response
             .text()
             .await
-            .map_err(|e| format!("Failed to read response: {:?}", e))?;
+            .map_err(|e| ClientError::Network(format!("Failed to read response: {:?}", e)))?;
 
         // Log response for debugging
-        web_sys::console::log_1(&JsValue::from_str(&format!(
+        crate::log::debug(&format!(
             "Response (status {}): {}",
             status,
             if text.len() > 300 {
@@ -204,44 +738,103 @@ impl OpenRouterClient {
             } else {
                 &text
             }
-        )));
-
-        // If we got a 401, provide more helpful error
-        if status == 401 {
-            return Err(format!(
-                "Authentication failed (401). Your API key may be invalid. \
-                Expected format: sk-or-v1-xxxxx (get one at openrouter.ai/keys). \
-                Server message: {}",
-                text
-            ));
-        }
+        ));
 
-        Self::parse_response(&text)
+        Self::handle_response(status, retry_after, &text)
     }
 
     /// Send a chat completion request (async, for native builds)
     #[cfg(not(target_arch = "wasm32"))]
-    pub async fn chat(&self, model: &str, messages: Vec<ChatMessage>) -> Result<String, String> {
-        let body = self.build_request_body(model, &messages);
+    async fn send_chat(
+        &self,
+        body: &str,
+        _cancel: &CancellationToken,
+    ) -> Result<ChatResponse, ClientError> {
+        let body = body.to_string();
         let url = format!("{}/chat/completions", self.config.base_url);
 
         let client = reqwest::Client::new();
-        let response = client
-            .post(&url)
-            .header("Content-Type", "application/json")
-            .header("Authorization", format!("Bearer {}", self.config.api_key))
-            .header("HTTP-Referer", &self.config.http_referer)
-            .header("X-Title", &self.config.x_title)
+        let response = self
+            .config
+            .headers()
+            .into_iter()
+            .fold(client.post(&url), |request, (name, value)| {
+                request.header(name, value)
+            })
             .body(body)
             .send()
             .await
-            .map_err(|e| format!("Request failed: {}", e))?;
-
+            .map_err(|e| ClientError::Network(format!("Request failed: {}", e)))?;
+
+        let status = response.status().as_u16();
+        let retry_after = parse_retry_after(
+            response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|value| value.to_str().ok()),
+        );
         let text = response
             .text()
             .await
-            .map_err(|e| format!("Failed to read response: {}", e))?;
+            .map_err(|e| ClientError::Network(format!("Failed to read response: {}", e)))?;
+
+        Self::handle_response(status, retry_after, &text)
+    }
+}
+
+/// The API's error message from a response body, or the trimmed body itself
+fn error_message(text: &str) -> String {
+    serde_json::from_str::<ChatCompletionResponse>(text)
+        .ok()
+        .and_then(|parsed| parsed.error)
+        .map(|error| error.message)
+        .unwrap_or_else(|| text.trim().to_string())
+}
+
+/// Run a request future, returning `ClientError::Cancelled` if the token fires first
+async fn until_cancelled<T>(
+    cancel: &CancellationToken,
+    request: impl std::future::Future<Output = Result<T, ClientError>>,
+) -> Result<T, ClientError> {
+    if cancel.is_cancelled() {
+        return Err(ClientError::Cancelled);
+    }
+
+    futures::pin_mut!(request);
+    let cancelled = cancel.cancelled();
+    futures::pin_mut!(cancelled);
 
-        Self::parse_response(&text)
+    match future::select(cancelled, request).await {
+        Either::Left(_) => Err(ClientError::Cancelled),
+        Either::Right((result, _)) => result,
     }
 }
+
+/// Parse a `Retry-After` header given in whole seconds
+pub fn parse_retry_after(value: Option<&str>) -> Option<Duration> {
+    value?.trim().parse::<u64>().ok().map(Duration::from_secs)
+}
+
+/// Exponential backoff with jitter: a random delay in `[d/2, d]` where
+/// `d = base * 2^(attempt - 1)`
+pub fn backoff_delay(base: Duration, attempt: u32) -> Duration {
+    // uuid v4 is already available on both targets as a random source
+    let jitter = (uuid::Uuid::new_v4().as_u128() % 1000) as f64 / 1000.0;
+    backoff_delay_with_jitter(base, attempt, jitter)
+}
+
+/// Backoff for `attempt` given a jitter fraction in `[0, 1]`: 0 waits half
+/// the exponential delay, 1 waits all of it
+pub fn backoff_delay_with_jitter(base: Duration, attempt: u32, jitter: f64) -> Duration {
+    let exponential = base.saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)));
+    let capped = exponential.min(MAX_RETRY_DELAY);
+    capped.mul_f64(0.5 + 0.5 * jitter.clamp(0.0, 1.0))
+}
+
+/// Wait for the given duration on the current platform's timer
+async fn sleep(delay: Duration) {
+    #[cfg(target_arch = "wasm32")]
+    gloo_timers::future::sleep(delay).await;
+    #[cfg(not(target_arch = "wasm32"))]
+    tokio::time::sleep(delay).await;
+}
@@ -5,17 +5,93 @@
 
 use serde::{Deserialize, Serialize};
 
-use super::client::{ChatMessage, OpenRouterConfig, Role};
+use super::client::{ChatMessage, OpenRouterConfig};
 
 /// Model for image generation
 pub const IMAGE_MODEL: &str = "google/gemini-3-pro-image-preview";
 
+/// Aspect ratios accepted by the image model
+pub const SUPPORTED_ASPECT_RATIOS: [&str; 10] = [
+    "1:1", "2:3", "3:2", "3:4", "4:3", "4:5", "5:4", "9:16", "16:9", "21:9",
+];
+
+/// Smallest width or height the provider will generate, in pixels
+pub const MIN_IMAGE_DIMENSION: u32 = 256;
+
+/// Largest width or height the provider will generate, in pixels
+pub const MAX_IMAGE_DIMENSION: u32 = 4096;
+
+/// Output shape requested from the image model
+///
+/// Unset fields are left to the provider (square, model-default size).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct GenerateOptions {
+    /// Aspect ratio such as "16:9" (see `SUPPORTED_ASPECT_RATIOS`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub aspect_ratio: Option<String>,
+    /// Requested width in pixels
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    /// Requested height in pixels
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+}
+
+impl GenerateOptions {
+    /// Options requesting an aspect ratio only
+    pub fn with_aspect_ratio(aspect_ratio: impl Into<String>) -> Self {
+        Self {
+            aspect_ratio: Some(aspect_ratio.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Builder method to request exact dimensions
+    pub fn with_size(mut self, width: u32, height: u32) -> Self {
+        self.width = Some(width);
+        self.height = Some(height);
+        self
+    }
+
+    /// Whether no output shape was requested
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Check the options against the provider's limits
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(ratio) = &self.aspect_ratio {
+            if !SUPPORTED_ASPECT_RATIOS.contains(&ratio.as_str()) {
+                return Err(format!(
+                    "Unsupported aspect ratio '{}' (expected one of {})",
+                    ratio,
+                    SUPPORTED_ASPECT_RATIOS.join(", ")
+                ));
+            }
+        }
+        for (name, value) in [("width", self.width), ("height", self.height)] {
+            if let Some(value) = value {
+                if !(MIN_IMAGE_DIMENSION..=MAX_IMAGE_DIMENSION).contains(&value) {
+                    return Err(format!(
+                        "Image {} {}px is outside the supported range {}-{}px",
+                        name, value, MIN_IMAGE_DIMENSION, MAX_IMAGE_DIMENSION
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
 /// Request body for image generation (with modalities)
 #[derive(Debug, Serialize)]
 struct ImageGenRequest<'a> {
     model: &'a str,
     messages: &'a [ChatMessage],
     modalities: Vec<&'a str>,
+    /// OpenRouter image parameters, omitted when nothing was requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    image_config: Option<&'a GenerateOptions>,
 }
 
 /// Response from image generation
@@ -72,16 +148,14 @@ impl ImageGenerator {
     }
 
     /// Build the request body JSON for image generation
-    fn build_request_body(&self, prompt: &str) -> String {
-        let messages = vec![ChatMessage {
-            role: Role::User,
-            content: prompt.to_string(),
-        }];
+    fn build_request_body(&self, prompt: &str, options: &GenerateOptions) -> String {
+        let messages = vec![ChatMessage::user(prompt.to_string())];
 
         let request = ImageGenRequest {
             model: IMAGE_MODEL,
             messages: &messages,
             modalities: vec!["image", "text"],
+            image_config: (!options.is_empty()).then_some(options),
         };
 
         serde_json::to_string(&request).unwrap_or_default()
@@ -136,25 +210,31 @@ impl ImageGenerator {
     ///
     /// Returns the image bytes and MIME type
     #[cfg(target_arch = "wasm32")]
-    pub async fn generate(&self, prompt: &str) -> Result<(Vec<u8>, String), String> {
+    pub async fn generate(
+        &self,
+        prompt: &str,
+        options: &GenerateOptions,
+    ) -> Result<(Vec<u8>, String), String> {
         use gloo_net::http::Request;
-        use wasm_bindgen::JsValue;
 
-        let body = self.build_request_body(prompt);
+        options.validate()?;
+        let body = self.build_request_body(prompt, options);
         let url = format!("{}/chat/completions", self.config.base_url);
 
         // Log for debugging
-        web_sys::console::log_1(&JsValue::from_str(&format!(
+        crate::log::debug(&format!(
             "Image generation request: url={}, model={}",
             url, IMAGE_MODEL
-        )));
+        ));
 
         // Build request with headers and body
-        let response = Request::post(&url)
-            .header("Content-Type", "application/json")
-            .header("Authorization", &format!("Bearer {}", self.config.api_key))
-            .header("HTTP-Referer", &self.config.http_referer)
-            .header("X-Title", &self.config.x_title)
+        let response = self
+            .config
+            .headers()
+            .iter()
+            .fold(Request::post(&url), |request, (name, value)| {
+                request.header(name, value)
+            })
             .body(body)
             .map_err(|e| format!("Failed to build request: {:?}", e))?
             .send()
@@ -168,7 +248,7 @@ impl ImageGenerator {
             .map_err(|e| format!("Failed to read response: {:?}", e))?;
 
         // Log response for debugging
-        web_sys::console::log_1(&JsValue::from_str(&format!(
+        crate::log::debug(&format!(
             "Image generation response (status {}): {}",
             status,
             if text.len() > 500 {
@@ -176,7 +256,7 @@ impl ImageGenerator {
             } else {
                 text.clone()
             }
-        )));
+        ));
 
         // Handle authentication errors
         if status == 401 {
@@ -193,17 +273,23 @@ impl ImageGenerator {
 
     /// Generate an image from a text prompt (native builds)
     #[cfg(not(target_arch = "wasm32"))]
-    pub async fn generate(&self, prompt: &str) -> Result<(Vec<u8>, String), String> {
-        let body = self.build_request_body(prompt);
+    pub async fn generate(
+        &self,
+        prompt: &str,
+        options: &GenerateOptions,
+    ) -> Result<(Vec<u8>, String), String> {
+        options.validate()?;
+        let body = self.build_request_body(prompt, options);
         let url = format!("{}/chat/completions", self.config.base_url);
 
         let client = reqwest::Client::new();
-        let response = client
-            .post(&url)
-            .header("Content-Type", "application/json")
-            .header("Authorization", format!("Bearer {}", self.config.api_key))
-            .header("HTTP-Referer", &self.config.http_referer)
-            .header("X-Title", &self.config.x_title)
+        let response = self
+            .config
+            .headers()
+            .into_iter()
+            .fold(client.post(&url), |request, (name, value)| {
+                request.header(name, value)
+            })
             .body(body)
             .send()
             .await
@@ -218,14 +304,6 @@ impl ImageGenerator {
     }
 }
 
-/// Decode base64 string to bytes
-fn decode_base64(encoded: &str) -> Result<Vec<u8>, String> {
-    use base64::Engine;
-    base64::engine::general_purpose::STANDARD
-        .decode(encoded)
-        .map_err(|e| format!("Base64 decode error: {}", e))
-}
-
 /// Model for alt description generation (fast, cheap model)
 pub const ALT_DESCRIPTION_MODEL: &str = "google/gemini-2.0-flash-001";
 
@@ -247,22 +325,10 @@ pub async fn generate_alt_description(
     let user_prompt = format!("The image was generated with this prompt: {}", prompt);
 
     let messages = vec![
-        ChatMessage {
-            role: Role::System,
-            content: system_prompt.to_string(),
-        },
-        ChatMessage {
-            role: Role::User,
-            content: user_prompt,
-        },
+        ChatMessage::system(system_prompt.to_string()),
+        ChatMessage::user(user_prompt),
     ];
 
-    #[derive(serde::Serialize)]
-    struct AltDescRequest<'a> {
-        model: &'a str,
-        messages: &'a [ChatMessage],
-    }
-
     let request = AltDescRequest {
         model: ALT_DESCRIPTION_MODEL,
         messages: &messages,
@@ -271,11 +337,12 @@ pub async fn generate_alt_description(
     let body = serde_json::to_string(&request).map_err(|e| format!("Serialize error: {}", e))?;
     let url = format!("{}/chat/completions", config.base_url);
 
-    let response = Request::post(&url)
-        .header("Content-Type", "application/json")
-        .header("Authorization", &format!("Bearer {}", config.api_key))
-        .header("HTTP-Referer", &config.http_referer)
-        .header("X-Title", &config.x_title)
+    let response = config
+        .headers()
+        .iter()
+        .fold(Request::post(&url), |request, (name, value)| {
+            request.header(name, value)
+        })
         .body(body)
         .map_err(|e| format!("Failed to build request: {:?}", e))?
         .send()
@@ -287,37 +354,7 @@ pub async fn generate_alt_description(
         .await
         .map_err(|e| format!("Failed to read response: {:?}", e))?;
 
-    // Parse response to extract content
-    #[derive(serde::Deserialize)]
-    struct AltDescResponse {
-        choices: Option<Vec<AltDescChoice>>,
-    }
-
-    #[derive(serde::Deserialize)]
-    struct AltDescChoice {
-        message: AltDescMessage,
-    }
-
-    #[derive(serde::Deserialize)]
-    struct AltDescMessage {
-        content: String,
-    }
-
-    let parsed: AltDescResponse =
-        serde_json::from_str(&text).map_err(|e| format!("Failed to parse response: {}", e))?;
-
-    let choices = parsed
-        .choices
-        .ok_or_else(|| "No choices in response".to_string())?;
-
-    if choices.is_empty() {
-        return Err("Empty choices array".to_string());
-    }
-
-    // Clean up the response (remove quotes, trim whitespace)
-    let alt = choices[0].message.content.trim();
-    let alt = alt.trim_matches('"');
-    Ok(alt.to_string())
+    parse_alt_description(&text)
 }
 
 /// Generate a short alt description for an image based on its generation prompt (native builds)
@@ -334,22 +371,10 @@ pub async fn generate_alt_description(
     let user_prompt = format!("The image was generated with this prompt: {}", prompt);
 
     let messages = vec![
-        ChatMessage {
-            role: Role::System,
-            content: system_prompt.to_string(),
-        },
-        ChatMessage {
-            role: Role::User,
-            content: user_prompt,
-        },
+        ChatMessage::system(system_prompt.to_string()),
+        ChatMessage::user(user_prompt),
     ];
 
-    #[derive(serde::Serialize)]
-    struct AltDescRequest<'a> {
-        model: &'a str,
-        messages: &'a [ChatMessage],
-    }
-
     let request = AltDescRequest {
         model: ALT_DESCRIPTION_MODEL,
         messages: &messages,
@@ -359,12 +384,12 @@ pub async fn generate_alt_description(
     let url = format!("{}/chat/completions", config.base_url);
 
     let client = reqwest::Client::new();
-    let response = client
-        .post(&url)
-        .header("Content-Type", "application/json")
-        .header("Authorization", format!("Bearer {}", config.api_key))
-        .header("HTTP-Referer", &config.http_referer)
-        .header("X-Title", &config.x_title)
+    let response = config
+        .headers()
+        .into_iter()
+        .fold(client.post(&url), |request, (name, value)| {
+            request.header(name, value)
+        })
         .body(body)
         .send()
         .await
@@ -375,66 +400,160 @@ pub async fn generate_alt_description(
         .await
         .map_err(|e| format!("Failed to read response: {}", e))?;
 
-    // Parse response to extract content
-    #[derive(serde::Deserialize)]
+    parse_alt_description(&text)
+}
+
+/// Instructions for describing an uploaded image from its pixels
+const UPLOAD_ALT_PROMPT: &str =
+    "Generate a short alt description (10-15 words max) for this image. \
+    The description should be concise and describe what the image shows. \
+    Output ONLY the description text, nothing else.";
+
+/// Request body for alt descriptions, sending attached images as content parts
+#[derive(Serialize)]
+struct AltDescRequest<'a> {
+    model: &'a str,
+    #[serde(serialize_with = "super::client::serialize_messages")]
+    messages: &'a [ChatMessage],
+}
+
+/// Build the vision request that asks for a description of an uploaded image
+fn build_upload_alt_request_body(image_bytes: &[u8], mime: &str) -> Result<String, String> {
+    use base64::Engine;
+
+    let data_url = format!(
+        "data:{};base64,{}",
+        mime,
+        base64::engine::general_purpose::STANDARD.encode(image_bytes)
+    );
+    let messages = [ChatMessage::user(UPLOAD_ALT_PROMPT.to_string()).with_image(data_url)];
+
+    let request = AltDescRequest {
+        model: ALT_DESCRIPTION_MODEL,
+        messages: &messages,
+    };
+    serde_json::to_string(&request).map_err(|e| format!("Failed to serialize request: {}", e))
+}
+
+/// Extract the description text from an alt description response
+fn parse_alt_description(response: &str) -> Result<String, String> {
+    #[derive(Deserialize)]
     struct AltDescResponse {
         choices: Option<Vec<AltDescChoice>>,
+        error: Option<ApiError>,
     }
 
-    #[derive(serde::Deserialize)]
+    #[derive(Deserialize)]
     struct AltDescChoice {
         message: AltDescMessage,
     }
 
-    #[derive(serde::Deserialize)]
+    #[derive(Deserialize)]
     struct AltDescMessage {
         content: String,
     }
 
     let parsed: AltDescResponse =
-        serde_json::from_str(&text).map_err(|e| format!("Failed to parse response: {}", e))?;
+        serde_json::from_str(response).map_err(|e| format!("Failed to parse response: {}", e))?;
+
+    if let Some(error) = parsed.error {
+        return Err(error.message);
+    }
 
     let choices = parsed
         .choices
         .ok_or_else(|| "No choices in response".to_string())?;
 
-    if choices.is_empty() {
+    let Some(choice) = choices.first() else {
         return Err("Empty choices array".to_string());
-    }
+    };
 
     // Clean up the response (remove quotes, trim whitespace)
-    let alt = choices[0].message.content.trim();
-    let alt = alt.trim_matches('"');
+    let alt = choice.message.content.trim().trim_matches('"').trim();
+    if alt.is_empty() {
+        return Err("Model returned an empty description".to_string());
+    }
     Ok(alt.to_string())
 }
 
+/// Describe an uploaded image by sending its pixels to a vision model
+///
+/// Uploads have no generation prompt, so the image itself is attached as a
+/// content part. Gemini Flash accepts image input.
+#[cfg(target_arch = "wasm32")]
+pub async fn generate_alt_for_upload(
+    config: &OpenRouterConfig,
+    image_bytes: &[u8],
+    mime: &str,
+) -> Result<String, String> {
+    use gloo_net::http::Request;
+
+    let body = build_upload_alt_request_body(image_bytes, mime)?;
+    let url = format!("{}/chat/completions", config.base_url);
+
+    let response = config
+        .headers()
+        .iter()
+        .fold(Request::post(&url), |request, (name, value)| {
+            request.header(name, value)
+        })
+        .body(body)
+        .map_err(|e| format!("Failed to build request: {:?}", e))?
+        .send()
+        .await
+        .map_err(|e| format!("Request failed: {:?}", e))?;
+
+    let text = response
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read response: {:?}", e))?;
+
+    parse_alt_description(&text)
+}
+
+/// Describe an uploaded image by sending its pixels to a vision model (native builds)
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn generate_alt_for_upload(
+    config: &OpenRouterConfig,
+    image_bytes: &[u8],
+    mime: &str,
+) -> Result<String, String> {
+    let body = build_upload_alt_request_body(image_bytes, mime)?;
+    let url = format!("{}/chat/completions", config.base_url);
+
+    let client = reqwest::Client::new();
+    let response = config
+        .headers()
+        .into_iter()
+        .fold(client.post(&url), |request, (name, value)| {
+            request.header(name, value)
+        })
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| format!("Request failed: {}", e))?;
+
+    let text = response
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read response: {}", e))?;
+
+    parse_alt_description(&text)
+}
+
 /// Extract image data from a data URL string
 /// Handles formats like: "![image](data:image/png;base64,...)" or "data:image/png;base64,..."
 fn extract_data_url(text: &str) -> Option<(Vec<u8>, String)> {
     // Look for data URL pattern
-    let data_url_start = text.find("data:")?;
-    let after_data = &text[data_url_start + 5..];
-
-    // Extract MIME type (between "data:" and ";base64,")
-    let semicolon = after_data.find(';')?;
-    let mime_type = after_data[..semicolon].to_string();
-
-    // Find base64 data
-    let base64_marker = "base64,";
-    let base64_start = after_data.find(base64_marker)? + base64_marker.len();
-    let after_base64 = &after_data[base64_start..];
+    let start = text.find("data:")?;
+    let data_url = &text[start..];
 
-    // Find end of base64 data (could end with ), ", or whitespace)
-    let end = after_base64
+    // Find end of the data URL (could end with ), ", or whitespace)
+    let end = data_url
         .find(|c: char| c == ')' || c == '"' || c == '\'' || c.is_whitespace())
-        .unwrap_or(after_base64.len());
+        .unwrap_or(data_url.len());
 
-    let base64_data = &after_base64[..end];
-
-    // Decode base64
-    let bytes = decode_base64(base64_data).ok()?;
-
-    Some((bytes, mime_type))
+    crate::images::parse_data_url(&data_url[..end]).ok()
 }
 
 #[cfg(test)]
@@ -472,13 +591,47 @@ mod tests {
     fn test_build_request_body() {
         let config = OpenRouterConfig::with_key("test-key".to_string());
         let generator = ImageGenerator::new(config);
-        let body = generator.build_request_body("A red apple");
+        let body = generator.build_request_body("A red apple", &GenerateOptions::default());
 
         assert!(body.contains("google/gemini-3-pro-image-preview"));
         assert!(body.contains("A red apple"));
         assert!(body.contains("modalities"));
         assert!(body.contains("image"));
         assert!(body.contains("text"));
+        assert!(!body.contains("image_config"));
+    }
+
+    #[test]
+    fn test_build_request_body_includes_aspect_ratio() {
+        let config = OpenRouterConfig::with_key("test-key".to_string());
+        let generator = ImageGenerator::new(config);
+        let options = GenerateOptions::with_aspect_ratio("16:9");
+
+        let body: serde_json::Value =
+            serde_json::from_str(&generator.build_request_body("A banner", &options)).unwrap();
+
+        assert_eq!(body["image_config"]["aspect_ratio"], "16:9");
+        assert!(body["image_config"].get("width").is_none());
+    }
+
+    #[test]
+    fn test_generate_options_validation() {
+        assert!(GenerateOptions::default().validate().is_ok());
+        assert!(GenerateOptions::with_aspect_ratio("1:1")
+            .with_size(512, 512)
+            .validate()
+            .is_ok());
+
+        let err = GenerateOptions::with_aspect_ratio("7:3")
+            .validate()
+            .unwrap_err();
+        assert!(err.contains("7:3"));
+
+        let err = GenerateOptions::default()
+            .with_size(8000, 512)
+            .validate()
+            .unwrap_err();
+        assert!(err.contains("width"));
     }
 
     #[test]
@@ -525,4 +678,36 @@ mod tests {
         assert_eq!(mime, "image/png");
         assert!(!bytes.is_empty());
     }
+
+    #[test]
+    fn test_build_upload_alt_request_body_attaches_image() {
+        let body: serde_json::Value = serde_json::from_str(
+            &build_upload_alt_request_body(&[0x89, b'P'], "image/png").unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(body["model"], ALT_DESCRIPTION_MODEL);
+        let parts = body["messages"][0]["content"].as_array().unwrap();
+        assert_eq!(parts[0]["type"], "text");
+        assert_eq!(parts[1]["type"], "image_url");
+        assert_eq!(parts[1]["image_url"]["url"], "data:image/png;base64,iVA=");
+    }
+
+    #[test]
+    fn test_parse_alt_description() {
+        let response = r#"{"choices": [{"message": {"content": " \"A red bicycle against a brick wall\"\n"}}]}"#;
+        assert_eq!(
+            parse_alt_description(response).unwrap(),
+            "A red bicycle against a brick wall"
+        );
+
+        let error = r#"{"error": {"message": "Model does not support images"}}"#;
+        assert_eq!(
+            parse_alt_description(error).unwrap_err(),
+            "Model does not support images"
+        );
+
+        let empty = r#"{"choices": [{"message": {"content": "  "}}]}"#;
+        assert!(parse_alt_description(empty).is_err());
+    }
 }
@@ -2,12 +2,14 @@
 //!
 //! This module provides:
 //! - OpenRouter client for LLM API calls
+//! - Cancellation tokens for in-flight requests
 //! - Prompt templates for different tasks
 //! - Visual verification logic
 //! - Agent orchestration loop
 //! - AI tools for JSON and template operations
 
 pub mod agent;
+pub mod cancel;
 pub mod client;
 pub mod image_gen;
 pub mod prompts;
@@ -18,23 +20,26 @@ pub mod verify;
 mod tests;
 
 pub use agent::{AgentConfig, AgentLoop, AgentResult};
-pub use client::{OpenRouterClient, OpenRouterConfig};
+pub use cancel::CancellationToken;
+pub use client::{ClientError, OpenRouterClient, OpenRouterConfig};
 
 // Re-exports for public API (not all used internally yet)
 #[allow(unused_imports)]
 pub use agent::AgentState;
 #[allow(unused_imports)]
-pub use client::{ChatMessage, Role};
+pub use client::{ChatMessage, ChatResponse, Role, TokenUsage, ToolCall, ToolDefinition};
 #[allow(unused_imports)]
-pub use image_gen::{generate_alt_description, ImageGenerator, IMAGE_MODEL};
+pub use image_gen::{
+    generate_alt_description, generate_alt_for_upload, GenerateOptions, ImageGenerator, IMAGE_MODEL,
+};
 #[allow(unused_imports)]
 pub use prompts::generate_system_prompt;
 #[allow(unused_imports)]
 pub use prompts::{generate_tool_editing_prompt, generate_user_prompt, PromptTemplate};
 #[allow(unused_imports)]
 pub use tools::{
-    AiTool, GenerateImageTool, ReadJsonTool, ReadTemplateTool, ToolResult, WriteJsonTool,
-    WriteTemplateTool,
+    AiTool, GenerateImageTool, ReadJsonTool, ReadTemplateTool, ToolContext, ToolResult,
+    WriteJsonTool, WriteTemplateTool,
 };
 #[allow(unused_imports)]
 pub use verify::{verify_change, VerificationResult};
@@ -10,8 +10,7 @@ pub enum PromptTemplate {
     /// Verify visual output matches intent (planned feature)
     #[allow(dead_code)]
     VisualVerification,
-    /// Tool-based editing (JSON + Template) (planned feature)
-    #[allow(dead_code)]
+    /// Tool-based editing (JSON + Template)
     ToolBasedEditing,
     /// Design-focused template creation with high visual quality
     DesignFocused,
@@ -200,7 +199,8 @@ Create atmosphere rather than flat, solid backgrounds:
 
 Remember: You are capable of extraordinary creative work. Don't hold back. Create something someone would actually WANT to look at and share."##;
 
-const TOOL_BASED_EDITING_SYSTEM: &str = r#"You are an AI assistant helping users create and edit marketing slick sheets.
+const TOOL_BASED_EDITING_SYSTEM: &str = concat!(
+    r#"You are an AI assistant helping users create and edit marketing slick sheets.
 
 ## Available Tools
 
@@ -230,9 +230,10 @@ JSON Schema:
 - body: string
 - sections: array of section objects
 - features: array of strings
-- stats: array of {value, label, color?}
+- stats: array of {value, label, color?, icon?}
 - contact: {email?, phone?, website?, address?}
-- style: {primaryColor?, accentColor?, fontFamily?}
+- style: {primaryColor?, accentColor?, fontFamily?, backgroundGradient?: [startHex, stopHex]}
+- page: {size?: "letter" | "legal" | "a4", orientation?: "portrait" | "landscape", margin?: length like "2cm"}
 
 ### read_template
 Read the current Typst template.
@@ -243,11 +244,9 @@ Update the Typst template.
 IMPORTANT: Always write the COMPLETE template, not partial updates.
 The system validates and test-compiles your template before accepting it.
 
-Templates use Handlebars-style placeholders:
-- {{field}} - Simple value substitution
-- {{#if field}}...{{/if}} - Conditional sections
-- {{#each items}}...{{/each}} - Loop over arrays
-- {{field | default: 'value'}} - Default values
+"#,
+    crate::template::syntax_reference!(),
+    r#"
 
 ## Workflow
 
@@ -261,7 +260,8 @@ Templates use Handlebars-style placeholders:
 - For content changes (text, data), modify the JSON
 - For layout/styling changes, modify the template
 - Always write complete files, never diffs or patches
-- Test compile errors will guide you to fix issues"#;
+- Test compile errors will guide you to fix issues"#
+);
 
 /// Generate a system prompt for the given template
 pub fn generate_system_prompt(template: PromptTemplate) -> String {
@@ -287,8 +287,7 @@ pub fn generate_error_recovery_prompt(code: &str, error: &str) -> String {
     )
 }
 
-/// Generate a prompt for visual verification with base64 image (planned feature)
-#[allow(dead_code)]
+/// Generate a prompt for visual verification; the image is attached separately
 pub fn generate_visual_verification_prompt(original_request: &str, _image_base64: &str) -> String {
     format!(
         "Original request: {}\n\nPlease verify the rendered output matches the intent. [Image attached]",
@@ -296,8 +295,15 @@ pub fn generate_visual_verification_prompt(original_request: &str, _image_base64
     )
 }
 
-/// Generate a user prompt for tool-based editing (planned feature)
-#[allow(dead_code)]
+/// Generate a prompt asking to revise code that visual verification rejected
+pub fn generate_verification_retry_prompt(request: &str, code: &str, feedback: &str) -> String {
+    format!(
+        "Current Typst code:\n```\n{}\n```\n\nRequest: {}\n\nThe rendered output did not match the request:\n{}\n\nPlease revise the code.",
+        code, request, feedback
+    )
+}
+
+/// Generate a user prompt for tool-based editing
 pub fn generate_tool_editing_prompt(
     request: &str,
     current_json: &str,
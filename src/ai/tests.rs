@@ -1,9 +1,17 @@
 //! Tests for the AI module
 
-use super::agent::{AgentConfig, AgentLoop, AgentState};
-use super::client::{ChatMessage, OpenRouterClient, OpenRouterConfig, Role};
+use super::agent::{AgentConfig, AgentLoop, AgentResult, AgentState};
+use super::cancel::CancellationToken;
+use super::client::{
+    backoff_delay, backoff_delay_with_jitter, parse_retry_after, ChatMessage, ClientError,
+    OpenRouterClient, OpenRouterConfig, Role, TokenUsage,
+};
 use super::prompts::{generate_system_prompt, generate_user_prompt, PromptTemplate};
-use super::verify::{verify_change, VerificationResult};
+use super::verify::{
+    parse_verification_response, parse_verification_response_with_threshold, render_png,
+    retry_feedback, verify_change, VerificationResult,
+};
+use std::time::Duration;
 
 // ============================================================================
 // OpenRouter Client Tests
@@ -23,6 +31,21 @@ fn test_openrouter_config_with_key() {
     assert_eq!(config.base_url, "https://openrouter.ai/api/v1");
 }
 
+#[test]
+fn test_openrouter_config_attribution_headers() {
+    let default_headers = OpenRouterConfig::with_key("k".to_string()).headers();
+    assert!(default_headers.contains(&("X-Title", "Slick Sheet Studio".to_string())));
+
+    let config = OpenRouterConfig::with_key("test-key-123".to_string())
+        .with_referer("https://sheets.example.com")
+        .with_title("Example Sheets");
+    let headers = config.headers();
+
+    assert!(headers.contains(&("Authorization", "Bearer test-key-123".to_string())));
+    assert!(headers.contains(&("HTTP-Referer", "https://sheets.example.com".to_string())));
+    assert!(headers.contains(&("X-Title", "Example Sheets".to_string())));
+}
+
 #[test]
 fn test_chat_message_creation() {
     let msg = ChatMessage::new(Role::User, "Hello".to_string());
@@ -51,6 +74,37 @@ fn test_chat_message_assistant() {
     assert_eq!(msg.content, "Here is the title.");
 }
 
+#[test]
+fn test_tool_message_serialization() {
+    let msg = ChatMessage::tool("call_123".to_string(), "Updated title".to_string())
+        .with_name("update_field");
+
+    assert_eq!(
+        serde_json::to_value(&msg).unwrap(),
+        serde_json::json!({
+            "role": "tool",
+            "content": "Updated title",
+            "tool_call_id": "call_123",
+            "name": "update_field"
+        })
+    );
+}
+
+#[test]
+fn test_plain_message_serialization_unchanged() {
+    for (msg, role) in [
+        (ChatMessage::system("S".to_string()), "system"),
+        (ChatMessage::user("U".to_string()), "user"),
+        (ChatMessage::assistant("A".to_string()), "assistant"),
+    ] {
+        let content = msg.content.clone();
+        assert_eq!(
+            serde_json::to_value(&msg).unwrap(),
+            serde_json::json!({ "role": role, "content": content })
+        );
+    }
+}
+
 #[test]
 fn test_openrouter_client_creation() {
     let config = OpenRouterConfig::with_key("test-key".to_string());
@@ -75,6 +129,55 @@ fn test_openrouter_client_build_request_body() {
     assert!(body.contains("Generate a title."));
 }
 
+#[test]
+fn test_build_request_body_includes_sampling_parameters() {
+    let config = OpenRouterConfig {
+        temperature: Some(0.2),
+        max_tokens: Some(1024),
+        ..OpenRouterConfig::with_key("test-key".to_string())
+    };
+    let client = OpenRouterClient::new(config);
+    let messages = vec![ChatMessage::user("Hi".to_string())];
+
+    let body: serde_json::Value =
+        serde_json::from_str(&client.build_request_body("test/model", &messages)).unwrap();
+
+    assert_eq!(
+        body["temperature"].as_f64().map(|t| (t * 100.0).round()),
+        Some(20.0)
+    );
+    assert_eq!(body["max_tokens"], 1024);
+    assert!(body.get("top_p").is_none());
+}
+
+#[test]
+fn test_build_request_body_omits_unset_sampling_parameters() {
+    let client = OpenRouterClient::new(OpenRouterConfig::with_key("test-key".to_string()));
+    let body = client.build_request_body("test/model", &[ChatMessage::user("Hi".to_string())]);
+
+    assert!(!body.contains("temperature"));
+    assert!(!body.contains("top_p"));
+    assert!(!body.contains("max_tokens"));
+}
+
+#[test]
+fn test_build_request_body_sends_images_as_content_parts() {
+    let client = OpenRouterClient::new(OpenRouterConfig::with_key("test-key".to_string()));
+    let messages = vec![
+        ChatMessage::system("Check it".to_string()),
+        ChatMessage::user("Does this match?".to_string()).with_image("data:image/png;base64,AAAA"),
+    ];
+    let body: serde_json::Value =
+        serde_json::from_str(&client.build_request_body("test/model", &messages)).unwrap();
+
+    assert_eq!(body["messages"][0]["content"], "Check it");
+    let parts = body["messages"][1]["content"].as_array().unwrap();
+    assert_eq!(parts[0]["type"], "text");
+    assert_eq!(parts[0]["text"], "Does this match?");
+    assert_eq!(parts[1]["type"], "image_url");
+    assert_eq!(parts[1]["image_url"]["url"], "data:image/png;base64,AAAA");
+}
+
 #[test]
 fn test_openrouter_client_parse_response_valid() {
     let response = r#"{
@@ -87,7 +190,59 @@ fn test_openrouter_client_parse_response_valid() {
 
     let result = OpenRouterClient::parse_response(response);
     assert!(result.is_ok());
-    assert_eq!(result.unwrap(), "= Hello World");
+    let response = result.unwrap();
+    assert_eq!(response.content, "= Hello World");
+    assert!(response.usage.is_none());
+}
+
+#[test]
+fn test_openrouter_client_parse_response_usage() {
+    let response = r#"{
+        "id": "gen-123",
+        "model": "google/gemini-3-flash-preview",
+        "choices": [{
+            "message": {
+                "role": "assistant",
+                "content": "{\"title\": \"Hi\"}"
+            },
+            "finish_reason": "stop"
+        }],
+        "usage": {
+            "prompt_tokens": 1200,
+            "completion_tokens": 345,
+            "total_tokens": 1545,
+            "cost": 0.00042
+        }
+    }"#;
+
+    let usage = OpenRouterClient::parse_response(response)
+        .unwrap()
+        .usage
+        .expect("usage should be parsed");
+    assert_eq!(usage.prompt, 1200);
+    assert_eq!(usage.completion, 345);
+    assert_eq!(usage.total, 1545);
+    assert_eq!(usage.cost, Some(0.00042));
+}
+
+#[test]
+fn test_token_usage_adds_counts_and_known_costs() {
+    let first = TokenUsage {
+        prompt: 100,
+        completion: 20,
+        total: 120,
+        cost: Some(0.5),
+    };
+    let second = TokenUsage {
+        prompt: 10,
+        completion: 5,
+        total: 15,
+        cost: None,
+    };
+
+    let sum = first + second;
+    assert_eq!((sum.prompt, sum.completion, sum.total), (110, 25, 135));
+    assert_eq!(sum.cost, Some(0.5));
 }
 
 #[test]
@@ -115,6 +270,625 @@ fn test_openrouter_client_parse_response_error_field() {
     assert!(result.unwrap_err().contains("Invalid API key"));
 }
 
+// ============================================================================
+// Cancellation Tests
+// ============================================================================
+
+#[test]
+fn test_cancellation_token_clones_share_state() {
+    let token = CancellationToken::new();
+    let clone = token.clone();
+    assert!(!token.is_cancelled());
+
+    clone.cancel();
+
+    assert!(token.is_cancelled());
+}
+
+#[tokio::test]
+async fn test_chat_cancelled_before_start() {
+    let client = OpenRouterClient::new(OpenRouterConfig::with_key("test-key".to_string()));
+    let token = CancellationToken::new();
+    token.cancel();
+
+    let result = client
+        .chat_cancellable(
+            "test/model",
+            vec![ChatMessage::user("Hi".to_string())],
+            &token,
+        )
+        .await;
+
+    assert_eq!(result, Err(ClientError::Cancelled));
+}
+
+#[tokio::test]
+async fn test_chat_cancelled_while_in_flight() {
+    // A server that accepts the connection but never responds
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind");
+    let addr = listener.local_addr().expect("local addr");
+    std::thread::spawn(move || {
+        let _held: Vec<_> = listener.incoming().take(1).collect();
+        std::thread::sleep(std::time::Duration::from_secs(30));
+    });
+
+    let config = OpenRouterConfig {
+        base_url: format!("http://{}", addr),
+        ..OpenRouterConfig::with_key("test-key".to_string())
+    };
+    let client = OpenRouterClient::new(config);
+    let token = CancellationToken::new();
+
+    let canceller = token.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        canceller.cancel();
+    });
+
+    let result = tokio::time::timeout(
+        std::time::Duration::from_secs(5),
+        client.chat_cancellable(
+            "test/model",
+            vec![ChatMessage::user("Hi".to_string())],
+            &token,
+        ),
+    )
+    .await
+    .expect("cancellation should stop the request");
+
+    assert_eq!(result, Err(ClientError::Cancelled));
+}
+
+// ============================================================================
+// Retry Tests
+// ============================================================================
+
+/// Serve canned HTTP responses, one per connection, in order
+fn serve_responses(responses: Vec<String>) -> std::net::SocketAddr {
+    serve_recorded(responses).0
+}
+
+/// Serve canned HTTP responses and record each raw request received
+fn serve_recorded(
+    responses: Vec<String>,
+) -> (
+    std::net::SocketAddr,
+    std::sync::Arc<std::sync::Mutex<Vec<String>>>,
+) {
+    use std::io::{Read, Write};
+
+    let requests = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let recorded = requests.clone();
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind");
+    let addr = listener.local_addr().expect("local addr");
+    std::thread::spawn(move || {
+        for (stream, response) in listener.incoming().zip(responses) {
+            let mut stream = stream.expect("accept");
+            let mut request = Vec::new();
+            let mut buf = [0u8; 4096];
+            // Read headers, then the body announced by Content-Length
+            loop {
+                let n = stream.read(&mut buf).unwrap_or(0);
+                request.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&request);
+                if let Some(end) = text.find("\r\n\r\n") {
+                    let len = text
+                        .lines()
+                        .find_map(|l| {
+                            l.to_ascii_lowercase()
+                                .strip_prefix("content-length:")
+                                .map(|v| v.trim().parse::<usize>().unwrap_or(0))
+                        })
+                        .unwrap_or(0);
+                    if request.len() >= end + 4 + len {
+                        break;
+                    }
+                }
+                if n == 0 {
+                    break;
+                }
+            }
+            recorded
+                .lock()
+                .unwrap()
+                .push(String::from_utf8_lossy(&request).into_owned());
+            let _ = stream.write_all(response.as_bytes());
+        }
+    });
+    (addr, requests)
+}
+
+fn http_response(status: &str, headers: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n{}\r\n{}",
+        status,
+        body.len(),
+        headers,
+        body
+    )
+}
+
+#[test]
+fn test_client_error_retryable_statuses() {
+    let from_status = |status| ClientError::from_status(status, None, String::new());
+    for status in [429, 500, 502, 503, 504] {
+        assert!(
+            from_status(status).is_retryable(),
+            "{} should be retryable",
+            status
+        );
+    }
+    assert!(!from_status(400).is_retryable());
+    assert!(!from_status(401).is_retryable());
+    assert!(!ClientError::BadResponse("Invalid API key".to_string()).is_retryable());
+    assert!(!ClientError::Cancelled.is_retryable());
+}
+
+#[test]
+fn test_handle_response_maps_unauthorized() {
+    let result = OpenRouterClient::handle_response(
+        401,
+        None,
+        r#"{"error": {"message": "No auth credentials found"}}"#,
+    );
+
+    let err = result.unwrap_err();
+    assert_eq!(
+        err,
+        ClientError::Unauthorized("No auth credentials found".to_string())
+    );
+    assert!(err.guidance().unwrap().contains("API key"));
+}
+
+#[test]
+fn test_handle_response_maps_rate_limited() {
+    let result = OpenRouterClient::handle_response(
+        429,
+        Some(Duration::from_secs(5)),
+        r#"{"error": {"message": "Rate limit exceeded"}}"#,
+    );
+
+    let err = result.unwrap_err();
+    assert_eq!(
+        err,
+        ClientError::RateLimited {
+            retry_after: Some(Duration::from_secs(5))
+        }
+    );
+    assert_eq!(err.retry_after(), Some(Duration::from_secs(5)));
+}
+
+#[test]
+fn test_handle_response_maps_model_unavailable() {
+    let result = OpenRouterClient::handle_response(503, None, "upstream down");
+
+    assert_eq!(
+        result,
+        Err(ClientError::ModelUnavailable("upstream down".to_string()))
+    );
+}
+
+#[test]
+fn test_handle_response_maps_error_body_to_bad_response() {
+    let result =
+        OpenRouterClient::handle_response(200, None, r#"{"error": {"message": "Invalid model"}}"#);
+
+    assert!(
+        matches!(result, Err(ClientError::BadResponse(message)) if message.contains("Invalid model"))
+    );
+}
+
+#[test]
+fn test_parse_retry_after_seconds() {
+    assert_eq!(parse_retry_after(Some("3")), Some(Duration::from_secs(3)));
+    assert_eq!(
+        parse_retry_after(Some("Wed, 21 Oct 2015 07:28:00 GMT")),
+        None
+    );
+    assert_eq!(parse_retry_after(None), None);
+}
+
+#[test]
+fn test_backoff_delay_with_jitter_is_deterministic() {
+    let base = Duration::from_secs(1);
+
+    // No jitter waits half the exponential delay, full jitter all of it
+    assert_eq!(
+        backoff_delay_with_jitter(base, 1, 0.0),
+        Duration::from_millis(500)
+    );
+    assert_eq!(backoff_delay_with_jitter(base, 1, 1.0), base);
+    assert_eq!(
+        backoff_delay_with_jitter(base, 2, 1.0),
+        Duration::from_secs(2)
+    );
+    assert_eq!(
+        backoff_delay_with_jitter(base, 3, 0.5),
+        Duration::from_secs(3)
+    );
+    // Capped at the maximum retry delay
+    assert_eq!(
+        backoff_delay_with_jitter(base, 10, 1.0),
+        Duration::from_secs(30)
+    );
+    // Out-of-range jitter is clamped
+    assert_eq!(backoff_delay_with_jitter(base, 1, 7.0), base);
+    assert_eq!(
+        backoff_delay_with_jitter(Duration::ZERO, 4, 1.0),
+        Duration::ZERO
+    );
+}
+
+#[test]
+fn test_backoff_delay_grows_with_jitter() {
+    let base = Duration::from_millis(100);
+    for attempt in 1..=4 {
+        let full = base * 2u32.pow(attempt - 1);
+        let delay = backoff_delay(base, attempt);
+        assert!(
+            delay >= full / 2 && delay <= full,
+            "attempt {}: {:?}",
+            attempt,
+            delay
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_chat_retries_rate_limit_then_succeeds() {
+    let rate_limited = http_response(
+        "429 Too Many Requests",
+        "Retry-After: 0\r\n",
+        r#"{"error": {"message": "Rate limit exceeded"}}"#,
+    );
+    let ok = http_response(
+        "200 OK",
+        "",
+        r#"{"choices": [{"message": {"content": "= Hello"}}]}"#,
+    );
+    let addr = serve_responses(vec![rate_limited.clone(), rate_limited, ok]);
+
+    let config = OpenRouterConfig {
+        base_url: format!("http://{}", addr),
+        retry_base_delay: Duration::from_millis(1),
+        ..OpenRouterConfig::with_key("test-key".to_string())
+    };
+    let client = OpenRouterClient::new(config);
+
+    let response = client
+        .chat("test/model", vec![ChatMessage::user("Hi".to_string())])
+        .await
+        .expect("should succeed after retries");
+
+    assert_eq!(response.content, "= Hello");
+}
+
+#[tokio::test]
+async fn test_chat_gives_up_after_max_attempts() {
+    let unavailable = http_response("503 Service Unavailable", "", "upstream down");
+    let addr = serve_responses(vec![unavailable.clone(), unavailable]);
+
+    let config = OpenRouterConfig {
+        base_url: format!("http://{}", addr),
+        max_attempts: 2,
+        retry_base_delay: Duration::from_millis(1),
+        ..OpenRouterConfig::with_key("test-key".to_string())
+    };
+    let client = OpenRouterClient::new(config);
+
+    let result = client
+        .chat("test/model", vec![ChatMessage::user("Hi".to_string())])
+        .await;
+
+    assert!(matches!(result, Err(ClientError::ModelUnavailable(_))));
+}
+
+#[tokio::test]
+async fn test_validate_key_accepts_valid_key() {
+    let (addr, requests) = serve_recorded(vec![http_response(
+        "200 OK",
+        "",
+        r#"{"data": {"label": "sk-or-v1-abc...xyz", "usage": 0}}"#,
+    )]);
+    let config = OpenRouterConfig {
+        base_url: format!("http://{}", addr),
+        ..OpenRouterConfig::with_key("sk-or-v1-test".to_string())
+    };
+
+    let result = OpenRouterClient::new(config).validate_key().await;
+
+    assert_eq!(result, Ok(()));
+    let requests = requests.lock().unwrap();
+    assert!(requests[0].starts_with("GET /key "));
+    assert!(requests[0].contains("Bearer sk-or-v1-test"));
+}
+
+#[tokio::test]
+async fn test_validate_key_rejects_invalid_key() {
+    let addr = serve_responses(vec![http_response(
+        "401 Unauthorized",
+        "",
+        r#"{"error": {"message": "Invalid API key"}}"#,
+    )]);
+    let config = OpenRouterConfig {
+        base_url: format!("http://{}", addr),
+        ..OpenRouterConfig::with_key("sk-or-v1-bad".to_string())
+    };
+
+    let result = OpenRouterClient::new(config).validate_key().await;
+
+    assert_eq!(
+        result,
+        Err(ClientError::Unauthorized("Invalid API key".to_string()))
+    );
+}
+
+#[tokio::test]
+async fn test_validate_key_requires_a_key() {
+    let client = OpenRouterClient::new(OpenRouterConfig::with_key("  ".to_string()));
+
+    assert!(matches!(
+        client.validate_key().await,
+        Err(ClientError::Unauthorized(_))
+    ));
+}
+
+// ============================================================================
+// Model Fallback Tests
+// ============================================================================
+
+#[test]
+fn test_client_error_model_unavailable() {
+    let from_status = |status| ClientError::from_status(status, None, String::new());
+    assert!(from_status(503).is_model_unavailable());
+    assert!(from_status(429).is_model_unavailable());
+    assert!(!from_status(500).is_model_unavailable());
+    assert!(!ClientError::BadResponse("Invalid API key".to_string()).is_model_unavailable());
+}
+
+#[tokio::test]
+async fn test_agent_falls_back_when_primary_model_unavailable() {
+    let (addr, requests) = serve_recorded(vec![
+        http_response(
+            "503 Service Unavailable",
+            "",
+            r#"{"error": {"message": "Model is overloaded"}}"#,
+        ),
+        http_response(
+            "200 OK",
+            "",
+            r#"{"choices": [{"message": {"content": "= Hello from the fallback"}}]}"#,
+        ),
+    ]);
+
+    let config = OpenRouterConfig {
+        base_url: format!("http://{}", addr),
+        max_attempts: 1,
+        ..OpenRouterConfig::with_key("test-key".to_string())
+    };
+    let agent_config = AgentConfig {
+        model: "primary/model".to_string(),
+        fallback_models: vec!["backup/model".to_string()],
+        ..AgentConfig::default()
+    };
+    let mut agent = AgentLoop::new(OpenRouterClient::new(config), agent_config);
+
+    let result = agent
+        .run("Say hello", None, |_code: &str| {
+            Ok(format!("<svg>{}</svg>", "x".repeat(100)))
+        })
+        .await;
+
+    match result {
+        AgentResult::Success { model, .. } => assert_eq!(model, "backup/model"),
+        other => panic!("expected Success, got {:?}", other),
+    }
+    let requests = requests.lock().unwrap();
+    assert!(requests[0].contains(r#""model":"primary/model""#));
+    assert!(requests[1].contains(r#""model":"backup/model""#));
+}
+
+#[tokio::test]
+async fn test_fallback_keeps_reported_model_apart_from_requested() {
+    let addr = serve_responses(vec![
+        http_response(
+            "503 Service Unavailable",
+            "",
+            r#"{"error": {"message": "Model is overloaded"}}"#,
+        ),
+        http_response(
+            "200 OK",
+            "",
+            r#"{"model": "backup/model-0501", "choices": [{"message": {"content": "Hi"}}]}"#,
+        ),
+    ]);
+    let config = OpenRouterConfig {
+        base_url: format!("http://{}", addr),
+        max_attempts: 1,
+        ..OpenRouterConfig::with_key("test-key".to_string())
+    };
+    let client =
+        OpenRouterClient::new(config).with_fallback_models(vec!["backup/model".to_string()]);
+
+    let response = client
+        .chat("primary/model", vec![ChatMessage::user("Hi".to_string())])
+        .await
+        .unwrap();
+
+    assert_eq!(response.model, "backup/model-0501");
+    assert_eq!(response.requested_model, "backup/model");
+    assert_eq!(response.served_model(), "backup/model-0501");
+}
+
+#[tokio::test]
+async fn test_agent_retries_when_visual_verification_rejects() {
+    let reply = |content: &str| {
+        http_response(
+            "200 OK",
+            "",
+            &serde_json::json!({"choices": [{"message": {"content": content}}]}).to_string(),
+        )
+    };
+    let (addr, requests) = serve_recorded(vec![
+        reply("= Hello"),
+        reply(
+            r#"{"matches_intent": false, "confidence": 0.9, "issues": ["Title is not red"], "suggestion": "Fill the title red"}"#,
+        ),
+        reply("#text(fill: red)[= Hello]"),
+        reply(r#"{"matches_intent": true, "confidence": 0.95, "issues": [], "suggestion": ""}"#),
+    ]);
+
+    let config = OpenRouterConfig {
+        base_url: format!("http://{}", addr),
+        max_attempts: 1,
+        ..OpenRouterConfig::with_key("test-key".to_string())
+    };
+    let agent_config = AgentConfig {
+        enable_visual_verification: true,
+        ..AgentConfig::default()
+    };
+    let mut agent = AgentLoop::new(OpenRouterClient::new(config), agent_config);
+    let svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="20" height="10"><!--{}--></svg>"#,
+        "x".repeat(100)
+    );
+
+    let result = agent
+        .run("Make a red title", None, |_code: &str| Ok(svg.clone()))
+        .await;
+
+    match result {
+        AgentResult::Success {
+            code, iterations, ..
+        } => {
+            assert_eq!(code, "#text(fill: red)[= Hello]");
+            assert_eq!(iterations, 2);
+        }
+        other => panic!("expected Success, got {:?}", other),
+    }
+    let requests = requests.lock().unwrap();
+    assert!(requests[1].contains("data:image/png;base64,"));
+    assert!(requests[2].contains("Fill the title red"));
+}
+
+// ============================================================================
+// Tool-Calling Agent Tests
+// ============================================================================
+
+#[test]
+fn test_build_request_body_advertises_tools() {
+    let client = OpenRouterClient::new(OpenRouterConfig::with_key("test-key".to_string()));
+    let messages = vec![ChatMessage::user("Hi".to_string())];
+
+    let body = client.build_request_body_with_tools(
+        "test/model",
+        &messages,
+        &super::tools::all_tool_definitions(),
+    );
+    let plain = client.build_request_body("test/model", &messages);
+
+    assert!(body.contains(r#""tools":[{"type":"function","function":{"name":"read_json""#));
+    assert!(!plain.contains("tools"));
+}
+
+#[test]
+fn test_parse_response_tool_calls() {
+    let response = r#"{"choices": [{"message": {
+        "content": null,
+        "tool_calls": [{
+            "id": "call_1",
+            "type": "function",
+            "function": {"name": "read_json", "arguments": "{}"}
+        }]
+    }}]}"#;
+
+    let parsed = OpenRouterClient::parse_response(response).unwrap();
+
+    assert_eq!(parsed.content, "");
+    assert_eq!(parsed.tool_calls.len(), 1);
+    assert_eq!(parsed.tool_calls[0].function.name, "read_json");
+}
+
+#[tokio::test]
+async fn test_agent_dispatches_write_json_tool_call() {
+    let arguments = serde_json::json!({ "json": r#"{"title": "From Tool"}"# }).to_string();
+    let tool_call_body = serde_json::json!({
+        "choices": [{"message": {
+            "content": null,
+            "tool_calls": [{
+                "id": "call_1",
+                "type": "function",
+                "function": {"name": "write_json", "arguments": arguments}
+            }]
+        }}]
+    })
+    .to_string();
+    let addr = serve_responses(vec![
+        http_response("200 OK", "", &tool_call_body),
+        http_response(
+            "200 OK",
+            "",
+            r#"{"choices": [{"message": {"content": "Updated the title."}}]}"#,
+        ),
+    ]);
+
+    let config = OpenRouterConfig {
+        base_url: format!("http://{}", addr),
+        ..OpenRouterConfig::with_key("test-key".to_string())
+    };
+    let mut agent = AgentLoop::new(OpenRouterClient::new(config), AgentConfig::default());
+
+    let result = agent
+        .run_with_tools(
+            "Change the title",
+            crate::data::SlickSheetData::new("Old"),
+            "= {{title}}",
+            |_code: &str| Ok("<svg></svg>".to_string()),
+            &CancellationToken::new(),
+        )
+        .await;
+
+    match result {
+        AgentResult::ToolSuccess {
+            data,
+            code,
+            iterations,
+            ..
+        } => {
+            assert_eq!(data.title, "From Tool");
+            assert_eq!(code, "= From Tool");
+            assert_eq!(iterations, 2);
+        }
+        other => panic!("expected ToolSuccess, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_agent_tool_run_stops_when_cancelled() {
+    let config = OpenRouterConfig {
+        base_url: "http://127.0.0.1:1".to_string(),
+        ..OpenRouterConfig::with_key("test-key".to_string())
+    };
+    let mut agent = AgentLoop::new(OpenRouterClient::new(config), AgentConfig::default());
+    let token = CancellationToken::new();
+    token.cancel();
+
+    let result = agent
+        .run_with_tools(
+            "Change the title",
+            crate::data::SlickSheetData::new("Old"),
+            "= {{title}}",
+            |_code: &str| Ok("<svg></svg>".to_string()),
+            &token,
+        )
+        .await;
+
+    match result {
+        AgentResult::Error(message) => assert!(message.contains("cancelled"), "{}", message),
+        other => panic!("expected Error, got {:?}", other),
+    }
+}
+
 // ============================================================================
 // Prompt Template Tests
 // ============================================================================
@@ -225,6 +999,66 @@ fn test_verify_change_with_empty_svg() {
     assert!(result.needs_retry());
 }
 
+#[test]
+fn test_parse_verification_response_accepts_confident_match() {
+    let result = parse_verification_response(
+        r#"{"matches_intent": true, "confidence": 0.9, "issues": [], "suggestion": ""}"#,
+    );
+
+    assert!(result.is_success());
+}
+
+#[test]
+fn test_parse_verification_response_in_markdown_fence() {
+    let response = "Here is my verdict:\n```json\n{\"matches_intent\": false, \"confidence\": 0.9, \"issues\": [\"Title is missing\"], \"suggestion\": \"Add a heading\"}\n```";
+    let result = parse_verification_response(response);
+
+    assert!(result.needs_retry());
+    assert_eq!(result.suggestion(), Some("Add a heading"));
+}
+
+#[test]
+fn test_parse_verification_response_respects_threshold() {
+    let response = r#"{"matches_intent": true, "confidence": 0.75, "issues": [], "suggestion": "Bolder title"}"#;
+
+    assert!(parse_verification_response_with_threshold(response, 0.7).is_success());
+    let strict = parse_verification_response_with_threshold(response, 0.8);
+    assert!(strict.needs_retry());
+    assert_eq!(strict.suggestion(), Some("Bolder title"));
+}
+
+#[test]
+fn test_retry_feedback_only_for_needs_retry() {
+    let retry = VerificationResult::NeedsRetry {
+        reason: "Title not visible".to_string(),
+        suggestion: "Increase font size".to_string(),
+    };
+    let feedback = retry_feedback(&retry).unwrap();
+    assert!(feedback.contains("Title not visible"));
+    assert!(feedback.contains("Increase font size"));
+
+    let success = VerificationResult::Success {
+        confidence: 0.9,
+        message: "ok".to_string(),
+    };
+    assert!(retry_feedback(&success).is_none());
+
+    // A verification that could not run keeps the compiled output
+    let failed = VerificationResult::Failed {
+        error: "Render failed".to_string(),
+    };
+    assert!(retry_feedback(&failed).is_none());
+}
+
+#[test]
+fn test_render_png_produces_png() {
+    let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="20" height="10"><rect width="20" height="10" fill="red"/></svg>"#;
+    let png = render_png(svg).unwrap();
+
+    assert!(png.starts_with(b"\x89PNG"));
+    assert!(render_png("not svg").is_err());
+}
+
 // ============================================================================
 // Agent Loop Tests
 // ============================================================================
@@ -243,6 +1077,9 @@ fn test_agent_config_custom() {
         max_iterations: 5,
         model: "anthropic/claude-3.5-haiku".to_string(),
         enable_visual_verification: true,
+        confidence_threshold: 0.8,
+        max_tool_rounds: 10,
+        fallback_models: Vec::new(),
     };
 
     assert_eq!(config.max_iterations, 5);
@@ -0,0 +1,162 @@
+//! Tool-call dispatch
+//!
+//! Routes function calls from the model to the matching tool and applies
+//! accepted writes to the shared `ToolContext`.
+
+#![allow(dead_code)]
+
+use super::{
+    AiTool, GenerateImageTool, ReadJsonTool, ReadTemplateTool, ToolResult, WriteJsonTool,
+    WriteTemplateTool,
+};
+use crate::ai::client::{ToolCall, ToolDefinition};
+use crate::data::SlickSheetData;
+
+/// An image produced by the `generate_image` tool
+#[derive(Debug, Clone)]
+pub struct GeneratedImage {
+    /// Prompt the image was generated from
+    pub prompt: String,
+    /// Raw image bytes
+    pub bytes: Vec<u8>,
+    /// MIME type of the image
+    pub mime_type: String,
+}
+
+/// Document state the tools read from and write to
+#[derive(Debug, Clone)]
+pub struct ToolContext {
+    /// Current content data
+    pub data: SlickSheetData,
+    /// Current Typst template
+    pub template: String,
+    /// API key used by `generate_image`
+    pub api_key: String,
+    /// Images generated during this run
+    pub images: Vec<GeneratedImage>,
+}
+
+impl ToolContext {
+    /// Create a context for the given data and template
+    pub fn new(data: SlickSheetData, template: impl Into<String>) -> Self {
+        Self {
+            data,
+            template: template.into(),
+            api_key: String::new(),
+            images: Vec::new(),
+        }
+    }
+
+    /// Set the API key used for image generation
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = api_key.into();
+        self
+    }
+}
+
+/// Function-calling definitions for every available tool
+pub fn all_tool_definitions() -> Vec<ToolDefinition> {
+    vec![
+        ReadJsonTool.definition(),
+        WriteJsonTool.definition(),
+        ReadTemplateTool.definition(),
+        WriteTemplateTool.definition(),
+        GenerateImageTool.definition(),
+    ]
+}
+
+/// Execute a tool call against the context
+///
+/// Write tools only update the context when validation and the test
+/// compile succeed; otherwise the error is returned for the model to fix.
+pub async fn dispatch_tool_call<F>(
+    call: &ToolCall,
+    ctx: &mut ToolContext,
+    compile_fn: &mut F,
+) -> ToolResult
+where
+    F: FnMut(&str) -> Result<String, String>,
+{
+    let args: serde_json::Value = if call.function.arguments.trim().is_empty() {
+        serde_json::Value::Object(Default::default())
+    } else {
+        match serde_json::from_str(&call.function.arguments) {
+            Ok(args) => args,
+            Err(e) => {
+                return ToolResult::Error(format!(
+                    "Invalid arguments for {}: {}",
+                    call.function.name, e
+                ))
+            }
+        }
+    };
+    let string_arg = |name: &str| {
+        args.get(name)
+            .and_then(|value| value.as_str())
+            .map(str::to_string)
+            .ok_or_else(|| {
+                ToolResult::Error(format!(
+                    "Missing string argument '{}' for {}",
+                    name, call.function.name
+                ))
+            })
+    };
+
+    match call.function.name.as_str() {
+        "read_json" => ReadJsonTool::execute(&ctx.data),
+        "read_template" => ReadTemplateTool::execute(&ctx.template),
+        "write_json" => {
+            let json = match string_arg("json") {
+                Ok(json) => json,
+                Err(err) => return err,
+            };
+            match WriteJsonTool::execute(&json, &ctx.template, |code| {
+                compile_fn(code).map_err(|e| vec![e])
+            }) {
+                Ok(data) => {
+                    ctx.data = data;
+                    ToolResult::Success("Content data updated.".to_string())
+                }
+                Err(err) => err,
+            }
+        }
+        "write_template" => {
+            let template = match string_arg("template") {
+                Ok(template) => template,
+                Err(err) => return err,
+            };
+            match WriteTemplateTool::execute(&template, &ctx.data, |code| {
+                compile_fn(code).map_err(|e| vec![e])
+            }) {
+                Ok(template) => {
+                    ctx.template = template;
+                    ToolResult::Success("Template updated.".to_string())
+                }
+                Err(err) => err,
+            }
+        }
+        "generate_image" => {
+            let prompt = match string_arg("prompt") {
+                Ok(prompt) => prompt,
+                Err(err) => return err,
+            };
+            match GenerateImageTool::execute(&ctx.api_key, &prompt).await {
+                Ok((bytes, mime_type)) => {
+                    let message = format!(
+                        "Generated image: {} bytes, type: {}",
+                        bytes.len(),
+                        mime_type
+                    );
+                    ctx.images.push(GeneratedImage {
+                        prompt,
+                        bytes,
+                        mime_type,
+                    });
+                    ToolResult::Success(message)
+                }
+                Err(e) => ToolResult::Error(e),
+            }
+        }
+        other => ToolResult::Error(format!("Unknown tool: {}", other)),
+    }
+}
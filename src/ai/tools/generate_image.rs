@@ -6,7 +6,7 @@
 
 use super::{AiTool, ToolResult};
 use crate::ai::client::OpenRouterConfig;
-use crate::ai::image_gen::ImageGenerator;
+use crate::ai::image_gen::{GenerateOptions, ImageGenerator};
 
 /// Tool for generating images from text prompts
 pub struct GenerateImageTool;
@@ -27,7 +27,9 @@ impl GenerateImageTool {
 
         let config = OpenRouterConfig::with_key(api_key.to_string());
         let generator = ImageGenerator::new(config);
-        generator.generate(prompt).await
+        generator
+            .generate(prompt, &GenerateOptions::default())
+            .await
     }
 
     /// Execute and return a ToolResult
@@ -51,6 +53,10 @@ impl AiTool for GenerateImageTool {
     fn description(&self) -> &'static str {
         "Generate an image using AI from a text description. Takes a prompt describing the desired image and returns the generated image data."
     }
+
+    fn parameters(&self) -> serde_json::Value {
+        super::string_parameter("prompt", "Description of the image to generate")
+    }
 }
 
 #[cfg(test)]
@@ -7,6 +7,9 @@
 
 #![allow(dead_code)]
 
+use crate::ai::client::ToolDefinition;
+
+mod dispatch;
 mod generate_image;
 mod read_json;
 mod read_template;
@@ -16,6 +19,7 @@ mod write_template;
 #[cfg(test)]
 mod tests;
 
+pub use dispatch::{all_tool_definitions, dispatch_tool_call, GeneratedImage, ToolContext};
 pub use generate_image::GenerateImageTool;
 pub use read_json::ReadJsonTool;
 pub use read_template::ReadTemplateTool;
@@ -53,4 +57,25 @@ pub trait AiTool {
 
     /// Get the tool description for the AI
     fn description(&self) -> &'static str;
+
+    /// JSON Schema of the tool's arguments
+    fn parameters(&self) -> serde_json::Value {
+        serde_json::json!({ "type": "object", "properties": {} })
+    }
+
+    /// Function-calling definition advertised to the model
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition::function(self.name(), self.description(), self.parameters())
+    }
+}
+
+/// JSON Schema for a tool taking a single required string argument
+fn string_parameter(name: &str, description: &str) -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            name: { "type": "string", "description": description }
+        },
+        "required": [name]
+    })
 }
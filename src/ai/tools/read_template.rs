@@ -20,24 +20,21 @@ impl AiTool for ReadTemplateTool {
     }
 
     fn description(&self) -> &'static str {
-        r#"Read the current Typst template. Returns the full template source with placeholders.
-
-Templates use Handlebars-style placeholders:
-- {{field}} - Simple value substitution (e.g., {{title}}, {{body}})
-- {{field.subfield}} - Nested values (e.g., {{style.primaryColor}}, {{contact.email}})
-- {{#if field}}...{{/if}} - Conditional sections
-- {{#if field}}...{{else}}...{{/if}} - Conditional with else
-- {{#each items}}...{{/each}} - Loop over arrays
-- {{this}} - Current item in a loop
-- {{field | default: 'value'}} - Default values if field is empty
+        concat!(
+            "Read the current Typst template. Returns the full template source with placeholders.\n\n",
+            crate::template::syntax_reference!(),
+            r#"
 
 Available data fields:
 - title, subtitle, body (strings)
 - features (array of strings)
 - sections (array of section objects)
-- stats (array of {value, label, color?})
+- stats (array of {value, label, color?, icon?})
 - contact.email, contact.phone, contact.website, contact.address
-- style.primaryColor, style.accentColor, style.fontFamily"#
+- style.primaryColor, style.accentColor, style.fontFamily
+- page.width, page.height (lengths from the page size/orientation; use in #set page), page.margin
+- style.gradientStart, style.gradientStop (hex stops from style.backgroundGradient; use unescaped {{{...}}} inside rgb("..."))"#
+        )
     }
 }
 
@@ -127,3 +127,159 @@ fn test_write_template_validation_errors() {
     let result = WriteTemplateTool::execute_without_compile("", &data);
     assert!(result.is_err());
 }
+
+#[test]
+fn test_write_json_dry_run_compiles() {
+    let template = "= {{title}}\n{{body}}";
+
+    let data = WriteJsonTool::execute_dry_run(r#"{"title": "Launch", "body": "Hi"}"#, template)
+        .expect("valid JSON should compile");
+    assert_eq!(data.title, "Launch");
+
+    let result = WriteJsonTool::execute_dry_run(r#"{"title": "#, template);
+    assert!(result.unwrap_err().message().contains("JSON parse error"));
+}
+
+#[test]
+fn test_write_template_dry_run_reports_compile_errors() {
+    let data = SlickSheetData::new("Launch");
+
+    let template = WriteTemplateTool::execute_dry_run("= {{title}}", &data)
+        .expect("valid template should compile");
+    assert_eq!(template, "= {{title}}");
+
+    // Renders fine but is not valid Typst
+    let result = WriteTemplateTool::execute_dry_run("#text(size: )[{{title}}]", &data);
+    assert!(result
+        .unwrap_err()
+        .message()
+        .contains("Typst compilation failed"));
+}
+
+// ============================================================================
+// Tool Dispatch Tests
+// ============================================================================
+
+fn tool_call(name: &str, arguments: serde_json::Value) -> crate::ai::client::ToolCall {
+    crate::ai::client::ToolCall {
+        id: format!("call_{}", name),
+        kind: "function".to_string(),
+        function: crate::ai::client::FunctionCall {
+            name: name.to_string(),
+            arguments: arguments.to_string(),
+        },
+    }
+}
+
+fn compile_ok(_code: &str) -> Result<String, String> {
+    Ok("<svg></svg>".to_string())
+}
+
+#[test]
+fn test_all_tool_definitions_names() {
+    let names: Vec<&str> = all_tool_definitions()
+        .iter()
+        .map(|tool| tool.function.name)
+        .collect();
+
+    assert_eq!(
+        names,
+        [
+            "read_json",
+            "write_json",
+            "read_template",
+            "write_template",
+            "generate_image"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_dispatch_read_json_returns_current_data() {
+    let mut ctx = ToolContext::new(SlickSheetData::new("Current"), "{{title}}");
+
+    let result = dispatch_tool_call(
+        &tool_call("read_json", serde_json::json!({})),
+        &mut ctx,
+        &mut compile_ok,
+    )
+    .await;
+
+    assert!(result.is_success());
+    assert!(result.message().contains("Current"));
+}
+
+#[tokio::test]
+async fn test_dispatch_write_json_updates_context() {
+    let mut ctx = ToolContext::new(SlickSheetData::new("Old"), "{{title}}");
+    let call = tool_call(
+        "write_json",
+        serde_json::json!({ "json": r#"{"title": "New", "features": ["Fast"]}"# }),
+    );
+
+    let result = dispatch_tool_call(&call, &mut ctx, &mut compile_ok).await;
+
+    assert!(result.is_success(), "{}", result.message());
+    assert_eq!(ctx.data.title, "New");
+    assert_eq!(ctx.data.features, vec!["Fast".to_string()]);
+}
+
+#[tokio::test]
+async fn test_dispatch_write_json_rejects_invalid_data() {
+    let mut ctx = ToolContext::new(SlickSheetData::new("Old"), "{{title}}");
+    let call = tool_call(
+        "write_json",
+        serde_json::json!({ "json": r#"{"title": ""}"# }),
+    );
+
+    let result = dispatch_tool_call(&call, &mut ctx, &mut compile_ok).await;
+
+    assert!(!result.is_success());
+    assert_eq!(ctx.data.title, "Old");
+}
+
+#[tokio::test]
+async fn test_dispatch_write_template_reports_compile_error() {
+    let mut ctx = ToolContext::new(SlickSheetData::new("Title"), "{{title}}");
+    let call = tool_call(
+        "write_template",
+        serde_json::json!({ "template": "= {{title}}" }),
+    );
+    let mut failing_compile = |_: &str| -> Result<String, String> { Err("bad".to_string()) };
+
+    let result = dispatch_tool_call(&call, &mut ctx, &mut failing_compile).await;
+
+    assert!(result.message().contains("Typst compilation failed"));
+    assert_eq!(ctx.template, "{{title}}");
+}
+
+#[tokio::test]
+async fn test_dispatch_missing_argument_and_unknown_tool() {
+    let mut ctx = ToolContext::new(SlickSheetData::new("Title"), "{{title}}");
+
+    let missing = dispatch_tool_call(
+        &tool_call("write_json", serde_json::json!({})),
+        &mut ctx,
+        &mut compile_ok,
+    )
+    .await;
+    assert!(missing.message().contains("Missing string argument 'json'"));
+
+    let unknown = dispatch_tool_call(
+        &tool_call("delete_everything", serde_json::json!({})),
+        &mut ctx,
+        &mut compile_ok,
+    )
+    .await;
+    assert_eq!(unknown.message(), "Unknown tool: delete_everything");
+}
+
+#[test]
+fn test_template_tools_share_syntax_reference() {
+    let reference = crate::template::syntax_reference!();
+    let tool_prompt = crate::ai::PromptTemplate::ToolBasedEditing.system_prompt();
+
+    assert!(ReadTemplateTool.description().contains(reference));
+    assert!(WriteTemplateTool.description().contains(reference));
+    assert!(tool_prompt.contains(reference));
+}
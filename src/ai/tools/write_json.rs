@@ -5,6 +5,7 @@
 use super::{AiTool, ToolResult};
 use crate::data::{validate_schema, SlickSheetData};
 use crate::template::TemplateEngine;
+use crate::world::VirtualWorld;
 
 /// Tool for writing new JSON content data
 pub struct WriteJsonTool;
@@ -61,6 +62,14 @@ impl WriteJsonTool {
         Ok(data)
     }
 
+    /// Execute with a dry-run compile of the current template in the built-in Typst world
+    pub fn execute_dry_run(
+        new_json: &str,
+        current_template: &str,
+    ) -> Result<SlickSheetData, ToolResult> {
+        Self::execute(new_json, current_template, VirtualWorld::compile_to_svg)
+    }
+
     /// Execute without compilation test (for simpler validation)
     pub fn execute_without_compile(new_json: &str) -> Result<SlickSheetData, ToolResult> {
         // Step 1: Parse JSON
@@ -103,17 +112,24 @@ Optional fields:
 - body: string
 - sections: array of section objects
 - features: array of strings
-- stats: array of {value, label, color?} objects
-- contact: {email?, phone?, website?, address?}
-- style: {primaryColor?, accentColor?, fontFamily?}
+- stats: array of {value, label, color?, icon?} objects
+- contact: {email?, phone?, website?, address?, linkedin?, twitter?, instagram?, social?: {platform: handle}}
+- style: {primaryColor?, accentColor?, fontFamily?, backgroundGradient?: [startHex, stopHex]}
+- page: {size?: "letter" | "legal" | "a4", orientation?: "portrait" | "landscape", margin?: "2cm"}
 
 Section object:
 - heading: string (required)
-- type: "text" | "list" | "table" | "quote"
+- type: "text" | "list" | "table" | "quote" | "image"
 - content: string (for text/quote)
 - items: string[] (for list)
 - rows: string[][] (for table)
-- columns: number (for table)"#
+- columns: number (for table)
+- imageRef: string (for image, a stored img_... ID or path)
+- caption: string (optional, for image)"#
+    }
+
+    fn parameters(&self) -> serde_json::Value {
+        super::string_parameter("json", "The complete content data as a JSON string")
     }
 }
 
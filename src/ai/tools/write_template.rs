@@ -5,6 +5,7 @@
 use super::{AiTool, ToolResult};
 use crate::data::SlickSheetData;
 use crate::template::{validate_template, TemplateEngine};
+use crate::world::VirtualWorld;
 
 /// Tool for writing a new Typst template
 pub struct WriteTemplateTool;
@@ -53,6 +54,14 @@ impl WriteTemplateTool {
         Ok(new_template.to_string())
     }
 
+    /// Execute with a dry-run compile in the built-in Typst world
+    pub fn execute_dry_run(
+        new_template: &str,
+        current_data: &SlickSheetData,
+    ) -> Result<String, ToolResult> {
+        Self::execute(new_template, current_data, VirtualWorld::compile_to_svg)
+    }
+
     /// Execute without compilation test (for simpler validation)
     pub fn execute_without_compile(
         new_template: &str,
@@ -85,16 +94,15 @@ impl AiTool for WriteTemplateTool {
     }
 
     fn description(&self) -> &'static str {
-        r#"Update the Typst template.
+        concat!(
+            r#"Update the Typst template.
 
 IMPORTANT: Always write the COMPLETE template, not partial updates.
 The system validates and test-compiles your template before accepting it.
 
-Templates use Handlebars-style placeholders:
-- {{field}} - Simple value substitution
-- {{#if field}}...{{/if}} - Conditional sections
-- {{#each items}}...{{/each}} - Loop over arrays
-- {{field | default: 'value'}} - Default values
+"#,
+            crate::template::syntax_reference!(),
+            r#"
 
 Example template structure:
 ```
@@ -118,6 +126,11 @@ Example template structure:
 {{/each}}
 {{/if}}
 ```"#
+        )
+    }
+
+    fn parameters(&self) -> serde_json::Value {
+        super::string_parameter("template", "The complete Typst template")
     }
 }
 
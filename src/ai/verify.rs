@@ -1,10 +1,23 @@
 //! Visual verification logic for AI-generated changes
 //!
-//! This module provides verification infrastructure for the planned
-//! visual verification feature. Some items are not yet used internally.
+//! The agent loop renders each successful compile to PNG and asks a vision
+//! model whether it matches the request. Some items are not yet used
+//! internally.
 
 #![allow(dead_code)]
 
+use base64::Engine;
+
+use super::client::{ChatMessage, OpenRouterClient};
+use super::prompts::{generate_system_prompt, generate_visual_verification_prompt, PromptTemplate};
+use crate::persistence::png_bytes_from_svg;
+
+/// Minimum confidence for a vision verdict to be accepted
+pub const DEFAULT_CONFIDENCE_THRESHOLD: f64 = 0.7;
+
+/// Scale applied when rasterizing the SVG preview (1.0 = 1px per pt)
+const PREVIEW_SCALE: f32 = 1.5;
+
 /// Result of verifying a change
 #[derive(Debug, Clone)]
 pub enum VerificationResult {
@@ -94,8 +107,18 @@ pub fn verify_change(
 
 /// Parse a vision LLM response for verification
 pub fn parse_verification_response(response: &str) -> VerificationResult {
+    parse_verification_response_with_threshold(response, DEFAULT_CONFIDENCE_THRESHOLD)
+}
+
+/// Parse a vision LLM response, accepting it only at or above `threshold`
+///
+/// The JSON object may be wrapped in markdown fences or surrounding prose.
+pub fn parse_verification_response_with_threshold(
+    response: &str,
+    threshold: f64,
+) -> VerificationResult {
     // Try to parse as JSON
-    if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(response) {
+    if let Some(parsed) = extract_json_object(response) {
         let matches_intent = parsed
             .get("matches_intent")
             .and_then(|v| v.as_bool())
@@ -122,7 +145,7 @@ pub fn parse_verification_response(response: &str) -> VerificationResult {
             .unwrap_or("")
             .to_string();
 
-        if matches_intent && confidence >= 0.7 {
+        if matches_intent && confidence >= threshold {
             VerificationResult::Success {
                 confidence,
                 message: if issues.is_empty() {
@@ -157,3 +180,63 @@ pub fn parse_verification_response(response: &str) -> VerificationResult {
         }
     }
 }
+
+/// Find and parse the first JSON object in a model response
+fn extract_json_object(response: &str) -> Option<serde_json::Value> {
+    let start = response.find('{')?;
+    let end = response.rfind('}')?;
+    if end < start {
+        return None;
+    }
+    serde_json::from_str::<serde_json::Value>(&response[start..=end])
+        .ok()
+        .filter(serde_json::Value::is_object)
+}
+
+/// Feedback to send into another iteration, if verification rejected the output
+///
+/// Only `NeedsRetry` asks for another attempt; a `Failed` verification means
+/// the check itself could not run and the compiled output is kept.
+pub fn retry_feedback(result: &VerificationResult) -> Option<String> {
+    match result {
+        VerificationResult::NeedsRetry { reason, suggestion } if suggestion.is_empty() => {
+            Some(reason.clone())
+        }
+        VerificationResult::NeedsRetry { reason, suggestion } => {
+            Some(format!("{}\nSuggestion: {}", reason, suggestion))
+        }
+        _ => None,
+    }
+}
+
+/// Rasterize compiled SVG output to PNG bytes
+pub fn render_png(svg: &str) -> Result<Vec<u8>, String> {
+    png_bytes_from_svg(svg, PREVIEW_SCALE)
+}
+
+/// Ask a vision model whether the rendered output matches the request
+pub async fn verify_change_with_vision(
+    client: &OpenRouterClient,
+    model: &str,
+    request: &str,
+    svg: &str,
+    threshold: f64,
+) -> VerificationResult {
+    let png = match render_png(svg) {
+        Ok(png) => png,
+        Err(error) => return VerificationResult::Failed { error },
+    };
+    let encoded = base64::engine::general_purpose::STANDARD.encode(&png);
+    let messages = vec![
+        ChatMessage::system(generate_system_prompt(PromptTemplate::VisualVerification)),
+        ChatMessage::user(generate_visual_verification_prompt(request, &encoded))
+            .with_image(format!("data:image/png;base64,{}", encoded)),
+    ];
+
+    match client.chat(model, messages).await {
+        Ok(response) => parse_verification_response_with_threshold(&response.content, threshold),
+        Err(e) => VerificationResult::Failed {
+            error: format!("Verification request failed: {}", e),
+        },
+    }
+}
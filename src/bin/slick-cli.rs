@@ -4,10 +4,15 @@
 //! - Read/write JSON content data
 //! - Read/write Typst templates
 //! - Render templates with data
-//! - Compile Typst to SVG/PDF
-//! - Run AI agent for automated editing
+//! - Validate templates, optionally against the data schema
+//! - Compile Typst to SVG/PDF, optionally rebuilding on change or piping
+//!   through stdin/stdout
+//! - Run AI agent for automated editing, on one project or a batch
+//! - Print the JSON Schema for content data
 
 use clap::{Parser, Subcommand};
+use slick_sheet_studio::ai::verify::DEFAULT_CONFIDENCE_THRESHOLD;
+use slick_sheet_studio::persistence::{PdfStandard, StreamFormat};
 use std::path::{Path, PathBuf};
 use std::process::ExitCode;
 
@@ -68,6 +73,17 @@ enum Commands {
         project: PathBuf,
     },
 
+    /// Export a project's Typst source as a standalone .typ file
+    ExportTypst {
+        /// Path to project JSON file
+        #[arg(short, long)]
+        project: PathBuf,
+
+        /// Output .typ file path (default: <project name>.typ in the current directory)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
     /// Write new template to a project (validates before accepting)
     WriteTemplate {
         /// Path to project JSON file
@@ -106,11 +122,27 @@ enum Commands {
         output: Option<PathBuf>,
     },
 
+    /// Validate a template's syntax and variable names
+    Validate {
+        /// Path to template file
+        #[arg(short, long)]
+        template: PathBuf,
+
+        /// Fail on variables whose top-level field is not in the data schema,
+        /// and on lints such as unguarded images
+        #[arg(long)]
+        strict: bool,
+    },
+
     /// Compile Typst source to SVG, PDF, or PNG
     Compile {
         /// Path to Typst source file
-        #[arg(short, long)]
-        input: PathBuf,
+        #[arg(short, long, required_unless_present = "stdin")]
+        input: Option<PathBuf>,
+
+        /// Read Typst source from standard input instead of --input
+        #[arg(long, conflicts_with_all = ["input", "watch"])]
+        stdin: bool,
 
         /// Output SVG file path
         #[arg(long)]
@@ -119,6 +151,31 @@ enum Commands {
         /// Output PDF file path
         #[arg(long)]
         output_pdf: Option<PathBuf>,
+
+        /// Write the first page as SVG text to standard output
+        #[arg(long, conflicts_with = "stdout_pdf")]
+        stdout_svg: bool,
+
+        /// Write the PDF as raw bytes to standard output
+        #[arg(long)]
+        stdout_pdf: bool,
+
+        /// Minify SVG output for web embedding, dropping editor-only edit links
+        #[arg(long)]
+        minify_svg: bool,
+
+        /// Extra file the source can #include/#import, as virtual-path=file
+        /// (repeatable), e.g. --include partials/header.typ=header.typ
+        #[arg(long = "include", value_name = "PATH=FILE", value_parser = parse_include)]
+        includes: Vec<(String, PathBuf)>,
+
+        /// PDF standard to conform to: "1.7" (default) or "a-2b" for PDF/A-2b
+        #[arg(long, value_name = "STANDARD", default_value = "1.7", value_parser = parse_pdf_standard)]
+        pdf_standard: PdfStandard,
+
+        /// Rebuild whenever the input or an include changes (Ctrl+C to stop)
+        #[arg(long)]
+        watch: bool,
     },
 
     /// Run AI agent loop to make changes based on a prompt
@@ -139,6 +196,10 @@ enum Commands {
         #[arg(short, long)]
         model: Option<String>,
 
+        /// Fallback model to try when the previous one is unavailable (repeatable)
+        #[arg(long = "fallback-model")]
+        fallback_models: Vec<String>,
+
         /// Maximum iterations (default: 3)
         #[arg(long, default_value = "3")]
         max_iterations: usize,
@@ -151,6 +212,10 @@ enum Commands {
         #[arg(long)]
         visual_verify: bool,
 
+        /// Minimum verification confidence before retrying (0.0 - 1.0)
+        #[arg(long, default_value = "0.7")]
+        confidence_threshold: f64,
+
         /// Directory to save verification screenshots
         #[arg(long)]
         save_screenshots: Option<PathBuf>,
@@ -163,6 +228,59 @@ enum Commands {
         #[arg(long)]
         dry_run: bool,
     },
+
+    /// Run the AI agent with one prompt across many project files
+    BatchAgent {
+        /// Glob matching project JSON files (e.g. "sheets/*.json")
+        #[arg(short, long)]
+        glob: String,
+
+        /// AI prompt text
+        #[arg(long)]
+        prompt: Option<String>,
+
+        /// Read prompt from file
+        #[arg(long)]
+        prompt_file: Option<PathBuf>,
+
+        /// AI model to use (default: google/gemini-3-flash-preview)
+        #[arg(short, long)]
+        model: Option<String>,
+
+        /// Fallback model to try when the previous one is unavailable (repeatable)
+        #[arg(long = "fallback-model")]
+        fallback_models: Vec<String>,
+
+        /// Maximum iterations per project (default: 3)
+        #[arg(long, default_value = "3")]
+        max_iterations: usize,
+
+        /// Use tool-based editing mode (read/write JSON and template)
+        #[arg(long)]
+        tool_mode: bool,
+
+        /// Directory for updated projects (file names are preserved)
+        #[arg(short, long)]
+        output_dir: PathBuf,
+
+        /// List the matching projects without running the agent
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Print the JSON Schema describing content data
+    Schema {
+        /// Output in compact format (default: pretty)
+        #[arg(long)]
+        compact: bool,
+    },
+
+    /// List the font families available to templates
+    Fonts {
+        /// Exit with an error unless this font family is available
+        #[arg(long)]
+        check: Option<String>,
+    },
 }
 
 fn main() -> ExitCode {
@@ -182,6 +300,7 @@ fn main() -> ExitCode {
             dry_run,
         } => cmd_write_json(&project, input.as_deref(), stdin, no_validate, dry_run),
         Commands::ReadTemplate { project } => cmd_read_template(&project),
+        Commands::ExportTypst { project, output } => cmd_export_typst(&project, output.as_deref()),
         Commands::WriteTemplate {
             project,
             input,
@@ -196,17 +315,45 @@ fn main() -> ExitCode {
         } => cmd_render(&data, &template, output.as_deref()),
         Commands::Compile {
             input,
+            stdin: _,
             output_svg,
             output_pdf,
-        } => cmd_compile(&input, output_svg.as_deref(), output_pdf.as_deref()),
+            stdout_svg,
+            stdout_pdf,
+            minify_svg,
+            includes,
+            pdf_standard,
+            watch,
+        } => {
+            let stdout = match (stdout_svg, stdout_pdf) {
+                (true, _) if minify_svg => Some(StreamFormat::MinifiedSvg),
+                (true, _) => Some(StreamFormat::Svg),
+                (_, true) => Some(StreamFormat::Pdf(pdf_standard)),
+                _ => None,
+            };
+            let outputs = CompileOutputs {
+                svg: output_svg.as_deref(),
+                pdf: output_pdf.as_deref(),
+                stdout,
+                pdf_standard,
+                minify_svg,
+            };
+            // clap rejects --stdin together with --input, and requires one of them
+            match input {
+                Some(input) => cmd_compile(&input, &outputs, &includes, watch),
+                None => cmd_compile_stdin(&outputs, &includes),
+            }
+        }
         Commands::Agent {
             project,
             prompt,
             prompt_file,
             model,
+            fallback_models,
             max_iterations,
             tool_mode,
             visual_verify,
+            confidence_threshold,
             save_screenshots,
             output,
             dry_run,
@@ -215,13 +362,39 @@ fn main() -> ExitCode {
             prompt.as_deref(),
             prompt_file.as_deref(),
             model.as_deref(),
+            &fallback_models,
             max_iterations,
             tool_mode,
             visual_verify,
+            confidence_threshold,
             save_screenshots.as_deref(),
             output.as_deref(),
             dry_run,
         ),
+        Commands::BatchAgent {
+            glob,
+            prompt,
+            prompt_file,
+            model,
+            fallback_models,
+            max_iterations,
+            tool_mode,
+            output_dir,
+            dry_run,
+        } => cmd_batch_agent(
+            &glob,
+            prompt.as_deref(),
+            prompt_file.as_deref(),
+            model.as_deref(),
+            &fallback_models,
+            max_iterations,
+            tool_mode,
+            &output_dir,
+            dry_run,
+        ),
+        Commands::Validate { template, strict } => cmd_validate(&template, strict),
+        Commands::Schema { compact } => cmd_schema(compact),
+        Commands::Fonts { check } => cmd_fonts(check.as_deref()),
     };
 
     match result {
@@ -246,21 +419,179 @@ fn cmd_read_json(project: &Path, compact: bool) -> Result<(), String> {
     let project_data =
         Project::from_json(&content).map_err(|e| format!("Failed to parse project: {}", e))?;
 
-    // For now, just output the source (which is the Typst code)
-    // In a full implementation, this would output the structured JSON data
-    let _ = compact; // TODO: Use compact flag for JSON output formatting
-    println!("{}", project_data.source);
+    println!("{}", project_data.content_json(compact)?);
 
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
+fn cmd_batch_agent(
+    glob: &str,
+    prompt: Option<&str>,
+    prompt_file: Option<&Path>,
+    model: Option<&str>,
+    fallback_models: &[String],
+    max_iterations: usize,
+    tool_mode: bool,
+    output_dir: &Path,
+    dry_run: bool,
+) -> Result<(), String> {
+    use slick_sheet_studio::ai::agent::AgentResult;
+    use slick_sheet_studio::persistence::{expand_glob, run_batch};
+
+    let prompt_text = read_prompt(prompt, prompt_file)?;
+    let files = expand_glob(glob)?;
+    if files.is_empty() {
+        return Err(format!("No project files match '{}'", glob));
+    }
+
+    let model_name = model.unwrap_or("google/gemini-3-flash-preview");
+
+    println!("Projects: {}", files.len());
+    println!("Prompt: {}", prompt_text);
+    println!("Model: {}", model_name);
+    println!("Output directory: {}", output_dir.display());
+
+    if dry_run {
+        println!("\n[DRY RUN] Would run AI agent on:");
+        for file in &files {
+            println!("  {}", file.display());
+        }
+        return Ok(());
+    }
+
+    let api_key = openrouter_api_key()?;
+    std::fs::create_dir_all(output_dir)
+        .map_err(|e| format!("Failed to create output directory: {}", e))?;
+
+    let rt = tokio::runtime::Runtime::new()
+        .map_err(|e| format!("Failed to create tokio runtime: {}", e))?;
+    let settings = AgentSettings {
+        prompt: &prompt_text,
+        model: model_name,
+        fallback_models,
+        max_iterations,
+        tool_mode,
+        visual_verify: false,
+        confidence_threshold: DEFAULT_CONFIDENCE_THRESHOLD,
+    };
+
+    let summary = run_batch(&files, output_dir, |path, mut project| {
+        println!("\nRunning AI agent on {}...", path.display());
+        match run_agent_on_project(&rt, &api_key, &settings, &project) {
+            AgentResult::Success {
+                code, iterations, ..
+            } => {
+                println!("  Success after {} iteration(s)", iterations);
+                project.source = code;
+                Ok(project)
+            }
+            AgentResult::ToolSuccess {
+                data,
+                template,
+                code,
+                iterations,
+                ..
+            } => {
+                println!("  Success after {} iteration(s)", iterations);
+                project.source = code;
+                Ok(project.with_content(*data, template))
+            }
+            AgentResult::MaxIterationsReached { last_error, .. } => {
+                let reason = last_error.unwrap_or_else(|| "unknown error".to_string());
+                eprintln!("  Max iterations reached: {}", reason);
+                Err(format!("Max iterations reached: {}", reason))
+            }
+            AgentResult::Error(e) => {
+                eprintln!("  Agent error: {}", e);
+                Err(format!("Agent error: {}", e))
+            }
+        }
+    });
+
+    println!("\n{}", summary);
+    if summary.is_success() {
+        Ok(())
+    } else {
+        Err(format!("{} project(s) failed", summary.failed.len()))
+    }
+}
+
+fn cmd_validate(template: &Path, strict: bool) -> Result<(), String> {
+    use slick_sheet_studio::template::{
+        lint, validate_template, validate_template_against_schema, SCHEMA_FIELDS,
+    };
+
+    let source = read_source_file(template)?;
+
+    fn join_errors<E: ToString>(errors: Vec<E>) -> String {
+        errors
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    if strict {
+        validate_template_against_schema(&source, SCHEMA_FIELDS).map_err(join_errors)?;
+        let lints = lint(&source);
+        if !lints.is_empty() {
+            return Err(join_errors(lints));
+        }
+    } else {
+        for warning in validate_template(&source).map_err(join_errors)? {
+            eprintln!("Warning: {}", warning);
+        }
+    }
+
+    println!("Template is valid: {}", template.display());
+    Ok(())
+}
+
+fn cmd_schema(compact: bool) -> Result<(), String> {
+    let schema = slick_sheet_studio::data::json_schema();
+    let output = if compact {
+        serde_json::to_string(&schema)
+    } else {
+        serde_json::to_string_pretty(&schema)
+    }
+    .map_err(|e| format!("Failed to serialize schema: {}", e))?;
+    println!("{}", output);
+
+    Ok(())
+}
+
+fn cmd_fonts(check: Option<&str>) -> Result<(), String> {
+    use slick_sheet_studio::world::VirtualWorld;
+
+    let families = VirtualWorld::new("").font_families();
+
+    match check {
+        Some(family) => {
+            if families.iter().any(|f| f.eq_ignore_ascii_case(family)) {
+                println!("Font family is available: {}", family);
+                Ok(())
+            } else {
+                Err(format!("Font family is not available: {}", family))
+            }
+        }
+        None => {
+            for family in families {
+                println!("{}", family);
+            }
+            Ok(())
+        }
+    }
+}
+
 fn cmd_write_json(
     project: &Path,
     input: Option<&Path>,
     stdin: bool,
-    _no_validate: bool,
+    no_validate: bool,
     dry_run: bool,
 ) -> Result<(), String> {
+    use slick_sheet_studio::ai::WriteJsonTool;
     use slick_sheet_studio::persistence::Project;
 
     // Read the new JSON content
@@ -285,8 +616,16 @@ fn cmd_write_json(
     let mut project_data = Project::from_json(&existing_content)
         .map_err(|e| format!("Failed to parse project: {}", e))?;
 
-    // Update the source with new content
-    project_data.source = new_content;
+    // Check the data against the schema and the current template
+    let (_, template) = project_content(&project_data);
+    let data = if no_validate {
+        serde_json::from_str(&new_content).map_err(|e| format!("JSON parse error: {}", e))?
+    } else {
+        WriteJsonTool::execute_dry_run(&new_content, &template)
+            .map_err(|result| result.message().to_string())?
+    };
+    project_data.source = render_content(&template, &data)?;
+    let project_data = project_data.with_content(data, template);
 
     if dry_run {
         println!("Validation passed. Would write to: {}", project.display());
@@ -305,6 +644,32 @@ fn cmd_write_json(
     Ok(())
 }
 
+/// The content data and template a project is edited through
+///
+/// Plain Typst projects have no content data, so the source is the template.
+fn project_content(
+    project: &slick_sheet_studio::persistence::Project,
+) -> (slick_sheet_studio::data::SlickSheetData, String) {
+    let data = project
+        .content_data
+        .clone()
+        .unwrap_or_else(|| slick_sheet_studio::data::SlickSheetData::new(&project.metadata.name));
+    let template = project
+        .template_source
+        .clone()
+        .unwrap_or_else(|| project.source.clone());
+    (data, template)
+}
+
+/// Render a template with content data into Typst source
+fn render_content(
+    template: &str,
+    data: &slick_sheet_studio::data::SlickSheetData,
+) -> Result<String, String> {
+    slick_sheet_studio::template::TemplateEngine::render(template, data)
+        .map_err(|errors| format!("Template rendering failed:\n{}", errors.join("\n")))
+}
+
 fn cmd_read_template(project: &Path) -> Result<(), String> {
     use slick_sheet_studio::persistence::Project;
 
@@ -318,13 +683,33 @@ fn cmd_read_template(project: &Path) -> Result<(), String> {
     Ok(())
 }
 
+fn cmd_export_typst(project: &Path, output: Option<&Path>) -> Result<(), String> {
+    use slick_sheet_studio::persistence::{typst_filename, Project};
+
+    let content = std::fs::read_to_string(project)
+        .map_err(|e| format!("Failed to read project file: {}", e))?;
+
+    let project_data =
+        Project::from_json(&content).map_err(|e| format!("Failed to parse project: {}", e))?;
+
+    let output = output
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from(typst_filename(&project_data.metadata.name)));
+    std::fs::write(&output, &project_data.source)
+        .map_err(|e| format!("Failed to write Typst file: {}", e))?;
+
+    println!("Typst source written to: {}", output.display());
+    Ok(())
+}
+
 fn cmd_write_template(
     project: &Path,
     input: Option<&Path>,
     stdin: bool,
-    _no_validate: bool,
+    no_validate: bool,
     dry_run: bool,
 ) -> Result<(), String> {
+    use slick_sheet_studio::ai::WriteTemplateTool;
     use slick_sheet_studio::persistence::Project;
 
     // Read the new template content
@@ -349,8 +734,16 @@ fn cmd_write_template(
     let mut project_data = Project::from_json(&existing_content)
         .map_err(|e| format!("Failed to parse project: {}", e))?;
 
-    // Update the source with new template
-    project_data.source = new_template;
+    // Check the template renders and compiles with the current data
+    let (data, _) = project_content(&project_data);
+    let template = if no_validate {
+        new_template
+    } else {
+        WriteTemplateTool::execute_dry_run(&new_template, &data)
+            .map_err(|result| result.message().to_string())?
+    };
+    project_data.source = render_content(&template, &data)?;
+    let project_data = project_data.with_content(data, template);
 
     if dry_run {
         println!("Validation passed. Would write to: {}", project.display());
@@ -374,8 +767,7 @@ fn cmd_render(data: &Path, template: &Path, output: Option<&Path>) -> Result<(),
     let _data_content =
         std::fs::read_to_string(data).map_err(|e| format!("Failed to read data file: {}", e))?;
 
-    let template_content = std::fs::read_to_string(template)
-        .map_err(|e| format!("Failed to read template file: {}", e))?;
+    let template_content = read_source_file(template)?;
 
     // For now, just output the template (full template engine integration would parse data)
     // This is a placeholder - in full implementation, use TemplateEngine::render()
@@ -392,46 +784,374 @@ fn cmd_render(data: &Path, template: &Path, output: Option<&Path>) -> Result<(),
     Ok(())
 }
 
+/// Read a Typst source file, dropping a BOM and reporting invalid UTF-8
+fn read_source_file(path: &Path) -> Result<String, String> {
+    let bytes =
+        std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    slick_sheet_studio::world::decode_source(&path.display().to_string(), &bytes)
+}
+
+/// Parse a `--include virtual-path=file` argument
+fn parse_include(arg: &str) -> Result<(String, PathBuf), String> {
+    match arg.split_once('=') {
+        Some((path, file)) if !path.trim().is_empty() && !file.trim().is_empty() => {
+            Ok((path.trim().to_string(), PathBuf::from(file.trim())))
+        }
+        _ => Err(format!("Expected PATH=FILE, got '{}'", arg)),
+    }
+}
+
+fn parse_pdf_standard(arg: &str) -> Result<PdfStandard, String> {
+    match arg.trim().to_ascii_lowercase().as_str() {
+        "1.7" | "pdf-1.7" => Ok(PdfStandard::Pdf17),
+        "a-2b" | "pdf/a-2b" | "pdfa-2b" => Ok(PdfStandard::PdfA2b),
+        _ => Err(format!("Expected 1.7 or a-2b, got '{}'", arg)),
+    }
+}
+
+/// Where `compile` writes its results
+struct CompileOutputs<'a> {
+    svg: Option<&'a Path>,
+    pdf: Option<&'a Path>,
+    /// Format streamed to standard output, if any
+    stdout: Option<StreamFormat>,
+    pdf_standard: PdfStandard,
+    /// Minify the SVG written by --output-svg
+    minify_svg: bool,
+}
+
+impl CompileOutputs<'_> {
+    fn check(&self) -> Result<(), String> {
+        if self.svg.is_none() && self.pdf.is_none() && self.stdout.is_none() {
+            return Err("At least one output format must be specified \
+                (--output-svg, --output-pdf, --stdout-svg or --stdout-pdf)"
+                .to_string());
+        }
+        Ok(())
+    }
+
+    /// Print a progress line, on stderr when stdout carries the output
+    fn report(&self, message: &str) {
+        if self.stdout.is_some() {
+            eprintln!("{}", message);
+        } else {
+            println!("{}", message);
+        }
+    }
+}
+
 fn cmd_compile(
     input: &Path,
-    output_svg: Option<&Path>,
-    output_pdf: Option<&Path>,
+    outputs: &CompileOutputs,
+    includes: &[(String, PathBuf)],
+    watch: bool,
 ) -> Result<(), String> {
-    use slick_sheet_studio::world::VirtualWorld;
+    outputs.check()?;
 
-    if output_svg.is_none() && output_pdf.is_none() {
-        return Err(
-            "At least one output format must be specified (--output-svg or --output-pdf)"
-                .to_string(),
-        );
+    let build = || compile_outputs(&read_source_file(input)?, outputs, includes);
+    if !watch {
+        return build();
     }
 
-    let source =
-        std::fs::read_to_string(input).map_err(|e| format!("Failed to read input file: {}", e))?;
+    let mut watched = vec![input.to_path_buf()];
+    watched.extend(includes.iter().map(|(_, file)| file.clone()));
+    watch_and_rebuild(&watched, build)
+}
+
+/// Compile source piped in on standard input
+fn cmd_compile_stdin(
+    outputs: &CompileOutputs,
+    includes: &[(String, PathBuf)],
+) -> Result<(), String> {
+    use slick_sheet_studio::persistence::read_source;
+
+    outputs.check()?;
+    let source = read_source(std::io::stdin().lock(), "stdin")?;
+    compile_outputs(&source, outputs, includes)
+}
+
+/// Compile the source (with includes) to the requested outputs once
+fn compile_outputs(
+    source: &str,
+    outputs: &CompileOutputs,
+    includes: &[(String, PathBuf)],
+) -> Result<(), String> {
+    use slick_sheet_studio::world::VirtualWorld;
+
+    let mut world = VirtualWorld::new(source);
+    for (virtual_path, file) in includes {
+        let content = std::fs::read(file)
+            .map_err(|e| format!("Failed to read include {}: {}", file.display(), e))?;
+        world.add_file(virtual_path, content);
+    }
 
     // Compile to SVG if requested
-    if let Some(svg_path) = output_svg {
-        let svg = VirtualWorld::compile_to_svg(&source)
+    if let Some(svg_path) = outputs.svg {
+        let svg = world
+            .compile_first_page_svg()
             .map_err(|errors| format!("Compilation failed:\n{}", errors.join("\n")))?;
+        let svg = if outputs.minify_svg {
+            slick_sheet_studio::persistence::minify_svg_for_export(&svg)
+        } else {
+            svg
+        };
 
         std::fs::write(svg_path, svg).map_err(|e| format!("Failed to write SVG file: {}", e))?;
 
-        println!("SVG written to: {}", svg_path.display());
+        outputs.report(&format!("SVG written to: {}", svg_path.display()));
     }
 
     // Compile to PDF if requested
-    if let Some(pdf_path) = output_pdf {
-        use slick_sheet_studio::persistence::pdf_bytes_from_source;
+    if let Some(pdf_path) = outputs.pdf {
+        use slick_sheet_studio::persistence::{pdf_bytes_from_world, PdfMeta};
 
-        let pdf_bytes =
-            pdf_bytes_from_source(&source).map_err(|e| format!("PDF export failed: {}", e))?;
+        let pdf_bytes = pdf_bytes_from_world(&world, PdfMeta::default(), outputs.pdf_standard)
+            .map_err(|e| format!("PDF export failed: {}", e))?;
 
         std::fs::write(pdf_path, pdf_bytes)
             .map_err(|e| format!("Failed to write PDF file: {}", e))?;
 
-        println!("PDF written to: {}", pdf_path.display());
+        outputs.report(&format!(
+            "PDF ({}) written to: {}",
+            outputs.pdf_standard.as_str(),
+            pdf_path.display()
+        ));
+    }
+
+    // Stream to stdout last, after any progress lines
+    if let Some(format) = outputs.stdout {
+        use slick_sheet_studio::persistence::write_compiled;
+
+        write_compiled(&world, format, std::io::stdout().lock())?;
+    }
+
+    Ok(())
+}
+
+/// Messages delivered to the watch loop
+enum WatchMessage {
+    /// A file-system event (or watcher error)
+    Changed(notify::Result<notify::Event>),
+    /// Ctrl+C was pressed
+    Interrupted,
+}
+
+/// Run `build` now and again after each debounced change to `files`
+///
+/// Parent directories are watched rather than the files themselves, so
+/// editors that save by replacing the file keep triggering rebuilds.
+fn watch_and_rebuild(
+    files: &[PathBuf],
+    build: impl Fn() -> Result<(), String>,
+) -> Result<(), String> {
+    use notify::{RecursiveMode, Watcher};
+    use slick_sheet_studio::persistence::Debouncer;
+    use std::collections::HashSet;
+    use std::sync::mpsc;
+    use std::time::Instant;
+
+    let watched: HashSet<PathBuf> = files
+        .iter()
+        .map(|file| std::fs::canonicalize(file).unwrap_or_else(|_| file.clone()))
+        .collect();
+    let dirs: HashSet<PathBuf> = watched
+        .iter()
+        .map(|file| match file.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => PathBuf::from("."),
+        })
+        .collect();
+
+    let (tx, rx) = mpsc::channel();
+    let fs_tx = tx.clone();
+    let mut watcher = notify::recommended_watcher(move |event| {
+        let _ = fs_tx.send(WatchMessage::Changed(event));
+    })
+    .map_err(|e| format!("Failed to start file watcher: {}", e))?;
+    for dir in &dirs {
+        watcher
+            .watch(dir, RecursiveMode::NonRecursive)
+            .map_err(|e| format!("Failed to watch {}: {}", dir.display(), e))?;
+    }
+
+    // Forward Ctrl+C so the loop can stop and drop the watcher cleanly
+    std::thread::spawn(move || {
+        if let Ok(rt) = tokio::runtime::Builder::new_current_thread()
+            .enable_io()
+            .build()
+        {
+            if rt.block_on(tokio::signal::ctrl_c()).is_ok() {
+                let _ = tx.send(WatchMessage::Interrupted);
+            }
+        }
+    });
+
+    report_build(build());
+    println!(
+        "Watching {} file(s) for changes. Press Ctrl+C to stop.",
+        watched.len()
+    );
+
+    let mut debouncer = Debouncer::default();
+    loop {
+        let message = match debouncer.time_until_ready(Instant::now()) {
+            Some(wait) => match rx.recv_timeout(wait) {
+                Ok(message) => Some(message),
+                Err(mpsc::RecvTimeoutError::Timeout) => None,
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            },
+            None => match rx.recv() {
+                Ok(message) => Some(message),
+                Err(_) => break,
+            },
+        };
+
+        match message {
+            Some(WatchMessage::Changed(Ok(event))) => {
+                let relevant = event.paths.iter().any(|path| {
+                    watched.contains(&std::fs::canonicalize(path).unwrap_or_else(|_| path.clone()))
+                });
+                if relevant && !event.kind.is_access() {
+                    debouncer.record(Instant::now());
+                }
+            }
+            Some(WatchMessage::Changed(Err(e))) => {
+                eprintln!("[{}] Watch error: {}", clock_time(), e);
+            }
+            Some(WatchMessage::Interrupted) => break,
+            None => {}
+        }
+
+        if debouncer.poll(Instant::now()) {
+            report_build(build());
+        }
+    }
+
+    println!("\nStopped watching.");
+    Ok(())
+}
+
+/// Print a timestamped line for a watch-mode build
+fn report_build(result: Result<(), String>) {
+    match result {
+        Ok(()) => println!("[{}] Build succeeded", clock_time()),
+        Err(e) => eprintln!("[{}] Build failed: {}", clock_time(), e),
+    }
+}
+
+/// Current UTC time of day as HH:MM:SS
+fn clock_time() -> String {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    format!(
+        "{:02}:{:02}:{:02}",
+        secs / 3600 % 24,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+/// Get the prompt from --prompt or --prompt-file
+fn read_prompt(prompt: Option<&str>, prompt_file: Option<&Path>) -> Result<String, String> {
+    if let Some(p) = prompt {
+        Ok(p.to_string())
+    } else if let Some(pf) = prompt_file {
+        std::fs::read_to_string(pf).map_err(|e| format!("Failed to read prompt file: {}", e))
+    } else {
+        Err("Either --prompt or --prompt-file must be provided".to_string())
+    }
+}
+
+/// Read the OpenRouter API key from the environment
+fn openrouter_api_key() -> Result<String, String> {
+    let api_key = std::env::var("OPENROUTER_API_KEY")
+        .map_err(|_| "OPENROUTER_API_KEY environment variable not set. Load .env.testing first.")?;
+
+    if api_key.is_empty() {
+        return Err("OPENROUTER_API_KEY is empty".to_string());
     }
+    Ok(api_key)
+}
+
+/// Agent options shared by the `agent` and `batch-agent` commands
+struct AgentSettings<'a> {
+    prompt: &'a str,
+    model: &'a str,
+    fallback_models: &'a [String],
+    max_iterations: usize,
+    tool_mode: bool,
+    visual_verify: bool,
+    confidence_threshold: f64,
+}
+
+/// Run the agent loop once against a project's source
+fn run_agent_on_project(
+    rt: &tokio::runtime::Runtime,
+    api_key: &str,
+    settings: &AgentSettings,
+    project: &slick_sheet_studio::persistence::Project,
+) -> slick_sheet_studio::ai::agent::AgentResult {
+    use slick_sheet_studio::ai::agent::{AgentConfig, AgentLoop};
+    use slick_sheet_studio::ai::client::{OpenRouterClient, OpenRouterConfig};
+    use slick_sheet_studio::ai::CancellationToken;
+    use slick_sheet_studio::world::VirtualWorld;
+
+    rt.block_on(async {
+        // Create client and config
+        let config = OpenRouterConfig::with_key(api_key.to_string());
+        let client = OpenRouterClient::new(config);
+
+        let agent_config = AgentConfig {
+            max_iterations: settings.max_iterations,
+            model: settings.model.to_string(),
+            fallback_models: settings.fallback_models.to_vec(),
+            enable_visual_verification: settings.visual_verify,
+            confidence_threshold: settings.confidence_threshold,
+            ..AgentConfig::default()
+        };
+
+        let mut agent = AgentLoop::new(client, agent_config);
+
+        // Compile function
+        let compile_fn = |code: &str| -> Result<String, String> {
+            VirtualWorld::compile_to_svg(code).map_err(|errors| errors.join("\n"))
+        };
+
+        // Run the agent
+        if settings.tool_mode {
+            let (data, template) = project_content(project);
+            agent
+                .run_with_tools(
+                    settings.prompt,
+                    data,
+                    &template,
+                    compile_fn,
+                    &CancellationToken::new(),
+                )
+                .await
+        } else {
+            agent
+                .run(settings.prompt, Some(&project.source), compile_fn)
+                .await
+        }
+    })
+}
 
+/// Write the project updated by `agent` to `output_path`
+fn write_agent_output(
+    project_data: &slick_sheet_studio::persistence::Project,
+    output_path: &Path,
+) -> Result<(), String> {
+    let output_json = project_data
+        .to_json_pretty()
+        .map_err(|e| format!("Failed to serialize project: {}", e))?;
+
+    std::fs::write(output_path, output_json)
+        .map_err(|e| format!("Failed to write output file: {}", e))?;
+
+    println!("Updated project written to: {}", output_path.display());
     Ok(())
 }
 
@@ -441,26 +1161,23 @@ fn cmd_agent(
     prompt: Option<&str>,
     prompt_file: Option<&Path>,
     model: Option<&str>,
+    fallback_models: &[String],
     max_iterations: usize,
-    _tool_mode: bool,
-    _visual_verify: bool,
+    tool_mode: bool,
+    visual_verify: bool,
+    confidence_threshold: f64,
     _save_screenshots: Option<&Path>,
     output: Option<&Path>,
     dry_run: bool,
 ) -> Result<(), String> {
-    use slick_sheet_studio::ai::agent::{AgentConfig, AgentLoop, AgentResult};
-    use slick_sheet_studio::ai::client::{OpenRouterClient, OpenRouterConfig};
+    use slick_sheet_studio::ai::agent::AgentResult;
     use slick_sheet_studio::persistence::Project;
-    use slick_sheet_studio::world::VirtualWorld;
 
-    // Get the prompt
-    let prompt_text = if let Some(p) = prompt {
-        p.to_string()
-    } else if let Some(pf) = prompt_file {
-        std::fs::read_to_string(pf).map_err(|e| format!("Failed to read prompt file: {}", e))?
-    } else {
-        return Err("Either --prompt or --prompt-file must be provided".to_string());
-    };
+    let prompt_text = read_prompt(prompt, prompt_file)?;
+
+    if !(0.0..=1.0).contains(&confidence_threshold) {
+        return Err("--confidence-threshold must be between 0.0 and 1.0".to_string());
+    }
 
     // Read project
     let project_content = std::fs::read_to_string(project)
@@ -474,20 +1191,23 @@ fn cmd_agent(
     println!("Project: {}", project_data.metadata.name);
     println!("Prompt: {}", prompt_text);
     println!("Model: {}", model_name);
+    if !fallback_models.is_empty() {
+        println!("Fallback models: {}", fallback_models.join(", "));
+    }
     println!("Max iterations: {}", max_iterations);
+    if tool_mode {
+        println!("Mode: tool-based editing");
+    }
+    if visual_verify {
+        println!("Visual verification: threshold {}", confidence_threshold);
+    }
 
     if dry_run {
         println!("\n[DRY RUN] Would run AI agent with the above settings.");
         return Ok(());
     }
 
-    // Check for API key
-    let api_key = std::env::var("OPENROUTER_API_KEY")
-        .map_err(|_| "OPENROUTER_API_KEY environment variable not set. Load .env.testing first.")?;
-
-    if api_key.is_empty() {
-        return Err("OPENROUTER_API_KEY is empty".to_string());
-    }
+    let api_key = openrouter_api_key()?;
 
     println!("\nRunning AI agent...");
 
@@ -495,50 +1215,50 @@ fn cmd_agent(
     let rt = tokio::runtime::Runtime::new()
         .map_err(|e| format!("Failed to create tokio runtime: {}", e))?;
 
-    let result = rt.block_on(async {
-        // Create client and config
-        let config = OpenRouterConfig::with_key(api_key);
-        let client = OpenRouterClient::new(config);
-
-        let agent_config = AgentConfig {
-            max_iterations,
-            model: model_name.to_string(),
-            enable_visual_verification: false,
-        };
-
-        let mut agent = AgentLoop::new(client, agent_config);
-
-        // Compile function
-        let compile_fn = |code: &str| -> Result<String, String> {
-            VirtualWorld::compile_to_svg(code).map_err(|errors| errors.join("\n"))
-        };
-
-        // Run the agent
-        agent
-            .run(&prompt_text, Some(&project_data.source), compile_fn)
-            .await
-    });
+    let settings = AgentSettings {
+        prompt: &prompt_text,
+        model: model_name,
+        fallback_models,
+        max_iterations,
+        tool_mode,
+        visual_verify,
+        confidence_threshold,
+    };
+    let result = run_agent_on_project(&rt, &api_key, &settings, &project_data);
 
     match result {
         AgentResult::Success {
-            code, iterations, ..
+            code,
+            iterations,
+            model,
+            ..
         } => {
-            println!("\nSuccess after {} iteration(s)!", iterations);
+            println!(
+                "\nSuccess after {} iteration(s) using {}!",
+                iterations, model
+            );
 
             // Update project
             project_data.source = code;
+            write_agent_output(&project_data, output.unwrap_or(project))
+        }
+        AgentResult::ToolSuccess {
+            data,
+            template,
+            code,
+            iterations,
+            model,
+            ..
+        } => {
+            println!(
+                "\nSuccess after {} iteration(s) using {}!",
+                iterations, model
+            );
 
-            // Write output
-            let output_path = output.unwrap_or(project);
-            let output_json = project_data
-                .to_json_pretty()
-                .map_err(|e| format!("Failed to serialize project: {}", e))?;
-
-            std::fs::write(output_path, output_json)
-                .map_err(|e| format!("Failed to write output file: {}", e))?;
-
-            println!("Updated project written to: {}", output_path.display());
-            Ok(())
+            // Keep the edited data and template so later runs start from them
+            project_data.source = code;
+            let project_data = project_data.with_content(*data, template);
+            write_agent_output(&project_data, output.unwrap_or(project))
         }
         AgentResult::MaxIterationsReached {
             last_code,
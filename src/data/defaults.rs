@@ -260,9 +260,21 @@ fn default_newsletter() -> SlickSheetData {
 
 fn default_infographic() -> SlickSheetData {
     SlickSheetData::new("Industry Statistics 2024")
-        .with_stat(Stat::new("78%", "Growth Rate").with_color("#4ecca3"))
-        .with_stat(Stat::new("2.5M", "Active Users").with_color("#e94560"))
-        .with_stat(Stat::new("$4.2B", "Market Size").with_color("#ffd93d"))
+        .with_stat(
+            Stat::new("78%", "Growth Rate")
+                .with_color("#4ecca3")
+                .with_icon("\u{2197}"),
+        )
+        .with_stat(
+            Stat::new("2.5M", "Active Users")
+                .with_color("#e94560")
+                .with_icon("\u{2605}"),
+        )
+        .with_stat(
+            Stat::new("$4.2B", "Market Size")
+                .with_color("#ffd93d")
+                .with_icon("\u{25C6}"),
+        )
         .with_section(Section::list(
             "Market Trends",
             vec![
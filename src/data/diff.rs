@@ -0,0 +1,251 @@
+//! Field-level diff between two versions of slick sheet data
+//!
+//! Used to preview AI-proposed changes before they are applied. Field names
+//! follow the click-to-edit field IDs (`title`, `features`, `stats/N/value`).
+
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use super::schema::{SlickSheetData, Stat};
+
+/// A single difference between two versions of the data
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldDiff {
+    /// A value present only in the new data
+    Added { field: String, value: String },
+    /// A value present only in the old data
+    Removed { field: String, value: String },
+    /// A value present in both but different
+    Changed {
+        field: String,
+        old: String,
+        new: String,
+    },
+}
+
+impl FieldDiff {
+    /// The field ID this diff applies to
+    pub fn field(&self) -> &str {
+        match self {
+            FieldDiff::Added { field, .. }
+            | FieldDiff::Removed { field, .. }
+            | FieldDiff::Changed { field, .. } => field,
+        }
+    }
+}
+
+impl fmt::Display for FieldDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldDiff::Added { field, value } => write!(f, "+ {}: {}", field, value),
+            FieldDiff::Removed { field, value } => write!(f, "- {}: {}", field, value),
+            FieldDiff::Changed { field, old, new } => {
+                write!(f, "~ {}: {} \u{2192} {}", field, old, new)
+            }
+        }
+    }
+}
+
+/// Compare two versions of the data field by field
+///
+/// Reports the title, subtitle and body; features as added/removed items
+/// (reordering alone is not a change); stats by position; sections by
+/// position, summarised by heading; and the remaining fields (contact,
+/// style, page, images, ...) as compact JSON. Any other difference is
+/// reported as a single `other` row, so changed data never diffs as empty.
+pub fn diff(old: &SlickSheetData, new: &SlickSheetData) -> Vec<FieldDiff> {
+    let mut diffs = Vec::new();
+
+    diff_text(&mut diffs, "title", Some(&old.title), Some(&new.title));
+    diff_text(
+        &mut diffs,
+        "subtitle",
+        old.subtitle.as_ref(),
+        new.subtitle.as_ref(),
+    );
+    diff_text(&mut diffs, "body", Some(&old.body), Some(&new.body));
+    diff_features(&mut diffs, &old.features, &new.features);
+    diff_stats(&mut diffs, &old.stats, &new.stats);
+
+    for i in 0..old.sections.len().max(new.sections.len()) {
+        let field = format!("sections/{}", i);
+        match (old.sections.get(i), new.sections.get(i)) {
+            (Some(old), Some(new)) if old != new => {
+                // Same heading means the section body was edited
+                let new_label = if old.heading == new.heading {
+                    format!("{} (edited)", new.heading)
+                } else {
+                    new.heading.clone()
+                };
+                diffs.push(FieldDiff::Changed {
+                    field,
+                    old: old.heading.clone(),
+                    new: new_label,
+                });
+            }
+            (None, Some(new)) => diffs.push(FieldDiff::Added {
+                field,
+                value: new.heading.clone(),
+            }),
+            (Some(old), None) => diffs.push(FieldDiff::Removed {
+                field,
+                value: old.heading.clone(),
+            }),
+            _ => {}
+        }
+    }
+
+    diff_json(
+        &mut diffs,
+        "body_format",
+        &old.body_format,
+        &new.body_format,
+    );
+    diff_json(&mut diffs, "contact", &old.contact, &new.contact);
+    diff_json(&mut diffs, "style", &old.style, &new.style);
+    diff_json(&mut diffs, "page", &old.page, &new.page);
+    diff_json(
+        &mut diffs,
+        "background_image",
+        &old.background_image,
+        &new.background_image,
+    );
+    diff_json(&mut diffs, "images", &old.images, &new.images);
+    diff_json(&mut diffs, "metadata", &old.metadata, &new.metadata);
+
+    if diffs.is_empty() && !same_ignoring_feature_order(old, new) {
+        diffs.push(FieldDiff::Changed {
+            field: "other".to_string(),
+            old: "previous values".to_string(),
+            new: "changed".to_string(),
+        });
+    }
+
+    diffs
+}
+
+/// Compare any other field by its JSON form, treating null, `""`, `[]` and
+/// `{}` as absent
+fn diff_json<T: Serialize>(diffs: &mut Vec<FieldDiff>, field: &str, old: &T, new: &T) {
+    let describe = |value: &T| {
+        let value = serde_json::to_value(value).ok()?;
+        let empty = match &value {
+            Value::Null => true,
+            Value::String(text) => text.is_empty(),
+            Value::Array(items) => items.is_empty(),
+            Value::Object(map) => map.is_empty(),
+            _ => false,
+        };
+        match value {
+            _ if empty => None,
+            Value::String(text) => Some(text),
+            value => Some(value.to_string()),
+        }
+    };
+    let old = describe(old);
+    let new = describe(new);
+    diff_text(diffs, field, old.as_ref(), new.as_ref());
+}
+
+/// Whether two versions match once features are put in the same order
+fn same_ignoring_feature_order(old: &SlickSheetData, new: &SlickSheetData) -> bool {
+    let sorted = |data: &SlickSheetData| {
+        let mut data = data.clone();
+        data.features.sort();
+        data
+    };
+    sorted(old) == sorted(new)
+}
+
+/// Compare an optional text field, treating empty text as absent
+fn diff_text(diffs: &mut Vec<FieldDiff>, field: &str, old: Option<&String>, new: Option<&String>) {
+    let old = old.filter(|s| !s.is_empty());
+    let new = new.filter(|s| !s.is_empty());
+    let field = field.to_string();
+    match (old, new) {
+        (Some(old), Some(new)) if old != new => diffs.push(FieldDiff::Changed {
+            field,
+            old: old.clone(),
+            new: new.clone(),
+        }),
+        (None, Some(new)) => diffs.push(FieldDiff::Added {
+            field,
+            value: new.clone(),
+        }),
+        (Some(old), None) => diffs.push(FieldDiff::Removed {
+            field,
+            value: old.clone(),
+        }),
+        _ => {}
+    }
+}
+
+/// Compare features as a multiset, reporting removals then additions
+fn diff_features(diffs: &mut Vec<FieldDiff>, old: &[String], new: &[String]) {
+    diffs.extend(
+        unmatched(old, new)
+            .into_iter()
+            .map(|feature| FieldDiff::Removed {
+                field: "features".to_string(),
+                value: feature.clone(),
+            }),
+    );
+    diffs.extend(
+        unmatched(new, old)
+            .into_iter()
+            .map(|feature| FieldDiff::Added {
+                field: "features".to_string(),
+                value: feature.clone(),
+            }),
+    );
+}
+
+/// Items of `items` left over after matching each against one in `others`
+fn unmatched<'a>(items: &'a [String], others: &[String]) -> Vec<&'a String> {
+    let mut available: HashMap<&str, usize> = HashMap::new();
+    for other in others {
+        *available.entry(other).or_default() += 1;
+    }
+    items
+        .iter()
+        .filter(|item| match available.get_mut(item.as_str()) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                false
+            }
+            _ => true,
+        })
+        .collect()
+}
+
+/// Compare stats position by position
+fn diff_stats(diffs: &mut Vec<FieldDiff>, old: &[Stat], new: &[Stat]) {
+    let describe = |stat: &Stat| format!("{} {}", stat.value, stat.label);
+
+    for i in 0..old.len().max(new.len()) {
+        match (old.get(i), new.get(i)) {
+            (Some(old), Some(new)) => {
+                for (part, old, new) in [
+                    ("value", Some(&old.value), Some(&new.value)),
+                    ("label", Some(&old.label), Some(&new.label)),
+                    ("color", old.color.as_ref(), new.color.as_ref()),
+                    ("icon", old.icon.as_ref(), new.icon.as_ref()),
+                ] {
+                    diff_text(diffs, &format!("stats/{}/{}", i, part), old, new);
+                }
+            }
+            (None, Some(new)) => diffs.push(FieldDiff::Added {
+                field: format!("stats/{}", i),
+                value: describe(new),
+            }),
+            (Some(old), None) => diffs.push(FieldDiff::Removed {
+                field: format!("stats/{}", i),
+                value: describe(old),
+            }),
+            (None, None) => {}
+        }
+    }
+}
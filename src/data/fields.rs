@@ -0,0 +1,165 @@
+//! Field-level access to slick sheet data
+//!
+//! Field IDs mirror the `cmd://edit/...` link paths used for click-to-edit:
+//! `title`, `subtitle`, `body`, `meta/key`, `features/N`, `stats/N/value`,
+//! `sections/N/heading`, `sections/N/caption` and `contact/email`.
+
+use super::schema::{ContactInfo, SlickSheetData};
+use thiserror::Error;
+
+/// Errors that can occur when reading or writing a field by ID
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum FieldError {
+    /// The field ID does not name an editable field
+    #[error("Unknown field: '{0}'")]
+    UnknownField(String),
+
+    /// The array index is past the end of the array
+    #[error("Index {index} is out of range for '{field}' (length {len})")]
+    IndexOutOfRange {
+        field: String,
+        index: usize,
+        len: usize,
+    },
+}
+
+/// Update a single field of the data from an edit-modal field ID
+pub fn update_data_field(
+    data: &mut SlickSheetData,
+    field_id: &str,
+    new_value: &str,
+) -> Result<(), FieldError> {
+    let unknown = || FieldError::UnknownField(field_id.to_string());
+    let parts: Vec<&str> = field_id.split('/').collect();
+
+    match parts.as_slice() {
+        ["title"] => data.title = new_value.to_string(),
+        ["subtitle"] => data.subtitle = non_empty(new_value),
+        ["body"] => data.body = new_value.to_string(),
+        ["meta", key] => {
+            data.metadata
+                .insert((*key).to_string(), new_value.to_string());
+        }
+        ["features", index] => {
+            let index = parse_index(index, field_id)?;
+            *element_mut(&mut data.features, "features", index)? = new_value.to_string();
+        }
+        ["stats", index, subfield] => {
+            let index = parse_index(index, field_id)?;
+            let stat = element_mut(&mut data.stats, "stats", index)?;
+            match *subfield {
+                "value" => stat.value = new_value.to_string(),
+                "label" => stat.label = new_value.to_string(),
+                "color" => stat.color = non_empty(new_value),
+                _ => return Err(unknown()),
+            }
+        }
+        ["sections", index, subfield] => {
+            let index = parse_index(index, field_id)?;
+            let section = element_mut(&mut data.sections, "sections", index)?;
+            match *subfield {
+                "heading" => section.heading = new_value.to_string(),
+                "content" => section.content = new_value.to_string(),
+                "caption" => section.caption = non_empty(new_value),
+                _ => return Err(unknown()),
+            }
+        }
+        ["contact", subfield] => {
+            let contact = data.contact.get_or_insert_with(ContactInfo::default);
+            let slot = match *subfield {
+                "email" => &mut contact.email,
+                "phone" => &mut contact.phone,
+                "website" => &mut contact.website,
+                "address" => &mut contact.address,
+                "linkedin" => &mut contact.linkedin,
+                "twitter" => &mut contact.twitter,
+                "instagram" => &mut contact.instagram,
+                _ => return Err(unknown()),
+            };
+            *slot = non_empty(new_value);
+        }
+        _ => return Err(unknown()),
+    }
+
+    Ok(())
+}
+
+/// Read a single field of the data by edit-modal field ID
+///
+/// Returns None if the field ID is unknown or the element does not exist.
+pub fn get_data_field(data: &SlickSheetData, field_id: &str) -> Option<String> {
+    let parts: Vec<&str> = field_id.split('/').collect();
+
+    match parts.as_slice() {
+        ["title"] => Some(data.title.clone()),
+        ["subtitle"] => Some(data.subtitle.clone().unwrap_or_default()),
+        ["body"] => Some(data.body.clone()),
+        ["meta", key] => Some(data.metadata.get(*key).cloned().unwrap_or_default()),
+        ["features", index] => data.features.get(index.parse::<usize>().ok()?).cloned(),
+        ["stats", index, subfield] => {
+            let stat = data.stats.get(index.parse::<usize>().ok()?)?;
+            match *subfield {
+                "value" => Some(stat.value.clone()),
+                "label" => Some(stat.label.clone()),
+                "color" => Some(stat.color.clone().unwrap_or_default()),
+                _ => None,
+            }
+        }
+        ["sections", index, subfield] => {
+            let section = data.sections.get(index.parse::<usize>().ok()?)?;
+            match *subfield {
+                "heading" => Some(section.heading.clone()),
+                "content" => Some(section.content.clone()),
+                "caption" => Some(section.caption.clone().unwrap_or_default()),
+                _ => None,
+            }
+        }
+        ["contact", subfield] => {
+            let contact = data.contact.clone().unwrap_or_default();
+            let value = match *subfield {
+                "email" => contact.email,
+                "phone" => contact.phone,
+                "website" => contact.website,
+                "address" => contact.address,
+                "linkedin" => contact.linkedin,
+                "twitter" => contact.twitter,
+                "instagram" => contact.instagram,
+                _ => return None,
+            };
+            Some(value.unwrap_or_default())
+        }
+        _ => None,
+    }
+}
+
+/// Parse an array index segment of a field ID
+fn parse_index(index: &str, field_id: &str) -> Result<usize, FieldError> {
+    index
+        .parse()
+        .map_err(|_| FieldError::UnknownField(field_id.to_string()))
+}
+
+/// Get a mutable reference to an array element, or an out-of-range error
+fn element_mut<'a, T>(
+    items: &'a mut [T],
+    field: &str,
+    index: usize,
+) -> Result<&'a mut T, FieldError> {
+    let len = items.len();
+    items
+        .get_mut(index)
+        .ok_or_else(|| FieldError::IndexOutOfRange {
+            field: field.to_string(),
+            index,
+            len,
+        })
+}
+
+/// Convert an empty string to None for optional fields
+fn non_empty(value: &str) -> Option<String> {
+    if value.trim().is_empty() {
+        None
+    } else {
+        Some(value.to_string())
+    }
+}
@@ -0,0 +1,152 @@
+//! JSON Schema for the content data model
+//!
+//! Hand-written to mirror the serde shape of `SlickSheetData`; the tests in
+//! `data::tests` check it against the structs so the two stay in sync.
+
+use serde_json::{json, Value};
+
+use super::schema::SectionType;
+
+/// Every `SectionType`, in declaration order
+const SECTION_TYPES: &[SectionType] = &[
+    SectionType::Text,
+    SectionType::List,
+    SectionType::Table,
+    SectionType::Quote,
+    SectionType::Image,
+];
+
+/// JSON Schema (draft 2020-12) describing serialized `SlickSheetData`
+pub fn json_schema() -> Value {
+    let section_types: Vec<&str> = SECTION_TYPES.iter().map(SectionType::as_str).collect();
+    let optional_string =
+        |description: &str| json!({ "type": "string", "description": description });
+
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "SlickSheetData",
+        "description": "Content data for a slick sheet",
+        "type": "object",
+        "required": ["title"],
+        "properties": {
+            "title": { "type": "string", "minLength": 1, "description": "Main title of the document" },
+            "subtitle": optional_string("Optional subtitle or tagline"),
+            "body": optional_string("Main body text"),
+            "body_format": {
+                "type": "string",
+                "enum": ["plain", "markdown"],
+                "default": "plain",
+                "description": "How body is written: plain text or markdown"
+            },
+            "sections": {
+                "type": "array",
+                "description": "Structured content sections",
+                "items": { "$ref": "#/$defs/section" }
+            },
+            "metadata": {
+                "type": "object",
+                "description": "Key-value metadata",
+                "additionalProperties": { "type": "string" }
+            },
+            "features": {
+                "type": "array",
+                "description": "Feature list items",
+                "items": { "type": "string" }
+            },
+            "stats": {
+                "type": "array",
+                "description": "Statistics/metrics to display",
+                "items": { "$ref": "#/$defs/stat" }
+            },
+            "contact": { "$ref": "#/$defs/contact" },
+            "style": { "$ref": "#/$defs/style" },
+            "page": { "$ref": "#/$defs/page" },
+            "background_image": optional_string("Image ID or path drawn full-bleed behind every page"),
+            "images": {
+                "type": "object",
+                "description": "Image references: semantic name -> image ID",
+                "additionalProperties": { "type": "string" }
+            }
+        },
+        "$defs": {
+            "section": {
+                "type": "object",
+                "required": ["heading"],
+                "properties": {
+                    "heading": { "type": "string" },
+                    "type": { "type": "string", "enum": section_types, "default": "text" },
+                    "content": optional_string("Content (for text and quote sections)"),
+                    "items": {
+                        "type": "array",
+                        "description": "List items (for list sections)",
+                        "items": { "type": "string" }
+                    },
+                    "rows": {
+                        "type": "array",
+                        "description": "Table rows (for table sections)",
+                        "items": { "type": "array", "items": { "type": "string" } }
+                    },
+                    "columns": {
+                        "type": "integer",
+                        "minimum": 0,
+                        "description": "Number of columns (for table sections)"
+                    },
+                    "imageRef": optional_string("Stored image ID or path (for image sections)"),
+                    "caption": optional_string("Image caption (for image sections)")
+                }
+            },
+            "stat": {
+                "type": "object",
+                "required": ["value", "label"],
+                "properties": {
+                    "value": { "type": "string" },
+                    "label": { "type": "string" },
+                    "color": optional_string("Hex color or color name"),
+                    "icon": optional_string("Short emoji/symbol or stored image ID (img_...)")
+                }
+            },
+            "contact": {
+                "type": "object",
+                "properties": {
+                    "email": optional_string("Email address"),
+                    "phone": optional_string("Phone number"),
+                    "website": optional_string("Website URL"),
+                    "address": optional_string("Physical address"),
+                    "linkedin": optional_string("LinkedIn profile or handle"),
+                    "twitter": optional_string("Twitter/X handle"),
+                    "instagram": optional_string("Instagram handle"),
+                    "social": {
+                        "type": "object",
+                        "description": "Other social platforms, keyed by platform name",
+                        "additionalProperties": { "type": "string" }
+                    }
+                }
+            },
+            "style": {
+                "type": "object",
+                "description": "Styling hints; camelCase aliases (primaryColor, ...) are also accepted",
+                "properties": {
+                    "primary_color": optional_string("Hex color or color name"),
+                    "accent_color": optional_string("Hex color or color name"),
+                    "font_family": optional_string("Font family name"),
+                    "background_gradient": {
+                        "type": "array",
+                        "description": "Background gradient as [start, stop] hex colors",
+                        "items": { "type": "string" },
+                        "minItems": 2,
+                        "maxItems": 2
+                    }
+                }
+            },
+            "page": {
+                "type": "object",
+                "description": "Page setup; US Letter portrait when absent",
+                "properties": {
+                    "size": { "type": "string", "enum": ["letter", "legal", "a4"] },
+                    "orientation": { "type": "string", "enum": ["portrait", "landscape"] },
+                    "margin": optional_string("Typst length such as 0.75in or 2cm")
+                }
+            }
+        }
+    })
+}
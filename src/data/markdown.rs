@@ -0,0 +1,161 @@
+//! Markdown-to-Typst conversion
+//!
+//! Covers the markdown subset that CMS content typically uses: ATX headings,
+//! bold, italic, inline code, links, and bulleted or numbered lists. Anything
+//! else is treated as text, with Typst markup characters escaped.
+
+/// Convert markdown to the equivalent Typst markup
+pub fn markdown_to_typst(markdown: &str) -> String {
+    markdown
+        .lines()
+        .map(convert_line)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Convert a single line, handling block-level markers
+fn convert_line(line: &str) -> String {
+    let trimmed = line.trim_start();
+    let indent = &line[..line.len() - trimmed.len()];
+
+    if let Some((level, text)) = heading(trimmed) {
+        return format!("{} {}", "=".repeat(level), convert_inline(text));
+    }
+    if let Some(text) = bullet_item(trimmed) {
+        return format!("{}- {}", indent, convert_inline(text));
+    }
+    if let Some(text) = numbered_item(trimmed) {
+        return format!("{}+ {}", indent, convert_inline(text));
+    }
+
+    let text = convert_inline(trimmed);
+    // A leading `=` or `/` would start a Typst heading or term list
+    if text.starts_with(['=', '/']) {
+        format!("{}\\{}", indent, text)
+    } else {
+        format!("{}{}", indent, text)
+    }
+}
+
+/// Parse `# Heading` through `###### Heading`
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    let rest = &line[level..];
+    if rest.is_empty() || rest.starts_with(' ') {
+        Some((level, rest.trim()))
+    } else {
+        None
+    }
+}
+
+/// Parse `- item`, `* item` or `+ item`
+fn bullet_item(line: &str) -> Option<&str> {
+    ["- ", "* ", "+ "]
+        .iter()
+        .find_map(|marker| line.strip_prefix(marker))
+}
+
+/// Parse `1. item` or `1) item`
+fn numbered_item(line: &str) -> Option<&str> {
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    if digits == 0 {
+        return None;
+    }
+    let rest = &line[digits..];
+    rest.strip_prefix(". ").or_else(|| rest.strip_prefix(") "))
+}
+
+/// Convert inline markup: emphasis, code spans and links
+fn convert_inline(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+
+    while i < chars.len() {
+        match convert_span(&chars, i) {
+            Some((converted, next)) => {
+                out.push_str(&converted);
+                i = next;
+            }
+            None => {
+                push_escaped(&mut out, chars[i]);
+                i += 1;
+            }
+        }
+    }
+
+    out
+}
+
+/// Try to convert a markdown construct starting at `i`
+///
+/// Returns the Typst markup and the index just past the construct, or `None`
+/// if the character at `i` is plain text.
+fn convert_span(chars: &[char], i: usize) -> Option<(String, usize)> {
+    let c = chars[i];
+    match c {
+        // Backslash escapes keep the next character literal
+        '\\' => {
+            let next = *chars.get(i + 1).filter(|n| n.is_ascii_punctuation())?;
+            let mut out = String::new();
+            push_escaped(&mut out, next);
+            Some((out, i + 2))
+        }
+        // Code spans map directly onto Typst raw text
+        '`' => {
+            let end = find(chars, i + 1, &['`'])?;
+            let code: String = chars[i + 1..end].iter().collect();
+            Some((format!("`{}`", code), end + 1))
+        }
+        // **bold** / __bold__
+        '*' | '_' if chars.get(i + 1) == Some(&c) => {
+            let end = find(chars, i + 2, &[c, c]).filter(|&end| end > i + 2)?;
+            let inner: String = chars[i + 2..end].iter().collect();
+            Some((format!("*{}*", convert_inline(&inner)), end + 2))
+        }
+        // *italic* / _italic_ (underscores inside words stay literal)
+        '*' | '_' => {
+            if c == '_' && i > 0 && chars[i - 1].is_alphanumeric() {
+                return None;
+            }
+            let end = (i + 2..chars.len()).find(|&j| chars[j] == c)?;
+            let inner: String = chars[i + 1..end].iter().collect();
+            Some((format!("_{}_", convert_inline(&inner)), end + 1))
+        }
+        // [text](url)
+        '[' => {
+            let mid = find(chars, i + 1, &[']', '('])?;
+            let end = find(chars, mid + 2, &[')'])?;
+            let label: String = chars[i + 1..mid].iter().collect();
+            let url: String = chars[mid + 2..end].iter().collect();
+            Some((
+                format!(
+                    "#link(\"{}\")[{}]",
+                    url.trim().replace('\\', "\\\\").replace('"', "\\\""),
+                    convert_inline(&label)
+                ),
+                end + 1,
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Find the first occurrence of `pattern` at or after `from`
+fn find(chars: &[char], from: usize, pattern: &[char]) -> Option<usize> {
+    (from..chars.len()).find(|&j| chars[j..].starts_with(pattern))
+}
+
+/// Push a text character, escaping Typst markup characters
+fn push_escaped(out: &mut String, c: char) {
+    if matches!(
+        c,
+        '@' | '<' | '>' | '[' | ']' | '#' | '$' | '*' | '_' | '`' | '\\'
+    ) {
+        out.push('\\');
+    }
+    out.push(c);
+}
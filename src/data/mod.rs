@@ -3,10 +3,18 @@
 //! This module provides:
 //! - `SlickSheetData` struct for JSON content storage
 //! - Schema validation for data integrity
+//! - Field-level get/update by click-to-edit field ID
 //! - Default data generators for templates
+//! - JSON Schema export of the content model
+//! - Markdown-to-Typst conversion for markdown bodies
+//! - Field-level diffs for previewing changes
 #![allow(dead_code)]
 
 mod defaults;
+mod diff;
+mod fields;
+mod json_schema;
+mod markdown;
 mod schema;
 mod validation;
 
@@ -14,10 +22,17 @@ mod validation;
 mod tests;
 
 pub use defaults::default_data_for_template;
-pub use schema::{Section, SectionType, SlickSheetData};
+pub use diff::{diff, FieldDiff};
+pub use fields::{get_data_field, update_data_field, FieldError};
+pub use json_schema::json_schema;
+pub use markdown::markdown_to_typst;
+pub use schema::{BodyFormat, Section, SectionType, SlickSheetData};
 
 // Public API - not all used internally yet
 #[allow(unused_imports)]
-pub use schema::{ContactInfo, Stat, StyleHints};
+pub use schema::{ContactInfo, Orientation, PageSetup, PageSize, Stat, StyleHints};
 #[allow(unused_imports)]
-pub use validation::{validate_schema, ValidationError};
+pub use validation::{
+    color_to_hex, is_valid_color, is_valid_length, validate_image_refs, validate_schema,
+    validate_schema_with, ValidationConfig, ValidationError,
+};
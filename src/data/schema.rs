@@ -6,8 +6,9 @@
 
 #![allow(dead_code)]
 
+use super::fields::FieldError;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 /// Main data model for a slick sheet document
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
@@ -23,6 +24,10 @@ pub struct SlickSheetData {
     #[serde(default)]
     pub body: String,
 
+    /// How `body` is written: plain text (escaped) or markdown (converted)
+    #[serde(default, skip_serializing_if = "BodyFormat::is_plain")]
+    pub body_format: BodyFormat,
+
     /// Structured content sections
     #[serde(default)]
     pub sections: Vec<Section>,
@@ -47,6 +52,14 @@ pub struct SlickSheetData {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub style: Option<StyleHints>,
 
+    /// Paper size, orientation and margin (US Letter portrait when absent)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page: Option<PageSetup>,
+
+    /// Image ID or path drawn full-bleed behind every page
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub background_image: Option<String>,
+
     /// Image references: semantic name -> image ID
     /// Example: { "logo": "img_abc123", "banner": "img_def456" }
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
@@ -74,6 +87,18 @@ impl SlickSheetData {
         self
     }
 
+    /// Builder method to set the body format
+    pub fn with_body_format(mut self, body_format: BodyFormat) -> Self {
+        self.body_format = body_format;
+        self
+    }
+
+    /// Builder method to set the page background image
+    pub fn with_background_image(mut self, image: impl Into<String>) -> Self {
+        self.background_image = Some(image.into());
+        self
+    }
+
     /// Builder method to add a section
     pub fn with_section(mut self, section: Section) -> Self {
         self.sections.push(section);
@@ -104,11 +129,55 @@ impl SlickSheetData {
         self
     }
 
+    /// Builder method to set the page setup
+    pub fn with_page(mut self, page: PageSetup) -> Self {
+        self.page = Some(page);
+        self
+    }
+
+    /// The page setup, or US Letter portrait when none is set
+    pub fn page_or_default(&self) -> PageSetup {
+        self.page.clone().unwrap_or_default()
+    }
+
     /// Builder method to add an image reference
     pub fn with_image(mut self, name: impl Into<String>, image_id: impl Into<String>) -> Self {
         self.images.insert(name.into(), image_id.into());
         self
     }
+
+    /// Insert a copy of the section at `index` directly after it
+    pub fn duplicate_section(&mut self, index: usize) -> Result<(), FieldError> {
+        let copy = self.section_at(index)?.clone();
+        self.sections.insert(index + 1, copy);
+        Ok(())
+    }
+
+    /// Move the section at `from` so it ends up at position `to`
+    pub fn move_section(&mut self, from: usize, to: usize) -> Result<(), FieldError> {
+        self.section_at(from)?;
+        self.section_at(to)?;
+        let section = self.sections.remove(from);
+        self.sections.insert(to, section);
+        Ok(())
+    }
+
+    /// Remove and return the section at `index`
+    pub fn remove_section(&mut self, index: usize) -> Result<Section, FieldError> {
+        self.section_at(index)?;
+        Ok(self.sections.remove(index))
+    }
+
+    /// Bounds-checked access to a section
+    fn section_at(&self, index: usize) -> Result<&Section, FieldError> {
+        self.sections
+            .get(index)
+            .ok_or_else(|| FieldError::IndexOutOfRange {
+                field: "sections".to_string(),
+                index,
+                len: self.sections.len(),
+            })
+    }
 }
 
 /// A content section in the document
@@ -136,6 +205,19 @@ pub struct Section {
     /// Number of columns (for table type)
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub columns: Option<usize>,
+
+    /// Stored image ID (`img_...`) or path (for image type)
+    #[serde(
+        default,
+        rename = "imageRef",
+        alias = "image_ref",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub image_ref: Option<String>,
+
+    /// Caption shown with the image (for image type)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub caption: Option<String>,
 }
 
 impl Default for Section {
@@ -147,6 +229,8 @@ impl Default for Section {
             items: None,
             rows: None,
             columns: None,
+            image_ref: None,
+            caption: None,
         }
     }
 }
@@ -192,6 +276,21 @@ impl Section {
             ..Default::default()
         }
     }
+
+    /// Create a new image section from a stored image ID or path
+    pub fn image(
+        heading: impl Into<String>,
+        image_ref: impl Into<String>,
+        caption: Option<String>,
+    ) -> Self {
+        Self {
+            heading: heading.into(),
+            section_type: SectionType::Image,
+            image_ref: Some(image_ref.into()),
+            caption,
+            ..Default::default()
+        }
+    }
 }
 
 /// Type of content section
@@ -207,6 +306,47 @@ pub enum SectionType {
     Table,
     /// Quote/testimonial
     Quote,
+    /// Stored image with optional caption
+    Image,
+}
+
+impl SectionType {
+    /// Get the serialized name of the section type (e.g. "list")
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SectionType::Text => "text",
+            SectionType::List => "list",
+            SectionType::Table => "table",
+            SectionType::Quote => "quote",
+            SectionType::Image => "image",
+        }
+    }
+}
+
+/// Markup used by the body field
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum BodyFormat {
+    /// Plain text, with Typst markup characters escaped
+    #[default]
+    Plain,
+    /// Markdown, converted to the equivalent Typst markup
+    Markdown,
+}
+
+impl BodyFormat {
+    /// Get the serialized name of the format (e.g. "markdown")
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BodyFormat::Plain => "plain",
+            BodyFormat::Markdown => "markdown",
+        }
+    }
+
+    /// Whether this is the default plain format
+    pub fn is_plain(&self) -> bool {
+        *self == BodyFormat::Plain
+    }
 }
 
 /// A statistic or metric to display
@@ -221,6 +361,10 @@ pub struct Stat {
     /// Optional color for the value
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub color: Option<String>,
+
+    /// Optional icon: a short emoji/symbol or a stored image ID (`img_...`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
 }
 
 impl Stat {
@@ -230,6 +374,7 @@ impl Stat {
             value: value.into(),
             label: label.into(),
             color: None,
+            icon: None,
         }
     }
 
@@ -238,6 +383,12 @@ impl Stat {
         self.color = Some(color.into());
         self
     }
+
+    /// Builder method to set the icon
+    pub fn with_icon(mut self, icon: impl Into<String>) -> Self {
+        self.icon = Some(icon.into());
+        self
+    }
 }
 
 /// Contact information for the document
@@ -258,6 +409,22 @@ pub struct ContactInfo {
     /// Physical address
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub address: Option<String>,
+
+    /// LinkedIn profile or handle
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub linkedin: Option<String>,
+
+    /// Twitter/X handle
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub twitter: Option<String>,
+
+    /// Instagram handle
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instagram: Option<String>,
+
+    /// Other social platforms, keyed by platform name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub social: BTreeMap<String, String>,
 }
 
 impl ContactInfo {
@@ -292,6 +459,14 @@ pub struct StyleHints {
     /// Font family name
     #[serde(default, skip_serializing_if = "Option::is_none", alias = "fontFamily")]
     pub font_family: Option<String>,
+
+    /// Background gradient as (start, stop) hex colors
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        alias = "backgroundGradient"
+    )]
+    pub background_gradient: Option<(String, String)>,
 }
 
 impl StyleHints {
@@ -310,3 +485,134 @@ impl StyleHints {
         self.font_family.as_deref().unwrap_or("Inter")
     }
 }
+
+/// Paper size for the document page
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PageSize {
+    /// US Letter, 8.5in x 11in
+    #[default]
+    Letter,
+    /// US Legal, 8.5in x 14in
+    Legal,
+    /// ISO A4, 210mm x 297mm
+    A4,
+}
+
+impl PageSize {
+    /// All sizes, in the order presets are offered
+    pub const ALL: [PageSize; 3] = [PageSize::Letter, PageSize::A4, PageSize::Legal];
+
+    /// Get the serialized name of the size (e.g. "a4")
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PageSize::Letter => "letter",
+            PageSize::Legal => "legal",
+            PageSize::A4 => "a4",
+        }
+    }
+
+    /// Portrait (width, height) as Typst lengths
+    pub fn dimensions(&self) -> (&'static str, &'static str) {
+        match self {
+            PageSize::Letter => ("8.5in", "11in"),
+            PageSize::Legal => ("8.5in", "14in"),
+            PageSize::A4 => ("210mm", "297mm"),
+        }
+    }
+
+    /// Display name (e.g. "A4")
+    pub fn label(&self) -> &'static str {
+        match self {
+            PageSize::Letter => "Letter",
+            PageSize::Legal => "Legal",
+            PageSize::A4 => "A4",
+        }
+    }
+}
+
+/// Page orientation
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Orientation {
+    /// Taller than wide
+    #[default]
+    Portrait,
+    /// Wider than tall
+    Landscape,
+}
+
+impl Orientation {
+    /// Both orientations, in the order presets are offered
+    pub const ALL: [Orientation; 2] = [Orientation::Portrait, Orientation::Landscape];
+
+    /// Get the serialized name of the orientation (e.g. "landscape")
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Orientation::Portrait => "portrait",
+            Orientation::Landscape => "landscape",
+        }
+    }
+
+    /// Display name (e.g. "Landscape")
+    pub fn label(&self) -> &'static str {
+        match self {
+            Orientation::Portrait => "Portrait",
+            Orientation::Landscape => "Landscape",
+        }
+    }
+}
+
+/// Page size, orientation and margin used by `#set page(...)`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct PageSetup {
+    /// Paper size
+    #[serde(default)]
+    pub size: PageSize,
+
+    /// Page orientation
+    #[serde(default)]
+    pub orientation: Orientation,
+
+    /// Page margin as a Typst length (e.g. "0.75in", "2cm"); templates pick one when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub margin: Option<String>,
+}
+
+impl PageSetup {
+    /// Create a page setup with the template's default margin
+    pub fn new(size: PageSize, orientation: Orientation) -> Self {
+        Self {
+            size,
+            orientation,
+            margin: None,
+        }
+    }
+
+    /// Builder method to set the margin
+    pub fn with_margin(mut self, margin: impl Into<String>) -> Self {
+        self.margin = Some(margin.into());
+        self
+    }
+
+    /// The margin, if it is a valid Typst length
+    pub fn margin_length(&self) -> Option<&str> {
+        self.margin
+            .as_deref()
+            .filter(|margin| super::validation::is_valid_length(margin))
+    }
+
+    /// (width, height) as Typst lengths, swapped for landscape
+    pub fn dimensions(&self) -> (&'static str, &'static str) {
+        let (width, height) = self.size.dimensions();
+        match self.orientation {
+            Orientation::Portrait => (width, height),
+            Orientation::Landscape => (height, width),
+        }
+    }
+
+    /// Short description such as "A4 Landscape"
+    pub fn label(&self) -> String {
+        format!("{} {}", self.size.label(), self.orientation.label())
+    }
+}
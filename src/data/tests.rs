@@ -1,5 +1,9 @@
 //! Tests for the data module
 
+use super::diff::*;
+use super::fields::*;
+use super::json_schema::json_schema;
+use super::markdown::markdown_to_typst;
 use super::schema::*;
 use super::validation::*;
 
@@ -76,6 +80,28 @@ fn test_stat_builder() {
     assert_eq!(stat.color, Some("#00ff00".to_string()));
 }
 
+#[test]
+fn test_stat_icon_round_trip() {
+    let stat = Stat::new("78%", "Growth").with_icon("\u{2197}");
+    let json = serde_json::to_string(&stat).expect("Should serialize");
+    assert!(json.contains("\"icon\":\"\u{2197}\""));
+    let deserialized: Stat = serde_json::from_str(&json).expect("Should deserialize");
+    assert_eq!(deserialized, stat);
+
+    let image = Stat::new("2.5M", "Users").with_icon("img_ab12cd.png");
+    let json = serde_json::to_string(&image).expect("Should serialize");
+    let deserialized: Stat = serde_json::from_str(&json).expect("Should deserialize");
+    assert_eq!(deserialized.icon.as_deref(), Some("img_ab12cd.png"));
+}
+
+#[test]
+fn test_stat_without_icon_omits_field() {
+    let json = serde_json::to_string(&Stat::new("1", "One")).expect("Should serialize");
+    assert!(!json.contains("icon"));
+    let stat: Stat = serde_json::from_str(r#"{"value": "1", "label": "One"}"#).unwrap();
+    assert_eq!(stat.icon, None);
+}
+
 #[test]
 fn test_style_hints_defaults() {
     let style = StyleHints::default();
@@ -91,6 +117,7 @@ fn test_style_hints_custom() {
         primary_color: Some("#ff0000".to_string()),
         accent_color: Some("#00ff00".to_string()),
         font_family: Some("Roboto".to_string()),
+        background_gradient: None,
     };
 
     assert_eq!(style.primary_color_or_default(), "#ff0000");
@@ -173,6 +200,7 @@ fn test_validation_multiple_errors() {
             value: "".to_string(),              // Error: empty value
             label: "".to_string(),              // Error: empty label
             color: Some("invalid".to_string()), // Error: invalid color
+            icon: None,
         }],
         ..Default::default()
     };
@@ -184,3 +212,595 @@ fn test_validation_multiple_errors() {
     // Should have multiple errors
     assert!(errors.len() >= 5);
 }
+
+// ============================================================================
+// Field Update Tests
+// ============================================================================
+
+fn field_test_data() -> SlickSheetData {
+    SlickSheetData::new("Title")
+        .with_subtitle("Subtitle")
+        .with_body("Body")
+        .with_feature("Fast")
+        .with_feature("Cheap")
+        .with_stat(Stat::new("99%", "Uptime"))
+        .with_section(Section::text("About", "We build things"))
+}
+
+#[test]
+fn test_update_data_field_title() {
+    let mut data = field_test_data();
+    update_data_field(&mut data, "title", "New Title").unwrap();
+    assert_eq!(data.title, "New Title");
+}
+
+#[test]
+fn test_update_data_field_subtitle() {
+    let mut data = field_test_data();
+    update_data_field(&mut data, "subtitle", "New Subtitle").unwrap();
+    assert_eq!(data.subtitle, Some("New Subtitle".to_string()));
+
+    // Clearing an optional field removes it
+    update_data_field(&mut data, "subtitle", "").unwrap();
+    assert!(data.subtitle.is_none());
+}
+
+#[test]
+fn test_update_data_field_body() {
+    let mut data = field_test_data();
+    update_data_field(&mut data, "body", "New body").unwrap();
+    assert_eq!(data.body, "New body");
+}
+
+#[test]
+fn test_update_data_field_feature() {
+    let mut data = field_test_data();
+    update_data_field(&mut data, "features/1", "Affordable").unwrap();
+    assert_eq!(data.features, vec!["Fast", "Affordable"]);
+}
+
+#[test]
+fn test_update_data_field_feature_out_of_range() {
+    let mut data = field_test_data();
+    let result = update_data_field(&mut data, "features/5", "Nope");
+    assert_eq!(
+        result,
+        Err(FieldError::IndexOutOfRange {
+            field: "features".to_string(),
+            index: 5,
+            len: 2,
+        })
+    );
+}
+
+#[test]
+fn test_update_data_field_stat() {
+    let mut data = field_test_data();
+    update_data_field(&mut data, "stats/0/value", "99.9%").unwrap();
+    update_data_field(&mut data, "stats/0/label", "Availability").unwrap();
+    assert_eq!(data.stats[0].value, "99.9%");
+    assert_eq!(data.stats[0].label, "Availability");
+}
+
+#[test]
+fn test_update_data_field_section() {
+    let mut data = field_test_data();
+    update_data_field(&mut data, "sections/0/heading", "Who We Are").unwrap();
+    assert_eq!(data.sections[0].heading, "Who We Are");
+}
+
+#[test]
+fn test_update_data_field_metadata() {
+    let mut data = field_test_data();
+    update_data_field(&mut data, "meta/author", "Jane").unwrap();
+    assert_eq!(data.metadata.get("author"), Some(&"Jane".to_string()));
+}
+
+#[test]
+fn test_update_data_field_contact() {
+    let mut data = field_test_data();
+    update_data_field(&mut data, "contact/email", "hi@example.com").unwrap();
+    assert_eq!(
+        data.contact.and_then(|c| c.email),
+        Some("hi@example.com".to_string())
+    );
+}
+
+#[test]
+fn test_update_data_field_unknown() {
+    let mut data = field_test_data();
+    assert_eq!(
+        update_data_field(&mut data, "stats/0/icon", "x"),
+        Err(FieldError::UnknownField("stats/0/icon".to_string()))
+    );
+    assert!(update_data_field(&mut data, "image", "x").is_err());
+    assert!(update_data_field(&mut data, "features/x", "x").is_err());
+}
+
+fn section_test_data() -> SlickSheetData {
+    SlickSheetData::new("Team")
+        .with_section(Section::text("Alice", "Founder"))
+        .with_section(Section::text("Bob", "Engineer"))
+        .with_section(Section::text("Carol", "Designer"))
+}
+
+fn headings(data: &SlickSheetData) -> Vec<&str> {
+    data.sections.iter().map(|s| s.heading.as_str()).collect()
+}
+
+#[test]
+fn test_duplicate_middle_section() {
+    let mut data = section_test_data();
+    data.duplicate_section(1).unwrap();
+    assert_eq!(headings(&data), vec!["Alice", "Bob", "Bob", "Carol"]);
+    assert_eq!(data.sections[1], data.sections[2]);
+}
+
+#[test]
+fn test_move_section_first_to_last() {
+    let mut data = section_test_data();
+    data.move_section(0, 2).unwrap();
+    assert_eq!(headings(&data), vec!["Bob", "Carol", "Alice"]);
+}
+
+#[test]
+fn test_remove_section() {
+    let mut data = section_test_data();
+    let removed = data.remove_section(1).unwrap();
+    assert_eq!(removed.heading, "Bob");
+    assert_eq!(headings(&data), vec!["Alice", "Carol"]);
+}
+
+#[test]
+fn test_section_operations_out_of_bounds() {
+    let mut data = section_test_data();
+    let out_of_range = FieldError::IndexOutOfRange {
+        field: "sections".to_string(),
+        index: 3,
+        len: 3,
+    };
+
+    assert_eq!(data.duplicate_section(3), Err(out_of_range.clone()));
+    assert_eq!(data.move_section(3, 0), Err(out_of_range.clone()));
+    assert_eq!(data.move_section(0, 3), Err(out_of_range.clone()));
+    assert_eq!(data.remove_section(3), Err(out_of_range));
+    assert_eq!(headings(&data), vec!["Alice", "Bob", "Carol"]);
+}
+
+#[test]
+fn test_get_data_field() {
+    let data = field_test_data();
+    assert_eq!(get_data_field(&data, "title"), Some("Title".to_string()));
+    assert_eq!(
+        get_data_field(&data, "features/1"),
+        Some("Cheap".to_string())
+    );
+    assert_eq!(
+        get_data_field(&data, "stats/0/label"),
+        Some("Uptime".to_string())
+    );
+    assert_eq!(get_data_field(&data, "features/9"), None);
+    assert_eq!(get_data_field(&data, "image"), None);
+}
+
+#[test]
+fn test_image_section_round_trip() {
+    let section = Section::image("Gallery", "img_abc123.png", Some("Our office".to_string()));
+    let json = serde_json::to_string(&section).expect("Should serialize");
+
+    assert!(json.contains(r#""type":"image""#));
+    assert!(json.contains(r#""imageRef":"img_abc123.png""#));
+    assert!(json.contains(r#""caption":"Our office""#));
+
+    let parsed: Section = serde_json::from_str(&json).expect("Should deserialize");
+    assert_eq!(parsed, section);
+    assert_eq!(parsed.section_type, SectionType::Image);
+}
+
+#[test]
+fn test_image_section_requires_ref() {
+    let data = SlickSheetData::new("Test").with_section(Section {
+        heading: "Photo".to_string(),
+        section_type: SectionType::Image,
+        ..Default::default()
+    });
+
+    let errors = validate_schema(&data).unwrap_err();
+    assert!(errors.contains(&ValidationError::MissingImageRef("Photo".to_string())));
+}
+
+#[test]
+fn test_validate_image_refs_warns_on_unknown() {
+    let data = SlickSheetData::new("Test")
+        .with_section(Section::image("Known", "img_1.png", None))
+        .with_section(Section::image("Missing", "img_2.png", None));
+
+    let warnings = validate_image_refs(&data, |image_ref| image_ref == "img_1.png");
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("img_2.png"));
+    assert!(validate_schema(&data).is_ok());
+}
+
+// ============================================================================
+// JSON Schema Tests
+// ============================================================================
+
+/// Minimal JSON Schema checker covering the keywords `json_schema` uses
+fn schema_errors(
+    root: &serde_json::Value,
+    schema: &serde_json::Value,
+    value: &serde_json::Value,
+    path: &str,
+) -> Vec<String> {
+    use serde_json::Value;
+
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        let target = root
+            .pointer(reference.trim_start_matches('#'))
+            .unwrap_or_else(|| panic!("Unresolved $ref {}", reference));
+        return schema_errors(root, target, value, path);
+    }
+
+    let mut errors = Vec::new();
+    let type_ok = match schema.get("type").and_then(Value::as_str) {
+        Some("object") => value.is_object(),
+        Some("array") => value.is_array(),
+        Some("string") => value.is_string(),
+        Some("integer") => value.is_u64() || value.is_i64(),
+        _ => true,
+    };
+    if !type_ok {
+        errors.push(format!("{}: wrong type", path));
+        return errors;
+    }
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        if !allowed.contains(value) {
+            errors.push(format!("{}: {} not in enum", path, value));
+        }
+    }
+    if let Some(min) = schema.get("minLength").and_then(Value::as_u64) {
+        if value.as_str().is_some_and(|s| (s.len() as u64) < min) {
+            errors.push(format!("{}: too short", path));
+        }
+    }
+    if let Some(object) = value.as_object() {
+        for required in schema
+            .get("required")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            if !object.contains_key(required.as_str().unwrap()) {
+                errors.push(format!("{}: missing {}", path, required));
+            }
+        }
+        for (key, field) in object {
+            let field_path = format!("{}/{}", path, key);
+            if let Some(property) = schema.pointer(&format!("/properties/{}", key)) {
+                errors.extend(schema_errors(root, property, field, &field_path));
+            } else if let Some(additional) = schema.get("additionalProperties") {
+                errors.extend(schema_errors(root, additional, field, &field_path));
+            } else {
+                errors.push(format!("{}: not described by schema", field_path));
+            }
+        }
+    }
+    if let (Some(items), Some(array)) = (schema.get("items"), value.as_array()) {
+        for (i, item) in array.iter().enumerate() {
+            errors.extend(schema_errors(root, items, item, &format!("{}/{}", path, i)));
+        }
+    }
+    errors
+}
+
+#[test]
+fn test_json_schema_key_properties() {
+    let schema = json_schema();
+
+    assert_eq!(schema["required"], serde_json::json!(["title"]));
+    for key in ["title", "subtitle", "sections", "stats", "contact", "style"] {
+        assert!(schema["properties"].get(key).is_some(), "missing {}", key);
+    }
+    assert_eq!(
+        schema["$defs"]["section"]["properties"]["type"]["enum"],
+        serde_json::json!(["text", "list", "table", "quote", "image"])
+    );
+    assert!(schema["$defs"]["contact"]["properties"]
+        .get("linkedin")
+        .is_some());
+}
+
+#[test]
+fn test_json_schema_accepts_known_good_data() {
+    let mut contact = ContactInfo::with_email("sales@example.com");
+    contact.twitter = Some("acme".to_string());
+    contact
+        .social
+        .insert("github".to_string(), "acme".to_string());
+
+    let data = SlickSheetData::new("Product Launch")
+        .with_subtitle("Now available")
+        .with_body("Body text")
+        .with_section(Section::text("Overview", "Intro"))
+        .with_section(Section::list("Features", vec!["Fast".to_string()]))
+        .with_section(Section::table(
+            "Specs",
+            vec![vec!["A".to_string(), "B".to_string()]],
+            2,
+        ))
+        .with_section(Section::quote("Praise", "Great"))
+        .with_section(Section::image(
+            "Photo",
+            "img_1.png",
+            Some("Caption".to_string()),
+        ))
+        .with_feature("Reliable")
+        .with_stat(Stat::new("99%", "Uptime"))
+        .with_contact(contact)
+        .with_style(StyleHints {
+            primary_color: Some("#112233".to_string()),
+            accent_color: Some("teal".to_string()),
+            font_family: Some("Inter".to_string()),
+            background_gradient: Some(("#112233".to_string(), "#445566".to_string())),
+        });
+    let mut value = serde_json::to_value(&data).unwrap();
+    value["metadata"]["author"] = serde_json::json!("Jane");
+    value["images"] = serde_json::json!({ "logo": "img_abc.png" });
+
+    let schema = json_schema();
+    let errors = schema_errors(&schema, &schema, &value, "");
+    assert!(errors.is_empty(), "Schema errors: {:?}", errors);
+}
+
+#[test]
+fn test_json_schema_rejects_bad_data() {
+    let schema = json_schema();
+    let value = serde_json::json!({
+        "subtitle": 5,
+        "sections": [{ "heading": "A", "type": "video" }]
+    });
+
+    let errors = schema_errors(&schema, &schema, &value, "");
+    assert!(errors.iter().any(|e| e.contains("missing \"title\"")));
+    assert!(errors.iter().any(|e| e.contains("/subtitle")));
+    assert!(errors.iter().any(|e| e.contains("not in enum")));
+}
+
+#[test]
+fn test_markdown_bold() {
+    assert_eq!(markdown_to_typst("**bold**"), "*bold*");
+    assert_eq!(
+        markdown_to_typst("some __strong__ text"),
+        "some *strong* text"
+    );
+}
+
+#[test]
+fn test_markdown_italic() {
+    assert_eq!(markdown_to_typst("*em* and _em_"), "_em_ and _em_");
+    // Intraword underscores are literal text
+    assert_eq!(markdown_to_typst("snake_case_name"), "snake\\_case\\_name");
+}
+
+#[test]
+fn test_markdown_headings() {
+    assert_eq!(markdown_to_typst("# Heading"), "= Heading");
+    assert_eq!(markdown_to_typst("### Sub **bold**"), "=== Sub *bold*");
+    assert_eq!(markdown_to_typst("#hashtag"), "\\#hashtag");
+}
+
+#[test]
+fn test_markdown_bulleted_list() {
+    let markdown = "Intro:\n- First\n* Second\n  - Nested\n+ Third";
+    assert_eq!(
+        markdown_to_typst(markdown),
+        "Intro:\n- First\n- Second\n  - Nested\n- Third"
+    );
+}
+
+#[test]
+fn test_markdown_numbered_list() {
+    assert_eq!(markdown_to_typst("1. One\n2) Two"), "+ One\n+ Two");
+}
+
+#[test]
+fn test_markdown_links_and_code() {
+    assert_eq!(
+        markdown_to_typst("See [the *docs*](https://example.com/a_b)"),
+        "See #link(\"https://example.com/a_b\")[the _docs_]"
+    );
+    assert_eq!(
+        markdown_to_typst("Run `cargo *test*`"),
+        "Run `cargo *test*`"
+    );
+}
+
+#[test]
+fn test_markdown_escapes_typst_markup() {
+    assert_eq!(
+        markdown_to_typst("Email me @ $5 <now> \\*literal\\*"),
+        "Email me \\@ \\$5 \\<now\\> \\*literal\\*"
+    );
+    assert_eq!(markdown_to_typst("= not a heading"), "\\= not a heading");
+    assert_eq!(markdown_to_typst("[unclosed"), "\\[unclosed");
+}
+
+#[test]
+fn test_body_format_serialization() {
+    let data = SlickSheetData::new("T");
+    let json = serde_json::to_string(&data).unwrap();
+    assert!(!json.contains("body_format"));
+
+    let data: SlickSheetData =
+        serde_json::from_str(r#"{"title": "T", "body_format": "markdown"}"#).unwrap();
+    assert_eq!(data.body_format, BodyFormat::Markdown);
+}
+
+fn diff_base() -> SlickSheetData {
+    SlickSheetData::new("Acme")
+        .with_subtitle("Widgets")
+        .with_body("We make widgets.")
+        .with_feature("Fast")
+        .with_feature("Cheap")
+        .with_stat(Stat::new("99%", "Uptime"))
+        .with_section(Section::text("About", "Founded 2020"))
+}
+
+#[test]
+fn test_diff_identical_is_empty() {
+    assert!(diff(&diff_base(), &diff_base()).is_empty());
+}
+
+#[test]
+fn test_diff_changed_text_fields() {
+    let mut new = diff_base();
+    new.title = "Acme Corp".to_string();
+    new.body = "We make better widgets.".to_string();
+
+    assert_eq!(
+        diff(&diff_base(), &new),
+        vec![
+            FieldDiff::Changed {
+                field: "title".to_string(),
+                old: "Acme".to_string(),
+                new: "Acme Corp".to_string(),
+            },
+            FieldDiff::Changed {
+                field: "body".to_string(),
+                old: "We make widgets.".to_string(),
+                new: "We make better widgets.".to_string(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_diff_added_and_removed_subtitle() {
+    let mut without = diff_base();
+    without.subtitle = None;
+
+    assert_eq!(
+        diff(&diff_base(), &without),
+        vec![FieldDiff::Removed {
+            field: "subtitle".to_string(),
+            value: "Widgets".to_string(),
+        }]
+    );
+    assert_eq!(
+        diff(&without, &diff_base()),
+        vec![FieldDiff::Added {
+            field: "subtitle".to_string(),
+            value: "Widgets".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn test_diff_features_added_and_removed() {
+    let mut new = diff_base();
+    new.features = vec!["Reliable".to_string(), "Fast".to_string()];
+
+    assert_eq!(
+        diff(&diff_base(), &new),
+        vec![
+            FieldDiff::Removed {
+                field: "features".to_string(),
+                value: "Cheap".to_string(),
+            },
+            FieldDiff::Added {
+                field: "features".to_string(),
+                value: "Reliable".to_string(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_diff_features_reorder_is_not_a_change() {
+    let mut new = diff_base();
+    new.features.reverse();
+    assert!(diff(&diff_base(), &new).is_empty());
+}
+
+#[test]
+fn test_diff_stat_changes() {
+    let mut new = diff_base();
+    new.stats[0].value = "99.9%".to_string();
+    new.stats.push(Stat::new("24/7", "Support"));
+
+    assert_eq!(
+        diff(&diff_base(), &new),
+        vec![
+            FieldDiff::Changed {
+                field: "stats/0/value".to_string(),
+                old: "99%".to_string(),
+                new: "99.9%".to_string(),
+            },
+            FieldDiff::Added {
+                field: "stats/1".to_string(),
+                value: "24/7 Support".to_string(),
+            },
+        ]
+    );
+
+    let mut fewer = diff_base();
+    fewer.stats.clear();
+    assert_eq!(
+        diff(&diff_base(), &fewer),
+        vec![FieldDiff::Removed {
+            field: "stats/0".to_string(),
+            value: "99% Uptime".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn test_diff_sections() {
+    let mut new = diff_base();
+    new.sections[0].content = "Founded 2019".to_string();
+    new.sections.push(Section::quote("Praise", "Great widgets"));
+
+    let diffs = diff(&diff_base(), &new);
+    assert_eq!(
+        diffs,
+        vec![
+            FieldDiff::Changed {
+                field: "sections/0".to_string(),
+                old: "About".to_string(),
+                new: "About (edited)".to_string(),
+            },
+            FieldDiff::Added {
+                field: "sections/1".to_string(),
+                value: "Praise".to_string(),
+            },
+        ]
+    );
+    assert_eq!(diffs[1].to_string(), "+ sections/1: Praise");
+}
+
+#[test]
+fn test_diff_remaining_fields() {
+    let mut new = diff_base();
+    new.contact = Some(ContactInfo {
+        email: Some("hi@acme.test".to_string()),
+        ..Default::default()
+    });
+    new.background_image = Some("img_bg.png".to_string());
+    new.images
+        .insert("logo".to_string(), "img_logo.png".to_string());
+    new.stats[0].color = Some("#00aa00".to_string());
+
+    let fields: Vec<String> = diff(&diff_base(), &new)
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    assert_eq!(
+        fields,
+        vec![
+            "+ stats/0/color: #00aa00",
+            r#"+ contact: {"email":"hi@acme.test"}"#,
+            "+ background_image: img_bg.png",
+            r#"+ images: {"logo":"img_logo.png"}"#,
+        ]
+    );
+}
@@ -1,6 +1,6 @@
 //! Validation logic for slick sheet data
 
-use super::schema::{SectionType, SlickSheetData};
+use super::schema::{ContactInfo, SectionType, SlickSheetData};
 use thiserror::Error;
 
 /// Validation error types
@@ -26,6 +26,10 @@ pub enum ValidationError {
     #[error("Table section '{0}' is missing column count")]
     MissingTableColumns(String),
 
+    /// Image section has no image reference
+    #[error("Image section '{0}' has no image reference")]
+    MissingImageRef(String),
+
     /// Stat has empty value
     #[error("Stat at index {0} has empty value")]
     EmptyStatValue(usize),
@@ -35,14 +39,81 @@ pub enum ValidationError {
     EmptyStatLabel(usize),
 
     /// Invalid color format
-    #[error("Invalid color format: '{0}' (expected hex color like #ffffff)")]
-    InvalidColorFormat(String),
+    #[error(
+        "Invalid color format for {field}: '{value}' (expected hex like #ffffff or a color name)"
+    )]
+    InvalidColor { field: String, value: String },
+
+    /// Text field is longer than the configured limit
+    #[error("{field} is {actual} characters long (maximum {max})")]
+    TooLong {
+        field: String,
+        max: usize,
+        actual: usize,
+    },
+
+    /// Field required by the validation config is missing or empty
+    #[error("{0} is required")]
+    MissingRequired(String),
+
+    /// Page margin is not a Typst length
+    #[error("Invalid page margin '{0}' (expected a length like 0.75in or 2cm)")]
+    InvalidMargin(String),
+}
+
+/// Extra layout rules for `validate_schema_with`
+///
+/// The default is permissive: no length limits and no extra required fields.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationConfig {
+    /// Maximum title length in characters
+    pub max_title_len: Option<usize>,
+    /// Maximum body length in characters
+    pub max_body_len: Option<usize>,
+    /// Require a non-empty subtitle
+    pub require_subtitle: bool,
+    /// Require at least one contact detail
+    pub require_contact: bool,
 }
 
+/// Accepted color names and the hex value templates receive for them
+///
+/// Templates wrap colors in `rgb("...")`, which only takes hex, so names are
+/// resolved to Typst's palette (with `grey` as an alias of `gray`).
+const COLOR_NAMES: &[(&str, &str)] = &[
+    ("black", "#000000"),
+    ("gray", "#aaaaaa"),
+    ("grey", "#aaaaaa"),
+    ("silver", "#dddddd"),
+    ("white", "#ffffff"),
+    ("navy", "#001f3f"),
+    ("blue", "#0074d9"),
+    ("aqua", "#7fdbff"),
+    ("teal", "#39cccc"),
+    ("eastern", "#239dad"),
+    ("purple", "#b10dc9"),
+    ("fuchsia", "#f012be"),
+    ("maroon", "#85144b"),
+    ("red", "#ff4136"),
+    ("orange", "#ff851b"),
+    ("yellow", "#ffdc00"),
+    ("olive", "#3d9970"),
+    ("green", "#2ecc40"),
+    ("lime", "#01ff70"),
+];
+
 /// Validate a SlickSheetData instance
 ///
 /// Returns a list of validation errors, empty if valid
 pub fn validate_schema(data: &SlickSheetData) -> Result<(), Vec<ValidationError>> {
+    validate_schema_with(data, &ValidationConfig::default())
+}
+
+/// Validate a SlickSheetData instance with extra length and required-field rules
+pub fn validate_schema_with(
+    data: &SlickSheetData,
+    config: &ValidationConfig,
+) -> Result<(), Vec<ValidationError>> {
     let mut errors = Vec::new();
 
     // Title is required
@@ -50,6 +121,21 @@ pub fn validate_schema(data: &SlickSheetData) -> Result<(), Vec<ValidationError>
         errors.push(ValidationError::EmptyTitle);
     }
 
+    check_length("title", &data.title, config.max_title_len, &mut errors);
+    check_length("body", &data.body, config.max_body_len, &mut errors);
+
+    if config.require_subtitle
+        && data
+            .subtitle
+            .as_ref()
+            .is_none_or(|subtitle| subtitle.trim().is_empty())
+    {
+        errors.push(ValidationError::MissingRequired("subtitle".to_string()));
+    }
+    if config.require_contact && !data.contact.as_ref().is_some_and(has_contact_details) {
+        errors.push(ValidationError::MissingRequired("contact".to_string()));
+    }
+
     // Validate sections
     for (i, section) in data.sections.iter().enumerate() {
         if section.heading.trim().is_empty() {
@@ -72,6 +158,15 @@ pub fn validate_schema(data: &SlickSheetData) -> Result<(), Vec<ValidationError>
                     ));
                 }
             }
+            SectionType::Image => {
+                if section
+                    .image_ref
+                    .as_ref()
+                    .is_none_or(|image_ref| image_ref.trim().is_empty())
+                {
+                    errors.push(ValidationError::MissingImageRef(section.heading.clone()));
+                }
+            }
             SectionType::Text | SectionType::Quote => {
                 // Text and quote sections don't have additional requirements
             }
@@ -86,24 +181,23 @@ pub fn validate_schema(data: &SlickSheetData) -> Result<(), Vec<ValidationError>
         if stat.label.trim().is_empty() {
             errors.push(ValidationError::EmptyStatLabel(i));
         }
-        if let Some(color) = &stat.color {
-            if !is_valid_hex_color(color) {
-                errors.push(ValidationError::InvalidColorFormat(color.clone()));
-            }
-        }
+        check_color(&format!("stats[{}].color", i), &stat.color, &mut errors);
     }
 
     // Validate style colors
     if let Some(style) = &data.style {
-        if let Some(color) = &style.primary_color {
-            if !is_valid_hex_color(color) {
-                errors.push(ValidationError::InvalidColorFormat(color.clone()));
-            }
+        check_color("style.primaryColor", &style.primary_color, &mut errors);
+        check_color("style.accentColor", &style.accent_color, &mut errors);
+        if let Some((start, stop)) = &style.background_gradient {
+            check_gradient_stop("style.gradientStart", start, &mut errors);
+            check_gradient_stop("style.gradientStop", stop, &mut errors);
         }
-        if let Some(color) = &style.accent_color {
-            if !is_valid_hex_color(color) {
-                errors.push(ValidationError::InvalidColorFormat(color.clone()));
-            }
+    }
+
+    // Validate page margin
+    if let Some(margin) = data.page.as_ref().and_then(|page| page.margin.as_deref()) {
+        if !is_valid_length(margin) {
+            errors.push(ValidationError::InvalidMargin(margin.to_string()));
         }
     }
 
@@ -114,14 +208,120 @@ pub fn validate_schema(data: &SlickSheetData) -> Result<(), Vec<ValidationError>
     }
 }
 
-/// Check if a string is a valid hex color (e.g., #ffffff or #fff)
+/// Check image section references against the known image IDs
+///
+/// `is_known` resolves an `imageRef` to whether the image exists (e.g. the
+/// project's image store). Returns warnings (not errors) for unknown
+/// references, since the image may be added after the data is written.
+pub fn validate_image_refs(data: &SlickSheetData, is_known: impl Fn(&str) -> bool) -> Vec<String> {
+    data.sections
+        .iter()
+        .filter(|section| section.section_type == SectionType::Image)
+        .filter_map(|section| section.image_ref.as_deref())
+        .filter(|image_ref| !image_ref.trim().is_empty() && !is_known(image_ref))
+        .map(|image_ref| {
+            format!(
+                "Unknown image '{}' referenced by an image section",
+                image_ref
+            )
+        })
+        .collect()
+}
+
+/// Record a `TooLong` error if `value` exceeds an optional character limit
+fn check_length(field: &str, value: &str, max: Option<usize>, errors: &mut Vec<ValidationError>) {
+    let Some(max) = max else {
+        return;
+    };
+    let actual = value.chars().count();
+    if actual > max {
+        errors.push(ValidationError::TooLong {
+            field: field.to_string(),
+            max,
+            actual,
+        });
+    }
+}
+
+/// Whether any contact field holds a non-empty value
+fn has_contact_details(contact: &ContactInfo) -> bool {
+    [
+        &contact.email,
+        &contact.phone,
+        &contact.website,
+        &contact.address,
+        &contact.linkedin,
+        &contact.twitter,
+        &contact.instagram,
+    ]
+    .into_iter()
+    .flatten()
+    .chain(contact.social.values())
+    .any(|value| !value.trim().is_empty())
+}
+
+/// Record an `InvalidColor` error if an optional color is set but invalid
+fn check_color(field: &str, color: &Option<String>, errors: &mut Vec<ValidationError>) {
+    if let Some(value) = color {
+        if !is_valid_color(value) {
+            errors.push(ValidationError::InvalidColor {
+                field: field.to_string(),
+                value: value.clone(),
+            });
+        }
+    }
+}
+
+/// Record an `InvalidColor` error for a gradient stop that is not a hex color
+///
+/// Templates interpolate stops into `rgb("...")`, which only accepts hex.
+fn check_gradient_stop(field: &str, color: &str, errors: &mut Vec<ValidationError>) {
+    if !is_valid_hex_color(color) {
+        errors.push(ValidationError::InvalidColor {
+            field: field.to_string(),
+            value: color.to_string(),
+        });
+    }
+}
+
+/// Check if a string is a hex color or a known color name
+pub fn is_valid_color(color: &str) -> bool {
+    is_valid_hex_color(color) || named_color_hex(color).is_some()
+}
+
+/// Resolve a known color name to hex, leaving other values unchanged
+pub fn color_to_hex(color: &str) -> String {
+    named_color_hex(color).unwrap_or(color).to_string()
+}
+
+/// The hex value of a known color name (case-insensitive)
+fn named_color_hex(color: &str) -> Option<&'static str> {
+    COLOR_NAMES
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(color))
+        .map(|&(_, hex)| hex)
+}
+
+/// Check if a string is an absolute Typst length such as `0.75in` or `2cm`
+pub fn is_valid_length(length: &str) -> bool {
+    let Some(number) = ["pt", "mm", "cm", "in"]
+        .iter()
+        .find_map(|unit| length.strip_suffix(unit))
+    else {
+        return false;
+    };
+    !number.is_empty()
+        && number.chars().all(|c| c.is_ascii_digit() || c == '.')
+        && number.parse::<f64>().is_ok()
+}
+
+/// Check if a string is a valid hex color (#RGB, #RRGGBB or #RRGGBBAA)
 fn is_valid_hex_color(color: &str) -> bool {
-    if !color.starts_with('#') {
+    let Some(hex_part) = color.strip_prefix('#') else {
         return false;
-    }
+    };
 
-    let hex_part = &color[1..];
-    let valid_length = hex_part.len() == 3 || hex_part.len() == 6;
+    let valid_length = matches!(hex_part.len(), 3 | 6 | 8);
     let all_hex = hex_part.chars().all(|c| c.is_ascii_hexdigit());
 
     valid_length && all_hex
@@ -130,7 +330,8 @@ fn is_valid_hex_color(color: &str) -> bool {
 #[cfg(test)]
 mod validation_tests {
     use super::*;
-    use crate::data::schema::{Section, Stat, StyleHints};
+    use crate::data::schema::{ContactInfo, Section, Stat, StyleHints};
+    use crate::data::schema::{Orientation, PageSetup, PageSize};
 
     #[test]
     fn test_valid_data() {
@@ -199,6 +400,7 @@ mod validation_tests {
                 value: "".to_string(),
                 label: "Test".to_string(),
                 color: None,
+                icon: None,
             }],
             ..Default::default()
         };
@@ -223,7 +425,83 @@ mod validation_tests {
         let result = validate_schema(&data);
         assert!(result.is_err());
         let errors = result.unwrap_err();
-        assert!(errors.contains(&ValidationError::InvalidColorFormat("invalid".to_string())));
+        assert!(errors.contains(&ValidationError::InvalidColor {
+            field: "style.primaryColor".to_string(),
+            value: "invalid".to_string(),
+        }));
+    }
+
+    #[test]
+    fn test_invalid_gradient_stop() {
+        let data = SlickSheetData {
+            title: "Test".to_string(),
+            style: Some(StyleHints {
+                background_gradient: Some(("#1a1a2e".to_string(), "#12345".to_string())),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let errors = validate_schema(&data).unwrap_err();
+        assert_eq!(
+            errors,
+            vec![ValidationError::InvalidColor {
+                field: "style.gradientStop".to_string(),
+                value: "#12345".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_invalid_stat_color_reports_field() {
+        let data = SlickSheetData {
+            title: "Test".to_string(),
+            stats: vec![
+                Stat::new("1", "One").with_color("#ff0000"),
+                Stat::new("2", "Two").with_color("#ff00"),
+            ],
+            ..Default::default()
+        };
+
+        let errors = validate_schema(&data).unwrap_err();
+        assert_eq!(
+            errors,
+            vec![ValidationError::InvalidColor {
+                field: "stats[1].color".to_string(),
+                value: "#ff00".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_accent_color_name_is_valid() {
+        let data = SlickSheetData {
+            title: "Test".to_string(),
+            style: Some(StyleHints {
+                accent_color: Some("Navy".to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        assert!(validate_schema(&data).is_ok());
+    }
+
+    #[test]
+    fn test_color_names() {
+        assert!(is_valid_color("red"));
+        assert!(is_valid_color("eastern"));
+        assert!(is_valid_color("GRAY"));
+        assert!(!is_valid_color("blurple"));
+        assert!(!is_valid_color("reddish"));
+    }
+
+    #[test]
+    fn test_color_names_resolve_to_hex() {
+        assert_eq!(color_to_hex("Navy"), "#001f3f");
+        assert_eq!(color_to_hex("grey"), color_to_hex("gray"));
+        assert_eq!(color_to_hex("#e94560"), "#e94560");
+        assert!(COLOR_NAMES.iter().all(|(_, hex)| is_valid_hex_color(hex)));
     }
 
     #[test]
@@ -233,6 +511,7 @@ mod validation_tests {
         assert!(is_valid_hex_color("#e94560"));
         assert!(is_valid_hex_color("#ABC"));
         assert!(is_valid_hex_color("#abcdef"));
+        assert!(is_valid_hex_color("#e9456080"));
     }
 
     #[test]
@@ -240,7 +519,97 @@ mod validation_tests {
         assert!(!is_valid_hex_color("fff"));
         assert!(!is_valid_hex_color("#ff"));
         assert!(!is_valid_hex_color("#fffffff"));
+        assert!(!is_valid_hex_color("#ff00"));
+        assert!(!is_valid_hex_color("#fffffffff"));
         assert!(!is_valid_hex_color("#gggggg"));
         assert!(!is_valid_hex_color(""));
     }
+
+    #[test]
+    fn test_config_rejects_long_title() {
+        let data = SlickSheetData::new("A title far too long to fit on one printed line");
+        let config = ValidationConfig {
+            max_title_len: Some(20),
+            ..Default::default()
+        };
+
+        let errors = validate_schema_with(&data, &config).unwrap_err();
+        assert_eq!(
+            errors,
+            vec![ValidationError::TooLong {
+                field: "title".to_string(),
+                max: 20,
+                actual: 47,
+            }]
+        );
+        // The default config has no length limit
+        assert!(validate_schema(&data).is_ok());
+    }
+
+    #[test]
+    fn test_config_requires_subtitle_and_contact() {
+        let data = SlickSheetData::new("Title");
+        let config = ValidationConfig {
+            require_subtitle: true,
+            require_contact: true,
+            ..Default::default()
+        };
+
+        let errors = validate_schema_with(&data, &config).unwrap_err();
+        assert_eq!(
+            errors,
+            vec![
+                ValidationError::MissingRequired("subtitle".to_string()),
+                ValidationError::MissingRequired("contact".to_string()),
+            ]
+        );
+
+        // An empty contact block does not count
+        let data = data
+            .with_subtitle("Subtitle")
+            .with_contact(ContactInfo::default());
+        assert_eq!(
+            validate_schema_with(&data, &config).unwrap_err(),
+            vec![ValidationError::MissingRequired("contact".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_config_passes_when_rules_met() {
+        let data = SlickSheetData::new("Short title")
+            .with_subtitle("Subtitle")
+            .with_body("Body")
+            .with_contact(ContactInfo::with_email("hello at example.com"));
+        let config = ValidationConfig {
+            max_title_len: Some(20),
+            max_body_len: Some(100),
+            require_subtitle: true,
+            require_contact: true,
+        };
+
+        assert!(validate_schema_with(&data, &config).is_ok());
+    }
+
+    #[test]
+    fn test_valid_lengths() {
+        assert!(is_valid_length("0.75in"));
+        assert!(is_valid_length("2cm"));
+        assert!(is_valid_length("12pt"));
+        assert!(!is_valid_length("in"));
+        assert!(!is_valid_length("2 cm"));
+        assert!(!is_valid_length("1em"));
+        assert!(!is_valid_length("1in); #x"));
+    }
+
+    #[test]
+    fn test_invalid_page_margin() {
+        let page = PageSetup::new(PageSize::A4, Orientation::Portrait).with_margin("wide");
+        let data = SlickSheetData::new("Test").with_page(page);
+
+        let errors = validate_schema(&data).unwrap_err();
+        assert_eq!(
+            errors,
+            vec![ValidationError::InvalidMargin("wide".to_string())]
+        );
+    }
 }
@@ -0,0 +1,45 @@
+//! Template variable autocompletion for the code editor
+//!
+//! Like the line-number helpers, carets here are textarea UTF-16 offsets.
+
+/// Block keywords that may precede a variable inside `{{`
+const BLOCK_PREFIXES: &[&str] = &["#if ", "#each "];
+
+/// The partially typed variable path ending at `caret`, if it directly
+/// follows an unclosed `{{`, `{{{`, `{{#if ` or `{{#each `
+pub fn variable_prefix(text: &str, caret: u32) -> Option<&str> {
+    let before = &text[..byte_offset(text, caret)];
+    let tag = &before[before.rfind("{{")? + 2..];
+    let tag = tag.strip_prefix('{').unwrap_or(tag);
+    let tag = BLOCK_PREFIXES
+        .iter()
+        .find_map(|prefix| tag.strip_prefix(prefix))
+        .unwrap_or(tag);
+    tag.chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '@'))
+        .then_some(tag)
+}
+
+/// Replace the partially typed path before `caret` with `path`
+///
+/// Returns the new text and the caret just after the inserted path.
+pub fn insert_completion(text: &str, caret: u32, path: &str) -> (String, u32) {
+    let end = byte_offset(text, caret);
+    let typed = variable_prefix(text, caret).map_or(0, str::len);
+    let start = end - typed;
+    let completed = format!("{}{}{}", &text[..start], path, &text[end..]);
+    let caret = text[..start].encode_utf16().count() + path.encode_utf16().count();
+    (completed, caret as u32)
+}
+
+/// Byte index of UTF-16 offset `caret`, clamped to the end of `text`
+fn byte_offset(text: &str, caret: u32) -> usize {
+    let mut units = 0;
+    for (index, c) in text.char_indices() {
+        if units >= caret as usize {
+            return index;
+        }
+        units += c.len_utf16();
+    }
+    text.len()
+}
@@ -3,10 +3,29 @@
 //! This component provides the AI chat interface for:
 //! - Sending prompts to the AI
 //! - Displaying chat history
-//! - Showing progress during AI processing
+//! - Showing progress during AI processing, including retry countdowns
+//! - Reviewing proposed content changes before they are applied
+//! - Queueing prompts while offline
+//! - Pinning an AI model to the current project
+
+use std::time::Duration;
 
 use leptos::*;
 
+use crate::ai::client::TokenUsage;
+use crate::data::FieldDiff;
+
+use super::settings_modal::AI_MODELS;
+use super::state::PendingPrompt;
+
+/// Display name for a model ID, falling back to the ID itself
+pub fn model_label(model: &str) -> &str {
+    AI_MODELS
+        .iter()
+        .find(|(id, _, _)| *id == model)
+        .map_or(model, |(_, name, _)| name)
+}
+
 /// Chat message types
 #[derive(Debug, Clone, PartialEq)]
 pub enum ChatMessageType {
@@ -27,6 +46,8 @@ pub struct ChatMessage {
     pub message_type: ChatMessageType,
     /// Message content
     pub content: String,
+    /// Token usage of the AI call that produced this message
+    pub usage: Option<TokenUsage>,
 }
 
 impl ChatMessage {
@@ -36,6 +57,7 @@ impl ChatMessage {
         Self {
             message_type: ChatMessageType::User,
             content: content.into(),
+            usage: None,
         }
     }
 
@@ -45,6 +67,7 @@ impl ChatMessage {
         Self {
             message_type: ChatMessageType::Assistant,
             content: content.into(),
+            usage: None,
         }
     }
 
@@ -54,15 +77,23 @@ impl ChatMessage {
         Self {
             message_type: ChatMessageType::System,
             content: content.into(),
+            usage: None,
         }
     }
 
+    /// Attach token usage from the AI call that produced this message
+    pub fn with_usage(mut self, usage: Option<TokenUsage>) -> Self {
+        self.usage = usage;
+        self
+    }
+
     /// Create an error message
     #[allow(dead_code)]
     pub fn error(content: impl Into<String>) -> Self {
         Self {
             message_type: ChatMessageType::Error,
             content: content.into(),
+            usage: None,
         }
     }
 }
@@ -80,6 +111,8 @@ pub enum AiProcessingState {
     /// Verifying output visually (planned for visual verification feature)
     #[allow(dead_code)]
     Verifying,
+    /// Waiting before the next attempt, with whole seconds remaining
+    Retrying { seconds: u32 },
     /// Processing complete
     Complete,
     /// Processing failed
@@ -88,21 +121,41 @@ pub enum AiProcessingState {
 
 impl AiProcessingState {
     /// Get display text for the state
-    pub fn display_text(&self) -> &str {
+    pub fn display_text(&self) -> String {
         match self {
-            Self::Ready => "Ready",
-            Self::Generating => "Generating code...",
-            Self::Compiling => "Compiling...",
-            Self::Verifying => "Verifying...",
-            Self::Complete => "Complete",
-            Self::Failed => "Failed",
+            Self::Ready => "Ready".to_string(),
+            Self::Generating => "Generating code...".to_string(),
+            Self::Compiling => "Compiling...".to_string(),
+            Self::Verifying => "Verifying...".to_string(),
+            Self::Retrying { seconds } => format!("Retrying in {}s...", seconds),
+            Self::Complete => "Complete".to_string(),
+            Self::Failed => "Failed".to_string(),
         }
     }
 
     /// Check if processing is active
     pub fn is_processing(&self) -> bool {
-        matches!(self, Self::Generating | Self::Compiling | Self::Verifying)
+        matches!(
+            self,
+            Self::Generating | Self::Compiling | Self::Verifying | Self::Retrying { .. }
+        )
+    }
+}
+
+/// Split a retry delay into one-second countdown steps
+///
+/// Each step is the whole seconds left to show and how long to wait before
+/// the next step; the waits add up to `delay`.
+pub fn retry_countdown(delay: Duration) -> Vec<(u32, Duration)> {
+    let mut steps = Vec::new();
+    let mut remaining = delay;
+    while !remaining.is_zero() {
+        let seconds = remaining.as_secs() as u32 + u32::from(remaining.subsec_nanos() > 0);
+        let wait = remaining.min(Duration::from_secs(1));
+        steps.push((seconds, wait));
+        remaining -= wait;
     }
+    steps
 }
 
 /// Chat panel component
@@ -124,6 +177,22 @@ pub fn ChatPanel(
     max_iterations: Signal<usize>,
     /// Callback when user sends a message
     on_send: Callback<String>,
+    /// Callback when user stops the in-flight request
+    on_stop: Callback<()>,
+    /// Field-level diff of AI changes awaiting review, if any
+    proposed_diff: Signal<Option<Vec<FieldDiff>>>,
+    /// Callback when the user applies the proposed changes
+    on_apply: Callback<()>,
+    /// Callback when the user discards the proposed changes
+    on_discard: Callback<()>,
+    /// Prompts queued while offline, oldest first
+    pending_prompts: Signal<Vec<PendingPrompt>>,
+    /// Callback when the user cancels a queued prompt
+    on_cancel_pending: Callback<u64>,
+    /// Model requests will use (project override or global setting)
+    model: Signal<String>,
+    /// Model pinned to the current project, if any
+    model_override: RwSignal<Option<String>>,
 ) -> impl IntoView {
     // Local state for the input
     let input_text = create_rw_signal(String::new());
@@ -157,6 +226,18 @@ pub fn ChatPanel(
         <div class="chat-panel" class:collapsed=move || collapsed.get()>
             <div class="chat-panel-header" on:click=toggle_collapse>
                 <span class="chat-panel-title">"AI Assistant"</span>
+                {move || (!collapsed.get()).then(|| view! {
+                    <span
+                        class="chat-panel-model"
+                        title=move || if model_override.with(Option::is_some) {
+                            "Pinned to this project"
+                        } else {
+                            "Global model from Settings"
+                        }
+                    >
+                        {move || model.with(|model| model_label(model).to_string())}
+                    </span>
+                })}
                 <button class="chat-panel-toggle">
                     {move || if collapsed.get() { "\u{25C0}" } else { "\u{25B6}" }}
                 </button>
@@ -164,12 +245,31 @@ pub fn ChatPanel(
 
             {move || (!collapsed.get()).then(|| view! {
                 <div class="chat-panel-body">
+                    <label class="chat-model-override">
+                        "Project model "
+                        <select on:change=move |ev| {
+                            let value = event_target_value(&ev);
+                            model_override.set((!value.is_empty()).then_some(value));
+                        }>
+                            <option value="" selected=move || model_override.with(Option::is_none)>
+                                "Use global setting"
+                            </option>
+                            {AI_MODELS.iter().map(|(id, name, _)| view! {
+                                <option
+                                    value=*id
+                                    selected=move || model_override.with(|m| m.as_deref() == Some(*id))
+                                >
+                                    {*name}
+                                </option>
+                            }).collect_view()}
+                        </select>
+                    </label>
                     // Status banner
                     {move || {
                         if !is_online.get() {
                             Some(view! {
                                 <div class="chat-status-banner chat-status-offline">
-                                    "AI unavailable offline"
+                                    "Offline - requests will send when reconnected"
                                 </div>
                             })
                         } else if !has_api_key.get() {
@@ -187,7 +287,7 @@ pub fn ChatPanel(
                     {move || {
                         let state = processing_state.get();
                         if state.is_processing() {
-                            let display_text = state.display_text().to_string();
+                            let display_text = state.display_text();
                             Some(view! {
                                 <div class="chat-progress">
                                     <div class="chat-progress-text">
@@ -196,6 +296,12 @@ pub fn ChatPanel(
                                     <div class="chat-progress-iteration">
                                         {format!("Iteration {}/{}", current_iteration.get(), max_iterations.get())}
                                     </div>
+                                    <button
+                                        class="chat-stop-btn"
+                                        on:click=move |_| on_stop.call(())
+                                    >
+                                        "Stop"
+                                    </button>
                                 </div>
                             })
                         } else {
@@ -213,21 +319,81 @@ pub fn ChatPanel(
                                 ChatMessageType::Error => "chat-message chat-message-error",
                             };
                             let content = msg.content.clone();
+                            let usage_note = msg.usage.map(|usage| {
+                                let text = match usage.cost {
+                                    Some(cost) => format!("{} tokens (${:.4})", usage.total, cost),
+                                    None => format!("{} tokens", usage.total),
+                                };
+                                view! { <div class="chat-message-usage">{text}</div> }
+                            });
                             view! {
                                 <div class=class_name>
                                     {content}
+                                    {usage_note}
                                 </div>
                             }
                         }).collect::<Vec<_>>()}
+                        {move || pending_prompts.get().into_iter().map(|pending| {
+                            let id = pending.id;
+                            view! {
+                                <div class="chat-message chat-message-system chat-message-pending">
+                                    {format!("Queued: {}", pending.prompt)}
+                                    <button
+                                        class="chat-pending-cancel"
+                                        on:click=move |_| on_cancel_pending.call(id)
+                                    >
+                                        "Cancel"
+                                    </button>
+                                </div>
+                            }
+                        }).collect_view()}
                     </div>
 
+                    // Proposed changes awaiting review
+                    {move || proposed_diff.get().map(|diffs| {
+                        let rows = if diffs.is_empty() {
+                            view! {
+                                <div class="chat-diff-empty">"No content field changes"</div>
+                            }.into_view()
+                        } else {
+                            diffs.into_iter().map(|diff| {
+                                let class_name = match diff {
+                                    FieldDiff::Added { .. } => "chat-diff-row chat-diff-added",
+                                    FieldDiff::Removed { .. } => "chat-diff-row chat-diff-removed",
+                                    FieldDiff::Changed { .. } => "chat-diff-row chat-diff-changed",
+                                };
+                                view! { <div class=class_name>{diff.to_string()}</div> }
+                            }).collect_view()
+                        };
+                        view! {
+                            <div class="chat-diff">
+                                <div class="chat-diff-title">"Proposed changes"</div>
+                                <div class="chat-diff-rows">{rows}</div>
+                                <div class="chat-diff-actions">
+                                    <button
+                                        class="btn btn-primary"
+                                        on:click=move |_| on_apply.call(())
+                                    >
+                                        "Apply"
+                                    </button>
+                                    <button
+                                        class="btn btn-secondary"
+                                        on:click=move |_| on_discard.call(())
+                                    >
+                                        "Discard"
+                                    </button>
+                                </div>
+                            </div>
+                        }
+                    })}
+
                     // Input area
                     <div class="chat-input-area">
                         <textarea
                             class="chat-input"
                             placeholder=move || {
                                 if !is_online.get() {
-                                    "AI unavailable offline..."
+                                    "Offline - your request will be queued..."
                                 } else if !has_api_key.get() {
                                     "Configure API key first..."
                                 } else if processing_state.get().is_processing() {
@@ -242,7 +408,7 @@ pub fn ChatPanel(
                             }
                             on:keydown=on_keydown
                             disabled=move || {
-                                !is_online.get() || !has_api_key.get() || processing_state.get().is_processing()
+                                !has_api_key.get() || processing_state.get().is_processing()
                             }
                         />
                         <button
@@ -251,7 +417,6 @@ pub fn ChatPanel(
                             disabled=move || {
                                 let text = input_text.get();
                                 text.trim().is_empty()
-                                    || !is_online.get()
                                     || !has_api_key.get()
                                     || processing_state.get().is_processing()
                             }
@@ -275,6 +440,52 @@ pub fn ChatPanel(
                 transition: width 0.2s, min-width 0.2s;
             }
 
+            .chat-diff {
+                margin: 0 0.75rem 0.75rem;
+                padding: 0.5rem;
+                border: 1px solid var(--accent);
+                border-radius: 6px;
+                background: var(--bg-tertiary);
+                font-size: 0.75rem;
+            }
+
+            .chat-diff-title {
+                font-weight: 600;
+                margin-bottom: 0.375rem;
+            }
+
+            .chat-diff-rows {
+                display: flex;
+                flex-direction: column;
+                gap: 0.125rem;
+                max-height: 12rem;
+                overflow-y: auto;
+                font-family: monospace;
+                word-break: break-word;
+            }
+
+            .chat-diff-added {
+                color: var(--accent);
+            }
+
+            .chat-diff-removed {
+                color: var(--error);
+            }
+
+            .chat-diff-changed {
+                color: var(--text-primary);
+            }
+
+            .chat-diff-empty {
+                color: var(--text-secondary);
+            }
+
+            .chat-diff-actions {
+                display: flex;
+                gap: 0.5rem;
+                margin-top: 0.5rem;
+            }
+
             .chat-panel.collapsed {
                 width: 40px;
                 min-width: 40px;
@@ -301,6 +512,32 @@ pub fn ChatPanel(
                 font-weight: 600;
             }
 
+            .chat-panel-model {
+                margin-left: auto;
+                margin-right: 0.5rem;
+                font-size: 0.75rem;
+                color: var(--text-secondary);
+            }
+
+            .chat-model-override {
+                display: flex;
+                align-items: center;
+                justify-content: space-between;
+                gap: 0.5rem;
+                padding: 0.375rem 0.75rem;
+                border-bottom: 1px solid var(--border);
+                font-size: 0.75rem;
+                color: var(--text-secondary);
+            }
+
+            .chat-model-override select {
+                background: var(--bg-tertiary);
+                color: var(--text-primary);
+                border: 1px solid var(--border);
+                border-radius: 4px;
+                font-size: 0.75rem;
+            }
+
             .chat-panel-toggle {
                 background: none;
                 border: none;
@@ -355,6 +592,18 @@ pub fn ChatPanel(
                 color: var(--text-secondary);
             }
 
+            .chat-stop-btn {
+                margin-top: 0.5rem;
+                padding: 0.25rem 0.75rem;
+                background: var(--error);
+                color: white;
+                border: none;
+                border-radius: 4px;
+                cursor: pointer;
+                font-size: 0.75rem;
+                font-weight: 500;
+            }
+
             .chat-history {
                 flex: 1;
                 overflow-y: auto;
@@ -385,6 +634,12 @@ pub fn ChatPanel(
                 align-self: flex-start;
             }
 
+            .chat-message-usage {
+                margin-top: 0.25rem;
+                font-size: 0.65rem;
+                color: var(--text-secondary);
+            }
+
             .chat-message-system {
                 background: transparent;
                 color: var(--text-secondary);
@@ -394,6 +649,22 @@ pub fn ChatPanel(
                 text-align: center;
             }
 
+            .chat-message-pending {
+                display: flex;
+                align-items: center;
+                gap: 0.5rem;
+            }
+
+            .chat-pending-cancel {
+                background: none;
+                border: none;
+                color: var(--error);
+                cursor: pointer;
+                padding: 0;
+                font-size: 0.7rem;
+                font-style: normal;
+            }
+
             .chat-message-error {
                 background: var(--error);
                 color: white;
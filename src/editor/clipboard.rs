@@ -0,0 +1,74 @@
+//! Copying the rendered page to the clipboard as a PNG image
+//!
+//! Image writes use the async Clipboard API (`ClipboardItem`), which some
+//! browsers lack or only allow for text; those report a fallback message.
+
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+
+/// MIME type of clipboard image writes
+pub const PNG_MIME: &str = "image/png";
+
+/// Resolution multiplier when rasterizing the page for sharing
+pub const COPY_SCALE: f32 = 2.0;
+
+/// Signature every PNG file starts with
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+/// Message shown when the browser can't put images on the clipboard
+pub const UNSUPPORTED_MESSAGE: &str =
+    "This browser can't copy images to the clipboard. Use Export PDF or Export HTML instead.";
+
+/// Check PNG bytes and pair them with the MIME type for a clipboard blob
+pub fn png_blob_parts(bytes: &[u8]) -> Result<(&'static str, &[u8]), String> {
+    if bytes.starts_with(&PNG_SIGNATURE) {
+        Ok((PNG_MIME, bytes))
+    } else {
+        Err("Rendered image is not a PNG".to_string())
+    }
+}
+
+/// Wrap PNG bytes in an `image/png` blob
+pub fn png_blob(bytes: &[u8]) -> Result<web_sys::Blob, String> {
+    let (mime, bytes) = png_blob_parts(bytes)?;
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(bytes));
+    let options = web_sys::BlobPropertyBag::new();
+    options.set_type(mime);
+    web_sys::Blob::new_with_u8_array_sequence_and_options(&parts, &options)
+        .map_err(|_| "Failed to create image blob".to_string())
+}
+
+/// Write PNG bytes to the system clipboard
+pub async fn copy_png_to_clipboard(bytes: &[u8]) -> Result<(), String> {
+    let window = web_sys::window().ok_or("No browser window")?;
+    let supported = js_sys::Reflect::has(&window, &JsValue::from_str("ClipboardItem"))
+        .unwrap_or(false)
+        && js_sys::Reflect::get(&window.navigator(), &JsValue::from_str("clipboard"))
+            .is_ok_and(|clipboard| !clipboard.is_undefined());
+    if !supported {
+        return Err(UNSUPPORTED_MESSAGE.to_string());
+    }
+
+    let blob = png_blob(bytes)?;
+    let record = js_sys::Object::new();
+    js_sys::Reflect::set(&record, &JsValue::from_str(PNG_MIME), &blob)
+        .map_err(|_| "Failed to prepare clipboard item".to_string())?;
+    let item = web_sys::ClipboardItem::new_with_record_from_str_to_blob_promise(&record)
+        .map_err(|_| UNSUPPORTED_MESSAGE.to_string())?;
+
+    let items = js_sys::Array::of1(item.unchecked_ref());
+    JsFuture::from(window.navigator().clipboard().write(&items))
+        .await
+        .map(|_| ())
+        .map_err(|e| {
+            let name = e
+                .dyn_ref::<web_sys::DomException>()
+                .map(|exception| exception.name())
+                .unwrap_or_default();
+            if name == "NotAllowedError" {
+                "Clipboard access was denied by the browser".to_string()
+            } else {
+                UNSUPPORTED_MESSAGE.to_string()
+            }
+        })
+}
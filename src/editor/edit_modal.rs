@@ -241,6 +241,24 @@ pub fn get_field_label(field_id: &str) -> String {
             let key = s.strip_prefix("meta/").unwrap_or(s);
             format!("Metadata: {}", key)
         }
-        other => other.to_string(),
+        other => array_item_label(other).unwrap_or_else(|| other.to_string()),
     }
 }
+
+/// Build a label for an array element field ID (e.g. "stats/0/value" -> "Stat 1 value")
+fn array_item_label(field_id: &str) -> Option<String> {
+    let mut parts = field_id.split('/');
+    let field = parts.next()?;
+    let index: usize = parts.next()?.parse().ok()?;
+    let name = field.strip_suffix('s').unwrap_or(field);
+    let mut name_chars = name.chars();
+    let name = match name_chars.next() {
+        Some(first) => first.to_uppercase().chain(name_chars).collect::<String>(),
+        None => return None,
+    };
+
+    Some(match parts.next() {
+        Some(subfield) => format!("{} {} {}", name, index + 1, subfield),
+        None => format!("{} {}", name, index + 1),
+    })
+}
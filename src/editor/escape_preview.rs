@@ -0,0 +1,104 @@
+//! Escape preview panel
+//!
+//! A read-only view of the Typst the template engine produces from the
+//! current template and content. Backslash escapes are highlighted so it is
+//! clear why text such as `C#` appears as `C\#` in the rendered source.
+
+use leptos::*;
+
+use crate::data::SlickSheetData;
+use crate::template::{annotate_escapes, TemplateEngine};
+
+/// Rendered Typst with escape sequences highlighted
+#[component]
+pub fn EscapePreview(
+    /// Template being rendered
+    template_source: RwSignal<String>,
+    /// Content data bound into the template
+    content_data: RwSignal<SlickSheetData>,
+) -> impl IntoView {
+    let rendered = move || {
+        let template = template_source.get();
+        content_data.with(|data| TemplateEngine::render(&template, data))
+    };
+
+    view! {
+        <div class="escape-preview">
+            <div class="escape-preview-header">
+                "Rendered Typst"
+                <span class="escape-preview-hint">"Highlighted: backslash escapes"</span>
+            </div>
+            {move || match rendered() {
+                Ok(source) => view! {
+                    <pre class="escape-preview-source">
+                        {annotate_escapes(&source)
+                            .into_iter()
+                            .map(|span| {
+                                if span.escaped {
+                                    view! { <mark class="escape-mark">{span.text}</mark> }
+                                        .into_view()
+                                } else {
+                                    span.text.into_view()
+                                }
+                            })
+                            .collect_view()}
+                    </pre>
+                }
+                .into_view(),
+                Err(errors) => view! {
+                    <pre class="escape-preview-source escape-preview-error">
+                        {errors.join("\n")}
+                    </pre>
+                }
+                .into_view(),
+            }}
+        </div>
+
+        <style>
+            r#"
+            .escape-preview {
+                display: flex;
+                flex-direction: column;
+                max-height: 40%;
+                border-top: 1px solid var(--border);
+            }
+
+            .escape-preview-header {
+                display: flex;
+                justify-content: space-between;
+                padding: 0.375rem 0.5rem;
+                font-size: 0.75rem;
+                font-weight: 600;
+                color: var(--text-secondary);
+            }
+
+            .escape-preview-hint {
+                font-weight: 400;
+            }
+
+            .escape-preview-source {
+                flex: 1;
+                margin: 0;
+                padding: 0.5rem;
+                overflow: auto;
+                font-family: 'JetBrains Mono', monospace;
+                font-size: 0.75rem;
+                line-height: 1.5;
+                white-space: pre-wrap;
+                color: var(--text-primary);
+                background: var(--bg-primary);
+            }
+
+            .escape-preview-error {
+                color: var(--error);
+            }
+
+            .escape-mark {
+                background: var(--accent);
+                color: white;
+                border-radius: 2px;
+            }
+            "#
+        </style>
+    }
+}
@@ -0,0 +1,194 @@
+//! Structured editor for the `features` list in the Content tab
+//!
+//! Rows are keyed by index, so typing into a row only updates its value and
+//! the input keeps focus while the document re-renders.
+
+use leptos::*;
+
+use crate::data::SlickSheetData;
+
+/// Append an empty feature
+pub fn add_feature(features: &mut Vec<String>) {
+    features.push(String::new());
+}
+
+/// Remove the feature at `index`, if it exists
+pub fn remove_feature(features: &mut Vec<String>, index: usize) {
+    if index < features.len() {
+        features.remove(index);
+    }
+}
+
+/// Move the feature at `from` to position `to`, returning whether it moved
+pub fn move_feature(features: &mut [String], from: usize, to: usize) -> bool {
+    if from == to || from >= features.len() || to >= features.len() {
+        return false;
+    }
+    if from < to {
+        features[from..=to].rotate_left(1);
+    } else {
+        features[to..=from].rotate_right(1);
+    }
+    true
+}
+
+/// Apply `apply` to a copy of the features and report the updated content
+fn edit_features(
+    content_data: RwSignal<SlickSheetData>,
+    on_change: Callback<SlickSheetData>,
+    apply: impl FnOnce(&mut Vec<String>),
+) {
+    let mut data = content_data.get_untracked();
+    apply(&mut data.features);
+    on_change.call(data);
+}
+
+/// List of feature inputs with add, remove and reorder buttons
+#[component]
+pub fn FeaturesEditor(
+    /// Document content whose `features` are edited
+    content_data: RwSignal<SlickSheetData>,
+    /// Called with the full updated content after each edit
+    on_change: Callback<SlickSheetData>,
+) -> impl IntoView {
+    let count = create_memo(move |_| content_data.with(|data| data.features.len()));
+
+    view! {
+        <div class="features-editor">
+            <div class="features-header">
+                <span class="features-title">"Features"</span>
+                <button class="features-btn" on:click=move |_| edit_features(content_data, on_change, add_feature)>
+                    "+ Add"
+                </button>
+            </div>
+            <For
+                each=move || 0..count.get()
+                key=|index| *index
+                children=move |index| {
+                    let value = move || {
+                        content_data.with(|data| data.features.get(index).cloned().unwrap_or_default())
+                    };
+                    view! {
+                        <div class="feature-row">
+                            <input
+                                type="text"
+                                class="feature-input"
+                                placeholder="Feature"
+                                prop:value=value
+                                on:input=move |ev| {
+                                    let text = event_target_value(&ev);
+                                    edit_features(content_data, on_change, move |features| {
+                                        if let Some(feature) = features.get_mut(index) {
+                                            *feature = text;
+                                        }
+                                    });
+                                }
+                            />
+                            <button
+                                class="features-btn"
+                                title="Move up"
+                                disabled=move || index == 0
+                                on:click=move |_| {
+                                    edit_features(content_data, on_change, move |features| {
+                                        move_feature(features, index, index.saturating_sub(1));
+                                    })
+                                }
+                            >
+                                "\u{2191}"
+                            </button>
+                            <button
+                                class="features-btn"
+                                title="Move down"
+                                disabled=move || index + 1 >= count.get()
+                                on:click=move |_| {
+                                    edit_features(content_data, on_change, move |features| {
+                                        move_feature(features, index, index + 1);
+                                    })
+                                }
+                            >
+                                "\u{2193}"
+                            </button>
+                            <button
+                                class="features-btn"
+                                title="Remove"
+                                on:click=move |_| {
+                                    edit_features(content_data, on_change, |features| remove_feature(features, index))
+                                }
+                            >
+                                "\u{00D7}"
+                            </button>
+                        </div>
+                    }
+                }
+            />
+            {move || (count.get() == 0).then(|| view! {
+                <p class="features-empty">"No features yet. Add one to list it on the sheet."</p>
+            })}
+        </div>
+
+        <style>
+            r#"
+            .features-editor {
+                display: flex;
+                flex-direction: column;
+                gap: 0.375rem;
+                padding: 0.75rem;
+                overflow-y: auto;
+            }
+
+            .features-header {
+                display: flex;
+                justify-content: space-between;
+                align-items: center;
+                margin-bottom: 0.25rem;
+            }
+
+            .features-title {
+                font-size: 0.875rem;
+                font-weight: 600;
+            }
+
+            .feature-row {
+                display: flex;
+                gap: 0.25rem;
+            }
+
+            .feature-input {
+                flex: 1;
+                min-width: 0;
+                padding: 0.375rem 0.5rem;
+                background: var(--bg-primary);
+                border: 1px solid var(--border);
+                border-radius: 4px;
+                color: var(--text-primary);
+                font-size: 0.875rem;
+            }
+
+            .feature-input:focus {
+                outline: none;
+                border-color: var(--accent);
+            }
+
+            .features-btn {
+                padding: 0.25rem 0.5rem;
+                background: var(--bg-secondary);
+                border: 1px solid var(--border);
+                border-radius: 4px;
+                color: var(--text-primary);
+                cursor: pointer;
+                font-size: 0.75rem;
+            }
+
+            .features-btn:disabled {
+                opacity: 0.5;
+                cursor: not-allowed;
+            }
+
+            .features-empty {
+                color: var(--text-secondary);
+                font-size: 0.8rem;
+            }
+            "#
+        </style>
+    }
+}
@@ -0,0 +1,355 @@
+//! Find-and-replace bar for the code editors
+//!
+//! Matches are computed on the Rust string (byte offsets) and shown by
+//! moving the textarea selection, which the DOM addresses in UTF-16 units.
+
+use std::ops::Range;
+
+use leptos::*;
+
+/// Search toggles
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SearchOptions {
+    /// Match letter case exactly
+    pub case_sensitive: bool,
+    /// Only match where the query is not part of a longer word
+    pub whole_word: bool,
+}
+
+/// Byte ranges of non-overlapping matches of `query` in `text`, in order
+pub fn find_matches(text: &str, query: &str, options: SearchOptions) -> Vec<Range<usize>> {
+    let mut matches = Vec::new();
+    if query.is_empty() {
+        return matches;
+    }
+
+    let mut start = 0;
+    while start < text.len() {
+        match match_len_at(&text[start..], query, options.case_sensitive) {
+            Some(len) if !options.whole_word || is_whole_word(text, start..start + len) => {
+                matches.push(start..start + len);
+                start += len;
+            }
+            _ => start += text[start..].chars().next().map_or(1, char::len_utf8),
+        }
+    }
+    matches
+}
+
+/// Replace every match, returning the new text and the number replaced
+pub fn replace_all(
+    text: &str,
+    query: &str,
+    replacement: &str,
+    options: SearchOptions,
+) -> (String, usize) {
+    let matches = find_matches(text, query, options);
+    let mut result = String::with_capacity(text.len());
+    let mut last = 0;
+    for range in &matches {
+        result.push_str(&text[last..range.start]);
+        result.push_str(replacement);
+        last = range.end;
+    }
+    result.push_str(&text[last..]);
+    (result, matches.len())
+}
+
+/// Convert a byte offset in `text` to the UTF-16 offset a textarea uses
+pub fn utf16_offset(text: &str, byte_offset: usize) -> u32 {
+    text[..byte_offset].encode_utf16().count() as u32
+}
+
+/// Length in bytes of `query` matched at the start of `haystack`, if it matches
+fn match_len_at(haystack: &str, query: &str, case_sensitive: bool) -> Option<usize> {
+    if case_sensitive {
+        return haystack.starts_with(query).then_some(query.len());
+    }
+
+    let mut chars = haystack.char_indices();
+    for expected in query.chars() {
+        let (_, actual) = chars.next()?;
+        if !actual.to_lowercase().eq(expected.to_lowercase()) {
+            return None;
+        }
+    }
+    Some(chars.next().map_or(haystack.len(), |(index, _)| index))
+}
+
+/// Whether `range` is not directly preceded or followed by a word character
+fn is_whole_word(text: &str, range: Range<usize>) -> bool {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let before = text[..range.start].chars().next_back();
+    let after = text[range.end..].chars().next();
+    !before.is_some_and(is_word) && !after.is_some_and(is_word)
+}
+
+/// Whether a keydown is the Ctrl+F / Cmd+F shortcut
+pub fn is_find_shortcut(ev: &web_sys::KeyboardEvent) -> bool {
+    (ev.ctrl_key() || ev.meta_key()) && ev.key().eq_ignore_ascii_case("f")
+}
+
+/// Find-and-replace bar shown above a code editor textarea
+#[component]
+pub fn FindReplaceBar(
+    /// Text being searched
+    source: RwSignal<String>,
+    /// Textarea whose selection follows the current match
+    textarea: NodeRef<html::Textarea>,
+    /// Called with the full new text after a replacement
+    on_replace: Callback<String>,
+    /// Whether the bar is shown
+    open: RwSignal<bool>,
+) -> impl IntoView {
+    let query = create_rw_signal(String::new());
+    let replacement = create_rw_signal(String::new());
+    let case_sensitive = create_rw_signal(false);
+    let whole_word = create_rw_signal(false);
+    let current = create_rw_signal(0_usize);
+    let query_ref = create_node_ref::<html::Input>();
+
+    let matches = create_memo(move |_| {
+        let options = SearchOptions {
+            case_sensitive: case_sensitive.get(),
+            whole_word: whole_word.get(),
+        };
+        source.with(|text| query.with(|q| find_matches(text, q, options)))
+    });
+
+    // Focus the search field whenever the bar opens
+    create_effect(move |_| {
+        if let Some(input) = query_ref.get() {
+            let _ = input.focus();
+            input.select();
+        }
+    });
+
+    // Select match `index` in the textarea, scrolling it into view
+    let select_match = move |index: usize| {
+        let found = matches.get_untracked();
+        if found.is_empty() {
+            return;
+        }
+        let index = index % found.len();
+        current.set(index);
+        let range = found[index].clone();
+        if let Some(area) = textarea.get_untracked() {
+            let (start, end) = source.with_untracked(|text| {
+                (
+                    utf16_offset(text, range.start),
+                    utf16_offset(text, range.end),
+                )
+            });
+            // Focusing scrolls the selection into view; then hand focus back
+            let _ = area.focus();
+            let _ = area.set_selection_range(start, end);
+            if let Some(input) = query_ref.get_untracked() {
+                let _ = input.focus();
+            }
+        }
+    };
+
+    let step = move |forward: bool| {
+        let count = matches.with_untracked(Vec::len);
+        if count == 0 {
+            return;
+        }
+        let index = current.get_untracked().min(count - 1);
+        select_match(if forward {
+            index + 1
+        } else {
+            index + count - 1
+        });
+    };
+
+    let options = move || SearchOptions {
+        case_sensitive: case_sensitive.get_untracked(),
+        whole_word: whole_word.get_untracked(),
+    };
+
+    let replace_current = move |_| {
+        let found = matches.get_untracked();
+        let Some(range) = found.get(current.get_untracked().min(found.len().saturating_sub(1)))
+        else {
+            return;
+        };
+        let mut text = source.get_untracked();
+        text.replace_range(range.clone(), &replacement.get_untracked());
+        on_replace.call(text);
+        // The next match now sits at the same index
+        select_match(current.get_untracked());
+    };
+
+    let replace_every = move |_| {
+        let text = source.get_untracked();
+        let (replaced, count) = replace_all(
+            &text,
+            &query.get_untracked(),
+            &replacement.get_untracked(),
+            options(),
+        );
+        if count > 0 {
+            on_replace.call(replaced);
+            current.set(0);
+        }
+    };
+
+    let on_query_keydown = move |ev: web_sys::KeyboardEvent| {
+        if ev.key() == "Enter" {
+            ev.prevent_default();
+            step(!ev.shift_key());
+        }
+    };
+
+    let on_bar_keydown = move |ev: web_sys::KeyboardEvent| {
+        if ev.key() == "Escape" {
+            ev.prevent_default();
+            open.set(false);
+            if let Some(area) = textarea.get_untracked() {
+                let _ = area.focus();
+            }
+        }
+    };
+
+    let status = move || {
+        let count = matches.with(Vec::len);
+        if query.with(String::is_empty) {
+            String::new()
+        } else if count == 0 {
+            "No results".to_string()
+        } else {
+            format!("{} of {}", current.get().min(count - 1) + 1, count)
+        }
+    };
+
+    view! {
+        <div class="find-bar" on:keydown=on_bar_keydown>
+            <div class="find-row">
+                <input
+                    type="text"
+                    class="find-input"
+                    placeholder="Find"
+                    node_ref=query_ref
+                    on:keydown=on_query_keydown
+                    prop:value=move || query.get()
+                    on:input=move |ev| {
+                        query.set(event_target_value(&ev));
+                        current.set(0);
+                    }
+                />
+                <span class="find-status">{status}</span>
+                <button
+                    class="find-toggle"
+                    class:active=move || case_sensitive.get()
+                    on:click=move |_| case_sensitive.update(|v| *v = !*v)
+                    title="Match case"
+                >
+                    "Aa"
+                </button>
+                <button
+                    class="find-toggle"
+                    class:active=move || whole_word.get()
+                    on:click=move |_| whole_word.update(|v| *v = !*v)
+                    title="Match whole word"
+                >
+                    "W"
+                </button>
+                <button class="find-btn" on:click=move |_| step(false) title="Previous match (Shift+Enter)">
+                    "\u{2191}"
+                </button>
+                <button class="find-btn" on:click=move |_| step(true) title="Next match (Enter)">
+                    "\u{2193}"
+                </button>
+                <button class="find-btn" on:click=move |_| open.set(false) title="Close (Esc)">
+                    "\u{00D7}"
+                </button>
+            </div>
+            <div class="find-row">
+                <input
+                    type="text"
+                    class="find-input"
+                    placeholder="Replace"
+                    prop:value=move || replacement.get()
+                    on:input=move |ev| replacement.set(event_target_value(&ev))
+                />
+                <button
+                    class="find-btn"
+                    on:click=replace_current
+                    disabled=move || matches.with(Vec::is_empty)
+                >
+                    "Replace"
+                </button>
+                <button
+                    class="find-btn"
+                    on:click=replace_every
+                    disabled=move || matches.with(Vec::is_empty)
+                >
+                    "All"
+                </button>
+            </div>
+        </div>
+
+        <style>
+            r#"
+            .find-bar {
+                display: flex;
+                flex-direction: column;
+                gap: 0.25rem;
+                padding: 0.375rem 0.5rem;
+                background: var(--bg-tertiary);
+                border-bottom: 1px solid var(--border);
+                font-size: 0.75rem;
+            }
+
+            .find-row {
+                display: flex;
+                align-items: center;
+                gap: 0.25rem;
+            }
+
+            .find-input {
+                flex: 1;
+                min-width: 0;
+                padding: 0.25rem 0.375rem;
+                background: var(--bg-primary);
+                border: 1px solid var(--border);
+                border-radius: 4px;
+                color: var(--text-primary);
+                font-family: 'JetBrains Mono', 'Fira Code', monospace;
+                font-size: 0.75rem;
+            }
+
+            .find-input:focus {
+                outline: none;
+                border-color: var(--accent);
+            }
+
+            .find-status {
+                min-width: 4.5rem;
+                color: var(--text-secondary);
+                text-align: right;
+            }
+
+            .find-btn, .find-toggle {
+                padding: 0.25rem 0.5rem;
+                background: var(--bg-secondary);
+                border: 1px solid var(--border);
+                border-radius: 4px;
+                color: var(--text-primary);
+                cursor: pointer;
+                font-size: 0.75rem;
+            }
+
+            .find-btn:disabled {
+                opacity: 0.5;
+                cursor: not-allowed;
+            }
+
+            .find-toggle.active {
+                border-color: var(--accent);
+                color: var(--accent);
+            }
+            "#
+        </style>
+    }
+}
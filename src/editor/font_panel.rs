@@ -0,0 +1,220 @@
+//! Fonts panel component
+//!
+//! Lets users upload TTF/OTF files that are stored in IndexedDB and
+//! registered with the compiler, so templates can use them by family name.
+
+use leptos::*;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+use crate::fonts::{is_supported_font_extension, FontMetadata, FontStore, MAX_FONT_SIZE};
+
+/// Fonts panel with upload and list of stored fonts
+#[component]
+pub fn FontsPanel(
+    /// Uploaded fonts to display
+    fonts: RwSignal<Vec<FontMetadata>>,
+    /// Font store used for uploads
+    store: RwSignal<Option<FontStore>>,
+    /// Called with the stored metadata and bytes after a successful upload
+    on_upload: Callback<(FontMetadata, Vec<u8>)>,
+    /// Called when delete is clicked
+    on_delete: Callback<String>,
+    /// Called when an upload fails
+    on_error: Callback<String>,
+) -> impl IntoView {
+    let is_uploading = create_rw_signal(false);
+
+    let on_file_select = move |ev: web_sys::Event| {
+        let input: web_sys::HtmlInputElement = ev.target().unwrap().unchecked_into();
+        if let Some(file) = input.files().and_then(|files| files.get(0)) {
+            handle_font_file(file, store, on_upload, on_error, is_uploading);
+        }
+        input.set_value("");
+    };
+
+    view! {
+        <div class="fonts-panel">
+            <div class="fonts-header">
+                <span class="fonts-title">"Fonts"</span>
+                <label class="btn btn-secondary fonts-upload-btn">
+                    {move || if is_uploading.get() { "Uploading..." } else { "Upload font" }}
+                    <input
+                        type="file"
+                        accept=".ttf,.otf,.ttc,.otc"
+                        class="fonts-upload-input"
+                        on:change=on_file_select
+                        disabled=move || is_uploading.get()
+                    />
+                </label>
+            </div>
+            {move || {
+                let font_list = fonts.get();
+                if font_list.is_empty() {
+                    view! {
+                        <p class="fonts-hint">
+                            "Upload a TTF or OTF file, then use it with #set text(font: \"Family Name\")"
+                        </p>
+                    }.into_view()
+                } else {
+                    font_list.into_iter().map(|font| {
+                        let id = font.id.clone();
+                        view! {
+                            <div class="font-item">
+                                <div class="font-info">
+                                    <span class="font-family">{font.family_label()}</span>
+                                    <span class="font-filename">{font.filename.clone()}</span>
+                                </div>
+                                <button
+                                    class="btn-icon font-delete"
+                                    title="Delete font"
+                                    on:click=move |_| on_delete.call(id.clone())
+                                >
+                                    "×"
+                                </button>
+                            </div>
+                        }
+                    }).collect_view()
+                }
+            }}
+        </div>
+
+        <style>
+            r#"
+            .fonts-panel {
+                display: flex;
+                flex-direction: column;
+                gap: 0.5rem;
+                padding: 0.75rem;
+                border: 1px solid var(--border);
+                border-radius: 8px;
+                background: var(--bg-tertiary);
+            }
+
+            .fonts-header {
+                display: flex;
+                align-items: center;
+                justify-content: space-between;
+            }
+
+            .fonts-title {
+                font-weight: 600;
+                font-size: 0.875rem;
+            }
+
+            .fonts-upload-btn {
+                position: relative;
+                overflow: hidden;
+            }
+
+            .fonts-upload-input {
+                position: absolute;
+                inset: 0;
+                opacity: 0;
+                cursor: pointer;
+            }
+
+            .fonts-hint {
+                font-size: 0.75rem;
+                color: var(--text-secondary);
+            }
+
+            .font-item {
+                display: flex;
+                align-items: center;
+                justify-content: space-between;
+                padding: 0.375rem 0.5rem;
+                border-radius: 4px;
+                background: var(--bg-secondary);
+            }
+
+            .font-info {
+                display: flex;
+                flex-direction: column;
+            }
+
+            .font-family {
+                font-size: 0.875rem;
+                color: var(--text-primary);
+            }
+
+            .font-filename {
+                font-size: 0.75rem;
+                color: var(--text-secondary);
+            }
+
+            .font-delete {
+                background: none;
+                border: none;
+                color: var(--text-secondary);
+                cursor: pointer;
+            }
+            "#
+        </style>
+    }
+}
+
+/// Validate, read and store a selected font file
+fn handle_font_file(
+    file: web_sys::File,
+    store: RwSignal<Option<FontStore>>,
+    on_upload: Callback<(FontMetadata, Vec<u8>)>,
+    on_error: Callback<String>,
+    is_uploading: RwSignal<bool>,
+) {
+    let filename = file.name();
+    let extension = filename.rsplit('.').next().unwrap_or("");
+    if !is_supported_font_extension(extension) {
+        on_error.call(format!(
+            "Unsupported font format: .{}. Use TTF or OTF.",
+            extension
+        ));
+        return;
+    }
+
+    let size = file.size() as usize;
+    if size > MAX_FONT_SIZE {
+        on_error.call(format!(
+            "Font too large: {} MB. Maximum size is 20 MB.",
+            size / (1024 * 1024)
+        ));
+        return;
+    }
+
+    is_uploading.set(true);
+
+    let reader = web_sys::FileReader::new().unwrap();
+    let reader_clone = reader.clone();
+
+    let onload = Closure::once(Box::new(move |_event: web_sys::Event| {
+        let Ok(result) = reader_clone.result() else {
+            on_error.call("Failed to read file".to_string());
+            is_uploading.set(false);
+            return;
+        };
+        let data = js_sys::Uint8Array::new(&result).to_vec();
+
+        spawn_local(async move {
+            match store.get_untracked() {
+                Some(font_store) => match font_store.store_font(filename, data.clone()).await {
+                    Ok(metadata) => on_upload.call((metadata, data)),
+                    Err(err) => on_error.call(err.to_string()),
+                },
+                None => on_error.call("Font store not initialized".to_string()),
+            }
+            is_uploading.set(false);
+        });
+    }) as Box<dyn FnOnce(_)>);
+
+    let onerror = Closure::once(Box::new(move |_event: web_sys::Event| {
+        on_error.call("Failed to read file".to_string());
+        is_uploading.set(false);
+    }) as Box<dyn FnOnce(_)>);
+
+    reader.set_onload(Some(onload.as_ref().unchecked_ref()));
+    reader.set_onerror(Some(onerror.as_ref().unchecked_ref()));
+    onload.forget();
+    onerror.forget();
+
+    let _ = reader.read_as_array_buffer(&file);
+}
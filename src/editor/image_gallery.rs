@@ -3,12 +3,15 @@
 //! Displays stored images in a grid with:
 //! - Thumbnail previews
 //! - Filename and size display
-//! - Click to copy ID for use in templates
+//! - Warning for formats Typst cannot render
+//! - Copy an `#image(...)` snippet or the bare ID for use in templates
+//! - Regenerate button for AI-generated images
+//! - Describe button that asks a vision model for alt text
 //! - Delete button
 
 use leptos::*;
 
-use crate::images::{ImageCache, ImageMetadata};
+use crate::images::{extension_from_mime_type, ImageCache, ImageMetadata};
 
 /// Image gallery component
 #[component]
@@ -19,8 +22,18 @@ pub fn ImageGallery(
     image_cache: RwSignal<ImageCache>,
     /// Called when an image is selected (copies ID to clipboard)
     on_select: Callback<ImageMetadata>,
+    /// Called when the Typst snippet copy is clicked
+    on_copy_snippet: Callback<ImageMetadata>,
     /// Called when delete is clicked
     on_delete: Callback<String>,
+    /// Called when regenerate is clicked on an image with a stored prompt
+    on_regenerate: Callback<ImageMetadata>,
+    /// ID of the image currently being regenerated, if any
+    regenerating: Signal<Option<String>>,
+    /// Called when describe is clicked, to generate alt text from the image
+    on_describe: Callback<ImageMetadata>,
+    /// ID of the image currently being described, if any
+    describing: Signal<Option<String>>,
 ) -> impl IntoView {
     view! {
         <div class="image-gallery">
@@ -37,8 +50,15 @@ pub fn ImageGallery(
                     view! {
                         <div class="gallery-grid">
                             {image_list.iter().map(|metadata| {
-                                let metadata_clone = metadata.clone();
                                 let metadata_for_select = metadata.clone();
+                                let metadata_for_snippet = metadata.clone();
+                                let metadata_for_regenerate = metadata.clone();
+                                let metadata_for_describe = metadata.clone();
+                                let alt_text = metadata
+                                    .alt_description
+                                    .clone()
+                                    .unwrap_or_else(|| metadata.filename.clone());
+                                let needs_conversion = !metadata.is_typst_compatible();
                                 let id_for_delete = metadata.id.clone();
                                 let cache = image_cache.get();
                                 let thumbnail_data = cache.get(&metadata.id).map(|bytes| {
@@ -50,7 +70,7 @@ pub fn ImageGallery(
                                         <div class="gallery-thumbnail">
                                             {if let Some(data_url) = thumbnail_data {
                                                 view! {
-                                                    <img src=data_url alt=metadata_clone.filename.clone() />
+                                                    <img src=data_url alt=alt_text.clone() />
                                                 }.into_view()
                                             } else {
                                                 view! {
@@ -64,7 +84,22 @@ pub fn ImageGallery(
                                             <div class="gallery-filename" title=metadata.filename.clone()>
                                                 {truncate_filename(&metadata.filename, 20)}
                                             </div>
+                                            {needs_conversion.then(|| view! {
+                                                <div
+                                                    class="gallery-warning"
+                                                    title="Typst cannot render this format; convert to PNG or JPEG before use"
+                                                >
+                                                    "Needs conversion"
+                                                </div>
+                                            })}
                                             <div class="gallery-actions">
+                                                <button
+                                                    class="gallery-copy-btn"
+                                                    on:click=move |_| on_copy_snippet.call(metadata_for_snippet.clone())
+                                                    title="Copy a ready-to-paste #image(...) snippet"
+                                                >
+                                                    "Copy Typst"
+                                                </button>
                                                 <button
                                                     class="gallery-copy-btn"
                                                     on:click=move |_| on_select.call(metadata_for_select.clone())
@@ -74,6 +109,44 @@ pub fn ImageGallery(
                                                 </button>
                                                 <span class="gallery-size">{format_size(metadata.size)}</span>
                                             </div>
+                                            {metadata.can_regenerate().then(|| {
+                                                let id = metadata.id.clone();
+                                                let is_busy = move || regenerating.get().is_some();
+                                                let is_this = move || regenerating.get().as_deref() == Some(id.as_str());
+                                                view! {
+                                                    <button
+                                                        class="gallery-regenerate-btn"
+                                                        on:click=move |_| on_regenerate.call(metadata_for_regenerate.clone())
+                                                        disabled=is_busy
+                                                        title="Generate a variation from the same prompt"
+                                                    >
+                                                        {move || if is_this() { "Regenerating..." } else { "Regenerate" }}
+                                                    </button>
+                                                }
+                                            })}
+                                            {metadata.alt_description.clone().map(|alt| view! {
+                                                <div class="gallery-alt" title=alt.clone()>{alt}</div>
+                                            })}
+                                            {
+                                                let id = metadata.id.clone();
+                                                let is_busy = move || describing.get().is_some();
+                                                let is_this = move || describing.get().as_deref() == Some(id.as_str());
+                                                view! {
+                                                    <button
+                                                        class="gallery-describe-btn"
+                                                        on:click=move |_| on_describe.call(metadata_for_describe.clone())
+                                                        disabled=is_busy
+                                                        title="Generate alt text from the image contents"
+                                                    >
+                                                        {move || if is_this() { "Describing..." } else { "Describe" }}
+                                                    </button>
+                                                }
+                                            }
+                                            {metadata.derived_from.clone().map(|original| view! {
+                                                <div class="gallery-derived" title=format!("Variation of {}", original)>
+                                                    "Variation"
+                                                </div>
+                                            })}
                                         </div>
                                         <button
                                             class="gallery-delete"
@@ -173,8 +246,15 @@ pub fn ImageGallery(
                 text-overflow: ellipsis;
             }
 
+            .gallery-warning {
+                font-size: 0.625rem;
+                color: #b45309;
+                margin-top: 0.125rem;
+            }
+
             .gallery-actions {
                 display: flex;
+                flex-wrap: wrap;
                 justify-content: space-between;
                 align-items: center;
                 margin-top: 0.25rem;
@@ -196,6 +276,41 @@ pub fn ImageGallery(
                 background: var(--accent-hover);
             }
 
+            .gallery-regenerate-btn,
+            .gallery-describe-btn {
+                margin-top: 0.25rem;
+                width: 100%;
+                padding: 0.25rem 0.5rem;
+                font-size: 0.625rem;
+                background: var(--bg-secondary);
+                color: var(--text-primary);
+                border: 1px solid var(--border);
+                border-radius: 3px;
+                cursor: pointer;
+            }
+
+            .gallery-regenerate-btn:disabled,
+            .gallery-describe-btn:disabled {
+                opacity: 0.6;
+                cursor: not-allowed;
+            }
+
+            .gallery-alt {
+                margin-top: 0.25rem;
+                font-size: 0.625rem;
+                color: var(--text-secondary);
+                display: -webkit-box;
+                -webkit-line-clamp: 2;
+                -webkit-box-orient: vertical;
+                overflow: hidden;
+            }
+
+            .gallery-derived {
+                margin-top: 0.25rem;
+                font-size: 0.625rem;
+                color: var(--text-secondary);
+            }
+
             .gallery-delete {
                 position: absolute;
                 top: 0.25rem;
@@ -227,6 +342,15 @@ pub fn ImageGallery(
     }
 }
 
+/// Build a ready-to-paste Typst `#image(...)` call for a stored image
+pub fn image_snippet(id: &str, mime_type: &str) -> String {
+    format!(
+        "#image(\"{}.{}\", width: 100%)",
+        id,
+        extension_from_mime_type(mime_type)
+    )
+}
+
 /// Create a data URL for an image
 fn create_data_url(mime_type: &str, bytes: &[u8]) -> String {
     let base64 = base64_encode(bytes);
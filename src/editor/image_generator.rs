@@ -5,10 +5,96 @@
 
 use leptos::*;
 
-use crate::ai::client::OpenRouterConfig;
-use crate::ai::image_gen::{generate_alt_description, ImageGenerator};
+use crate::ai::image_gen::{generate_alt_description, GenerateOptions, ImageGenerator};
 use crate::images::{generate_image_id, ImageMetadata, ImageStore};
 
+/// Output shape presets offered in the generator panel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AspectPreset {
+    /// Square image (1:1)
+    Square,
+    /// Widescreen banner (16:9)
+    Banner,
+    /// Small square logo (512x512)
+    Logo,
+}
+
+impl AspectPreset {
+    const ALL: [AspectPreset; 3] = [
+        AspectPreset::Square,
+        AspectPreset::Banner,
+        AspectPreset::Logo,
+    ];
+
+    /// Value used in the preset `<select>`
+    fn key(self) -> &'static str {
+        match self {
+            AspectPreset::Square => "square",
+            AspectPreset::Banner => "banner",
+            AspectPreset::Logo => "logo",
+        }
+    }
+
+    /// Label shown in the preset `<select>`
+    fn label(self) -> &'static str {
+        match self {
+            AspectPreset::Square => "Square (1:1)",
+            AspectPreset::Banner => "Banner (16:9)",
+            AspectPreset::Logo => "Logo (512×512)",
+        }
+    }
+
+    /// Look up a preset by its `<select>` value
+    fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|preset| preset.key() == key)
+    }
+
+    /// Generation options for this preset
+    fn options(self) -> GenerateOptions {
+        match self {
+            AspectPreset::Square => GenerateOptions::with_aspect_ratio("1:1"),
+            AspectPreset::Banner => GenerateOptions::with_aspect_ratio("16:9"),
+            AspectPreset::Logo => GenerateOptions::with_aspect_ratio("1:1").with_size(512, 512),
+        }
+    }
+}
+
+/// Generate an image, describe it, and save it to the store
+///
+/// `derived_from` is set when re-running the prompt of an existing image.
+pub async fn generate_and_store(
+    api_key: String,
+    store: Option<ImageStore>,
+    prompt: &str,
+    options: &GenerateOptions,
+    derived_from: Option<String>,
+) -> Result<ImageMetadata, String> {
+    let image_store = store.ok_or_else(|| "Image store not initialized".to_string())?;
+    let config = super::openrouter_config(api_key);
+    let generator = ImageGenerator::new(config.clone());
+
+    let (bytes, mime_type) = generator
+        .generate(prompt, options)
+        .await
+        .map_err(|e| format!("Generation failed: {}", e))?;
+
+    // Generate filename
+    let id = generate_image_id();
+    let ext = crate::images::extension_from_mime_type(&mime_type);
+    let filename = format!("generated_{}.{}", &id[4..12], ext);
+
+    // Generate alt description using Gemini Flash
+    let alt_desc = generate_alt_description(&config, prompt)
+        .await
+        .unwrap_or_else(|_| "AI-generated image".to_string());
+
+    // Store with prompt and alt description
+    image_store
+        .store_generated_image(filename, bytes, prompt.to_string(), alt_desc, derived_from)
+        .await
+        .map_err(|e| format!("Failed to save image: {}", e))
+}
+
 /// Image generator component with prompt input
 #[component]
 pub fn ImageGeneratorPanel(
@@ -23,12 +109,14 @@ pub fn ImageGeneratorPanel(
 ) -> impl IntoView {
     let prompt = create_rw_signal(String::new());
     let is_generating = create_rw_signal(false);
+    let preset = create_rw_signal(AspectPreset::Square);
 
     let has_api_key = create_memo(move |_| !api_key.get().trim().is_empty());
 
     let on_submit = move |_| {
         let prompt_text = prompt.get();
         let key = api_key.get();
+        let options = preset.get().options();
 
         // Validate
         if prompt_text.trim().is_empty() {
@@ -47,47 +135,13 @@ pub fn ImageGeneratorPanel(
         is_generating.set(true);
 
         spawn_local(async move {
-            let config = OpenRouterConfig::with_key(key);
-            let generator = ImageGenerator::new(config.clone());
-
-            match generator.generate(&prompt_text).await {
-                Ok((bytes, mime_type)) => {
-                    // Generate filename
-                    let id = generate_image_id();
-                    let ext = crate::images::extension_from_mime_type(&mime_type);
-                    let filename = format!("generated_{}.{}", &id[4..12], ext);
-
-                    // Generate alt description using Gemini Flash
-                    let alt_desc = generate_alt_description(&config, &prompt_text)
-                        .await
-                        .unwrap_or_else(|_| "AI-generated image".to_string());
-
-                    if let Some(image_store) = store.get() {
-                        // Store with prompt and alt description
-                        match image_store
-                            .store_generated_image(
-                                filename.clone(),
-                                bytes,
-                                prompt_text.clone(),
-                                alt_desc,
-                            )
-                            .await
-                        {
-                            Ok(metadata) => {
-                                on_generate.call(metadata);
-                                prompt.set(String::new()); // Clear prompt on success
-                            }
-                            Err(e) => {
-                                on_error.call(format!("Failed to save image: {}", e));
-                            }
-                        }
-                    } else {
-                        on_error.call("Image store not initialized".to_string());
-                    }
-                }
-                Err(e) => {
-                    on_error.call(format!("Generation failed: {}", e));
+            match generate_and_store(key, store.get_untracked(), &prompt_text, &options, None).await
+            {
+                Ok(metadata) => {
+                    on_generate.call(metadata);
+                    prompt.set(String::new()); // Clear prompt on success
                 }
+                Err(e) => on_error.call(e),
             }
 
             is_generating.set(false);
@@ -109,6 +163,29 @@ pub fn ImageGeneratorPanel(
                     disabled=move || is_generating.get()
                     rows="3"
                 />
+                <select
+                    class="generator-preset"
+                    on:change=move |ev| {
+                        if let Some(selected) = AspectPreset::from_key(&event_target_value(&ev)) {
+                            preset.set(selected);
+                        }
+                    }
+                    disabled=move || is_generating.get()
+                >
+                    {AspectPreset::ALL
+                        .into_iter()
+                        .map(|option| {
+                            view! {
+                                <option
+                                    value=option.key()
+                                    selected=move || preset.get() == option
+                                >
+                                    {option.label()}
+                                </option>
+                            }
+                        })
+                        .collect_view()}
+                </select>
                 <button
                     class="generator-btn"
                     on:click=on_submit
@@ -186,6 +263,15 @@ pub fn ImageGeneratorPanel(
                 min-height: 60px;
             }
 
+            .generator-preset {
+                padding: 0.5rem;
+                border: 1px solid var(--border);
+                border-radius: 6px;
+                background: var(--bg-primary);
+                color: var(--text-primary);
+                font-size: 0.875rem;
+            }
+
             .generator-prompt:focus {
                 outline: none;
                 border-color: var(--accent);
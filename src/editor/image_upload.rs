@@ -3,14 +3,25 @@
 //! Provides a file upload interface for images with:
 //! - Drag-and-drop zone
 //! - File picker fallback
+//! - Pasting an image as a base64 data URL
 //! - Format and size validation
+//! - Optional downscaling before storage
 //! - Progress indication
 
 use leptos::*;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
 
-use crate::images::{is_supported_extension, ImageMetadata, ImageStore, MAX_IMAGE_SIZE};
+use crate::images::{
+    is_supported_extension, transform_image, ImageError, ImageMetadata, ImageStore,
+    TransformOptions, MAX_IMAGE_SIZE,
+};
+
+/// Default longest side for resized uploads, in pixels
+const DEFAULT_MAX_DIMENSION: u32 = 1600;
+
+/// Filename recorded for images added from a pasted data URL
+const PASTED_FILENAME: &str = "pasted-image";
 
 /// Image upload component with drag-drop support
 #[component]
@@ -21,6 +32,18 @@ pub fn ImageUpload(
 ) -> impl IntoView {
     let is_dragging = create_rw_signal(false);
     let is_uploading = create_rw_signal(false);
+    let resize_enabled = create_rw_signal(true);
+    let max_dimension = create_rw_signal(DEFAULT_MAX_DIMENSION);
+    // Stays visible until an upload succeeds, unlike the transient status
+    let storage_full = create_rw_signal(Option::<String>::None);
+    let pasted_data_url = create_rw_signal(String::new());
+
+    // Bound to apply to the next upload, if resizing is enabled
+    let resize_bound = move || {
+        resize_enabled
+            .get_untracked()
+            .then(|| max_dimension.get_untracked())
+    };
 
     // Handle file selection from input
     let on_file_select = move |ev: web_sys::Event| {
@@ -29,7 +52,15 @@ pub fn ImageUpload(
 
         if let Some(files) = input.files() {
             if let Some(file) = files.get(0) {
-                handle_file(file, store, on_upload, on_error, is_uploading);
+                handle_file(
+                    file,
+                    resize_bound(),
+                    store,
+                    on_upload,
+                    on_error,
+                    is_uploading,
+                    storage_full,
+                );
             }
         }
     };
@@ -51,12 +82,51 @@ pub fn ImageUpload(
         if let Some(data_transfer) = ev.data_transfer() {
             if let Some(files) = data_transfer.files() {
                 if let Some(file) = files.get(0) {
-                    handle_file(file, store, on_upload, on_error, is_uploading);
+                    handle_file(
+                        file,
+                        resize_bound(),
+                        store,
+                        on_upload,
+                        on_error,
+                        is_uploading,
+                        storage_full,
+                    );
                 }
             }
         }
     };
 
+    // Store a pasted data URL like a normal upload
+    let on_paste_submit = move |_| {
+        let data_url = pasted_data_url.get_untracked();
+        if data_url.trim().is_empty() {
+            return;
+        }
+        is_uploading.set(true);
+        spawn_local(async move {
+            if let Some(image_store) = store.get_untracked() {
+                match image_store
+                    .store_data_url(PASTED_FILENAME.to_string(), &data_url)
+                    .await
+                {
+                    Ok(metadata) => {
+                        storage_full.set(None);
+                        pasted_data_url.set(String::new());
+                        on_upload.call(metadata);
+                    }
+                    Err(err @ ImageError::QuotaExceeded { .. }) => {
+                        storage_full.set(Some(err.to_string()));
+                        on_error.call(err.to_string());
+                    }
+                    Err(err) => on_error.call(err.to_string()),
+                }
+            } else {
+                on_error.call("Image store not initialized".to_string());
+            }
+            is_uploading.set(false);
+        });
+    };
+
     view! {
         <div
             class=move || {
@@ -86,10 +156,10 @@ pub fn ImageUpload(
                         <div class="upload-content">
                             <div class="upload-icon">"+"</div>
                             <div class="upload-text">"Drop image here or click to upload"</div>
-                            <div class="upload-hint">"PNG, JPEG, SVG, GIF, WebP (max 10MB)"</div>
+                            <div class="upload-hint">"PNG, JPEG, SVG, GIF, WebP, BMP, TIFF (max 10MB)"</div>
                             <input
                                 type="file"
-                                accept="image/png,image/jpeg,image/svg+xml,image/gif,image/webp"
+                                accept="image/png,image/jpeg,image/svg+xml,image/gif,image/webp,image/bmp,image/tiff"
                                 class="upload-input"
                                 on:change=on_file_select
                             />
@@ -99,6 +169,53 @@ pub fn ImageUpload(
             }}
         </div>
 
+        <div class="upload-resize">
+            <label>
+                <input
+                    type="checkbox"
+                    prop:checked=move || resize_enabled.get()
+                    on:change=move |ev| resize_enabled.set(event_target_checked(&ev))
+                />
+                " Resize to max "
+            </label>
+            <input
+                type="number"
+                class="upload-max-dimension"
+                min="16"
+                step="1"
+                prop:value=move || max_dimension.get().to_string()
+                prop:disabled=move || !resize_enabled.get()
+                on:change=move |ev| {
+                    if let Ok(value) = event_target_value(&ev).parse::<u32>() {
+                        max_dimension.set(value.max(16));
+                    }
+                }
+            />
+            " px"
+        </div>
+        <div class="upload-paste">
+            <input
+                type="text"
+                class="upload-paste-input"
+                placeholder="Paste image data URL (data:image/png;base64,...)"
+                prop:value=move || pasted_data_url.get()
+                on:input=move |ev| pasted_data_url.set(event_target_value(&ev))
+            />
+            <button
+                class="upload-paste-btn"
+                prop:disabled=move || is_uploading.get() || pasted_data_url.with(|url| url.trim().is_empty())
+                on:click=on_paste_submit
+            >
+                "Add"
+            </button>
+        </div>
+        {move || storage_full.get().map(|message| view! {
+            <div class="upload-storage-full" role="alert">
+                <span>{message}</span>
+                <button class="upload-dismiss" on:click=move |_| storage_full.set(None)>"×"</button>
+            </div>
+        })}
+
         <style>
             r#"
             .image-upload-zone {
@@ -159,6 +276,76 @@ pub fn ImageUpload(
                 cursor: pointer;
             }
 
+            .upload-storage-full {
+                display: flex;
+                align-items: flex-start;
+                gap: 0.5rem;
+                margin-top: 0.5rem;
+                padding: 0.5rem;
+                border: 1px solid var(--error);
+                border-radius: 4px;
+                font-size: 0.75rem;
+                color: var(--error);
+            }
+
+            .upload-dismiss {
+                margin-left: auto;
+                background: none;
+                border: none;
+                color: inherit;
+                cursor: pointer;
+            }
+
+            .upload-resize {
+                display: flex;
+                align-items: center;
+                gap: 0.25rem;
+                margin-top: 0.5rem;
+                font-size: 0.75rem;
+                color: var(--text-secondary);
+            }
+
+            .upload-paste {
+                display: flex;
+                gap: 0.25rem;
+                margin-top: 0.5rem;
+            }
+
+            .upload-paste-input {
+                flex: 1;
+                min-width: 0;
+                padding: 0.25rem 0.5rem;
+                background: var(--bg-tertiary);
+                border: 1px solid var(--border);
+                border-radius: 4px;
+                color: var(--text-primary);
+                font-size: 0.75rem;
+            }
+
+            .upload-paste-btn {
+                padding: 0.25rem 0.75rem;
+                background: var(--accent);
+                border: none;
+                border-radius: 4px;
+                color: white;
+                font-size: 0.75rem;
+                cursor: pointer;
+            }
+
+            .upload-paste-btn:disabled {
+                opacity: 0.5;
+                cursor: default;
+            }
+
+            .upload-max-dimension {
+                width: 5rem;
+                padding: 0.125rem 0.25rem;
+                background: var(--bg-tertiary);
+                border: 1px solid var(--border);
+                border-radius: 4px;
+                color: var(--text-primary);
+            }
+
             .upload-status {
                 display: flex;
                 align-items: center;
@@ -184,20 +371,22 @@ pub fn ImageUpload(
     }
 }
 
-/// Handle a selected file
+/// Handle a selected file, downscaling it to `max_dimension` if given
 fn handle_file(
     file: web_sys::File,
+    max_dimension: Option<u32>,
     store: RwSignal<Option<ImageStore>>,
     on_upload: Callback<ImageMetadata>,
     on_error: Callback<String>,
     is_uploading: RwSignal<bool>,
+    storage_full: RwSignal<Option<String>>,
 ) {
     // Check file extension
     let filename = file.name();
     let extension = filename.rsplit('.').next().unwrap_or("");
     if !is_supported_extension(extension) {
         on_error.call(format!(
-            "Unsupported file format: .{}. Use PNG, JPEG, SVG, GIF, or WebP.",
+            "Unsupported file format: .{}. Use PNG, JPEG, SVG, GIF, WebP, BMP, or TIFF.",
             extension
         ));
         return;
@@ -224,15 +413,31 @@ fn handle_file(
         if let Ok(result) = reader_clone.result() {
             let array_buffer: js_sys::ArrayBuffer = result.unchecked_into();
             let uint8_array = js_sys::Uint8Array::new(&array_buffer);
-            let data = uint8_array.to_vec();
+            let mut data = uint8_array.to_vec();
+
+            if let Some(max) = max_dimension {
+                match transform_image(&data, &TransformOptions::max_dimension(max)) {
+                    Ok(resized) => data = resized,
+                    Err(err) => {
+                        on_error.call(err.to_string());
+                        is_uploading.set(false);
+                        return;
+                    }
+                }
+            }
 
             // Upload to store
             spawn_local(async move {
                 if let Some(image_store) = store.get() {
                     match image_store.store_image(filename_clone, data).await {
                         Ok(metadata) => {
+                            storage_full.set(None);
                             on_upload.call(metadata);
                         }
+                        Err(err @ ImageError::QuotaExceeded { .. }) => {
+                            storage_full.set(Some(err.to_string()));
+                            on_error.call(err.to_string());
+                        }
                         Err(err) => {
                             on_error.call(err.to_string());
                         }
@@ -0,0 +1,65 @@
+//! Line-number gutter support for the code editor
+//!
+//! Textarea caret positions are UTF-16 offsets, so the helpers here take
+//! and return UTF-16 units rather than byte offsets.
+
+/// Number of lines in `text` (an empty string is one empty line)
+pub fn line_count(text: &str) -> usize {
+    text.split('\n').count()
+}
+
+/// 1-based line containing the caret at UTF-16 offset `caret`
+///
+/// Offsets past the end of the text land on the last line.
+pub fn caret_line(text: &str, caret: u32) -> usize {
+    let mut units = 0;
+    let mut line = 1;
+    for c in text.chars() {
+        if units >= caret as usize {
+            break;
+        }
+        if c == '\n' {
+            line += 1;
+        }
+        units += c.len_utf16();
+    }
+    line
+}
+
+/// UTF-16 offset of the start of 1-based `line`, clamped to the last line
+pub fn line_start(text: &str, line: usize) -> u32 {
+    let mut units = 0;
+    let mut current = 1;
+    for c in text.chars() {
+        if current >= line {
+            break;
+        }
+        units += c.len_utf16();
+        if c == '\n' {
+            current += 1;
+        }
+    }
+    units as u32
+}
+
+/// 1-based source line named by a compile error's " (line N)" suffix
+pub fn error_line(message: &str) -> Option<usize> {
+    let (_, line) = message.strip_suffix(')')?.rsplit_once(" (line ")?;
+    line.parse().ok().filter(|&line| line > 0)
+}
+
+/// Move the caret to the start of 1-based `line` and scroll it into view
+pub fn scroll_to_line(textarea: &web_sys::HtmlTextAreaElement, line: usize) {
+    let offset = line_start(&textarea.value(), line);
+    let _ = textarea.focus();
+    let _ = textarea.set_selection_range(offset, offset);
+
+    // Put the line a few rows below the top edge so it has some context
+    let line_height = web_sys::window()
+        .and_then(|w| w.get_computed_style(textarea).ok().flatten())
+        .and_then(|style| style.get_property_value("line-height").ok())
+        .and_then(|value| value.trim_end_matches("px").parse::<f64>().ok())
+        .unwrap_or(22.4);
+    let row = line.saturating_sub(4) as f64;
+    textarea.set_scroll_top((row * line_height) as i32);
+}
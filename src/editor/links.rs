@@ -13,6 +13,38 @@ pub enum EditCommand {
     Image,
     /// Edit a metadata field
     Metadata(String),
+    /// Edit an element of an array field (e.g. a feature or a stat)
+    ArrayItem {
+        /// Array field name (e.g. "features", "stats")
+        field: String,
+        /// Index of the element within the array
+        index: usize,
+        /// Optional field within the element (e.g. "value", "label")
+        subfield: Option<String>,
+    },
+}
+
+impl EditCommand {
+    /// Field identifier used by the edit modal (e.g. "title", "meta/key", "stats/0/value")
+    pub fn field_id(&self) -> String {
+        match self {
+            EditCommand::Title => "title".to_string(),
+            EditCommand::Subtitle => "subtitle".to_string(),
+            EditCommand::Body => "body".to_string(),
+            EditCommand::Image => "image".to_string(),
+            EditCommand::Metadata(key) => format!("meta/{}", key),
+            EditCommand::ArrayItem {
+                field,
+                index,
+                subfield: Some(subfield),
+            } => format!("{}/{}/{}", field, index, subfield),
+            EditCommand::ArrayItem {
+                field,
+                index,
+                subfield: None,
+            } => format!("{}/{}", field, index),
+        }
+    }
 }
 
 /// Parse a cmd:// URL into an EditCommand
@@ -31,6 +63,16 @@ pub fn parse_cmd_url(url: &str) -> Option<EditCommand> {
         ["edit", "body"] => Some(EditCommand::Body),
         ["edit", "image"] => Some(EditCommand::Image),
         ["edit", "meta", key] => Some(EditCommand::Metadata((*key).to_string())),
+        ["edit", field, index] => Some(EditCommand::ArrayItem {
+            field: (*field).to_string(),
+            index: index.parse().ok()?,
+            subfield: None,
+        }),
+        ["edit", field, index, subfield] if !subfield.is_empty() => Some(EditCommand::ArrayItem {
+            field: (*field).to_string(),
+            index: index.parse().ok()?,
+            subfield: Some((*subfield).to_string()),
+        }),
         _ => None,
     }
 }
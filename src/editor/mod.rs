@@ -1,7 +1,8 @@
 //! Editor module - Split-pane editor with live preview
 //!
 //! This module provides the main editor interface with:
-//! - Split pane layout (code left, preview right, AI chat right)
+//! - Split pane layout (code left, preview right, AI chat right) with a
+//!   draggable divider
 //! - Content model for structured editing
 //! - Debounced auto-preview or manual refresh
 //! - Template gallery for quick start
@@ -10,19 +11,34 @@
 //! - AI-powered code generation
 //! - Settings for AI configuration
 //! - Status bar with online/offline indicator
+//! - Light/dark theme toggle
 
+mod autocomplete;
 mod chat_panel;
+mod clipboard;
 mod content;
 mod edit_modal;
+mod escape_preview;
+mod features_editor;
+mod find_replace;
+mod font_panel;
 mod image_gallery;
 mod image_generator;
 mod image_upload;
+mod line_numbers;
 mod links;
+mod page_setup;
+mod partials_panel;
+mod sections_editor;
 mod settings_modal;
+mod split;
 mod state;
 mod status_bar;
+mod theme;
+mod zoom;
 
 #[cfg(test)]
+#[allow(clippy::useless_vec)]
 mod tests;
 
 #[allow(unused_imports)]
@@ -31,25 +47,72 @@ pub use content::Content;
 pub use links::{parse_cmd_url, EditCommand};
 pub use state::{EditorState, EditorTab};
 
-use chat_panel::{AiProcessingState, ChatMessage, ChatPanel};
+use state::{is_rendered_from_data, ConversationHistory, PendingPrompts, PENDING_PROMPT_LIMIT};
+
+use autocomplete::{insert_completion, variable_prefix};
+use chat_panel::{retry_countdown, AiProcessingState, ChatMessage, ChatPanel};
+use clipboard::{copy_png_to_clipboard, COPY_SCALE};
 use edit_modal::{get_field_label, get_field_type, EditFieldData, EditModal};
-use image_gallery::{copy_to_clipboard, ImageGallery};
-use image_generator::ImageGeneratorPanel;
+use escape_preview::EscapePreview;
+use features_editor::FeaturesEditor;
+use find_replace::{is_find_shortcut, FindReplaceBar};
+use font_panel::FontsPanel;
+use image_gallery::{copy_to_clipboard, image_snippet, ImageGallery};
+use image_generator::{generate_and_store, ImageGeneratorPanel};
 use image_upload::ImageUpload;
-use settings_modal::{AiSettings, SettingsModal};
-use status_bar::{use_online_status, StatusBar};
+use line_numbers::{caret_line, error_line, line_count, scroll_to_line};
+use page_setup::PageSetupSelect;
+use partials_panel::PartialsPanel;
+use sections_editor::SectionsEditor;
+use settings_modal::{load_preview_delay, AiSettings, SettingsModal};
+use split::{load_split_ratio, save_split_ratio, split_ratio_at, DEFAULT_SPLIT_RATIO};
+use status_bar::{use_online_status, StatusBar, TextStats};
+use theme::{apply_theme, load_theme, save_theme, Theme};
+use zoom::{fit_width_zoom, step_zoom, svg_page_size};
+
+use std::collections::BTreeMap;
 
 use leptos::*;
 use wasm_bindgen::closure::Closure;
 use wasm_bindgen::JsCast;
 
-use crate::ai::client::ChatMessage as AiChatMessage;
-use crate::ai::{OpenRouterClient, OpenRouterConfig};
+use crate::ai::client::{backoff_delay, ChatMessage as AiChatMessage};
+use crate::ai::{
+    generate_alt_for_upload, AgentConfig, AgentLoop, AgentResult, CancellationToken,
+    GenerateOptions, OpenRouterClient, OpenRouterConfig,
+};
+use crate::data::{diff, get_data_field, update_data_field, FieldDiff, PageSetup, SlickSheetData};
+use crate::fonts::{FontCache, FontMetadata, FontStore};
 use crate::images::{ImageCache, ImageMetadata, ImageStore};
-use crate::persistence::{pdf_data_url, Project};
-use crate::template::TemplateEngine;
-use crate::templates::TEMPLATES;
-use crate::world::VirtualWorld;
+use crate::persistence::autosave::{
+    clear_autosave, load_recoverable_autosave, mark_saved, store_autosave, AUTOSAVE_DELAY_MS,
+};
+use crate::persistence::{
+    effective_ai_model, from_zip_bundle, generate_project_id, html_from_svg,
+    pdf_data_url_from_world, png_bytes_for_preset, png_bytes_from_world, typst_filename,
+    zip_bundle, Autosave, PdfMeta, PdfStandard, Project, RecentProject, RecentStore,
+    RECENT_PROJECTS_SHOWN,
+};
+use crate::template::{
+    apply_export_preset, matching_variables, ExportPreset, TemplateEngine, VariableInfo,
+};
+use crate::templates::{system_prompt_with_hint, TemplateCategory, TemplateGallery, TEMPLATES};
+use crate::world::{
+    image_version_keys, CompileCache, IncrementalCompiler, VirtualWorld, EMPTY_DOCUMENT_MESSAGE,
+};
+
+/// An AI-generated update that compiled, held until the user applies it
+#[derive(Debug, Clone)]
+struct ProposedChange {
+    data: SlickSheetData,
+    template: String,
+    typst: String,
+    svg: String,
+    diffs: Vec<FieldDiff>,
+    /// The prompt and JSON reply, remembered as context once applied
+    prompt: String,
+    response: String,
+}
 
 /// Main Editor component with split pane layout
 #[component]
@@ -59,25 +122,85 @@ pub fn Editor() -> impl IntoView {
 
     // Get signals from state
     let active_tab = state.active_tab;
+    // Content tab shows the raw JSON instead of the structured editor
+    let content_json_view = create_rw_signal(false);
+    // Debug panel showing rendered Typst under the Content and Template tabs
+    let show_escape_preview = create_rw_signal(false);
     let content_data = state.content_data;
     let template_source = state.template_source;
     let typst_source = state.typst_source;
     let svg_output = state.svg_output;
     let error = state.error;
     let auto_preview = state.auto_preview;
+    let preview_delay_ms = state.preview_delay_ms;
+    preview_delay_ms.set(load_preview_delay());
 
     // Modal states
     let show_template_gallery = create_rw_signal(false);
     let show_settings_modal = create_rw_signal(false);
     let show_edit_modal = create_rw_signal(Option::<EditFieldData>::None);
     let project_name = create_rw_signal("Untitled Project".to_string());
+    // Stable ID that keeps this project's "Recent" entry apart from others
+    let project_id = create_rw_signal(generate_project_id());
+    // AI model pinned to this project, overriding the global setting
+    let project_ai_model = create_rw_signal(None::<String>);
     let status_message = create_rw_signal(Option::<String>::None);
     let last_saved = create_rw_signal(Option::<String>::None);
 
     // AI settings
     let ai_settings = create_rw_signal(AiSettings::load());
+    // Model the assistant uses: the project's pinned model, else the global one
+    let effective_model = move || {
+        let global = ai_settings.with(|settings| settings.model.clone());
+        project_ai_model.with(|model| effective_ai_model(model.as_deref(), &global).to_string())
+    };
     let has_api_key = create_memo(move |_| ai_settings.get().has_api_key());
 
+    // Theme, applied to the root element whenever it changes
+    let theme = create_rw_signal(load_theme());
+    create_effect(move |_| apply_theme(theme.get()));
+    let toggle_theme = move |_| {
+        let next = theme.get_untracked().toggled();
+        save_theme(next);
+        theme.set(next);
+    };
+
+    // Code/preview split, dragged with mouse or touch
+    let split_ref = create_node_ref::<html::Div>();
+    let split_ratio = create_rw_signal(load_split_ratio());
+    let split_dragging = create_rw_signal(false);
+    let drag_split_to = move |client_x: f64| {
+        if let Some(pane) = split_ref.get_untracked() {
+            let rect = pane.get_bounding_client_rect();
+            split_ratio.set(split_ratio_at(client_x - rect.left(), rect.width()));
+        }
+    };
+    let end_split_drag = move || {
+        if split_dragging.get_untracked() {
+            split_dragging.set(false);
+            save_split_ratio(split_ratio.get_untracked());
+        }
+    };
+    let _split_mouse_move = window_event_listener(ev::mousemove, move |ev| {
+        if split_dragging.get_untracked() {
+            ev.prevent_default();
+            drag_split_to(ev.client_x() as f64);
+        }
+    });
+    let _split_touch_move = window_event_listener(ev::touchmove, move |ev| {
+        if split_dragging.get_untracked() {
+            if let Some(touch) = ev.touches().get(0) {
+                drag_split_to(touch.client_x() as f64);
+            }
+        }
+    });
+    let _split_mouse_up = window_event_listener(ev::mouseup, move |_| end_split_drag());
+    let _split_touch_end = window_event_listener(ev::touchend, move |_| end_split_drag());
+    let reset_split = move |_| {
+        split_ratio.set(DEFAULT_SPLIT_RATIO);
+        save_split_ratio(DEFAULT_SPLIT_RATIO);
+    };
+
     // Online status
     let connection_status = use_online_status();
     let is_online = create_memo(move |_| connection_status.get().is_online());
@@ -86,14 +209,23 @@ pub fn Editor() -> impl IntoView {
     let chat_collapsed = create_rw_signal(false);
     let chat_messages = create_rw_signal(Vec::<ChatMessage>::new());
     let processing_state = create_rw_signal(AiProcessingState::Ready);
+    let chat_cancel = create_rw_signal(None::<CancellationToken>);
     let current_iteration = create_rw_signal(0_usize);
     let max_iterations_signal = create_memo(move |_| ai_settings.get().max_iterations as usize);
+    // AI result awaiting Apply/Discard
+    let proposed_change = create_rw_signal(Option::<ProposedChange>::None);
+    let proposed_diff = Signal::derive(move || {
+        proposed_change.with(|change| change.as_ref().map(|c| c.diffs.clone()))
+    });
 
     // Image state
     let image_store = create_rw_signal(Option::<ImageStore>::None);
     let image_cache = create_rw_signal(ImageCache::new());
     let images_list = create_rw_signal(Vec::<ImageMetadata>::new());
 
+    // Shown once when images fall back to memory and won't survive a reload
+    let show_image_storage_warning = create_rw_signal(false);
+
     // Initialize image store on mount
     spawn_local(async move {
         match ImageStore::open().await {
@@ -102,11 +234,29 @@ pub fn Editor() -> impl IntoView {
                 if let Ok(images) = store.list_images().await {
                     // Pre-load images into cache
                     let mut cache = ImageCache::new();
-                    if let Err(e) = cache.preload_all(&store).await {
-                        web_sys::console::warn_1(&wasm_bindgen::JsValue::from_str(&format!(
-                            "Failed to preload images: {}",
-                            e
-                        )));
+                    let on_progress = move |loaded: usize, total: usize| {
+                        status_message.set(Some(format!("Loading images ({}/{})", loaded, total)));
+                    };
+                    // Replace the progress message with the outcome, then clear it
+                    let outcome = match cache
+                        .preload_all_with_progress(&store, Some(&on_progress))
+                        .await
+                    {
+                        Err(e) => {
+                            crate::log::warn(&format!("Failed to preload images: {}", e));
+                            Some(format!("Failed to load images: {}", e))
+                        }
+                        Ok(()) if cache.len() < images.len() => {
+                            Some(format!("Loaded {} of {} images", cache.len(), images.len()))
+                        }
+                        Ok(()) => None,
+                    };
+                    let shown = outcome.is_some() || !images.is_empty();
+                    if let Some(message) = outcome {
+                        status_message.set(Some(message));
+                    }
+                    if shown {
+                        clear_status_after_delay(status_message);
                     }
                     image_cache.set(cache);
                     images_list.set(images);
@@ -114,21 +264,66 @@ pub fn Editor() -> impl IntoView {
                 image_store.set(Some(store));
             }
             Err(e) => {
-                web_sys::console::error_1(&wasm_bindgen::JsValue::from_str(&format!(
-                    "Failed to open image store: {}",
+                // Keep uploads working for this session, e.g. in private browsing
+                crate::log::warn(&format!(
+                    "Failed to open image store, keeping images in memory: {}",
                     e
-                )));
+                ));
+                image_store.set(Some(ImageStore::in_memory()));
+                show_image_storage_warning.set(true);
             }
         }
     });
 
-    // Compile function (with image support)
+    // Font state
+    let font_store = create_rw_signal(Option::<FontStore>::None);
+    let font_cache = create_rw_signal(FontCache::new());
+    let fonts_list = create_rw_signal(Vec::<FontMetadata>::new());
+
+    // Extra Typst files saved with the project, keyed by path
+    let partials = create_rw_signal(BTreeMap::<String, String>::new());
+
+    // Recently compiled previews, so unchanged source isn't recompiled
+    let compile_cache = store_value(CompileCache::default());
+    // One world reused across compiles, keeping Typst's parse and layout caches warm
+    let compiler = store_value(IncrementalCompiler::new());
+    // Pages in the last successful compile, shown in the status bar
+    let page_count = create_rw_signal(None::<usize>);
+
+    // Compile function (with partial, image and font support)
     let compile = move || {
         let source = typst_source.get();
+        let files = partials.get();
         let cache = image_cache.get();
-        match VirtualWorld::compile_to_svg_with_images(&source, &cache) {
-            Ok(svg) => {
-                svg_output.set(Some(svg));
+        let fonts = font_cache.get();
+        // Partial contents are part of the cache key, since edits change the output
+        let partial_keys: Vec<String> = files
+            .iter()
+            .map(|(path, content)| format!("{}\n{}", path, content))
+            .collect();
+        // Referenced images are keyed by content, so replacing one recompiles
+        let image_keys = image_version_keys(&source, &cache);
+        let asset_ids: Vec<&String> = cache
+            .image_ids()
+            .into_iter()
+            .chain(fonts.font_ids())
+            .chain(&partial_keys)
+            .chain(&image_keys)
+            .collect();
+        let mut result = Err(Vec::new());
+        compile_cache.update_value(|compile_cache| {
+            result = compile_cache.get_or_compile(&source, &asset_ids, || {
+                let mut output = Err(Vec::new());
+                compiler.update_value(|compiler| {
+                    output = compiler.compile_preview(&source, &files, &cache, &fonts);
+                });
+                output
+            });
+        });
+        match result {
+            Ok(preview) => {
+                svg_output.set(Some(preview.svg));
+                page_count.set(Some(preview.page_count));
                 error.set(None);
             }
             Err(errors) => {
@@ -140,11 +335,36 @@ pub fn Editor() -> impl IntoView {
     // Initial compile
     compile();
 
+    // Load uploaded fonts on mount, recompiling once they are available
+    spawn_local(async move {
+        match FontStore::open().await {
+            Ok(store) => {
+                if let Ok(fonts) = store.list_fonts().await {
+                    let mut cache = FontCache::new();
+                    if let Err(e) = cache.preload_all(&store).await {
+                        crate::log::warn(&format!("Failed to preload fonts: {}", e));
+                    }
+                    let has_fonts = !cache.is_empty();
+                    font_cache.set(cache);
+                    fonts_list.set(fonts);
+                    if has_fonts {
+                        compile();
+                    }
+                }
+                font_store.set(Some(store));
+            }
+            Err(e) => {
+                crate::log::error(&format!("Failed to open font store: {}", e));
+            }
+        }
+    });
+
     // Debounce handle
     let debounce_handle = create_rw_signal(Option::<i32>::None);
 
     // Handle source changes with debounce
     let on_source_change = move |new_source: String| {
+        state.record_undo_grouped("typst");
         typst_source.set(new_source);
 
         if auto_preview.get() {
@@ -155,13 +375,21 @@ pub fn Editor() -> impl IntoView {
                 }
             }
 
+            // A zero delay compiles on every change
+            let delay = preview_delay_ms.get_untracked();
+            if delay == 0 {
+                debounce_handle.set(None);
+                compile();
+                return;
+            }
+
             // Set new timeout
             if let Some(window) = web_sys::window() {
                 let closure =
                     wasm_bindgen::closure::Closure::once(Box::new(compile) as Box<dyn FnOnce()>);
                 if let Ok(handle) = window.set_timeout_with_callback_and_timeout_and_arguments_0(
                     closure.as_ref().unchecked_ref(),
-                    300,
+                    delay as i32,
                 ) {
                     debounce_handle.set(Some(handle));
                 }
@@ -170,12 +398,107 @@ pub fn Editor() -> impl IntoView {
         }
     };
 
+    // Unsaved work from a previous session, offered for restore until dismissed
+    let pending_autosave = create_rw_signal(load_recoverable_autosave());
+    let autosave_handle = create_rw_signal(Option::<i32>::None);
+    // Set when edits were not autosaved because the banner was still showing
+    let edited_while_pending = store_value(false);
+    let snapshot = move || {
+        Autosave::new(
+            Project::from_source(project_name.get_untracked(), typst_source.get_untracked())
+                .with_id(project_id.get_untracked())
+                .with_partials(partials.get_untracked())
+                .with_ai_model(project_ai_model.get_untracked()),
+            content_data.get_untracked(),
+            template_source.get_untracked(),
+        )
+    };
+
+    // Autosave with debounce whenever the document or project name changes
+    create_effect(move |prev: Option<()>| {
+        typst_source.track();
+        partials.track();
+        content_data.track();
+        template_source.track();
+        project_name.track();
+        project_ai_model.track();
+
+        // Skip the initial run, and don't overwrite a snapshot awaiting restore
+        // (dismissing the banner saves what was skipped)
+        if prev.is_none() {
+            return;
+        }
+        if pending_autosave.get_untracked().is_some() {
+            edited_while_pending.set_value(true);
+            return;
+        }
+
+        let Some(window) = web_sys::window() else {
+            return;
+        };
+        if let Some(handle) = autosave_handle.get_untracked() {
+            window.clear_timeout_with_handle(handle);
+        }
+        let closure = wasm_bindgen::closure::Closure::once(Box::new(move || {
+            store_autosave(&snapshot());
+        }) as Box<dyn FnOnce()>);
+        if let Ok(handle) = window.set_timeout_with_callback_and_timeout_and_arguments_0(
+            closure.as_ref().unchecked_ref(),
+            AUTOSAVE_DELAY_MS,
+        ) {
+            autosave_handle.set(Some(handle));
+        }
+        closure.forget();
+    });
+
+    let on_restore_autosave = move |_| {
+        if let Some(autosave) = pending_autosave.get_untracked() {
+            state.record_undo();
+            project_name.set(autosave.project.metadata.name);
+            project_id.set(
+                autosave
+                    .project
+                    .metadata
+                    .id
+                    .unwrap_or_else(generate_project_id),
+            );
+            project_ai_model.set(autosave.project.metadata.ai_model);
+            typst_source.set(autosave.project.source);
+            state.active_template.set(None);
+            partials.set(autosave.project.partials);
+            if let Some(data) = autosave.content_data {
+                content_data.set(data);
+            }
+            if let Some(template) = autosave.template_source {
+                template_source.set(template);
+            }
+            pending_autosave.set(None);
+            edited_while_pending.set_value(false);
+            compile();
+            status_message.set(Some("Unsaved work restored".to_string()));
+            clear_status_after_delay(status_message);
+        }
+    };
+
+    let on_dismiss_autosave = move |_| {
+        clear_autosave();
+        pending_autosave.set(None);
+        // Edits made while the banner showed replace the dismissed snapshot
+        if edited_while_pending.get_value() {
+            edited_while_pending.set_value(false);
+            store_autosave(&snapshot());
+        }
+    };
+
     // Handle template selection - load both template source AND default JSON data
     let on_template_select = Callback::new(move |template_id: String| {
         // Find the template by ID
         if let Some(template) = TEMPLATES.iter().find(|t| t.id == template_id) {
+            state.record_undo();
+
             // Load the raw Typst source
             typst_source.set(template.source.to_string());
+            state.active_template.set(Some(template_id.clone()));
 
             // Load the default JSON data for this template
             let default_data = crate::data::default_data_for_template(&template_id);
@@ -202,25 +525,121 @@ pub fn Editor() -> impl IntoView {
 
     // Handle edit modal save
     let on_edit_save = Callback::new(move |(field_id, new_value): (String, String)| {
-        // Get current source and update the field
         let source = typst_source.get();
-        if let Some(updated_source) = update_field_in_source(&source, &field_id, &new_value) {
+        let template = template_source.get();
+        let mut data = content_data.get();
+
+        if is_rendered_from_data(&template, &data, &source)
+            && update_data_field(&mut data, &field_id, &new_value).is_ok()
+        {
+            // Edit the structured data and re-render the template
+            match TemplateEngine::render(&template, &data) {
+                Ok(rendered) => {
+                    state.record_undo();
+                    content_data.set(data);
+                    typst_source.set(rendered);
+                    compile();
+                }
+                Err(errors) => {
+                    error.set(Some(format!(
+                        "Template rendering error:\n{}",
+                        errors.join("\n")
+                    )));
+                }
+            }
+        } else if let Some(updated_source) = update_field_in_source(&source, &field_id, &new_value)
+        {
+            // Raw Typst source not backed by data: patch the link content directly
+            state.record_undo();
             typst_source.set(updated_source);
             compile();
         }
         show_edit_modal.set(None);
     });
 
+    // Handle undo - restore the snapshot taken before the last change
+    let undo = move || {
+        if state.undo() {
+            compile();
+            status_message.set(Some("Undid last change".to_string()));
+        } else {
+            status_message.set(Some("Nothing to undo".to_string()));
+        }
+        clear_status_after_delay(status_message);
+    };
+
+    // Ctrl+Z / Cmd+Z outside text fields, which keep their native undo
+    let _undo_listener = window_event_listener(ev::keydown, move |ev| {
+        if (ev.ctrl_key() || ev.meta_key())
+            && !ev.shift_key()
+            && ev.key().eq_ignore_ascii_case("z")
+            && !is_text_field_event(&ev)
+        {
+            ev.prevent_default();
+            undo();
+        }
+    });
+
+    // Recently opened and saved projects, listed newest first
+    let recent_store = create_rw_signal(Option::<RecentStore>::None);
+    let recent_projects = create_rw_signal(Vec::<RecentProject>::new());
+    spawn_local(async move {
+        match RecentStore::open().await {
+            Ok(store) => {
+                if let Ok(recent) = store.list(RECENT_PROJECTS_SHOWN).await {
+                    recent_projects.set(recent);
+                }
+                recent_store.set(Some(store));
+            }
+            Err(e) => {
+                crate::log::error(&format!("Failed to open recent projects store: {}", e));
+            }
+        }
+    });
+    let remember_project = move |project: Project| {
+        let Some(store) = recent_store.get_untracked() else {
+            return;
+        };
+        spawn_local(async move {
+            if let Err(e) = store.record(&RecentProject::new(project)).await {
+                crate::log::warn(&format!("Failed to remember project: {}", e));
+                return;
+            }
+            if let Ok(recent) = store.list(RECENT_PROJECTS_SHOWN).await {
+                recent_projects.set(recent);
+            }
+        });
+    };
+
+    // Replace the document with an opened project
+    let open_project = move |mut project: Project| {
+        state.record_undo();
+        project_id.set(project.ensure_id().to_string());
+        project_name.set(project.metadata.name);
+        project_ai_model.set(project.metadata.ai_model);
+        typst_source.set(project.source);
+        partials.set(project.partials);
+        state.active_template.set(None);
+        // Earlier AI exchanges were about a different document
+        state.conversation.update(ConversationHistory::clear);
+        compile();
+    };
+
     // Handle save
     let on_save = move |_| {
-        let project = Project::from_source(project_name.get(), typst_source.get());
+        let project = Project::from_source(project_name.get(), typst_source.get())
+            .with_id(project_id.get())
+            .with_partials(partials.get())
+            .with_ai_model(project_ai_model.get());
         match project.to_json_pretty() {
             Ok(json) => {
+                remember_project(project);
                 trigger_download(
                     &json,
                     &format!("{}.json", project_name.get()),
                     "application/json",
                 );
+                mark_saved();
                 let now = get_current_time();
                 last_saved.set(Some(now));
                 status_message.set(Some("Project saved!".to_string()));
@@ -234,24 +653,93 @@ pub fn Editor() -> impl IntoView {
 
     // Handle load
     let on_load = move |_| {
-        trigger_file_load(move |content| match Project::from_json(&content) {
-            Ok(project) => {
-                project_name.set(project.metadata.name);
-                typst_source.set(project.source);
-                compile();
-                status_message.set(Some("Project loaded!".to_string()));
-                clear_status_after_delay(status_message);
-            }
-            Err(e) => {
-                status_message.set(Some(format!("Load failed: {}", e)));
+        trigger_file_load(move |file| {
+            let loaded = match file {
+                LoadedFile::Json(content) => {
+                    Project::from_json(&content).map(|project| (project, Vec::new()))
+                }
+                LoadedFile::Zip(bytes) => from_zip_bundle(&bytes),
+            };
+            match loaded {
+                Ok((mut project, images)) => {
+                    // Older files have no ID; give them one before it becomes their key
+                    project.ensure_id();
+                    remember_project(project.clone());
+                    open_project(project);
+                    status_message.set(Some("Project loaded!".to_string()));
+                    clear_status_after_delay(status_message);
+
+                    // Bundled images keep their IDs so the source still resolves them
+                    if let Some(store) = image_store.get_untracked().filter(|_| !images.is_empty())
+                    {
+                        spawn_local(async move {
+                            let total = images.len();
+                            let mut restored = 0;
+                            for (metadata, data) in images {
+                                let data = match store.restore_image(&metadata, &data).await {
+                                    Ok(stored) => stored,
+                                    Err(e) => {
+                                        crate::log::warn(&format!(
+                                            "Failed to restore {}: {}",
+                                            metadata.filename, e
+                                        ));
+                                        continue;
+                                    }
+                                };
+                                let ext =
+                                    crate::images::extension_from_mime_type(&metadata.mime_type);
+                                image_cache.update(|cache| {
+                                    cache.add(metadata.id.clone(), data, ext.to_string());
+                                });
+                                images_list.update(|list| {
+                                    list.retain(|existing| existing.id != metadata.id);
+                                    list.insert(0, metadata);
+                                });
+                                restored += 1;
+                            }
+                            compile();
+                            status_message.set(Some(format!(
+                                "Project loaded with {}/{} image(s)",
+                                restored, total
+                            )));
+                            clear_status_after_delay(status_message);
+                        });
+                    }
+                }
+                Err(e) => {
+                    status_message.set(Some(format!("Load failed: {}", e)));
+                }
             }
         });
     };
 
+    // Handle picking a project from the "Recent" menu
+    let on_open_recent = move |ev: leptos::ev::Event| {
+        let select = event_target::<web_sys::HtmlSelectElement>(&ev);
+        let Ok(index) = select.value().parse::<usize>() else {
+            return;
+        };
+        select.set_value("");
+
+        let Some(entry) = recent_projects.with_untracked(|recent| recent.get(index).cloned())
+        else {
+            return;
+        };
+        status_message.set(Some(format!("Opened {}", entry.project.metadata.name)));
+        clear_status_after_delay(status_message);
+        remember_project(entry.project.clone());
+        open_project(entry.project);
+    };
+
     // Handle PDF export
     let on_export_pdf = move |_| {
-        let source = typst_source.get();
-        match pdf_data_url(&source) {
+        let mut world = VirtualWorld::new(&typst_source.get());
+        world.add_partials(&partials.get());
+        match pdf_data_url_from_world(
+            &world,
+            PdfMeta::with_title(project_name.get()),
+            PdfStandard::default(),
+        ) {
             Ok(data_url) => {
                 trigger_download_url(&data_url, &format!("{}.pdf", project_name.get()));
                 status_message.set(Some("PDF exported!".to_string()));
@@ -263,8 +751,184 @@ pub fn Editor() -> impl IntoView {
         }
     };
 
+    // Handle PNG copy - rasterizes the first page with the preview's assets
+    let on_copy_png = move |_| {
+        let mut world = VirtualWorld::new(&typst_source.get());
+        world.add_partials(&partials.get());
+        image_cache.get().populate_world(&mut world);
+        font_cache.get().populate_world(&mut world);
+        match png_bytes_from_world(&world, COPY_SCALE) {
+            Ok(bytes) => spawn_local(async move {
+                match copy_png_to_clipboard(&bytes).await {
+                    Ok(()) => {
+                        status_message.set(Some("Page copied to clipboard as PNG".to_string()));
+                        clear_status_after_delay(status_message);
+                    }
+                    Err(e) => status_message.set(Some(format!("Copy failed: {}", e))),
+                }
+            }),
+            Err(e) => status_message.set(Some(format!("Copy failed: {}", e))),
+        }
+    };
+
+    // Handle social-size PNG export - re-lays out the page at the preset size
+    let on_export_preset = move |ev: leptos::ev::Event| {
+        let select = event_target::<web_sys::HtmlSelectElement>(&ev);
+        let Some(preset) = ExportPreset::from_key(&select.value()) else {
+            return;
+        };
+        select.set_value("");
+
+        let mut world = VirtualWorld::new(&apply_export_preset(&typst_source.get(), preset));
+        world.add_partials(&partials.get());
+        image_cache.get().populate_world(&mut world);
+        font_cache.get().populate_world(&mut world);
+        match png_bytes_for_preset(&world, preset) {
+            Ok(export) => {
+                use base64::Engine;
+                let data_url = format!(
+                    "data:image/png;base64,{}",
+                    base64::engine::general_purpose::STANDARD.encode(&export.bytes)
+                );
+                trigger_download_url(
+                    &data_url,
+                    &format!("{}-{}.png", project_name.get(), preset.key()),
+                );
+                match export.warning {
+                    Some(warning) => status_message.set(Some(warning)),
+                    None => {
+                        status_message.set(Some(format!("Exported {}", preset.label())));
+                        clear_status_after_delay(status_message);
+                    }
+                }
+            }
+            Err(e) => status_message.set(Some(format!("Export failed: {}", e))),
+        }
+    };
+
+    // Handle page size/orientation presets
+    let on_page_setup = Callback::new(move |page: PageSetup| {
+        let label = page.label();
+        state.set_page_setup(page);
+        compile();
+        status_message.set(Some(format!("Page set to {}", label)));
+        clear_status_after_delay(status_message);
+    });
+    let page_setup = Signal::derive(move || content_data.with(SlickSheetData::page_or_default));
+
+    // Start the content over from the active template's defaults
+    let on_reset_content = move |_| {
+        let confirmed = web_sys::window()
+            .and_then(|window| {
+                window
+                    .confirm_with_message(
+                        "Replace the content with this template's defaults? You can undo this.",
+                    )
+                    .ok()
+            })
+            .unwrap_or(false);
+        if confirmed && state.reset_content() {
+            compile();
+            status_message.set(Some("Content reset to template defaults".to_string()));
+            clear_status_after_delay(status_message);
+        }
+    };
+
+    // Handle bundle export - source, data, project file and referenced images
+    let on_export_bundle = move |_| {
+        use base64::Engine;
+
+        let source = typst_source.get();
+        let project_partials = partials.get();
+        let is_referenced = |id: &str| {
+            source.contains(id)
+                || project_partials
+                    .values()
+                    .any(|partial| partial.contains(id))
+        };
+        let cache = image_cache.get();
+        let images: Vec<(ImageMetadata, Vec<u8>)> = images_list
+            .get()
+            .into_iter()
+            .filter(|metadata| is_referenced(&metadata.id))
+            .filter_map(|metadata| {
+                let bytes = cache.get(&metadata.id)?.as_slice().to_vec();
+                Some((metadata, bytes))
+            })
+            .collect();
+
+        let project = Project::from_source(project_name.get(), source.clone())
+            .with_id(project_id.get())
+            .with_partials(project_partials.clone())
+            .with_ai_model(project_ai_model.get())
+            .with_content(content_data.get(), template_source.get());
+        match zip_bundle(&project, &images) {
+            Ok(bytes) => {
+                let data_url = format!(
+                    "data:application/zip;base64,{}",
+                    base64::engine::general_purpose::STANDARD.encode(&bytes)
+                );
+                trigger_download_url(&data_url, &format!("{}.zip", project_name.get()));
+                status_message.set(Some(format!(
+                    "Bundle exported with {} image(s)!",
+                    images.len()
+                )));
+                clear_status_after_delay(status_message);
+            }
+            Err(e) => {
+                status_message.set(Some(format!("Export failed: {}", e)));
+            }
+        }
+    };
+
+    // Handle .typ export - the source is already rendered from Content + Template
+    let on_export_typst = move |_| {
+        trigger_download(
+            &typst_source.get(),
+            &typst_filename(&project_name.get()),
+            "text/plain",
+        );
+        status_message.set(Some("Typst source exported!".to_string()));
+        clear_status_after_delay(status_message);
+    };
+
+    // Handle HTML export - wraps the current preview, which already has images
+    let on_export_html = move |_| match svg_output.get() {
+        Some(svg) => {
+            let html = html_from_svg(&svg, &project_name.get());
+            trigger_download(&html, &format!("{}.html", project_name.get()), "text/html");
+            status_message.set(Some("HTML exported!".to_string()));
+            clear_status_after_delay(status_message);
+        }
+        None => {
+            status_message.set(Some(
+                "Export failed: fix compile errors before exporting".to_string(),
+            ));
+        }
+    };
+
     // Handle AI chat send - uses tool-based editing (JSON + Template) with retry
     let on_chat_send = Callback::new(move |prompt: String| {
+        // Offline: hold the prompt until the connection returns
+        if !is_online.get_untracked() {
+            let queued = state
+                .pending_prompts
+                .try_update(|queue| queue.enqueue(prompt))
+                .flatten();
+            if queued.is_none() {
+                chat_messages.update(|msgs| {
+                    msgs.push(ChatMessage::error(format!(
+                        "Offline queue is full ({} requests). Cancel one or wait to reconnect.",
+                        PENDING_PROMPT_LIMIT
+                    )));
+                });
+            }
+            return;
+        }
+
+        // A new request supersedes any proposal still awaiting review
+        proposed_change.set(None);
+
         // Add user message to history
         chat_messages.update(|msgs| {
             msgs.push(ChatMessage::user(prompt.clone()));
@@ -274,13 +938,19 @@ pub fn Editor() -> impl IntoView {
         processing_state.set(AiProcessingState::Generating);
         current_iteration.set(1);
 
-        // Get settings and current state
-        let settings = ai_settings.get();
+        // Get settings and current state, honouring the project's model override
+        let mut settings = ai_settings.get();
+        settings.model = effective_model();
         let current_data = content_data.get();
         let current_template = template_source.get();
         let max_retries = settings.max_iterations as usize;
+        let retry_base_delay = std::time::Duration::from_millis(settings.retry_delay_ms.into());
         let available_images = images_list.get();
         let current_image_cache = image_cache.get();
+        let current_font_cache = font_cache.get();
+        let current_partials = partials.get();
+        let history = state.conversation.get_untracked();
+        let active_template = state.active_template.get_untracked();
 
         // Validate API key before starting
         if settings.api_key.trim().is_empty() {
@@ -294,21 +964,26 @@ pub fn Editor() -> impl IntoView {
             return;
         }
 
+        // Token for the Stop button to cancel this request
+        let cancel = CancellationToken::new();
+        chat_cancel.set(Some(cancel.clone()));
+
         // Spawn async task for AI processing
         spawn_local(async move {
             // Log for debugging
-            web_sys::console::log_1(&wasm_bindgen::JsValue::from_str(&format!(
+            crate::log::debug(&format!(
                 "Starting AI request (tool-based) with model: {}",
                 settings.model
-            )));
+            ));
 
-            let config = OpenRouterConfig::with_key(settings.api_key.clone());
+            let config = OpenRouterConfig {
+                temperature: settings.temperature,
+                top_p: settings.top_p,
+                max_tokens: settings.max_tokens,
+                ..openrouter_config(settings.api_key.clone())
+            };
             let client = OpenRouterClient::new(config);
 
-            // Serialize current JSON data
-            let current_json =
-                serde_json::to_string_pretty(&current_data).unwrap_or_else(|_| "{}".to_string());
-
             // Build available images info for the prompt
             let images_info = if available_images.is_empty() {
                 "No images available. User can upload images in the Images tab.".to_string()
@@ -333,6 +1008,13 @@ pub fn Editor() -> impl IntoView {
                             full_path, img.filename, size_str
                         );
 
+                        // Warn about formats Typst cannot render directly
+                        if !img.is_typst_compatible() {
+                            desc.push_str(
+                                "\n    Warning: format not renderable by Typst, needs conversion to PNG/JPEG before use",
+                            );
+                        }
+
                         // Add alt description if available
                         if let Some(alt) = &img.alt_description {
                             desc.push_str(&format!("\n    Alt: \"{}\"", alt));
@@ -354,12 +1036,10 @@ pub fn Editor() -> impl IntoView {
                 format!("Available images:\n{}", image_list.join("\n"))
             };
 
-            // Build the system prompt for content editing
-            let system_prompt = format!(
-                r##"You are editing a marketing document. You receive:
-1. TEMPLATE - Shows the layout structure (read-only)
-2. JSON - The content you will edit
-3. AVAILABLE IMAGES - Images the user has uploaded
+            // Editing rules for the tool loop, with the images the user has uploaded
+            let notes = format!(
+                r##"You are editing a marketing document. Edit the JSON content with the tools.
+Only change the template when the request needs a different layout.
 
 CRITICAL RULES:
 
@@ -406,13 +1086,9 @@ CRITICAL RULES:
    BAD: "Expertise in scalable solutions and innovative approaches"
    GOOD: "Built the core rendering engine in Rust"
 
-6. OUTPUT FORMAT
-   Return ONLY valid JSON. No markdown, no explanation.
-   Start with {{ end with }}
-
 {}
 
-EXAMPLE - Adding a logo image to a design:
+EXAMPLE - JSON adding a logo image to a design:
 {{
   "title": "Company Name",
   "subtitle": "Our Amazing Product",
@@ -423,193 +1099,151 @@ EXAMPLE - Adding a logo image to a design:
 }}"##,
                 images_info
             );
+            let mut context = vec![AiChatMessage::system(system_prompt_with_hint(
+                &notes,
+                active_template.as_deref(),
+            ))];
+            // Replay recent exchanges so follow-ups refine them
+            for turn in history.turns() {
+                context.push(AiChatMessage::user(turn.prompt.clone()));
+                context.push(AiChatMessage::assistant(turn.response.clone()));
+            }
+
+            let agent_config = AgentConfig {
+                model: settings.model.clone(),
+                ..AgentConfig::default()
+            };
+
+            // Compile with the project's partials, images and fonts
+            let compile = |code: &str| {
+                processing_state.set(AiProcessingState::Compiling);
+                let result = VirtualWorld::compile_to_svg_with_assets(
+                    code,
+                    &current_partials,
+                    &current_image_cache,
+                    &current_font_cache,
+                )
+                .map_err(|errors| errors.join(", "));
+                processing_state.set(AiProcessingState::Generating);
+                result
+            };
 
-            // Retry loop
+            // Retry loop over whole tool runs that end without a compiling document
             let mut attempt = 0;
             let mut last_error: Option<String> = None;
-            let mut last_response: Option<String> = None;
 
             while attempt < max_retries {
+                // Back off before retrying so a briefly overloaded model can recover
+                if attempt > 0 {
+                    let delay = backoff_delay(retry_base_delay, attempt as u32);
+                    for (seconds, wait) in retry_countdown(delay) {
+                        if cancel.is_cancelled() {
+                            break;
+                        }
+                        processing_state.set(AiProcessingState::Retrying { seconds });
+                        gloo_timers::future::sleep(wait).await;
+                    }
+                    if cancel.is_cancelled() {
+                        chat_messages.update(|msgs| {
+                            msgs.push(ChatMessage::system("Stopped."));
+                        });
+                        processing_state.set(AiProcessingState::Ready);
+                        break;
+                    }
+                    processing_state.set(AiProcessingState::Generating);
+                }
+
                 attempt += 1;
                 current_iteration.set(attempt);
 
-                // Build the user prompt - include template, JSON, and images for context
-                let user_prompt = if let Some(ref err) = last_error {
-                    format!(
-                        r#"CURRENT TEMPLATE (read-only, for context):
-```
-{}
-```
-
-CURRENT JSON (edit this):
-```json
-{}
-```
-
-{}
-
-REQUEST: {}
-
-ERROR FROM PREVIOUS ATTEMPT: {}
-Previous response was: {}
-
-Fix the error and return ONLY valid JSON (starting with {{ and ending with }}):"#,
-                        current_template,
-                        current_json,
-                        images_info,
-                        prompt,
-                        err,
-                        last_response.as_deref().unwrap_or("N/A")
+                let mut agent = AgentLoop::new(client.clone(), agent_config.clone())
+                    .with_context(context.clone());
+                let result = agent
+                    .run_with_tools(
+                        &prompt,
+                        current_data.clone(),
+                        &current_template,
+                        compile,
+                        &cancel,
                     )
-                } else {
-                    format!(
-                        r#"CURRENT TEMPLATE (read-only, for context):
-```
-{}
-```
-
-CURRENT JSON (edit this):
-```json
-{}
-```
-
-{}
-
-REQUEST: {}
-
-Return the updated JSON:"#,
-                        current_template, current_json, images_info, prompt
-                    )
-                };
-
-                // Send to LLM
-                let messages = vec![
-                    AiChatMessage::system(system_prompt.to_string()),
-                    AiChatMessage::user(user_prompt),
-                ];
-
-                match client.chat(&settings.model, messages).await {
-                    Ok(response) => {
-                        last_response = Some(response.clone());
-
-                        // Try to parse the response as JSON
-                        let json_str = response.trim();
-                        // Remove markdown code fences if present
-                        let json_str = json_str
-                            .strip_prefix("```json")
-                            .or_else(|| json_str.strip_prefix("```"))
-                            .unwrap_or(json_str);
-                        let json_str = json_str.strip_suffix("```").unwrap_or(json_str).trim();
-
-                        match serde_json::from_str::<crate::data::SlickSheetData>(json_str) {
-                            Ok(new_data) => {
-                                // Validate the data
-                                if let Err(errors) = crate::data::validate_schema(&new_data) {
-                                    let error_msg = errors
-                                        .iter()
-                                        .map(|e| e.to_string())
-                                        .collect::<Vec<_>>()
-                                        .join(", ");
-                                    last_error = Some(format!("Invalid data: {}", error_msg));
-                                    chat_messages.update(|msgs| {
-                                        msgs.push(ChatMessage::assistant(format!(
-                                            "Attempt {}/{}: Validation error, retrying...",
-                                            attempt, max_retries
-                                        )));
-                                    });
-                                    continue;
-                                }
-
-                                // Render the template with new data
-                                match TemplateEngine::render(&current_template, &new_data) {
-                                    Ok(rendered_typst) => {
-                                        // Try to compile (use image cache for image support)
-                                        processing_state.set(AiProcessingState::Compiling);
-                                        match VirtualWorld::compile_to_svg_with_images(
-                                            &rendered_typst,
-                                            &current_image_cache,
-                                        ) {
-                                            Ok(svg) => {
-                                                // Success! Update all the signals
-                                                content_data.set(new_data);
-                                                typst_source.set(rendered_typst);
-                                                svg_output.set(Some(svg));
-                                                error.set(None);
-
-                                                // Switch to Content tab so user sees their data
-                                                active_tab.set(EditorTab::Content);
-
-                                                processing_state.set(AiProcessingState::Complete);
-                                                chat_messages.update(|msgs| {
-                                                    msgs.push(ChatMessage::assistant(format!(
-                                                        "Done! Updated the content (attempt {}).",
-                                                        attempt
-                                                    )));
-                                                });
-                                                // Success - exit retry loop
-                                                break;
-                                            }
-                                            Err(compile_errors) => {
-                                                last_error = Some(format!(
-                                                    "Compilation failed: {}",
-                                                    compile_errors.join(", ")
-                                                ));
-                                                chat_messages.update(|msgs| {
-                                                    msgs.push(ChatMessage::assistant(format!(
-                                                        "Attempt {}/{}: Compilation error, retrying...", attempt, max_retries
-                                                    )));
-                                                });
-                                                continue;
-                                            }
-                                        }
-                                    }
-                                    Err(render_errors) => {
-                                        last_error = Some(format!(
-                                            "Template render failed: {}",
-                                            render_errors.join(", ")
-                                        ));
-                                        chat_messages.update(|msgs| {
-                                            msgs.push(ChatMessage::assistant(format!(
-                                                "Attempt {}/{}: Template error, retrying...",
-                                                attempt, max_retries
-                                            )));
-                                        });
-                                        continue;
-                                    }
-                                }
-                            }
-                            Err(parse_err) => {
-                                // Log the raw response for debugging
-                                web_sys::console::log_1(&wasm_bindgen::JsValue::from_str(
-                                    &format!(
-                                        "Failed to parse AI response as JSON: {}\nResponse was: {}",
-                                        parse_err, json_str
-                                    ),
-                                ));
-                                last_error = Some(format!("Invalid JSON: {}", parse_err));
-                                chat_messages.update(|msgs| {
-                                    msgs.push(ChatMessage::assistant(format!(
-                                        "Attempt {}/{}: JSON parse error, retrying...",
-                                        attempt, max_retries
-                                    )));
-                                });
-                                continue;
-                            }
-                        }
+                    .await;
+
+                match result {
+                    AgentResult::ToolSuccess {
+                        data,
+                        template,
+                        code,
+                        svg,
+                        iterations,
+                        usage,
+                        ..
+                    } => {
+                        // Success! Hold the result for review
+                        let diffs = diff(&current_data, &data);
+                        let response = serde_json::to_string_pretty(&data)
+                            .unwrap_or_else(|_| "{}".to_string());
+                        proposed_change.set(Some(ProposedChange {
+                            data: *data,
+                            template,
+                            typst: code,
+                            svg,
+                            diffs,
+                            prompt: prompt.clone(),
+                            response,
+                        }));
+                        last_error = None;
+
+                        processing_state.set(AiProcessingState::Complete);
+                        chat_messages.update(|msgs| {
+                            msgs.push(
+                                ChatMessage::assistant(format!(
+                                    "Done! Review the proposed changes below (attempt {}, {} model turns).",
+                                    attempt, iterations
+                                ))
+                                .with_usage(usage),
+                            );
+                        });
+                        break;
+                    }
+                    AgentResult::MaxIterationsReached {
+                        last_error: run_error,
+                        ..
+                    } => {
+                        let run_error = run_error
+                            .unwrap_or_else(|| "No compiling document was produced".to_string());
+                        chat_messages.update(|msgs| {
+                            msgs.push(ChatMessage::assistant(format!(
+                                "Attempt {}/{}: {}, retrying...",
+                                attempt, max_retries, run_error
+                            )));
+                        });
+                        last_error = Some(run_error);
                     }
-                    Err(err) => {
+                    AgentResult::Error(_) if cancel.is_cancelled() => {
+                        chat_messages.update(|msgs| {
+                            msgs.push(ChatMessage::system("Stopped."));
+                        });
+                        processing_state.set(AiProcessingState::Ready);
+                        break;
+                    }
+                    AgentResult::Error(message) => {
                         // API error - don't retry, just fail
                         chat_messages.update(|msgs| {
-                            msgs.push(ChatMessage::error(format!("API Error: {}", err)));
+                            msgs.push(ChatMessage::error(message));
                         });
                         processing_state.set(AiProcessingState::Failed);
                         break;
                     }
+                    AgentResult::Success { .. } => {
+                        unreachable!("tool runs finish with ToolSuccess")
+                    }
                 }
             }
 
+            chat_cancel.set(None);
+
             // If we exhausted all retries without success
-            if attempt >= max_retries && last_error.is_some() {
+            if attempt >= max_retries && last_error.is_some() && !cancel.is_cancelled() {
                 chat_messages.update(|msgs| {
                     msgs.push(ChatMessage::error(format!(
                         "Failed after {} attempts. Last error: {}",
@@ -634,6 +1268,71 @@ Return the updated JSON:"#,
         });
     });
 
+    // Handle Stop button - cancel the in-flight AI request
+    let on_chat_stop = Callback::new(move |_: ()| {
+        if let Some(cancel) = chat_cancel.get_untracked() {
+            cancel.cancel();
+        }
+    });
+
+    // Replay prompts queued while offline, one at a time, once reconnected.
+    // Waits for any proposal under review so each request builds on the last.
+    create_effect(move |_| {
+        if !is_online.get()
+            || processing_state.get().is_processing()
+            || proposed_change.with(Option::is_some)
+        {
+            return;
+        }
+        let next = state
+            .pending_prompts
+            .try_update(PendingPrompts::pop_next)
+            .flatten();
+        if let Some(pending) = next {
+            untrack(move || on_chat_send.call(pending.prompt));
+        }
+    });
+
+    // Drop a prompt queued while offline
+    let on_cancel_pending = Callback::new(move |id: u64| {
+        state.pending_prompts.update(|queue| {
+            queue.cancel(id);
+        });
+    });
+    let pending_prompts = Signal::derive(move || {
+        state
+            .pending_prompts
+            .with(|queue| queue.iter().cloned().collect::<Vec<_>>())
+    });
+
+    // Apply the reviewed AI proposal
+    let on_apply_proposal = Callback::new(move |_: ()| {
+        let Some(change) = proposed_change.get_untracked() else {
+            return;
+        };
+        proposed_change.set(None);
+        state.record_undo();
+        // Only accepted exchanges are replayed as context for follow-ups
+        state
+            .conversation
+            .update(|history| history.push(change.prompt, change.response));
+        content_data.set(change.data);
+        template_source.set(change.template);
+        typst_source.set(change.typst);
+        svg_output.set(Some(change.svg));
+        error.set(None);
+
+        // Switch to Content tab so user sees their data
+        active_tab.set(EditorTab::Content);
+        chat_messages.update(|msgs| msgs.push(ChatMessage::system("Changes applied.")));
+    });
+
+    // Drop the AI proposal, leaving the document untouched
+    let on_discard_proposal = Callback::new(move |_: ()| {
+        proposed_change.set(None);
+        chat_messages.update(|msgs| msgs.push(ChatMessage::system("Changes discarded.")));
+    });
+
     // Handle image upload success
     let on_image_upload = Callback::new(move |metadata: ImageMetadata| {
         // Add to images list
@@ -665,6 +1364,89 @@ Return the updated JSON:"#,
         clear_status_after_delay(status_message);
     });
 
+    // Handle regenerating an AI image from its stored prompt
+    let regenerating_image = create_rw_signal(Option::<String>::None);
+    let on_image_regenerate = Callback::new(move |original: ImageMetadata| {
+        let Some(prompt) = original.generation_prompt.clone() else {
+            return;
+        };
+        let api_key = ai_settings.get_untracked().api_key;
+        if api_key.trim().is_empty() {
+            on_image_error.call(
+                "No API key configured. Please add your OpenRouter API key in Settings."
+                    .to_string(),
+            );
+            return;
+        }
+
+        regenerating_image.set(Some(original.id.clone()));
+        spawn_local(async move {
+            let result = generate_and_store(
+                api_key,
+                image_store.get_untracked(),
+                &prompt,
+                &GenerateOptions::default(),
+                Some(original.id.clone()),
+            )
+            .await;
+            regenerating_image.set(None);
+            match result {
+                Ok(metadata) => {
+                    on_image_upload.call(metadata);
+                    status_message.set(Some(format!("Regenerated: {}", original.filename)));
+                }
+                Err(e) => on_image_error.call(e),
+            }
+        });
+    });
+
+    // Describe an image from its pixels and store the result as alt text
+    let describing_image = create_rw_signal(Option::<String>::None);
+    let on_image_describe = Callback::new(move |metadata: ImageMetadata| {
+        let api_key = ai_settings.get_untracked().api_key;
+        if api_key.trim().is_empty() {
+            on_image_error.call(
+                "No API key configured. Please add your OpenRouter API key in Settings."
+                    .to_string(),
+            );
+            return;
+        }
+        let Some(store) = image_store.get_untracked() else {
+            on_image_error.call("Image store not initialized".to_string());
+            return;
+        };
+
+        describing_image.set(Some(metadata.id.clone()));
+        spawn_local(async move {
+            let config = openrouter_config(api_key);
+            let result = async {
+                let bytes = store
+                    .get_image_data(&metadata.id)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                let alt = generate_alt_for_upload(&config, &bytes, &metadata.mime_type).await?;
+                store
+                    .set_alt_description(&metadata.id, alt)
+                    .await
+                    .map_err(|e| e.to_string())
+            }
+            .await;
+            describing_image.set(None);
+            match result {
+                Ok(updated) => {
+                    images_list.update(|list| {
+                        if let Some(entry) = list.iter_mut().find(|entry| entry.id == updated.id) {
+                            *entry = updated.clone();
+                        }
+                    });
+                    status_message.set(Some(format!("Described: {}", updated.filename)));
+                    clear_status_after_delay(status_message);
+                }
+                Err(e) => on_image_error.call(format!("Describe failed: {}", e)),
+            }
+        });
+    });
+
     // Handle image selection (copy ID to clipboard)
     let on_image_select = Callback::new(move |metadata: ImageMetadata| {
         let id = metadata.id.clone();
@@ -673,6 +1455,14 @@ Return the updated JSON:"#,
         clear_status_after_delay(status_message);
     });
 
+    // Copy a ready-to-paste #image(...) call for the Typst tab
+    let on_image_copy_snippet = Callback::new(move |metadata: ImageMetadata| {
+        let snippet = image_snippet(&metadata.id, &metadata.mime_type);
+        copy_to_clipboard(&snippet);
+        status_message.set(Some(format!("Copied: {}", snippet)));
+        clear_status_after_delay(status_message);
+    });
+
     // Handle image deletion
     let on_image_delete = Callback::new(move |id: String| {
         let id_clone = id.clone();
@@ -702,6 +1492,52 @@ Return the updated JSON:"#,
         }
     });
 
+    // Handle font upload success
+    let on_font_upload = Callback::new(move |(metadata, data): (FontMetadata, Vec<u8>)| {
+        fonts_list.update(|list| list.insert(0, metadata.clone()));
+        font_cache.update(|cache| cache.add(metadata.id.clone(), data));
+        compile();
+        status_message.set(Some(format!("Font added: {}", metadata.family_label())));
+        clear_status_after_delay(status_message);
+    });
+
+    // Recompile when partials change, following the auto-preview setting
+    let on_partials_change = Callback::new(move |_: ()| {
+        if auto_preview.get_untracked() {
+            compile();
+        }
+    });
+
+    // Handle font upload error (including unparseable files)
+    let on_font_error = Callback::new(move |err: String| {
+        status_message.set(Some(format!("Font error: {}", err)));
+        clear_status_after_delay(status_message);
+    });
+
+    // Handle font deletion
+    let on_font_delete = Callback::new(move |id: String| {
+        if let Some(store) = font_store.get() {
+            spawn_local(async move {
+                if let Err(e) = store.delete_font(&id).await {
+                    status_message.set(Some(format!("Delete failed: {}", e)));
+                } else {
+                    fonts_list.update(|list| list.retain(|font| font.id != id));
+                    font_cache.update(|cache| cache.remove(&id));
+                    compile();
+                    status_message.set(Some("Font deleted".to_string()));
+                }
+                clear_status_after_delay(status_message);
+            });
+        }
+    });
+
+    // Clicking a compile error opens the Typst source at its line
+    let goto_line = create_rw_signal(Option::<usize>::None);
+    let on_error_line = Callback::new(move |line: usize| {
+        active_tab.set(EditorTab::Typst);
+        goto_line.set(Some(line));
+    });
+
     // Handle preview click for cmd:// links
     let on_preview_click = move |ev: web_sys::MouseEvent| {
         if let Some(target) = ev.target() {
@@ -715,31 +1551,23 @@ Return the updated JSON:"#,
                                 ev.prevent_default();
                                 // Get current value for the field
                                 let source = typst_source.get();
-                                let field_id = match &cmd {
-                                    EditCommand::Title => "title",
-                                    EditCommand::Subtitle => "subtitle",
-                                    EditCommand::Body => "body",
-                                    EditCommand::Image => "image",
-                                    EditCommand::Metadata(key) => {
-                                        // Return early with the metadata key
-                                        let meta_field_id = format!("meta/{}", key);
-                                        let current_value =
-                                            extract_field_value(&source, &meta_field_id);
-                                        show_edit_modal.set(Some(EditFieldData {
-                                            label: get_field_label(&meta_field_id),
-                                            value: current_value,
-                                            field_type: get_field_type(&meta_field_id),
-                                            field_id: meta_field_id,
-                                        }));
-                                        return;
-                                    }
-                                };
-                                let current_value = extract_field_value(&source, field_id);
+                                let data = content_data.get();
+                                let field_id = cmd.field_id();
+                                let current_value = if is_rendered_from_data(
+                                    &template_source.get(),
+                                    &data,
+                                    &source,
+                                ) {
+                                    get_data_field(&data, &field_id)
+                                } else {
+                                    None
+                                }
+                                .unwrap_or_else(|| extract_field_value(&source, &field_id));
                                 show_edit_modal.set(Some(EditFieldData {
-                                    label: get_field_label(field_id),
+                                    label: get_field_label(&field_id),
                                     value: current_value,
-                                    field_type: get_field_type(field_id),
-                                    field_id: field_id.to_string(),
+                                    field_type: get_field_type(&field_id),
+                                    field_id,
                                 }));
                                 return;
                             }
@@ -767,10 +1595,26 @@ Return the updated JSON:"#,
                     <button
                         class="btn btn-secondary"
                         on:click=on_load
-                        title="Open project"
+                        title="Open project (.json) or bundle (.zip)"
                     >
                         "Open"
                     </button>
+                    <select
+                        class="page-setup-select"
+                        title="Open a recently opened or saved project"
+                        on:change=on_open_recent
+                        disabled=move || recent_projects.with(Vec::is_empty)
+                    >
+                        <option value="" selected=true>"Recent"</option>
+                        {move || recent_projects
+                            .get()
+                            .into_iter()
+                            .enumerate()
+                            .map(|(index, entry)| view! {
+                                <option value=index.to_string()>{entry.project.metadata.name}</option>
+                            })
+                            .collect_view()}
+                    </select>
                     <button
                         class="btn btn-secondary"
                         on:click=on_save
@@ -785,6 +1629,62 @@ Return the updated JSON:"#,
                     >
                         "Export PDF"
                     </button>
+                    <button
+                        class="btn btn-secondary"
+                        on:click=on_export_html
+                        title="Export as standalone HTML"
+                    >
+                        "Export HTML"
+                    </button>
+                    <button
+                        class="btn btn-secondary"
+                        on:click=on_copy_png
+                        title="Copy the first page to the clipboard as a PNG image"
+                    >
+                        "Copy PNG"
+                    </button>
+                    <select
+                        class="page-setup-select"
+                        title="Export the first page as a PNG at a social media size"
+                        on:change=on_export_preset
+                    >
+                        <option value="" selected=true>"Export for social..."</option>
+                        {ExportPreset::ALL
+                            .into_iter()
+                            .map(|preset| view! { <option value=preset.key()>{preset.label()}</option> })
+                            .collect_view()}
+                    </select>
+                    <button
+                        class="btn btn-secondary"
+                        on:click=on_export_typst
+                        title="Export the rendered Typst source as a .typ file"
+                    >
+                        "Export .typ"
+                    </button>
+                    <button
+                        class="btn btn-secondary"
+                        on:click=on_export_bundle
+                        title="Export source, data and images as a .zip"
+                    >
+                        "Export Bundle"
+                    </button>
+                    <PageSetupSelect page=page_setup on_select=on_page_setup />
+                    <button
+                        class="btn btn-secondary"
+                        on:click=on_reset_content
+                        disabled=move || state.active_template.with(Option::is_none)
+                        title="Replace the content with the current template's default data"
+                    >
+                        "Reset Content"
+                    </button>
+                    <button
+                        class="btn btn-secondary"
+                        on:click=move |_| undo()
+                        disabled=move || state.undo_stack.with(|stack| stack.is_empty())
+                        title="Undo last change (Ctrl+Z)"
+                    >
+                        "Undo"
+                    </button>
                     <span class="separator" />
                     <label class="toggle-label">
                         <input
@@ -804,19 +1704,64 @@ Return the updated JSON:"#,
                         "Refresh"
                     </button>
                     <span class="separator" />
+                    <button
+                        class="btn btn-secondary btn-icon"
+                        on:click=toggle_theme
+                        title=move || format!("Switch to {} theme", theme.get().toggled().as_str())
+                    >
+                        {move || match theme.get() {
+                            Theme::Dark => "\u{2600}",
+                            Theme::Light => "\u{263E}",
+                        }}
+                    </button>
                     <button
                         class="btn btn-secondary btn-icon"
                         on:click=move |_| show_settings_modal.set(true)
-                        title="AI Settings"
+                        title="Settings"
                     >
                         "\u{2699}"
                     </button>
                 </div>
             </header>
 
+            {move || pending_autosave.get().map(|autosave| view! {
+                <div class="autosave-banner">
+                    <span>
+                        {format!("Restore unsaved work from \"{}\"?", autosave.project.metadata.name)}
+                    </span>
+                    <button class="btn btn-primary" on:click=on_restore_autosave>
+                        "Restore"
+                    </button>
+                    <button class="btn btn-secondary" on:click=on_dismiss_autosave>
+                        "Discard"
+                    </button>
+                </div>
+            })}
+
+            {move || show_image_storage_warning.get().then(|| view! {
+                <div class="autosave-banner" role="status">
+                    <span>
+                        "Browser storage is unavailable (e.g. private browsing), so images work for this session but won't survive a reload."
+                    </span>
+                    <button
+                        class="btn btn-secondary"
+                        on:click=move |_| show_image_storage_warning.set(false)
+                    >
+                        "Dismiss"
+                    </button>
+                </div>
+            })}
+
             <main class="main-content">
-                <div class="split-pane">
-                    <div class="code-pane">
+                <div
+                    class="split-pane"
+                    class:dragging=move || split_dragging.get()
+                    node_ref=split_ref
+                >
+                    <div
+                        class="code-pane"
+                        style:flex-basis=move || format!("{:.2}%", split_ratio.get() * 100.0)
+                    >
                         <div class="tab-header">
                             <button
                                 class=move || if active_tab.get() == EditorTab::Content { "tab-btn active" } else { "tab-btn" }
@@ -845,30 +1790,68 @@ Return the updated JSON:"#,
                         </div>
                         {move || match active_tab.get() {
                             EditorTab::Content => {
-                                let json_source = create_rw_signal(
-                                    serde_json::to_string_pretty(&content_data.get()).unwrap_or_default()
-                                );
+                                // Shared by the structured and raw JSON editors
+                                let on_content_change = move |data: SlickSheetData| {
+                                    state.record_undo_grouped("content");
+                                    content_data.set(data);
+                                    // Re-render template
+                                    let template = template_source.get();
+                                    if let Ok(rendered) = crate::template::TemplateEngine::render(&template, &content_data.get()) {
+                                        typst_source.set(rendered);
+                                    }
+                                };
                                 view! {
-                                    <JsonEditor
-                                        source=json_source
-                                        on_change=move |new_json: String| {
-                                            if let Ok(data) = serde_json::from_str::<crate::data::SlickSheetData>(&new_json) {
-                                                content_data.set(data);
-                                                // Re-render template
-                                                let template = template_source.get();
-                                                if let Ok(rendered) = crate::template::TemplateEngine::render(&template, &content_data.get()) {
-                                                    typst_source.set(rendered);
-                                                }
-                                            }
-                                        }
-                                    />
+                                    <div class="content-tab">
+                                        <div class="content-view-toggle">
+                                            <button
+                                                class=move || if content_json_view.get() { "view-btn" } else { "view-btn active" }
+                                                on:click=move |_| content_json_view.set(false)
+                                            >
+                                                "Features"
+                                            </button>
+                                            <button
+                                                class=move || if content_json_view.get() { "view-btn active" } else { "view-btn" }
+                                                on:click=move |_| content_json_view.set(true)
+                                            >
+                                                "JSON"
+                                            </button>
+                                        </div>
+                                        {move || if content_json_view.get() {
+                                            let json_source = create_rw_signal(
+                                                serde_json::to_string_pretty(&content_data.get()).unwrap_or_default()
+                                            );
+                                            view! {
+                                                <JsonEditor
+                                                    source=json_source
+                                                    on_change=move |new_json: String| {
+                                                        if let Ok(data) = serde_json::from_str::<SlickSheetData>(&new_json) {
+                                                            on_content_change(data);
+                                                        }
+                                                    }
+                                                />
+                                            }.into_view()
+                                        } else {
+                                            view! {
+                                                <FeaturesEditor
+                                                    content_data=content_data
+                                                    on_change=Callback::new(on_content_change)
+                                                />
+                                                <SectionsEditor
+                                                    content_data=content_data
+                                                    on_change=Callback::new(on_content_change)
+                                                />
+                                            }.into_view()
+                                        }}
+                                    </div>
                                 }.into_view()
                             }
                             EditorTab::Template => {
                                 view! {
                                     <CodeEditor
                                         source=template_source
+                                        autocomplete=true
                                         on_change=move |new_template: String| {
+                                            state.record_undo_grouped("template");
                                             template_source.set(new_template.clone());
                                             // Re-render with current data
                                             if let Ok(rendered) = crate::template::TemplateEngine::render(&new_template, &content_data.get()) {
@@ -880,10 +1863,17 @@ Return the updated JSON:"#,
                             }
                             EditorTab::Typst => {
                                 view! {
-                                    <CodeEditor
-                                        source=typst_source
-                                        on_change=on_source_change
-                                    />
+                                    <div class="typst-tab">
+                                        <CodeEditor
+                                            source=typst_source
+                                            on_change=on_source_change
+                                            goto_line=goto_line
+                                        />
+                                        <PartialsPanel
+                                            partials=partials
+                                            on_change=on_partials_change
+                                        />
+                                    </div>
                                 }.into_view()
                             }
                             EditorTab::Images => {
@@ -904,20 +1894,61 @@ Return the updated JSON:"#,
                                             images=images_list
                                             image_cache=image_cache
                                             on_select=on_image_select
+                                            on_copy_snippet=on_image_copy_snippet
                                             on_delete=on_image_delete
+                                            on_regenerate=on_image_regenerate
+                                            regenerating=regenerating_image.into()
+                                            on_describe=on_image_describe
+                                            describing=describing_image.into()
+                                        />
+                                        <FontsPanel
+                                            fonts=fonts_list
+                                            store=font_store
+                                            on_upload=on_font_upload
+                                            on_delete=on_font_delete
+                                            on_error=on_font_error
                                         />
                                     </div>
                                 }.into_view()
                             }
                         }}
+                        {move || matches!(active_tab.get(), EditorTab::Content | EditorTab::Template).then(|| view! {
+                            <div class="content-view-toggle">
+                                <button
+                                    class=move || if show_escape_preview.get() { "view-btn active" } else { "view-btn" }
+                                    on:click=move |_| show_escape_preview.update(|shown| *shown = !*shown)
+                                    title="Show the Typst produced from the template, with escapes highlighted"
+                                >
+                                    "Show escapes"
+                                </button>
+                            </div>
+                            {move || show_escape_preview.get().then(|| view! {
+                                <EscapePreview
+                                    template_source=template_source
+                                    content_data=content_data
+                                />
+                            })}
+                        })}
                     </div>
 
+                    <div
+                        class="split-divider"
+                        on:mousedown=move |ev| {
+                            ev.prevent_default();
+                            split_dragging.set(true);
+                        }
+                        on:touchstart=move |_| split_dragging.set(true)
+                        on:dblclick=reset_split
+                        title="Drag to resize, double-click to reset"
+                    />
+
                     <div class="preview-pane">
                         <div class="pane-header">"Preview (click to edit)"</div>
                         <Preview
                             svg=svg_output.into()
                             error=error.into()
                             on_click=on_preview_click
+                            on_error_line=on_error_line
                         />
                     </div>
                 </div>
@@ -932,6 +1963,14 @@ Return the updated JSON:"#,
                     current_iteration=current_iteration.into()
                     max_iterations=max_iterations_signal.into()
                     on_send=on_chat_send
+                    on_stop=on_chat_stop
+                    proposed_diff=proposed_diff
+                    on_apply=on_apply_proposal
+                    on_discard=on_discard_proposal
+                    pending_prompts=pending_prompts
+                    on_cancel_pending=on_cancel_pending
+                    model=Signal::derive(effective_model)
+                    model_override=project_ai_model
                 />
             </main>
 
@@ -941,6 +1980,9 @@ Return the updated JSON:"#,
                 project_name=project_name.into()
                 last_saved=last_saved.into()
                 status_message=status_message.into()
+                body_stats=Signal::derive(move || content_data.with(|data| TextStats::of(&data.body)))
+                total_stats=Signal::derive(move || typst_source.with(|source| TextStats::of(source)))
+                page_count=page_count.into()
             />
 
             // Template Gallery Modal
@@ -954,6 +1996,7 @@ Return the updated JSON:"#,
             // Settings Modal
             {move || show_settings_modal.get().then(|| view! {
                 <SettingsModal
+                    preview_delay_ms=preview_delay_ms
                     on_save=on_settings_save
                     on_close=Callback::new(move |_| show_settings_modal.set(false))
                 />
@@ -987,6 +2030,20 @@ Return the updated JSON:"#,
                 border-bottom: 1px solid var(--border);
             }
 
+            .autosave-banner {
+                display: flex;
+                align-items: center;
+                gap: 0.75rem;
+                padding: 0.5rem 1rem;
+                background: var(--warning);
+                color: var(--bg-primary);
+                font-size: 0.875rem;
+            }
+
+            .autosave-banner span {
+                flex: 1;
+            }
+
             .app-title {
                 font-size: 1.25rem;
                 font-weight: 600;
@@ -1061,15 +2118,37 @@ Return the updated JSON:"#,
                 overflow: hidden;
             }
 
+            .split-pane.dragging {
+                cursor: col-resize;
+                user-select: none;
+            }
+
             .code-pane, .preview-pane {
-                flex: 1;
                 display: flex;
                 flex-direction: column;
                 overflow: hidden;
+                min-width: 0;
             }
 
             .code-pane {
-                border-right: 1px solid var(--border);
+                flex: 0 0 50%;
+            }
+
+            .preview-pane {
+                flex: 1;
+            }
+
+            .split-divider {
+                flex: 0 0 5px;
+                background: var(--border);
+                cursor: col-resize;
+                touch-action: none;
+                transition: background 0.2s;
+            }
+
+            .split-divider:hover,
+            .split-pane.dragging .split-divider {
+                background: var(--accent);
             }
 
             .pane-header {
@@ -1113,7 +2192,43 @@ Return the updated JSON:"#,
                 border-bottom: 2px solid var(--accent);
             }
 
+            .content-tab {
+                flex: 1;
+                display: flex;
+                flex-direction: column;
+                overflow: hidden;
+            }
+
+            .content-view-toggle {
+                display: flex;
+                gap: 0.25rem;
+                padding: 0.375rem 0.5rem;
+                border-bottom: 1px solid var(--border);
+            }
+
+            .view-btn {
+                padding: 0.25rem 0.625rem;
+                background: transparent;
+                border: 1px solid var(--border);
+                border-radius: 4px;
+                color: var(--text-secondary);
+                cursor: pointer;
+                font-size: 0.75rem;
+            }
+
+            .view-btn.active {
+                border-color: var(--accent);
+                color: var(--accent);
+            }
+
             /* Images panel */
+            .typst-tab {
+                flex: 1;
+                display: flex;
+                flex-direction: column;
+                overflow: hidden;
+            }
+
             .images-panel {
                 flex: 1;
                 display: flex;
@@ -1200,6 +2315,68 @@ Return the updated JSON:"#,
                 border-color: var(--accent);
             }
 
+            .template-card.hidden {
+                display: none;
+            }
+
+            .template-filters {
+                display: flex;
+                flex-direction: column;
+                gap: 0.75rem;
+                margin-bottom: 1rem;
+            }
+
+            .template-search {
+                padding: 0.5rem 0.75rem;
+                background: var(--bg-tertiary);
+                color: var(--text-primary);
+                border: 1px solid var(--border);
+                border-radius: 6px;
+                font-size: 0.875rem;
+            }
+
+            .filter-chips {
+                display: flex;
+                flex-wrap: wrap;
+                gap: 0.5rem;
+            }
+
+            .filter-chip {
+                padding: 0.25rem 0.75rem;
+                background: var(--bg-tertiary);
+                color: var(--text-secondary);
+                border: 1px solid var(--border);
+                border-radius: 999px;
+                font-size: 0.75rem;
+                cursor: pointer;
+            }
+
+            .filter-chip.active {
+                background: var(--accent);
+                border-color: var(--accent);
+                color: white;
+            }
+
+            .template-empty {
+                color: var(--text-secondary);
+                font-size: 0.875rem;
+            }
+
+            .template-thumbnail {
+                aspect-ratio: 8.5 / 11;
+                margin-bottom: 0.75rem;
+                background: white;
+                border-radius: 4px;
+                overflow: hidden;
+                pointer-events: none;
+            }
+
+            .template-thumbnail svg {
+                display: block;
+                width: 100%;
+                height: auto;
+            }
+
             .template-name {
                 font-weight: 600;
                 margin-bottom: 0.5rem;
@@ -1221,24 +2398,274 @@ Return the updated JSON:"#,
     }
 }
 
-/// Code editor component (textarea wrapper)
+/// Code editor component (textarea with a line-number gutter)
+///
+/// With `autocomplete` set, typing `{{` offers the template variables.
 #[component]
-fn CodeEditor(source: RwSignal<String>, on_change: impl Fn(String) + 'static) -> impl IntoView {
+fn CodeEditor(
+    source: RwSignal<String>,
+    on_change: impl Fn(String) + 'static,
+    #[prop(optional)] autocomplete: bool,
+    /// Line to jump to once the textarea is mounted, cleared after the jump
+    #[prop(optional)]
+    goto_line: Option<RwSignal<Option<usize>>>,
+) -> impl IntoView {
+    let on_change = Callback::new(on_change);
+    let textarea_ref = create_node_ref::<html::Textarea>();
+    let gutter_ref = create_node_ref::<html::Div>();
+    let find_open = create_rw_signal(false);
+
+    // Gutter lines and the line holding the caret
+    let lines = create_memo(move |_| source.with(|text| line_count(text)));
+    let current_line = create_rw_signal(1_usize);
+    let update_caret = move || {
+        if let Some(area) = textarea_ref.get_untracked() {
+            let caret = area.selection_start().ok().flatten().unwrap_or(0);
+            current_line.set(caret_line(&area.value(), caret));
+        }
+    };
+    let scroll_top = create_rw_signal(0);
+    let sync_gutter_scroll = move |_| {
+        if let (Some(area), Some(gutter)) =
+            (textarea_ref.get_untracked(), gutter_ref.get_untracked())
+        {
+            gutter.set_scroll_top(area.scroll_top());
+            scroll_top.set(area.scroll_top());
+        }
+    };
+
+    // Jump to a requested line (e.g. from a clicked compile error)
+    create_effect(move |_| {
+        let Some(goto_line) = goto_line else {
+            return;
+        };
+        if let (Some(line), Some(area)) = (goto_line.get(), textarea_ref.get()) {
+            scroll_to_line(&area, line);
+            current_line.set(line.min(lines.get_untracked()));
+            if let Some(gutter) = gutter_ref.get_untracked() {
+                gutter.set_scroll_top(area.scroll_top());
+            }
+            scroll_top.set(area.scroll_top());
+            goto_line.set(None);
+        }
+    });
+
+    // Replacements go through on_change first so undo captures the old text
+    let on_replace = Callback::new(move |text: String| {
+        on_change.call(text.clone());
+        if source
+            .try_get_untracked()
+            .is_some_and(|current| current != text)
+        {
+            source.set(text);
+        }
+    });
+
+    // Variable suggestions for the path being typed after `{{`
+    let suggestions = create_rw_signal(Vec::<VariableInfo>::new());
+    let highlighted = create_rw_signal(0_usize);
+    let update_suggestions = move || {
+        let Some(area) = textarea_ref.get_untracked().filter(|_| autocomplete) else {
+            return;
+        };
+        let caret = area.selection_start().ok().flatten().unwrap_or(0);
+        let matches = variable_prefix(&area.value(), caret)
+            .map(matching_variables)
+            .unwrap_or_default();
+        suggestions.set(matches);
+        highlighted.set(0);
+    };
+    let accept_suggestion = move |index: usize| {
+        let Some(variable) = suggestions.with_untracked(|list| list.get(index).copied()) else {
+            return;
+        };
+        let Some(area) = textarea_ref.get_untracked() else {
+            return;
+        };
+        let caret = area.selection_start().ok().flatten().unwrap_or(0);
+        let (text, caret) = insert_completion(&area.value(), caret, variable.path);
+        on_replace.call(text.clone());
+        area.set_value(&text);
+        let _ = area.set_selection_range(caret, caret);
+        suggestions.set(Vec::new());
+    };
+    let on_suggestion_key = move |ev: &web_sys::KeyboardEvent| {
+        let count = suggestions.with_untracked(Vec::len);
+        if count == 0 {
+            return;
+        }
+        match ev.key().as_str() {
+            "ArrowDown" => highlighted.update(|i| *i = (*i + 1) % count),
+            "ArrowUp" => highlighted.update(|i| *i = (*i + count - 1) % count),
+            "Enter" | "Tab" => accept_suggestion(highlighted.get_untracked()),
+            "Escape" => suggestions.set(Vec::new()),
+            _ => return,
+        }
+        ev.prevent_default();
+    };
+
     view! {
-        <textarea
-            class="code-editor"
-            prop:value=move || source.get()
-            on:input=move |ev| {
-                let value = event_target_value(&ev);
-                on_change(value);
+        <div
+            class="editor-wrap"
+            on:keydown=move |ev| {
+                if is_find_shortcut(&ev) {
+                    ev.prevent_default();
+                    find_open.set(true);
+                }
             }
-            spellcheck="false"
-        />
+        >
+            {move || find_open.get().then(|| view! {
+                <FindReplaceBar
+                    source=source
+                    textarea=textarea_ref
+                    on_replace=on_replace
+                    open=find_open
+                />
+            })}
+            <div class="code-editor-body">
+                <div class="line-gutter" node_ref=gutter_ref aria-hidden="true">
+                    <For each=move || 1..=lines.get() key=|line| *line let:line>
+                        <div class="line-number" class:current=move || current_line.get() == line>
+                            {line}
+                        </div>
+                    </For>
+                </div>
+                <textarea
+                    class="code-editor"
+                    node_ref=textarea_ref
+                    prop:value=move || source.get()
+                    on:input=move |ev| {
+                        let value = event_target_value(&ev);
+                        on_change.call(value);
+                        update_caret();
+                        update_suggestions();
+                    }
+                    on:keydown=move |ev| on_suggestion_key(&ev)
+                    on:keyup=move |_| update_caret()
+                    on:click=move |_| {
+                        update_caret();
+                        suggestions.set(Vec::new());
+                    }
+                    on:blur=move |_| suggestions.set(Vec::new())
+                    on:select=move |_| update_caret()
+                    on:scroll=sync_gutter_scroll
+                    spellcheck="false"
+                    wrap="off"
+                />
+                {move || (!suggestions.with(Vec::is_empty)).then(|| view! {
+                    <ul
+                        class="variable-suggestions"
+                        role="listbox"
+                        style=move || format!(
+                            "top: calc(1rem + {}rem - {}px);",
+                            current_line.get() as f64 * 1.4,
+                            scroll_top.get()
+                        )
+                    >
+                        {suggestions
+                            .get()
+                            .into_iter()
+                            .enumerate()
+                            .map(|(index, variable)| view! {
+                                <li
+                                    role="option"
+                                    class:highlighted=move || highlighted.get() == index
+                                    on:mousedown=move |ev| {
+                                        // Keep focus in the textarea
+                                        ev.prevent_default();
+                                        accept_suggestion(index);
+                                    }
+                                >
+                                    <code>{variable.path}</code>
+                                    <span>{variable.description}</span>
+                                </li>
+                            })
+                            .collect_view()}
+                    </ul>
+                })}
+            </div>
+        </div>
 
         <style>
             r#"
+            .editor-wrap {
+                flex: 1;
+                display: flex;
+                flex-direction: column;
+                min-height: 0;
+            }
+
+            .code-editor-body {
+                position: relative;
+                flex: 1;
+                display: flex;
+                min-height: 0;
+                background: var(--bg-primary);
+            }
+
+            .variable-suggestions {
+                position: absolute;
+                left: 4rem;
+                z-index: 10;
+                margin: 0;
+                padding: 0.25rem 0;
+                max-height: 14rem;
+                overflow-y: auto;
+                list-style: none;
+                background: var(--bg-secondary);
+                border: 1px solid var(--border);
+                border-radius: 4px;
+                box-shadow: 0 4px 12px rgba(0, 0, 0, 0.3);
+                font-size: 0.8125rem;
+            }
+
+            .variable-suggestions li {
+                display: flex;
+                gap: 0.75rem;
+                padding: 0.25rem 0.75rem;
+                cursor: pointer;
+            }
+
+            .variable-suggestions li.highlighted {
+                background: var(--bg-tertiary);
+            }
+
+            .variable-suggestions code {
+                color: var(--accent);
+                font-family: 'JetBrains Mono', 'Fira Code', monospace;
+            }
+
+            .variable-suggestions span {
+                color: var(--text-secondary);
+            }
+
+            .line-gutter {
+                flex: 0 0 auto;
+                min-width: 3rem;
+                padding: 1rem 0.5rem 1rem 0;
+                overflow: hidden;
+                border-right: 1px solid var(--border);
+                color: var(--text-secondary);
+                text-align: right;
+                font-family: 'JetBrains Mono', 'Fira Code', monospace;
+                font-size: 0.875rem;
+                line-height: 1.6;
+                user-select: none;
+            }
+
+            .line-number {
+                padding-left: 0.5rem;
+                opacity: 0.6;
+            }
+
+            .line-number.current {
+                color: var(--accent);
+                opacity: 1;
+            }
+
             .code-editor {
                 flex: 1;
+                min-width: 0;
                 padding: 1rem;
                 background: var(--bg-primary);
                 color: var(--text-primary);
@@ -1248,6 +2675,8 @@ fn CodeEditor(source: RwSignal<String>, on_change: impl Fn(String) + 'static) ->
                 font-size: 0.875rem;
                 line-height: 1.6;
                 outline: none;
+                white-space: pre;
+                overflow-wrap: normal;
             }
             "#
         </style>
@@ -1257,16 +2686,50 @@ fn CodeEditor(source: RwSignal<String>, on_change: impl Fn(String) + 'static) ->
 /// JSON editor component (textarea wrapper with JSON styling)
 #[component]
 fn JsonEditor(source: RwSignal<String>, on_change: impl Fn(String) + 'static) -> impl IntoView {
+    let on_change = Callback::new(on_change);
+    let textarea_ref = create_node_ref::<html::Textarea>();
+    let find_open = create_rw_signal(false);
+
+    // Replacements go through on_change first so undo captures the old text
+    let on_replace = Callback::new(move |text: String| {
+        on_change.call(text.clone());
+        if source
+            .try_get_untracked()
+            .is_some_and(|current| current != text)
+        {
+            source.set(text);
+        }
+    });
+
     view! {
-        <textarea
-            class="json-editor"
-            prop:value=move || source.get()
-            on:input=move |ev| {
-                let value = event_target_value(&ev);
-                on_change(value);
+        <div
+            class="editor-wrap"
+            on:keydown=move |ev| {
+                if is_find_shortcut(&ev) {
+                    ev.prevent_default();
+                    find_open.set(true);
+                }
             }
-            spellcheck="false"
-        />
+        >
+            {move || find_open.get().then(|| view! {
+                <FindReplaceBar
+                    source=source
+                    textarea=textarea_ref
+                    on_replace=on_replace
+                    open=find_open
+                />
+            })}
+            <textarea
+                class="json-editor"
+                node_ref=textarea_ref
+                prop:value=move || source.get()
+                on:input=move |ev| {
+                    let value = event_target_value(&ev);
+                    on_change.call(value);
+                }
+                spellcheck="false"
+            />
+        </div>
 
         <style>
             r#"
@@ -1290,6 +2753,33 @@ fn JsonEditor(source: RwSignal<String>, on_change: impl Fn(String) + 'static) ->
 /// Template Gallery Modal component
 #[component]
 fn TemplateGalleryModal(on_select: Callback<String>, on_close: Callback<()>) -> impl IntoView {
+    let query = create_rw_signal(String::new());
+    let category = create_rw_signal(None::<TemplateCategory>);
+    let no_matches = move || {
+        query.with(|query| {
+            TemplateGallery::new()
+                .search(query, category.get())
+                .is_empty()
+        })
+    };
+
+    // "All" followed by one chip per category present in the gallery
+    let chips = std::iter::once(None)
+        .chain(TemplateGallery::new().categories().into_iter().map(Some))
+        .map(|chip| {
+            let label = chip.map_or("All", |c: TemplateCategory| c.as_str());
+            view! {
+                <button
+                    class="filter-chip"
+                    class:active=move || category.get() == chip
+                    on:click=move |_| category.set(chip)
+                >
+                    {label}
+                </button>
+            }
+        })
+        .collect::<Vec<_>>();
+
     view! {
         <div class="modal-overlay" on:click=move |_| on_close.call(())>
             <div class="modal-content" on:click=|ev| ev.stop_propagation()>
@@ -1297,14 +2787,40 @@ fn TemplateGalleryModal(on_select: Callback<String>, on_close: Callback<()>) ->
                     <h2 class="modal-title">"Choose a Template"</h2>
                     <button class="modal-close" on:click=move |_| on_close.call(())>"×"</button>
                 </div>
+                <div class="template-filters">
+                    <input
+                        type="search"
+                        class="template-search"
+                        placeholder="Search templates..."
+                        prop:value=move || query.get()
+                        on:input=move |ev| query.set(event_target_value(&ev))
+                    />
+                    <div class="filter-chips">{chips}</div>
+                </div>
+                <Show when=no_matches>
+                    <p class="template-empty">"No templates match your search."</p>
+                </Show>
                 <div class="template-grid">
                     {TEMPLATES.iter().map(|template| {
                         let template_id = template.id.to_string();
+                        // Render thumbnails after the modal paints, one per task
+                        let preview = create_rw_signal(None::<String>);
+                        set_timeout(
+                            move || preview.set(template.cached_preview().ok()),
+                            std::time::Duration::ZERO,
+                        );
                         view! {
                             <div
                                 class="template-card"
+                                class:hidden=move || {
+                                    !query.with(|query| template.matches(query, category.get()))
+                                }
                                 on:click=move |_| on_select.call(template_id.clone())
                             >
+                                <div
+                                    class="template-thumbnail"
+                                    inner_html=move || preview.get().unwrap_or_default()
+                                />
                                 <div class="template-name">{template.name}</div>
                                 <div class="template-description">{template.description}</div>
                                 <div class="template-category">{template.category.as_str()}</div>
@@ -1365,13 +2881,21 @@ fn trigger_download_url(data_url: &str, filename: &str) {
     }
 }
 
-/// Trigger a file load dialog
-fn trigger_file_load(on_load: impl Fn(String) + 'static) {
+/// Contents of a file picked in the Open dialog
+enum LoadedFile {
+    /// A `.json` project file
+    Json(String),
+    /// A `.zip` bundle from "Export Bundle"
+    Zip(Vec<u8>),
+}
+
+/// Trigger a file load dialog for a project file or bundle
+fn trigger_file_load(on_load: impl Fn(LoadedFile) + 'static) {
     if let Some(window) = web_sys::window() {
         if let Some(document) = window.document() {
             if let Ok(input) = document.create_element("input") {
                 let _ = input.set_attribute("type", "file");
-                let _ = input.set_attribute("accept", ".json");
+                let _ = input.set_attribute("accept", ".json,.zip");
 
                 let on_load = std::rc::Rc::new(on_load);
                 let input_ref = input.clone();
@@ -1383,13 +2907,17 @@ fn trigger_file_load(on_load: impl Fn(String) + 'static) {
                                 if let Some(file) = files.get(0) {
                                     let reader = web_sys::FileReader::new().unwrap();
                                     let on_load = on_load.clone();
+                                    let is_zip = file.name().to_lowercase().ends_with(".zip");
 
                                     let reader_ref = reader.clone();
                                     let onload = wasm_bindgen::closure::Closure::wrap(Box::new(
                                         move |_: web_sys::Event| {
                                             if let Ok(result) = reader_ref.result() {
-                                                if let Some(text) = result.as_string() {
-                                                    on_load(text);
+                                                if is_zip {
+                                                    let bytes = js_sys::Uint8Array::new(&result);
+                                                    on_load(LoadedFile::Zip(bytes.to_vec()));
+                                                } else if let Some(text) = result.as_string() {
+                                                    on_load(LoadedFile::Json(text));
                                                 }
                                             }
                                         },
@@ -1399,7 +2927,11 @@ fn trigger_file_load(on_load: impl Fn(String) + 'static) {
                                     reader.set_onload(Some(onload.as_ref().unchecked_ref()));
                                     onload.forget();
 
-                                    let _ = reader.read_as_text(&file);
+                                    let _ = if is_zip {
+                                        reader.read_as_array_buffer(&file)
+                                    } else {
+                                        reader.read_as_text(&file)
+                                    };
                                 }
                             }
                         }
@@ -1417,6 +2949,29 @@ fn trigger_file_load(on_load: impl Fn(String) + 'static) {
     }
 }
 
+/// Check whether a keyboard event targets an editable text field
+fn is_text_field_event(ev: &web_sys::KeyboardEvent) -> bool {
+    ev.target()
+        .and_then(|target| target.dyn_into::<web_sys::Element>().ok())
+        .map(|element| matches!(element.tag_name().as_str(), "INPUT" | "TEXTAREA"))
+        .unwrap_or(false)
+}
+
+/// OpenRouter config attributed to the page the editor is served from
+///
+/// Self-hosted copies then show up under their own URL in OpenRouter's
+/// usage stats; `file://` and other non-web origins keep the default.
+fn openrouter_config(api_key: String) -> OpenRouterConfig {
+    let origin = web_sys::window()
+        .and_then(|window| window.location().origin().ok())
+        .filter(|origin| origin.starts_with("http"));
+    let config = OpenRouterConfig::with_key(api_key);
+    match origin {
+        Some(origin) => config.with_referer(origin),
+        None => config,
+    }
+}
+
 /// Clear status message after a delay
 fn clear_status_after_delay(status: RwSignal<Option<String>>) {
     if let Some(window) = web_sys::window() {
@@ -1445,7 +3000,8 @@ fn extract_field_value(source: &str, field_id: &str) -> String {
     // This is a simplified extraction - in a real app, you'd parse the Typst AST
     // Look for link patterns with cmd://edit/{field_id}
     // Typst syntax: #link("url")[content]
-    let pattern = format!("cmd://edit/{}", field_id);
+    // Match the quoted URL so "features/1" does not also match "features/10"
+    let pattern = format!("\"cmd://edit/{}\"", field_id);
 
     for line in source.lines() {
         if line.contains(&pattern) {
@@ -1466,14 +3022,13 @@ fn extract_field_value(source: &str, field_id: &str) -> String {
 /// Update a field value in Typst source (simplified update)
 fn update_field_in_source(source: &str, field_id: &str, new_value: &str) -> Option<String> {
     let pattern = format!("cmd://edit/{}", field_id);
+    let link_pattern = format!("#link(\"{}\")", pattern);
     let mut result = String::new();
     let mut modified = false;
 
     for line in source.lines() {
-        if line.contains(&pattern) {
+        if line.contains(&link_pattern) {
             // Replace the content between ][ and the next ]
-            // Note: Typst uses #link() so we search for #link or link (to handle both cases)
-            let link_pattern = format!("#link(\"{}\")", pattern);
             if let Some(link_start) = line.find(&link_pattern) {
                 let before_link = &line[..link_start];
                 let link_part = &line[link_start..];
@@ -1508,25 +3063,122 @@ fn update_field_in_source(source: &str, field_id: &str, new_value: &str) -> Opti
 }
 
 /// Preview component for SVG output
+///
+/// Zoom scales `.svg-container` with a CSS transform inside a sizer sized to
+/// the scaled page, so scrolling follows the zoomed size. Clicks still reach
+/// the cmd:// `<a>` overlays because the browser hit-tests transformed
+/// content.
 #[component]
 fn Preview(
     svg: Signal<Option<String>>,
     error: Signal<Option<String>>,
     on_click: impl Fn(web_sys::MouseEvent) + 'static,
+    /// Called with the source line of a clicked error
+    on_error_line: Callback<usize>,
 ) -> impl IntoView {
+    let zoom = create_rw_signal(1.0_f32);
+    let content_ref = create_node_ref::<html::Div>();
+    let fitted_once = store_value(false);
+
+    let fit_width = move || {
+        let Some(content) = content_ref.get_untracked() else {
+            return;
+        };
+        let Some((page_width, _)) = svg.with_untracked(|s| s.as_deref().and_then(svg_page_size))
+        else {
+            return;
+        };
+        // Leave room for the preview padding on both sides
+        let available = f64::from(content.client_width()) - 32.0;
+        zoom.set(fit_width_zoom(available, page_width));
+    };
+
+    // Start out fitted to the pane once the first page has rendered
+    create_effect(move |_| {
+        if svg.with(Option::is_some) && !fitted_once.get_value() {
+            fitted_once.set_value(true);
+            request_animation_frame(fit_width);
+        }
+    });
+
+    let on_wheel = move |ev: web_sys::WheelEvent| {
+        if ev.ctrl_key() {
+            ev.prevent_default();
+            let steps = if ev.delta_y() < 0.0 { 1 } else { -1 };
+            zoom.update(|z| *z = step_zoom(*z, steps));
+        }
+    };
+
     view! {
-        <div class="preview-content" on:click=on_click>
+        <div class="preview-zoom-bar">
+            <button
+                class="zoom-btn"
+                title="Zoom out (Ctrl+scroll)"
+                on:click=move |_| zoom.update(|z| *z = step_zoom(*z, -1))
+            >
+                "−"
+            </button>
+            <span class="zoom-level">{move || format!("{:.0}%", zoom.get() * 100.0)}</span>
+            <button
+                class="zoom-btn"
+                title="Zoom in (Ctrl+scroll)"
+                on:click=move |_| zoom.update(|z| *z = step_zoom(*z, 1))
+            >
+                "+"
+            </button>
+            <button class="zoom-btn" on:click=move |_| fit_width()>"Fit width"</button>
+            <button class="zoom-btn" on:click=move |_| zoom.set(1.0)>"100%"</button>
+        </div>
+        <div class="preview-content" node_ref=content_ref on:click=on_click on:wheel=on_wheel>
             {move || {
-                if let Some(err) = error.get() {
+                if error.with(|err| err.as_deref() == Some(EMPTY_DOCUMENT_MESSAGE)) {
+                    view! {
+                        <div class="empty-preview">{EMPTY_DOCUMENT_MESSAGE}</div>
+                    }.into_view()
+                } else if let Some(err) = error.get() {
                     view! {
                         <div class="error-display">
                             <strong>"Compilation Error:"</strong>
-                            <pre>{err}</pre>
+                            <pre>
+                                {err.lines().map(|message| match error_line(message) {
+                                    Some(line) => view! {
+                                        <div
+                                            class="error-line error-line-link"
+                                            title="Go to line in the Typst source"
+                                            on:click=move |ev| {
+                                                ev.stop_propagation();
+                                                on_error_line.call(line);
+                                            }
+                                        >
+                                            {message.to_string()}
+                                        </div>
+                                    },
+                                    None => view! {
+                                        <div class="error-line">{message.to_string()}</div>
+                                    },
+                                }).collect_view()}
+                            </pre>
                         </div>
                     }.into_view()
                 } else if let Some(svg_content) = svg.get() {
+                    let page_size = svg_page_size(&svg_content);
+                    let sizer_style = move || match page_size {
+                        Some((width, height)) => {
+                            let zoom = f64::from(zoom.get());
+                            format!("width: {}px; height: {}px;", width * zoom, height * zoom)
+                        }
+                        None => String::new(),
+                    };
+                    let container_style = move || {
+                        let width = page_size
+                            .map(|(width, _)| format!("width: {}px; ", width))
+                            .unwrap_or_default();
+                        format!("{}transform: scale({});", width, zoom.get())
+                    };
                     view! {
-                        <div class="svg-container" inner_html=svg_content />
+                        <div class="svg-sizer" style=sizer_style>
+                            <div class="svg-container" style=container_style inner_html=svg_content />
+                        </div>
                     }.into_view()
                 } else {
                     view! {
@@ -1538,6 +3190,31 @@ fn Preview(
 
         <style>
             r#"
+            .preview-zoom-bar {
+                display: flex;
+                align-items: center;
+                gap: 0.25rem;
+                padding: 0.25rem 0.75rem;
+                border-bottom: 1px solid var(--border);
+                background: var(--bg-secondary);
+                font-size: 0.75rem;
+            }
+
+            .zoom-btn {
+                padding: 0.125rem 0.5rem;
+                background: none;
+                border: 1px solid var(--border);
+                border-radius: 4px;
+                color: var(--text-primary);
+                cursor: pointer;
+            }
+
+            .zoom-level {
+                min-width: 3rem;
+                text-align: center;
+                color: var(--text-secondary);
+            }
+
             .preview-content {
                 flex: 1;
                 overflow: auto;
@@ -1546,13 +3223,18 @@ fn Preview(
                 cursor: pointer;
             }
 
+            .svg-sizer {
+                margin: 0 auto;
+                overflow: hidden;
+            }
+
             .svg-container {
-                display: flex;
-                justify-content: center;
+                transform-origin: top left;
             }
 
             .svg-container svg {
-                max-width: 100%;
+                display: block;
+                width: 100%;
                 height: auto;
                 box-shadow: 0 2px 8px rgba(0, 0, 0, 0.1);
             }
@@ -1582,6 +3264,26 @@ fn Preview(
                 color: var(--error);
             }
 
+            .error-line-link {
+                cursor: pointer;
+                text-decoration: underline dotted;
+            }
+
+            .error-line-link:hover {
+                text-decoration: underline;
+            }
+
+            .empty-preview {
+                display: flex;
+                align-items: center;
+                justify-content: center;
+                height: 100%;
+                min-height: 200px;
+                border: 1px dashed var(--border);
+                border-radius: 8px;
+                color: var(--text-secondary);
+            }
+
             .loading-preview {
                 display: flex;
                 align-items: center;
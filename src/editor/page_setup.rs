@@ -0,0 +1,83 @@
+//! Page size presets offered in the toolbar
+//!
+//! Presets combine a paper size and orientation; any custom margin in the
+//! content data is kept when switching between them.
+
+use leptos::*;
+
+use crate::data::{Orientation, PageSetup, PageSize};
+
+/// Every size/orientation combination, Letter first
+pub fn page_presets() -> Vec<PageSetup> {
+    PageSize::ALL
+        .into_iter()
+        .flat_map(|size| {
+            Orientation::ALL
+                .into_iter()
+                .map(move |orientation| PageSetup::new(size, orientation))
+        })
+        .collect()
+}
+
+/// Value used in the preset `<select>` (e.g. "a4-landscape")
+pub fn preset_key(page: &PageSetup) -> String {
+    format!("{}-{}", page.size.as_str(), page.orientation.as_str())
+}
+
+/// Look up a preset by its `<select>` value
+pub fn preset_from_key(key: &str) -> Option<PageSetup> {
+    page_presets()
+        .into_iter()
+        .find(|preset| preset_key(preset) == key)
+}
+
+/// Page size and orientation picker
+#[component]
+pub fn PageSetupSelect(
+    /// Current page setup
+    #[prop(into)]
+    page: Signal<PageSetup>,
+    /// Called with the chosen preset
+    on_select: Callback<PageSetup>,
+) -> impl IntoView {
+    view! {
+        <select
+            class="page-setup-select"
+            title="Page size and orientation"
+            on:change=move |ev| {
+                if let Some(preset) = preset_from_key(&event_target_value(&ev)) {
+                    on_select.call(preset);
+                }
+            }
+        >
+            {page_presets()
+                .into_iter()
+                .map(|preset| {
+                    let key = preset_key(&preset);
+                    let selected_key = key.clone();
+                    view! {
+                        <option
+                            value=key
+                            selected=move || page.with(|page| preset_key(page) == selected_key)
+                        >
+                            {preset.label()}
+                        </option>
+                    }
+                })
+                .collect_view()}
+        </select>
+
+        <style>
+            r#"
+            .page-setup-select {
+                padding: 0.375rem 0.5rem;
+                border: 1px solid var(--border);
+                border-radius: 6px;
+                background: var(--bg-secondary);
+                color: var(--text-primary);
+                font-size: 0.8rem;
+            }
+            "#
+        </style>
+    }
+}
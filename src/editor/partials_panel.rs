@@ -0,0 +1,215 @@
+//! Partials panel component
+//!
+//! Lets users define extra Typst files that are saved with the project and
+//! can be pulled into the main source with `#include` or `#import`.
+
+use std::collections::BTreeMap;
+
+use leptos::*;
+
+/// Path reserved for the main source file
+const MAIN_PATH: &str = "main.typ";
+
+/// Turn a user-entered partial name into a virtual path
+///
+/// Adds a missing `.typ` extension and strips leading slashes, rejecting
+/// empty names, parent-directory segments and the main file's path.
+pub fn normalize_partial_path(name: &str) -> Result<String, String> {
+    let trimmed = name.trim().trim_start_matches('/');
+    if trimmed.is_empty() {
+        return Err("Partial name cannot be empty".to_string());
+    }
+    if trimmed
+        .split('/')
+        .any(|part| part.is_empty() || part == "..")
+    {
+        return Err(format!("Invalid partial path: '{}'", name.trim()));
+    }
+
+    let path = if trimmed.ends_with(".typ") {
+        trimmed.to_string()
+    } else {
+        format!("{}.typ", trimmed)
+    };
+    if path == MAIN_PATH {
+        return Err(format!("'{}' is reserved for the main source", MAIN_PATH));
+    }
+    Ok(path)
+}
+
+/// Partials panel listing editable extra `.typ` files
+#[component]
+pub fn PartialsPanel(
+    /// Partials keyed by virtual path
+    partials: RwSignal<BTreeMap<String, String>>,
+    /// Called after any partial is added, edited or removed
+    on_change: Callback<()>,
+) -> impl IntoView {
+    let new_name = create_rw_signal(String::new());
+    let add_error = create_rw_signal(Option::<String>::None);
+    // Only re-render the list when paths change, so typing keeps focus
+    let paths = create_memo(move |_| partials.with(|p| p.keys().cloned().collect::<Vec<_>>()));
+
+    let on_add = move |_| match normalize_partial_path(&new_name.get_untracked()) {
+        Ok(path) if partials.with_untracked(|p| p.contains_key(&path)) => {
+            add_error.set(Some(format!("'{}' already exists", path)));
+        }
+        Ok(path) => {
+            partials.update(|p| {
+                p.insert(path, String::new());
+            });
+            new_name.set(String::new());
+            add_error.set(None);
+            on_change.call(());
+        }
+        Err(e) => add_error.set(Some(e)),
+    };
+
+    view! {
+        <div class="partials-panel">
+            <div class="partials-header">
+                <span class="partials-title">"Partials"</span>
+                <input
+                    type="text"
+                    class="partials-name-input"
+                    placeholder="partials/header.typ"
+                    prop:value=move || new_name.get()
+                    on:input=move |ev| new_name.set(event_target_value(&ev))
+                />
+                <button class="btn btn-secondary" on:click=on_add>"Add"</button>
+            </div>
+            {move || add_error.get().map(|e| view! { <p class="partials-error">{e}</p> })}
+            {move || {
+                let paths = paths.get();
+                if paths.is_empty() {
+                    view! {
+                        <p class="partials-hint">
+                            "Add a partial, then use it with #include \"partials/header.typ\""
+                        </p>
+                    }.into_view()
+                } else {
+                    paths.into_iter().map(|path| {
+                        let content_path = path.clone();
+                        let input_path = path.clone();
+                        let delete_path = path.clone();
+                        view! {
+                            <div class="partial-item">
+                                <div class="partial-item-header">
+                                    <code class="partial-path">{path}</code>
+                                    <button
+                                        class="btn-icon partial-delete"
+                                        title="Delete partial"
+                                        on:click=move |_| {
+                                            partials.update(|p| {
+                                                p.remove(&delete_path);
+                                            });
+                                            on_change.call(());
+                                        }
+                                    >
+                                        "×"
+                                    </button>
+                                </div>
+                                <textarea
+                                    class="partial-source"
+                                    spellcheck="false"
+                                    prop:value=move || partials.with_untracked(|p| {
+                                        p.get(&content_path).cloned().unwrap_or_default()
+                                    })
+                                    on:input=move |ev| {
+                                        let value = event_target_value(&ev);
+                                        partials.update(|p| {
+                                            p.insert(input_path.clone(), value);
+                                        });
+                                        on_change.call(());
+                                    }
+                                ></textarea>
+                            </div>
+                        }
+                    }).collect_view()
+                }
+            }}
+        </div>
+
+        <style>
+            r#"
+            .partials-panel {
+                display: flex;
+                flex-direction: column;
+                gap: 0.5rem;
+                padding: 0.75rem;
+                border-top: 1px solid var(--border);
+                background: var(--bg-tertiary);
+                max-height: 40%;
+                overflow-y: auto;
+            }
+
+            .partials-header {
+                display: flex;
+                align-items: center;
+                gap: 0.5rem;
+            }
+
+            .partials-title {
+                font-weight: 600;
+                font-size: 0.875rem;
+                margin-right: auto;
+            }
+
+            .partials-name-input {
+                padding: 0.25rem 0.5rem;
+                background: var(--bg-secondary);
+                border: 1px solid var(--border);
+                border-radius: 4px;
+                color: var(--text-primary);
+                font-size: 0.75rem;
+            }
+
+            .partials-hint {
+                font-size: 0.75rem;
+                color: var(--text-secondary);
+            }
+
+            .partials-error {
+                font-size: 0.75rem;
+                color: var(--error);
+            }
+
+            .partial-item {
+                display: flex;
+                flex-direction: column;
+                gap: 0.25rem;
+            }
+
+            .partial-item-header {
+                display: flex;
+                align-items: center;
+                justify-content: space-between;
+            }
+
+            .partial-path {
+                font-size: 0.75rem;
+                color: var(--text-primary);
+            }
+
+            .partial-delete {
+                background: none;
+                border: none;
+                color: var(--text-secondary);
+                cursor: pointer;
+            }
+
+            .partial-source {
+                min-height: 5rem;
+                padding: 0.5rem;
+                background: var(--bg-secondary);
+                border: 1px solid var(--border);
+                border-radius: 4px;
+                color: var(--text-primary);
+                font-family: monospace;
+                font-size: 0.75rem;
+                resize: vertical;
+            }
+            "#
+        </style>
+    }
+}
@@ -0,0 +1,194 @@
+//! Sections list in the Content tab
+//!
+//! Lists each section's heading with drag-to-reorder, add and delete. The
+//! other section fields are still edited in the JSON view.
+
+use leptos::*;
+
+use crate::data::{Section, SlickSheetData};
+
+/// Heading given to newly added sections
+pub const NEW_SECTION_HEADING: &str = "New section";
+
+/// Append an empty text section
+pub fn add_section(data: &mut SlickSheetData) {
+    data.sections.push(Section::text(NEW_SECTION_HEADING, ""));
+}
+
+/// Move the section at `from` to position `to`, returning whether it moved
+pub fn reorder_section(data: &mut SlickSheetData, from: usize, to: usize) -> bool {
+    from != to && data.move_section(from, to).is_ok()
+}
+
+/// Remove the section at `index`, returning whether one was removed
+pub fn delete_section(data: &mut SlickSheetData, index: usize) -> bool {
+    data.remove_section(index).is_ok()
+}
+
+/// Apply `apply` to a copy of the content and report it if anything changed
+fn edit_sections(
+    content_data: RwSignal<SlickSheetData>,
+    on_change: Callback<SlickSheetData>,
+    apply: impl FnOnce(&mut SlickSheetData) -> bool,
+) {
+    let mut data = content_data.get_untracked();
+    if apply(&mut data) {
+        on_change.call(data);
+    }
+}
+
+/// Section headings with drag handles, add and delete buttons
+#[component]
+pub fn SectionsEditor(
+    /// Document content whose `sections` are edited
+    content_data: RwSignal<SlickSheetData>,
+    /// Called with the full updated content after each edit
+    on_change: Callback<SlickSheetData>,
+) -> impl IntoView {
+    let count = create_memo(move |_| content_data.with(|data| data.sections.len()));
+    let dragging = create_rw_signal(Option::<usize>::None);
+    let drop_target = create_rw_signal(Option::<usize>::None);
+
+    view! {
+        <div class="sections-editor">
+            <div class="features-header">
+                <span class="features-title">"Sections"</span>
+                <button
+                    class="features-btn"
+                    on:click=move |_| edit_sections(content_data, on_change, |data| {
+                        add_section(data);
+                        true
+                    })
+                >
+                    "+ Add"
+                </button>
+            </div>
+            <For
+                each=move || 0..count.get()
+                key=|index| *index
+                children=move |index| {
+                    let heading = move || {
+                        content_data.with(|data| {
+                            data.sections.get(index).map(|section| section.heading.clone()).unwrap_or_default()
+                        })
+                    };
+                    let is_target = move || drop_target.get() == Some(index) && dragging.get() != Some(index);
+                    view! {
+                        <div
+                            class="feature-row section-row"
+                            class:drop-target=is_target
+                            draggable="true"
+                            on:dragstart=move |ev: web_sys::DragEvent| {
+                                if let Some(transfer) = ev.data_transfer() {
+                                    transfer.set_effect_allowed("move");
+                                    let _ = transfer.set_data("text/plain", &index.to_string());
+                                }
+                                dragging.set(Some(index));
+                            }
+                            on:dragover=move |ev: web_sys::DragEvent| {
+                                if dragging.get_untracked().is_some() {
+                                    ev.prevent_default();
+                                    drop_target.set(Some(index));
+                                }
+                            }
+                            on:drop=move |ev: web_sys::DragEvent| {
+                                ev.prevent_default();
+                                if let Some(from) = dragging.get_untracked() {
+                                    edit_sections(content_data, on_change, |data| reorder_section(data, from, index));
+                                }
+                                dragging.set(None);
+                                drop_target.set(None);
+                            }
+                            on:dragend=move |_| {
+                                dragging.set(None);
+                                drop_target.set(None);
+                            }
+                        >
+                            <span class="section-handle" title="Drag to reorder">"\u{2630}"</span>
+                            <input
+                                type="text"
+                                class="feature-input"
+                                placeholder="Section heading"
+                                prop:value=heading
+                                on:input=move |ev| {
+                                    let text = event_target_value(&ev);
+                                    edit_sections(content_data, on_change, move |data| {
+                                        match data.sections.get_mut(index) {
+                                            Some(section) => {
+                                                section.heading = text;
+                                                true
+                                            }
+                                            None => false,
+                                        }
+                                    });
+                                }
+                            />
+                            <button
+                                class="features-btn"
+                                title="Move up"
+                                disabled=move || index == 0
+                                on:click=move |_| {
+                                    edit_sections(content_data, on_change, |data| {
+                                        reorder_section(data, index, index.saturating_sub(1))
+                                    })
+                                }
+                            >
+                                "\u{2191}"
+                            </button>
+                            <button
+                                class="features-btn"
+                                title="Move down"
+                                disabled=move || index + 1 >= count.get()
+                                on:click=move |_| {
+                                    edit_sections(content_data, on_change, |data| reorder_section(data, index, index + 1))
+                                }
+                            >
+                                "\u{2193}"
+                            </button>
+                            <button
+                                class="features-btn"
+                                title="Delete section"
+                                on:click=move |_| {
+                                    edit_sections(content_data, on_change, |data| delete_section(data, index))
+                                }
+                            >
+                                "\u{00D7}"
+                            </button>
+                        </div>
+                    }
+                }
+            />
+            {move || (count.get() == 0).then(|| view! {
+                <p class="features-empty">"No sections yet. Add one, then fill it in from the JSON view."</p>
+            })}
+        </div>
+
+        <style>
+            r#"
+            .sections-editor {
+                display: flex;
+                flex-direction: column;
+                gap: 0.375rem;
+                padding: 0.75rem;
+                border-top: 1px solid var(--border);
+            }
+
+            .section-row {
+                align-items: center;
+                border-radius: 4px;
+            }
+
+            .section-row.drop-target {
+                outline: 2px dashed var(--accent);
+            }
+
+            .section-handle {
+                cursor: grab;
+                color: var(--text-secondary);
+                padding: 0 0.25rem;
+                user-select: none;
+            }
+            "#
+        </style>
+    }
+}
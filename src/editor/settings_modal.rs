@@ -1,13 +1,19 @@
-//! AI Settings modal component
+//! Settings modal component
 //!
 //! This component provides a modal for configuring AI settings:
-//! - API key for OpenRouter
+//! - API key for OpenRouter, with a button to test it
 //! - Model selection
-//! - Max iterations for agent loop
+//! - Max iterations for agent loop and the delay between attempts
+//! - Sampling parameters (temperature, top_p, max_tokens)
+//!
+//! and the auto-preview delay, which is stored on its own.
 
 use leptos::*;
 use wasm_bindgen::JsCast;
 
+use super::state::{resolve_preview_delay, MAX_PREVIEW_DELAY_MS};
+use crate::ai::OpenRouterClient;
+
 /// Available AI models (valid OpenRouter model IDs)
 pub const AI_MODELS: &[(&str, &str, &str)] = &[
     (
@@ -28,6 +34,9 @@ pub const AI_MODELS: &[(&str, &str, &str)] = &[
     ),
 ];
 
+/// Default base delay between AI retries, in milliseconds
+pub const DEFAULT_RETRY_DELAY_MS: u32 = 1000;
+
 /// Settings stored in localStorage
 #[derive(Debug, Clone, Default)]
 pub struct AiSettings {
@@ -37,13 +46,37 @@ pub struct AiSettings {
     pub model: String,
     /// Max iterations for agent loop (1-10)
     pub max_iterations: u8,
+    /// Base delay before retrying a failed attempt, in milliseconds (0 = none)
+    pub retry_delay_ms: u32,
+    /// Sampling temperature (None = provider default)
+    pub temperature: Option<f32>,
+    /// Nucleus sampling cutoff (None = provider default)
+    pub top_p: Option<f32>,
+    /// Maximum tokens to generate (None = provider default)
+    pub max_tokens: Option<u32>,
 }
 
+/// localStorage key for the auto-preview delay
+const PREVIEW_DELAY_STORAGE_KEY: &str = "slick_preview_delay_ms";
+
 /// Get localStorage if available
 fn get_storage() -> Option<web_sys::Storage> {
     web_sys::window()?.local_storage().ok().flatten()
 }
 
+/// Load the auto-preview delay from localStorage
+pub fn load_preview_delay() -> u32 {
+    let stored = get_storage().and_then(|s| s.get_item(PREVIEW_DELAY_STORAGE_KEY).ok().flatten());
+    resolve_preview_delay(stored.as_deref())
+}
+
+/// Remember the auto-preview delay in localStorage
+fn save_preview_delay(ms: u32) {
+    if let Some(storage) = get_storage() {
+        let _ = storage.set_item(PREVIEW_DELAY_STORAGE_KEY, &ms.to_string());
+    }
+}
+
 impl AiSettings {
     /// Load settings from localStorage
     pub fn load() -> Self {
@@ -73,10 +106,23 @@ impl AiSettings {
             .and_then(|s| s.parse().ok())
             .unwrap_or(3);
 
+        let retry_delay_ms = storage
+            .get_item("slick_ai_retry_delay_ms")
+            .ok()
+            .flatten()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_RETRY_DELAY_MS);
+
+        let read_optional = |key: &str| storage.get_item(key).ok().flatten();
+
         let settings = Self {
             api_key,
             model,
             max_iterations,
+            retry_delay_ms,
+            temperature: read_optional("slick_ai_temperature").and_then(|s| s.parse().ok()),
+            top_p: read_optional("slick_ai_top_p").and_then(|s| s.parse().ok()),
+            max_tokens: read_optional("slick_ai_max_tokens").and_then(|s| s.parse().ok()),
         };
 
         // Save migrated settings if model was changed
@@ -108,6 +154,26 @@ impl AiSettings {
         let _ = storage.set_item("slick_ai_api_key", &self.api_key);
         let _ = storage.set_item("slick_ai_model", &self.model);
         let _ = storage.set_item("slick_ai_max_iterations", &self.max_iterations.to_string());
+        let _ = storage.set_item("slick_ai_retry_delay_ms", &self.retry_delay_ms.to_string());
+
+        // Unset sampling parameters are removed so the provider default applies
+        let optional = [
+            (
+                "slick_ai_temperature",
+                self.temperature.map(|v| v.to_string()),
+            ),
+            ("slick_ai_top_p", self.top_p.map(|v| v.to_string())),
+            (
+                "slick_ai_max_tokens",
+                self.max_tokens.map(|v| v.to_string()),
+            ),
+        ];
+        for (key, value) in optional {
+            let _ = match value {
+                Some(value) => storage.set_item(key, &value),
+                None => storage.remove_item(key),
+            };
+        }
     }
 
     /// Create default settings
@@ -116,6 +182,10 @@ impl AiSettings {
             api_key: String::new(),
             model: AI_MODELS[0].0.to_string(),
             max_iterations: 3,
+            retry_delay_ms: DEFAULT_RETRY_DELAY_MS,
+            temperature: None,
+            top_p: None,
+            max_tokens: None,
         }
     }
 
@@ -125,9 +195,22 @@ impl AiSettings {
     }
 }
 
+/// Format an optional setting, showing unset values as the provider default
+fn optional_label<T: Copy>(value: Option<T>, format: impl Fn(T) -> String) -> String {
+    value
+        .map(format)
+        .unwrap_or_else(|| "provider default".to_string())
+}
+
 /// Settings modal component
+///
+/// `preview_delay_ms` is updated and persisted on save.
 #[component]
-pub fn SettingsModal(on_save: Callback<AiSettings>, on_close: Callback<()>) -> impl IntoView {
+pub fn SettingsModal(
+    preview_delay_ms: RwSignal<u32>,
+    on_save: Callback<AiSettings>,
+    on_close: Callback<()>,
+) -> impl IntoView {
     // Load current settings
     let initial_settings = AiSettings::load();
 
@@ -135,6 +218,32 @@ pub fn SettingsModal(on_save: Callback<AiSettings>, on_close: Callback<()>) -> i
     let api_key = create_rw_signal(initial_settings.api_key);
     let model = create_rw_signal(initial_settings.model);
     let max_iterations = create_rw_signal(initial_settings.max_iterations);
+    let retry_delay_ms = create_rw_signal(initial_settings.retry_delay_ms);
+    let temperature = create_rw_signal(initial_settings.temperature);
+    let top_p = create_rw_signal(initial_settings.top_p);
+    let max_tokens = create_rw_signal(initial_settings.max_tokens);
+    let preview_delay = create_rw_signal(preview_delay_ms.get_untracked());
+
+    // API key test: in flight, then the outcome
+    let key_testing = create_rw_signal(false);
+    let key_status = create_rw_signal::<Option<Result<(), String>>>(None);
+
+    let handle_test_key = move |_| {
+        let client = OpenRouterClient::new(super::openrouter_config(api_key.get_untracked()));
+        key_testing.set(true);
+        key_status.set(None);
+        spawn_local(async move {
+            let result = client
+                .validate_key()
+                .await
+                .map_err(|err| match err.guidance() {
+                    Some(hint) => format!("{} {}", err, hint),
+                    None => err.to_string(),
+                });
+            key_status.set(Some(result));
+            key_testing.set(false);
+        });
+    };
 
     // Handle save
     let handle_save = move |_| {
@@ -142,8 +251,14 @@ pub fn SettingsModal(on_save: Callback<AiSettings>, on_close: Callback<()>) -> i
             api_key: api_key.get(),
             model: model.get(),
             max_iterations: max_iterations.get(),
+            retry_delay_ms: retry_delay_ms.get(),
+            temperature: temperature.get(),
+            top_p: top_p.get(),
+            max_tokens: max_tokens.get(),
         };
         settings.save();
+        save_preview_delay(preview_delay.get());
+        preview_delay_ms.set(preview_delay.get());
         on_save.call(settings);
     };
 
@@ -162,7 +277,7 @@ pub fn SettingsModal(on_save: Callback<AiSettings>, on_close: Callback<()>) -> i
         >
             <div class="settings-modal-content" on:click=|ev| ev.stop_propagation()>
                 <div class="settings-modal-header">
-                    <h3 class="settings-modal-title">"AI Settings"</h3>
+                    <h3 class="settings-modal-title">"Settings"</h3>
                     <button class="settings-modal-close" on:click=move |_| on_close.call(())>
                         {"\u{00D7}"}
                     </button>
@@ -172,15 +287,33 @@ pub fn SettingsModal(on_save: Callback<AiSettings>, on_close: Callback<()>) -> i
                     // API Key
                     <div class="settings-field">
                         <label class="settings-label">"API Key (OpenRouter)"</label>
-                        <input
-                            type="password"
-                            class="settings-input"
-                            placeholder="sk-or-..."
-                            prop:value=move || api_key.get()
-                            on:input=move |ev| {
-                                api_key.set(event_target_value(&ev));
-                            }
-                        />
+                        <div class="settings-key-row">
+                            <input
+                                type="password"
+                                class="settings-input"
+                                placeholder="sk-or-..."
+                                prop:value=move || api_key.get()
+                                on:input=move |ev| {
+                                    api_key.set(event_target_value(&ev));
+                                    key_status.set(None);
+                                }
+                            />
+                            <button
+                                class="btn btn-secondary settings-key-test"
+                                on:click=handle_test_key
+                                disabled=move || key_testing.get() || api_key.with(|k| k.trim().is_empty())
+                            >
+                                {move || if key_testing.get() { "Testing..." } else { "Test" }}
+                            </button>
+                        </div>
+                        {move || key_status.get().map(|status| match status {
+                            Ok(()) => view! {
+                                <div class="settings-key-status ok">"\u{2713} API key is valid"</div>
+                            },
+                            Err(error) => view! {
+                                <div class="settings-key-status error">{error}</div>
+                            },
+                        })}
                         <a
                             class="settings-help-link"
                             href="https://openrouter.ai/keys"
@@ -235,6 +368,141 @@ pub fn SettingsModal(on_save: Callback<AiSettings>, on_close: Callback<()>) -> i
                             <span>"10"</span>
                         </div>
                     </div>
+
+                    // Retry delay
+                    <div class="settings-field">
+                        <label class="settings-label">
+                            {move || match retry_delay_ms.get() {
+                                0 => "Retry Delay: none".to_string(),
+                                ms => format!("Retry Delay: {:.2}s (doubles each attempt)", ms as f64 / 1000.0),
+                            }}
+                        </label>
+                        <input
+                            type="range"
+                            class="settings-range"
+                            min="0"
+                            max="5000"
+                            step="250"
+                            prop:value=move || retry_delay_ms.get().to_string()
+                            on:input=move |ev| {
+                                if let Ok(val) = event_target_value(&ev).parse::<u32>() {
+                                    retry_delay_ms.set(val);
+                                }
+                            }
+                        />
+                        <div class="settings-range-labels">
+                            <span>"0s"</span>
+                            <span>"5s"</span>
+                        </div>
+                    </div>
+
+                    // Temperature
+                    <div class="settings-field">
+                        <label class="settings-label">
+                            {move || format!("Temperature: {}", optional_label(temperature.get(), |v| format!("{:.1}", v)))}
+                            <button class="settings-reset" on:click=move |_| temperature.set(None)>
+                                "Default"
+                            </button>
+                        </label>
+                        <input
+                            type="range"
+                            class="settings-range"
+                            min="0"
+                            max="2"
+                            step="0.1"
+                            prop:value=move || temperature.get().unwrap_or(1.0).to_string()
+                            on:input=move |ev| {
+                                if let Ok(val) = event_target_value(&ev).parse::<f32>() {
+                                    temperature.set(Some(val));
+                                }
+                            }
+                        />
+                        <div class="settings-range-labels">
+                            <span>"0 (precise)"</span>
+                            <span>"2 (creative)"</span>
+                        </div>
+                    </div>
+
+                    // Top P
+                    <div class="settings-field">
+                        <label class="settings-label">
+                            {move || format!("Top P: {}", optional_label(top_p.get(), |v| format!("{:.2}", v)))}
+                            <button class="settings-reset" on:click=move |_| top_p.set(None)>
+                                "Default"
+                            </button>
+                        </label>
+                        <input
+                            type="range"
+                            class="settings-range"
+                            min="0"
+                            max="1"
+                            step="0.05"
+                            prop:value=move || top_p.get().unwrap_or(1.0).to_string()
+                            on:input=move |ev| {
+                                if let Ok(val) = event_target_value(&ev).parse::<f32>() {
+                                    top_p.set(Some(val));
+                                }
+                            }
+                        />
+                        <div class="settings-range-labels">
+                            <span>"0"</span>
+                            <span>"1"</span>
+                        </div>
+                    </div>
+
+                    // Max tokens
+                    <div class="settings-field">
+                        <label class="settings-label">
+                            {move || format!("Max Tokens: {}", optional_label(max_tokens.get(), |v| v.to_string()))}
+                            <button class="settings-reset" on:click=move |_| max_tokens.set(None)>
+                                "Default"
+                            </button>
+                        </label>
+                        <input
+                            type="range"
+                            class="settings-range"
+                            min="256"
+                            max="16384"
+                            step="256"
+                            prop:value=move || max_tokens.get().unwrap_or(4096).to_string()
+                            on:input=move |ev| {
+                                if let Ok(val) = event_target_value(&ev).parse::<u32>() {
+                                    max_tokens.set(Some(val));
+                                }
+                            }
+                        />
+                        <div class="settings-range-labels">
+                            <span>"256"</span>
+                            <span>"16384"</span>
+                        </div>
+                    </div>
+
+                    // Auto-preview delay
+                    <div class="settings-field">
+                        <label class="settings-label">
+                            {move || match preview_delay.get() {
+                                0 => "Preview Delay: none (compile on every change)".to_string(),
+                                ms => format!("Preview Delay: {}ms", ms),
+                            }}
+                        </label>
+                        <input
+                            type="range"
+                            class="settings-range"
+                            min="0"
+                            max=MAX_PREVIEW_DELAY_MS.to_string()
+                            step="50"
+                            prop:value=move || preview_delay.get().to_string()
+                            on:input=move |ev| {
+                                if let Ok(val) = event_target_value(&ev).parse::<u32>() {
+                                    preview_delay.set(val);
+                                }
+                            }
+                        />
+                        <div class="settings-range-labels">
+                            <span>"0s"</span>
+                            <span>{format!("{}s", MAX_PREVIEW_DELAY_MS / 1000)}</span>
+                        </div>
+                    </div>
                 </div>
 
                 <div class="settings-modal-footer">
@@ -307,6 +575,8 @@ pub fn SettingsModal(on_save: Callback<AiSettings>, on_close: Callback<()>) -> i
 
             .settings-modal-body {
                 padding: 1.5rem;
+                max-height: 70vh;
+                overflow-y: auto;
             }
 
             .settings-field {
@@ -348,6 +618,33 @@ pub fn SettingsModal(on_save: Callback<AiSettings>, on_close: Callback<()>) -> i
                 cursor: pointer;
             }
 
+            .settings-key-row {
+                display: flex;
+                gap: 0.5rem;
+            }
+
+            .settings-key-test {
+                flex-shrink: 0;
+            }
+
+            .settings-key-test:disabled {
+                opacity: 0.5;
+                cursor: not-allowed;
+            }
+
+            .settings-key-status {
+                margin-top: 0.5rem;
+                font-size: 0.8rem;
+            }
+
+            .settings-key-status.ok {
+                color: var(--success);
+            }
+
+            .settings-key-status.error {
+                color: var(--error);
+            }
+
             .settings-help-link {
                 display: inline-block;
                 margin-top: 0.5rem;
@@ -372,6 +669,20 @@ pub fn SettingsModal(on_save: Callback<AiSettings>, on_close: Callback<()>) -> i
                 color: var(--text-secondary);
             }
 
+            .settings-reset {
+                margin-left: 0.5rem;
+                background: none;
+                border: none;
+                color: var(--accent);
+                font-size: 0.75rem;
+                cursor: pointer;
+                padding: 0;
+            }
+
+            .settings-reset:hover {
+                text-decoration: underline;
+            }
+
             .settings-modal-footer {
                 display: flex;
                 justify-content: flex-end;
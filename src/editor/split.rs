@@ -0,0 +1,48 @@
+//! Code/preview split pane sizing
+//!
+//! The split is stored as the fraction of the pane's width given to the code
+//! side, so it survives window resizes.
+
+/// Ratio used on first load and after a double-click reset
+pub const DEFAULT_SPLIT_RATIO: f64 = 0.5;
+
+/// Narrowest either side may be dragged, in CSS pixels
+pub const MIN_PANE_WIDTH: f64 = 240.0;
+
+/// localStorage key for the split ratio
+const SPLIT_STORAGE_KEY: &str = "slick_split_ratio";
+
+/// Code-side ratio for a pointer `offset` pixels from the container's left edge
+///
+/// Both sides keep at least `MIN_PANE_WIDTH`; a container too narrow for
+/// that falls back to an even split.
+pub fn split_ratio_at(offset: f64, container_width: f64) -> f64 {
+    if !offset.is_finite() || container_width < MIN_PANE_WIDTH * 2.0 {
+        return DEFAULT_SPLIT_RATIO;
+    }
+    offset.clamp(MIN_PANE_WIDTH, container_width - MIN_PANE_WIDTH) / container_width
+}
+
+/// Parse a stored ratio, rejecting values outside (0, 1)
+pub fn parse_split_ratio(value: &str) -> Option<f64> {
+    value
+        .parse::<f64>()
+        .ok()
+        .filter(|ratio| *ratio > 0.0 && *ratio < 1.0)
+}
+
+/// Load the split ratio from localStorage
+pub fn load_split_ratio() -> f64 {
+    web_sys::window()
+        .and_then(|w| w.local_storage().ok().flatten())
+        .and_then(|storage| storage.get_item(SPLIT_STORAGE_KEY).ok().flatten())
+        .and_then(|value| parse_split_ratio(&value))
+        .unwrap_or(DEFAULT_SPLIT_RATIO)
+}
+
+/// Remember the split ratio in localStorage
+pub fn save_split_ratio(ratio: f64) {
+    if let Some(storage) = web_sys::window().and_then(|w| w.local_storage().ok().flatten()) {
+        let _ = storage.set_item(SPLIT_STORAGE_KEY, &ratio.to_string());
+    }
+}
@@ -1,9 +1,12 @@
 //! Editor state management with Leptos signals
 
+use std::collections::VecDeque;
+
 use leptos::*;
 
-use crate::data::{default_data_for_template, SlickSheetData};
-use crate::template::TemplateEngine;
+use crate::data::{default_data_for_template, PageSetup, SlickSheetData};
+use crate::template::{apply_page_setup, TemplateEngine};
+use crate::templates::TEMPLATES;
 
 /// Editor tab enum for the 4-way split
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -20,11 +23,11 @@ pub enum EditorTab {
 }
 
 /// Default template with Handlebars placeholders - Modern dark theme
-pub const DEFAULT_TEMPLATE: &str = r##"#set page(width: 8.5in, height: 11in, margin: 0.75in, fill: rgb("#0f0f1a"))
+pub const DEFAULT_TEMPLATE: &str = r##"#set page(width: {{page.width}}, height: {{page.height}}, margin: {{page.margin | default: '0.75in'}}, fill: rgb("#0f0f1a"))
 #set text(font: "Inter", size: 11pt, fill: rgb("#e8e8e8"))
 
 // Header with optional logo
-#rect(width: 100%, fill: rgb("#1a1a2e"), radius: 8pt, inset: 1.2em)[
+#rect(width: 100%, fill: {{#if style.gradientStart}}gradient.linear(rgb("{{{style.gradientStart}}}"), rgb("{{{style.gradientStop}}}"), angle: 135deg){{else}}rgb("#1a1a2e"){{/if}}, radius: 8pt, inset: 1.2em)[
   #grid(
     columns: (auto, 1fr),
     gutter: 1em,
@@ -60,6 +63,18 @@ pub const DEFAULT_TEMPLATE: &str = r##"#set page(width: 8.5in, height: 11in, mar
 #v(1em)
 {{/if}}
 
+{{#each sections}}
+#text(size: 14pt, weight: "bold", fill: rgb("#e94560"))[{{this.heading}}]
+#v(0.3em)
+{{#if this.content}}
+#text(fill: rgb("#b8b8b8"))[{{this.content}}]
+{{/if}}
+{{#each this.items}}
+- {{this}}
+{{/each}}
+#v(1em)
+{{/each}}
+
 {{#if features}}
 #text(size: 14pt, weight: "bold", fill: rgb("#e94560"))[FEATURES]
 #v(0.5em)
@@ -77,7 +92,7 @@ pub const DEFAULT_TEMPLATE: &str = r##"#set page(width: 8.5in, height: 11in, mar
   inset: 12pt,
   align: center,
   {{#each stats}}
-  [#text(size: 28pt, weight: "bold", fill: rgb("#4ecca3"))[{{this.value}}]],
+  [#text(size: 28pt, weight: "bold", fill: rgb("#4ecca3"))[{{#if this.icon}}{{this.icon}} {{/if}}{{this.value}}]],
   {{/each}}
   {{#each stats}}
   [#text(size: 10pt, fill: rgb("#888888"))[{{this.label}}]],
@@ -98,7 +113,254 @@ pub const DEFAULT_TEMPLATE: &str = r##"#set page(width: 8.5in, height: 11in, mar
 {{/if}}
 "##;
 
+/// Auto-preview delay used until the user picks one, in milliseconds
+pub const DEFAULT_PREVIEW_DELAY_MS: u32 = 300;
+
+/// Longest auto-preview delay the settings accept, in milliseconds
+pub const MAX_PREVIEW_DELAY_MS: u32 = 5000;
+
+/// Auto-preview delay from its stored setting
+///
+/// Missing, unparseable or out-of-range values fall back to
+/// `DEFAULT_PREVIEW_DELAY_MS`; 0 is kept and means no debounce.
+pub fn resolve_preview_delay(stored: Option<&str>) -> u32 {
+    stored
+        .and_then(|value| value.trim().parse::<u32>().ok())
+        .filter(|ms| *ms <= MAX_PREVIEW_DELAY_MS)
+        .unwrap_or(DEFAULT_PREVIEW_DELAY_MS)
+}
+
+/// Maximum number of undo snapshots kept
+pub const UNDO_LIMIT: usize = 50;
+
+/// Document state captured before a change, for undo
+#[derive(Debug, Clone, PartialEq)]
+pub struct EditorSnapshot {
+    /// JSON content data
+    pub content_data: SlickSheetData,
+    /// Template source
+    pub template_source: String,
+    /// Rendered Typst source
+    pub typst_source: String,
+}
+
+/// Bounded undo history of editor snapshots
+///
+/// Grouped pushes coalesce consecutive edits of the same kind (e.g. typing
+/// in one editor tab) into a single entry; any other push or a pop closes
+/// the group.
+#[derive(Debug, Clone, Default)]
+pub struct UndoStack {
+    entries: VecDeque<EditorSnapshot>,
+    open_group: Option<&'static str>,
+}
+
+impl UndoStack {
+    /// Create an empty undo stack
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a snapshot taken before a discrete change
+    pub fn push(&mut self, snapshot: EditorSnapshot) {
+        self.open_group = None;
+        self.push_entry(snapshot);
+    }
+
+    /// Record a snapshot unless an edit of the same group is already open
+    pub fn push_grouped(&mut self, group: &'static str, snapshot: EditorSnapshot) {
+        if self.open_group == Some(group) {
+            return;
+        }
+        self.open_group = Some(group);
+        self.push_entry(snapshot);
+    }
+
+    /// Remove and return the most recent snapshot
+    pub fn pop(&mut self) -> Option<EditorSnapshot> {
+        self.open_group = None;
+        self.entries.pop_back()
+    }
+
+    /// Number of snapshots available to undo
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if there is nothing to undo
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn push_entry(&mut self, snapshot: EditorSnapshot) {
+        if self.entries.back() == Some(&snapshot) {
+            return;
+        }
+        self.entries.push_back(snapshot);
+        while self.entries.len() > UNDO_LIMIT {
+            self.entries.pop_front();
+        }
+    }
+}
+
+/// Maximum number of past AI exchanges sent back as context
+pub const CONVERSATION_LIMIT: usize = 3;
+
+/// A completed AI exchange: the user's request and the JSON it produced
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConversationTurn {
+    /// The user's prompt
+    pub prompt: String,
+    /// The assistant's JSON response
+    pub response: String,
+}
+
+/// Bounded history of recent AI exchanges
+///
+/// Replayed into follow-up requests so "make it more formal" refines the
+/// previous result instead of starting over. Only the most recent `limit`
+/// turns are kept.
+#[derive(Debug, Clone)]
+pub struct ConversationHistory {
+    turns: VecDeque<ConversationTurn>,
+    limit: usize,
+}
+
+impl Default for ConversationHistory {
+    fn default() -> Self {
+        Self::new(CONVERSATION_LIMIT)
+    }
+}
+
+impl ConversationHistory {
+    /// Create an empty history keeping at most `limit` turns
+    pub fn new(limit: usize) -> Self {
+        Self {
+            turns: VecDeque::with_capacity(limit),
+            limit,
+        }
+    }
+
+    /// Record a completed exchange, dropping the oldest beyond the limit
+    pub fn push(&mut self, prompt: impl Into<String>, response: impl Into<String>) {
+        self.turns.push_back(ConversationTurn {
+            prompt: prompt.into(),
+            response: response.into(),
+        });
+        while self.turns.len() > self.limit {
+            self.turns.pop_front();
+        }
+    }
+
+    /// Recorded turns, oldest first
+    pub fn turns(&self) -> impl Iterator<Item = &ConversationTurn> {
+        self.turns.iter()
+    }
+
+    /// Number of recorded turns
+    pub fn len(&self) -> usize {
+        self.turns.len()
+    }
+
+    /// Check if there are no recorded turns
+    pub fn is_empty(&self) -> bool {
+        self.turns.is_empty()
+    }
+
+    /// Forget all turns
+    pub fn clear(&mut self) {
+        self.turns.clear();
+    }
+}
+
+/// Maximum number of AI prompts held while offline
+pub const PENDING_PROMPT_LIMIT: usize = 5;
+
+/// An AI prompt submitted while offline, waiting to be sent
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingPrompt {
+    /// Stable ID used to cancel this prompt
+    pub id: u64,
+    /// The user's prompt
+    pub prompt: String,
+}
+
+/// Bounded FIFO of prompts submitted while offline
+///
+/// Prompts are replayed oldest first once the connection returns. When the
+/// queue is full new prompts are rejected rather than dropping older ones.
+#[derive(Debug, Clone)]
+pub struct PendingPrompts {
+    items: VecDeque<PendingPrompt>,
+    next_id: u64,
+    limit: usize,
+}
+
+impl Default for PendingPrompts {
+    fn default() -> Self {
+        Self::new(PENDING_PROMPT_LIMIT)
+    }
+}
+
+impl PendingPrompts {
+    /// Create an empty queue holding at most `limit` prompts
+    pub fn new(limit: usize) -> Self {
+        Self {
+            items: VecDeque::with_capacity(limit),
+            next_id: 0,
+            limit,
+        }
+    }
+
+    /// Queue a prompt, returning its ID, or None if the queue is full
+    pub fn enqueue(&mut self, prompt: impl Into<String>) -> Option<u64> {
+        if self.items.len() >= self.limit {
+            return None;
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        self.items.push_back(PendingPrompt {
+            id,
+            prompt: prompt.into(),
+        });
+        Some(id)
+    }
+
+    /// Remove a queued prompt, returning false if it was not queued
+    pub fn cancel(&mut self, id: u64) -> bool {
+        let before = self.items.len();
+        self.items.retain(|item| item.id != id);
+        self.items.len() != before
+    }
+
+    /// Remove and return the oldest queued prompt
+    pub fn pop_next(&mut self) -> Option<PendingPrompt> {
+        self.items.pop_front()
+    }
+
+    /// Queued prompts, oldest first
+    pub fn iter(&self) -> impl Iterator<Item = &PendingPrompt> {
+        self.items.iter()
+    }
+
+    /// Number of queued prompts
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Check if no prompts are queued
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Check if another prompt would be rejected
+    pub fn is_full(&self) -> bool {
+        self.items.len() >= self.limit
+    }
+}
+
 /// Editor state with reactive signals
+#[derive(Clone, Copy)]
 pub struct EditorState {
     /// Active editor tab
     pub active_tab: RwSignal<EditorTab>,
@@ -114,6 +376,17 @@ pub struct EditorState {
     pub error: RwSignal<Option<String>>,
     /// Auto-preview enabled
     pub auto_preview: RwSignal<bool>,
+    /// Delay after the last source edit before auto-preview compiles, in
+    /// milliseconds (0 = compile on every change)
+    pub preview_delay_ms: RwSignal<u32>,
+    /// Snapshots for undoing AI and manual changes
+    pub undo_stack: RwSignal<UndoStack>,
+    /// Recent AI exchanges replayed as context for follow-ups
+    pub conversation: RwSignal<ConversationHistory>,
+    /// AI prompts submitted while offline, sent on reconnect
+    pub pending_prompts: RwSignal<PendingPrompts>,
+    /// ID of the built-in template the document was created from, if any
+    pub active_template: RwSignal<Option<String>>,
 }
 
 impl EditorState {
@@ -134,6 +407,11 @@ impl EditorState {
             svg_output: create_rw_signal(None),
             error: create_rw_signal(None),
             auto_preview: create_rw_signal(true),
+            preview_delay_ms: create_rw_signal(DEFAULT_PREVIEW_DELAY_MS),
+            undo_stack: create_rw_signal(UndoStack::new()),
+            conversation: create_rw_signal(ConversationHistory::default()),
+            pending_prompts: create_rw_signal(PendingPrompts::default()),
+            active_template: create_rw_signal(None),
         }
     }
 
@@ -150,6 +428,11 @@ impl EditorState {
             svg_output: create_rw_signal(None),
             error: create_rw_signal(None),
             auto_preview: create_rw_signal(true),
+            preview_delay_ms: create_rw_signal(DEFAULT_PREVIEW_DELAY_MS),
+            undo_stack: create_rw_signal(UndoStack::new()),
+            conversation: create_rw_signal(ConversationHistory::default()),
+            pending_prompts: create_rw_signal(PendingPrompts::default()),
+            active_template: create_rw_signal(None),
         }
     }
 
@@ -170,6 +453,11 @@ impl EditorState {
             svg_output: create_rw_signal(None),
             error: create_rw_signal(None),
             auto_preview: create_rw_signal(true),
+            preview_delay_ms: create_rw_signal(DEFAULT_PREVIEW_DELAY_MS),
+            undo_stack: create_rw_signal(UndoStack::new()),
+            conversation: create_rw_signal(ConversationHistory::default()),
+            pending_prompts: create_rw_signal(PendingPrompts::default()),
+            active_template: create_rw_signal(Some(template_id.to_string())),
         }
     }
 
@@ -207,6 +495,93 @@ impl EditorState {
         self.render_template();
     }
 
+    /// Whether the Typst source is the template rendered with the content data
+    pub fn is_data_bound(&self) -> bool {
+        self.template_source.with_untracked(|template| {
+            self.content_data.with_untracked(|data| {
+                self.typst_source
+                    .with_untracked(|source| is_rendered_from_data(template, data, source))
+            })
+        })
+    }
+
+    /// Switch the page size and orientation, keeping any custom margin
+    ///
+    /// The choice is stored in the content data. A data-bound document is
+    /// re-rendered from it; plain Typst gets an override rule instead.
+    pub fn set_page_setup(&self, page: PageSetup) {
+        let data_bound = self.is_data_bound();
+        self.record_undo();
+        let mut applied = page;
+        self.content_data.update(|data| {
+            applied.margin = data.page.as_ref().and_then(|page| page.margin.clone());
+            data.page = Some(applied.clone());
+        });
+        if data_bound {
+            self.render_template();
+        } else {
+            self.typst_source
+                .update(|source| *source = apply_page_setup(source, &applied));
+        }
+    }
+
+    /// Replace the content with the active template's default data
+    ///
+    /// A data-bound document is re-rendered with the defaults; plain Typst
+    /// loaded from a built-in template gets that template's source back.
+    /// Records an undo snapshot first. Returns false, changing nothing, when
+    /// the document was not created from a built-in template.
+    pub fn reset_content(&self) -> bool {
+        let Some(template_id) = self.active_template.get_untracked() else {
+            return false;
+        };
+        let data_bound = self.is_data_bound();
+        self.record_undo();
+        self.content_data
+            .set(default_data_for_template(&template_id));
+        if data_bound {
+            self.render_template();
+        } else if let Some(template) = TEMPLATES.iter().find(|t| t.id == template_id) {
+            self.typst_source.set(template.source.to_string());
+        }
+        true
+    }
+
+    /// Capture the current content, template and source
+    pub fn snapshot(&self) -> EditorSnapshot {
+        EditorSnapshot {
+            content_data: self.content_data.get_untracked(),
+            template_source: self.template_source.get_untracked(),
+            typst_source: self.typst_source.get_untracked(),
+        }
+    }
+
+    /// Record the current state before a discrete change (AI apply, modal edit)
+    pub fn record_undo(&self) {
+        let snapshot = self.snapshot();
+        self.undo_stack.update(|stack| stack.push(snapshot));
+    }
+
+    /// Record the current state before a run of edits in one editor
+    pub fn record_undo_grouped(&self, group: &'static str) {
+        let snapshot = self.snapshot();
+        self.undo_stack
+            .update(|stack| stack.push_grouped(group, snapshot));
+    }
+
+    /// Restore the most recent snapshot, returning false if there was none
+    pub fn undo(&self) -> bool {
+        let mut popped = None;
+        self.undo_stack.update(|stack| popped = stack.pop());
+        let Some(snapshot) = popped else {
+            return false;
+        };
+        self.content_data.set(snapshot.content_data);
+        self.template_source.set(snapshot.template_source);
+        self.typst_source.set(snapshot.typst_source);
+        true
+    }
+
     /// Get JSON representation of current content
     #[allow(dead_code)]
     pub fn get_json(&self) -> Result<String, String> {
@@ -229,3 +604,11 @@ impl Default for EditorState {
         Self::new()
     }
 }
+
+/// Check whether the Typst source is the current template rendered with the data
+///
+/// Built-in templates load raw Typst, so edits only go through the data when
+/// the preview actually reflects it.
+pub fn is_rendered_from_data(template: &str, data: &SlickSheetData, source: &str) -> bool {
+    TemplateEngine::render(template, data).is_ok_and(|rendered| rendered == source)
+}
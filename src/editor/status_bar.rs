@@ -5,6 +5,7 @@
 //! - Project name
 //! - Last saved timestamp
 //! - Current status message
+//! - Word/character counts and the compiled page count
 
 use leptos::*;
 use wasm_bindgen::prelude::*;
@@ -25,6 +26,34 @@ impl ConnectionStatus {
     }
 }
 
+/// Word and character counts of a piece of text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TextStats {
+    /// Whitespace-separated words
+    pub words: usize,
+    /// Unicode characters, including whitespace
+    pub characters: usize,
+}
+
+impl TextStats {
+    /// Count the words and characters in `text`
+    pub fn of(text: &str) -> Self {
+        Self {
+            words: text.split_whitespace().count(),
+            characters: text.chars().count(),
+        }
+    }
+}
+
+/// Label for the compiled page count, e.g. "2 pages"
+pub fn page_count_label(pages: usize) -> String {
+    if pages == 1 {
+        "1 page".to_string()
+    } else {
+        format!("{} pages", pages)
+    }
+}
+
 /// Status bar component
 #[component]
 pub fn StatusBar(
@@ -36,6 +65,12 @@ pub fn StatusBar(
     last_saved: Signal<Option<String>>,
     /// Current status message (optional)
     status_message: Signal<Option<String>>,
+    /// Counts for the content body
+    body_stats: Signal<TextStats>,
+    /// Counts for the whole Typst source
+    total_stats: Signal<TextStats>,
+    /// Pages in the last successful compile (None before the first)
+    page_count: Signal<Option<usize>>,
 ) -> impl IntoView {
     view! {
         <footer class="status-bar-container">
@@ -65,6 +100,33 @@ pub fn StatusBar(
             // Spacer
             <span class="status-spacer" />
 
+            // Body and source sizes, plus pages so overflow is visible
+            <span class="status-counts" title="Body words and characters / Typst source characters">
+                {move || {
+                    let body = body_stats.get();
+                    format!(
+                        "Body: {} words, {} chars / Total: {} chars",
+                        body.words,
+                        body.characters,
+                        total_stats.get().characters
+                    )
+                }}
+            </span>
+            {move || page_count.get().map(|pages| {
+                let overflow = pages > 1;
+                view! {
+                    <span class="status-separator">"|"</span>
+                    <span
+                        class="status-pages"
+                        class:overflow=overflow
+                        title=if overflow { "Content spills past the first page" } else { "" }
+                    >
+                        {page_count_label(pages)}
+                    </span>
+                }
+            })}
+            <span class="status-separator">"|"</span>
+
             // Connection status indicator
             <span class="status-connection" class:online=move || connection_status.get().is_online()>
                 {move || if connection_status.get().is_online() {
@@ -108,6 +170,11 @@ pub fn StatusBar(
                 flex: 1;
             }
 
+            .status-pages.overflow {
+                color: var(--error);
+                font-weight: 500;
+            }
+
             .status-connection {
                 font-weight: 500;
             }
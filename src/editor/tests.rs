@@ -1,7 +1,29 @@
 //! Tests for the editor module
 
+use super::autocomplete::{insert_completion, variable_prefix};
+use super::clipboard::{png_blob_parts, PNG_MIME};
 use super::content::Content;
+use super::features_editor::{add_feature, move_feature, remove_feature};
+use super::find_replace::{find_matches, replace_all, utf16_offset, SearchOptions};
+use super::image_gallery::image_snippet;
+use super::line_numbers::{caret_line, error_line, line_count, line_start};
 use super::links::{parse_cmd_url, EditCommand};
+use super::page_setup::{page_presets, preset_from_key, preset_key};
+use super::partials_panel::normalize_partial_path;
+use super::sections_editor::{add_section, delete_section, reorder_section, NEW_SECTION_HEADING};
+use super::split::{parse_split_ratio, split_ratio_at, DEFAULT_SPLIT_RATIO, MIN_PANE_WIDTH};
+use super::state::{
+    resolve_preview_delay, ConversationHistory, EditorSnapshot, EditorState, PendingPrompts,
+    UndoStack, CONVERSATION_LIMIT, DEFAULT_PREVIEW_DELAY_MS, MAX_PREVIEW_DELAY_MS,
+    PENDING_PROMPT_LIMIT, UNDO_LIMIT,
+};
+use super::status_bar::{page_count_label, TextStats};
+use super::theme::{resolve_theme, Theme};
+use super::zoom::{
+    clamp_zoom, fit_width_zoom, step_zoom, svg_page_size, MAX_ZOOM, MIN_ZOOM, ZOOM_STEP,
+};
+use crate::data::{default_data_for_template, SlickSheetData};
+use leptos::*;
 use std::collections::HashMap;
 
 // ============================================================================
@@ -138,6 +160,51 @@ fn test_parse_cmd_url_metadata() {
     assert_eq!(result, Some(EditCommand::Metadata("author".to_string())));
 }
 
+#[test]
+fn test_parse_cmd_url_array_item() {
+    let result = parse_cmd_url("cmd://edit/features/2");
+    assert_eq!(
+        result,
+        Some(EditCommand::ArrayItem {
+            field: "features".to_string(),
+            index: 2,
+            subfield: None,
+        })
+    );
+}
+
+#[test]
+fn test_parse_cmd_url_array_item_subfield() {
+    let result = parse_cmd_url("cmd://edit/stats/0/value");
+    assert_eq!(
+        result,
+        Some(EditCommand::ArrayItem {
+            field: "stats".to_string(),
+            index: 0,
+            subfield: Some("value".to_string()),
+        })
+    );
+}
+
+#[test]
+fn test_parse_cmd_url_array_item_invalid_index_returns_none() {
+    assert_eq!(parse_cmd_url("cmd://edit/features/two"), None);
+    assert_eq!(parse_cmd_url("cmd://edit/stats/-1/value"), None);
+}
+
+#[test]
+fn test_edit_command_field_id() {
+    assert_eq!(EditCommand::Title.field_id(), "title");
+    assert_eq!(
+        EditCommand::Metadata("author".to_string()).field_id(),
+        "meta/author"
+    );
+    assert_eq!(
+        parse_cmd_url("cmd://edit/stats/0/value").map(|cmd| cmd.field_id()),
+        Some("stats/0/value".to_string())
+    );
+}
+
 #[test]
 fn test_parse_cmd_url_https_returns_none() {
     let result = parse_cmd_url("https://example.com");
@@ -169,9 +236,14 @@ fn test_edit_command_covers_all_fields() {
         EditCommand::Body,
         EditCommand::Image,
         EditCommand::Metadata("test".to_string()),
+        EditCommand::ArrayItem {
+            field: "features".to_string(),
+            index: 0,
+            subfield: None,
+        },
     ];
 
-    assert_eq!(commands.len(), 5);
+    assert_eq!(commands.len(), 6);
 }
 
 #[test]
@@ -187,3 +259,808 @@ fn test_edit_command_debug() {
     let debug = format!("{:?}", cmd);
     assert!(debug.contains("Title"));
 }
+
+// ============================================================================
+// Source Field Update Tests
+// ============================================================================
+
+#[test]
+fn test_update_field_in_source_targets_array_item() {
+    let source = "#link(\"cmd://edit/features/1\")[Fast]\n#link(\"cmd://edit/features/10\")[Cheap]";
+
+    let updated = super::update_field_in_source(source, "features/1", "Faster").unwrap();
+
+    assert!(updated.contains("#link(\"cmd://edit/features/1\")[Faster]"));
+    assert!(updated.contains("#link(\"cmd://edit/features/10\")[Cheap]"));
+    assert_eq!(super::extract_field_value(&updated, "features/10"), "Cheap");
+}
+
+// ============================================================================
+// Undo Stack Tests
+// ============================================================================
+
+fn snapshot(title: &str) -> EditorSnapshot {
+    EditorSnapshot {
+        content_data: SlickSheetData::new(title),
+        template_source: "{{title}}".to_string(),
+        typst_source: title.to_string(),
+    }
+}
+
+#[test]
+fn test_undo_stack_push_and_pop_in_reverse_order() {
+    let mut stack = UndoStack::new();
+    stack.push(snapshot("first"));
+    stack.push(snapshot("second"));
+
+    assert_eq!(stack.len(), 2);
+    assert_eq!(stack.pop(), Some(snapshot("second")));
+    assert_eq!(stack.pop(), Some(snapshot("first")));
+    assert!(stack.is_empty());
+}
+
+#[test]
+fn test_undo_stack_pop_empty_returns_none() {
+    let mut stack = UndoStack::new();
+    assert!(stack.is_empty());
+    assert_eq!(stack.pop(), None);
+}
+
+#[test]
+fn test_undo_stack_is_capped() {
+    let mut stack = UndoStack::new();
+    for i in 0..UNDO_LIMIT + 10 {
+        stack.push(snapshot(&format!("v{}", i)));
+    }
+
+    assert_eq!(stack.len(), UNDO_LIMIT);
+    assert_eq!(stack.pop(), Some(snapshot(&format!("v{}", UNDO_LIMIT + 9))));
+}
+
+#[test]
+fn test_undo_stack_skips_duplicate_snapshot() {
+    let mut stack = UndoStack::new();
+    stack.push(snapshot("same"));
+    stack.push(snapshot("same"));
+
+    assert_eq!(stack.len(), 1);
+}
+
+#[test]
+fn test_undo_stack_grouped_edits_coalesce() {
+    let mut stack = UndoStack::new();
+    stack.push_grouped("content", snapshot("before typing"));
+    stack.push_grouped("content", snapshot("after one key"));
+    stack.push_grouped("template", snapshot("before template edit"));
+
+    assert_eq!(stack.len(), 2);
+    assert_eq!(stack.pop(), Some(snapshot("before template edit")));
+
+    // Popping closes the group, so the next edit is recorded again
+    stack.push_grouped("template", snapshot("new template edit"));
+    assert_eq!(stack.len(), 2);
+}
+
+// ============================================================================
+// Partials Tests
+// ============================================================================
+
+#[test]
+fn test_normalize_partial_path_adds_extension() {
+    assert_eq!(
+        normalize_partial_path(" partials/header "),
+        Ok("partials/header.typ".to_string())
+    );
+    assert_eq!(
+        normalize_partial_path("/footer.typ"),
+        Ok("footer.typ".to_string())
+    );
+}
+
+#[test]
+fn test_normalize_partial_path_rejects_invalid() {
+    assert!(normalize_partial_path("").is_err());
+    assert!(normalize_partial_path("../secret").is_err());
+    assert!(normalize_partial_path("a//b").is_err());
+    assert!(normalize_partial_path("main").is_err());
+}
+
+// ============================================================================
+// Conversation History Tests
+// ============================================================================
+
+#[test]
+fn test_conversation_history_keeps_most_recent_pairs() {
+    let mut history = ConversationHistory::new(2);
+    for i in 1..=4 {
+        history.push(format!("prompt {}", i), format!("{{\"title\": \"{}\"}}", i));
+    }
+
+    assert_eq!(history.len(), 2);
+    let prompts: Vec<&str> = history.turns().map(|t| t.prompt.as_str()).collect();
+    assert_eq!(prompts, vec!["prompt 3", "prompt 4"]);
+    assert_eq!(
+        history.turns().last().unwrap().response,
+        "{\"title\": \"4\"}"
+    );
+}
+
+#[test]
+fn test_conversation_history_default_limit_and_clear() {
+    let mut history = ConversationHistory::default();
+    assert!(history.is_empty());
+    for i in 0..CONVERSATION_LIMIT + 2 {
+        history.push(i.to_string(), "{}");
+    }
+    assert_eq!(history.len(), CONVERSATION_LIMIT);
+
+    history.clear();
+    assert!(history.is_empty());
+}
+
+// ============================================================================
+// Offline Prompt Queue Tests
+// ============================================================================
+
+fn drain_prompts(queue: &mut PendingPrompts) -> Vec<String> {
+    std::iter::from_fn(|| queue.pop_next())
+        .map(|pending| pending.prompt)
+        .collect()
+}
+
+#[test]
+fn test_pending_prompts_flush_in_submission_order() {
+    let mut queue = PendingPrompts::default();
+    queue.enqueue("first");
+    queue.enqueue("second");
+    queue.enqueue("third");
+
+    assert_eq!(drain_prompts(&mut queue), vec!["first", "second", "third"]);
+    assert!(queue.is_empty());
+}
+
+#[test]
+fn test_pending_prompts_rejects_when_full() {
+    let mut queue = PendingPrompts::default();
+    for i in 0..PENDING_PROMPT_LIMIT {
+        assert!(queue.enqueue(format!("prompt {}", i)).is_some());
+    }
+    assert!(queue.is_full());
+    assert_eq!(queue.enqueue("overflow"), None);
+
+    // The oldest prompt is kept, not evicted
+    assert_eq!(queue.pop_next().unwrap().prompt, "prompt 0");
+    assert!(queue.enqueue("now fits").is_some());
+}
+
+#[test]
+fn test_pending_prompts_cancel_removes_only_that_prompt() {
+    let mut queue = PendingPrompts::new(3);
+    queue.enqueue("keep");
+    let cancelled = queue.enqueue("drop").unwrap();
+    queue.enqueue("also keep");
+
+    assert!(queue.cancel(cancelled));
+    assert!(!queue.cancel(cancelled));
+    assert_eq!(drain_prompts(&mut queue), vec!["keep", "also keep"]);
+}
+
+// ============================================================================
+// Find and Replace Tests
+// ============================================================================
+
+const FIND_SAMPLE: &str = "Title: title #title subtitle Title_case";
+
+#[test]
+fn test_find_matches_case_insensitive_by_default() {
+    let matches = find_matches(FIND_SAMPLE, "title", SearchOptions::default());
+    assert_eq!(matches, vec![0..5, 7..12, 14..19, 23..28, 29..34]);
+}
+
+#[test]
+fn test_find_matches_case_sensitive() {
+    let options = SearchOptions {
+        case_sensitive: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        find_matches(FIND_SAMPLE, "Title", options),
+        vec![0..5, 29..34]
+    );
+}
+
+#[test]
+fn test_find_matches_whole_word() {
+    let options = SearchOptions {
+        whole_word: true,
+        ..Default::default()
+    };
+    // "subtitle" and "Title_case" contain the query inside a longer word
+    assert_eq!(
+        find_matches(FIND_SAMPLE, "title", options),
+        vec![0..5, 7..12, 14..19]
+    );
+}
+
+#[test]
+fn test_find_matches_empty_query_and_multibyte_text() {
+    assert!(find_matches(FIND_SAMPLE, "", SearchOptions::default()).is_empty());
+
+    let text = "caf\u{e9} Caf\u{c9}";
+    let matches = find_matches(text, "CAF\u{c9}", SearchOptions::default());
+    assert_eq!(matches, vec![0..5, 6..11]);
+    // Textarea selections count UTF-16 units, not bytes
+    assert_eq!(utf16_offset(text, matches[1].start), 5);
+}
+
+#[test]
+fn test_replace_all_counts_replacements() {
+    let options = SearchOptions {
+        whole_word: true,
+        ..Default::default()
+    };
+    let (text, count) = replace_all(FIND_SAMPLE, "title", "name", options);
+    assert_eq!(count, 3);
+    assert_eq!(text, "name: name #name subtitle Title_case");
+}
+
+// ============================================================================
+// Line Number Tests
+// ============================================================================
+
+const LINES_SAMPLE: &str = "#set page()\n= Title\n\nBody";
+
+#[test]
+fn test_line_count() {
+    assert_eq!(line_count(""), 1);
+    assert_eq!(line_count("one line"), 1);
+    assert_eq!(line_count(LINES_SAMPLE), 4);
+    // A trailing newline starts a new, empty line
+    assert_eq!(line_count("a\nb\n"), 3);
+}
+
+#[test]
+fn test_caret_line_from_offset() {
+    assert_eq!(caret_line(LINES_SAMPLE, 0), 1);
+    // Just before and just after the first newline
+    assert_eq!(caret_line(LINES_SAMPLE, 11), 1);
+    assert_eq!(caret_line(LINES_SAMPLE, 12), 2);
+    assert_eq!(caret_line(LINES_SAMPLE, 20), 3);
+    assert_eq!(caret_line(LINES_SAMPLE, 21), 4);
+    // Past the end stays on the last line
+    assert_eq!(caret_line(LINES_SAMPLE, 500), 4);
+}
+
+#[test]
+fn test_caret_line_counts_utf16_units() {
+    // The emoji is two UTF-16 units, so offset 3 is after the newline
+    assert_eq!(caret_line("\u{1F600}\nx", 2), 1);
+    assert_eq!(caret_line("\u{1F600}\nx", 3), 2);
+}
+
+#[test]
+fn test_line_start_offsets() {
+    assert_eq!(line_start(LINES_SAMPLE, 1), 0);
+    assert_eq!(line_start(LINES_SAMPLE, 2), 12);
+    assert_eq!(line_start(LINES_SAMPLE, 4), 21);
+    assert_eq!(line_start(LINES_SAMPLE, 99), LINES_SAMPLE.len() as u32);
+}
+
+#[test]
+fn test_error_line_from_compile_message() {
+    assert_eq!(error_line("Error: unknown variable: x (line 12)"), Some(12));
+    assert_eq!(error_line("Error: expected (a) (line 3)"), Some(3));
+    assert_eq!(error_line("Error likely in field: body"), None);
+    assert_eq!(error_line("Error: bad (line 0)"), None);
+    assert_eq!(error_line("Error: call f(x)"), None);
+}
+
+// ============================================================================
+// Variable Autocomplete Tests
+// ============================================================================
+
+#[test]
+fn test_variable_prefix_after_open_braces() {
+    assert_eq!(variable_prefix("Hi {{", 5), Some(""));
+    assert_eq!(variable_prefix("Hi {{cont", 9), Some("cont"));
+    assert_eq!(variable_prefix("{{#if contact.em", 16), Some("contact.em"));
+    assert_eq!(variable_prefix("{{{tit", 6), Some("tit"));
+
+    // Closed tags, block keywords and plain text offer nothing
+    assert_eq!(variable_prefix("{{title}} ", 10), None);
+    assert_eq!(variable_prefix("{{#", 3), None);
+    assert_eq!(variable_prefix("plain text", 5), None);
+}
+
+#[test]
+fn test_insert_completion_replaces_typed_prefix() {
+    let (text, caret) = insert_completion("é {{cont}}", 8, "contact.email");
+    assert_eq!(text, "é {{contact.email}}");
+    assert_eq!(caret, 17);
+}
+
+// ============================================================================
+// Image Snippet Tests
+// ============================================================================
+
+#[test]
+fn test_image_snippet_uses_extension_from_mime_type() {
+    assert_eq!(
+        image_snippet("img_abc123", "image/png"),
+        "#image(\"img_abc123.png\", width: 100%)"
+    );
+    assert_eq!(
+        image_snippet("img_photo", "image/jpeg"),
+        "#image(\"img_photo.jpg\", width: 100%)"
+    );
+    assert_eq!(
+        image_snippet("img_logo", "image/svg+xml"),
+        "#image(\"img_logo.svg\", width: 100%)"
+    );
+}
+
+// ============================================================================
+// Preview Zoom Tests
+// ============================================================================
+
+#[test]
+fn test_clamp_zoom_limits_range() {
+    assert_eq!(clamp_zoom(0.01), MIN_ZOOM);
+    assert_eq!(clamp_zoom(100.0), MAX_ZOOM);
+    assert_eq!(clamp_zoom(1.5), 1.5);
+    assert_eq!(clamp_zoom(f32::NAN), 1.0);
+}
+
+#[test]
+fn test_step_zoom_multiplies_and_stays_clamped() {
+    assert!((step_zoom(1.0, 1) - ZOOM_STEP).abs() < 1e-6);
+    assert!((step_zoom(ZOOM_STEP, -1) - 1.0).abs() < 1e-6);
+    assert_eq!(step_zoom(MAX_ZOOM, 1), MAX_ZOOM);
+    assert_eq!(step_zoom(MIN_ZOOM, -3), MIN_ZOOM);
+}
+
+#[test]
+fn test_fit_width_zoom() {
+    assert!((fit_width_zoom(400.0, 800.0) - 0.5).abs() < 1e-6);
+    // Tiny panes clamp rather than shrinking the page to nothing
+    assert_eq!(fit_width_zoom(10.0, 800.0), MIN_ZOOM);
+    // Unmeasured layout falls back to 100%
+    assert_eq!(fit_width_zoom(0.0, 800.0), 1.0);
+}
+
+#[test]
+fn test_svg_page_size_converts_points_to_pixels() {
+    let svg = r#"<svg class="typst-doc" viewBox="0 0 612 792" width="612pt" height="792pt">"#;
+    assert_eq!(svg_page_size(svg), Some((816.0, 1056.0)));
+    assert_eq!(svg_page_size("<div>no svg</div>"), None);
+}
+
+// ============================================================================
+// Split Pane Tests
+// ============================================================================
+
+#[test]
+fn test_split_ratio_follows_pointer() {
+    assert_eq!(split_ratio_at(500.0, 1000.0), 0.5);
+    assert_eq!(split_ratio_at(700.0, 1000.0), 0.7);
+}
+
+#[test]
+fn test_split_ratio_keeps_minimum_pane_width() {
+    let width = 1000.0;
+    assert_eq!(split_ratio_at(10.0, width), MIN_PANE_WIDTH / width);
+    assert_eq!(
+        split_ratio_at(990.0, width),
+        (width - MIN_PANE_WIDTH) / width
+    );
+    // Dragged past the container edges
+    assert_eq!(split_ratio_at(-50.0, width), MIN_PANE_WIDTH / width);
+    assert_eq!(
+        split_ratio_at(2000.0, width),
+        (width - MIN_PANE_WIDTH) / width
+    );
+}
+
+#[test]
+fn test_split_ratio_narrow_container_uses_default() {
+    assert_eq!(split_ratio_at(100.0, MIN_PANE_WIDTH), DEFAULT_SPLIT_RATIO);
+    assert_eq!(split_ratio_at(f64::NAN, 1000.0), DEFAULT_SPLIT_RATIO);
+}
+
+#[test]
+fn test_parse_split_ratio_rejects_out_of_range() {
+    assert_eq!(parse_split_ratio("0.35"), Some(0.35));
+    assert_eq!(parse_split_ratio("0"), None);
+    assert_eq!(parse_split_ratio("1.5"), None);
+    assert_eq!(parse_split_ratio("wide"), None);
+}
+
+#[test]
+fn test_resolve_preview_delay() {
+    assert_eq!(resolve_preview_delay(Some("800")), 800);
+    assert_eq!(resolve_preview_delay(Some("0")), 0);
+    assert_eq!(
+        resolve_preview_delay(Some(&MAX_PREVIEW_DELAY_MS.to_string())),
+        MAX_PREVIEW_DELAY_MS
+    );
+
+    // Missing, out-of-range and malformed values use the default
+    assert_eq!(resolve_preview_delay(None), DEFAULT_PREVIEW_DELAY_MS);
+    assert_eq!(
+        resolve_preview_delay(Some("60000")),
+        DEFAULT_PREVIEW_DELAY_MS
+    );
+    assert_eq!(
+        resolve_preview_delay(Some("-100")),
+        DEFAULT_PREVIEW_DELAY_MS
+    );
+    assert_eq!(
+        resolve_preview_delay(Some("fast")),
+        DEFAULT_PREVIEW_DELAY_MS
+    );
+}
+
+// ============================================================================
+// Theme Tests
+// ============================================================================
+
+#[test]
+fn test_resolve_theme_stored_value_beats_system() {
+    assert_eq!(resolve_theme(Some("dark"), true), Theme::Dark);
+    assert_eq!(resolve_theme(Some("light"), false), Theme::Light);
+}
+
+#[test]
+fn test_resolve_theme_falls_back_to_system() {
+    assert_eq!(resolve_theme(None, true), Theme::Light);
+    assert_eq!(resolve_theme(None, false), Theme::Dark);
+    // Unrecognized stored values are ignored
+    assert_eq!(resolve_theme(Some("sepia"), true), Theme::Light);
+}
+
+#[test]
+fn test_theme_round_trips_and_toggles() {
+    for theme in [Theme::Dark, Theme::Light] {
+        assert_eq!(Theme::parse(theme.as_str()), Some(theme));
+        assert_eq!(theme.toggled().toggled(), theme);
+    }
+    assert_eq!(Theme::Dark.toggled(), Theme::Light);
+}
+
+// ============================================================================
+// Features Editor Tests
+// ============================================================================
+
+fn features(items: &[&str]) -> Vec<String> {
+    items.iter().map(|s| s.to_string()).collect()
+}
+
+#[test]
+fn test_add_feature_appends_empty_item() {
+    let mut list = features(&["Fast"]);
+    add_feature(&mut list);
+
+    assert_eq!(list, features(&["Fast", ""]));
+}
+
+#[test]
+fn test_remove_feature() {
+    let mut list = features(&["Fast", "Cheap", "Good"]);
+    remove_feature(&mut list, 1);
+    assert_eq!(list, features(&["Fast", "Good"]));
+
+    // Out of range is a no-op
+    remove_feature(&mut list, 5);
+    assert_eq!(list, features(&["Fast", "Good"]));
+}
+
+#[test]
+fn test_move_feature() {
+    let mut list = features(&["A", "B", "C", "D"]);
+
+    assert!(move_feature(&mut list, 0, 2));
+    assert_eq!(list, features(&["B", "C", "A", "D"]));
+
+    assert!(move_feature(&mut list, 3, 0));
+    assert_eq!(list, features(&["D", "B", "C", "A"]));
+
+    assert!(!move_feature(&mut list, 1, 1));
+    assert!(!move_feature(&mut list, 0, 4));
+    assert_eq!(list, features(&["D", "B", "C", "A"]));
+}
+
+// ============================================================================
+// Sections Editor Tests
+// ============================================================================
+
+fn section_headings(data: &SlickSheetData) -> Vec<&str> {
+    data.sections.iter().map(|s| s.heading.as_str()).collect()
+}
+
+fn sections_data(headings: &[&str]) -> SlickSheetData {
+    use crate::data::Section;
+
+    headings
+        .iter()
+        .fold(SlickSheetData::new("Sheet"), |data, heading| {
+            data.with_section(Section::text(*heading, format!("{} text", heading)))
+        })
+}
+
+#[test]
+fn test_add_section_appends_blank_text_section() {
+    let mut data = sections_data(&["Intro"]);
+    add_section(&mut data);
+
+    assert_eq!(section_headings(&data), ["Intro", NEW_SECTION_HEADING]);
+    assert!(data.sections[1].content.is_empty());
+}
+
+#[test]
+fn test_reorder_section() {
+    let mut data = sections_data(&["A", "B", "C"]);
+
+    assert!(reorder_section(&mut data, 0, 2));
+    assert_eq!(section_headings(&data), ["B", "C", "A"]);
+    assert_eq!(data.sections[2].content, "A text");
+
+    assert!(reorder_section(&mut data, 2, 0));
+    assert_eq!(section_headings(&data), ["A", "B", "C"]);
+
+    assert!(!reorder_section(&mut data, 1, 1));
+    assert!(!reorder_section(&mut data, 0, 3));
+    assert_eq!(section_headings(&data), ["A", "B", "C"]);
+}
+
+#[test]
+fn test_delete_section() {
+    let mut data = sections_data(&["A", "B", "C"]);
+
+    assert!(delete_section(&mut data, 1));
+    assert_eq!(section_headings(&data), ["A", "C"]);
+    assert!(!delete_section(&mut data, 2));
+    assert_eq!(section_headings(&data), ["A", "C"]);
+}
+
+#[test]
+fn test_default_template_renders_sections_in_order() {
+    use crate::template::TemplateEngine;
+
+    let mut data = sections_data(&["Overview", "Pricing"]);
+    let position = |source: &str, text: &str| source.find(text).unwrap();
+
+    let source = TemplateEngine::render(super::state::DEFAULT_TEMPLATE, &data).unwrap();
+    assert!(position(&source, "Overview text") < position(&source, "Pricing text"));
+
+    reorder_section(&mut data, 1, 0);
+    let source = TemplateEngine::render(super::state::DEFAULT_TEMPLATE, &data).unwrap();
+    assert!(position(&source, "Pricing text") < position(&source, "Overview text"));
+    assert!(crate::world::VirtualWorld::compile_to_svg(&source).is_ok());
+}
+
+#[test]
+fn test_default_template_has_no_lints() {
+    assert_eq!(
+        crate::template::lint(super::state::DEFAULT_TEMPLATE),
+        vec![]
+    );
+}
+
+#[test]
+fn test_default_template_compiles_with_gradient() {
+    use crate::data::{SlickSheetData, StyleHints};
+    use crate::template::TemplateEngine;
+    use crate::world::VirtualWorld;
+
+    let data = SlickSheetData::new("Gradient").with_style(StyleHints {
+        background_gradient: Some(("#1a1a2e".to_string(), "#e94560".to_string())),
+        ..Default::default()
+    });
+    let source = TemplateEngine::render(super::state::DEFAULT_TEMPLATE, &data).unwrap();
+
+    assert!(source.contains("gradient.linear("));
+    let result = VirtualWorld::compile_to_svg(&source);
+    assert!(result.is_ok(), "{:?}", result.err());
+}
+
+// ============================================================================
+// Retry Countdown Tests
+// ============================================================================
+
+#[test]
+fn test_retry_countdown_steps_whole_seconds() {
+    use super::chat_panel::retry_countdown;
+    use std::time::Duration;
+
+    assert_eq!(
+        retry_countdown(Duration::from_millis(2500)),
+        vec![
+            (3, Duration::from_secs(1)),
+            (2, Duration::from_secs(1)),
+            (1, Duration::from_millis(500)),
+        ]
+    );
+    assert_eq!(
+        retry_countdown(Duration::from_secs(2)),
+        vec![(2, Duration::from_secs(1)), (1, Duration::from_secs(1))]
+    );
+    assert!(retry_countdown(Duration::ZERO).is_empty());
+}
+
+#[test]
+fn test_retrying_state_shows_countdown() {
+    use super::chat_panel::AiProcessingState;
+
+    let state = AiProcessingState::Retrying { seconds: 2 };
+    assert!(state.is_processing());
+    assert_eq!(state.display_text(), "Retrying in 2s...");
+}
+
+// ============================================================================
+// Reset Content Tests
+// ============================================================================
+
+#[test]
+fn test_reset_content_restores_template_defaults() {
+    let runtime = leptos::create_runtime();
+    let state = EditorState::from_template("event-flyer");
+    let edited = SlickSheetData::new("Edited by hand");
+    state.content_data.set(edited.clone());
+
+    assert!(state.reset_content());
+
+    let defaults = default_data_for_template("event-flyer");
+    assert_eq!(state.content_data.get_untracked(), defaults);
+    assert!(state.typst_source.get_untracked().contains(&defaults.title));
+    // The edited content is one undo away
+    assert!(state.undo());
+    assert_eq!(state.content_data.get_untracked(), edited);
+
+    runtime.dispose();
+}
+
+#[test]
+fn test_reset_content_reloads_plain_template_source() {
+    let runtime = leptos::create_runtime();
+    let template = crate::templates::TEMPLATES
+        .iter()
+        .find(|t| t.id == "product-sheet")
+        .unwrap();
+    // As loaded from the gallery: raw Typst, with the default template unchanged
+    let state = EditorState::new();
+    state.typst_source.set(template.source.to_string());
+    state.active_template.set(Some(template.id.to_string()));
+    state.typst_source.set("= Hand-edited".to_string());
+
+    assert!(state.reset_content());
+
+    assert_eq!(state.typst_source.get_untracked(), template.source);
+    assert_eq!(
+        state.content_data.get_untracked(),
+        default_data_for_template(template.id)
+    );
+
+    runtime.dispose();
+}
+
+#[test]
+fn test_reset_content_needs_active_template() {
+    let runtime = leptos::create_runtime();
+    let state = EditorState::new();
+    let before = state.content_data.get_untracked();
+
+    assert!(!state.reset_content());
+    assert_eq!(state.content_data.get_untracked(), before);
+    assert!(state.undo_stack.with_untracked(|stack| stack.is_empty()));
+
+    runtime.dispose();
+}
+
+// ============================================================================
+// Clipboard PNG Tests
+// ============================================================================
+
+#[test]
+fn test_png_blob_parts_accepts_rendered_png() {
+    let bytes = crate::persistence::png_bytes_from_source("= Hello", 0.5).unwrap();
+    let (mime, payload) = png_blob_parts(&bytes).unwrap();
+    assert_eq!(mime, PNG_MIME);
+    assert_eq!(payload, bytes.as_slice());
+}
+
+#[test]
+fn test_png_blob_parts_rejects_non_png() {
+    assert!(png_blob_parts(b"<svg></svg>").is_err());
+    assert!(png_blob_parts(&[]).is_err());
+}
+
+// ============================================================================
+// Page Setup Tests
+// ============================================================================
+
+#[test]
+fn test_page_presets_round_trip_keys() {
+    let presets = page_presets();
+    assert_eq!(presets.len(), 6);
+    for preset in &presets {
+        assert_eq!(preset_from_key(&preset_key(preset)).as_ref(), Some(preset));
+    }
+    assert!(preset_from_key("tabloid-portrait").is_none());
+}
+
+#[test]
+fn test_default_template_renders_page_setup() {
+    use crate::data::{Orientation, PageSetup, PageSize};
+    use crate::template::TemplateEngine;
+    use crate::world::VirtualWorld;
+
+    let data = SlickSheetData::new("A4 sheet")
+        .with_page(PageSetup::new(PageSize::A4, Orientation::Landscape).with_margin("1cm"));
+    let source = TemplateEngine::render(super::state::DEFAULT_TEMPLATE, &data).unwrap();
+    assert!(source.starts_with("#set page(width: 297mm, height: 210mm, margin: 1cm,"));
+    assert!(VirtualWorld::compile_to_svg(&source).is_ok());
+}
+
+#[test]
+fn test_set_page_setup_rerenders_data_bound_document() {
+    use crate::data::{Orientation, PageSetup, PageSize};
+
+    let runtime = leptos::create_runtime();
+    let state = EditorState::new();
+    assert!(state.is_data_bound());
+
+    state.set_page_setup(PageSetup::new(PageSize::A4, Orientation::Landscape));
+
+    let source = state.typst_source.get_untracked();
+    assert!(
+        source.starts_with("#set page(width: 297mm, height: 210mm"),
+        "{}",
+        source
+    );
+    assert!(!source.contains("// page setup"));
+    assert!(state.is_data_bound());
+
+    runtime.dispose();
+}
+
+#[test]
+fn test_set_page_setup_keeps_margin_and_overrides_source() {
+    use crate::data::{Orientation, PageSetup, PageSize};
+
+    let runtime = leptos::create_runtime();
+    let state =
+        EditorState::with_source("#set page(width: 8.5in, height: 11in)\n= Plain".to_string());
+    state.content_data.update(|data| {
+        data.page = Some(PageSetup::default().with_margin("2cm"));
+    });
+
+    state.set_page_setup(PageSetup::new(PageSize::A4, Orientation::Portrait));
+
+    let page = state
+        .content_data
+        .with_untracked(|data| data.page_or_default());
+    assert_eq!(page.size, PageSize::A4);
+    assert_eq!(page.margin.as_deref(), Some("2cm"));
+    assert!(state
+        .typst_source
+        .get_untracked()
+        .contains("#set page(width: 210mm, height: 297mm, margin: 2cm) // page setup"));
+    assert!(!state.undo_stack.with_untracked(|stack| stack.is_empty()));
+
+    runtime.dispose();
+}
+
+#[test]
+fn test_text_stats_counts_words_and_characters() {
+    assert_eq!(TextStats::of(""), TextStats::default());
+    let stats = TextStats::of("  Fast, reliable\nwidgets \u{2014} caf\u{e9} ");
+    assert_eq!(stats.words, 5);
+    // Characters, not bytes
+    assert_eq!(stats.characters, 32);
+}
+
+#[test]
+fn test_page_count_label() {
+    assert_eq!(page_count_label(1), "1 page");
+    assert_eq!(page_count_label(3), "3 pages");
+}
@@ -0,0 +1,89 @@
+//! Light/dark editor theme
+//!
+//! The palette lives in CSS variables keyed off a `data-theme` attribute on
+//! the root element. An explicit choice is stored in localStorage; until the
+//! user picks one, the system `prefers-color-scheme` setting is followed.
+
+/// localStorage key for the chosen theme
+pub const THEME_STORAGE_KEY: &str = "slick_theme";
+
+/// Editor color theme
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Theme {
+    /// Dark palette (default)
+    #[default]
+    Dark,
+    /// Light palette for bright rooms
+    Light,
+}
+
+impl Theme {
+    /// Value stored in localStorage and the `data-theme` attribute
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Dark => "dark",
+            Self::Light => "light",
+        }
+    }
+
+    /// Parse a stored theme name, ignoring unknown values
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "dark" => Some(Self::Dark),
+            "light" => Some(Self::Light),
+            _ => None,
+        }
+    }
+
+    /// The other theme
+    pub fn toggled(self) -> Self {
+        match self {
+            Self::Dark => Self::Light,
+            Self::Light => Self::Dark,
+        }
+    }
+}
+
+/// Pick the theme: a valid stored choice wins, otherwise follow the system
+pub fn resolve_theme(stored: Option<&str>, system_prefers_light: bool) -> Theme {
+    match stored.and_then(Theme::parse) {
+        Some(theme) => theme,
+        None if system_prefers_light => Theme::Light,
+        None => Theme::Dark,
+    }
+}
+
+/// Load the theme from localStorage, falling back to `prefers-color-scheme`
+pub fn load_theme() -> Theme {
+    let Some(window) = web_sys::window() else {
+        return Theme::default();
+    };
+    let stored = window
+        .local_storage()
+        .ok()
+        .flatten()
+        .and_then(|storage| storage.get_item(THEME_STORAGE_KEY).ok().flatten());
+    let prefers_light = window
+        .match_media("(prefers-color-scheme: light)")
+        .ok()
+        .flatten()
+        .is_some_and(|query| query.matches());
+    resolve_theme(stored.as_deref(), prefers_light)
+}
+
+/// Remember an explicit theme choice
+pub fn save_theme(theme: Theme) {
+    if let Some(storage) = web_sys::window().and_then(|w| w.local_storage().ok().flatten()) {
+        let _ = storage.set_item(THEME_STORAGE_KEY, theme.as_str());
+    }
+}
+
+/// Set `data-theme` on the root element so the CSS palette switches
+pub fn apply_theme(theme: Theme) {
+    if let Some(root) = web_sys::window()
+        .and_then(|w| w.document())
+        .and_then(|d| d.document_element())
+    {
+        let _ = root.set_attribute("data-theme", theme.as_str());
+    }
+}
@@ -0,0 +1,57 @@
+//! Preview zoom calculations
+//!
+//! Zoom is a scale factor relative to the page's intrinsic size, so 1.0
+//! shows the page at 100% (one Typst point is 4/3 CSS pixels).
+
+/// Smallest allowed zoom factor
+pub const MIN_ZOOM: f32 = 0.25;
+
+/// Largest allowed zoom factor
+pub const MAX_ZOOM: f32 = 4.0;
+
+/// Multiplier applied per zoom step (button press or wheel notch)
+pub const ZOOM_STEP: f32 = 1.2;
+
+/// CSS pixels per Typst point
+const PX_PER_PT: f64 = 96.0 / 72.0;
+
+/// Clamp a zoom factor into the supported range, treating NaN as 100%
+pub fn clamp_zoom(zoom: f32) -> f32 {
+    if zoom.is_nan() {
+        1.0
+    } else {
+        zoom.clamp(MIN_ZOOM, MAX_ZOOM)
+    }
+}
+
+/// Zoom in (positive `steps`) or out (negative `steps`) from `zoom`
+pub fn step_zoom(zoom: f32, steps: i32) -> f32 {
+    clamp_zoom(zoom * ZOOM_STEP.powi(steps))
+}
+
+/// Zoom that makes a page `page_width` pixels wide fill `available` pixels
+pub fn fit_width_zoom(available: f64, page_width: f64) -> f32 {
+    if available <= 0.0 || page_width <= 0.0 {
+        return 1.0;
+    }
+    clamp_zoom((available / page_width) as f32)
+}
+
+/// Intrinsic page size in CSS pixels, read from the `width`/`height`
+/// attributes of the root `<svg>` element emitted by typst-svg
+pub fn svg_page_size(svg: &str) -> Option<(f64, f64)> {
+    let start = svg.find("<svg")?;
+    let tag = &svg[start..start + svg[start..].find('>')?];
+    Some((svg_length(tag, "width")?, svg_length(tag, "height")?))
+}
+
+/// Parse a `name="612pt"` (or unitless pixel) attribute into CSS pixels
+fn svg_length(tag: &str, name: &str) -> Option<f64> {
+    let pattern = format!(" {}=\"", name);
+    let start = tag.find(&pattern)? + pattern.len();
+    let value = &tag[start..start + tag[start..].find('"')?];
+    match value.strip_suffix("pt") {
+        Some(points) => points.parse::<f64>().ok().map(|pt| pt * PX_PER_PT),
+        None => value.trim_end_matches("px").parse().ok(),
+    }
+}
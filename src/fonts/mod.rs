@@ -0,0 +1,212 @@
+//! Custom font support for Slick Sheet Studio
+//!
+//! This module provides:
+//! - Font metadata and validation of uploaded TTF/OTF files
+//! - IndexedDB-based font storage
+//! - Font cache for registering fonts with VirtualWorld before compile
+
+mod store;
+
+pub use store::FontStore;
+
+use serde::{Deserialize, Serialize};
+use typst::foundations::Bytes;
+use typst::text::Font;
+
+use crate::world::VirtualWorld;
+
+/// Maximum allowed font size in bytes (20 MB)
+pub const MAX_FONT_SIZE: usize = 20 * 1024 * 1024;
+
+/// Supported font file extensions
+pub const SUPPORTED_FONT_EXTENSIONS: &[&str] = &["ttf", "otf", "ttc", "otc"];
+
+/// Metadata for a stored font
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FontMetadata {
+    /// Unique identifier (e.g., "font_a1b2c3d4")
+    pub id: String,
+    /// Original filename
+    pub filename: String,
+    /// Family names of the faces in the file
+    pub families: Vec<String>,
+    /// Size in bytes
+    pub size: usize,
+    /// Creation timestamp (ISO 8601)
+    pub created_at: String,
+}
+
+impl FontMetadata {
+    /// Create new font metadata
+    pub fn new(id: String, filename: String, families: Vec<String>, size: usize) -> Self {
+        Self {
+            id,
+            filename,
+            families,
+            size,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+
+    /// Family names joined for display
+    pub fn family_label(&self) -> String {
+        self.families.join(", ")
+    }
+}
+
+/// Check if a file extension is a supported font format
+pub fn is_supported_font_extension(extension: &str) -> bool {
+    SUPPORTED_FONT_EXTENSIONS.contains(&extension.to_lowercase().as_str())
+}
+
+/// Parse font bytes and return the distinct family names they contain
+pub fn parse_font_families(data: &[u8]) -> Result<Vec<String>, FontError> {
+    let mut families: Vec<String> = Vec::new();
+    for font in Font::iter(Bytes::from(data.to_vec())) {
+        if !families.contains(&font.info().family) {
+            families.push(font.info().family.clone());
+        }
+    }
+
+    if families.is_empty() {
+        Err(FontError::Unparseable)
+    } else {
+        Ok(families)
+    }
+}
+
+/// Generate a unique font ID
+pub fn generate_font_id() -> String {
+    let uuid = uuid::Uuid::new_v4().simple().to_string();
+    format!("font_{}", &uuid[..16])
+}
+
+/// In-memory cache of uploaded fonts
+///
+/// Fonts are loaded asynchronously from IndexedDB and registered with each
+/// VirtualWorld before compilation.
+#[derive(Debug, Clone, Default)]
+pub struct FontCache {
+    /// Cached font data: font_id -> bytes
+    fonts: Vec<(String, Bytes)>,
+}
+
+impl FontCache {
+    /// Create a new empty font cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a font to the cache, replacing any with the same ID
+    pub fn add(&mut self, id: String, data: Vec<u8>) {
+        self.remove(&id);
+        self.fonts.push((id, Bytes::from(data)));
+    }
+
+    /// Remove a font by ID
+    pub fn remove(&mut self, id: &str) {
+        self.fonts.retain(|(font_id, _)| font_id != id);
+    }
+
+    /// Check if a font is cached
+    pub fn contains(&self, id: &str) -> bool {
+        self.fonts.iter().any(|(font_id, _)| font_id == id)
+    }
+
+    /// Get all cached font IDs
+    pub fn font_ids(&self) -> Vec<&String> {
+        self.fonts.iter().map(|(id, _)| id).collect()
+    }
+
+    /// Get the number of cached fonts
+    pub fn len(&self) -> usize {
+        self.fonts.len()
+    }
+
+    /// Check if cache is empty
+    pub fn is_empty(&self) -> bool {
+        self.fonts.is_empty()
+    }
+
+    /// Pre-load all fonts from IndexedDB
+    pub async fn preload_all(&mut self, store: &FontStore) -> Result<(), FontError> {
+        for metadata in store.list_fonts().await? {
+            if !self.contains(&metadata.id) {
+                let data = store.get_font_data(&metadata.id).await?;
+                self.add(metadata.id, data);
+            }
+        }
+        Ok(())
+    }
+
+    /// Register all cached fonts with a VirtualWorld
+    ///
+    /// Fonts are validated on upload, so unparseable entries are skipped.
+    pub fn populate_world(&self, world: &mut VirtualWorld) {
+        for (_, bytes) in &self.fonts {
+            let _ = world.add_font(bytes.clone());
+        }
+    }
+}
+
+/// Errors that can occur during font operations
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum FontError {
+    /// File is too large
+    #[error("Font file too large: {0} bytes (max: {MAX_FONT_SIZE} bytes)")]
+    FileTooLarge(usize),
+    /// File is not a parseable TTF/OTF font
+    #[error("Unparseable font file: expected a TTF or OTF font")]
+    Unparseable,
+    /// Storage error
+    #[error("Storage error: {0}")]
+    StorageError(String),
+    /// Font not found
+    #[error("Font not found: {0}")]
+    NotFound(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TUFFY: &[u8] = include_bytes!("../../tests/fixtures/fonts/Tuffy.ttf");
+
+    #[test]
+    fn test_is_supported_font_extension() {
+        assert!(is_supported_font_extension("ttf"));
+        assert!(is_supported_font_extension("OTF"));
+        assert!(!is_supported_font_extension("woff2"));
+        assert!(!is_supported_font_extension("png"));
+    }
+
+    #[test]
+    fn test_parse_font_families() {
+        assert_eq!(parse_font_families(TUFFY), Ok(vec!["Tuffy".to_string()]));
+        assert_eq!(
+            parse_font_families(b"not a font at all"),
+            Err(FontError::Unparseable)
+        );
+    }
+
+    #[test]
+    fn test_generate_font_id() {
+        let id = generate_font_id();
+        assert!(id.starts_with("font_"));
+        assert_eq!(id.len(), "font_".len() + 16);
+        assert_ne!(id, generate_font_id());
+    }
+
+    #[test]
+    fn test_font_cache_populates_world() {
+        let mut cache = FontCache::new();
+        cache.add("font_1".to_string(), TUFFY.to_vec());
+        cache.add("font_1".to_string(), TUFFY.to_vec());
+        assert_eq!(cache.len(), 1);
+
+        let mut world = VirtualWorld::new("Hello");
+        let before = world.fonts().len();
+        cache.populate_world(&mut world);
+        assert_eq!(world.fonts().len(), before + 1);
+    }
+}
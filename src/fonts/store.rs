@@ -0,0 +1,199 @@
+//! IndexedDB-based font storage
+//!
+//! Provides persistent storage for uploaded fonts in the browser using
+//! IndexedDB, mirroring the image store layout.
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{IdbDatabase, IdbRequest};
+
+use super::{generate_font_id, parse_font_families, FontError, FontMetadata, MAX_FONT_SIZE};
+use crate::images::store::{wait_for_request, wait_for_transaction};
+
+/// Database name for font storage
+const DB_NAME: &str = "slick_sheet_fonts";
+/// Database version
+const DB_VERSION: u32 = 1;
+/// Object store for font metadata
+const METADATA_STORE: &str = "metadata";
+/// Object store for font binary data
+const DATA_STORE: &str = "data";
+
+/// Convert an IndexedDB helper error into a font storage error
+fn storage_error(err: impl std::fmt::Display) -> FontError {
+    FontError::StorageError(err.to_string())
+}
+
+/// Font store backed by IndexedDB
+#[derive(Clone)]
+pub struct FontStore {
+    db: IdbDatabase,
+}
+
+impl FontStore {
+    /// Open or create the font store database
+    pub async fn open() -> Result<Self, FontError> {
+        let window = web_sys::window().ok_or_else(|| storage_error("No window object"))?;
+
+        let indexed_db = window
+            .indexed_db()
+            .map_err(|e| storage_error(format!("IndexedDB not available: {:?}", e)))?
+            .ok_or_else(|| storage_error("IndexedDB is null"))?;
+
+        let open_request = indexed_db
+            .open_with_u32(DB_NAME, DB_VERSION)
+            .map_err(|e| storage_error(format!("Failed to open database: {:?}", e)))?;
+
+        // Create object stores on first open
+        let on_upgrade = Closure::once(Box::new(move |event: web_sys::IdbVersionChangeEvent| {
+            let target = event.target().unwrap();
+            let request: IdbRequest = target.unchecked_into();
+            let db: IdbDatabase = request.result().unwrap().unchecked_into();
+
+            let store_names = db.object_store_names();
+            if !store_names.contains(METADATA_STORE) {
+                let _ = db.create_object_store(METADATA_STORE);
+            }
+            if !store_names.contains(DATA_STORE) {
+                let _ = db.create_object_store(DATA_STORE);
+            }
+        }) as Box<dyn FnOnce(_)>);
+
+        open_request.set_onupgradeneeded(Some(on_upgrade.as_ref().unchecked_ref()));
+        on_upgrade.forget();
+
+        let db = wait_for_request(&open_request)
+            .await
+            .map_err(storage_error)?;
+
+        Ok(Self {
+            db: db.unchecked_into(),
+        })
+    }
+
+    /// Validate and store a new font file
+    ///
+    /// Returns the generated font metadata, or `FontError::Unparseable` if
+    /// the bytes are not a TTF/OTF font.
+    pub async fn store_font(
+        &self,
+        filename: String,
+        data: Vec<u8>,
+    ) -> Result<FontMetadata, FontError> {
+        if data.len() > MAX_FONT_SIZE {
+            return Err(FontError::FileTooLarge(data.len()));
+        }
+
+        let families = parse_font_families(&data)?;
+        let id = generate_font_id();
+        let metadata = FontMetadata::new(id.clone(), filename, families, data.len());
+
+        let transaction = self
+            .db
+            .transaction_with_str_sequence_and_mode(
+                &js_sys::Array::of2(&METADATA_STORE.into(), &DATA_STORE.into()),
+                web_sys::IdbTransactionMode::Readwrite,
+            )
+            .map_err(|e| storage_error(format!("Transaction failed: {:?}", e)))?;
+
+        let metadata_store = transaction
+            .object_store(METADATA_STORE)
+            .map_err(|e| storage_error(format!("Store access failed: {:?}", e)))?;
+        let metadata_json = serde_json::to_string(&metadata)
+            .map_err(|e| storage_error(format!("Serialization failed: {}", e)))?;
+        let put_metadata = metadata_store
+            .put_with_key(&JsValue::from_str(&metadata_json), &JsValue::from_str(&id))
+            .map_err(|e| storage_error(format!("Put metadata failed: {:?}", e)))?;
+        wait_for_request(&put_metadata)
+            .await
+            .map_err(storage_error)?;
+
+        let data_store = transaction
+            .object_store(DATA_STORE)
+            .map_err(|e| storage_error(format!("Store access failed: {:?}", e)))?;
+        let uint8_array = js_sys::Uint8Array::from(data.as_slice());
+        let put_data = data_store
+            .put_with_key(&uint8_array, &JsValue::from_str(&id))
+            .map_err(|e| storage_error(format!("Put data failed: {:?}", e)))?;
+        wait_for_request(&put_data).await.map_err(storage_error)?;
+
+        wait_for_transaction(&transaction)
+            .await
+            .map_err(storage_error)?;
+
+        Ok(metadata)
+    }
+
+    /// Get font binary data by ID
+    pub async fn get_font_data(&self, id: &str) -> Result<Vec<u8>, FontError> {
+        let transaction = self
+            .db
+            .transaction_with_str(DATA_STORE)
+            .map_err(|e| storage_error(format!("Transaction failed: {:?}", e)))?;
+        let store = transaction
+            .object_store(DATA_STORE)
+            .map_err(|e| storage_error(format!("Store access failed: {:?}", e)))?;
+        let request = store
+            .get(&JsValue::from_str(id))
+            .map_err(|e| storage_error(format!("Get failed: {:?}", e)))?;
+
+        let result = wait_for_request(&request).await.map_err(storage_error)?;
+        if result.is_undefined() || result.is_null() {
+            return Err(FontError::NotFound(id.to_string()));
+        }
+
+        let uint8_array: js_sys::Uint8Array = result.unchecked_into();
+        Ok(uint8_array.to_vec())
+    }
+
+    /// List all stored fonts, newest first
+    pub async fn list_fonts(&self) -> Result<Vec<FontMetadata>, FontError> {
+        let transaction = self
+            .db
+            .transaction_with_str(METADATA_STORE)
+            .map_err(|e| storage_error(format!("Transaction failed: {:?}", e)))?;
+        let store = transaction
+            .object_store(METADATA_STORE)
+            .map_err(|e| storage_error(format!("Store access failed: {:?}", e)))?;
+        let request = store
+            .get_all()
+            .map_err(|e| storage_error(format!("Get all failed: {:?}", e)))?;
+
+        let result = wait_for_request(&request).await.map_err(storage_error)?;
+        let array: js_sys::Array = result.unchecked_into();
+
+        let mut fonts: Vec<FontMetadata> = array
+            .iter()
+            .filter_map(|item| item.as_string())
+            .filter_map(|json| serde_json::from_str(&json).ok())
+            .collect();
+        fonts.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+
+        Ok(fonts)
+    }
+
+    /// Delete a font by ID
+    pub async fn delete_font(&self, id: &str) -> Result<(), FontError> {
+        let transaction = self
+            .db
+            .transaction_with_str_sequence_and_mode(
+                &js_sys::Array::of2(&METADATA_STORE.into(), &DATA_STORE.into()),
+                web_sys::IdbTransactionMode::Readwrite,
+            )
+            .map_err(|e| storage_error(format!("Transaction failed: {:?}", e)))?;
+
+        for store_name in [METADATA_STORE, DATA_STORE] {
+            let store = transaction
+                .object_store(store_name)
+                .map_err(|e| storage_error(format!("Store access failed: {:?}", e)))?;
+            let delete = store
+                .delete(&JsValue::from_str(id))
+                .map_err(|e| storage_error(format!("Delete failed: {:?}", e)))?;
+            wait_for_request(&delete).await.map_err(storage_error)?;
+        }
+
+        wait_for_transaction(&transaction)
+            .await
+            .map_err(storage_error)
+    }
+}
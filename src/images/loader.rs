@@ -3,7 +3,10 @@
 //! Typst's World::file() trait requires synchronous access to binary data.
 //! This module provides an in-memory cache that pre-loads images from IndexedDB.
 
+use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
+use std::future::Future;
+use std::hash::{Hash, Hasher};
 
 use typst::foundations::Bytes;
 
@@ -43,6 +46,14 @@ impl ImageCache {
         self.images.get(id).map(|(_, ext)| ext.as_str())
     }
 
+    /// Hash of an image's bytes, which changes when the image is replaced
+    pub fn content_hash(&self, id: &str) -> Option<u64> {
+        let bytes = self.get(id)?;
+        let mut hasher = DefaultHasher::new();
+        bytes.hash(&mut hasher);
+        Some(hasher.finish())
+    }
+
     /// Check if an image is cached
     pub fn contains(&self, id: &str) -> bool {
         self.images.contains_key(id)
@@ -96,13 +107,47 @@ impl ImageCache {
 
     /// Pre-load all images from IndexedDB
     pub async fn preload_all(&mut self, store: &ImageStore) -> Result<(), ImageError> {
-        let images = store.list_images().await?;
+        self.preload_all_with_progress(store, None).await
+    }
 
-        for metadata in images {
-            if !self.contains(&metadata.id) {
-                let ext = extension_from_mime_type(&metadata.mime_type);
-                let data = store.get_image_data(&metadata.id).await?;
-                self.add(metadata.id, data, ext.to_string());
+    /// Pre-load all images from IndexedDB, reporting `(loaded, total)` per image
+    ///
+    /// Images already in the cache are not counted. An image that fails to
+    /// load is logged and skipped; only failing to list the store is an error.
+    pub async fn preload_all_with_progress(
+        &mut self,
+        store: &ImageStore,
+        on_progress: Option<&dyn Fn(usize, usize)>,
+    ) -> Result<(), ImageError> {
+        let pending: Vec<_> = store
+            .list_images()
+            .await?
+            .into_iter()
+            .filter(|metadata| !self.contains(&metadata.id))
+            .collect();
+
+        let results = load_each(
+            pending,
+            |metadata| {
+                let id = metadata.id.clone();
+                async move { store.get_image_data(&id).await }
+            },
+            on_progress,
+        )
+        .await;
+
+        for (metadata, result) in results {
+            match result {
+                Ok(data) => {
+                    let ext = extension_from_mime_type(&metadata.mime_type);
+                    self.add(metadata.id, data, ext.to_string());
+                }
+                Err(e) => {
+                    crate::log::warn(&format!(
+                        "Skipping image {} ({}): {}",
+                        metadata.id, metadata.filename, e
+                    ));
+                }
             }
         }
 
@@ -121,12 +166,40 @@ impl ImageCache {
     }
 }
 
+/// Load each item in order, calling `on_progress(loaded, total)` after every one
+///
+/// Failures are returned alongside their item rather than stopping the run.
+pub(crate) async fn load_each<T, V, E, Fut>(
+    items: Vec<T>,
+    mut load: impl FnMut(&T) -> Fut,
+    on_progress: Option<&dyn Fn(usize, usize)>,
+) -> Vec<(T, Result<V, E>)>
+where
+    Fut: Future<Output = Result<V, E>>,
+{
+    let total = items.len();
+    let mut results = Vec::with_capacity(total);
+    for (index, item) in items.into_iter().enumerate() {
+        let result = load(&item).await;
+        results.push((item, result));
+        if let Some(on_progress) = on_progress {
+            on_progress(index + 1, total);
+        }
+    }
+    results
+}
+
 /// Extract image IDs referenced in a document's JSON data
 ///
-/// Looks for the `images` field in SlickSheetData and returns all referenced IDs.
+/// Returns the IDs in SlickSheetData's `images` field, plus the page
+/// background image if one is set.
 #[allow(dead_code)]
 pub fn extract_image_ids_from_data(data: &crate::data::SlickSheetData) -> Vec<String> {
-    data.images.values().cloned().collect()
+    data.images
+        .values()
+        .chain(&data.background_image)
+        .cloned()
+        .collect()
 }
 
 #[cfg(test)]
@@ -194,12 +267,65 @@ mod tests {
 
         let data = crate::data::SlickSheetData {
             images,
+            background_image: Some("img_789".to_string()),
             ..Default::default()
         };
 
         let ids = extract_image_ids_from_data(&data);
-        assert_eq!(ids.len(), 2);
+        assert_eq!(ids.len(), 3);
         assert!(ids.contains(&"img_123".to_string()));
         assert!(ids.contains(&"img_456".to_string()));
+        assert!(ids.contains(&"img_789".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_load_each_reports_progress_and_skips_failures() {
+        use std::cell::RefCell;
+
+        let progress = RefCell::new(Vec::new());
+        let on_progress = |loaded, total| progress.borrow_mut().push((loaded, total));
+        let ids = vec!["img_a", "img_bad", "img_c"];
+
+        let results = load_each(
+            ids,
+            |id| {
+                let id = id.to_string();
+                async move {
+                    if id == "img_bad" {
+                        Err(format!("{} is corrupt", id))
+                    } else {
+                        Ok(id.len())
+                    }
+                }
+            },
+            Some(&on_progress),
+        )
+        .await;
+
+        assert_eq!(progress.into_inner(), vec![(1, 3), (2, 3), (3, 3)]);
+        let loaded: Vec<_> = results
+            .iter()
+            .filter(|(_, result)| result.is_ok())
+            .map(|(id, _)| *id)
+            .collect();
+        assert_eq!(loaded, vec!["img_a", "img_c"]);
+        assert_eq!(results[1].1, Err("img_bad is corrupt".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_load_each_without_items_or_callback() {
+        let results = load_each(Vec::<&str>::new(), |_| async { Ok::<_, String>(()) }, None).await;
+        assert!(results.is_empty());
+
+        let results = load_each(
+            vec![1, 2],
+            |n| {
+                let n = *n;
+                async move { Ok::<_, String>(n * 2) }
+            },
+            None,
+        )
+        .await;
+        assert_eq!(results, vec![(1, Ok(2)), (2, Ok(4))]);
     }
 }
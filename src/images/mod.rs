@@ -2,14 +2,21 @@
 //!
 //! This module provides:
 //! - Image metadata and format validation
-//! - IndexedDB-based image storage
+//! - IndexedDB-based image storage, with an in-memory fallback
 //! - Image cache for synchronous access in VirtualWorld
+//! - Crop/resize of uploads before they are stored
+//! - Decoding of pasted `data:` URLs
+//! - Sanitization of uploaded SVGs
 
 mod loader;
-mod store;
+mod sanitize;
+pub(crate) mod store;
+mod transform;
 
 pub use loader::ImageCache;
-pub use store::ImageStore;
+pub use sanitize::sanitize_svg;
+pub use store::{storage_estimate, ImageStore, StorageEstimate};
+pub use transform::{transform_image, Rect, TransformOptions};
 
 use serde::{Deserialize, Serialize};
 
@@ -23,10 +30,23 @@ pub const SUPPORTED_FORMATS: &[&str] = &[
     "image/svg+xml",
     "image/gif",
     "image/webp",
+    "image/bmp",
+    "image/tiff",
 ];
 
 /// Supported file extensions
-pub const SUPPORTED_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "svg", "gif", "webp"];
+pub const SUPPORTED_EXTENSIONS: &[&str] = &[
+    "png", "jpg", "jpeg", "svg", "gif", "webp", "bmp", "tif", "tiff",
+];
+
+/// Formats Typst can render directly (others must be converted first)
+pub const TYPST_COMPATIBLE_FORMATS: &[&str] = &[
+    "image/png",
+    "image/jpeg",
+    "image/svg+xml",
+    "image/gif",
+    "image/webp",
+];
 
 /// Metadata for a stored image
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -47,15 +67,15 @@ pub struct ImageMetadata {
     /// Short AI-generated description of the image content
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub alt_description: Option<String>,
+    /// ID of the image this one was regenerated from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub derived_from: Option<String>,
 }
 
 impl ImageMetadata {
     /// Create new image metadata
     pub fn new(id: String, filename: String, mime_type: String, size: usize) -> Self {
-        let created_at = js_sys::Date::new_0()
-            .to_iso_string()
-            .as_string()
-            .unwrap_or_else(|| "unknown".to_string());
+        let created_at = now_iso_string();
 
         Self {
             id,
@@ -65,6 +85,7 @@ impl ImageMetadata {
             created_at,
             generation_prompt: None,
             alt_description: None,
+            derived_from: None,
         }
     }
 
@@ -77,10 +98,7 @@ impl ImageMetadata {
         generation_prompt: String,
         alt_description: String,
     ) -> Self {
-        let created_at = js_sys::Date::new_0()
-            .to_iso_string()
-            .as_string()
-            .unwrap_or_else(|| "unknown".to_string());
+        let created_at = now_iso_string();
 
         Self {
             id,
@@ -90,8 +108,27 @@ impl ImageMetadata {
             created_at,
             generation_prompt: Some(generation_prompt),
             alt_description: Some(alt_description),
+            derived_from: None,
         }
     }
+
+    /// Builder method to record the image this one was regenerated from
+    pub fn with_derived_from(mut self, original_id: impl Into<String>) -> Self {
+        self.derived_from = Some(original_id.into());
+        self
+    }
+
+    /// Whether the image can be regenerated from its stored prompt
+    pub fn can_regenerate(&self) -> bool {
+        self.generation_prompt
+            .as_deref()
+            .is_some_and(|prompt| !prompt.trim().is_empty())
+    }
+
+    /// Whether Typst can render this image without conversion
+    pub fn is_typst_compatible(&self) -> bool {
+        is_typst_compatible(&self.mime_type)
+    }
 }
 
 /// Check if a MIME type is supported
@@ -99,6 +136,11 @@ pub fn is_supported_mime_type(mime_type: &str) -> bool {
     SUPPORTED_FORMATS.contains(&mime_type)
 }
 
+/// Check if Typst can render a MIME type directly
+pub fn is_typst_compatible(mime_type: &str) -> bool {
+    TYPST_COMPATIBLE_FORMATS.contains(&mime_type)
+}
+
 /// Check if a file extension is supported
 pub fn is_supported_extension(extension: &str) -> bool {
     SUPPORTED_EXTENSIONS.contains(&extension.to_lowercase().as_str())
@@ -131,6 +173,17 @@ pub fn detect_mime_type(bytes: &[u8]) -> Option<&'static str> {
         return Some("image/webp");
     }
 
+    // BMP: 42 4D (BM)
+    if bytes.starts_with(&[0x42, 0x4D]) {
+        return Some("image/bmp");
+    }
+
+    // TIFF: 49 49 2A 00 (little-endian) or 4D 4D 00 2A (big-endian)
+    if bytes.starts_with(&[0x49, 0x49, 0x2A, 0x00]) || bytes.starts_with(&[0x4D, 0x4D, 0x00, 0x2A])
+    {
+        return Some("image/tiff");
+    }
+
     // SVG: Check for XML/SVG header
     if let Ok(text) = std::str::from_utf8(&bytes[..bytes.len().min(1024)]) {
         let text_lower = text.to_lowercase();
@@ -144,6 +197,57 @@ pub fn detect_mime_type(bytes: &[u8]) -> Option<&'static str> {
     None
 }
 
+/// Split a `data:<mime>;base64,<data>` URL into its bytes and MIME type
+///
+/// Only base64 payloads are accepted; whitespace inside the payload (as
+/// left by line-wrapping tools) is ignored. The bytes are not inspected.
+pub fn parse_data_url(data_url: &str) -> Result<(Vec<u8>, String), ImageError> {
+    use base64::Engine;
+
+    let rest = data_url
+        .trim()
+        .strip_prefix("data:")
+        .ok_or_else(|| ImageError::InvalidData("Expected a data: URL".to_string()))?;
+    let (header, payload) = rest
+        .split_once(',')
+        .ok_or_else(|| ImageError::InvalidData("Data URL has no payload".to_string()))?;
+    let mime_type = header
+        .strip_suffix(";base64")
+        .ok_or_else(|| ImageError::InvalidData("Data URL is not base64-encoded".to_string()))?;
+    let mime_type = mime_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+
+    let payload: String = payload.chars().filter(|c| !c.is_whitespace()).collect();
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(payload)
+        .map_err(|e| ImageError::InvalidData(format!("Base64 decode error: {}", e)))?;
+
+    Ok((bytes, mime_type))
+}
+
+/// Decode a pasted image data URL, checking its size and format
+///
+/// The declared MIME type and the detected format must both be supported.
+pub fn decode_image_data_url(data_url: &str) -> Result<Vec<u8>, ImageError> {
+    let (bytes, mime_type) = parse_data_url(data_url)?;
+    if !is_supported_mime_type(&mime_type) {
+        return Err(ImageError::UnsupportedFormat(mime_type));
+    }
+    if bytes.len() > MAX_IMAGE_SIZE {
+        return Err(ImageError::FileTooLarge(bytes.len()));
+    }
+    match detect_mime_type(&bytes) {
+        Some(detected) if is_supported_mime_type(detected) => Ok(bytes),
+        _ => Err(ImageError::InvalidData(format!(
+            "Data is not a valid {} image",
+            mime_type
+        ))),
+    }
+}
+
 /// Get file extension from MIME type
 pub fn extension_from_mime_type(mime_type: &str) -> &'static str {
     match mime_type {
@@ -152,10 +256,26 @@ pub fn extension_from_mime_type(mime_type: &str) -> &'static str {
         "image/gif" => "gif",
         "image/webp" => "webp",
         "image/svg+xml" => "svg",
+        "image/bmp" => "bmp",
+        "image/tiff" => "tiff",
         _ => "bin",
     }
 }
 
+/// Get the MIME type for a file extension, if it is a supported image
+pub fn mime_type_from_extension(extension: &str) -> Option<&'static str> {
+    match extension.to_lowercase().as_str() {
+        "png" => Some("image/png"),
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "gif" => Some("image/gif"),
+        "webp" => Some("image/webp"),
+        "svg" => Some("image/svg+xml"),
+        "bmp" => Some("image/bmp"),
+        "tif" | "tiff" => Some("image/tiff"),
+        _ => None,
+    }
+}
+
 /// Generate a unique image ID
 pub fn generate_image_id() -> String {
     // Use crypto.getRandomValues for better randomness in browser
@@ -177,6 +297,21 @@ pub fn generate_image_id() -> String {
     format!("img_{}", random_part)
 }
 
+/// Generate an image ID derived from the image bytes
+///
+/// Identical bytes always give the same `img_<hash>` ID, so bundles built
+/// from the same images are reproducible and re-uploads dedupe. Uses 64-bit
+/// FNV-1a, which is stable across builds, unlike `DefaultHasher`.
+pub fn generate_content_id(bytes: &[u8]) -> String {
+    const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let hash = bytes.iter().fold(FNV_OFFSET, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+    });
+    format!("img_{}", hex_encode(&hash.to_be_bytes()))
+}
+
 /// Fallback random ID generation using JS Math.random
 fn fallback_random_id() -> String {
     let random = js_sys::Math::random();
@@ -184,6 +319,11 @@ fn fallback_random_id() -> String {
     format!("{:012x}", id)
 }
 
+/// Current time in the `Date.toISOString()` format, e.g. `2025-01-01T12:00:00.000Z`
+fn now_iso_string() -> String {
+    chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
+}
+
 /// Simple hex encoding
 fn hex_encode(bytes: &[u8]) -> String {
     bytes.iter().map(|b| format!("{:02x}", b)).collect()
@@ -202,6 +342,13 @@ pub enum ImageError {
     NotFound(String),
     /// Invalid data
     InvalidData(String),
+    /// Browser storage quota exhausted
+    QuotaExceeded {
+        /// Bytes already in use by this origin (0 if unknown)
+        used: u64,
+        /// Bytes the failed write needed
+        requested: u64,
+    },
 }
 
 impl std::fmt::Display for ImageError {
@@ -226,12 +373,25 @@ impl std::fmt::Display for ImageError {
             ImageError::InvalidData(msg) => {
                 write!(f, "Invalid image data: {}", msg)
             }
+            ImageError::QuotaExceeded { used, requested } => {
+                write!(
+                    f,
+                    "Storage full: {} used, {} more needed. Delete unused images to free space.",
+                    format_megabytes(*used),
+                    format_megabytes(*requested)
+                )
+            }
         }
     }
 }
 
 impl std::error::Error for ImageError {}
 
+/// Format a byte count as megabytes with one decimal place
+fn format_megabytes(bytes: u64) -> String {
+    format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -243,10 +403,53 @@ mod tests {
         assert!(is_supported_mime_type("image/svg+xml"));
         assert!(is_supported_mime_type("image/gif"));
         assert!(is_supported_mime_type("image/webp"));
-        assert!(!is_supported_mime_type("image/tiff"));
+        assert!(is_supported_mime_type("image/bmp"));
+        assert!(is_supported_mime_type("image/tiff"));
+        assert!(!is_supported_mime_type("image/heic"));
         assert!(!is_supported_mime_type("application/pdf"));
     }
 
+    /// A 1x1 transparent PNG
+    const PIXEL_PNG_BASE64: &str = "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNkYPhfDwAChwGA60e6kgAAAABJRU5ErkJggg==";
+
+    #[test]
+    fn test_decode_image_data_url_accepts_png() {
+        let data_url = format!("data:image/png;base64,{}", PIXEL_PNG_BASE64);
+        let bytes = decode_image_data_url(&data_url).unwrap();
+        assert_eq!(detect_mime_type(&bytes), Some("image/png"));
+
+        // Surrounding and wrapped whitespace is ignored
+        let wrapped = format!(
+            "  data:image/png;base64,{}\n{}  ",
+            &PIXEL_PNG_BASE64[..20],
+            &PIXEL_PNG_BASE64[20..]
+        );
+        assert_eq!(decode_image_data_url(&wrapped).unwrap(), bytes);
+    }
+
+    #[test]
+    fn test_decode_image_data_url_rejects_malformed_input() {
+        let invalid = |data_url: &str| {
+            matches!(
+                decode_image_data_url(data_url),
+                Err(ImageError::InvalidData(_))
+            )
+        };
+        assert!(invalid("https://example.com/logo.png"));
+        assert!(invalid("data:image/png;base64"));
+        assert!(invalid("data:image/png,plain-text"));
+        assert!(invalid("data:image/png;base64,not*base64!"));
+        // Valid base64 that isn't a PNG
+        assert!(invalid(
+            "data:image/png;base64,aGVsbG8gd29ybGQsIHRoaXMgaXMgdGV4dA=="
+        ));
+
+        assert!(matches!(
+            decode_image_data_url(&format!("data:text/plain;base64,{}", PIXEL_PNG_BASE64)),
+            Err(ImageError::UnsupportedFormat(_))
+        ));
+    }
+
     #[test]
     fn test_is_supported_extension() {
         assert!(is_supported_extension("png"));
@@ -256,8 +459,10 @@ mod tests {
         assert!(is_supported_extension("svg"));
         assert!(is_supported_extension("gif"));
         assert!(is_supported_extension("webp"));
-        assert!(!is_supported_extension("tiff"));
-        assert!(!is_supported_extension("bmp"));
+        assert!(is_supported_extension("bmp"));
+        assert!(is_supported_extension("tif"));
+        assert!(is_supported_extension("TIFF"));
+        assert!(!is_supported_extension("heic"));
     }
 
     #[test]
@@ -292,6 +497,30 @@ mod tests {
         assert_eq!(detect_mime_type(&webp_header), Some("image/webp"));
     }
 
+    #[test]
+    fn test_detect_mime_type_bmp() {
+        let bmp_header = [
+            0x42, 0x4D, 0x36, 0x00, 0x0C, 0x00, 0x00, 0x00, 0x00, 0x00, 0x36, 0x00,
+        ];
+        assert_eq!(detect_mime_type(&bmp_header), Some("image/bmp"));
+    }
+
+    #[test]
+    fn test_detect_mime_type_tiff_little_endian() {
+        let tiff_header = [
+            0x49, 0x49, 0x2A, 0x00, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+        assert_eq!(detect_mime_type(&tiff_header), Some("image/tiff"));
+    }
+
+    #[test]
+    fn test_detect_mime_type_tiff_big_endian() {
+        let tiff_header = [
+            0x4D, 0x4D, 0x00, 0x2A, 0x00, 0x00, 0x00, 0x08, 0x00, 0x00, 0x00, 0x00,
+        ];
+        assert_eq!(detect_mime_type(&tiff_header), Some("image/tiff"));
+    }
+
     #[test]
     fn test_detect_mime_type_svg() {
         let svg_data = b"<?xml version=\"1.0\"?><svg xmlns=\"http://www.w3.org/2000/svg\"></svg>";
@@ -313,19 +542,115 @@ mod tests {
         assert_eq!(extension_from_mime_type("image/gif"), "gif");
         assert_eq!(extension_from_mime_type("image/webp"), "webp");
         assert_eq!(extension_from_mime_type("image/svg+xml"), "svg");
+        assert_eq!(extension_from_mime_type("image/bmp"), "bmp");
+        assert_eq!(extension_from_mime_type("image/tiff"), "tiff");
         assert_eq!(extension_from_mime_type("unknown"), "bin");
     }
 
+    #[test]
+    fn test_mime_type_from_extension() {
+        assert_eq!(mime_type_from_extension("png"), Some("image/png"));
+        assert_eq!(mime_type_from_extension("JPEG"), Some("image/jpeg"));
+        assert_eq!(mime_type_from_extension("tif"), Some("image/tiff"));
+        assert_eq!(mime_type_from_extension("txt"), None);
+    }
+
+    #[test]
+    fn test_is_typst_compatible() {
+        assert!(is_typst_compatible("image/png"));
+        assert!(is_typst_compatible("image/svg+xml"));
+        assert!(!is_typst_compatible("image/bmp"));
+        assert!(!is_typst_compatible("image/tiff"));
+    }
+
     #[test]
     fn test_image_error_display() {
         let err = ImageError::FileTooLarge(20_000_000);
         assert!(err.to_string().contains("20000000"));
         assert!(err.to_string().contains("too large"));
 
-        let err = ImageError::UnsupportedFormat("image/tiff".to_string());
-        assert!(err.to_string().contains("tiff"));
+        let err = ImageError::UnsupportedFormat("image/heic".to_string());
+        assert!(err.to_string().contains("heic"));
 
         let err = ImageError::NotFound("img_123".to_string());
         assert!(err.to_string().contains("img_123"));
     }
+
+    #[test]
+    fn test_quota_exceeded_display() {
+        let err = ImageError::QuotaExceeded {
+            used: 50 * 1024 * 1024,
+            requested: 3 * 1024 * 1024 / 2,
+        };
+        assert_eq!(
+            err.to_string(),
+            "Storage full: 50.0 MB used, 1.5 MB more needed. Delete unused images to free space."
+        );
+    }
+
+    fn generated_metadata(id: &str) -> ImageMetadata {
+        ImageMetadata {
+            id: id.to_string(),
+            filename: "generated.png".to_string(),
+            mime_type: "image/png".to_string(),
+            size: 1024,
+            created_at: "2026-01-01T00:00:00.000Z".to_string(),
+            generation_prompt: Some("A red apple".to_string()),
+            alt_description: Some("A red apple".to_string()),
+            derived_from: None,
+        }
+    }
+
+    #[test]
+    fn test_derived_from_round_trip() {
+        let variation = generated_metadata("img_bbbbbbbb").with_derived_from("img_aaaaaaaa");
+
+        let json = serde_json::to_string(&variation).unwrap();
+        assert!(json.contains("\"derived_from\":\"img_aaaaaaaa\""));
+
+        let restored: ImageMetadata = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, variation);
+        assert_eq!(restored.derived_from.as_deref(), Some("img_aaaaaaaa"));
+    }
+
+    #[test]
+    fn test_derived_from_omitted_for_originals() {
+        let original = generated_metadata("img_aaaaaaaa");
+
+        let json = serde_json::to_string(&original).unwrap();
+        assert!(!json.contains("derived_from"));
+
+        // Metadata stored before the field existed still loads
+        let legacy = r#"{"id":"img_1","filename":"a.png","mime_type":"image/png","size":1,"created_at":"x"}"#;
+        let restored: ImageMetadata = serde_json::from_str(legacy).unwrap();
+        assert_eq!(restored.derived_from, None);
+    }
+
+    #[test]
+    fn test_can_regenerate_requires_prompt() {
+        assert!(generated_metadata("img_1").can_regenerate());
+
+        let mut uploaded = generated_metadata("img_2");
+        uploaded.generation_prompt = None;
+        assert!(!uploaded.can_regenerate());
+    }
+
+    #[test]
+    fn test_content_id_is_stable_for_same_bytes() {
+        let bytes = b"\x89PNG\r\n\x1a\nsame pixels";
+        let id = generate_content_id(bytes);
+        assert_eq!(id, generate_content_id(bytes));
+        assert!(id.starts_with("img_"));
+        assert_eq!(id.len(), "img_".len() + 16);
+        // Pinned so a hash change (which would break existing bundles) is noticed
+        assert_eq!(generate_content_id(b""), "img_cbf29ce484222325");
+    }
+
+    #[test]
+    fn test_content_id_differs_for_different_bytes() {
+        assert_ne!(
+            generate_content_id(b"\x89PNG\r\n\x1a\nfirst"),
+            generate_content_id(b"\x89PNG\r\n\x1a\nsecond")
+        );
+    }
 }
@@ -0,0 +1,356 @@
+//! Sanitization of uploaded SVG images
+//!
+//! Uploaded SVGs end up inlined in the preview, so anything that can run
+//! script or fetch from the network is removed. The rest of the document is
+//! copied through token by token, so legitimate markup is left untouched.
+//! Attribute values are compared after decoding character references, so
+//! `hr&#101;f` is treated like `href`.
+
+use xmlparser::{ElementEnd, Token, Tokenizer};
+
+use super::ImageError;
+
+/// Elements removed together with their content
+const BLOCKED_ELEMENTS: &[&str] = &["script", "foreignobject"];
+
+/// Animation elements that are removed when they target a link or handler
+const ANIMATION_ELEMENTS: &[&str] = &["set", "animate"];
+
+/// Remove scripts, event handlers and external references from an SVG
+///
+/// Strips `<script>` and `<foreignObject>` elements, `on*` attributes,
+/// `href`/`xlink:href` values other than `#fragment` or `data:image/` URLs,
+/// animations that rewrite those attributes, external `url(...)` references
+/// in attributes, `<style>` elements that import or load external resources,
+/// elements nested inside `<style>`, and DTDs and processing instructions (which can pull in external entities
+/// or stylesheets).
+pub fn sanitize_svg(bytes: &[u8]) -> Result<Vec<u8>, ImageError> {
+    let svg = std::str::from_utf8(bytes)
+        .map_err(|e| ImageError::InvalidData(format!("SVG is not valid UTF-8: {}", e)))?;
+
+    let mut output = String::with_capacity(svg.len());
+    // Start tag being read, written out once its attributes have been seen
+    let mut pending: Option<PendingTag> = None;
+    // Depth inside a removed element (0 when copying)
+    let mut skip_depth = 0_usize;
+    // Output length before an open `<style>` and the CSS read inside it
+    let mut style: Option<(usize, String)> = None;
+
+    for token in Tokenizer::from(svg) {
+        let token = token.map_err(|e| ImageError::InvalidData(format!("Invalid SVG: {}", e)))?;
+        match token {
+            Token::ElementStart { local, span, .. } => {
+                if skip_depth > 0 {
+                    skip_depth += 1;
+                } else {
+                    pending = Some(PendingTag::new(local.as_str(), span.as_str()));
+                }
+            }
+            Token::Attribute {
+                prefix,
+                local,
+                value,
+                span,
+            } => {
+                if let Some(tag) = pending.as_mut() {
+                    tag.attribute(
+                        prefix.as_str(),
+                        local.as_str(),
+                        value.as_str(),
+                        span.as_str(),
+                    );
+                }
+            }
+            Token::ElementEnd { end, span } => {
+                if let Some(tag) = pending.take() {
+                    // Stylesheets hold only text, so elements inside one are dropped
+                    // and the CSS is checked at the matching `</style>`
+                    if tag.is_blocked() || style.is_some() {
+                        // An empty element ends here; an open one at its close tag
+                        skip_depth = usize::from(matches!(end, ElementEnd::Open));
+                    } else {
+                        if tag.name == "style" && matches!(end, ElementEnd::Open) {
+                            style = Some((output.len(), String::new()));
+                        }
+                        output.push_str(&tag.markup);
+                        output.push_str(span.as_str());
+                    }
+                } else if skip_depth > 0 {
+                    if matches!(end, ElementEnd::Empty | ElementEnd::Close(..)) {
+                        skip_depth -= 1;
+                    }
+                } else if let Some((start, css)) = style.take() {
+                    // The stylesheet is only known once its element closes
+                    if is_unsafe_css(&css) {
+                        output.truncate(start);
+                    } else {
+                        output.push_str(span.as_str());
+                    }
+                } else {
+                    output.push_str(span.as_str());
+                }
+            }
+            Token::Text { text } if skip_depth == 0 => {
+                if let Some((_, css)) = style.as_mut() {
+                    css.push_str(&decode_entities(text.as_str()));
+                }
+                output.push_str(text.as_str());
+            }
+            Token::Cdata { text, span } if skip_depth == 0 => {
+                if let Some((_, css)) = style.as_mut() {
+                    css.push_str(text.as_str());
+                }
+                output.push_str(span.as_str());
+            }
+            Token::Comment { span, .. } | Token::Declaration { span, .. } if skip_depth == 0 => {
+                output.push_str(span.as_str())
+            }
+            _ => {}
+        }
+    }
+
+    Ok(output.into_bytes())
+}
+
+/// A start tag with the attributes that survived sanitization
+struct PendingTag {
+    /// Lowercased local name
+    name: String,
+    /// `<name` followed by the kept attributes
+    markup: String,
+    /// Whether an animation targets a link or event handler attribute
+    animates_unsafe_attribute: bool,
+}
+
+impl PendingTag {
+    fn new(local: &str, start: &str) -> Self {
+        Self {
+            name: local.to_ascii_lowercase(),
+            markup: start.to_string(),
+            animates_unsafe_attribute: false,
+        }
+    }
+
+    fn attribute(&mut self, prefix: &str, local: &str, value: &str, span: &str) {
+        let name = local.to_ascii_lowercase();
+        let value = decode_entities(value);
+        if name == "attributename" {
+            let target = value
+                .rsplit(':')
+                .next()
+                .unwrap_or(&value)
+                .trim()
+                .to_ascii_lowercase();
+            self.animates_unsafe_attribute |= is_event_handler(&target) || target == "href";
+        }
+        let external_href = name == "href"
+            && (prefix.is_empty() || prefix == "xlink")
+            && !is_local_reference(&value);
+        let external_css = if name == "style" {
+            is_unsafe_css(&value)
+        } else {
+            has_external_url(&value)
+        };
+        if is_event_handler(&name) || external_href || external_css {
+            return;
+        }
+        self.markup.push(' ');
+        self.markup.push_str(span);
+    }
+
+    fn is_blocked(&self) -> bool {
+        BLOCKED_ELEMENTS.contains(&self.name.as_str())
+            || (ANIMATION_ELEMENTS.contains(&self.name.as_str()) && self.animates_unsafe_attribute)
+    }
+}
+
+/// Whether an attribute name is an event handler such as `onload`
+fn is_event_handler(name: &str) -> bool {
+    name.starts_with("on")
+}
+
+/// Whether a link stays inside the document: a fragment or an inline image
+///
+/// Whitespace and control characters are ignored, as browsers do in URLs.
+fn is_local_reference(value: &str) -> bool {
+    let value: String = value
+        .chars()
+        .filter(|c| !c.is_whitespace() && !c.is_control())
+        .collect::<String>()
+        .to_ascii_lowercase();
+    value.starts_with('#') || value.starts_with("data:image/")
+}
+
+/// Whether a value holds a `url(...)` that leaves the document
+fn has_external_url(value: &str) -> bool {
+    let lower = value.to_ascii_lowercase();
+    lower.match_indices("url(").any(|(at, _)| {
+        let target = &value[at + 4..];
+        let target = target.split(')').next().unwrap_or(target);
+        let target = target.trim().trim_matches(|c| c == '"' || c == '\'');
+        !is_local_reference(target)
+    })
+}
+
+/// Whether CSS can load anything from outside the document
+///
+/// CSS escapes (`\75 rl(`) are rejected outright rather than decoded.
+fn is_unsafe_css(css: &str) -> bool {
+    css.contains('\\') || css.to_ascii_lowercase().contains("@import") || has_external_url(css)
+}
+
+/// Decode XML character references (`&#101;`, `&#x65;`, `&amp;`, ...)
+///
+/// Unknown or malformed references are kept as written.
+fn decode_entities(value: &str) -> String {
+    let mut decoded = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(at) = rest.find('&') {
+        decoded.push_str(&rest[..at]);
+        rest = &rest[at..];
+        let entity = rest[1..]
+            .find(';')
+            .map(|end| &rest[1..end + 1])
+            .and_then(|name| Some((name, decode_entity(name)?)));
+        match entity {
+            Some((name, c)) => {
+                decoded.push(c);
+                rest = &rest[name.len() + 2..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+/// The character an entity name (without `&` and `;`) stands for
+fn decode_entity(name: &str) -> Option<char> {
+    let code = if let Some(hex) = name.strip_prefix("#x").or_else(|| name.strip_prefix("#X")) {
+        u32::from_str_radix(hex, 16).ok()?
+    } else if let Some(decimal) = name.strip_prefix('#') {
+        decimal.parse().ok()?
+    } else {
+        return match name {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => None,
+        };
+    };
+    char::from_u32(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sanitize(svg: &str) -> String {
+        String::from_utf8(sanitize_svg(svg.as_bytes()).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_sanitize_svg_removes_script_elements() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg"><script>alert(1)</script><path d="M0 0L10 10"/></svg>"#;
+        let clean = sanitize(svg);
+
+        assert!(!clean.contains("script"), "{}", clean);
+        assert!(!clean.contains("alert"), "{}", clean);
+        assert!(clean.contains(r#"<path d="M0 0L10 10"/>"#), "{}", clean);
+    }
+
+    #[test]
+    fn test_sanitize_svg_removes_event_handlers() {
+        let svg = r##"<svg xmlns="http://www.w3.org/2000/svg" onload="alert(1)"><rect width="5" height="5" fill="#f00" onClick="steal()"/></svg>"##;
+        let clean = sanitize(svg);
+
+        assert!(!clean.to_lowercase().contains("onload"), "{}", clean);
+        assert!(!clean.to_lowercase().contains("onclick"), "{}", clean);
+        assert_eq!(
+            clean,
+            r##"<svg xmlns="http://www.w3.org/2000/svg"><rect width="5" height="5" fill="#f00"/></svg>"##
+        );
+    }
+
+    #[test]
+    fn test_sanitize_svg_removes_external_references() {
+        let svg = r##"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink">
+  <defs><circle id="dot" r="2"/></defs>
+  <use xlink:href="#dot"/>
+  <image href="https://tracker.example/pixel.png" width="1" height="1"/>
+  <a href="javascript:alert(1)"><text>Hi</text></a>
+  <set attributeName="href" to="javascript:alert(1)"/>
+</svg>"##;
+        let clean = sanitize(svg);
+
+        assert!(clean.contains(r##"<use xlink:href="#dot"/>"##), "{}", clean);
+        assert!(!clean.contains("tracker.example"), "{}", clean);
+        assert!(!clean.contains("javascript:"), "{}", clean);
+        assert!(clean.contains("<text>Hi</text>"), "{}", clean);
+    }
+
+    #[test]
+    fn test_sanitize_svg_decodes_entities_before_checking() {
+        let svg = r##"<svg xmlns="http://www.w3.org/2000/svg"><a href="jav&#x61;script:alert(1)"><text>Hi</text></a><set attributeName="hr&#101;f" to="javascript:alert(1)"/><use href="&#35;dot"/></svg>"##;
+        let clean = sanitize(svg);
+
+        assert!(!clean.contains("script"), "{}", clean);
+        assert!(!clean.contains("<set"), "{}", clean);
+        assert!(clean.contains(r#"<use href="&#35;dot"/>"#), "{}", clean);
+    }
+
+    #[test]
+    fn test_sanitize_svg_removes_external_css_references() {
+        let svg = r##"<svg xmlns="http://www.w3.org/2000/svg"><style>@import "https://evil.example/x.css";</style><style>.a { fill: red }</style><rect fill="url(https://evil.example/p.svg#g)"/><rect fill="url(#grad)" style="background: url( 'http://evil.example/b.png' )"/></svg>"##;
+        let clean = sanitize(svg);
+
+        assert!(!clean.contains("evil.example"), "{}", clean);
+        assert!(
+            clean.contains("<style>.a { fill: red }</style>"),
+            "{}",
+            clean
+        );
+        assert!(
+            clean.contains(r##"<rect fill="url(#grad)"/>"##),
+            "{}",
+            clean
+        );
+    }
+
+    #[test]
+    fn test_sanitize_svg_checks_css_after_elements_inside_style() {
+        let svg =
+            r#"<svg><style><x></x>@import url(http://evil.example/x.css);</style><rect/></svg>"#;
+        let clean = sanitize(svg);
+
+        assert!(!clean.contains("evil.example"), "{}", clean);
+        assert!(!clean.contains("<x>"), "{}", clean);
+        assert_eq!(clean, "<svg><rect/></svg>");
+
+        let safe = sanitize("<svg><style>.a { fill: red }<x/></style></svg>");
+        assert_eq!(safe, "<svg><style>.a { fill: red }</style></svg>");
+    }
+
+    #[test]
+    fn test_sanitize_svg_keeps_nested_content_after_removed_element() {
+        let svg = r#"<svg><foreignObject><div><p>html</p></div></foreignObject><g><path d="M1 1"/></g></svg>"#;
+        assert_eq!(sanitize(svg), r#"<svg><g><path d="M1 1"/></g></svg>"#);
+    }
+
+    #[test]
+    fn test_sanitize_svg_rejects_malformed_input() {
+        assert!(matches!(
+            sanitize_svg(b"<svg><path d=oops"),
+            Err(ImageError::InvalidData(_))
+        ));
+        assert!(matches!(
+            sanitize_svg(&[0x3c, 0xff, 0xfe]),
+            Err(ImageError::InvalidData(_))
+        ));
+    }
+}
@@ -1,14 +1,20 @@
 //! IndexedDB-based image storage
 //!
-//! Provides persistent storage for images in the browser using IndexedDB.
+//! Provides persistent storage for images in the browser using IndexedDB,
+//! with an in-memory fallback for sessions where IndexedDB is unavailable.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
 
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
 use web_sys::{IdbDatabase, IdbRequest, IdbTransaction};
 
 use super::{
-    detect_mime_type, extension_from_mime_type, generate_image_id, is_supported_mime_type,
-    ImageError, ImageMetadata, MAX_IMAGE_SIZE,
+    decode_image_data_url, detect_mime_type, extension_from_mime_type, generate_content_id,
+    generate_image_id, is_supported_mime_type, sanitize_svg, ImageError, ImageMetadata,
+    MAX_IMAGE_SIZE,
 };
 
 /// Database name for image storage
@@ -20,15 +26,31 @@ const METADATA_STORE: &str = "metadata";
 /// Object store for image binary data
 const DATA_STORE: &str = "data";
 
-/// Image store backed by IndexedDB
+/// Storage operations behind `ImageStore`
+///
+/// Validation and ID generation stay in `ImageStore`; backends only keep
+/// metadata and bytes.
+trait ImageBackend {
+    /// Write metadata and bytes together
+    async fn put(&self, metadata: &ImageMetadata, data: &[u8]) -> Result<(), ImageError>;
+    /// Replace the metadata of an existing image
+    async fn put_metadata(&self, metadata: &ImageMetadata) -> Result<(), ImageError>;
+    async fn get_data(&self, id: &str) -> Result<Vec<u8>, ImageError>;
+    async fn get_metadata(&self, id: &str) -> Result<ImageMetadata, ImageError>;
+    /// All stored metadata, in no particular order
+    async fn list(&self) -> Result<Vec<ImageMetadata>, ImageError>;
+    async fn delete(&self, id: &str) -> Result<(), ImageError>;
+}
+
+/// Images persisted in IndexedDB
 #[derive(Clone)]
-pub struct ImageStore {
+struct IdbBackend {
     db: IdbDatabase,
 }
 
-impl ImageStore {
+impl IdbBackend {
     /// Open or create the image store database
-    pub async fn open() -> Result<Self, ImageError> {
+    async fn open() -> Result<Self, ImageError> {
         let window = web_sys::window()
             .ok_or_else(|| ImageError::StorageError("No window object".to_string()))?;
 
@@ -67,38 +89,16 @@ impl ImageStore {
 
         // Wait for database to open
         let db = wait_for_request(&open_request).await?;
-        let db: IdbDatabase = db.unchecked_into();
 
-        Ok(Self { db })
+        Ok(Self {
+            db: db.unchecked_into(),
+        })
     }
+}
 
-    /// Store a new image
-    ///
-    /// Returns the generated image ID and metadata
-    pub async fn store_image(
-        &self,
-        filename: String,
-        data: Vec<u8>,
-    ) -> Result<ImageMetadata, ImageError> {
-        // Validate size
-        if data.len() > MAX_IMAGE_SIZE {
-            return Err(ImageError::FileTooLarge(data.len()));
-        }
-
-        // Detect MIME type from bytes
-        let mime_type = detect_mime_type(&data)
-            .ok_or_else(|| ImageError::UnsupportedFormat("Unknown format".to_string()))?;
-
-        // Validate MIME type
-        if !is_supported_mime_type(mime_type) {
-            return Err(ImageError::UnsupportedFormat(mime_type.to_string()));
-        }
-
-        // Generate unique ID
-        let id = generate_image_id();
-
-        // Create metadata
-        let metadata = ImageMetadata::new(id.clone(), filename, mime_type.to_string(), data.len());
+impl ImageBackend for IdbBackend {
+    async fn put(&self, metadata: &ImageMetadata, data: &[u8]) -> Result<(), ImageError> {
+        let id = &metadata.id;
 
         // Start transaction
         let transaction = self
@@ -114,11 +114,11 @@ impl ImageStore {
             .object_store(METADATA_STORE)
             .map_err(|e| ImageError::StorageError(format!("Store access failed: {:?}", e)))?;
 
-        let metadata_json = serde_json::to_string(&metadata)
+        let metadata_json = serde_json::to_string(metadata)
             .map_err(|e| ImageError::StorageError(format!("Serialization failed: {}", e)))?;
 
         let put_metadata = metadata_store
-            .put_with_key(&JsValue::from_str(&metadata_json), &JsValue::from_str(&id))
+            .put_with_key(&JsValue::from_str(&metadata_json), &JsValue::from_str(id))
             .map_err(|e| ImageError::StorageError(format!("Put metadata failed: {:?}", e)))?;
 
         wait_for_request(&put_metadata).await?;
@@ -128,99 +128,42 @@ impl ImageStore {
             .object_store(DATA_STORE)
             .map_err(|e| ImageError::StorageError(format!("Store access failed: {:?}", e)))?;
 
-        let uint8_array = js_sys::Uint8Array::from(data.as_slice());
+        let uint8_array = js_sys::Uint8Array::from(data);
         let put_data = data_store
-            .put_with_key(&uint8_array, &JsValue::from_str(&id))
+            .put_with_key(&uint8_array, &JsValue::from_str(id))
             .map_err(|e| ImageError::StorageError(format!("Put data failed: {:?}", e)))?;
 
         wait_for_request(&put_data).await?;
 
         // Wait for transaction to complete
-        wait_for_transaction(&transaction).await?;
-
-        Ok(metadata)
+        wait_for_transaction(&transaction).await
     }
 
-    /// Store a new AI-generated image with prompt and alt description
-    ///
-    /// Returns the generated image ID and metadata
-    pub async fn store_generated_image(
-        &self,
-        filename: String,
-        data: Vec<u8>,
-        generation_prompt: String,
-        alt_description: String,
-    ) -> Result<ImageMetadata, ImageError> {
-        // Validate size
-        if data.len() > MAX_IMAGE_SIZE {
-            return Err(ImageError::FileTooLarge(data.len()));
-        }
-
-        // Detect MIME type from bytes
-        let mime_type = detect_mime_type(&data)
-            .ok_or_else(|| ImageError::UnsupportedFormat("Unknown format".to_string()))?;
-
-        // Validate MIME type
-        if !is_supported_mime_type(mime_type) {
-            return Err(ImageError::UnsupportedFormat(mime_type.to_string()));
-        }
-
-        // Generate unique ID
-        let id = generate_image_id();
-
-        // Create metadata with generation info
-        let metadata = ImageMetadata::new_generated(
-            id.clone(),
-            filename,
-            mime_type.to_string(),
-            data.len(),
-            generation_prompt,
-            alt_description,
-        );
-
-        // Start transaction
+    async fn put_metadata(&self, metadata: &ImageMetadata) -> Result<(), ImageError> {
         let transaction = self
             .db
-            .transaction_with_str_sequence_and_mode(
-                &js_sys::Array::of2(&METADATA_STORE.into(), &DATA_STORE.into()),
-                web_sys::IdbTransactionMode::Readwrite,
-            )
+            .transaction_with_str_and_mode(METADATA_STORE, web_sys::IdbTransactionMode::Readwrite)
             .map_err(|e| ImageError::StorageError(format!("Transaction failed: {:?}", e)))?;
 
-        // Store metadata
-        let metadata_store = transaction
+        let store = transaction
             .object_store(METADATA_STORE)
             .map_err(|e| ImageError::StorageError(format!("Store access failed: {:?}", e)))?;
 
-        let metadata_json = serde_json::to_string(&metadata)
+        let metadata_json = serde_json::to_string(metadata)
             .map_err(|e| ImageError::StorageError(format!("Serialization failed: {}", e)))?;
 
-        let put_metadata = metadata_store
-            .put_with_key(&JsValue::from_str(&metadata_json), &JsValue::from_str(&id))
+        let put_metadata = store
+            .put_with_key(
+                &JsValue::from_str(&metadata_json),
+                &JsValue::from_str(&metadata.id),
+            )
             .map_err(|e| ImageError::StorageError(format!("Put metadata failed: {:?}", e)))?;
 
         wait_for_request(&put_metadata).await?;
-
-        // Store binary data
-        let data_store = transaction
-            .object_store(DATA_STORE)
-            .map_err(|e| ImageError::StorageError(format!("Store access failed: {:?}", e)))?;
-
-        let uint8_array = js_sys::Uint8Array::from(data.as_slice());
-        let put_data = data_store
-            .put_with_key(&uint8_array, &JsValue::from_str(&id))
-            .map_err(|e| ImageError::StorageError(format!("Put data failed: {:?}", e)))?;
-
-        wait_for_request(&put_data).await?;
-
-        // Wait for transaction to complete
-        wait_for_transaction(&transaction).await?;
-
-        Ok(metadata)
+        wait_for_transaction(&transaction).await
     }
 
-    /// Get image binary data by ID
-    pub async fn get_image_data(&self, id: &str) -> Result<Vec<u8>, ImageError> {
+    async fn get_data(&self, id: &str) -> Result<Vec<u8>, ImageError> {
         let transaction = self
             .db
             .transaction_with_str(DATA_STORE)
@@ -244,8 +187,7 @@ impl ImageStore {
         Ok(uint8_array.to_vec())
     }
 
-    /// Get image metadata by ID
-    pub async fn get_metadata(&self, id: &str) -> Result<ImageMetadata, ImageError> {
+    async fn get_metadata(&self, id: &str) -> Result<ImageMetadata, ImageError> {
         let transaction = self
             .db
             .transaction_with_str(METADATA_STORE)
@@ -273,8 +215,7 @@ impl ImageStore {
             .map_err(|e| ImageError::InvalidData(format!("Invalid metadata JSON: {}", e)))
     }
 
-    /// List all stored images
-    pub async fn list_images(&self) -> Result<Vec<ImageMetadata>, ImageError> {
+    async fn list(&self) -> Result<Vec<ImageMetadata>, ImageError> {
         let transaction = self
             .db
             .transaction_with_str(METADATA_STORE)
@@ -302,14 +243,10 @@ impl ImageStore {
             }
         }
 
-        // Sort by creation date (newest first)
-        images.sort_by(|a, b| b.created_at.cmp(&a.created_at));
-
         Ok(images)
     }
 
-    /// Delete an image by ID
-    pub async fn delete_image(&self, id: &str) -> Result<(), ImageError> {
+    async fn delete(&self, id: &str) -> Result<(), ImageError> {
         let transaction = self
             .db
             .transaction_with_str_sequence_and_mode(
@@ -341,10 +278,340 @@ impl ImageStore {
         wait_for_request(&delete_data).await?;
 
         // Wait for transaction to complete
-        wait_for_transaction(&transaction).await?;
+        wait_for_transaction(&transaction).await
+    }
+}
+
+/// Stored image metadata and bytes, keyed by image ID
+type MemoryImages = BTreeMap<String, (ImageMetadata, Vec<u8>)>;
+
+/// Images kept in memory for the session only
+///
+/// Clones share the same images, like clones of an IndexedDB handle.
+#[derive(Clone, Default)]
+struct MemoryBackend {
+    images: Rc<RefCell<MemoryImages>>,
+}
+
+impl ImageBackend for MemoryBackend {
+    async fn put(&self, metadata: &ImageMetadata, data: &[u8]) -> Result<(), ImageError> {
+        self.images
+            .borrow_mut()
+            .insert(metadata.id.clone(), (metadata.clone(), data.to_vec()));
+        Ok(())
+    }
+
+    async fn put_metadata(&self, metadata: &ImageMetadata) -> Result<(), ImageError> {
+        match self.images.borrow_mut().get_mut(&metadata.id) {
+            Some((stored, _)) => {
+                *stored = metadata.clone();
+                Ok(())
+            }
+            None => Err(ImageError::NotFound(metadata.id.clone())),
+        }
+    }
+
+    async fn get_data(&self, id: &str) -> Result<Vec<u8>, ImageError> {
+        self.images
+            .borrow()
+            .get(id)
+            .map(|(_, data)| data.clone())
+            .ok_or_else(|| ImageError::NotFound(id.to_string()))
+    }
 
+    async fn get_metadata(&self, id: &str) -> Result<ImageMetadata, ImageError> {
+        self.images
+            .borrow()
+            .get(id)
+            .map(|(metadata, _)| metadata.clone())
+            .ok_or_else(|| ImageError::NotFound(id.to_string()))
+    }
+
+    async fn list(&self) -> Result<Vec<ImageMetadata>, ImageError> {
+        Ok(self
+            .images
+            .borrow()
+            .values()
+            .map(|(metadata, _)| metadata.clone())
+            .collect())
+    }
+
+    async fn delete(&self, id: &str) -> Result<(), ImageError> {
+        self.images.borrow_mut().remove(id);
         Ok(())
     }
+}
+
+/// The backend an `ImageStore` writes to
+#[derive(Clone)]
+enum Backend {
+    IndexedDb(IdbBackend),
+    Memory(MemoryBackend),
+}
+
+impl ImageBackend for Backend {
+    async fn put(&self, metadata: &ImageMetadata, data: &[u8]) -> Result<(), ImageError> {
+        match self {
+            Self::IndexedDb(backend) => backend.put(metadata, data).await,
+            Self::Memory(backend) => backend.put(metadata, data).await,
+        }
+    }
+
+    async fn put_metadata(&self, metadata: &ImageMetadata) -> Result<(), ImageError> {
+        match self {
+            Self::IndexedDb(backend) => backend.put_metadata(metadata).await,
+            Self::Memory(backend) => backend.put_metadata(metadata).await,
+        }
+    }
+
+    async fn get_data(&self, id: &str) -> Result<Vec<u8>, ImageError> {
+        match self {
+            Self::IndexedDb(backend) => backend.get_data(id).await,
+            Self::Memory(backend) => backend.get_data(id).await,
+        }
+    }
+
+    async fn get_metadata(&self, id: &str) -> Result<ImageMetadata, ImageError> {
+        match self {
+            Self::IndexedDb(backend) => backend.get_metadata(id).await,
+            Self::Memory(backend) => backend.get_metadata(id).await,
+        }
+    }
+
+    async fn list(&self) -> Result<Vec<ImageMetadata>, ImageError> {
+        match self {
+            Self::IndexedDb(backend) => backend.list().await,
+            Self::Memory(backend) => backend.list().await,
+        }
+    }
+
+    async fn delete(&self, id: &str) -> Result<(), ImageError> {
+        match self {
+            Self::IndexedDb(backend) => backend.delete(id).await,
+            Self::Memory(backend) => backend.delete(id).await,
+        }
+    }
+}
+
+/// Image store backed by IndexedDB, or by memory when IndexedDB is unavailable
+#[derive(Clone)]
+pub struct ImageStore {
+    backend: Backend,
+    /// Derive IDs from image bytes instead of generating random ones
+    content_addressed: bool,
+}
+
+impl ImageStore {
+    /// Open or create the image store database
+    pub async fn open() -> Result<Self, ImageError> {
+        let backend = IdbBackend::open().await?;
+        Ok(Self {
+            backend: Backend::IndexedDb(backend),
+            content_addressed: false,
+        })
+    }
+
+    /// Create a store that keeps images in memory for this session only
+    ///
+    /// Used when IndexedDB cannot be opened, e.g. in some private browsing
+    /// modes, so uploads still work until the page is reloaded.
+    pub fn in_memory() -> Self {
+        Self {
+            backend: Backend::Memory(MemoryBackend::default()),
+            content_addressed: false,
+        }
+    }
+
+    /// Whether stored images survive a reload
+    pub fn is_persistent(&self) -> bool {
+        matches!(self.backend, Backend::IndexedDb(_))
+    }
+
+    /// Builder method to derive new image IDs from the image bytes
+    ///
+    /// With this set, storing the same bytes twice reuses one ID (and
+    /// overwrites the earlier entry) instead of creating a duplicate.
+    pub fn content_addressed(mut self, enabled: bool) -> Self {
+        self.content_addressed = enabled;
+        self
+    }
+
+    /// Pick the ID for newly stored image bytes
+    fn new_image_id(&self, data: &[u8]) -> String {
+        if self.content_addressed {
+            generate_content_id(data)
+        } else {
+            generate_image_id()
+        }
+    }
+
+    /// Store a new image
+    ///
+    /// Returns the generated image ID and metadata
+    pub async fn store_image(
+        &self,
+        filename: String,
+        data: Vec<u8>,
+    ) -> Result<ImageMetadata, ImageError> {
+        // Validate size
+        if data.len() > MAX_IMAGE_SIZE {
+            return Err(ImageError::FileTooLarge(data.len()));
+        }
+
+        // Detect MIME type from bytes
+        let mime_type = detect_mime_type(&data)
+            .ok_or_else(|| ImageError::UnsupportedFormat("Unknown format".to_string()))?;
+
+        // Validate MIME type
+        if !is_supported_mime_type(mime_type) {
+            return Err(ImageError::UnsupportedFormat(mime_type.to_string()));
+        }
+
+        // Generate unique ID
+        let id = self.new_image_id(&data);
+
+        // Create metadata
+        let metadata = ImageMetadata::new(id.clone(), filename, mime_type.to_string(), data.len());
+
+        let (metadata, _) = self.put_image(metadata, data).await?;
+        Ok(metadata)
+    }
+
+    /// Store an image given as a `data:<mime>;base64,<data>` URL
+    ///
+    /// The URL is decoded and validated, then stored like a normal upload.
+    pub async fn store_data_url(
+        &self,
+        filename: String,
+        data_url: &str,
+    ) -> Result<ImageMetadata, ImageError> {
+        let data = decode_image_data_url(data_url)?;
+        self.store_image(filename, data).await
+    }
+
+    /// Store a new AI-generated image with prompt and alt description
+    ///
+    /// `derived_from` links a regenerated image to the image it was re-run
+    /// from. Returns the generated image ID and metadata
+    pub async fn store_generated_image(
+        &self,
+        filename: String,
+        data: Vec<u8>,
+        generation_prompt: String,
+        alt_description: String,
+        derived_from: Option<String>,
+    ) -> Result<ImageMetadata, ImageError> {
+        // Validate size
+        if data.len() > MAX_IMAGE_SIZE {
+            return Err(ImageError::FileTooLarge(data.len()));
+        }
+
+        // Detect MIME type from bytes
+        let mime_type = detect_mime_type(&data)
+            .ok_or_else(|| ImageError::UnsupportedFormat("Unknown format".to_string()))?;
+
+        // Validate MIME type
+        if !is_supported_mime_type(mime_type) {
+            return Err(ImageError::UnsupportedFormat(mime_type.to_string()));
+        }
+
+        // Generate unique ID
+        let id = self.new_image_id(&data);
+
+        // Create metadata with generation info
+        let mut metadata = ImageMetadata::new_generated(
+            id.clone(),
+            filename,
+            mime_type.to_string(),
+            data.len(),
+            generation_prompt,
+            alt_description,
+        );
+        metadata.derived_from = derived_from;
+
+        let (metadata, _) = self.put_image(metadata, data).await?;
+        Ok(metadata)
+    }
+
+    /// Store an image under existing metadata, keeping its ID
+    ///
+    /// Used when importing a bundle whose source already references the ID.
+    /// Returns the bytes as stored, which differ from `data` for SVGs.
+    pub async fn restore_image(
+        &self,
+        metadata: &ImageMetadata,
+        data: &[u8],
+    ) -> Result<Vec<u8>, ImageError> {
+        if data.len() > MAX_IMAGE_SIZE {
+            return Err(ImageError::FileTooLarge(data.len()));
+        }
+        if !is_supported_mime_type(&metadata.mime_type) {
+            return Err(ImageError::UnsupportedFormat(metadata.mime_type.clone()));
+        }
+        let (_, data) = self.put_image(metadata.clone(), data.to_vec()).await?;
+        Ok(data)
+    }
+
+    /// Replace the alt description of a stored image, leaving its bytes alone
+    pub async fn set_alt_description(
+        &self,
+        id: &str,
+        alt_description: String,
+    ) -> Result<ImageMetadata, ImageError> {
+        let mut metadata = self.get_metadata(id).await?;
+        metadata.alt_description = Some(alt_description);
+        self.backend.put_metadata(&metadata).await?;
+        Ok(metadata)
+    }
+
+    /// Write metadata and bytes in one transaction
+    ///
+    /// SVGs are inlined in the preview, so every write path strips their
+    /// scripts and external fetches here; the stored size and returned bytes
+    /// are the sanitized ones. A quota failure is reported as
+    /// `ImageError::QuotaExceeded` with the current usage from
+    /// `storage_estimate()`.
+    async fn put_image(
+        &self,
+        mut metadata: ImageMetadata,
+        data: Vec<u8>,
+    ) -> Result<(ImageMetadata, Vec<u8>), ImageError> {
+        let data = if metadata.mime_type == "image/svg+xml" {
+            sanitize_svg(&data)?
+        } else {
+            data
+        };
+        metadata.size = data.len();
+        match self.backend.put(&metadata, &data).await {
+            Err(ImageError::QuotaExceeded { .. }) => Err(ImageError::QuotaExceeded {
+                used: storage_estimate().await.map(|e| e.usage).unwrap_or(0),
+                requested: data.len() as u64,
+            }),
+            result => result.map(|()| (metadata, data)),
+        }
+    }
+
+    /// Get image binary data by ID
+    pub async fn get_image_data(&self, id: &str) -> Result<Vec<u8>, ImageError> {
+        self.backend.get_data(id).await
+    }
+
+    /// Get image metadata by ID
+    pub async fn get_metadata(&self, id: &str) -> Result<ImageMetadata, ImageError> {
+        self.backend.get_metadata(id).await
+    }
+
+    /// List all stored images, newest first
+    pub async fn list_images(&self) -> Result<Vec<ImageMetadata>, ImageError> {
+        let mut images = self.backend.list().await?;
+        images.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        Ok(images)
+    }
+
+    /// Delete an image by ID
+    pub async fn delete_image(&self, id: &str) -> Result<(), ImageError> {
+        self.backend.delete(id).await
+    }
 
     /// Get the file path for an image ID (used in Typst templates)
     ///
@@ -356,7 +623,7 @@ impl ImageStore {
 }
 
 /// Wait for an IDB request to complete
-async fn wait_for_request(request: &IdbRequest) -> Result<JsValue, ImageError> {
+pub(crate) async fn wait_for_request(request: &IdbRequest) -> Result<JsValue, ImageError> {
     use wasm_bindgen_futures::JsFuture;
 
     let promise = js_sys::Promise::new(&mut |resolve, reject| {
@@ -370,13 +637,17 @@ async fn wait_for_request(request: &IdbRequest) -> Result<JsValue, ImageError> {
             resolve_clone.call1(&JsValue::UNDEFINED, &result).unwrap();
         }) as Box<dyn FnOnce(_)>);
 
+        let failed_request = request.clone();
         let onerror = Closure::once(Box::new(move |_event: web_sys::Event| {
-            // Simply report a generic error - the actual error is hard to extract
+            // Report the DOMException name so quota failures can be recognised
+            let name = failed_request
+                .error()
+                .ok()
+                .flatten()
+                .map(|e| e.name())
+                .unwrap_or_else(|| "IndexedDB request failed".to_string());
             reject_clone
-                .call1(
-                    &JsValue::UNDEFINED,
-                    &JsValue::from_str("IndexedDB request failed"),
-                )
+                .call1(&JsValue::UNDEFINED, &JsValue::from_str(&name))
                 .unwrap();
         }) as Box<dyn FnOnce(_)>);
 
@@ -388,11 +659,11 @@ async fn wait_for_request(request: &IdbRequest) -> Result<JsValue, ImageError> {
 
     JsFuture::from(promise)
         .await
-        .map_err(|e| ImageError::StorageError(format!("Request failed: {:?}", e)))
+        .map_err(|e| idb_error(e, "Request failed"))
 }
 
 /// Wait for an IDB transaction to complete
-async fn wait_for_transaction(transaction: &IdbTransaction) -> Result<(), ImageError> {
+pub(crate) async fn wait_for_transaction(transaction: &IdbTransaction) -> Result<(), ImageError> {
     use wasm_bindgen_futures::JsFuture;
 
     let promise = js_sys::Promise::new(&mut |resolve, reject| {
@@ -403,23 +674,187 @@ async fn wait_for_transaction(transaction: &IdbTransaction) -> Result<(), ImageE
             resolve_clone.call0(&JsValue::UNDEFINED).unwrap();
         }) as Box<dyn FnOnce(_)>);
 
-        let onerror = Closure::once(Box::new(move |_event: web_sys::Event| {
+        // Quota failures usually surface at commit time as an abort
+        let failed_transaction = transaction.clone();
+        let onabort = Closure::once(Box::new(move |_event: web_sys::Event| {
+            let name = failed_transaction
+                .error()
+                .map(|e| e.name())
+                .unwrap_or_else(|| "Transaction failed".to_string());
             reject_clone
-                .call1(
-                    &JsValue::UNDEFINED,
-                    &JsValue::from_str("Transaction failed"),
-                )
+                .call1(&JsValue::UNDEFINED, &JsValue::from_str(&name))
                 .unwrap();
         }) as Box<dyn FnOnce(_)>);
 
         transaction.set_oncomplete(Some(oncomplete.as_ref().unchecked_ref()));
-        transaction.set_onerror(Some(onerror.as_ref().unchecked_ref()));
+        // Errors abort the transaction, so onabort covers both cases
+        transaction.set_onabort(Some(onabort.as_ref().unchecked_ref()));
         oncomplete.forget();
-        onerror.forget();
+        onabort.forget();
     });
 
     JsFuture::from(promise)
         .await
         .map(|_| ())
-        .map_err(|e| ImageError::StorageError(format!("Transaction failed: {:?}", e)))
+        .map_err(|e| idb_error(e, "Transaction failed"))
+}
+
+/// DOMException name IndexedDB reports when the origin is out of space
+const QUOTA_EXCEEDED_ERROR: &str = "QuotaExceededError";
+
+/// Map a rejected IndexedDB promise to an error, recognising quota exhaustion
+fn idb_error(value: JsValue, context: &str) -> ImageError {
+    match value.as_string() {
+        Some(name) if name == QUOTA_EXCEEDED_ERROR => ImageError::QuotaExceeded {
+            used: 0,
+            requested: 0,
+        },
+        _ => ImageError::StorageError(format!("{}: {:?}", context, value)),
+    }
+}
+
+/// Storage usage reported by `navigator.storage.estimate()`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StorageEstimate {
+    /// Bytes used by this origin
+    pub usage: u64,
+    /// Bytes available to this origin in total
+    pub quota: u64,
+}
+
+/// Query how much browser storage this origin is using
+pub async fn storage_estimate() -> Result<StorageEstimate, ImageError> {
+    use wasm_bindgen_futures::JsFuture;
+
+    let window = web_sys::window()
+        .ok_or_else(|| ImageError::StorageError("No window object".to_string()))?;
+    let promise = window
+        .navigator()
+        .storage()
+        .estimate()
+        .map_err(|e| ImageError::StorageError(format!("Storage estimate failed: {:?}", e)))?;
+    let estimate = JsFuture::from(promise)
+        .await
+        .map_err(|e| ImageError::StorageError(format!("Storage estimate failed: {:?}", e)))?;
+
+    let field = |name: &str| {
+        js_sys::Reflect::get(&estimate, &JsValue::from_str(name))
+            .ok()
+            .and_then(|value| value.as_f64())
+            .unwrap_or(0.0) as u64
+    };
+    Ok(StorageEstimate {
+        usage: field("usage"),
+        quota: field("quota"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+
+    /// Bytes that pass PNG detection, varied by `seed` for distinct IDs
+    fn png_bytes(seed: u8) -> Vec<u8> {
+        let mut bytes = vec![0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A];
+        bytes.extend([seed; 8]);
+        bytes
+    }
+
+    /// In-memory store with IDs that don't need browser randomness
+    fn memory_store() -> ImageStore {
+        ImageStore::in_memory().content_addressed(true)
+    }
+
+    #[test]
+    fn test_in_memory_store_and_get() {
+        let store = memory_store();
+        assert!(!store.is_persistent());
+
+        let metadata = block_on(store.store_image("logo.png".to_string(), png_bytes(1))).unwrap();
+        assert_eq!(metadata.mime_type, "image/png");
+
+        assert_eq!(
+            block_on(store.get_image_data(&metadata.id)).unwrap(),
+            png_bytes(1)
+        );
+        assert_eq!(
+            block_on(store.get_metadata(&metadata.id)).unwrap(),
+            metadata
+        );
+    }
+
+    #[test]
+    fn test_in_memory_list_and_delete() {
+        let store = memory_store();
+        let first = block_on(store.store_image("a.png".to_string(), png_bytes(1))).unwrap();
+        let second = block_on(store.store_image("b.png".to_string(), png_bytes(2))).unwrap();
+
+        let mut listed: Vec<_> = block_on(store.list_images())
+            .unwrap()
+            .into_iter()
+            .map(|image| image.filename)
+            .collect();
+        listed.sort();
+        assert_eq!(listed, vec!["a.png", "b.png"]);
+
+        block_on(store.delete_image(&first.id)).unwrap();
+        assert!(matches!(
+            block_on(store.get_image_data(&first.id)),
+            Err(ImageError::NotFound(_))
+        ));
+        let remaining = block_on(store.list_images()).unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].id, second.id);
+    }
+
+    #[test]
+    fn test_in_memory_clones_share_images() {
+        let store = memory_store();
+        let clone = store.clone();
+        let metadata = block_on(store.store_image("a.png".to_string(), png_bytes(3))).unwrap();
+
+        let updated =
+            block_on(clone.set_alt_description(&metadata.id, "A logo".to_string())).unwrap();
+        assert_eq!(updated.alt_description.as_deref(), Some("A logo"));
+        assert_eq!(
+            block_on(store.get_metadata(&metadata.id))
+                .unwrap()
+                .alt_description
+                .as_deref(),
+            Some("A logo")
+        );
+    }
+
+    #[test]
+    fn test_in_memory_rejects_unsupported_data() {
+        let store = memory_store();
+        let result = block_on(store.store_image("notes.txt".to_string(), b"plain text!!".to_vec()));
+        assert!(matches!(result, Err(ImageError::UnsupportedFormat(_))));
+        assert!(block_on(store.list_images()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_restore_image_sanitizes_svg() {
+        let store = memory_store();
+        let svg = br#"<svg xmlns="http://www.w3.org/2000/svg"><script>alert(1)</script><rect onclick="steal()" width="5" height="5"/></svg>"#;
+        let metadata = ImageMetadata::new(
+            "img_bundled".to_string(),
+            "logo.svg".to_string(),
+            "image/svg+xml".to_string(),
+            svg.len(),
+        );
+
+        let restored = block_on(store.restore_image(&metadata, svg)).unwrap();
+        let stored = block_on(store.get_image_data("img_bundled")).unwrap();
+
+        assert_eq!(stored, restored);
+        let stored = String::from_utf8(stored).unwrap();
+        assert!(!stored.contains("script"), "{}", stored);
+        assert!(!stored.contains("onclick"), "{}", stored);
+        assert_eq!(
+            block_on(store.get_metadata("img_bundled")).unwrap().size,
+            stored.len()
+        );
+    }
 }
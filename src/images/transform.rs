@@ -0,0 +1,201 @@
+//! Pre-store image transforms
+//!
+//! Oversized uploads are cropped and scaled down before they are stored,
+//! keeping projects small and layouts predictable.
+
+use std::io::Cursor;
+
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageFormat};
+
+use super::{detect_mime_type, ImageError};
+
+/// Rectangle in pixel coordinates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+    /// Left edge
+    pub x: u32,
+    /// Top edge
+    pub y: u32,
+    /// Width in pixels
+    pub width: u32,
+    /// Height in pixels
+    pub height: u32,
+}
+
+/// How to transform an image before storing it
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TransformOptions {
+    /// Maximum width after resizing (aspect ratio is preserved)
+    pub max_width: Option<u32>,
+    /// Maximum height after resizing (aspect ratio is preserved)
+    pub max_height: Option<u32>,
+    /// Region to crop to before resizing
+    pub crop: Option<Rect>,
+}
+
+impl TransformOptions {
+    /// Fit within a square bound, e.g. 1600px on the longest side
+    pub fn max_dimension(max: u32) -> Self {
+        Self {
+            max_width: Some(max),
+            max_height: Some(max),
+            crop: None,
+        }
+    }
+}
+
+/// Crop and/or downscale an image, re-encoding it in its original format
+///
+/// SVGs and formats without an encoder here (WebP, BMP, TIFF) pass through
+/// untouched, as do images that already fit and need no crop. Images are
+/// never upscaled.
+pub fn transform_image(bytes: &[u8], options: &TransformOptions) -> Result<Vec<u8>, ImageError> {
+    let format = match detect_mime_type(bytes) {
+        Some("image/png") => ImageFormat::Png,
+        Some("image/jpeg") => ImageFormat::Jpeg,
+        Some("image/gif") => ImageFormat::Gif,
+        _ => return Ok(bytes.to_vec()),
+    };
+
+    let mut img = image::load_from_memory_with_format(bytes, format)
+        .map_err(|e| ImageError::InvalidData(format!("Failed to decode image: {}", e)))?;
+    let mut changed = false;
+
+    if let Some(crop) = options.crop {
+        let fits = crop.width > 0
+            && crop.height > 0
+            && crop
+                .x
+                .checked_add(crop.width)
+                .is_some_and(|r| r <= img.width())
+            && crop
+                .y
+                .checked_add(crop.height)
+                .is_some_and(|b| b <= img.height());
+        if !fits {
+            return Err(ImageError::InvalidData(format!(
+                "Crop {}x{} at ({}, {}) is outside the {}x{} image",
+                crop.width,
+                crop.height,
+                crop.x,
+                crop.y,
+                img.width(),
+                img.height()
+            )));
+        }
+        img = img.crop_imm(crop.x, crop.y, crop.width, crop.height);
+        changed = true;
+    }
+
+    let max_width = options.max_width.unwrap_or(u32::MAX).max(1);
+    let max_height = options.max_height.unwrap_or(u32::MAX).max(1);
+    if img.width() > max_width || img.height() > max_height {
+        img = img.resize(max_width, max_height, FilterType::Lanczos3);
+        changed = true;
+    }
+
+    if !changed {
+        return Ok(bytes.to_vec());
+    }
+    encode(img, format)
+}
+
+/// Encode an image, dropping alpha for formats that cannot store it
+fn encode(img: DynamicImage, format: ImageFormat) -> Result<Vec<u8>, ImageError> {
+    let img = match format {
+        ImageFormat::Jpeg => DynamicImage::ImageRgb8(img.to_rgb8()),
+        _ => img,
+    };
+
+    let mut out = Vec::new();
+    img.write_to(&mut Cursor::new(&mut out), format)
+        .map_err(|e| ImageError::InvalidData(format!("Failed to encode image: {}", e)))?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn synthetic_png(width: u32, height: u32) -> Vec<u8> {
+        let img = image::RgbaImage::from_fn(width, height, |x, y| {
+            image::Rgba([(x % 256) as u8, (y % 256) as u8, 128, 255])
+        });
+        let mut out = Vec::new();
+        DynamicImage::ImageRgba8(img)
+            .write_to(&mut Cursor::new(&mut out), ImageFormat::Png)
+            .unwrap();
+        out
+    }
+
+    fn dimensions(bytes: &[u8]) -> (u32, u32) {
+        let img = image::load_from_memory(bytes).expect("result should decode");
+        (img.width(), img.height())
+    }
+
+    #[test]
+    fn test_resize_to_bound() {
+        let png = synthetic_png(1000, 1000);
+        let resized = transform_image(&png, &TransformOptions::max_dimension(200)).unwrap();
+
+        assert_eq!(dimensions(&resized), (200, 200));
+        assert_eq!(detect_mime_type(&resized), Some("image/png"));
+        assert!(resized.len() < png.len());
+    }
+
+    #[test]
+    fn test_resize_preserves_aspect_ratio() {
+        let png = synthetic_png(1000, 500);
+        let resized = transform_image(&png, &TransformOptions::max_dimension(200)).unwrap();
+        assert_eq!(dimensions(&resized), (200, 100));
+    }
+
+    #[test]
+    fn test_small_image_is_untouched() {
+        let png = synthetic_png(100, 50);
+        let result = transform_image(&png, &TransformOptions::max_dimension(200)).unwrap();
+        assert_eq!(result, png);
+    }
+
+    #[test]
+    fn test_crop_then_resize() {
+        let png = synthetic_png(1000, 1000);
+        let options = TransformOptions {
+            crop: Some(Rect {
+                x: 100,
+                y: 100,
+                width: 800,
+                height: 400,
+            }),
+            ..TransformOptions::max_dimension(400)
+        };
+        let result = transform_image(&png, &options).unwrap();
+        assert_eq!(dimensions(&result), (400, 200));
+    }
+
+    #[test]
+    fn test_crop_out_of_bounds_errors() {
+        let png = synthetic_png(100, 100);
+        let options = TransformOptions {
+            crop: Some(Rect {
+                x: 50,
+                y: 50,
+                width: 100,
+                height: 10,
+            }),
+            ..Default::default()
+        };
+        assert!(matches!(
+            transform_image(&png, &options),
+            Err(ImageError::InvalidData(_))
+        ));
+    }
+
+    #[test]
+    fn test_svg_passes_through() {
+        let svg = br#"<svg xmlns="http://www.w3.org/2000/svg" width="5000" height="5000"></svg>"#;
+        let result = transform_image(svg, &TransformOptions::max_dimension(10)).unwrap();
+        assert_eq!(result, svg.to_vec());
+    }
+}
@@ -4,12 +4,16 @@
 //! - Typst compilation via VirtualWorld
 //! - Template engine with Handlebars-style syntax
 //! - Data models for slick sheet content
+//! - Custom font upload and storage
 //! - AI agent integration
 //! - Persistence (save/load/export)
+//! - Logging to the browser console or `tracing`
 
 pub mod ai;
 pub mod data;
+pub mod fonts;
 pub mod images;
+pub mod log;
 pub mod persistence;
 pub mod template;
 pub mod templates;
@@ -0,0 +1,132 @@
+//! Logging that works in the browser and natively
+//!
+//! Messages go to the browser console on WASM and to `tracing` elsewhere.
+//! `debug` messages (request/response dumps and the like) are only emitted
+//! with the `verbose-logs` feature, so they can be left in place.
+
+/// Severity of a log message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    /// Diagnostic detail, emitted only with `verbose-logs`
+    Debug,
+    /// Normal operational messages
+    Info,
+    /// Something went wrong but work continues
+    Warn,
+    /// An operation failed
+    Error,
+}
+
+/// Log diagnostic detail (compiled in only with the `verbose-logs` feature)
+pub fn debug(message: &str) {
+    if cfg!(feature = "verbose-logs") {
+        emit(Level::Debug, message);
+    }
+}
+
+/// Log an informational message
+pub fn info(message: &str) {
+    emit(Level::Info, message);
+}
+
+/// Log a warning
+pub fn warn(message: &str) {
+    emit(Level::Warn, message);
+}
+
+/// Log an error
+pub fn error(message: &str) {
+    emit(Level::Error, message);
+}
+
+fn emit(level: Level, message: &str) {
+    #[cfg(test)]
+    if capture::record(level, message) {
+        return;
+    }
+    write(level, message);
+}
+
+#[cfg(target_arch = "wasm32")]
+fn write(level: Level, message: &str) {
+    use wasm_bindgen::JsValue;
+    use web_sys::console;
+
+    let message = JsValue::from_str(message);
+    match level {
+        Level::Debug => console::debug_1(&message),
+        Level::Info => console::log_1(&message),
+        Level::Warn => console::warn_1(&message),
+        Level::Error => console::error_1(&message),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write(level: Level, message: &str) {
+    match level {
+        Level::Debug => tracing::debug!("{}", message),
+        Level::Info => tracing::info!("{}", message),
+        Level::Warn => tracing::warn!("{}", message),
+        Level::Error => tracing::error!("{}", message),
+    }
+}
+
+/// Test hook collecting messages instead of writing them
+#[cfg(test)]
+pub(crate) mod capture {
+    use std::cell::RefCell;
+
+    use super::Level;
+
+    thread_local! {
+        static CAPTURED: RefCell<Option<Vec<(Level, String)>>> = const { RefCell::new(None) };
+    }
+
+    /// Run `f`, returning the messages it logged on this thread
+    pub(crate) fn logs(f: impl FnOnce()) -> Vec<(Level, String)> {
+        CAPTURED.with(|captured| *captured.borrow_mut() = Some(Vec::new()));
+        f();
+        CAPTURED.with(|captured| captured.borrow_mut().take().unwrap_or_default())
+    }
+
+    /// Store a message if capturing; returns whether it was captured
+    pub(super) fn record(level: Level, message: &str) -> bool {
+        CAPTURED.with(|captured| match captured.borrow_mut().as_mut() {
+            Some(messages) => {
+                messages.push((level, message.to_string()));
+                true
+            }
+            None => false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::capture::logs;
+    use super::*;
+
+    #[test]
+    fn test_messages_are_captured_with_their_level() {
+        let captured = logs(|| {
+            info("compiled");
+            warn("Failed to preload fonts: quota");
+            error("Failed to open image store");
+        });
+
+        assert_eq!(
+            captured,
+            vec![
+                (Level::Info, "compiled".to_string()),
+                (Level::Warn, "Failed to preload fonts: quota".to_string()),
+                (Level::Error, "Failed to open image store".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_debug_messages_follow_verbose_feature() {
+        let captured = logs(|| debug("request body"));
+        assert_eq!(captured.len(), usize::from(cfg!(feature = "verbose-logs")));
+    }
+}
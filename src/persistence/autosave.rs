@@ -0,0 +1,111 @@
+//! Autosave of the working project to localStorage
+//!
+//! The editor periodically writes an `Autosave` snapshot so unsaved work can
+//! be recovered after a crash or accidental close. The key is versioned so a
+//! future payload change can ignore older snapshots instead of failing.
+
+use serde::{Deserialize, Serialize};
+
+use super::project::Project;
+use crate::data::SlickSheetData;
+
+/// localStorage key for the autosave payload
+pub const AUTOSAVE_KEY: &str = "slick_autosave_v1";
+
+/// localStorage key for the timestamp of the last explicit save
+pub const LAST_SAVED_KEY: &str = "slick_last_saved_at";
+
+/// Delay after the last change before writing an autosave (milliseconds)
+pub const AUTOSAVE_DELAY_MS: i32 = 3000;
+
+/// Snapshot of unsaved editor work
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Autosave {
+    /// When the snapshot was taken (RFC 3339)
+    pub saved_at: String,
+    /// Project name and Typst source
+    pub project: Project,
+    /// JSON content data
+    #[serde(default)]
+    pub content_data: Option<SlickSheetData>,
+    /// Template source
+    #[serde(default)]
+    pub template_source: Option<String>,
+}
+
+impl Autosave {
+    /// Create a snapshot stamped with the current time
+    pub fn new(project: Project, content_data: SlickSheetData, template_source: String) -> Self {
+        Self {
+            saved_at: chrono::Utc::now().to_rfc3339(),
+            project,
+            content_data: Some(content_data),
+            template_source: Some(template_source),
+        }
+    }
+
+    /// Serialize to JSON
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string(self).map_err(|e| format!("Serialization failed: {e}"))
+    }
+
+    /// Deserialize from JSON, migrating the embedded project
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let mut autosave: Self =
+            serde_json::from_str(json).map_err(|e| format!("Deserialization failed: {e}"))?;
+        autosave.project = super::migration::migrate(autosave.project);
+        Ok(autosave)
+    }
+
+    /// Check whether this snapshot is newer than the last explicit save
+    ///
+    /// With no recorded save any snapshot counts as newer; an unparseable
+    /// snapshot timestamp never does.
+    pub fn is_newer_than(&self, last_saved_at: Option<&str>) -> bool {
+        let Ok(saved_at) = chrono::DateTime::parse_from_rfc3339(&self.saved_at) else {
+            return false;
+        };
+        match last_saved_at.map(chrono::DateTime::parse_from_rfc3339) {
+            Some(Ok(last_saved)) => saved_at > last_saved,
+            Some(Err(_)) | None => true,
+        }
+    }
+}
+
+fn get_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok().flatten()
+}
+
+/// Write an autosave snapshot to localStorage
+pub fn store_autosave(autosave: &Autosave) {
+    let (Some(storage), Ok(json)) = (get_storage(), autosave.to_json()) else {
+        return;
+    };
+    let _ = storage.set_item(AUTOSAVE_KEY, &json);
+}
+
+/// Load the autosave snapshot if it is newer than the last explicit save
+pub fn load_recoverable_autosave() -> Option<Autosave> {
+    let storage = get_storage()?;
+    let json = storage.get_item(AUTOSAVE_KEY).ok().flatten()?;
+    let autosave = Autosave::from_json(&json).ok()?;
+    let last_saved = storage.get_item(LAST_SAVED_KEY).ok().flatten();
+    autosave
+        .is_newer_than(last_saved.as_deref())
+        .then_some(autosave)
+}
+
+/// Discard the autosave snapshot
+pub fn clear_autosave() {
+    if let Some(storage) = get_storage() {
+        let _ = storage.remove_item(AUTOSAVE_KEY);
+    }
+}
+
+/// Record an explicit save and discard the now-redundant autosave
+pub fn mark_saved() {
+    if let Some(storage) = get_storage() {
+        let _ = storage.set_item(LAST_SAVED_KEY, &chrono::Utc::now().to_rfc3339());
+        let _ = storage.remove_item(AUTOSAVE_KEY);
+    }
+}
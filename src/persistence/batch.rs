@@ -0,0 +1,155 @@
+//! Batch processing of project files on disk
+//!
+//! Used by the CLI to apply one change (e.g. an agent prompt) across many
+//! projects. Each project is processed independently, so one failure does
+//! not stop the rest of the batch.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use super::project::Project;
+
+/// Outcome of a batch run
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct BatchSummary {
+    /// Output paths of projects that were processed and written
+    pub succeeded: Vec<PathBuf>,
+    /// Input paths that failed, with the reason
+    pub failed: Vec<(PathBuf, String)>,
+}
+
+impl BatchSummary {
+    /// Total number of projects attempted
+    pub fn total(&self) -> usize {
+        self.succeeded.len() + self.failed.len()
+    }
+
+    /// Whether every project succeeded
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+impl fmt::Display for BatchSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} of {} project(s) succeeded, {} failed",
+            self.succeeded.len(),
+            self.total(),
+            self.failed.len()
+        )?;
+        for (path, error) in &self.failed {
+            write!(f, "\n  {}: {}", path.display(), error)?;
+        }
+        Ok(())
+    }
+}
+
+/// Expand a glob like `sheets/*.json` into matching file paths, sorted
+///
+/// Wildcards (`*` and `?`) are supported in the final path component only.
+pub fn expand_glob(pattern: &str) -> Result<Vec<PathBuf>, String> {
+    let pattern_path = Path::new(pattern);
+    let file_pattern = pattern_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| format!("Invalid glob pattern: '{}'", pattern))?;
+    let dir = match pattern_path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    if dir.to_string_lossy().contains(['*', '?']) {
+        return Err(format!(
+            "Wildcards are only supported in the file name: '{}'",
+            pattern
+        ));
+    }
+
+    let entries = std::fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read directory {}: {}", dir.display(), e))?;
+    let mut matches: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| wildcard_match(file_pattern, name))
+        })
+        .collect();
+    matches.sort();
+    Ok(matches)
+}
+
+/// Match `name` against a pattern where `*` is any run and `?` any one char
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // Position of the last `*` and the name index it was tried at
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star, tried)) => {
+                    p = star + 1;
+                    n = tried + 1;
+                    backtrack = Some((star, tried + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Run `process` on each project file, writing results into `output_dir`
+///
+/// Output files keep their input file names. Read, parse, processing and
+/// write errors are recorded in the summary and the batch moves on.
+pub fn run_batch<F>(files: &[PathBuf], output_dir: &Path, mut process: F) -> BatchSummary
+where
+    F: FnMut(&Path, Project) -> Result<Project, String>,
+{
+    let mut summary = BatchSummary::default();
+
+    for file in files {
+        match process_file(file, output_dir, &mut process) {
+            Ok(output) => summary.succeeded.push(output),
+            Err(e) => summary.failed.push((file.clone(), e)),
+        }
+    }
+
+    summary
+}
+
+/// Read, process and write a single project
+fn process_file<F>(file: &Path, output_dir: &Path, process: &mut F) -> Result<PathBuf, String>
+where
+    F: FnMut(&Path, Project) -> Result<Project, String>,
+{
+    let file_name = file
+        .file_name()
+        .ok_or_else(|| format!("Not a file: {}", file.display()))?;
+    let content = std::fs::read_to_string(file).map_err(|e| format!("Failed to read: {}", e))?;
+    let project = Project::from_json(&content).map_err(|e| format!("Failed to parse: {}", e))?;
+
+    let updated = process(file, project)?;
+
+    let output = output_dir.join(file_name);
+    let json = updated
+        .to_json_pretty()
+        .map_err(|e| format!("Failed to serialize: {}", e))?;
+    std::fs::write(&output, json).map_err(|e| format!("Failed to write: {}", e))?;
+    Ok(output)
+}
@@ -1,6 +1,12 @@
-//! Export functionality for PDF generation
+//! Export functionality for PDF, PNG, HTML and zip bundle generation
 
-use crate::world::VirtualWorld;
+use std::io::Write;
+
+use crate::images::{extension_from_mime_type, ImageMetadata};
+use crate::template::ExportPreset;
+use crate::world::{minify_svg_with_options, page_to_svg, MinifyOptions, VirtualWorld};
+
+use super::project::Project;
 
 /// Format compilation errors into a single error string
 fn format_errors<I, T>(errors: I, prefix: &str) -> String
@@ -15,22 +21,425 @@ where
         .join("\n")
 }
 
+/// Document metadata embedded in exported PDFs
+///
+/// Fields left empty keep whatever the source sets via `#set document(...)`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PdfMeta {
+    /// Document title shown in PDF viewers instead of "Untitled"
+    pub title: Option<String>,
+    /// Document author
+    pub author: Option<String>,
+    /// Short description of the document
+    ///
+    /// typst-pdf 0.12 has no subject entry, so this is written as the first keyword.
+    pub subject: Option<String>,
+    /// Search keywords
+    pub keywords: Vec<String>,
+}
+
+impl PdfMeta {
+    /// Create metadata with just a title
+    pub fn with_title(title: impl Into<String>) -> Self {
+        Self {
+            title: Some(title.into()),
+            ..Default::default()
+        }
+    }
+}
+
+/// PDF standard an export conforms to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PdfStandard {
+    /// Plain PDF 1.7
+    #[default]
+    Pdf17,
+    /// PDF/A-2b for long-term archiving
+    PdfA2b,
+}
+
+impl PdfStandard {
+    /// Get the display name of the standard (e.g. "PDF/A-2b")
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PdfStandard::Pdf17 => "PDF 1.7",
+            PdfStandard::PdfA2b => "PDF/A-2b",
+        }
+    }
+
+    /// Map to the typst-pdf export options
+    fn options(self) -> Result<typst_pdf::PdfOptions<'static>, String> {
+        let standard = match self {
+            PdfStandard::Pdf17 => typst_pdf::PdfStandard::V_1_7,
+            PdfStandard::PdfA2b => typst_pdf::PdfStandard::A_2b,
+        };
+        let standards = typst_pdf::PdfStandards::new(&[standard])
+            .map_err(|e| format!("{}: {}", self.as_str(), e))?;
+        Ok(typst_pdf::PdfOptions {
+            standards,
+            ..Default::default()
+        })
+    }
+}
+
 /// Generate PDF bytes from Typst source code
-pub fn pdf_bytes_from_source(source: &str) -> Result<Vec<u8>, String> {
-    let world = VirtualWorld::new(source);
+pub fn pdf_bytes_from_source(source: &str, standard: PdfStandard) -> Result<Vec<u8>, String> {
+    pdf_bytes_with_metadata(source, PdfMeta::default(), standard)
+}
 
-    let document = typst::compile(&world)
-        .output
+/// Generate PDF bytes from Typst source code with document metadata
+pub fn pdf_bytes_with_metadata(
+    source: &str,
+    meta: PdfMeta,
+    standard: PdfStandard,
+) -> Result<Vec<u8>, String> {
+    pdf_bytes_from_world(&VirtualWorld::new(source), meta, standard)
+}
+
+/// Generate PDF bytes from a prepared world (e.g. with partials registered)
+///
+/// typst-pdf embeds every font it uses. For PDF/A it additionally rejects
+/// content the standard can't represent, such as text with no font that
+/// covers it; those failures are reported with the standard's name.
+pub fn pdf_bytes_from_world(
+    world: &VirtualWorld,
+    meta: PdfMeta,
+    standard: PdfStandard,
+) -> Result<Vec<u8>, String> {
+    let mut document = world
+        .compile()
         .map_err(|errors| format_errors(errors.iter().map(|e| &e.message), "Error"))?;
 
-    typst_pdf::pdf(&document, &typst_pdf::PdfOptions::default())
-        .map_err(|errors| format_errors(errors.iter().map(|e| &e.message), "PDF Error"))
+    apply_metadata(&mut document.info, meta);
+
+    let prefix = match standard {
+        PdfStandard::Pdf17 => "PDF Error".to_string(),
+        PdfStandard::PdfA2b => format!("{} Error", standard.as_str()),
+    };
+    typst_pdf::pdf(&document, &standard.options()?)
+        .map_err(|errors| format_errors(errors.iter().map(|e| &e.message), &prefix))
+}
+
+/// Copy non-empty metadata fields into the document info
+fn apply_metadata(info: &mut typst::model::DocumentInfo, meta: PdfMeta) {
+    let non_empty = |value: Option<String>| value.filter(|v| !v.trim().is_empty());
+
+    if let Some(title) = non_empty(meta.title) {
+        info.title = Some(title.into());
+    }
+    if let Some(author) = non_empty(meta.author) {
+        info.author = vec![author.into()];
+    }
+
+    let keywords: Vec<_> = non_empty(meta.subject)
+        .into_iter()
+        .chain(meta.keywords)
+        .filter(|k| !k.trim().is_empty())
+        .map(Into::into)
+        .collect();
+    if !keywords.is_empty() {
+        info.keywords = keywords;
+    }
 }
 
 /// Generate a data URL for the PDF
-pub fn pdf_data_url(source: &str) -> Result<String, String> {
+pub fn pdf_data_url(source: &str, standard: PdfStandard) -> Result<String, String> {
+    pdf_data_url_with_metadata(source, PdfMeta::default(), standard)
+}
+
+/// Generate a data URL for the PDF with document metadata
+pub fn pdf_data_url_with_metadata(
+    source: &str,
+    meta: PdfMeta,
+    standard: PdfStandard,
+) -> Result<String, String> {
+    pdf_data_url_from_world(&VirtualWorld::new(source), meta, standard)
+}
+
+/// Generate a data URL for the PDF of a prepared world
+pub fn pdf_data_url_from_world(
+    world: &VirtualWorld,
+    meta: PdfMeta,
+    standard: PdfStandard,
+) -> Result<String, String> {
     use base64::{engine::general_purpose::STANDARD, Engine};
-    let bytes = pdf_bytes_from_source(source)?;
+    let bytes = pdf_bytes_from_world(world, meta, standard)?;
     let base64 = STANDARD.encode(&bytes);
     Ok(format!("data:application/pdf;base64,{base64}"))
 }
+
+/// Title used for HTML exports when the document sets none
+const DEFAULT_HTML_TITLE: &str = "Slick Sheet";
+
+/// Generate a standalone HTML page from Typst source code
+///
+/// The title comes from `#set document(title: ...)` when present.
+pub fn html_from_source(source: &str) -> Result<String, String> {
+    let world = VirtualWorld::new(source);
+
+    let document = world
+        .compile()
+        .map_err(|errors| format_errors(errors.iter().map(|e| &e.message), "Error"))?;
+    let page = document
+        .pages
+        .first()
+        .ok_or_else(|| "Document has no pages".to_string())?;
+
+    let title = document
+        .info
+        .title
+        .as_ref()
+        .map(|title| title.to_string())
+        .unwrap_or_else(|| DEFAULT_HTML_TITLE.to_string());
+
+    Ok(html_from_svg(&page_to_svg(page), &title))
+}
+
+/// Wrap a rendered SVG in a self-contained, responsive HTML page
+///
+/// The SVG is minified like `minify_svg_for_export`, which strips editor
+/// `cmd://` link overlays so the file works outside the studio. Web links
+/// already open in a new tab; `mailto:` and `tel:` links keep `_self`.
+pub fn html_from_svg(svg: &str, title: &str) -> String {
+    let svg = minify_svg_for_export(svg);
+    let title = escape_html(title);
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{title}</title>
+<style>
+  body {{ margin: 0; padding: 1rem; background: #f0f0f0; display: flex; justify-content: center; }}
+  main {{ width: 100%; max-width: 850px; box-shadow: 0 2px 12px rgba(0, 0, 0, 0.15); background: white; }}
+  main svg {{ display: block; width: 100%; height: auto; }}
+</style>
+</head>
+<body>
+<main>
+{svg}
+</main>
+</body>
+</html>
+"#
+    )
+}
+
+/// Rasterize an SVG page to PNG bytes, `scale` times its CSS pixel size
+///
+/// The page is drawn over white, since Typst pages without a fill are
+/// transparent.
+pub fn png_bytes_from_svg(svg: &str, scale: f32) -> Result<Vec<u8>, String> {
+    rasterize(&parse_svg(svg)?, scale)
+}
+
+fn parse_svg(svg: &str) -> Result<resvg::usvg::Tree, String> {
+    resvg::usvg::Tree::from_str(svg, &resvg::usvg::Options::default())
+        .map_err(|e| format!("Failed to parse SVG: {e}"))
+}
+
+fn rasterize(tree: &resvg::usvg::Tree, scale: f32) -> Result<Vec<u8>, String> {
+    use resvg::tiny_skia;
+
+    let size = tree
+        .size()
+        .to_int_size()
+        .scale_by(scale)
+        .ok_or("Empty SVG")?;
+    let mut pixmap =
+        tiny_skia::Pixmap::new(size.width(), size.height()).ok_or("Failed to allocate image")?;
+    pixmap.fill(tiny_skia::Color::WHITE);
+    resvg::render(
+        tree,
+        tiny_skia::Transform::from_scale(scale, scale),
+        &mut pixmap.as_mut(),
+    );
+    pixmap
+        .encode_png()
+        .map_err(|e| format!("Failed to encode PNG: {e}"))
+}
+
+/// Generate PNG bytes of the first page from a prepared world
+pub fn png_bytes_from_world(world: &VirtualWorld, scale: f32) -> Result<Vec<u8>, String> {
+    let svg = world
+        .compile_first_page_svg()
+        .map_err(|errors| errors.join("\n"))?;
+    png_bytes_from_svg(&svg, scale)
+}
+
+/// First-page PNG rendered for an export preset
+#[derive(Debug, Clone)]
+pub struct PresetPng {
+    /// PNG bytes at the preset's pixel width
+    pub bytes: Vec<u8>,
+    /// Set when the content no longer fits on one page at the preset size
+    pub warning: Option<String>,
+}
+
+/// Render the first page of a world laid out with `apply_export_preset`
+///
+/// The PNG is scaled to the preset's pixel width. Content that spills onto
+/// further pages is not exported, so that case comes back with a warning.
+pub fn png_bytes_for_preset(
+    world: &VirtualWorld,
+    preset: ExportPreset,
+) -> Result<PresetPng, String> {
+    let preview = world
+        .compile_preview()
+        .map_err(|errors| errors.join("\n"))?;
+
+    let tree = parse_svg(&preview.svg)?;
+    let (width, _) = preset.pixel_size();
+    let bytes = rasterize(&tree, width as f32 / tree.size().width())?;
+
+    let warning = (preview.page_count > 1).then(|| {
+        format!(
+            "Content overflows the {} page ({} pages); only the first was exported",
+            preset.label(),
+            preview.page_count
+        )
+    });
+    Ok(PresetPng { bytes, warning })
+}
+
+/// Generate PNG bytes of the first page from Typst source code
+pub fn png_bytes_from_source(source: &str, scale: f32) -> Result<Vec<u8>, String> {
+    png_bytes_from_world(&VirtualWorld::new(source), scale)
+}
+
+/// Output written by `write_compiled` for pipeline use
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamFormat {
+    /// First page as SVG text
+    Svg,
+    /// First page as minified SVG text, without editor `cmd://` overlays
+    MinifiedSvg,
+    /// Whole document as raw PDF bytes
+    Pdf(PdfStandard),
+}
+
+/// Read Typst source from a stream such as stdin
+///
+/// A BOM is dropped and invalid UTF-8 is reported like it is for files.
+pub fn read_source(mut reader: impl std::io::Read, name: &str) -> Result<String, String> {
+    let mut bytes = Vec::new();
+    reader
+        .read_to_end(&mut bytes)
+        .map_err(|e| format!("Failed to read {}: {}", name, e))?;
+    crate::world::decode_source(name, &bytes)
+}
+
+/// Compile a world and write the result to a stream such as stdout
+pub fn write_compiled(
+    world: &VirtualWorld,
+    format: StreamFormat,
+    mut writer: impl Write,
+) -> Result<(), String> {
+    let bytes = match format {
+        StreamFormat::Svg => world
+            .compile_first_page_svg()
+            .map_err(|errors| format!("Compilation failed:\n{}", errors.join("\n")))?
+            .into_bytes(),
+        StreamFormat::MinifiedSvg => world
+            .compile_first_page_svg()
+            .map(|svg| minify_svg_for_export(&svg))
+            .map_err(|errors| format!("Compilation failed:\n{}", errors.join("\n")))?
+            .into_bytes(),
+        StreamFormat::Pdf(standard) => pdf_bytes_from_world(world, PdfMeta::default(), standard)
+            .map_err(|e| format!("PDF export failed: {}", e))?,
+    };
+    writer
+        .write_all(&bytes)
+        .and_then(|()| writer.flush())
+        .map_err(|e| format!("Failed to write output: {}", e))
+}
+
+/// File name for a `.typ` export of the named project
+///
+/// Spaces and inner dots are kept, a trailing `.typ` or `.json` is replaced,
+/// and characters that are not allowed in file names become `-`.
+pub fn typst_filename(project_name: &str) -> String {
+    let name = project_name.trim();
+    let lower = name.to_ascii_lowercase();
+    let stem = [".typ", ".json"]
+        .iter()
+        .find(|ext| lower.ends_with(*ext))
+        .map_or(name, |ext| &name[..name.len() - ext.len()])
+        .trim();
+    let stem: String = stem
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '-',
+            c if c.is_control() => '-',
+            c => c,
+        })
+        .collect();
+    if stem.trim_matches('.').is_empty() {
+        "untitled.typ".to_string()
+    } else {
+        format!("{stem}.typ")
+    }
+}
+
+/// Package a project as a zip archive for handoff
+///
+/// The archive holds `main.typ` (the Typst source), `data.json` (the
+/// project's content data, see `Project::content_json`), `project.json` (the full project file), any partials at their
+/// include paths, and each image as `images/<id>.<ext>`.
+pub fn zip_bundle(
+    project: &Project,
+    images: &[(ImageMetadata, Vec<u8>)],
+) -> Result<Vec<u8>, String> {
+    let data_json = project.content_json(false)?;
+    let project_json = project.to_json_pretty()?;
+
+    let mut files: Vec<(String, &[u8])> = vec![
+        ("main.typ".to_string(), project.source.as_bytes()),
+        ("data.json".to_string(), data_json.as_bytes()),
+        ("project.json".to_string(), project_json.as_bytes()),
+    ];
+    for (path, content) in &project.partials {
+        files.push((path.clone(), content.as_bytes()));
+    }
+    for (metadata, bytes) in images {
+        let ext = extension_from_mime_type(&metadata.mime_type);
+        files.push((format!("images/{}.{}", metadata.id, ext), bytes.as_slice()));
+    }
+
+    let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    for (name, content) in files {
+        writer
+            .start_file(name.as_str(), options)
+            .map_err(|e| format!("Failed to add {} to bundle: {}", name, e))?;
+        writer
+            .write_all(content)
+            .map_err(|e| format!("Failed to write {} to bundle: {}", name, e))?;
+    }
+    let cursor = writer
+        .finish()
+        .map_err(|e| format!("Failed to finish bundle: {}", e))?;
+    Ok(cursor.into_inner())
+}
+
+/// Minify a rendered SVG for web embedding
+///
+/// Editor `cmd://` overlays are dropped since nothing outside the studio
+/// handles them; external link overlays are kept.
+pub fn minify_svg_for_export(svg: &str) -> String {
+    let options = MinifyOptions {
+        remove_edit_overlays: true,
+    };
+    minify_svg_with_options(svg, &options)
+}
+
+/// Escape text for use in HTML
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
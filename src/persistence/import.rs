@@ -0,0 +1,110 @@
+//! Import of projects from zip bundles written by `export::zip_bundle`
+
+use std::io::Read;
+
+use crate::images::{
+    detect_mime_type, is_supported_mime_type, mime_type_from_extension, ImageMetadata,
+    MAX_IMAGE_SIZE,
+};
+
+use super::project::Project;
+
+/// An image read from a bundle, with its rebuilt metadata
+pub type BundledImage = (ImageMetadata, Vec<u8>);
+
+/// Folder holding bundled images
+const IMAGES_DIR: &str = "images/";
+
+/// Largest `project.json` or `main.typ` accepted from a bundle
+const MAX_TEXT_SIZE: usize = 10 * 1024 * 1024;
+
+/// Read a project and its images back from a zip bundle
+///
+/// `project.json` supplies metadata and partials and `main.typ` the source.
+/// Image metadata is rebuilt from each `images/<id>.<ext>` entry, detecting
+/// the MIME type from the bytes and falling back to the extension.
+pub fn from_zip_bundle(bytes: &[u8]) -> Result<(Project, Vec<BundledImage>), String> {
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes))
+        .map_err(|e| format!("Not a valid zip bundle: {}", e))?;
+
+    let project_json = read_text(&mut archive, "project.json")?;
+    let mut project = Project::from_json(&project_json)
+        .map_err(|e| format!("Invalid project.json in bundle: {}", e))?;
+    project.source = read_text(&mut archive, "main.typ")?;
+
+    let image_names: Vec<String> = archive
+        .file_names()
+        .filter(|name| name.starts_with(IMAGES_DIR) && !name.ends_with('/'))
+        .map(str::to_string)
+        .collect();
+
+    let mut images = Vec::with_capacity(image_names.len());
+    for name in image_names {
+        let data = read_bytes(&mut archive, &name, MAX_IMAGE_SIZE)?;
+        images.push((image_metadata(&name, &data)?, data));
+    }
+    images.sort_by(|(a, _), (b, _)| a.id.cmp(&b.id));
+
+    Ok((project, images))
+}
+
+/// Rebuild metadata for the bundled image at `path`
+fn image_metadata(path: &str, data: &[u8]) -> Result<ImageMetadata, String> {
+    let filename = &path[IMAGES_DIR.len()..];
+    let (id, extension) = filename
+        .rsplit_once('.')
+        .filter(|(id, _)| !id.is_empty() && !id.contains('/'))
+        .ok_or_else(|| format!("Unexpected image entry in bundle: {}", path))?;
+
+    let mime_type = detect_mime_type(data)
+        .or_else(|| mime_type_from_extension(extension))
+        .filter(|mime_type| is_supported_mime_type(mime_type))
+        .ok_or_else(|| format!("Unsupported image in bundle: {}", path))?;
+
+    Ok(ImageMetadata {
+        id: id.to_string(),
+        filename: filename.to_string(),
+        mime_type: mime_type.to_string(),
+        size: data.len(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        generation_prompt: None,
+        alt_description: None,
+        derived_from: None,
+    })
+}
+
+/// Read a required UTF-8 entry
+fn read_text<R: std::io::Read + std::io::Seek>(
+    archive: &mut zip::ZipArchive<R>,
+    name: &str,
+) -> Result<String, String> {
+    let bytes = read_bytes(archive, name, MAX_TEXT_SIZE)?;
+    crate::world::decode_source(name, &bytes)
+}
+
+/// Read a required entry of at most `limit` bytes
+///
+/// The declared size is checked before anything is decompressed, and the
+/// read stops one byte past the limit in case the header understates it,
+/// so a zip bomb can't exhaust memory.
+fn read_bytes<R: std::io::Read + std::io::Seek>(
+    archive: &mut zip::ZipArchive<R>,
+    name: &str,
+    limit: usize,
+) -> Result<Vec<u8>, String> {
+    let file = archive
+        .by_name(name)
+        .map_err(|_| format!("Bundle is missing {}", name))?;
+    let too_large = |size: u64| format!("{} is too large ({} bytes)", name, size);
+    if file.size() > limit as u64 {
+        return Err(too_large(file.size()));
+    }
+    let mut bytes = Vec::new();
+    file.take(limit as u64 + 1)
+        .read_to_end(&mut bytes)
+        .map_err(|e| format!("Failed to read {} from bundle: {}", name, e))?;
+    if bytes.len() > limit {
+        return Err(too_large(bytes.len() as u64));
+    }
+    Ok(bytes)
+}
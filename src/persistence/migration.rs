@@ -0,0 +1,65 @@
+//! Schema migrations for saved projects
+//!
+//! Each step upgrades the raw project JSON by one schema version, so older
+//! files are reshaped before they are deserialized into `Project`.
+
+use serde_json::Value;
+
+use super::project::Project;
+
+/// Schema version written by this build
+pub const CURRENT_SCHEMA_VERSION: u32 = 2;
+
+/// A migration step from version `N` to `N + 1`
+type MigrationStep = fn(&mut Value);
+
+/// Ordered migration steps; index `i` upgrades version `i + 1` to `i + 2`
+const MIGRATIONS: &[MigrationStep] = &[migrate_v1_to_v2];
+
+/// Upgrade a project to the current schema version
+pub fn migrate(project: Project) -> Project {
+    let Ok(mut value) = serde_json::to_value(&project) else {
+        return project;
+    };
+    migrate_value(&mut value);
+    serde_json::from_value(value).unwrap_or(project)
+}
+
+/// Upgrade raw project JSON in place to the current schema version
+///
+/// Files without `metadata.schema_version` are treated as version 1.
+pub fn migrate_value(value: &mut Value) {
+    let version = value
+        .pointer("/metadata/schema_version")
+        .and_then(Value::as_u64)
+        .map_or(1, |v| v as u32);
+
+    for step in MIGRATIONS.iter().skip(version.saturating_sub(1) as usize) {
+        step(value);
+    }
+
+    if version < CURRENT_SCHEMA_VERSION {
+        if let Some(metadata) = value.get_mut("metadata").and_then(Value::as_object_mut) {
+            metadata.insert(
+                "schema_version".to_string(),
+                Value::from(CURRENT_SCHEMA_VERSION),
+            );
+        }
+    }
+}
+
+/// v1 → v2: rename the legacy `contact.url` field to `contact.website`
+///
+/// The contact lives in the project's `content_data`; projects without
+/// content data are left alone.
+fn migrate_v1_to_v2(value: &mut Value) {
+    let Some(contact) = value
+        .pointer_mut("/content_data/contact")
+        .and_then(Value::as_object_mut)
+    else {
+        return;
+    };
+    if let Some(url) = contact.remove("url") {
+        contact.entry("website").or_insert(url);
+    }
+}
@@ -2,17 +2,61 @@
 //!
 //! This module provides:
 //! - Project save/load with JSON format
-//! - PDF export
+//! - Schema migrations for older project files
+//! - Autosave to localStorage for crash recovery
+//! - PDF, PNG, HTML and zip bundle export
+//! - Zip bundle import
+//! - Recently opened and saved projects, stored in IndexedDB
 //! - File handling utilities
+//! - Batch processing of project files and watch-mode debouncing (native only)
 #![allow(dead_code)]
 
+pub mod autosave;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod batch;
 pub mod export;
+pub mod import;
+pub mod migration;
 pub mod project;
+pub mod recent;
+pub mod recent_store;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod watch;
 
 #[cfg(test)]
 mod tests;
 
+pub use autosave::Autosave;
+#[cfg(not(target_arch = "wasm32"))]
+pub use batch::{expand_glob, run_batch, BatchSummary};
+pub use export::html_from_source;
+pub use export::html_from_svg;
+pub use export::minify_svg_for_export;
 pub use export::pdf_bytes_from_source;
+pub use export::pdf_bytes_from_world;
+pub use export::pdf_bytes_with_metadata;
 pub use export::pdf_data_url;
+pub use export::pdf_data_url_from_world;
+pub use export::pdf_data_url_with_metadata;
+pub use export::png_bytes_for_preset;
+pub use export::png_bytes_from_source;
+pub use export::png_bytes_from_svg;
+pub use export::png_bytes_from_world;
+pub use export::read_source;
+pub use export::typst_filename;
+pub use export::write_compiled;
+pub use export::zip_bundle;
+pub use export::PdfMeta;
+pub use export::PdfStandard;
+pub use export::PresetPng;
+pub use export::StreamFormat;
+pub use import::from_zip_bundle;
+pub use migration::{migrate, CURRENT_SCHEMA_VERSION};
+pub use project::effective_ai_model;
+pub use project::generate_project_id;
 pub use project::Project;
 pub use project::ProjectMetadata;
+pub use recent::{most_recent, RecentProject, RECENT_PROJECTS_CAP, RECENT_PROJECTS_SHOWN};
+pub use recent_store::RecentStore;
+#[cfg(not(target_arch = "wasm32"))]
+pub use watch::Debouncer;
@@ -1,38 +1,60 @@
 //! Project data structure for save/load functionality
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::migration::{migrate_value, CURRENT_SCHEMA_VERSION};
+use crate::data::SlickSheetData;
 
 /// Project metadata
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ProjectMetadata {
+    /// Stable ID that survives renames and re-saves (none in older files)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
     /// Project name
     pub name: String,
     /// Optional description
     pub description: Option<String>,
     /// Project format version
     pub version: String,
+    /// Schema version used for migrations (legacy files default to 1)
+    #[serde(default = "legacy_schema_version")]
+    pub schema_version: u32,
     /// Creation timestamp (ISO 8601)
     pub created_at: Option<String>,
     /// Last modified timestamp (ISO 8601)
     pub modified_at: Option<String>,
+    /// AI model pinned to this project, overriding the global setting
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ai_model: Option<String>,
 }
 
 impl Default for ProjectMetadata {
     fn default() -> Self {
         Self {
+            id: None,
             name: "Untitled Project".to_string(),
             description: None,
             version: "1.0.0".to_string(),
+            schema_version: CURRENT_SCHEMA_VERSION,
             created_at: None,
             modified_at: None,
+            ai_model: None,
         }
     }
 }
 
+/// Schema version assumed for files saved before versioning existed
+fn legacy_schema_version() -> u32 {
+    1
+}
+
 impl ProjectMetadata {
-    /// Create metadata with a specific name and current timestamp
+    /// Create metadata with a specific name, a fresh ID and current timestamp
     fn new_with_name(name: impl Into<String>) -> Self {
         Self {
+            id: Some(generate_project_id()),
             name: name.into(),
             created_at: Some(chrono::Utc::now().to_rfc3339()),
             ..Default::default()
@@ -40,6 +62,22 @@ impl ProjectMetadata {
     }
 }
 
+/// Generate a unique project ID
+pub fn generate_project_id() -> String {
+    let uuid = uuid::Uuid::new_v4().simple().to_string();
+    format!("proj_{}", &uuid[..16])
+}
+
+/// Pick the AI model for a project
+///
+/// A non-blank project override wins; otherwise the global model is used.
+pub fn effective_ai_model<'a>(project_model: Option<&'a str>, global_model: &'a str) -> &'a str {
+    project_model
+        .map(str::trim)
+        .filter(|model| !model.is_empty())
+        .unwrap_or(global_model)
+}
+
 /// A slick sheet project
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Project {
@@ -47,6 +85,15 @@ pub struct Project {
     pub metadata: ProjectMetadata,
     /// Typst source code
     pub source: String,
+    /// Extra Typst files the source can `#include`/`#import`, keyed by path
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub partials: BTreeMap<String, String>,
+    /// JSON content data the source was rendered from, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_data: Option<SlickSheetData>,
+    /// Template the content data is rendered through, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template_source: Option<String>,
 }
 
 impl Default for Project {
@@ -61,6 +108,9 @@ impl Project {
         Self {
             metadata: ProjectMetadata::new_with_name("Untitled Project"),
             source: DEFAULT_SOURCE.to_string(),
+            partials: BTreeMap::new(),
+            content_data: None,
+            template_source: None,
         }
     }
 
@@ -69,6 +119,9 @@ impl Project {
         Self {
             metadata: ProjectMetadata::new_with_name(name),
             source: DEFAULT_SOURCE.to_string(),
+            partials: BTreeMap::new(),
+            content_data: None,
+            template_source: None,
         }
     }
 
@@ -77,9 +130,42 @@ impl Project {
         Self {
             metadata: ProjectMetadata::new_with_name(name),
             source,
+            partials: BTreeMap::new(),
+            content_data: None,
+            template_source: None,
         }
     }
 
+    /// Builder method to set the project partials
+    pub fn with_partials(mut self, partials: BTreeMap<String, String>) -> Self {
+        self.partials = partials;
+        self
+    }
+
+    /// Builder method to store the content data and template behind the source
+    pub fn with_content(mut self, data: SlickSheetData, template_source: String) -> Self {
+        self.content_data = Some(data);
+        self.template_source = Some(template_source);
+        self
+    }
+
+    /// Builder method to set the project ID, keeping it across saves
+    pub fn with_id(mut self, id: impl Into<String>) -> Self {
+        self.metadata.id = Some(id.into());
+        self
+    }
+
+    /// The project ID, generating one for files saved before IDs existed
+    pub fn ensure_id(&mut self) -> &str {
+        self.metadata.id.get_or_insert_with(generate_project_id)
+    }
+
+    /// Builder method to pin (or clear) the project's AI model
+    pub fn with_ai_model(mut self, ai_model: Option<String>) -> Self {
+        self.metadata.ai_model = ai_model;
+        self
+    }
+
     /// Serialize to pretty JSON
     pub fn to_json_pretty(&self) -> Result<String, String> {
         serde_json::to_string_pretty(self).map_err(|e| format!("Serialization failed: {e}"))
@@ -90,9 +176,33 @@ impl Project {
         serde_json::to_string(self).map_err(|e| format!("Serialization failed: {e}"))
     }
 
-    /// Deserialize from JSON
+    /// Format the project's content data as JSON
+    ///
+    /// Stored content data is returned first, then `SlickSheetData` held in
+    /// the source (as written by `write-json`); otherwise the whole project
+    /// payload is.
+    pub fn content_json(&self, compact: bool) -> Result<String, String> {
+        let data = match &self.content_data {
+            Some(data) => Ok(data.clone()),
+            None => serde_json::from_str::<SlickSheetData>(&self.source),
+        };
+        match data {
+            Ok(data) if compact => {
+                serde_json::to_string(&data).map_err(|e| format!("Serialization failed: {e}"))
+            }
+            Ok(data) => serde_json::to_string_pretty(&data)
+                .map_err(|e| format!("Serialization failed: {e}")),
+            Err(_) if compact => self.to_json(),
+            Err(_) => self.to_json_pretty(),
+        }
+    }
+
+    /// Deserialize from JSON, migrating older schema versions
     pub fn from_json(json: &str) -> Result<Self, String> {
-        serde_json::from_str(json).map_err(|e| format!("Deserialization failed: {e}"))
+        let mut value: serde_json::Value =
+            serde_json::from_str(json).map_err(|e| format!("Deserialization failed: {e}"))?;
+        migrate_value(&mut value);
+        serde_json::from_value(value).map_err(|e| format!("Deserialization failed: {e}"))
     }
 
     /// Update the modified timestamp
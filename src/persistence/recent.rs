@@ -0,0 +1,63 @@
+//! Recently opened and saved projects
+//!
+//! Entries are kept in IndexedDB (see `recent_store`) keyed by project ID,
+//! so saving a project again refreshes its entry instead of adding another,
+//! while two projects that share a name (such as "Untitled Project") each
+//! keep their own. The name is only the menu label.
+
+use serde::{Deserialize, Serialize};
+
+use super::project::Project;
+
+/// Number of projects listed in the editor's "Recent" menu
+pub const RECENT_PROJECTS_SHOWN: usize = 8;
+
+/// Maximum number of projects kept in storage; older ones are pruned
+pub const RECENT_PROJECTS_CAP: usize = 20;
+
+/// A project remembered for the "Recent" menu
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RecentProject {
+    /// When the project was last opened or saved (RFC 3339)
+    pub modified_at: String,
+    /// The project as it was opened or saved
+    pub project: Project,
+}
+
+impl RecentProject {
+    /// Create an entry stamped with the current time
+    pub fn new(project: Project) -> Self {
+        Self {
+            modified_at: chrono::Utc::now().to_rfc3339(),
+            project,
+        }
+    }
+
+    /// Storage key for the entry
+    ///
+    /// Entries recorded before projects had IDs were stored under the name,
+    /// which stays their key so they can still be pruned.
+    pub fn key(&self) -> &str {
+        let metadata = &self.project.metadata;
+        metadata.id.as_deref().unwrap_or(&metadata.name)
+    }
+}
+
+/// The `limit` most recently modified entries, newest first
+pub fn most_recent(mut entries: Vec<RecentProject>, limit: usize) -> Vec<RecentProject> {
+    entries.sort_by(|a, b| b.modified_at.cmp(&a.modified_at));
+    entries.truncate(limit);
+    entries
+}
+
+/// Keys of the entries to delete so at most `cap` remain, oldest first
+pub fn keys_to_prune(entries: &[RecentProject], cap: usize) -> Vec<String> {
+    let mut by_age: Vec<&RecentProject> = entries.iter().collect();
+    by_age.sort_by(|a, b| a.modified_at.cmp(&b.modified_at));
+    let excess = entries.len().saturating_sub(cap);
+    by_age
+        .into_iter()
+        .take(excess)
+        .map(|entry| entry.key().to_string())
+        .collect()
+}
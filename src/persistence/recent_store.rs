@@ -0,0 +1,144 @@
+//! IndexedDB-based storage for recent projects
+//!
+//! Mirrors the image store layout: one database with a `projects` object
+//! store holding each `RecentProject` as JSON, keyed by project ID
+//! (see `RecentProject::key`).
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{IdbDatabase, IdbRequest};
+
+use super::recent::{keys_to_prune, most_recent, RecentProject, RECENT_PROJECTS_CAP};
+use crate::images::store::{wait_for_request, wait_for_transaction};
+
+/// Database name for recent project storage
+const DB_NAME: &str = "slick_sheet_projects";
+/// Database version
+const DB_VERSION: u32 = 1;
+/// Object store for recent projects
+const PROJECTS_STORE: &str = "projects";
+
+/// Recent project store backed by IndexedDB
+#[derive(Clone)]
+pub struct RecentStore {
+    db: IdbDatabase,
+}
+
+impl RecentStore {
+    /// Open or create the recent project database
+    pub async fn open() -> Result<Self, String> {
+        let window = web_sys::window().ok_or("No window object")?;
+
+        let indexed_db = window
+            .indexed_db()
+            .map_err(|e| format!("IndexedDB not available: {:?}", e))?
+            .ok_or("IndexedDB is null")?;
+
+        let open_request = indexed_db
+            .open_with_u32(DB_NAME, DB_VERSION)
+            .map_err(|e| format!("Failed to open database: {:?}", e))?;
+
+        // Create the object store on first open
+        let on_upgrade = Closure::once(Box::new(move |event: web_sys::IdbVersionChangeEvent| {
+            let target = event.target().unwrap();
+            let request: IdbRequest = target.unchecked_into();
+            let db: IdbDatabase = request.result().unwrap().unchecked_into();
+
+            if !db.object_store_names().contains(PROJECTS_STORE) {
+                let _ = db.create_object_store(PROJECTS_STORE);
+            }
+        }) as Box<dyn FnOnce(_)>);
+
+        open_request.set_onupgradeneeded(Some(on_upgrade.as_ref().unchecked_ref()));
+        on_upgrade.forget();
+
+        let db = wait_for_request(&open_request)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(Self {
+            db: db.unchecked_into(),
+        })
+    }
+
+    /// Remember a project, replacing any entry with the same name
+    ///
+    /// Entries beyond `RECENT_PROJECTS_CAP` are pruned, oldest first.
+    pub async fn record(&self, entry: &RecentProject) -> Result<(), String> {
+        let json =
+            serde_json::to_string(entry).map_err(|e| format!("Serialization failed: {}", e))?;
+
+        let transaction = self
+            .db
+            .transaction_with_str_and_mode(PROJECTS_STORE, web_sys::IdbTransactionMode::Readwrite)
+            .map_err(|e| format!("Transaction failed: {:?}", e))?;
+        let store = transaction
+            .object_store(PROJECTS_STORE)
+            .map_err(|e| format!("Store access failed: {:?}", e))?;
+        let put = store
+            .put_with_key(&JsValue::from_str(&json), &JsValue::from_str(entry.key()))
+            .map_err(|e| format!("Put failed: {:?}", e))?;
+        wait_for_request(&put).await.map_err(|e| e.to_string())?;
+        wait_for_transaction(&transaction)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        self.prune().await
+    }
+
+    /// List the `limit` most recently opened or saved projects, newest first
+    pub async fn list(&self, limit: usize) -> Result<Vec<RecentProject>, String> {
+        Ok(most_recent(self.all().await?, limit))
+    }
+
+    /// Every stored entry, in no particular order
+    async fn all(&self) -> Result<Vec<RecentProject>, String> {
+        let transaction = self
+            .db
+            .transaction_with_str(PROJECTS_STORE)
+            .map_err(|e| format!("Transaction failed: {:?}", e))?;
+        let store = transaction
+            .object_store(PROJECTS_STORE)
+            .map_err(|e| format!("Store access failed: {:?}", e))?;
+        let request = store
+            .get_all()
+            .map_err(|e| format!("Get all failed: {:?}", e))?;
+
+        let result = wait_for_request(&request)
+            .await
+            .map_err(|e| e.to_string())?;
+        let array: js_sys::Array = result.unchecked_into();
+
+        Ok(array
+            .iter()
+            .filter_map(|item| item.as_string())
+            .filter_map(|json| serde_json::from_str(&json).ok())
+            .collect())
+    }
+
+    /// Delete the oldest entries beyond `RECENT_PROJECTS_CAP`
+    async fn prune(&self) -> Result<(), String> {
+        let stale = keys_to_prune(&self.all().await?, RECENT_PROJECTS_CAP);
+        if stale.is_empty() {
+            return Ok(());
+        }
+
+        let transaction = self
+            .db
+            .transaction_with_str_and_mode(PROJECTS_STORE, web_sys::IdbTransactionMode::Readwrite)
+            .map_err(|e| format!("Transaction failed: {:?}", e))?;
+        let store = transaction
+            .object_store(PROJECTS_STORE)
+            .map_err(|e| format!("Store access failed: {:?}", e))?;
+        for key in stale {
+            let delete = store
+                .delete(&JsValue::from_str(&key))
+                .map_err(|e| format!("Delete failed: {:?}", e))?;
+            wait_for_request(&delete).await.map_err(|e| e.to_string())?;
+        }
+
+        wait_for_transaction(&transaction)
+            .await
+            .map_err(|e| e.to_string())
+    }
+}
@@ -1,7 +1,18 @@
 //! Tests for the persistence module
 
-use super::export::pdf_bytes_from_source;
-use super::project::{Project, ProjectMetadata};
+use super::autosave::Autosave;
+use super::batch::{expand_glob, run_batch};
+use super::export::{
+    html_from_source, html_from_svg, pdf_bytes_from_source, pdf_bytes_with_metadata,
+    png_bytes_for_preset, png_bytes_from_source, png_bytes_from_svg, png_bytes_from_world,
+    read_source, typst_filename, write_compiled, zip_bundle, PdfMeta, PdfStandard, StreamFormat,
+};
+use super::import::from_zip_bundle;
+use super::migration::{migrate, migrate_value, CURRENT_SCHEMA_VERSION};
+use super::project::{effective_ai_model, Project, ProjectMetadata};
+use super::recent::{keys_to_prune, most_recent, RecentProject};
+use super::watch::Debouncer;
+use std::time::{Duration, Instant};
 
 // ============================================================================
 // Project Serialization Tests
@@ -57,13 +68,19 @@ fn test_project_deserializes_from_json() {
 fn test_project_roundtrip() {
     let original = Project {
         metadata: ProjectMetadata {
+            id: Some("proj_roundtrip".to_string()),
             name: "Round Trip Test".to_string(),
             description: Some("Test description".to_string()),
             version: "1.0.0".to_string(),
+            schema_version: CURRENT_SCHEMA_VERSION,
             created_at: Some("2024-01-15T12:00:00Z".to_string()),
             modified_at: Some("2024-01-15T13:00:00Z".to_string()),
+            ai_model: None,
         },
         source: "= Test\n\nContent here".to_string(),
+        partials: Default::default(),
+        content_data: None,
+        template_source: None,
     };
 
     let json = serde_json::to_string(&original).expect("serialize");
@@ -104,6 +121,49 @@ fn test_project_from_invalid_json() {
     assert!(result.is_err());
 }
 
+#[test]
+fn test_project_content_json_formats_structured_data() {
+    let project = Project::from_source(
+        "Data",
+        r#"{ "title": "Widget",   "body": "Fast." }"#.to_string(),
+    );
+
+    let compact = project.content_json(true).unwrap();
+    assert!(!compact.contains('\n'));
+    assert!(compact.contains(r#""title":"Widget""#));
+
+    let pretty = project.content_json(false).unwrap();
+    assert!(pretty.contains('\n'));
+    assert!(pretty.contains(r#""title": "Widget""#));
+}
+
+#[test]
+fn test_project_content_json_falls_back_to_project_payload() {
+    let project = Project::from_source("Typst", "= Title\nBody".to_string());
+
+    let compact = project.content_json(true).unwrap();
+    assert!(!compact.contains('\n'));
+    assert!(compact.contains(r#""name":"Typst""#));
+
+    let pretty = project.content_json(false).unwrap();
+    assert!(pretty.contains('\n'));
+}
+
+#[test]
+fn test_project_content_survives_roundtrip() {
+    let project = Project::from_source("Sheet", "= Widget".to_string()).with_content(
+        crate::data::SlickSheetData::new("Widget"),
+        "= {{title}}".to_string(),
+    );
+
+    let restored = Project::from_json(&project.to_json().unwrap()).unwrap();
+    assert_eq!(restored, project);
+    assert!(restored
+        .content_json(true)
+        .unwrap()
+        .contains(r#""title":"Widget""#));
+}
+
 // ============================================================================
 // Project Metadata Tests
 // ============================================================================
@@ -127,6 +187,190 @@ fn test_metadata_custom_name() {
     assert_eq!(metadata.name, "Custom Name");
 }
 
+#[test]
+fn test_project_ai_model_roundtrip() {
+    let project = Project::with_name("Client Sheet")
+        .with_ai_model(Some("anthropic/claude-3.5-sonnet".to_string()));
+
+    let restored = Project::from_json(&project.to_json().unwrap()).unwrap();
+    assert_eq!(
+        restored.metadata.ai_model.as_deref(),
+        Some("anthropic/claude-3.5-sonnet")
+    );
+
+    // Projects without an override don't write the field at all
+    let json = Project::with_name("Draft").to_json().unwrap();
+    assert!(!json.contains("ai_model"));
+    assert_eq!(Project::from_json(&json).unwrap().metadata.ai_model, None);
+}
+
+#[test]
+fn test_effective_ai_model_prefers_project_override() {
+    let global = "google/gemini-3-flash-preview";
+
+    assert_eq!(
+        effective_ai_model(Some("anthropic/claude-3.5-sonnet"), global),
+        "anthropic/claude-3.5-sonnet"
+    );
+    assert_eq!(effective_ai_model(None, global), global);
+    assert_eq!(effective_ai_model(Some("  "), global), global);
+}
+
+// ============================================================================
+// Schema Migration Tests
+// ============================================================================
+
+const V1_PROJECT: &str = r#"{
+    "metadata": {
+        "name": "Legacy Project",
+        "description": null,
+        "version": "1.0.0",
+        "created_at": "2024-01-15T12:00:00Z",
+        "modified_at": null
+    },
+    "source": "= Legacy",
+    "content_data": {
+        "title": "Legacy",
+        "contact": { "email": "hi@example.com", "url": "https://example.com" }
+    },
+    "template_source": "= {{title}}"
+}"#;
+
+#[test]
+fn test_new_project_uses_current_schema_version() {
+    let project = Project::new();
+    assert_eq!(project.metadata.schema_version, CURRENT_SCHEMA_VERSION);
+}
+
+#[test]
+fn test_from_json_migrates_v1_project() {
+    let project = Project::from_json(V1_PROJECT).expect("should load legacy project");
+
+    assert_eq!(project.metadata.schema_version, CURRENT_SCHEMA_VERSION);
+    assert_eq!(project.metadata.name, "Legacy Project");
+    assert_eq!(project.source, "= Legacy");
+    let contact = project.content_data.expect("content data").contact.unwrap();
+    assert_eq!(contact.website.as_deref(), Some("https://example.com"));
+    assert_eq!(contact.email.as_deref(), Some("hi@example.com"));
+}
+
+#[test]
+fn test_from_json_migrates_saved_v1_project() {
+    // Save a real project, then turn it back into its v1 shape
+    let mut data = crate::data::SlickSheetData::new("Saved");
+    data.contact = Some(crate::data::ContactInfo {
+        website: Some("https://example.com".to_string()),
+        ..Default::default()
+    });
+    let project = Project::with_name("Saved").with_content(data, "= {{title}}".to_string());
+    let mut value: serde_json::Value = serde_json::from_str(&project.to_json().unwrap()).unwrap();
+    value["metadata"]
+        .as_object_mut()
+        .unwrap()
+        .remove("schema_version");
+    let contact = value["content_data"]["contact"].as_object_mut().unwrap();
+    let website = contact.remove("website").unwrap();
+    contact.insert("url".to_string(), website);
+
+    let migrated = Project::from_json(&value.to_string()).expect("should load saved v1 project");
+
+    assert_eq!(migrated, project);
+}
+
+#[test]
+fn test_migrate_value_renames_contact_url() {
+    let mut value: serde_json::Value = serde_json::from_str(V1_PROJECT).unwrap();
+    migrate_value(&mut value);
+
+    let contact = &value["content_data"]["contact"];
+    assert_eq!(contact["website"], "https://example.com");
+    assert!(contact.get("url").is_none());
+    assert_eq!(contact["email"], "hi@example.com");
+    assert_eq!(value["metadata"]["schema_version"], CURRENT_SCHEMA_VERSION);
+}
+
+#[test]
+fn test_migrate_value_skips_current_version() {
+    let mut value = serde_json::json!({
+        "metadata": { "name": "Current", "version": "1.0.0", "schema_version": CURRENT_SCHEMA_VERSION },
+        "source": "",
+        "content_data": { "contact": { "url": "kept" } }
+    });
+    migrate_value(&mut value);
+
+    assert_eq!(value["content_data"]["contact"]["url"], "kept");
+}
+
+#[test]
+fn test_migrate_project_is_idempotent() {
+    let project = Project::with_name("Current");
+    assert_eq!(migrate(project.clone()), project);
+}
+
+// ============================================================================
+// Autosave Tests
+// ============================================================================
+
+fn autosave_at(saved_at: &str) -> Autosave {
+    Autosave {
+        saved_at: saved_at.to_string(),
+        ..Autosave::new(
+            Project::from_source("Draft", "= Draft".to_string()),
+            crate::data::SlickSheetData::new("Draft"),
+            "= {{title}}".to_string(),
+        )
+    }
+}
+
+#[test]
+fn test_autosave_roundtrip() {
+    let autosave = Autosave::new(
+        Project::from_source("Draft", "= Draft".to_string()),
+        crate::data::SlickSheetData::new("Draft").with_body("Unsaved"),
+        "= {{title}}".to_string(),
+    );
+
+    let json = autosave.to_json().expect("serialize");
+    let restored = Autosave::from_json(&json).expect("deserialize");
+
+    assert_eq!(restored, autosave);
+    assert_eq!(restored.project.metadata.name, "Draft");
+    assert_eq!(restored.content_data.unwrap().body, "Unsaved");
+}
+
+#[test]
+fn test_autosave_without_editor_state_deserializes() {
+    let json = r#"{
+        "saved_at": "2024-01-15T12:00:00Z",
+        "project": { "metadata": { "name": "Old", "description": null, "version": "1.0.0",
+            "created_at": null, "modified_at": null }, "source": "= Old" }
+    }"#;
+
+    let autosave = Autosave::from_json(json).expect("deserialize");
+    assert!(autosave.content_data.is_none());
+    assert_eq!(
+        autosave.project.metadata.schema_version,
+        CURRENT_SCHEMA_VERSION
+    );
+}
+
+#[test]
+fn test_autosave_newer_than_last_save() {
+    let autosave = autosave_at("2024-01-15T12:00:00+00:00");
+
+    assert!(autosave.is_newer_than(None));
+    assert!(autosave.is_newer_than(Some("2024-01-15T11:59:59Z")));
+    assert!(!autosave.is_newer_than(Some("2024-01-15T12:00:00Z")));
+    assert!(!autosave.is_newer_than(Some("2024-01-15T13:00:00+01:00")));
+    assert!(!autosave.is_newer_than(Some("2024-01-16T08:00:00Z")));
+    assert!(autosave.is_newer_than(Some("not a date")));
+}
+
+#[test]
+fn test_autosave_with_bad_timestamp_is_never_newer() {
+    assert!(!autosave_at("yesterday").is_newer_than(None));
+}
+
 // ============================================================================
 // PDF Export Tests
 // ============================================================================
@@ -138,7 +382,7 @@ fn test_pdf_export_from_source() {
 
 This is a test document."#;
 
-    let result = pdf_bytes_from_source(source);
+    let result = pdf_bytes_from_source(source, PdfStandard::default());
 
     assert!(result.is_ok(), "PDF export failed: {:?}", result.err());
 
@@ -156,12 +400,58 @@ This is a test document."#;
 fn test_pdf_export_with_invalid_source() {
     let source = "= Title\n\n#invalid_function()";
 
-    let result = pdf_bytes_from_source(source);
+    let result = pdf_bytes_from_source(source, PdfStandard::default());
 
     // Should return an error for invalid Typst
     assert!(result.is_err());
 }
 
+/// Check whether a byte sequence occurs in the PDF output
+fn pdf_contains(pdf: &[u8], needle: &str) -> bool {
+    pdf.windows(needle.len()).any(|w| w == needle.as_bytes())
+}
+
+#[test]
+fn test_pdf_export_embeds_metadata() {
+    let meta = PdfMeta {
+        title: Some("Quarterly Launch Sheet".to_string()),
+        author: Some("Acme Marketing".to_string()),
+        subject: None,
+        keywords: vec!["launch".to_string()],
+    };
+
+    let pdf_bytes = pdf_bytes_with_metadata("= Hello", meta, PdfStandard::default()).unwrap();
+
+    assert!(pdf_contains(&pdf_bytes, "Quarterly Launch Sheet"));
+    assert!(pdf_contains(&pdf_bytes, "Acme Marketing"));
+    assert!(pdf_contains(&pdf_bytes, "launch"));
+}
+
+#[test]
+fn test_pdf_export_without_metadata_keeps_source_title() {
+    let source = "#set document(title: \"From Source\")\n= Hello";
+
+    let pdf_bytes =
+        pdf_bytes_with_metadata(source, PdfMeta::default(), PdfStandard::default()).unwrap();
+
+    assert!(pdf_contains(&pdf_bytes, "From Source"));
+}
+
+#[test]
+fn test_pdf_export_with_pdfa_includes_conformance_metadata() {
+    let pdf_bytes = pdf_bytes_from_source("= Archived Sheet", PdfStandard::PdfA2b).unwrap();
+
+    assert!(pdf_contains(&pdf_bytes, "pdfaid:part"));
+    assert!(pdf_contains(&pdf_bytes, "OutputIntent"));
+}
+
+#[test]
+fn test_pdf_export_default_standard_is_not_pdfa() {
+    let pdf_bytes = pdf_bytes_from_source("= Plain Sheet", PdfStandard::default()).unwrap();
+
+    assert!(!pdf_contains(&pdf_bytes, "pdfaid:part"));
+}
+
 #[test]
 fn test_pdf_export_with_complex_document() {
     let source = r##"#set page(width: 8.5in, height: 11in, margin: 0.75in)
@@ -180,9 +470,549 @@ Lorem ipsum dolor sit amet.
 )
 "##;
 
-    let result = pdf_bytes_from_source(source);
+    let result = pdf_bytes_from_source(source, PdfStandard::default());
 
     assert!(result.is_ok());
     let pdf_bytes = result.unwrap();
     assert!(pdf_bytes.len() > 1000); // Complex doc should produce larger PDF
 }
+
+// ============================================================================
+// HTML Export Tests
+// ============================================================================
+
+#[test]
+fn test_html_export_contains_svg_and_title() {
+    let source = r#"#set document(title: "Product Launch")
+= Hello World"#;
+
+    let html = html_from_source(source).expect("should export HTML");
+
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<svg"));
+    assert!(html.contains("<title>Product Launch</title>"));
+}
+
+#[test]
+fn test_html_export_strips_cmd_links() {
+    let source = r#"#link("cmd://edit/title")[Title]
+#link("https://example.com")[Site]"#;
+
+    let html = html_from_source(source).expect("should export HTML");
+
+    assert!(!html.contains("cmd://"));
+    assert!(html.contains(r#"href="https://example.com""#));
+    assert!(html.contains(r#"target="_blank""#));
+    assert!(html.contains("<title>Slick Sheet</title>"));
+}
+
+#[test]
+fn test_html_export_opens_only_web_links_in_new_tab() {
+    let source = r#"#link("mailto:sales@example.com")[Mail]
+#link("tel:+15550100")[Call]
+#link("https://example.com")[Site]"#;
+
+    let html = html_from_source(source).expect("should export HTML");
+
+    assert!(html.contains(r#"href="mailto:sales@example.com" target="_self""#));
+    assert!(html.contains(r#"href="tel:+15550100" target="_self""#));
+    assert!(html.contains(r#"href="https://example.com" target="_blank" rel="noopener""#));
+}
+
+#[test]
+fn test_html_from_svg_escapes_title() {
+    let html = html_from_svg("<svg></svg>", "Q&A <draft>");
+    assert!(html.contains("<title>Q&amp;A &lt;draft&gt;</title>"));
+}
+
+#[test]
+fn test_html_export_invalid_source_errors() {
+    assert!(html_from_source("#invalid_function()").is_err());
+}
+
+#[test]
+fn test_png_export_scales_first_page() {
+    let source = "#set page(width: 100pt, height: 50pt)\nHi";
+    let size = |scale| {
+        let png = png_bytes_from_source(source, scale).unwrap();
+        assert!(png.starts_with(b"\x89PNG"));
+        let decoded = image::load_from_memory(&png).unwrap();
+        (decoded.width(), decoded.height())
+    };
+    let (width, height) = size(1.0);
+    assert!(width > height);
+    assert_eq!(size(2.0), (width * 2, height * 2));
+}
+
+#[test]
+fn test_png_export_keeps_embedded_raster_images() {
+    use crate::world::VirtualWorld;
+
+    let mut photo = Vec::new();
+    image::RgbImage::from_pixel(8, 8, image::Rgb([200, 0, 0]))
+        .write_to(
+            &mut std::io::Cursor::new(&mut photo),
+            image::ImageFormat::Png,
+        )
+        .unwrap();
+    let mut world = VirtualWorld::new(
+        "#set page(width: 40pt, height: 40pt, margin: 0pt)\n#image(\"photo.png\", width: 100%, height: 100%)",
+    );
+    world.add_file("photo.png", photo);
+
+    let png = png_bytes_from_world(&world, 1.0).unwrap();
+    let decoded = image::load_from_memory(&png).unwrap().to_rgb8();
+    let center = decoded.get_pixel(decoded.width() / 2, decoded.height() / 2);
+    assert_ne!(center.0, [255, 255, 255], "embedded image was dropped");
+}
+
+#[test]
+fn test_square_preset_relayouts_page() {
+    use crate::template::{apply_export_preset, ExportPreset};
+    use crate::world::VirtualWorld;
+
+    let template =
+        "#set page(width: 8.5in, height: 11in, margin: 0.5in, fill: white)\n= Product\nDetails";
+    let source = apply_export_preset(template, ExportPreset::InstagramSquare);
+    let world = VirtualWorld::new(&source);
+
+    let document = world.compile().unwrap();
+    let size = document.pages[0].frame.size();
+    assert_eq!(size.x.to_pt(), 810.0);
+    assert_eq!(size.y.to_pt(), 810.0);
+
+    let export = png_bytes_for_preset(&world, ExportPreset::InstagramSquare).unwrap();
+    let decoded = image::load_from_memory(&export.bytes).unwrap();
+    assert_eq!((decoded.width(), decoded.height()), (1080, 1080));
+    assert!(export.warning.is_none());
+}
+
+#[test]
+fn test_preset_export_warns_on_overflow() {
+    use crate::template::{apply_export_preset, ExportPreset};
+    use crate::world::VirtualWorld;
+
+    let long = format!("#set page(margin: 1in)\n{}", "Line of copy\n\n".repeat(80));
+    let world = VirtualWorld::new(&apply_export_preset(&long, ExportPreset::InstagramSquare));
+
+    let export = png_bytes_for_preset(&world, ExportPreset::InstagramSquare).unwrap();
+    assert!(export.warning.unwrap().contains("overflows"));
+}
+
+#[test]
+fn test_stream_compile_writes_svg() {
+    use crate::world::VirtualWorld;
+
+    let input = std::io::Cursor::new(b"\xEF\xBB\xBF= Piped\nHello".to_vec());
+    let source = read_source(input, "stdin").unwrap();
+    assert_eq!(source, "= Piped\nHello");
+
+    let mut output = Vec::new();
+    write_compiled(&VirtualWorld::new(&source), StreamFormat::Svg, &mut output).unwrap();
+    let svg = String::from_utf8(output).unwrap();
+    assert!(svg.starts_with("<svg"));
+    assert!(svg.trim_end().ends_with("</svg>"));
+}
+
+#[test]
+fn test_stream_compile_writes_minified_svg_without_edit_links() {
+    use crate::world::VirtualWorld;
+
+    let world = VirtualWorld::new(
+        "#link(\"https://example.com\")[Site]\n#link(\"cmd://edit/title\")[Edit]",
+    );
+    let mut plain = Vec::new();
+    write_compiled(&world, StreamFormat::Svg, &mut plain).unwrap();
+    let mut output = Vec::new();
+    write_compiled(&world, StreamFormat::MinifiedSvg, &mut output).unwrap();
+    let svg = String::from_utf8(output).unwrap();
+
+    assert!(svg.len() < plain.len());
+    assert!(svg.starts_with("<svg"));
+    assert!(svg.contains(r#"<a href="https://example.com""#));
+    assert!(!svg.contains("cmd://"));
+}
+
+#[test]
+fn test_stream_compile_writes_pdf_and_reports_errors() {
+    use crate::world::VirtualWorld;
+
+    let mut output = Vec::new();
+    let world = VirtualWorld::new("= Piped");
+    write_compiled(&world, StreamFormat::Pdf(PdfStandard::Pdf17), &mut output).unwrap();
+    assert!(output.starts_with(b"%PDF-"));
+
+    let mut output = Vec::new();
+    let broken = VirtualWorld::new("#invalid_function()");
+    assert!(write_compiled(&broken, StreamFormat::Svg, &mut output).is_err());
+    assert!(output.is_empty());
+
+    let invalid = std::io::Cursor::new(vec![b'=', b' ', 0xFF]);
+    assert!(read_source(invalid, "stdin").unwrap_err().contains("stdin"));
+}
+
+#[test]
+fn test_png_export_errors() {
+    assert!(png_bytes_from_source("#invalid_function()", 1.0).is_err());
+    assert!(png_bytes_from_svg("not svg", 1.0).is_err());
+}
+
+#[test]
+fn test_typst_filename_from_project_name() {
+    assert_eq!(typst_filename("Spring Launch"), "Spring Launch.typ");
+    assert_eq!(
+        typst_filename("  v1.2 release notes "),
+        "v1.2 release notes.typ"
+    );
+    assert_eq!(typst_filename("flyer.json"), "flyer.typ");
+    assert_eq!(typst_filename("flyer.TYP"), "flyer.typ");
+    assert_eq!(typst_filename("Q1/Q2: plan"), "Q1-Q2- plan.typ");
+    assert_eq!(typst_filename(""), "untitled.typ");
+    assert_eq!(typst_filename(".."), "untitled.typ");
+}
+
+#[test]
+fn test_project_partials_roundtrip() {
+    let mut partials = std::collections::BTreeMap::new();
+    partials.insert("partials/header.typ".to_string(), "= Header".to_string());
+    let original = Project::from_source("Partials", "#include \"partials/header.typ\"".into())
+        .with_partials(partials);
+
+    let json = original.to_json().expect("serialize");
+    let loaded = Project::from_json(&json).expect("deserialize");
+    assert_eq!(loaded.partials, original.partials);
+
+    // Projects without partials omit the field and still load
+    let json = Project::with_name("Plain").to_json().expect("serialize");
+    assert!(!json.contains("partials"));
+    assert!(Project::from_json(&json).unwrap().partials.is_empty());
+}
+
+// ============================================================================
+// Bundle Export Tests
+// ============================================================================
+
+#[test]
+fn test_zip_bundle_contains_source_data_and_images() {
+    use crate::data::SlickSheetData;
+    use crate::images::ImageMetadata;
+    use std::io::Read;
+
+    let project = Project::from_source("Launch", "#image(\"img_logo.png\")".to_string())
+        .with_content(SlickSheetData::new("Launch Sheet"), String::new());
+    let image = ImageMetadata {
+        id: "img_logo".to_string(),
+        filename: "logo.png".to_string(),
+        mime_type: "image/png".to_string(),
+        size: 4,
+        created_at: "2024-01-01T00:00:00Z".to_string(),
+        generation_prompt: None,
+        alt_description: None,
+        derived_from: None,
+    };
+
+    let bytes = zip_bundle(&project, &[(image, vec![0x89, b'P', b'N', b'G'])])
+        .expect("bundle should build");
+
+    let mut archive =
+        zip::ZipArchive::new(std::io::Cursor::new(bytes)).expect("bundle should re-open");
+    let mut names: Vec<&str> = archive.file_names().collect();
+    names.sort();
+    assert_eq!(
+        names,
+        vec![
+            "data.json",
+            "images/img_logo.png",
+            "main.typ",
+            "project.json"
+        ]
+    );
+
+    let mut source = String::new();
+    archive
+        .by_name("main.typ")
+        .unwrap()
+        .read_to_string(&mut source)
+        .unwrap();
+    assert_eq!(source, project.source);
+
+    let mut data_json = String::new();
+    archive
+        .by_name("data.json")
+        .unwrap()
+        .read_to_string(&mut data_json)
+        .unwrap();
+    let data: SlickSheetData = serde_json::from_str(&data_json).unwrap();
+    assert_eq!(data.title, "Launch Sheet");
+
+    let mut image_bytes = Vec::new();
+    archive
+        .by_name("images/img_logo.png")
+        .unwrap()
+        .read_to_end(&mut image_bytes)
+        .unwrap();
+    assert_eq!(image_bytes, vec![0x89, b'P', b'N', b'G']);
+}
+
+#[test]
+fn test_zip_bundle_round_trip() {
+    use crate::images::ImageMetadata;
+
+    let png = vec![
+        0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A, 0x00, 0x00, 0x00, 0x0D,
+    ];
+    let mut partials = std::collections::BTreeMap::new();
+    partials.insert("header.typ".to_string(), "= Header".to_string());
+    let project = Project::from_source("Launch", "#image(\"img_hero.png\")".to_string())
+        .with_partials(partials);
+    let image = ImageMetadata {
+        id: "img_hero".to_string(),
+        filename: "hero.png".to_string(),
+        mime_type: "image/png".to_string(),
+        size: png.len(),
+        created_at: "2024-01-01T00:00:00Z".to_string(),
+        generation_prompt: None,
+        alt_description: None,
+        derived_from: None,
+    };
+
+    let bundle = zip_bundle(&project, &[(image, png.clone())]).expect("bundle should build");
+    let (imported, images) = from_zip_bundle(&bundle).expect("bundle should import");
+
+    assert_eq!(imported.metadata.name, "Launch");
+    assert_eq!(imported.source, project.source);
+    assert_eq!(imported.partials, project.partials);
+    assert_eq!(images.len(), 1);
+    let (metadata, bytes) = &images[0];
+    assert_eq!(metadata.id, "img_hero");
+    assert_eq!(metadata.mime_type, "image/png");
+    assert_eq!(metadata.size, png.len());
+    assert_eq!(bytes, &png);
+}
+
+#[test]
+fn test_from_zip_bundle_reports_missing_entries() {
+    use std::io::Write;
+
+    let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    writer
+        .start_file("main.typ", zip::write::SimpleFileOptions::default())
+        .unwrap();
+    writer.write_all(b"= Hello").unwrap();
+    let bytes = writer.finish().unwrap().into_inner();
+
+    let err = from_zip_bundle(&bytes).unwrap_err();
+    assert!(err.contains("missing project.json"), "{}", err);
+    assert!(from_zip_bundle(b"not a zip").is_err());
+}
+
+#[test]
+fn test_from_zip_bundle_rejects_oversized_entries() {
+    use std::io::Write;
+
+    // Zeros deflate to a tiny entry that would expand past the image limit
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    writer.start_file("project.json", options).unwrap();
+    writer
+        .write_all(Project::with_name("Bomb").to_json().unwrap().as_bytes())
+        .unwrap();
+    writer.start_file("main.typ", options).unwrap();
+    writer.write_all(b"= Hello").unwrap();
+    writer.start_file("images/img_bomb.png", options).unwrap();
+    writer
+        .write_all(&vec![0; crate::images::MAX_IMAGE_SIZE + 1])
+        .unwrap();
+    let bytes = writer.finish().unwrap().into_inner();
+
+    assert!(bytes.len() < 100_000);
+    let err = from_zip_bundle(&bytes).unwrap_err();
+    assert!(err.contains("images/img_bomb.png is too large"), "{}", err);
+}
+
+// ============================================================================
+// Batch Processing Tests
+// ============================================================================
+
+/// Glob matching the two project fixtures
+fn project_fixture_glob() -> String {
+    format!(
+        "{}/tests/fixtures/projects/*.json",
+        env!("CARGO_MANIFEST_DIR")
+    )
+}
+
+/// Fresh, empty output directory for a test
+fn batch_output_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("slick_batch_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn test_expand_glob_matches_project_fixtures() {
+    let files = expand_glob(&project_fixture_glob()).unwrap();
+
+    let names: Vec<_> = files
+        .iter()
+        .map(|f| f.file_name().unwrap().to_string_lossy().into_owned())
+        .collect();
+    assert_eq!(names, vec!["launch.json", "pricing.json"]);
+}
+
+#[test]
+fn test_run_batch_processes_every_project() {
+    let files = expand_glob(&project_fixture_glob()).unwrap();
+    let output_dir = batch_output_dir("all");
+
+    // Stand-in for the agent: append a footer to each source
+    let summary = run_batch(&files, &output_dir, |_, mut project| {
+        project.source.push_str("\n\nGDPR compliant.");
+        Ok(project)
+    });
+
+    assert_eq!(summary.succeeded.len(), 2);
+    assert!(summary.failed.is_empty());
+    for name in ["launch.json", "pricing.json"] {
+        let written = std::fs::read_to_string(output_dir.join(name)).unwrap();
+        let project = Project::from_json(&written).unwrap();
+        assert!(project.source.ends_with("GDPR compliant."));
+    }
+    let _ = std::fs::remove_dir_all(&output_dir);
+}
+
+#[test]
+fn test_run_batch_continues_past_failures() {
+    let files = expand_glob(&project_fixture_glob()).unwrap();
+    let output_dir = batch_output_dir("partial");
+
+    let summary = run_batch(&files, &output_dir, |path, project| {
+        if path.ends_with("launch.json") {
+            Err("Agent did not succeed within max iterations".to_string())
+        } else {
+            Ok(project)
+        }
+    });
+
+    assert_eq!(summary.total(), 2);
+    assert_eq!(summary.succeeded.len(), 1);
+    assert_eq!(summary.failed.len(), 1);
+    assert!(summary.failed[0].0.ends_with("launch.json"));
+    assert!(summary
+        .to_string()
+        .starts_with("1 of 2 project(s) succeeded, 1 failed"));
+    assert!(output_dir.join("pricing.json").exists());
+    assert!(!output_dir.join("launch.json").exists());
+    let _ = std::fs::remove_dir_all(&output_dir);
+}
+
+// ============================================================================
+// Watch Debounce Tests
+// ============================================================================
+
+#[test]
+fn test_debouncer_waits_for_quiet_period() {
+    let start = Instant::now();
+    let mut debouncer = Debouncer::new(Duration::from_millis(100));
+
+    assert!(!debouncer.poll(start));
+    debouncer.record(start);
+    assert!(!debouncer.poll(start + Duration::from_millis(50)));
+    assert!(debouncer.poll(start + Duration::from_millis(100)));
+    // Fires once per burst
+    assert!(!debouncer.poll(start + Duration::from_millis(200)));
+}
+
+#[test]
+fn test_debouncer_coalesces_rapid_events() {
+    let start = Instant::now();
+    let mut debouncer = Debouncer::new(Duration::from_millis(100));
+
+    // A save that emits three events 40ms apart
+    for i in 0..3 {
+        debouncer.record(start + Duration::from_millis(40 * i));
+    }
+
+    // Measured from the last event, not the first
+    assert!(!debouncer.poll(start + Duration::from_millis(120)));
+    assert_eq!(
+        debouncer.time_until_ready(start + Duration::from_millis(120)),
+        Some(Duration::from_millis(60))
+    );
+    assert!(debouncer.poll(start + Duration::from_millis(180)));
+    assert!(!debouncer.is_pending());
+}
+
+// ============================================================================
+// Recent Project Tests
+// ============================================================================
+
+/// A recent entry named `name` (and with ID `name`), modified on day `day`
+/// of January 2025
+fn recent_entry(name: &str, day: u32) -> RecentProject {
+    RecentProject {
+        modified_at: format!("2025-01-{:02}T12:00:00+00:00", day),
+        project: Project::from_source(name, "= Sheet".to_string()).with_id(name),
+    }
+}
+
+#[test]
+fn test_most_recent_orders_newest_first_and_caps() {
+    let entries = vec![
+        recent_entry("old", 1),
+        recent_entry("newest", 9),
+        recent_entry("middle", 5),
+        recent_entry("older", 3),
+    ];
+
+    let names: Vec<_> = most_recent(entries, 3)
+        .iter()
+        .map(|entry| entry.project.metadata.name.clone())
+        .collect();
+
+    assert_eq!(names, vec!["newest", "middle", "older"]);
+}
+
+#[test]
+fn test_most_recent_with_fewer_entries_than_limit() {
+    let recent = most_recent(vec![recent_entry("only", 2)], 8);
+    assert_eq!(recent.len(), 1);
+    assert!(most_recent(Vec::new(), 8).is_empty());
+}
+
+#[test]
+fn test_keys_to_prune_drops_oldest_beyond_cap() {
+    let entries = vec![
+        recent_entry("b", 2),
+        recent_entry("d", 4),
+        recent_entry("a", 1),
+        recent_entry("c", 3),
+    ];
+
+    assert_eq!(keys_to_prune(&entries, 2), vec!["a", "b"]);
+    assert!(keys_to_prune(&entries, 4).is_empty());
+    assert!(keys_to_prune(&entries, 10).is_empty());
+}
+
+#[test]
+fn test_recent_key_tells_same_named_projects_apart() {
+    let first = RecentProject::new(Project::new());
+    let second = RecentProject::new(Project::new());
+
+    assert_eq!(first.project.metadata.name, second.project.metadata.name);
+    assert_ne!(first.key(), second.key());
+    assert!(first.key().starts_with("proj_"));
+}
+
+#[test]
+fn test_recent_key_falls_back_to_name_for_legacy_projects() {
+    let json = r#"{"metadata": {"name": "Legacy", "description": null, "version": "1.0.0", "created_at": null, "modified_at": null}, "source": "= Old"}"#;
+    let mut project = Project::from_json(json).unwrap();
+    assert_eq!(RecentProject::new(project.clone()).key(), "Legacy");
+
+    let id = project.ensure_id().to_string();
+    assert!(id.starts_with("proj_"));
+    assert_eq!(project.ensure_id(), id);
+}
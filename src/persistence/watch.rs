@@ -0,0 +1,59 @@
+//! Coalescing of file-change events for watch mode
+//!
+//! Editors often emit several events per save (truncate, write, rename), so
+//! the CLI waits for a short quiet period before rebuilding.
+
+use std::time::{Duration, Instant};
+
+/// Default quiet period before a rebuild
+pub const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Collapses bursts of change events into a single rebuild
+#[derive(Debug, Clone)]
+pub struct Debouncer {
+    delay: Duration,
+    last_event: Option<Instant>,
+}
+
+impl Debouncer {
+    /// Create a debouncer that fires once `delay` has passed without events
+    pub fn new(delay: Duration) -> Self {
+        Self {
+            delay,
+            last_event: None,
+        }
+    }
+
+    /// Record a change event, restarting the quiet period
+    pub fn record(&mut self, now: Instant) {
+        self.last_event = Some(now);
+    }
+
+    /// Whether a change is waiting for its quiet period to end
+    pub fn is_pending(&self) -> bool {
+        self.last_event.is_some()
+    }
+
+    /// Time left until the pending change is ready, if any
+    pub fn time_until_ready(&self, now: Instant) -> Option<Duration> {
+        self.last_event
+            .map(|last| (last + self.delay).saturating_duration_since(now))
+    }
+
+    /// Return true (once) when the quiet period after the last event is over
+    pub fn poll(&mut self, now: Instant) -> bool {
+        match self.time_until_ready(now) {
+            Some(remaining) if remaining.is_zero() => {
+                self.last_event = None;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Default for Debouncer {
+    fn default() -> Self {
+        Self::new(DEFAULT_DEBOUNCE)
+    }
+}
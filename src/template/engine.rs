@@ -1,19 +1,82 @@
 //! Template rendering engine
 
-use super::parser::{parse_template, TemplateNode};
-use crate::data::{Section, SectionType, SlickSheetData};
+use std::collections::HashSet;
+
+use super::escape::EscapePolicy;
+use super::format::{format_compact, format_currency, format_number};
+use super::page::apply_page_background;
+use super::parser::{parse_template, CompareOp, Comparison, Filter, TemplateNode};
+use crate::data::{
+    color_to_hex, markdown_to_typst, BodyFormat, Section, SectionType, SlickSheetData, Stat,
+};
+
+/// Virtual path of the built-in image substituted for missing references
+///
+/// `VirtualWorld` always serves an SVG placeholder at this path.
+pub const PLACEHOLDER_IMAGE_PATH: &str = "__slick_missing_image.svg";
+
+/// Options for `TemplateEngine::render_with_options`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RenderOptions<'a> {
+    /// How `{{x}}` output is escaped
+    pub escape: EscapePolicy,
+    /// Render missing image references as `PLACEHOLDER_IMAGE_PATH`
+    ///
+    /// A reference is missing when it resolves to nothing or, if
+    /// `known_images` is set, to an ID outside that set.
+    pub image_placeholder: bool,
+    /// IDs of the images available to the compile
+    pub known_images: Option<&'a HashSet<String>>,
+}
 
 /// Template rendering engine
 pub struct TemplateEngine;
 
 impl TemplateEngine {
-    /// Render a template with data
+    /// Render a template with data, fully escaping interpolated text
     pub fn render(template: &str, data: &SlickSheetData) -> Result<String, Vec<String>> {
+        Self::render_with_policy(template, data, EscapePolicy::Full)
+    }
+
+    /// Render a template with data, escaping `{{x}}` output per `policy`
+    ///
+    /// Only `Full` makes arbitrary user text safe; see `EscapePolicy` for
+    /// what the looser policies let through.
+    pub fn render_with_policy(
+        template: &str,
+        data: &SlickSheetData,
+        policy: EscapePolicy,
+    ) -> Result<String, Vec<String>> {
+        let options = RenderOptions {
+            escape: policy,
+            ..RenderOptions::default()
+        };
+        Self::render_with_options(template, data, &options)
+    }
+
+    /// Render a template with data using the given options
+    pub fn render_with_options(
+        template: &str,
+        data: &SlickSheetData,
+        options: &RenderOptions,
+    ) -> Result<String, Vec<String>> {
         let nodes = parse_template(template).map_err(|e| vec![e.to_string()])?;
         let mut output = String::new();
         let mut errors = Vec::new();
 
-        Self::render_nodes(&nodes, data, &mut output, &mut errors, None);
+        Self::render_nodes(&nodes, data, options, &mut output, &mut errors, None);
+
+        if let Some(image) = data.background_image.as_deref().filter(|s| !s.is_empty()) {
+            // Missing backgrounds fall back like inline images do
+            let image = if options.image_placeholder
+                && Self::is_missing_image(image, options.known_images)
+            {
+                PLACEHOLDER_IMAGE_PATH
+            } else {
+                image
+            };
+            output = apply_page_background(&output, image);
+        }
 
         if errors.is_empty() {
             Ok(output)
@@ -25,6 +88,7 @@ impl TemplateEngine {
     fn render_nodes(
         nodes: &[TemplateNode],
         data: &SlickSheetData,
+        options: &RenderOptions,
         output: &mut String,
         errors: &mut Vec<String>,
         loop_context: Option<&LoopContext>,
@@ -34,42 +98,111 @@ impl TemplateEngine {
                 TemplateNode::Text(text) => {
                     output.push_str(text);
                 }
-                TemplateNode::Variable { path, default } => {
+                TemplateNode::Variable { path, filters, raw } => {
                     let value = Self::resolve_path(path, data, loop_context);
-                    let rendered = value.or_else(|| default.clone()).unwrap_or_default();
-                    // Image IDs are safe system-generated identifiers, don't escape them
-                    // They only contain: img_ prefix + hex characters
-                    let is_image_ref = path.first().map(|s| s == "images").unwrap_or(false);
-                    if is_image_ref {
+                    let rendered = filters
+                        .iter()
+                        .fold(value, Self::apply_filter)
+                        .unwrap_or_default();
+                    // Image references are written inside a template's `#image("...")`
+                    let is_image_ref = path.first().map(|s| s == "images").unwrap_or(false)
+                        || (path.len() == 2 && path[0] == "this" && path[1] == "imageRef");
+                    let is_markdown_body = path.len() == 1
+                        && path[0] == "body"
+                        && data.body_format == BodyFormat::Markdown;
+                    let is_icon = path.len() == 2 && path[0] == "this" && path[1] == "icon";
+                    if is_icon && is_image_id(&rendered) {
+                        // Stored image icons become an inline image sized to the text
+                        output.push_str(&format!("#image(\"{}\", height: 1em)", rendered));
+                    } else if is_image_ref
+                        && options.image_placeholder
+                        && Self::is_missing_image(&rendered, options.known_images)
+                    {
+                        output.push_str(PLACEHOLDER_IMAGE_PATH);
+                    } else if *raw || (is_image_ref && is_image_id(&rendered)) {
+                        // Raw {{{path}}} output is template-controlled Typst markup, and
+                        // stored image IDs only hold characters that are safe in quotes
                         output.push_str(&rendered);
+                    } else if is_image_ref {
+                        // Other refs come from content, so they can't leave the string
+                        output.push_str(&rendered.replace('\\', "\\\\").replace('"', "\\\""));
+                    } else if is_markdown_body {
+                        // Markdown bodies become Typst markup instead of escaped text
+                        output.push_str(&markdown_to_typst(&rendered));
                     } else {
                         // Escape Typst special characters in user data
-                        output.push_str(&Self::escape_typst(&rendered));
+                        output.push_str(&options.escape.escape(&rendered));
                     }
                 }
                 TemplateNode::Conditional {
                     path,
+                    comparison,
                     then_branch,
                     else_branch,
                 } => {
-                    let is_truthy = Self::is_path_truthy(path, data, loop_context);
+                    let is_truthy = match comparison {
+                        Some(comparison) => {
+                            Self::compare_path(path, comparison, data, loop_context)
+                        }
+                        None => Self::is_path_truthy(path, data, loop_context),
+                    };
                     if is_truthy {
-                        Self::render_nodes(then_branch, data, output, errors, loop_context);
+                        Self::render_nodes(
+                            then_branch,
+                            data,
+                            options,
+                            output,
+                            errors,
+                            loop_context,
+                        );
                     } else {
-                        Self::render_nodes(else_branch, data, output, errors, loop_context);
+                        Self::render_nodes(
+                            else_branch,
+                            data,
+                            options,
+                            output,
+                            errors,
+                            loop_context,
+                        );
                     }
                 }
                 TemplateNode::Loop { path, body } => {
-                    Self::render_loop(path, body, data, output, errors, loop_context);
+                    Self::render_loop(path, body, data, options, output, errors, loop_context);
                 }
             }
         }
     }
 
+    /// Apply a filter to a resolved value (before Typst escaping)
+    fn apply_filter(value: Option<String>, filter: &Filter) -> Option<String> {
+        match filter {
+            Filter::Default(default) => value.or_else(|| Some(default.clone())),
+            Filter::Uppercase => value.map(|v| v.to_uppercase()),
+            Filter::Lowercase => value.map(|v| v.to_lowercase()),
+            Filter::Capitalize => value.map(|v| {
+                let mut chars = v.chars();
+                match chars.next() {
+                    Some(first) => first.to_uppercase().chain(chars).collect(),
+                    None => String::new(),
+                }
+            }),
+            // Non-numeric values pass through unchanged
+            Filter::Number => value.map(|v| format_number(&v).unwrap_or(v)),
+            Filter::CompactNumber => value.map(|v| format_compact(&v).unwrap_or(v)),
+            Filter::Currency(symbol) => value.map(|v| format_currency(&v, symbol).unwrap_or(v)),
+        }
+    }
+
+    /// Whether an image reference has no image behind it
+    fn is_missing_image(id: &str, known_images: Option<&HashSet<String>>) -> bool {
+        id.is_empty() || known_images.is_some_and(|known| !known.contains(id))
+    }
+
     fn render_loop(
         path: &[String],
         body: &[TemplateNode],
         data: &SlickSheetData,
+        options: &RenderOptions,
         output: &mut String,
         errors: &mut Vec<String>,
         parent_context: Option<&LoopContext>,
@@ -82,7 +215,7 @@ impl TemplateEngine {
                 index,
                 parent: parent_context,
             };
-            Self::render_nodes(body, data, output, errors, Some(&context));
+            Self::render_nodes(body, data, options, output, errors, Some(&context));
         }
     }
 
@@ -99,7 +232,11 @@ impl TemplateEngine {
 
         // Check for special loop variables
         if first == "this" {
-            return loop_context.map(|ctx| ctx.item.clone());
+            let item = &loop_context?.item;
+            return match path.get(1) {
+                None => Some(item.as_string()),
+                Some(field) => item.field(field),
+            };
         }
 
         if first == "@index" {
@@ -138,9 +275,32 @@ impl TemplateEngine {
             "style" => {
                 let style = data.style.as_ref()?;
                 match second.as_str() {
-                    "primaryColor" | "primary_color" => style.primary_color.clone(),
-                    "accentColor" | "accent_color" => style.accent_color.clone(),
+                    "primaryColor" | "primary_color" => {
+                        style.primary_color.as_deref().map(color_to_hex)
+                    }
+                    "accentColor" | "accent_color" => {
+                        style.accent_color.as_deref().map(color_to_hex)
+                    }
                     "fontFamily" | "font_family" => style.font_family.clone(),
+                    "gradientStart" | "gradient_start" => style
+                        .background_gradient
+                        .as_ref()
+                        .map(|(start, _)| start.clone()),
+                    "gradientStop" | "gradient_stop" => style
+                        .background_gradient
+                        .as_ref()
+                        .map(|(_, stop)| stop.clone()),
+                    _ => None,
+                }
+            }
+            "page" => {
+                let page = data.page_or_default();
+                match second.as_str() {
+                    "width" => Some(page.dimensions().0.to_string()),
+                    "height" => Some(page.dimensions().1.to_string()),
+                    "margin" => page.margin_length().map(str::to_string),
+                    "size" => Some(page.size.label().to_string()),
+                    "orientation" => Some(page.orientation.as_str().to_string()),
                     _ => None,
                 }
             }
@@ -151,6 +311,9 @@ impl TemplateEngine {
                     "phone" => contact.phone.clone(),
                     "website" => contact.website.clone(),
                     "address" => contact.address.clone(),
+                    "linkedin" => contact.linkedin.clone(),
+                    "twitter" => contact.twitter.clone(),
+                    "instagram" => contact.instagram.clone(),
                     _ => None,
                 }
             }
@@ -205,6 +368,7 @@ impl TemplateEngine {
             "subtitle" => data.subtitle.as_ref().is_some_and(|s| !s.is_empty()),
             "contact" => data.contact.is_some(),
             "style" => data.style.is_some(),
+            "page" if path.len() == 1 => data.page.is_some(),
             "images" => {
                 // If path is just "images", check if any images exist
                 // If path is "images.X", check if that specific image exists
@@ -214,6 +378,11 @@ impl TemplateEngine {
                     data.images.contains_key(&path[1])
                 }
             }
+            "this"
+                if path.len() > 1 && !Self::resolve_array(path, data, loop_context).is_empty() =>
+            {
+                true
+            }
             _ => {
                 // Try to resolve as a value and check if non-empty
                 Self::resolve_path(path, data, loop_context)
@@ -223,11 +392,37 @@ impl TemplateEngine {
         }
     }
 
+    /// Evaluate `path op literal`, comparing numerically when both sides are numbers
+    fn compare_path(
+        path: &[String],
+        comparison: &Comparison,
+        data: &SlickSheetData,
+        loop_context: Option<&LoopContext>,
+    ) -> bool {
+        let left = Self::resolve_path(path, data, loop_context).unwrap_or_default();
+        let right = comparison.value.as_str();
+
+        match comparison.op {
+            CompareOp::Eq => left == right,
+            CompareOp::Ne => left != right,
+            CompareOp::Gt | CompareOp::Lt => {
+                let ordering = match (left.trim().parse::<f64>(), right.trim().parse::<f64>()) {
+                    (Ok(l), Ok(r)) => l.partial_cmp(&r),
+                    _ => Some(left.as_str().cmp(right)),
+                };
+                match comparison.op {
+                    CompareOp::Gt => ordering == Some(std::cmp::Ordering::Greater),
+                    _ => ordering == Some(std::cmp::Ordering::Less),
+                }
+            }
+        }
+    }
+
     fn resolve_array(
         path: &[String],
         data: &SlickSheetData,
-        _loop_context: Option<&LoopContext>,
-    ) -> Vec<String> {
+        loop_context: Option<&LoopContext>,
+    ) -> Vec<LoopItem> {
         if path.is_empty() {
             return Vec::new();
         }
@@ -235,12 +430,27 @@ impl TemplateEngine {
         let first = &path[0];
 
         match first.as_str() {
-            "features" => data.features.clone(),
-            "sections" => data.sections.iter().map(Self::section_to_string).collect(),
-            "stats" => data
-                .stats
+            // Nested loop over a field of the current item: {{#each this.items}}
+            "this" => match (loop_context, path.get(1)) {
+                (Some(ctx), Some(field)) => ctx.item.array(field),
+                _ => Vec::new(),
+            },
+            "features" => data.features.iter().cloned().map(LoopItem::Text).collect(),
+            "sections" => data
+                .sections
+                .iter()
+                .cloned()
+                .map(LoopItem::Section)
+                .collect(),
+            "stats" => data.stats.iter().cloned().map(LoopItem::Stat).collect(),
+            "contact" if path.get(1).is_some_and(|field| field == "social") => data
+                .contact
                 .iter()
-                .map(|s| format!("{}: {}", s.value, s.label))
+                .flat_map(|contact| contact.social.iter())
+                .map(|(platform, handle)| LoopItem::Social {
+                    platform: platform.clone(),
+                    handle: handle.clone(),
+                })
                 .collect(),
             _ => Vec::new(),
         }
@@ -263,44 +473,100 @@ impl TemplateEngine {
             SectionType::Quote => {
                 format!("{}: \"{}\"", section.heading, section.content)
             }
+            SectionType::Image => {
+                let image = section.image_ref.as_deref().unwrap_or_default();
+                match &section.caption {
+                    Some(caption) => format!("{}: <image {}> {}", section.heading, image, caption),
+                    None => format!("{}: <image {}>", section.heading, image),
+                }
+            }
         }
     }
+}
 
-    /// Escape Typst special characters in user-provided content
-    ///
-    /// This prevents user data from being interpreted as Typst syntax.
-    /// Characters that need escaping to prevent compilation errors:
-    /// - `@` - label references
-    /// - `<` and `>` - label definitions
-    /// - `[` and `]` - content blocks (causes "unclosed delimiter")
-    /// - `#` - code mode / function calls
-    /// - `$` - math mode
-    /// - `*` and `_` - emphasis markers
-    /// - `\` - escape character itself
-    fn escape_typst(s: &str) -> String {
-        let mut result = String::with_capacity(s.len() + s.len() / 4);
-        for c in s.chars() {
-            match c {
-                '@' => result.push_str("\\@"),
-                '<' => result.push_str("\\<"),
-                '>' => result.push_str("\\>"),
-                '[' => result.push_str("\\["),
-                ']' => result.push_str("\\]"),
-                '#' => result.push_str("\\#"),
-                '$' => result.push_str("\\$"),
-                '*' => result.push_str("\\*"),
-                '_' => result.push_str("\\_"),
-                '\\' => result.push_str("\\\\"),
-                _ => result.push(c),
-            }
+/// Whether a value is a stored image ID (`img_` + hex, optionally with an extension)
+///
+/// The character set is checked so the ID can be quoted into Typst safely.
+fn is_image_id(value: &str) -> bool {
+    value.strip_prefix("img_").is_some_and(|rest| {
+        !rest.is_empty()
+            && rest
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '_' || c == '-')
+    })
+}
+
+/// A single item being iterated in a loop
+#[derive(Debug, Clone)]
+enum LoopItem {
+    /// Plain string (features, list section items)
+    Text(String),
+    /// A stat, exposing `this.value`, `this.label`, `this.color` and `this.icon`
+    Stat(Stat),
+    /// A section, exposing `this.heading`, `this.content`, `this.type`,
+    /// `this.imageRef`, `this.caption` and `this.items`
+    Section(Section),
+    /// A `contact.social` entry, exposing `this.platform` and `this.handle`
+    Social { platform: String, handle: String },
+}
+
+impl LoopItem {
+    /// String form used for a bare `{{this}}`
+    fn as_string(&self) -> String {
+        match self {
+            LoopItem::Text(text) => text.clone(),
+            LoopItem::Stat(stat) => format!("{}: {}", stat.value, stat.label),
+            LoopItem::Section(section) => TemplateEngine::section_to_string(section),
+            LoopItem::Social { platform, handle } => format!("{}: {}", platform, handle),
+        }
+    }
+
+    /// Resolve `this.<field>` to a string value
+    fn field(&self, name: &str) -> Option<String> {
+        match self {
+            LoopItem::Text(_) => None,
+            LoopItem::Stat(stat) => match name {
+                "value" => Some(stat.value.clone()),
+                "label" => Some(stat.label.clone()),
+                "color" => stat.color.as_deref().map(color_to_hex),
+                "icon" => stat.icon.clone(),
+                _ => None,
+            },
+            LoopItem::Section(section) => match name {
+                "heading" => Some(section.heading.clone()),
+                "content" => Some(section.content.clone()),
+                "type" => Some(section.section_type.as_str().to_string()),
+                "columns" => section.columns.map(|c| c.to_string()),
+                "imageRef" => section.image_ref.clone(),
+                "caption" => section.caption.clone(),
+                _ => None,
+            },
+            LoopItem::Social { platform, handle } => match name {
+                "platform" => Some(platform.clone()),
+                "handle" => Some(handle.clone()),
+                _ => None,
+            },
+        }
+    }
+
+    /// Resolve `this.<field>` to a list of nested loop items
+    fn array(&self, name: &str) -> Vec<LoopItem> {
+        match (self, name) {
+            (LoopItem::Section(section), "items") => section
+                .items
+                .iter()
+                .flatten()
+                .cloned()
+                .map(LoopItem::Text)
+                .collect(),
+            _ => Vec::new(),
         }
-        result
     }
 }
 
 /// Context for loop iterations
 struct LoopContext<'a> {
-    item: String,
+    item: LoopItem,
     index: usize,
     #[allow(dead_code)]
     parent: Option<&'a LoopContext<'a>>,
@@ -333,6 +599,145 @@ mod engine_tests {
         assert_eq!(result, "Subtitle: None");
     }
 
+    #[test]
+    fn test_render_uppercase_filter() {
+        let data = SlickSheetData::new("Event name");
+        let result = TemplateEngine::render("{{title | uppercase}}", &data).unwrap();
+        assert_eq!(result, "EVENT NAME");
+    }
+
+    #[test]
+    fn test_render_lowercase_filter() {
+        let data = SlickSheetData::default().with_body("Quiet PLEASE");
+        let result = TemplateEngine::render("{{body | lowercase}}", &data).unwrap();
+        assert_eq!(result, "quiet please");
+    }
+
+    #[test]
+    fn test_render_capitalize_filter() {
+        let data = SlickSheetData::default().with_subtitle("join us for something amazing");
+        let result = TemplateEngine::render("{{subtitle | capitalize}}", &data).unwrap();
+        assert_eq!(result, "Join us for something amazing");
+    }
+
+    #[test]
+    fn test_render_default_then_filter() {
+        let data = SlickSheetData::default();
+        let result =
+            TemplateEngine::render("{{subtitle | default: 'coming soon' | uppercase}}", &data)
+                .unwrap();
+        assert_eq!(result, "COMING SOON");
+    }
+
+    #[test]
+    fn test_render_filter_before_escaping() {
+        let data = SlickSheetData::new("a_b");
+        let result = TemplateEngine::render("{{title | uppercase}}", &data).unwrap();
+        assert_eq!(result, "A\\_B");
+    }
+
+    #[test]
+    fn test_render_number_filter_groups_thousands() {
+        let data = SlickSheetData::new("1234567");
+        let result = TemplateEngine::render("{{title | number}}", &data).unwrap();
+        assert_eq!(result, "1,234,567");
+
+        let data = SlickSheetData::new("-9876543.21");
+        let result = TemplateEngine::render("{{title | number}}", &data).unwrap();
+        assert_eq!(result, "-9,876,543.21");
+    }
+
+    #[test]
+    fn test_render_number_compact() {
+        let cases = [
+            ("1234567", "1.2M"),
+            ("2000000000", "2B"),
+            ("999950", "1M"),
+            ("1500", "1.5K"),
+            ("950", "950"),
+        ];
+        for (input, expected) in cases {
+            let data = SlickSheetData::new(input);
+            let result = TemplateEngine::render("{{title | number:compact}}", &data).unwrap();
+            assert_eq!(result, expected, "input {}", input);
+        }
+    }
+
+    #[test]
+    fn test_render_currency_filter() {
+        let data = SlickSheetData::new("1234567");
+        let result = TemplateEngine::render("{{title | currency:'$'}}", &data).unwrap();
+        assert_eq!(result, "\\$1,234,567");
+
+        let data = SlickSheetData::new("1234.5");
+        let result = TemplateEngine::render("{{title | currency:'€'}}", &data).unwrap();
+        assert_eq!(result, "€1,234.50");
+
+        let data = SlickSheetData::new("1234567");
+        let result =
+            TemplateEngine::render("{{title | number:compact | currency:'€'}}", &data).unwrap();
+        assert_eq!(result, "€1.2M");
+    }
+
+    #[test]
+    fn test_render_number_filters_pass_through_non_numeric() {
+        let data = SlickSheetData::new("95%");
+        for filter in ["number", "number:compact", "currency:'$'"] {
+            let template = format!("{{{{title | {}}}}}", filter);
+            let result = TemplateEngine::render(&template, &data).unwrap();
+            assert_eq!(result, "95%", "filter {}", filter);
+        }
+    }
+
+    #[test]
+    fn test_render_raw_variable_skips_escaping() {
+        let data = SlickSheetData::default().with_body("*bold*");
+        let raw = TemplateEngine::render("{{{body}}}", &data).unwrap();
+        let escaped = TemplateEngine::render("{{body}}", &data).unwrap();
+        assert_eq!(raw, "*bold*");
+        assert_eq!(escaped, "\\*bold\\*");
+    }
+
+    /// Render `{{body}}` holding `#`, `*` and `@` under `policy`
+    fn render_policy(policy: EscapePolicy) -> String {
+        let data = SlickSheetData::default().with_body("#tag *bold* @ref");
+        TemplateEngine::render_with_policy("{{body}}", &data, policy).unwrap()
+    }
+
+    #[test]
+    fn test_render_full_policy_escapes_everything() {
+        let full = render_policy(EscapePolicy::Full);
+        assert_eq!(full, "\\#tag \\*bold\\* \\@ref");
+        let data = SlickSheetData::default().with_body("#tag *bold* @ref");
+        assert_eq!(TemplateEngine::render("{{body}}", &data).unwrap(), full);
+    }
+
+    #[test]
+    fn test_render_minimal_policy_keeps_emphasis() {
+        assert_eq!(
+            render_policy(EscapePolicy::MinimalMath),
+            "\\#tag *bold* \\@ref"
+        );
+    }
+
+    #[test]
+    fn test_render_none_policy_passes_text_through() {
+        assert_eq!(render_policy(EscapePolicy::None), "#tag *bold* @ref");
+    }
+
+    #[test]
+    fn test_render_markdown_body_is_converted() {
+        let data = SlickSheetData::default()
+            .with_body("**bold** and #tag")
+            .with_body_format(BodyFormat::Markdown);
+        let result = TemplateEngine::render("{{body}}", &data).unwrap();
+        assert_eq!(result, "*bold* and \\#tag");
+
+        let plain = data.with_body_format(BodyFormat::Plain);
+        let result = TemplateEngine::render("{{body}}", &plain).unwrap();
+        assert_eq!(result, "\\*\\*bold\\*\\* and \\#tag");
+    }
+
     #[test]
     fn test_render_nested_variable() {
         let data = SlickSheetData::default().with_style(StyleHints {
@@ -344,6 +749,16 @@ mod engine_tests {
         assert_eq!(result, "Color: \\#ff0000");
     }
 
+    #[test]
+    fn test_render_color_name_as_hex() {
+        let data = SlickSheetData::default().with_style(StyleHints {
+            accent_color: Some("Navy".to_string()),
+            ..Default::default()
+        });
+        let result = TemplateEngine::render("Color: {{style.accentColor}}", &data).unwrap();
+        assert_eq!(result, "Color: \\#001f3f");
+    }
+
     #[test]
     fn test_render_conditional_true() {
         let data = SlickSheetData::new("Title").with_subtitle("Subtitle");
@@ -365,6 +780,39 @@ mod engine_tests {
         assert_eq!(result, "no");
     }
 
+    #[test]
+    fn test_render_conditional_string_equality() {
+        let mut data = SlickSheetData::new("Pricing");
+        data.metadata
+            .insert("plan".to_string(), "Professional".to_string());
+        let template = r#"{{#if plan == "Professional"}}highlight{{else}}plain{{/if}}"#;
+        assert_eq!(
+            TemplateEngine::render(template, &data).unwrap(),
+            "highlight"
+        );
+
+        data.metadata
+            .insert("plan".to_string(), "Starter".to_string());
+        assert_eq!(TemplateEngine::render(template, &data).unwrap(), "plain");
+
+        let template = r#"{{#if plan != "Professional"}}other{{/if}}"#;
+        assert_eq!(TemplateEngine::render(template, &data).unwrap(), "other");
+    }
+
+    #[test]
+    fn test_render_conditional_numeric_greater_than() {
+        let data = SlickSheetData::default()
+            .with_stat(Stat::new("1", "One"))
+            .with_stat(Stat::new("2", "Two"))
+            .with_stat(Stat::new("3", "Three"));
+        // Numeric comparison: 3 > 10 is false even though "3" > "10" as strings
+        let template = "{{#if stats.length > 2}}many{{/if}}{{#if stats.length > 10}}lots{{/if}}";
+        assert_eq!(TemplateEngine::render(template, &data).unwrap(), "many");
+
+        let template = "{{#if stats.length < 10}}few{{/if}}";
+        assert_eq!(TemplateEngine::render(template, &data).unwrap(), "few");
+    }
+
     #[test]
     fn test_render_loop() {
         let data = SlickSheetData::default()
@@ -375,6 +823,49 @@ mod engine_tests {
         assert_eq!(result, "[Feature 1][Feature 2]");
     }
 
+    #[test]
+    fn test_render_stat_fields_in_loop() {
+        let data = SlickSheetData::default()
+            .with_stat(Stat::new("99%", "Uptime"))
+            .with_stat(Stat::new("2x", "Speed"));
+        let result = TemplateEngine::render(
+            "{{#each stats}}[{{this.value}}|{{this.label}}]{{/each}}",
+            &data,
+        )
+        .unwrap();
+        assert_eq!(result, "[99%|Uptime][2x|Speed]");
+    }
+
+    #[test]
+    fn test_render_text_section_fields() {
+        let data = SlickSheetData::default()
+            .with_section(crate::data::Section::text("About", "We build tools"));
+        let template =
+            "{{#each sections}}== {{this.heading}} ({{this.type}})\n{{this.content}}{{/each}}";
+        let result = TemplateEngine::render(template, &data).unwrap();
+        assert_eq!(result, "== About (text)\nWe build tools");
+    }
+
+    #[test]
+    fn test_render_list_section_nested_items() {
+        let data = SlickSheetData::default().with_section(crate::data::Section::list(
+            "Perks",
+            vec!["Remote".to_string(), "Lunch".to_string()],
+        ));
+        let template = "{{#each sections}}{{this.heading}}:{{#if this.items}}{{#each this.items}} - {{this}}{{/each}}{{/if}}{{/each}}";
+        let result = TemplateEngine::render(template, &data).unwrap();
+        assert_eq!(result, "Perks: - Remote - Lunch");
+    }
+
+    #[test]
+    fn test_render_text_section_has_no_items() {
+        let data =
+            SlickSheetData::default().with_section(crate::data::Section::text("About", "Text"));
+        let template = "{{#each sections}}{{#if this.items}}list{{else}}text{{/if}}{{/each}}";
+        let result = TemplateEngine::render(template, &data).unwrap();
+        assert_eq!(result, "text");
+    }
+
     #[test]
     fn test_render_contact() {
         let data = SlickSheetData::default().with_contact(ContactInfo {
@@ -481,4 +972,76 @@ Features:
         // Missing image reference should render as empty string
         assert_eq!(result, "#image(\"\")");
     }
+
+    #[test]
+    fn test_render_missing_image_uses_placeholder_when_enabled() {
+        let data = SlickSheetData::default().with_image("hero", "img_gone.png");
+        let known = HashSet::from(["img_logo.png".to_string()]);
+        let options = RenderOptions {
+            image_placeholder: true,
+            known_images: Some(&known),
+            ..RenderOptions::default()
+        };
+        let template = "#image(\"{{images.logo}}\") #image(\"{{images.hero}}\")";
+
+        let result = TemplateEngine::render_with_options(template, &data, &options).unwrap();
+        let expected = format!("#image(\"{0}\") #image(\"{0}\")", PLACEHOLDER_IMAGE_PATH);
+        assert_eq!(result, expected);
+
+        // Known images are kept
+        let data = data.with_image("logo", "img_logo.png");
+        let result = TemplateEngine::render_with_options("{{images.logo}}", &data, &options);
+        assert_eq!(result.unwrap(), "img_logo.png");
+    }
+
+    #[test]
+    fn test_render_placeholder_keeps_document_compiling() {
+        let options = RenderOptions {
+            image_placeholder: true,
+            ..RenderOptions::default()
+        };
+        let source = TemplateEngine::render_with_options(
+            "#image(\"{{images.logo}}\", width: 2in)",
+            &SlickSheetData::default(),
+            &options,
+        )
+        .unwrap();
+
+        let result = crate::world::VirtualWorld::compile_to_svg(&source);
+        assert!(result.is_ok(), "{:?}", result.err());
+    }
+
+    #[test]
+    fn test_render_page_background_only_when_set() {
+        let template = "#set page(width: 8.5in, height: 11in)\n= {{title}}\n";
+        let data = SlickSheetData::new("Launch");
+
+        let plain = TemplateEngine::render(template, &data).unwrap();
+        assert!(!plain.contains("background:"), "{}", plain);
+
+        let data = data.with_background_image("img_bg.png");
+        let result = TemplateEngine::render(template, &data).unwrap();
+        assert_eq!(
+            result,
+            "#set page(width: 8.5in, height: 11in)\n\
+             #set page(background: image(\"img_bg.png\", width: 100%, height: 100%, fit: \"cover\"))\n\
+             = Launch\n"
+        );
+    }
+
+    #[test]
+    fn test_render_missing_page_background_keeps_document_compiling() {
+        let known = HashSet::new();
+        let options = RenderOptions {
+            image_placeholder: true,
+            known_images: Some(&known),
+            ..RenderOptions::default()
+        };
+        let data = SlickSheetData::new("Launch").with_background_image("img_gone.png");
+        let source = TemplateEngine::render_with_options("= {{title}}", &data, &options).unwrap();
+        assert!(source.contains(PLACEHOLDER_IMAGE_PATH), "{}", source);
+
+        let result = crate::world::VirtualWorld::compile_to_svg(&source);
+        assert!(result.is_ok(), "{:?}", result.err());
+    }
 }
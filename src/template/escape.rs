@@ -0,0 +1,125 @@
+//! Typst escaping of interpolated values
+//!
+//! `{{x}}` output is escaped so user text can't trigger markup. The same
+//! character set drives `annotate_escapes`, which the editor uses to show
+//! where escapes appear in rendered source.
+
+/// Characters that `{{x}}` interpolation prefixes with a backslash
+///
+/// Left unescaped they would be read as Typst syntax:
+/// - `@` - label references
+/// - `<` and `>` - label definitions
+/// - `[` and `]` - content blocks (causes "unclosed delimiter")
+/// - `#` - code mode / function calls
+/// - `$` - math mode
+/// - `*` and `_` - emphasis markers
+/// - `\` - escape character itself
+pub const ESCAPED_CHARS: &[char] = &['@', '<', '>', '[', ']', '#', '$', '*', '_', '\\'];
+
+/// Escape Typst special characters in user-provided content
+pub fn escape_typst(s: &str) -> String {
+    escape_chars(s, ESCAPED_CHARS)
+}
+
+/// Characters still escaped under `EscapePolicy::MinimalMath`
+///
+/// Everything in `ESCAPED_CHARS` except the `*` and `_` emphasis markers.
+const MINIMAL_ESCAPED_CHARS: &[char] = &['@', '<', '>', '[', ']', '#', '$', '\\'];
+
+/// How `{{x}}` interpolation escapes text before it enters the Typst source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EscapePolicy {
+    /// Escape every special character, so text always renders literally
+    #[default]
+    Full,
+    /// Leave `*` and `_` so markdown-style `*bold*` and `_italic_` work
+    ///
+    /// Code, math, labels and content blocks stay escaped. Unbalanced
+    /// markers can still fail compilation or restyle the rest of a line.
+    MinimalMath,
+    /// Pass text through unchanged, for trusted content only
+    ///
+    /// Interpolated text becomes live Typst: `#` runs arbitrary code (e.g.
+    /// `#include` or `#read` of other project files) and brackets can break
+    /// the template's structure. Never use this with user or AI input.
+    None,
+}
+
+impl EscapePolicy {
+    /// Escape `text` according to this policy
+    pub fn escape(self, text: &str) -> String {
+        match self {
+            Self::Full => escape_typst(text),
+            Self::MinimalMath => escape_chars(text, MINIMAL_ESCAPED_CHARS),
+            Self::None => text.to_string(),
+        }
+    }
+}
+
+/// Prefix each character of `s` found in `special` with a backslash
+fn escape_chars(s: &str, special: &[char]) -> String {
+    let mut result = String::with_capacity(s.len() + s.len() / 4);
+    for c in s.chars() {
+        if special.contains(&c) {
+            result.push('\\');
+        }
+        result.push(c);
+    }
+    result
+}
+
+/// A run of rendered Typst, either plain or a single escape sequence
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderedSpan {
+    /// The source text of the run
+    pub text: String,
+    /// Whether the run is a backslash escape such as `\#`
+    pub escaped: bool,
+}
+
+impl RenderedSpan {
+    fn plain(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            escaped: false,
+        }
+    }
+}
+
+/// Split rendered Typst into plain runs and escape sequences
+///
+/// An escape is a backslash followed by one of `ESCAPED_CHARS`, read left to
+/// right so `\\\#` is two escapes. Escapes written by hand in the template
+/// are marked too, since Typst treats them the same way.
+pub fn annotate_escapes(rendered: &str) -> Vec<RenderedSpan> {
+    let mut spans = Vec::new();
+    let mut plain_start = 0;
+    let mut chars = rendered.char_indices().peekable();
+
+    while let Some((index, c)) = chars.next() {
+        if c != '\\' {
+            continue;
+        }
+        let Some(&(next_index, next)) = chars.peek() else {
+            break;
+        };
+        if !ESCAPED_CHARS.contains(&next) {
+            continue;
+        }
+        chars.next();
+        if plain_start < index {
+            spans.push(RenderedSpan::plain(&rendered[plain_start..index]));
+        }
+        let end = next_index + next.len_utf8();
+        spans.push(RenderedSpan {
+            text: rendered[index..end].to_string(),
+            escaped: true,
+        });
+        plain_start = end;
+    }
+
+    if plain_start < rendered.len() {
+        spans.push(RenderedSpan::plain(&rendered[plain_start..]));
+    }
+    spans
+}
@@ -0,0 +1,140 @@
+//! Number formatting for the `number` and `currency` filters
+//!
+//! Each formatter returns `None` when the value is not numeric so the filter
+//! can pass it through unchanged.
+
+/// Compact suffixes in descending order of magnitude
+const COMPACT_UNITS: [(f64, &str); 4] = [(1e12, "T"), (1e9, "B"), (1e6, "M"), (1e3, "K")];
+
+/// A number split into its textual parts
+struct Decimal<'a> {
+    negative: bool,
+    integer: &'a str,
+    fraction: Option<&'a str>,
+}
+
+impl<'a> Decimal<'a> {
+    /// Parse `-1234.56`-style input, allowing surrounding whitespace
+    fn parse(value: &'a str) -> Option<Self> {
+        let value = value.trim();
+        let (negative, digits) = match value.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, value),
+        };
+        let (integer, fraction) = match digits.split_once('.') {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (digits, None),
+        };
+
+        let all_digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+        if !all_digits(integer) || !fraction.is_none_or(all_digits) {
+            return None;
+        }
+
+        Some(Self {
+            negative,
+            integer,
+            fraction,
+        })
+    }
+
+    /// Parsed numeric value
+    fn value(&self) -> f64 {
+        let magnitude: f64 = match self.fraction {
+            Some(fraction) => format!("{}.{}", self.integer, fraction).parse(),
+            None => self.integer.parse(),
+        }
+        .unwrap_or(0.0);
+        if self.negative {
+            -magnitude
+        } else {
+            magnitude
+        }
+    }
+}
+
+/// Format with thousands separators, keeping any fractional digits as written
+pub(crate) fn format_number(value: &str) -> Option<String> {
+    let decimal = Decimal::parse(value)?;
+    let mut out = String::new();
+    if decimal.negative {
+        out.push('-');
+    }
+    out.push_str(&group_thousands(decimal.integer));
+    if let Some(fraction) = decimal.fraction {
+        out.push('.');
+        out.push_str(fraction);
+    }
+    Some(out)
+}
+
+/// Abbreviate large numbers with K/M/B/T suffixes and one decimal place
+pub(crate) fn format_compact(value: &str) -> Option<String> {
+    let number = Decimal::parse(value)?.value();
+    let magnitude = number.abs();
+
+    for (i, &(scale, suffix)) in COMPACT_UNITS.iter().enumerate() {
+        if magnitude < scale {
+            continue;
+        }
+        let scaled = (magnitude / scale * 10.0).round() / 10.0;
+        // 999,950 rounds to 1000.0K; promote it to 1M instead
+        let (scaled, suffix) = match i.checked_sub(1) {
+            Some(larger) if scaled >= 1000.0 => (1.0, COMPACT_UNITS[larger].1),
+            _ => (scaled, suffix),
+        };
+        let sign = if number < 0.0 { "-" } else { "" };
+        return Some(format!("{}{}{}", sign, trim_decimal(scaled), suffix));
+    }
+
+    format_number(value)
+}
+
+/// Prefix a currency symbol, grouping thousands and showing cents if present
+///
+/// Values already abbreviated by `number:compact` (e.g. `1.2M`) are accepted
+/// so the two filters can be chained.
+pub(crate) fn format_currency(value: &str, symbol: &str) -> Option<String> {
+    let value = value.trim();
+    let (amount, suffix) = match value.char_indices().last() {
+        Some((i, c)) if COMPACT_UNITS.iter().any(|(_, s)| s.starts_with(c)) => {
+            (&value[..i], &value[i..])
+        }
+        _ => (value, ""),
+    };
+
+    let decimal = Decimal::parse(amount)?;
+    let sign = if decimal.negative { "-" } else { "" };
+    let formatted = match (decimal.fraction, suffix.is_empty()) {
+        (Some(_), true) => {
+            let cents = format!("{:.2}", decimal.value().abs());
+            let (integer, fraction) = cents.split_once('.').unwrap_or((&cents, "00"));
+            format!("{}.{}", group_thousands(integer), fraction)
+        }
+        (Some(fraction), false) => format!("{}.{}", group_thousands(decimal.integer), fraction),
+        (None, _) => group_thousands(decimal.integer),
+    };
+
+    Some(format!("{}{}{}{}", sign, symbol, formatted, suffix))
+}
+
+/// Insert commas between groups of three digits
+fn group_thousands(digits: &str) -> String {
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Format with one decimal place, dropping a trailing `.0`
+fn trim_decimal(value: f64) -> String {
+    let formatted = format!("{:.1}", value);
+    formatted
+        .strip_suffix(".0")
+        .map(str::to_string)
+        .unwrap_or(formatted)
+}
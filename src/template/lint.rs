@@ -0,0 +1,127 @@
+//! Lints for templates that parse and compile but render badly
+//!
+//! These are heuristics over the parsed template: Typst context is tracked
+//! per line, so a string literal spanning lines is not recognised.
+
+use super::parser::{parse_template, TemplateNode};
+use thiserror::Error;
+
+/// A likely mistake in a template
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum Lint {
+    /// `{{images.X}}` used outside `{{#if images.X}}`
+    #[error(
+        "Image '{path}' is used outside {{{{#if {path}}}}}, so it breaks when the image is missing"
+    )]
+    UnguardedImage { path: String },
+
+    /// `{{x}}` inside a Typst string such as `rgb("{{x}}")`
+    ///
+    /// The backslash escapes meant for markup end up in the string value.
+    #[error("'{{{{{path}}}}}' is escaped inside a Typst string; use '{{{{{{{path}}}}}}}' to insert it as-is")]
+    EscapedInString { path: String },
+}
+
+/// Check a template for common Typst pitfalls
+///
+/// Templates that fail to parse have no lints; `validate_template` reports
+/// the parse error instead.
+pub fn lint(template: &str) -> Vec<Lint> {
+    let Ok(nodes) = parse_template(template) else {
+        return Vec::new();
+    };
+    let mut linter = Linter::default();
+    linter.visit(&nodes, &[]);
+    linter.lints
+}
+
+#[derive(Default)]
+struct Linter {
+    lints: Vec<Lint>,
+    /// Template text seen so far on the current line
+    line: String,
+}
+
+impl Linter {
+    fn visit(&mut self, nodes: &[TemplateNode], guards: &[String]) {
+        for node in nodes {
+            match node {
+                TemplateNode::Text(text) => self.push_text(text),
+                TemplateNode::Variable { path, raw, .. } => {
+                    let joined = path.join(".");
+                    let is_image = path.first().is_some_and(|first| first == "images");
+                    if is_image && path.len() > 1 && !guards.contains(&joined) {
+                        self.report(Lint::UnguardedImage { path: joined });
+                    } else if !is_image && !raw && in_code_string(&self.line) {
+                        self.report(Lint::EscapedInString { path: joined });
+                    }
+                    // Stand-in for the rendered value
+                    self.line.push('x');
+                }
+                TemplateNode::Conditional {
+                    path,
+                    comparison,
+                    then_branch,
+                    else_branch,
+                } => {
+                    let line = self.line.clone();
+                    let mut then_guards = guards.to_vec();
+                    if comparison.is_none() {
+                        then_guards.push(path.join("."));
+                    }
+                    self.visit(then_branch, &then_guards);
+                    let after_then = std::mem::replace(&mut self.line, line);
+                    self.visit(else_branch, guards);
+                    self.line = after_then;
+                }
+                TemplateNode::Loop { body, .. } => self.visit(body, guards),
+            }
+        }
+    }
+
+    fn push_text(&mut self, text: &str) {
+        match text.rfind('\n') {
+            Some(newline) => self.line = text[newline + 1..].to_string(),
+            None => self.line.push_str(text),
+        }
+    }
+
+    /// Record a lint once, however often the variable appears
+    fn report(&mut self, lint: Lint) {
+        if !self.lints.contains(&lint) {
+            self.lints.push(lint);
+        }
+    }
+}
+
+/// Whether the end of `line` is inside a string literal in Typst code
+///
+/// `#` switches to code, `[` back to markup (where quotes are just text),
+/// and `]` returns to the enclosing code.
+fn in_code_string(line: &str) -> bool {
+    let mut code = false;
+    let mut in_string = false;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if in_string {
+            match c {
+                '\\' => {
+                    chars.next();
+                }
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '\\' if !code => {
+                chars.next();
+            }
+            '#' | ']' => code = true,
+            '[' => code = false,
+            '"' if code => in_string = true,
+            _ => {}
+        }
+    }
+    in_string
+}
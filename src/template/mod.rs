@@ -3,20 +3,63 @@
 //! This module provides:
 //! - Handlebars-style template parsing
 //! - Data binding and rendering
-//! - Template validation
+//! - Number and currency formatting filters
+//! - Template validation and linting
+//! - Escape annotation for inspecting rendered output
+//! - The list of resolvable variable paths, for autocompletion
+//! - Page size overrides for plain Typst templates
+//!
+//! The placeholder syntax is summarized for the AI once, in
+//! `syntax_reference!`; prompts and tool descriptions splice it in rather
+//! than keeping their own copies.
 
 mod engine;
+mod escape;
+mod format;
+mod lint;
+mod page;
 mod parser;
 mod validation;
+mod variables;
 
 #[cfg(test)]
 mod tests;
 
-pub use engine::TemplateEngine;
+pub use engine::{RenderOptions, TemplateEngine, PLACEHOLDER_IMAGE_PATH};
+pub use escape::{annotate_escapes, EscapePolicy, RenderedSpan};
+pub use lint::{lint, Lint};
+pub use page::{
+    apply_export_preset, apply_page_background, apply_page_setup, page_background_rule,
+    page_setup_rule, ExportPreset,
+};
 pub use validation::validate_template;
+pub use variables::{available_variables, matching_variables, VariableInfo};
 
 // Re-exports for public API (not all used internally yet)
 #[allow(unused_imports)]
-pub use parser::{parse_template, TemplateNode};
+pub use parser::{parse_template, Filter, TemplateNode};
 #[allow(unused_imports)]
 pub use validation::TemplateValidationError;
+#[allow(unused_imports)]
+pub use validation::{validate_template_against_schema, SCHEMA_FIELDS};
+
+/// Placeholder syntax summary for AI prompts and tool descriptions
+///
+/// A macro rather than a `const` so it can be used inside `concat!`.
+macro_rules! syntax_reference {
+    () => {
+        r#"Templates use Handlebars-style placeholders:
+- {{field}} - Simple value substitution (e.g., {{title}}, {{body}})
+- {{field.subfield}} - Nested values (e.g., {{style.primaryColor}}, {{contact.email}})
+- {{#if field}}...{{/if}} - Conditional sections
+- {{#if field == "value"}}...{{/if}} - Comparison conditionals (==, !=, >, <)
+- {{#if field}}...{{else}}...{{/if}} - Conditional with else
+- {{#each items}}...{{/each}} - Loop over arrays
+- {{this}} - Current item in a loop
+- {{field | default: 'value'}} - Default values if field is empty
+- {{field | uppercase}} - String filters: uppercase, lowercase, capitalize (chainable)
+- {{field | number}} - Number filters: number (1,234,567), number:compact (1.2M), currency:'$' ($1,234)
+- {{{field}}} - Raw substitution without Typst escaping (for trusted markup only)"#
+    };
+}
+pub(crate) use syntax_reference;
@@ -0,0 +1,175 @@
+//! Applying a page setup to plain Typst source
+//!
+//! Data-bound templates read `{{page.width}}`/`{{page.height}}`, but the
+//! built-in templates are plain Typst with a hardcoded `#set page(...)`. For
+//! those, a marked override rule is placed right after the first page rule,
+//! so the template keeps its own fill and (unless overridden) margin.
+
+use crate::data::PageSetup;
+
+/// Comment marking the override line, so it can be replaced later
+const PAGE_SETUP_MARKER: &str = "// page setup";
+
+/// The `#set page(...)` rule for a page setup
+pub fn page_setup_rule(page: &PageSetup) -> String {
+    let (width, height) = page.dimensions();
+    match page.margin_length() {
+        Some(margin) => format!(
+            "#set page(width: {}, height: {}, margin: {}) {}",
+            width, height, margin, PAGE_SETUP_MARKER
+        ),
+        None => format!(
+            "#set page(width: {}, height: {}) {}",
+            width, height, PAGE_SETUP_MARKER
+        ),
+    }
+}
+
+/// Apply a page setup to Typst source
+///
+/// Replaces an earlier override if there is one, otherwise inserts the rule
+/// after the first (possibly multi-line) `#set page(...)`, or at the top when
+/// the source has none.
+pub fn apply_page_setup(source: &str, page: &PageSetup) -> String {
+    let rule = page_setup_rule(page);
+    let mut lines: Vec<&str> = source.lines().collect();
+
+    if let Some(index) = lines
+        .iter()
+        .position(|line| line.trim_end().ends_with(PAGE_SETUP_MARKER))
+    {
+        lines[index] = &rule;
+    } else if let Some(end) = page_rule_end(&lines) {
+        lines.insert(end + 1, &rule);
+    } else {
+        lines.insert(0, &rule);
+    }
+
+    let mut result = lines.join("\n");
+    if source.ends_with('\n') {
+        result.push('\n');
+    }
+    result
+}
+
+/// Index of the line that closes the first `#set page(` rule
+fn page_rule_end(lines: &[&str]) -> Option<usize> {
+    let start = lines
+        .iter()
+        .position(|line| line.trim_start().starts_with("#set page("))?;
+    let mut depth = 0i32;
+    for (index, line) in lines.iter().enumerate().skip(start) {
+        for c in line.chars() {
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ => {}
+            }
+        }
+        if depth <= 0 {
+            return Some(index);
+        }
+    }
+    None
+}
+
+/// Fixed-size social media formats the first page can be re-exported at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportPreset {
+    /// Square feed post, 1080x1080 px
+    InstagramSquare,
+    /// Vertical story, 1080x1920 px
+    InstagramStory,
+}
+
+impl ExportPreset {
+    /// All presets, in menu order
+    pub const ALL: [ExportPreset; 2] =
+        [ExportPreset::InstagramSquare, ExportPreset::InstagramStory];
+
+    /// Value used in the export `<select>` (e.g. "instagram-square")
+    pub fn key(&self) -> &'static str {
+        match self {
+            ExportPreset::InstagramSquare => "instagram-square",
+            ExportPreset::InstagramStory => "instagram-story",
+        }
+    }
+
+    /// Display name (e.g. "Instagram Square (1080×1080)")
+    pub fn label(&self) -> &'static str {
+        match self {
+            ExportPreset::InstagramSquare => "Instagram Square (1080×1080)",
+            ExportPreset::InstagramStory => "Instagram Story (1080×1920)",
+        }
+    }
+
+    /// Look up a preset by its `<select>` value
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|preset| preset.key() == key)
+    }
+
+    /// Target image size in pixels (width, height)
+    pub fn pixel_size(&self) -> (u32, u32) {
+        match self {
+            ExportPreset::InstagramSquare => (1080, 1080),
+            ExportPreset::InstagramStory => (1080, 1920),
+        }
+    }
+
+    /// (width, height) as Typst lengths
+    ///
+    /// Pixels are taken as CSS pixels (0.75pt), so text keeps roughly the
+    /// size it has on a printed page.
+    pub fn dimensions(&self) -> (String, String) {
+        let (width, height) = self.pixel_size();
+        let to_pt = |px: u32| format!("{}pt", f64::from(px) * 0.75);
+        (to_pt(width), to_pt(height))
+    }
+}
+
+/// Re-layout Typst source at an export preset's page size
+///
+/// The size rule goes after the page setup override or the first
+/// `#set page(...)`, so margins and fills still come from the source.
+pub fn apply_export_preset(source: &str, preset: ExportPreset) -> String {
+    let (width, height) = preset.dimensions();
+    let rule = format!("#set page(width: {}, height: {})", width, height);
+    insert_after_page_rule(source, &rule)
+}
+
+/// The `#set page(background: ...)` rule drawing `image` behind the whole page
+pub fn page_background_rule(image: &str) -> String {
+    let quoted = image.replace('\\', "\\\\").replace('"', "\\\"");
+    format!(
+        "#set page(background: image(\"{}\", width: 100%, height: 100%, fit: \"cover\"))",
+        quoted
+    )
+}
+
+/// Put a full-bleed background image behind every page
+///
+/// Like an export preset, the rule goes after the page setup override or the
+/// first `#set page(...)`, so it wins over a background set by the template.
+pub fn apply_page_background(source: &str, image: &str) -> String {
+    insert_after_page_rule(source, &page_background_rule(image))
+}
+
+/// Insert `rule` after the page setup override or the first page rule
+fn insert_after_page_rule(source: &str, rule: &str) -> String {
+    let mut lines: Vec<&str> = source.lines().collect();
+
+    let after = lines
+        .iter()
+        .position(|line| line.trim_end().ends_with(PAGE_SETUP_MARKER))
+        .or_else(|| page_rule_end(&lines));
+    match after {
+        Some(index) => lines.insert(index + 1, rule),
+        None => lines.insert(0, rule),
+    }
+
+    let mut result = lines.join("\n");
+    if source.ends_with('\n') {
+        result.push('\n');
+    }
+    result
+}
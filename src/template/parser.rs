@@ -11,15 +11,19 @@ pub enum TemplateNode {
     /// Raw text content
     Text(String),
 
-    /// Simple variable substitution: {{field}} or {{field.subfield}}
+    /// Simple variable substitution: {{field}} or {{field.subfield | filter}}
+    ///
+    /// Triple braces ({{{field}}}) set `raw` and skip Typst escaping.
     Variable {
         path: Vec<String>,
-        default: Option<String>,
+        filters: Vec<Filter>,
+        raw: bool,
     },
 
-    /// Conditional block: {{#if field}}...{{/if}}
+    /// Conditional block: {{#if field}}...{{/if}} or {{#if field == "value"}}...{{/if}}
     Conditional {
         path: Vec<String>,
+        comparison: Option<Comparison>,
         then_branch: Vec<TemplateNode>,
         else_branch: Vec<TemplateNode>,
     },
@@ -31,6 +35,47 @@ pub enum TemplateNode {
     },
 }
 
+/// A filter applied to a variable value, in the order written: {{title | uppercase}}
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    /// Fallback when the value is missing: `default: 'value'`
+    Default(String),
+    /// Convert to upper case: `uppercase`
+    Uppercase,
+    /// Convert to lower case: `lowercase`
+    Lowercase,
+    /// Upper-case the first character: `capitalize`
+    Capitalize,
+    /// Group thousands: `number` (1234567 -> 1,234,567)
+    Number,
+    /// Abbreviate with K/M/B suffixes: `number:compact` (1234567 -> 1.2M)
+    CompactNumber,
+    /// Group thousands and prefix a symbol: `currency:'$'` (1234.5 -> $1,234.50)
+    Currency(String),
+}
+
+/// Comparison operator in a conditional: {{#if path op literal}}
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    /// `==`
+    Eq,
+    /// `!=`
+    Ne,
+    /// `>`
+    Gt,
+    /// `<`
+    Lt,
+}
+
+/// The right-hand side of a conditional comparison
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    /// Comparison operator
+    pub op: CompareOp,
+    /// Literal value to compare the path against
+    pub value: String,
+}
+
 /// Parse a template string into a list of nodes
 pub fn parse_template(input: &str) -> Result<Vec<TemplateNode>, ParseError> {
     let mut parser = Parser::new(input);
@@ -54,6 +99,7 @@ fn collect_variables(nodes: &[TemplateNode], vars: &mut HashSet<String>) {
                 path,
                 then_branch,
                 else_branch,
+                ..
             } => {
                 vars.insert(path.join("."));
                 collect_variables(then_branch, vars);
@@ -69,6 +115,8 @@ fn collect_variables(nodes: &[TemplateNode], vars: &mut HashSet<String>) {
 }
 
 /// Parse error types
+///
+/// Positions are character (not byte) indices into the template.
 #[derive(Debug, Clone, PartialEq)]
 pub enum ParseError {
     /// Unclosed tag
@@ -129,8 +177,10 @@ impl<'a> Parser<'a> {
 
             // Check for template tags
             if self.remaining().starts_with("{{") {
-                if let Some(node) = self.parse_tag()? {
-                    nodes.push(node);
+                match self.parse_tag()? {
+                    Some(node) => nodes.push(node),
+                    // A closing tag that doesn't end this block would otherwise be revisited forever
+                    None => return Err(self.unexpected_closing_tag(end_tags)),
                 }
             } else {
                 // Parse raw text until next {{ or end
@@ -144,17 +194,37 @@ impl<'a> Parser<'a> {
         Ok(nodes)
     }
 
+    /// Error for a `{{/...}}` tag at `self.pos` that matches none of `end_tags`
+    fn unexpected_closing_tag(&self, end_tags: &[&str]) -> ParseError {
+        let found = match self.remaining().find("}}") {
+            Some(end) => &self.remaining()[..end + 2],
+            None => self.remaining(),
+        };
+        ParseError::UnexpectedClosingTag {
+            expected: end_tags
+                .last()
+                .map_or("end of template", |tag| tag)
+                .to_string(),
+            found: found.to_string(),
+        }
+    }
+
     fn parse_text(&mut self) -> String {
         let start = self.pos;
-        while self.pos < self.input.len() && !self.remaining().starts_with("{{") {
-            self.pos += 1;
-        }
+        let next_tag = self.remaining().find("{{");
+        self.pos = next_tag.map_or(self.input.len(), |offset| start + offset);
         self.input[start..self.pos].to_string()
     }
 
     fn parse_tag(&mut self) -> Result<Option<TemplateNode>, ParseError> {
         let tag_start = self.pos;
 
+        // Raw (unescaped) variable: {{{path}}}
+        if self.remaining().starts_with("{{{") {
+            self.pos += 3;
+            return self.parse_variable(tag_start, true);
+        }
+
         // Skip {{
         self.pos += 2;
 
@@ -175,10 +245,14 @@ impl<'a> Parser<'a> {
         }
 
         // Parse variable
-        self.parse_variable(tag_start)
+        self.parse_variable(tag_start, false)
     }
 
-    fn parse_variable(&mut self, tag_start: usize) -> Result<Option<TemplateNode>, ParseError> {
+    fn parse_variable(
+        &mut self,
+        tag_start: usize,
+        raw: bool,
+    ) -> Result<Option<TemplateNode>, ParseError> {
         self.skip_whitespace();
 
         let var_start = self.pos;
@@ -189,7 +263,7 @@ impl<'a> Parser<'a> {
             if c == '}' || c == '|' || c.is_whitespace() {
                 break;
             }
-            self.pos += 1;
+            self.pos += c.len_utf8();
         }
 
         // Parse path by splitting on dots
@@ -202,52 +276,120 @@ impl<'a> Parser<'a> {
 
         if path.is_empty() {
             return Err(ParseError::EmptyVariableName {
-                position: var_start,
+                position: self.char_position(var_start),
             });
         }
 
         self.skip_whitespace();
 
-        // Parse optional default value: | default: 'value'
-        let default = self.parse_default_value();
+        // Parse optional filters: | default: 'value' | uppercase
+        let filters = self.parse_filters()?;
 
         self.skip_whitespace();
 
-        // Expect }}
-        if !self.remaining().starts_with("}}") {
+        // Expect }} (or }}} for raw variables)
+        let closing = if raw { "}}}" } else { "}}" };
+        if !self.remaining().starts_with(closing) {
             return Err(ParseError::InvalidSyntax {
-                message: "Expected '}}' to close variable tag".to_string(),
-                position: tag_start,
+                message: format!("Expected '{}' to close variable tag", closing),
+                position: self.char_position(tag_start),
             });
         }
-        self.pos += 2;
+        self.pos += closing.len();
+
+        Ok(Some(TemplateNode::Variable { path, filters, raw }))
+    }
+
+    fn parse_filters(&mut self) -> Result<Vec<Filter>, ParseError> {
+        let mut filters = Vec::new();
+
+        while self.remaining().starts_with('|') {
+            self.pos += 1;
+            self.skip_whitespace();
+
+            // Read the filter name
+            let name_start = self.pos;
+            while self.pos < self.input.len() {
+                let c = self.current_char();
+                if !(c.is_alphanumeric() || c == '_') {
+                    break;
+                }
+                self.pos += c.len_utf8();
+            }
+            let name = &self.input[name_start..self.pos];
+
+            let filter = match name {
+                "default" => Filter::Default(self.parse_quoted_argument(name, name_start)?),
+                "uppercase" => Filter::Uppercase,
+                "lowercase" => Filter::Lowercase,
+                "capitalize" => Filter::Capitalize,
+                "number" => self.parse_number_filter(name_start)?,
+                "currency" => Filter::Currency(self.parse_quoted_argument(name, name_start)?),
+                _ => {
+                    return Err(ParseError::InvalidSyntax {
+                        message: format!("Unknown filter: '{}'", name),
+                        position: self.char_position(name_start),
+                    })
+                }
+            };
+            filters.push(filter);
+
+            self.skip_whitespace();
+        }
 
-        Ok(Some(TemplateNode::Variable { path, default }))
+        Ok(filters)
     }
 
-    fn parse_default_value(&mut self) -> Option<String> {
-        if !self.remaining().starts_with('|') {
-            return None;
+    /// Parse `number` or `number:compact`
+    fn parse_number_filter(&mut self, filter_start: usize) -> Result<Filter, ParseError> {
+        if !self.remaining().starts_with(':') {
+            return Ok(Filter::Number);
         }
-        self.pos += 1;
+        self.pos += 1; // skip ":"
         self.skip_whitespace();
 
-        if !self.remaining().starts_with("default:") {
-            return None;
+        let style_start = self.pos;
+        while self.pos < self.input.len() && self.current_char().is_alphanumeric() {
+            self.pos += self.current_char().len_utf8();
+        }
+
+        match &self.input[style_start..self.pos] {
+            "compact" => Ok(Filter::CompactNumber),
+            style => Err(ParseError::InvalidSyntax {
+                message: format!("Unknown number style: '{}'", style),
+                position: self.char_position(filter_start),
+            }),
         }
-        self.pos += 8; // skip "default:"
+    }
+
+    /// Parse the `: 'value'` argument of a filter such as `default` or `currency`
+    fn parse_quoted_argument(
+        &mut self,
+        filter_name: &str,
+        filter_start: usize,
+    ) -> Result<String, ParseError> {
+        let position = self.char_position(filter_start);
+        let invalid = || ParseError::InvalidSyntax {
+            message: format!("Expected quoted value after '{}:'", filter_name),
+            position,
+        };
+
+        if !self.remaining().starts_with(':') {
+            return Err(invalid());
+        }
+        self.pos += 1; // skip ":"
         self.skip_whitespace();
 
         // Parse quoted string
         let quote = self.current_char();
         if quote != '\'' && quote != '"' {
-            return None;
+            return Err(invalid());
         }
         self.pos += 1;
         let default_start = self.pos;
 
         while self.pos < self.input.len() && self.current_char() != quote {
-            self.pos += 1;
+            self.pos += self.current_char().len_utf8();
         }
 
         let value = self.input[default_start..self.pos].to_string();
@@ -256,7 +398,12 @@ impl<'a> Parser<'a> {
             self.pos += 1; // skip closing quote
         }
 
-        Some(value)
+        Ok(value)
+    }
+
+    /// Character index of a byte offset, as reported in `ParseError`
+    fn char_position(&self, byte_pos: usize) -> usize {
+        self.input[..byte_pos].chars().count()
     }
 
     fn current_char(&self) -> char {
@@ -273,7 +420,7 @@ impl<'a> Parser<'a> {
             if c.is_whitespace() || c == '}' {
                 break;
             }
-            self.pos += 1;
+            self.pos += c.len_utf8();
         }
         let block_type = &self.input[type_start..self.pos];
 
@@ -286,35 +433,103 @@ impl<'a> Parser<'a> {
             if c == '}' || c.is_whitespace() {
                 break;
             }
-            self.pos += 1;
+            self.pos += c.len_utf8();
         }
         let path_str = self.input[path_start..self.pos].trim();
         let path: Vec<String> = path_str.split('.').map(|s| s.to_string()).collect();
 
         self.skip_whitespace();
 
+        // Optional comparison: {{#if path op literal}}
+        let comparison = if block_type == "if" {
+            self.parse_comparison()?
+        } else {
+            None
+        };
+
         // Expect }}
         if !self.remaining().starts_with("}}") {
             return Err(ParseError::InvalidSyntax {
                 message: format!("Expected '}}' after block tag '{}'", block_type),
-                position: self.pos,
+                position: self.char_position(self.pos),
             });
         }
         self.pos += 2;
 
         match block_type {
-            "if" => self.parse_if_block(path, tag_start),
+            "if" => self.parse_if_block(path, comparison, tag_start),
             "each" => self.parse_each_block(path, tag_start),
             _ => Err(ParseError::InvalidSyntax {
                 message: format!("Unknown block type: {}", block_type),
-                position: type_start,
+                position: self.char_position(type_start),
             }),
         }
     }
 
+    fn parse_comparison(&mut self) -> Result<Option<Comparison>, ParseError> {
+        let op = if self.remaining().starts_with("==") {
+            CompareOp::Eq
+        } else if self.remaining().starts_with("!=") {
+            CompareOp::Ne
+        } else if self.remaining().starts_with('>') {
+            CompareOp::Gt
+        } else if self.remaining().starts_with('<') {
+            CompareOp::Lt
+        } else {
+            return Ok(None);
+        };
+        let op_start = self.pos;
+        self.pos += match op {
+            CompareOp::Eq | CompareOp::Ne => 2,
+            CompareOp::Gt | CompareOp::Lt => 1,
+        };
+        self.skip_whitespace();
+
+        // Literal is either a quoted string or a bare word/number
+        let quote = self.current_char();
+        let value = if quote == '\'' || quote == '"' {
+            self.pos += 1;
+            let value_start = self.pos;
+            while self.pos < self.input.len() && self.current_char() != quote {
+                self.pos += self.current_char().len_utf8();
+            }
+            if self.pos >= self.input.len() {
+                return Err(ParseError::InvalidSyntax {
+                    message: "Unterminated string in comparison".to_string(),
+                    position: self.char_position(value_start - 1),
+                });
+            }
+            let value = self.input[value_start..self.pos].to_string();
+            self.pos += 1; // skip closing quote
+            value
+        } else {
+            let value_start = self.pos;
+            while self.pos < self.input.len() {
+                let c = self.current_char();
+                if c == '}' || c.is_whitespace() {
+                    break;
+                }
+                self.pos += c.len_utf8();
+            }
+            self.input[value_start..self.pos].to_string()
+        };
+
+        if value.is_empty() && quote != '\'' && quote != '"' {
+            return Err(ParseError::InvalidSyntax {
+                message: "Expected a value after comparison operator".to_string(),
+                position: self.char_position(op_start),
+            });
+        }
+
+        self.skip_whitespace();
+
+        Ok(Some(Comparison { op, value }))
+    }
+
     fn parse_if_block(
         &mut self,
         path: Vec<String>,
+        comparison: Option<Comparison>,
         tag_start: usize,
     ) -> Result<Option<TemplateNode>, ParseError> {
         // Parse the then branch until {{else}} or {{/if}}
@@ -332,13 +547,14 @@ impl<'a> Parser<'a> {
         if !self.remaining().starts_with("{{/if}}") {
             return Err(ParseError::UnclosedTag {
                 tag: "if".to_string(),
-                position: tag_start,
+                position: self.char_position(tag_start),
             });
         }
         self.pos += 7; // skip {{/if}}
 
         Ok(Some(TemplateNode::Conditional {
             path,
+            comparison,
             then_branch,
             else_branch,
         }))
@@ -356,7 +572,7 @@ impl<'a> Parser<'a> {
         if !self.remaining().starts_with("{{/each}}") {
             return Err(ParseError::UnclosedTag {
                 tag: "each".to_string(),
-                position: tag_start,
+                position: self.char_position(tag_start),
             });
         }
         self.pos += 9; // skip {{/each}}
@@ -366,7 +582,7 @@ impl<'a> Parser<'a> {
 
     fn skip_whitespace(&mut self) {
         while self.pos < self.input.len() && self.current_char().is_whitespace() {
-            self.pos += 1;
+            self.pos += self.current_char().len_utf8();
         }
     }
 }
@@ -387,9 +603,9 @@ mod parser_tests {
         let result = parse_template("{{title}}").unwrap();
         assert_eq!(result.len(), 1);
         match &result[0] {
-            TemplateNode::Variable { path, default } => {
+            TemplateNode::Variable { path, filters, .. } => {
                 assert_eq!(path, &vec!["title".to_string()]);
-                assert!(default.is_none());
+                assert!(filters.is_empty());
             }
             _ => panic!("Expected Variable node"),
         }
@@ -400,9 +616,9 @@ mod parser_tests {
         let result = parse_template("{{style.primaryColor}}").unwrap();
         assert_eq!(result.len(), 1);
         match &result[0] {
-            TemplateNode::Variable { path, default } => {
+            TemplateNode::Variable { path, filters, .. } => {
                 assert_eq!(path, &vec!["style".to_string(), "primaryColor".to_string()]);
-                assert!(default.is_none());
+                assert!(filters.is_empty());
             }
             _ => panic!("Expected Variable node"),
         }
@@ -412,14 +628,93 @@ mod parser_tests {
     fn test_parse_variable_with_default() {
         let result = parse_template("{{title | default: 'Untitled'}}").unwrap();
         match &result[0] {
-            TemplateNode::Variable { path, default } => {
+            TemplateNode::Variable { path, filters, .. } => {
+                assert_eq!(path, &vec!["title".to_string()]);
+                assert_eq!(filters, &vec![Filter::Default("Untitled".to_string())]);
+            }
+            _ => panic!("Expected Variable node"),
+        }
+    }
+
+    #[test]
+    fn test_parse_variable_with_filter() {
+        let result = parse_template("{{title | uppercase}}").unwrap();
+        match &result[0] {
+            TemplateNode::Variable { path, filters, .. } => {
                 assert_eq!(path, &vec!["title".to_string()]);
-                assert_eq!(default, &Some("Untitled".to_string()));
+                assert_eq!(filters, &vec![Filter::Uppercase]);
+            }
+            _ => panic!("Expected Variable node"),
+        }
+    }
+
+    #[test]
+    fn test_parse_variable_with_chained_filters() {
+        let result = parse_template("{{subtitle | default: 'none yet' | capitalize}}").unwrap();
+        match &result[0] {
+            TemplateNode::Variable { filters, .. } => {
+                assert_eq!(
+                    filters,
+                    &vec![Filter::Default("none yet".to_string()), Filter::Capitalize]
+                );
             }
             _ => panic!("Expected Variable node"),
         }
     }
 
+    #[test]
+    fn test_parse_number_filters() {
+        let result =
+            parse_template("{{a | number}}{{b | number:compact}}{{c | currency: '€'}}").unwrap();
+        let filters: Vec<&Vec<Filter>> = result
+            .iter()
+            .map(|node| match node {
+                TemplateNode::Variable { filters, .. } => filters,
+                _ => panic!("Expected Variable node"),
+            })
+            .collect();
+        assert_eq!(filters[0], &vec![Filter::Number]);
+        assert_eq!(filters[1], &vec![Filter::CompactNumber]);
+        assert_eq!(filters[2], &vec![Filter::Currency("€".to_string())]);
+
+        assert!(parse_template("{{a | number:huge}}").is_err());
+        assert!(parse_template("{{a | currency}}").is_err());
+    }
+
+    #[test]
+    fn test_parse_unknown_filter() {
+        let result = parse_template("{{title | reverse}}");
+        assert!(matches!(result, Err(ParseError::InvalidSyntax { .. })));
+    }
+
+    #[test]
+    fn test_parse_raw_variable() {
+        let result = parse_template("{{{ metadata.style }}}").unwrap();
+        assert_eq!(result.len(), 1);
+        match &result[0] {
+            TemplateNode::Variable { path, raw, .. } => {
+                assert_eq!(path, &vec!["metadata".to_string(), "style".to_string()]);
+                assert!(raw);
+            }
+            _ => panic!("Expected Variable node"),
+        }
+    }
+
+    #[test]
+    fn test_parse_double_brace_is_not_raw() {
+        let result = parse_template("{{body}}").unwrap();
+        assert!(matches!(
+            &result[0],
+            TemplateNode::Variable { raw: false, .. }
+        ));
+    }
+
+    #[test]
+    fn test_parse_raw_variable_unclosed() {
+        let result = parse_template("{{{body}}");
+        assert!(matches!(result, Err(ParseError::InvalidSyntax { .. })));
+    }
+
     #[test]
     fn test_parse_if_block() {
         let result = parse_template("{{#if subtitle}}has subtitle{{/if}}").unwrap();
@@ -427,10 +722,12 @@ mod parser_tests {
         match &result[0] {
             TemplateNode::Conditional {
                 path,
+                comparison,
                 then_branch,
                 else_branch,
             } => {
                 assert_eq!(path, &vec!["subtitle".to_string()]);
+                assert!(comparison.is_none());
                 assert_eq!(then_branch.len(), 1);
                 assert!(else_branch.is_empty());
             }
@@ -454,6 +751,49 @@ mod parser_tests {
         }
     }
 
+    #[test]
+    fn test_parse_if_string_equality() {
+        let result = parse_template(r#"{{#if plan == "Professional"}}highlight{{/if}}"#).unwrap();
+        match &result[0] {
+            TemplateNode::Conditional {
+                path, comparison, ..
+            } => {
+                assert_eq!(path, &vec!["plan".to_string()]);
+                assert_eq!(
+                    comparison,
+                    &Some(Comparison {
+                        op: CompareOp::Eq,
+                        value: "Professional".to_string(),
+                    })
+                );
+            }
+            _ => panic!("Expected Conditional node"),
+        }
+    }
+
+    #[test]
+    fn test_parse_if_numeric_greater_than() {
+        let result = parse_template("{{#if stats.length > 2}}many{{else}}few{{/if}}").unwrap();
+        match &result[0] {
+            TemplateNode::Conditional { comparison, .. } => {
+                assert_eq!(
+                    comparison,
+                    &Some(Comparison {
+                        op: CompareOp::Gt,
+                        value: "2".to_string(),
+                    })
+                );
+            }
+            _ => panic!("Expected Conditional node"),
+        }
+    }
+
+    #[test]
+    fn test_parse_if_comparison_missing_value() {
+        let result = parse_template("{{#if plan ==}}x{{/if}}");
+        assert!(result.is_err());
+    }
+
     #[test]
     fn test_parse_each_block() {
         let result = parse_template("{{#each features}}item{{/each}}").unwrap();
@@ -498,4 +838,74 @@ mod parser_tests {
         let result = parse_template("{{#each items}}content");
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_multibyte_text_before_tags() {
+        let result = parse_template("café{{title}}").unwrap();
+        assert_eq!(result[0], TemplateNode::Text("café".to_string()));
+        assert!(matches!(&result[1], TemplateNode::Variable { path, .. } if path == &["title"]));
+
+        let result = parse_template("🎉{{#if x}}y{{/if}}").unwrap();
+        assert_eq!(result[0], TemplateNode::Text("🎉".to_string()));
+        match &result[1] {
+            TemplateNode::Conditional {
+                path, then_branch, ..
+            } => {
+                assert_eq!(path, &["x"]);
+                assert_eq!(then_branch, &[TemplateNode::Text("y".to_string())]);
+            }
+            _ => panic!("Expected Conditional node"),
+        }
+    }
+
+    #[test]
+    fn test_multibyte_inside_tags() {
+        // Non-ASCII names and non-breaking spaces inside the braces
+        let result = parse_template("{{\u{a0}naïve\u{a0}}}{{#each ünits}}-{{/each}}").unwrap();
+        assert!(matches!(&result[0], TemplateNode::Variable { path, .. } if path == &["naïve"]));
+        assert!(matches!(&result[1], TemplateNode::Loop { path, .. } if path == &["ünits"]));
+    }
+
+    #[test]
+    fn test_error_positions_are_char_indices() {
+        assert_eq!(
+            parse_template("é🎉{{#if x}}open"),
+            Err(ParseError::UnclosedTag {
+                tag: "if".to_string(),
+                position: 2,
+            })
+        );
+        assert_eq!(
+            parse_template("日本{{ }}"),
+            Err(ParseError::EmptyVariableName { position: 5 })
+        );
+    }
+
+    #[test]
+    fn test_stray_closing_tag_is_an_error() {
+        assert_eq!(
+            parse_template("a{{/if}}"),
+            Err(ParseError::UnexpectedClosingTag {
+                expected: "end of template".to_string(),
+                found: "{{/if}}".to_string(),
+            })
+        );
+        assert_eq!(
+            parse_template("{{#if x}}{{/each}}{{/if}}"),
+            Err(ParseError::UnexpectedClosingTag {
+                expected: "{{/if}}".to_string(),
+                found: "{{/each}}".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_truncated_multibyte_templates_never_panic() {
+        let template = "é{{#if ü == 'ñ'}}🎉{{x | default: '日本'}}{{else}}{{{ÿ}}}{{/if}}{{#each ö}}{{this}}{{/each}}";
+        for (end, _) in template.char_indices() {
+            let _ = parse_template(&template[..end]);
+            let _ = parse_template(&template[end..]);
+        }
+        assert!(parse_template(template).is_ok());
+    }
 }
@@ -1,9 +1,14 @@
 //! Integration tests for the template module
 
 use super::engine::TemplateEngine;
+use super::escape::{annotate_escapes, RenderedSpan};
+use super::page::apply_page_setup;
 use super::parser::parse_template;
 use super::validation::validate_template;
-use crate::data::{ContactInfo, Section, SlickSheetData, Stat, StyleHints};
+use super::variables::{available_variables, matching_variables};
+use crate::data::{
+    ContactInfo, Orientation, PageSetup, PageSize, Section, SlickSheetData, Stat, StyleHints,
+};
 
 #[test]
 fn test_full_product_sheet_template() {
@@ -122,6 +127,32 @@ fn test_default_values() {
     assert_eq!(result, "Color: \\#000000, Font: Arial");
 }
 
+#[test]
+fn test_gradient_stops_resolve() {
+    let template = "{{#if style.gradientStart}}{{style.gradientStart}} -> {{style.gradientStop}}{{else}}flat{{/if}}";
+
+    let data = SlickSheetData::new("Test");
+    assert_eq!(TemplateEngine::render(template, &data).unwrap(), "flat");
+
+    let data = SlickSheetData::new("Test").with_style(StyleHints {
+        background_gradient: Some(("#1a1a2e".to_string(), "#e94560".to_string())),
+        ..Default::default()
+    });
+    let result = TemplateEngine::render(template, &data).unwrap();
+    assert_eq!(result, "\\#1a1a2e -> \\#e94560");
+}
+
+#[test]
+fn test_gradient_parses_from_camel_case_json() {
+    let data: SlickSheetData = serde_json::from_str(
+        r##"{"title": "Test", "style": {"backgroundGradient": ["#000000", "#ffffff"]}}"##,
+    )
+    .unwrap();
+
+    let result = TemplateEngine::render("{{style.gradient_stop}}", &data).unwrap();
+    assert_eq!(result, "\\#ffffff");
+}
+
 #[test]
 fn test_stats_rendering() {
     let template = "Stats count: {{stats.length}}";
@@ -135,6 +166,31 @@ fn test_stats_rendering() {
     assert_eq!(result, "Stats count: 3");
 }
 
+#[test]
+fn test_stat_icons_render_text_and_images() {
+    let template = "{{#each stats}}[{{#if this.icon}}{{this.icon}} {{/if}}{{this.value}}]{{/each}}";
+
+    let data = SlickSheetData::default()
+        .with_stat(Stat::new("50%", "Growth").with_icon("\u{1F4C8}"))
+        .with_stat(Stat::new("100", "Users").with_icon("img_ab12cd.png"))
+        .with_stat(Stat::new("$1M", "Revenue"));
+
+    let result = TemplateEngine::render(template, &data).unwrap();
+    assert_eq!(
+        result,
+        "[\u{1F4C8} 50%][#image(\"img_ab12cd.png\", height: 1em) 100][\\$1M]"
+    );
+}
+
+#[test]
+fn test_stat_text_icon_is_escaped() {
+    let template = "{{#each stats}}{{this.icon}}{{/each}}";
+    let data = SlickSheetData::default().with_stat(Stat::new("1", "One").with_icon("#1"));
+
+    let result = TemplateEngine::render(template, &data).unwrap();
+    assert_eq!(result, "\\#1");
+}
+
 #[test]
 fn test_empty_arrays() {
     let template = "{{#if features}}Has features{{else}}No features{{/if}}";
@@ -191,3 +247,351 @@ fn test_whitespace_preservation() {
     let result = TemplateEngine::render(template, &data).unwrap();
     assert_eq!(result, "Line 1\nMiddle\nLine 3");
 }
+
+#[test]
+fn test_image_section_rendering() {
+    let template = r#"{{#each sections}}{{#if this.imageRef}}#image("{{this.imageRef}}"){{#if this.caption}} {{this.caption}}{{/if}}{{/if}}{{/each}}"#;
+
+    let data = SlickSheetData::new("Test")
+        .with_section(Section::text("Intro", "No image"))
+        .with_section(Section::image(
+            "Office",
+            "img_abc123.png",
+            Some("HQ_1".to_string()),
+        ));
+
+    let result = TemplateEngine::render(template, &data).unwrap();
+    assert_eq!(result, r#"#image("img_abc123.png") HQ\_1"#);
+}
+
+#[test]
+fn test_image_ref_cannot_break_out_of_string() {
+    let template = r#"{{#each sections}}#image("{{this.imageRef}}"){{/each}}"#;
+    let data = SlickSheetData::new("Test").with_section(Section::image(
+        "Office",
+        r#"x.png") #read("secret.txt") #("#,
+        None,
+    ));
+
+    let result = TemplateEngine::render(template, &data).unwrap();
+    assert_eq!(result, r#"#image("x.png\") #read(\"secret.txt\") #(")"#);
+    // Paths without quotes pass through unchanged
+    let data = SlickSheetData::new("Test").with_section(Section::image(
+        "Office",
+        "photos/office.png",
+        None,
+    ));
+    let result = TemplateEngine::render(template, &data).unwrap();
+    assert_eq!(result, r#"#image("photos/office.png")"#);
+}
+
+#[test]
+fn test_contact_social_handles() {
+    let data = SlickSheetData::new("Test").with_contact(ContactInfo {
+        linkedin: Some("acme-corp".to_string()),
+        twitter: Some("acme".to_string()),
+        ..Default::default()
+    });
+
+    let template =
+        "{{contact.linkedin}} / {{contact.twitter}} / {{contact.instagram | default: 'none'}}";
+    let result = TemplateEngine::render(template, &data).unwrap();
+    assert_eq!(result, "acme-corp / acme / none");
+}
+
+#[test]
+fn test_contact_social_loop() {
+    let mut contact = ContactInfo::with_email("hi@example.com");
+    contact
+        .social
+        .insert("mastodon".to_string(), "acme.social".to_string());
+    contact
+        .social
+        .insert("github".to_string(), "acme".to_string());
+    let data = SlickSheetData::new("Test").with_contact(contact);
+
+    let template = "{{#each contact.social}}{{this.platform}}={{this.handle}};{{/each}}";
+    let result = TemplateEngine::render(template, &data).unwrap();
+    assert_eq!(result, "github=acme;mastodon=acme.social;");
+}
+
+#[test]
+fn test_contact_without_social_fields_deserializes() {
+    let json = r#"{"title": "Test", "contact": {"email": "hi@example.com"}}"#;
+    let data: SlickSheetData = serde_json::from_str(json).unwrap();
+    let contact = data.contact.unwrap();
+
+    assert!(contact.linkedin.is_none());
+    assert!(contact.social.is_empty());
+    assert!(!serde_json::to_string(&contact).unwrap().contains("social"));
+}
+
+#[test]
+fn test_page_a4_landscape_dimensions() {
+    let template = "#set page(width: {{page.width}}, height: {{page.height}})";
+    let data =
+        SlickSheetData::new("T").with_page(PageSetup::new(PageSize::A4, Orientation::Landscape));
+
+    let result = TemplateEngine::render(template, &data).unwrap();
+    assert_eq!(result, "#set page(width: 297mm, height: 210mm)");
+}
+
+#[test]
+fn test_page_defaults_to_letter_portrait() {
+    let template = "{{page.width}}x{{page.height}} {{page.margin | default: '1in'}} {{#if page}}set{{else}}unset{{/if}}";
+
+    let result = TemplateEngine::render(template, &SlickSheetData::new("T")).unwrap();
+    assert_eq!(result, "8.5inx11in 1in unset");
+}
+
+#[test]
+fn test_page_margin_only_renders_valid_lengths() {
+    let template = "{{page.margin | default: '1in'}}";
+    let page = PageSetup::new(PageSize::Legal, Orientation::Portrait);
+
+    let data = SlickSheetData::new("T").with_page(page.clone().with_margin("2cm"));
+    assert_eq!(TemplateEngine::render(template, &data).unwrap(), "2cm");
+
+    let data = SlickSheetData::new("T").with_page(page.with_margin("1in); #panic()"));
+    assert_eq!(TemplateEngine::render(template, &data).unwrap(), "1in");
+}
+
+#[test]
+fn test_page_parses_from_json() {
+    let data: SlickSheetData = serde_json::from_str(
+        r#"{"title": "T", "page": {"size": "a4", "orientation": "landscape", "margin": "1cm"}}"#,
+    )
+    .unwrap();
+    let page = data.page.expect("page should parse");
+    assert_eq!(page.dimensions(), ("297mm", "210mm"));
+    assert_eq!(page.margin.as_deref(), Some("1cm"));
+}
+
+#[test]
+fn test_apply_page_setup_inserts_after_page_rule() {
+    let source = "#set page(width: 8.5in, height: 11in, margin: 0.5in)\n= Hello\n";
+    let page = PageSetup::new(PageSize::A4, Orientation::Portrait);
+
+    let result = apply_page_setup(source, &page);
+    assert_eq!(
+        result,
+        "#set page(width: 8.5in, height: 11in, margin: 0.5in)\n#set page(width: 210mm, height: 297mm) // page setup\n= Hello\n"
+    );
+}
+
+#[test]
+fn test_apply_page_setup_replaces_previous_override() {
+    let source = "#set page(\n  width: 8.5in,\n  height: 11in,\n)\n= Hello";
+    let a4 = apply_page_setup(source, &PageSetup::new(PageSize::A4, Orientation::Portrait));
+    assert!(
+        a4.starts_with("#set page(\n  width: 8.5in,\n  height: 11in,\n)\n#set page(width: 210mm")
+    );
+
+    let landscape = PageSetup::new(PageSize::Letter, Orientation::Landscape).with_margin("1cm");
+    let result = apply_page_setup(&a4, &landscape);
+    assert_eq!(result.matches("// page setup").count(), 1);
+    assert!(result.contains("#set page(width: 11in, height: 8.5in, margin: 1cm) // page setup"));
+    assert!(result.ends_with("= Hello"));
+}
+
+#[test]
+fn test_apply_export_preset_keeps_margin_and_override() {
+    use super::page::{apply_export_preset, ExportPreset};
+
+    let source = "#set page(width: 8.5in, height: 11in, margin: 0.5in)\n= Hi\n";
+    assert_eq!(
+        apply_export_preset(source, ExportPreset::InstagramStory),
+        "#set page(width: 8.5in, height: 11in, margin: 0.5in)\n#set page(width: 810pt, height: 1440pt)\n= Hi\n"
+    );
+
+    let page = PageSetup::new(PageSize::A4, Orientation::Portrait);
+    let overridden = apply_page_setup(source, &page);
+    let result = apply_export_preset(&overridden, ExportPreset::InstagramSquare);
+    assert!(result.ends_with("// page setup\n#set page(width: 810pt, height: 810pt)\n= Hi\n"));
+}
+
+#[test]
+fn test_apply_page_setup_without_page_rule() {
+    let page = PageSetup::new(PageSize::Legal, Orientation::Portrait);
+    assert_eq!(
+        apply_page_setup("= Hello", &page),
+        "#set page(width: 8.5in, height: 14in) // page setup\n= Hello"
+    );
+}
+
+/// Shorthand for an expected span
+fn span(text: &str, escaped: bool) -> RenderedSpan {
+    RenderedSpan {
+        text: text.to_string(),
+        escaped,
+    }
+}
+
+#[test]
+fn test_annotate_escapes_marks_inserted_escapes() {
+    let data = SlickSheetData::new("C# & *bold*");
+    let rendered = TemplateEngine::render("= {{title}}", &data).unwrap();
+
+    assert_eq!(
+        annotate_escapes(&rendered),
+        vec![
+            span("= C", false),
+            span("\\#", true),
+            span(" & ", false),
+            span("\\*", true),
+            span("bold", false),
+            span("\\*", true),
+        ]
+    );
+}
+
+#[test]
+fn test_annotate_escapes_reads_escaped_backslashes_left_to_right() {
+    // A literal `\#` from user text renders as `\\\#`
+    let rendered = TemplateEngine::render("{{title}}", &SlickSheetData::new("\\#")).unwrap();
+
+    assert_eq!(
+        annotate_escapes(&rendered),
+        vec![span("\\\\", true), span("\\#", true)]
+    );
+}
+
+#[test]
+fn test_annotate_escapes_leaves_other_backslashes_plain() {
+    // Line breaks and unicode escapes are not interpolation escapes
+    assert_eq!(
+        annotate_escapes("a \\\nb \\u{2014}"),
+        vec![span("a \\\nb \\u{2014}", false)]
+    );
+    assert_eq!(
+        annotate_escapes("trailing \\"),
+        vec![span("trailing \\", false)]
+    );
+    assert!(annotate_escapes("").is_empty());
+}
+
+// ============================================================================
+// Lint Tests
+// ============================================================================
+
+#[test]
+fn test_lint_flags_unguarded_image() {
+    use super::{lint, Lint};
+
+    let lints = lint("#image(\"{{images.logo}}\", width: 2cm)\n= {{title}}");
+    assert_eq!(
+        lints,
+        vec![Lint::UnguardedImage {
+            path: "images.logo".to_string()
+        }]
+    );
+    assert!(lints[0].to_string().contains("{{#if images.logo}}"));
+
+    // The else branch runs exactly when the image is missing
+    let in_else = "{{#if images.logo}}ok{{else}}#image(\"{{images.logo}}\"){{/if}}";
+    assert_eq!(lint(in_else).len(), 1);
+}
+
+#[test]
+fn test_lint_flags_escaped_variable_in_string() {
+    use super::{lint, Lint};
+
+    let lints = lint("#text(fill: rgb(\"{{style.primaryColor}}\"))[{{title}}]");
+    assert_eq!(
+        lints,
+        vec![Lint::EscapedInString {
+            path: "style.primaryColor".to_string()
+        }]
+    );
+    assert!(lints[0].to_string().contains("{{{style.primaryColor}}}"));
+}
+
+#[test]
+fn test_lint_clean_template() {
+    use super::lint;
+
+    let template = concat!(
+        "#set page(width: {{page.width}}, height: {{page.height}})\n",
+        "{{#if images.logo}}#image(\"{{images.logo}}\", width: 2cm){{/if}}\n",
+        "#text(fill: rgb(\"{{{style.primaryColor}}}\"))[{{title}}]\n",
+        "#link(\"cmd://edit/body\")[He said \"{{body}}\"]\n",
+    );
+    assert_eq!(lint(template), vec![]);
+}
+
+// ============================================================================
+// Variable List Tests
+// ============================================================================
+
+#[test]
+fn test_available_variables_lists_supported_paths() {
+    let paths: Vec<_> = available_variables().iter().map(|v| v.path).collect();
+
+    for expected in ["title", "contact.email", "stats.length"] {
+        assert!(paths.contains(&expected), "missing {}", expected);
+    }
+    for unsupported in [
+        "contact.fax",
+        "stats.count",
+        "style.background",
+        "page.color",
+    ] {
+        assert!(!paths.contains(&unsupported), "unexpected {}", unsupported);
+    }
+    assert!(available_variables()
+        .iter()
+        .all(|v| !v.description.is_empty()));
+}
+
+#[test]
+fn test_available_variables_resolve_in_engine() {
+    let mut contact = ContactInfo::with_email("a@example.com");
+    contact.phone = Some("555".to_string());
+    contact.website = Some("example.com".to_string());
+    contact.address = Some("1 Main St".to_string());
+    contact.linkedin = Some("in/acme".to_string());
+    contact.twitter = Some("@acme".to_string());
+    contact.instagram = Some("acme".to_string());
+    contact
+        .social
+        .insert("mastodon".to_string(), "@acme@social".to_string());
+    let data = SlickSheetData::new("Title")
+        .with_subtitle("Subtitle")
+        .with_body("Body")
+        .with_section(Section::text("Heading", "Content"))
+        .with_feature("Feature")
+        .with_stat(Stat::new("1", "One"))
+        .with_contact(contact)
+        .with_style(StyleHints {
+            primary_color: Some("#111111".to_string()),
+            accent_color: Some("#222222".to_string()),
+            font_family: Some("Inter".to_string()),
+            background_gradient: Some(("#000000".to_string(), "#ffffff".to_string())),
+        })
+        .with_page(PageSetup::new(PageSize::Letter, Orientation::Portrait).with_margin("1in"));
+
+    // Collections and loop locals are used as blocks; everything else is a value
+    let blocks = ["sections", "features", "stats", "contact.social"];
+    for variable in available_variables() {
+        let template = match variable.path {
+            "this" | "@index" => "{{#each features}}{{this}}{{@index}}{{/each}}".to_string(),
+            "contact" => "{{#if contact}}yes{{/if}}".to_string(),
+            path if blocks.contains(&path) => format!("{{{{#each {}}}}}x{{{{/each}}}}", path),
+            path => format!("{{{{{}}}}}", path),
+        };
+        let rendered = TemplateEngine::render(&template, &data).unwrap();
+        assert!(!rendered.is_empty(), "{} did not resolve", variable.path);
+    }
+}
+
+#[test]
+fn test_matching_variables_filters_by_prefix() {
+    let contact: Vec<_> = matching_variables("contact.")
+        .iter()
+        .map(|v| v.path)
+        .collect();
+    assert!(contact.contains(&"contact.email"));
+    assert!(contact.iter().all(|path| path.starts_with("contact.")));
+    assert_eq!(matching_variables("").len(), available_variables().len());
+    assert!(matching_variables("nope").is_empty());
+}
@@ -14,9 +14,9 @@ pub enum TemplateValidationError {
     #[error("Template parse error: {0}")]
     ParseError(String),
 
-    /// Unknown variable in template
-    #[error("Unknown variable: {0}")]
-    UnknownVariable(String),
+    /// Variable whose top-level field is not part of the data schema
+    #[error("Unknown variable: {name}")]
+    UnknownVariable { name: String },
 
     /// Template is empty
     #[error("Template cannot be empty")]
@@ -47,12 +47,23 @@ const KNOWN_VARIABLES: &[&str] = &[
     "style.accent_color",
     "style.fontFamily",
     "style.font_family",
+    // Page setup fields
+    "page",
+    "page.width",
+    "page.height",
+    "page.margin",
+    "page.size",
+    "page.orientation",
     // Nested contact fields
     "contact",
     "contact.email",
     "contact.phone",
     "contact.website",
     "contact.address",
+    "contact.linkedin",
+    "contact.twitter",
+    "contact.instagram",
+    "contact.social",
     // Array lengths
     "sections.length",
     "features.length",
@@ -67,12 +78,27 @@ const KNOWN_VARIABLES: &[&str] = &[
     "items",
     "rows",
     "columns",
+    "imageRef",
+    "caption",
     // Stat fields (used in loops)
     "value",
     "label",
     "color",
+    "icon",
+    // Social fields (used in contact.social loops)
+    "platform",
+    "handle",
 ];
 
+/// Top-level data fields a template may reference in strict validation
+pub const SCHEMA_FIELDS: &[&str] = &[
+    "title", "subtitle", "body", "features", "stats", "sections", "contact", "style", "page",
+    "images",
+];
+
+/// Variables that only exist inside `{{#each}}` blocks
+const LOOP_LOCALS: &[&str] = &["this", "@index"];
+
 /// Validate a template string
 ///
 /// Checks for:
@@ -100,6 +126,39 @@ pub fn validate_template(template: &str) -> Result<Vec<String>, Vec<TemplateVali
     Ok(warnings)
 }
 
+/// Validate a template in strict mode, rejecting variables outside the schema
+///
+/// Only the top-level segment of each path is checked against `known_fields`
+/// (so `contact.email` passes when `contact` is known); loop locals such as
+/// `this.heading` and `@index` are always accepted.
+pub fn validate_template_against_schema(
+    template: &str,
+    known_fields: &[&str],
+) -> Result<(), Vec<TemplateValidationError>> {
+    validate_template(template)?;
+
+    let nodes = parse_template(template)
+        .map_err(|e| vec![TemplateValidationError::ParseError(e.to_string())])?;
+
+    let mut unknown: Vec<String> = extract_variables(&nodes)
+        .into_iter()
+        .filter(|var| {
+            let top = var.split('.').next().unwrap_or_default();
+            !known_fields.contains(&top) && !LOOP_LOCALS.contains(&top)
+        })
+        .collect();
+
+    if unknown.is_empty() {
+        return Ok(());
+    }
+
+    unknown.sort();
+    Err(unknown
+        .into_iter()
+        .map(|name| TemplateValidationError::UnknownVariable { name })
+        .collect())
+}
+
 /// Check if a variable path is known
 fn is_known_variable(var: &str) -> bool {
     KNOWN_VARIABLES.contains(&var)
@@ -171,6 +230,35 @@ mod validation_tests {
         assert!(warnings[0].contains("unknownField"));
     }
 
+    #[test]
+    fn test_strict_accepts_known_variable() {
+        let result = validate_template_against_schema(
+            "{{title}} {{#each stats}}{{this.value}} {{@index}}{{/each}}",
+            SCHEMA_FIELDS,
+        );
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_strict_rejects_unknown_top_level_variable() {
+        let result = validate_template_against_schema("{{titel}}", SCHEMA_FIELDS);
+        assert_eq!(
+            result.unwrap_err(),
+            vec![TemplateValidationError::UnknownVariable {
+                name: "titel".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_strict_accepts_nested_path() {
+        let result = validate_template_against_schema(
+            "{{#if contact}}{{contact.email}}{{/if}} {{style.primaryColor}} {{images.logo}}",
+            SCHEMA_FIELDS,
+        );
+        assert!(result.is_ok());
+    }
+
     #[test]
     fn test_template_with_all_features() {
         let template = r#"
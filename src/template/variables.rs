@@ -0,0 +1,65 @@
+//! Variable paths the template engine can resolve, for autocompletion
+//!
+//! The list mirrors `TemplateEngine::resolve_path`; snake_case aliases such
+//! as `style.primary_color`, and open-ended paths like `images.<name>` or
+//! metadata keys, are left out.
+
+/// A variable path a template can use, with a short description
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VariableInfo {
+    /// Dotted path as written between `{{` and `}}`
+    pub path: &'static str,
+    /// What the path resolves to
+    pub description: &'static str,
+}
+
+const fn var(path: &'static str, description: &'static str) -> VariableInfo {
+    VariableInfo { path, description }
+}
+
+const VARIABLES: &[VariableInfo] = &[
+    var("title", "Document title"),
+    var("subtitle", "Subtitle, if set"),
+    var("body", "Main body text"),
+    var("sections", "Content sections, for {{#each}}"),
+    var("sections.length", "Number of sections"),
+    var("features", "Feature list, for {{#each}}"),
+    var("features.length", "Number of features"),
+    var("stats", "Statistics, for {{#each}}"),
+    var("stats.length", "Number of statistics"),
+    var("contact", "Contact details, for {{#if}}"),
+    var("contact.email", "Contact email address"),
+    var("contact.phone", "Contact phone number"),
+    var("contact.website", "Website URL"),
+    var("contact.address", "Postal address"),
+    var("contact.linkedin", "LinkedIn profile"),
+    var("contact.twitter", "Twitter/X handle"),
+    var("contact.instagram", "Instagram handle"),
+    var("contact.social", "Other social profiles, for {{#each}}"),
+    var("style.primaryColor", "Primary color (hex)"),
+    var("style.accentColor", "Accent color (hex)"),
+    var("style.fontFamily", "Font family name"),
+    var("style.gradientStart", "Background gradient start color"),
+    var("style.gradientStop", "Background gradient stop color"),
+    var("page.width", "Page width, e.g. 8.5in"),
+    var("page.height", "Page height, e.g. 11in"),
+    var("page.margin", "Page margin, if set"),
+    var("page.size", "Page size name, e.g. Letter"),
+    var("page.orientation", "portrait or landscape"),
+    var("this", "Current item inside {{#each}}"),
+    var("@index", "Zero-based index inside {{#each}}"),
+];
+
+/// Every variable path the engine resolves, in display order
+pub fn available_variables() -> Vec<VariableInfo> {
+    VARIABLES.to_vec()
+}
+
+/// Variables whose path starts with `prefix`, for an autocomplete list
+pub fn matching_variables(prefix: &str) -> Vec<VariableInfo> {
+    VARIABLES
+        .iter()
+        .filter(|variable| variable.path.starts_with(prefix))
+        .copied()
+        .collect()
+}
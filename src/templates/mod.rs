@@ -9,8 +9,14 @@
 #![allow(dead_code)]
 
 #[cfg(test)]
+#[allow(clippy::useless_vec)]
 mod tests;
 
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use crate::world::VirtualWorld;
+
 /// Template category for organization
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum TemplateCategory {
@@ -50,12 +56,56 @@ pub struct Template {
     pub description: &'static str,
     /// Category for organization
     pub category: TemplateCategory,
-    /// Optional preview SVG (base64 encoded)
+    /// Optional pre-rendered preview SVG (rendered on demand when `None`)
     pub preview_svg: Option<&'static str>,
+    /// Extra guidance for the AI when editing documents made from this template
+    pub ai_hint: Option<&'static str>,
     /// Typst source code
     pub source: &'static str,
 }
 
+thread_local! {
+    /// Rendered previews keyed by template id
+    static PREVIEW_CACHE: RefCell<HashMap<&'static str, String>> = RefCell::new(HashMap::new());
+}
+
+impl Template {
+    /// Render the template's first page to SVG for use as a thumbnail
+    ///
+    /// Thumbnails are inlined into the gallery, so the `cmd://` edit links
+    /// are left out rather than overlaid as focusable anchors.
+    pub fn render_preview(&self) -> Result<String, Vec<String>> {
+        match self.preview_svg {
+            Some(svg) => Ok(svg.to_string()),
+            None => VirtualWorld::compile_to_svg_and_links(self.source).map(|(svg, _)| svg),
+        }
+    }
+
+    /// Whether the template matches a gallery search
+    ///
+    /// `query` is matched case-insensitively against the name and
+    /// description; a `None` category matches every category.
+    pub fn matches(&self, query: &str, category: Option<TemplateCategory>) -> bool {
+        if category.is_some_and(|category| category != self.category) {
+            return false;
+        }
+        let query = query.trim().to_lowercase();
+        query.is_empty()
+            || self.name.to_lowercase().contains(&query)
+            || self.description.to_lowercase().contains(&query)
+    }
+
+    /// Like `render_preview`, but each template is only compiled once
+    pub fn cached_preview(&self) -> Result<String, Vec<String>> {
+        if let Some(svg) = PREVIEW_CACHE.with(|cache| cache.borrow().get(self.id).cloned()) {
+            return Ok(svg);
+        }
+        let svg = self.render_preview()?;
+        PREVIEW_CACHE.with(|cache| cache.borrow_mut().insert(self.id, svg.clone()));
+        Ok(svg)
+    }
+}
+
 /// All available templates
 pub static TEMPLATES: &[Template] = &[
     // 1. Product Sheet
@@ -65,6 +115,9 @@ pub static TEMPLATES: &[Template] = &[
         description: "Single product showcase with specifications",
         category: TemplateCategory::Marketing,
         preview_svg: None,
+        ai_hint: Some(
+            "This is a product sheet: focus on one product. Put concrete benefits in features and measurable specifications (dimensions, weight, materials, warranty) in stats.",
+        ),
         source: r##"#set page(width: 8.5in, height: 11in, margin: 0.75in)
 #set text(font: "Inter", size: 11pt)
 
@@ -119,6 +172,9 @@ pub static TEMPLATES: &[Template] = &[
         description: "Date, time, and location focused event announcement",
         category: TemplateCategory::Event,
         preview_svg: None,
+        ai_hint: Some(
+            "This is an event flyer: the date, time and location matter most. Put them in stats, keep the title short and punchy, and end with how to register or attend.",
+        ),
         source: r##"#set page(width: 8.5in, height: 11in, margin: 0.5in)
 #set text(font: "Inter", size: 11pt)
 
@@ -183,6 +239,9 @@ Lorem ipsum dolor sit amet, consectetur adipiscing elit. Sed do eiusmod tempor i
         description: "Executive summary style document",
         category: TemplateCategory::Marketing,
         preview_svg: None,
+        ai_hint: Some(
+            "This is an executive one-pager: lead with the single most important takeaway, keep the body to a short summary, and make every feature a crisp, scannable point.",
+        ),
         source: r##"#set page(width: 8.5in, height: 11in, margin: 0.75in)
 #set text(font: "Inter", size: 10pt)
 
@@ -248,6 +307,9 @@ We provide a revolutionary approach that addresses these challenges through:
         description: "Side-by-side feature comparison",
         category: TemplateCategory::Data,
         preview_svg: None,
+        ai_hint: Some(
+            "This is a comparison chart: compare options side by side on the same criteria. Use features for the compared criteria and keep wording parallel across items.",
+        ),
         source: r##"#set page(width: 8.5in, height: 11in, margin: 0.75in)
 #set text(font: "Inter", size: 10pt)
 
@@ -307,6 +369,9 @@ Our solution provides the most comprehensive feature set at a competitive price
         description: "Problem, solution, and results format",
         category: TemplateCategory::Business,
         preview_svg: None,
+        ai_hint: Some(
+            "This is a case study: structure the content as problem, solution and results. Results should be specific numbers in stats (percent improvement, time saved, revenue).",
+        ),
         source: r##"#set page(width: 8.5in, height: 11in, margin: 0.75in)
 #set text(font: "Inter", size: 11pt)
 
@@ -379,6 +444,9 @@ We implemented a comprehensive solution including:
         description: "Staff and team member highlights",
         category: TemplateCategory::Business,
         preview_svg: None,
+        ai_hint: Some(
+            "This is a team profile: features list the people, each as \"Name - Role\" with one concrete responsibility or achievement. Use real names from the request.",
+        ),
         source: r##"#set page(width: 8.5in, height: 11in, margin: 0.75in)
 #set text(font: "Inter", size: 11pt)
 
@@ -448,6 +516,9 @@ We implemented a comprehensive solution including:
         description: "Tiered pricing display",
         category: TemplateCategory::Business,
         preview_svg: None,
+        ai_hint: Some(
+            "This is a pricing table: each tier needs a name, a price and what is included. Use stats for prices and features for what sets the tiers apart.",
+        ),
         source: r##"#set page(width: 8.5in, height: 11in, margin: 0.75in)
 #set text(font: "Inter", size: 11pt)
 
@@ -547,6 +618,9 @@ We implemented a comprehensive solution including:
         description: "Multi-section content layout",
         category: TemplateCategory::Marketing,
         preview_svg: None,
+        ai_hint: Some(
+            "This is a newsletter: organize content into short, dated sections with clear headlines. Keep each section to a few sentences and the tone conversational.",
+        ),
         source: r##"#set page(width: 8.5in, height: 11in, margin: 0.5in)
 #set text(font: "Inter", size: 10pt)
 
@@ -637,6 +711,9 @@ We implemented a comprehensive solution including:
         description: "Data visualization focused layout",
         category: TemplateCategory::Data,
         preview_svg: None,
+        ai_hint: Some(
+            "This is an infographic: lead with numbers. Stats should be numeric values with short labels, and text should stay brief so the figures stand out.",
+        ),
         source: r##"#set page(width: 8.5in, height: 11in, margin: 0.5in)
 #set text(font: "Inter", size: 10pt)
 
@@ -656,6 +733,8 @@ We implemented a comprehensive solution including:
   // Stat 1
   rect(fill: rgb("#1a1a2e"), inset: 1em, radius: 8pt)[
     #align(center)[
+      #text(fill: rgb("#4ecca3"), size: 20pt)[↗]
+      #v(0.25em)
       #text(fill: rgb("#4ecca3"), size: 36pt, weight: "bold")[78%]
       #v(0.25em)
       #text(fill: white)[Growth Rate]
@@ -665,6 +744,8 @@ We implemented a comprehensive solution including:
   // Stat 2
   rect(fill: rgb("#1a1a2e"), inset: 1em, radius: 8pt)[
     #align(center)[
+      #text(fill: rgb("#e94560"), size: 20pt)[★]
+      #v(0.25em)
       #text(fill: rgb("#e94560"), size: 36pt, weight: "bold")[2.5M]
       #v(0.25em)
       #text(fill: white)[Active Users]
@@ -674,6 +755,8 @@ We implemented a comprehensive solution including:
   // Stat 3
   rect(fill: rgb("#1a1a2e"), inset: 1em, radius: 8pt)[
     #align(center)[
+      #text(fill: rgb("#ffd93d"), size: 20pt)[◆]
+      #v(0.25em)
       #text(fill: rgb("#ffd93d"), size: 36pt, weight: "bold")[\$4.2B]
       #v(0.25em)
       #text(fill: white)[Market Size]
@@ -736,6 +819,9 @@ We implemented a comprehensive solution including:
         description: "Clean, typography-focused design",
         category: TemplateCategory::Minimal,
         preview_svg: None,
+        ai_hint: Some(
+            "This is a minimal, typography-focused design: use few words, prefer a strong title and one short paragraph, and leave features and stats empty unless asked.",
+        ),
         source: r##"#set page(width: 8.5in, height: 11in, margin: 1in)
 #set text(font: "Inter", size: 12pt)
 #set par(leading: 0.8em)
@@ -779,6 +865,17 @@ Nemo enim ipsam voluptatem quia voluptas sit aspernatur aut odit aut fugit, sed
     },
 ];
 
+/// Prepend the AI hint of template `template_id`, if it has one, to a system prompt
+pub fn system_prompt_with_hint(system_prompt: &str, template_id: Option<&str>) -> String {
+    let hint = template_id
+        .and_then(|id| TEMPLATES.iter().find(|t| t.id == id))
+        .and_then(|t| t.ai_hint);
+    match hint {
+        Some(hint) => format!("TEMPLATE GUIDANCE: {}\n\n{}", hint, system_prompt),
+        None => system_prompt.to_string(),
+    }
+}
+
 /// Template gallery for browsing and selecting templates
 #[derive(Debug)]
 pub struct TemplateGallery {
@@ -817,6 +914,14 @@ impl TemplateGallery {
             .collect()
     }
 
+    /// Get templates matching a search query and optional category
+    pub fn search(&self, query: &str, category: Option<TemplateCategory>) -> Vec<&Template> {
+        self.templates
+            .iter()
+            .filter(|t| t.matches(query, category))
+            .collect()
+    }
+
     /// Get all unique categories
     pub fn categories(&self) -> Vec<TemplateCategory> {
         use std::collections::HashSet;
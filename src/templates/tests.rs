@@ -1,6 +1,6 @@
 //! Tests for the templates module
 
-use super::{Template, TemplateCategory, TemplateGallery, TEMPLATES};
+use super::{system_prompt_with_hint, Template, TemplateCategory, TemplateGallery, TEMPLATES};
 use crate::world::VirtualWorld;
 
 // ============================================================================
@@ -15,6 +15,7 @@ fn test_template_has_required_fields() {
         description: "A test template",
         category: TemplateCategory::Marketing,
         preview_svg: None,
+        ai_hint: None,
         source: "= Hello",
     };
 
@@ -89,6 +90,48 @@ fn test_gallery_filter_by_category() {
     }
 }
 
+/// Ids of the templates matching a search
+fn search_ids(query: &str, category: Option<TemplateCategory>) -> Vec<&'static str> {
+    TEMPLATES
+        .iter()
+        .filter(|t| t.matches(query, category))
+        .map(|t| t.id)
+        .collect()
+}
+
+#[test]
+fn test_template_search_defaults_to_all() {
+    assert_eq!(search_ids("", None).len(), TEMPLATES.len());
+    assert_eq!(search_ids("   ", None).len(), TEMPLATES.len());
+}
+
+#[test]
+fn test_template_search_matches_name_case_insensitively() {
+    assert_eq!(search_ids("PRICING", None), vec!["pricing-table"]);
+    assert!(search_ids("no such template", None).is_empty());
+}
+
+#[test]
+fn test_template_search_filters_by_category() {
+    let data = search_ids("", Some(TemplateCategory::Data));
+    assert_eq!(data, vec!["comparison-chart", "infographic"]);
+}
+
+#[test]
+fn test_template_search_combines_query_and_category() {
+    assert_eq!(
+        search_ids("sheet", Some(TemplateCategory::Marketing)),
+        vec!["product-sheet"]
+    );
+    assert!(search_ids("pricing", Some(TemplateCategory::Marketing)).is_empty());
+    assert_eq!(
+        TemplateGallery::new()
+            .search("pricing", Some(TemplateCategory::Business))
+            .len(),
+        1
+    );
+}
+
 #[test]
 fn test_gallery_all_ids_unique() {
     let gallery = TemplateGallery::new();
@@ -119,6 +162,56 @@ fn test_all_templates_compile() {
     }
 }
 
+#[test]
+fn test_all_templates_compile_in_a4_landscape() {
+    use crate::data::{Orientation, PageSetup, PageSize};
+    use crate::template::apply_page_setup;
+
+    let page = PageSetup::new(PageSize::A4, Orientation::Landscape);
+    for template in TEMPLATES.iter() {
+        let source = apply_page_setup(template.source, &page);
+        let svg = VirtualWorld::compile_to_svg(&source).unwrap_or_else(|errors| {
+            panic!(
+                "Template '{}' failed in A4 landscape: {:?}",
+                template.id, errors
+            )
+        });
+        // 297mm x 210mm is 841.89pt x 595.28pt
+        assert!(
+            svg.contains(r#"viewBox="0 0 841.88"#),
+            "Template '{}' did not switch to A4 landscape",
+            template.id
+        );
+    }
+}
+
+#[test]
+fn test_all_templates_render_preview() {
+    for template in TEMPLATES.iter() {
+        let svg = template.render_preview().unwrap_or_else(|errors| {
+            panic!("Template '{}' preview failed: {:?}", template.id, errors)
+        });
+        assert!(
+            svg.contains("<svg"),
+            "Template '{}' preview is not SVG",
+            template.id
+        );
+        assert!(
+            !svg.contains("cmd://"),
+            "Template '{}' preview has edit links",
+            template.id
+        );
+    }
+}
+
+#[test]
+fn test_cached_preview_matches_render() {
+    let template = &TEMPLATES[0];
+    let first = template.cached_preview().expect("preview should render");
+    assert_eq!(first, template.render_preview().unwrap());
+    assert_eq!(template.cached_preview().unwrap(), first);
+}
+
 #[test]
 fn test_product_sheet_template_compiles() {
     let gallery = TemplateGallery::new();
@@ -177,3 +270,35 @@ fn test_templates_use_fonts() {
 
     assert!(has_font_setup, "At least one template should set fonts");
 }
+
+// ============================================================================
+// AI Hint Tests
+// ============================================================================
+
+#[test]
+fn test_all_templates_have_ai_hints() {
+    for template in TEMPLATES {
+        assert!(
+            template.ai_hint.is_some_and(|hint| !hint.is_empty()),
+            "Template {} is missing an AI hint",
+            template.id
+        );
+    }
+}
+
+#[test]
+fn test_system_prompt_with_hint_prepends_template_hint() {
+    for template in TEMPLATES {
+        let prompt = system_prompt_with_hint("Edit the JSON.", Some(template.id));
+        let hint = template.ai_hint.unwrap();
+
+        assert!(prompt.contains(hint));
+        assert!(prompt.find(hint) < prompt.find("Edit the JSON."));
+    }
+}
+
+#[test]
+fn test_system_prompt_with_hint_without_template() {
+    assert_eq!(system_prompt_with_hint("Base", None), "Base");
+    assert_eq!(system_prompt_with_hint("Base", Some("unknown")), "Base");
+}
@@ -0,0 +1,63 @@
+//! Cooperative compilation deadline
+//!
+//! Typst has no cancellation hook, but it calls back into the world to load
+//! sources, files and fonts. Those calls check a shared deadline and fail
+//! once it has passed, so an overrunning compile stops at its next world
+//! access instead of running to the end. comemo re-validates memoized
+//! results against the world, so failed calls never poison later compiles.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Deadline shared by a world and its clones, in milliseconds since the epoch
+///
+/// Unset (infinite) unless a budgeted compile is running.
+#[derive(Debug, Clone)]
+pub(super) struct Deadline(Arc<AtomicU64>);
+
+impl Default for Deadline {
+    fn default() -> Self {
+        Self(Arc::new(AtomicU64::new(f64::INFINITY.to_bits())))
+    }
+}
+
+impl Deadline {
+    /// Start a budget of `timeout` from now
+    pub(super) fn start(&self, timeout: Duration) {
+        self.set(now_ms() + timeout.as_secs_f64() * 1000.0);
+    }
+
+    /// Remove the deadline
+    pub(super) fn clear(&self) {
+        self.set(f64::INFINITY);
+    }
+
+    /// End the budget now, so the compile stops at its next world access
+    pub(super) fn expire(&self) {
+        self.set(f64::NEG_INFINITY);
+    }
+
+    /// Whether the deadline has passed
+    pub(super) fn is_expired(&self) -> bool {
+        now_ms() >= f64::from_bits(self.0.load(Ordering::Relaxed))
+    }
+
+    fn set(&self, deadline_ms: f64) {
+        self.0.store(deadline_ms.to_bits(), Ordering::Relaxed);
+    }
+}
+
+/// Wall-clock time in milliseconds (`Instant` is unavailable on WASM)
+#[cfg(target_arch = "wasm32")]
+fn now_ms() -> f64 {
+    js_sys::Date::now()
+}
+
+/// Wall-clock time in milliseconds (`Instant` is unavailable on WASM)
+#[cfg(not(target_arch = "wasm32"))]
+fn now_ms() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0.0, |elapsed| elapsed.as_secs_f64() * 1000.0)
+}
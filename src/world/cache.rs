@@ -0,0 +1,142 @@
+//! Compile cache for the editor preview
+//!
+//! Recompiling identical source (tab switches, Refresh clicks) is wasted
+//! work, so compiled SVGs (or richer previews) are kept for a few recent
+//! inputs and reused. Images can be replaced under the same ID, so the key
+//! also covers the bytes of every image the source references.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::VecDeque;
+use std::hash::{Hash, Hasher};
+
+use crate::images::ImageCache;
+
+/// Default number of compiled outputs kept
+pub const DEFAULT_COMPILE_CACHE_CAPACITY: usize = 8;
+
+/// Small LRU cache of compiled output keyed by source and referenced assets
+#[derive(Debug, Clone)]
+pub struct CompileCache<T = String> {
+    /// Most recently used entries last
+    entries: VecDeque<(u64, T)>,
+    capacity: usize,
+}
+
+impl<T: Clone> Default for CompileCache<T> {
+    fn default() -> Self {
+        Self::new(DEFAULT_COMPILE_CACHE_CAPACITY)
+    }
+}
+
+impl<T: Clone> CompileCache<T> {
+    /// Create a cache holding at most `capacity` outputs
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::with_capacity(capacity),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Return the cached output for this input, or compile and cache it
+    ///
+    /// `asset_ids` are the image (and font) IDs available to the compile;
+    /// their order does not matter. Failed compiles are not cached.
+    pub fn get_or_compile<S, F>(
+        &mut self,
+        source: &str,
+        asset_ids: &[S],
+        compile: F,
+    ) -> Result<T, Vec<String>>
+    where
+        S: AsRef<str>,
+        F: FnOnce() -> Result<T, Vec<String>>,
+    {
+        let key = cache_key(source, asset_ids);
+
+        if let Some(index) = self.entries.iter().position(|(k, _)| *k == key) {
+            let entry = self.entries.remove(index).expect("index is in bounds");
+            let output = entry.1.clone();
+            self.entries.push_back(entry);
+            return Ok(output);
+        }
+
+        let output = compile()?;
+        if self.entries.len() >= self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((key, output.clone()));
+        Ok(output)
+    }
+
+    /// Number of cached outputs
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if the cache is empty
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Drop all cached outputs
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// Hash the source together with the sorted asset IDs
+fn cache_key<S: AsRef<str>>(source: &str, asset_ids: &[S]) -> u64 {
+    let mut ids: Vec<&str> = asset_ids.iter().map(AsRef::as_ref).collect();
+    ids.sort_unstable();
+
+    let mut hasher = DefaultHasher::new();
+    source.hash(&mut hasher);
+    ids.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// IDs of the images a source loads with `image("...")`, in first-use order
+///
+/// Paths are image files such as `img_abc.png`; the extension is dropped to
+/// get the ID. Paths built at runtime (not string literals) are not found.
+pub fn referenced_image_ids(source: &str) -> Vec<String> {
+    let mut ids: Vec<String> = Vec::new();
+    for (start, _) in source.match_indices("image(") {
+        // Skip calls like `myimage(` whose name only ends in "image"
+        let is_call = source[..start]
+            .chars()
+            .next_back()
+            .is_none_or(|c| !(c.is_alphanumeric() || c == '_' || c == '-'));
+        if !is_call {
+            continue;
+        }
+        let Some(path) = source[start + "image(".len()..]
+            .trim_start()
+            .strip_prefix('"')
+            .and_then(|rest| rest.split_once('"'))
+            .map(|(path, _)| path)
+            .filter(|path| !path.is_empty())
+        else {
+            continue;
+        };
+        let id = path.rsplit_once('.').map_or(path, |(stem, _)| stem);
+        if !ids.iter().any(|known| known == id) {
+            ids.push(id.to_string());
+        }
+    }
+    ids
+}
+
+/// Cache key parts (`id@hash`) for the cached images a source references
+///
+/// Passed along with the asset IDs, so replacing an image's bytes under the
+/// same ID misses the cache.
+pub fn image_version_keys(source: &str, images: &ImageCache) -> Vec<String> {
+    referenced_image_ids(source)
+        .into_iter()
+        .filter_map(|id| {
+            let hash = images.content_hash(&id)?;
+            Some(format!("{}@{:016x}", id, hash))
+        })
+        .collect()
+}
@@ -1,7 +1,9 @@
 //! Font loading for the VirtualWorld
 //!
-//! This module handles embedded fonts and font book creation for Typst.
+//! This module handles embedded fonts, user-uploaded fonts and font book
+//! creation for Typst.
 
+use typst::foundations::Bytes;
 use typst::text::{Font, FontBook};
 
 /// Embedded font data
@@ -13,6 +15,7 @@ const FONT_DATA: &[&[u8]] = &[
 ];
 
 /// Font loader that manages embedded fonts
+#[derive(Clone)]
 pub struct FontLoader {
     fonts: Vec<Font>,
 }
@@ -33,6 +36,24 @@ impl FontLoader {
         &self.fonts
     }
 
+    /// Parse a TTF/OTF (or collection) and register every face it contains
+    ///
+    /// Returns the family names of the added faces, or an error if the bytes
+    /// contain no parseable font.
+    pub fn add_font(&mut self, data: impl Into<Bytes>) -> Result<Vec<String>, String> {
+        let faces: Vec<Font> = Font::iter(data.into()).collect();
+        if faces.is_empty() {
+            return Err("Unparseable font file: expected a TTF or OTF font".to_string());
+        }
+
+        let families = faces
+            .iter()
+            .map(|font| font.info().family.clone())
+            .collect();
+        self.fonts.extend(faces);
+        Ok(families)
+    }
+
     /// Get a font by index
     pub fn font(&self, index: usize) -> Option<Font> {
         self.fonts.get(index).cloned()
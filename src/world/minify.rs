@@ -0,0 +1,280 @@
+//! SVG minification for web embedding
+//!
+//! Typst indents its SVG output and restates inherited fills on every glyph,
+//! which adds up on text-heavy pages. The document is copied token by token
+//! (like `images::sanitize_svg`), so only whitespace, comments and
+//! attributes that cannot change the rendering are dropped.
+
+use xmlparser::{ElementEnd, Token, Tokenizer};
+
+/// Options for `minify_svg_with_options`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MinifyOptions {
+    /// Drop the `cmd://` edit link overlays, which only work in the editor
+    ///
+    /// External link overlays are kept either way.
+    pub remove_edit_overlays: bool,
+}
+
+/// Elements whose text content is rendered, so whitespace inside is kept
+const TEXT_ELEMENTS: &[&str] = &["text", "tspan", "textPath", "title", "desc", "style"];
+
+/// Inherited presentation attributes and their initial values
+const INHERITED_DEFAULTS: &[(&str, &str)] = &[
+    ("fill", "#000000"),
+    ("fill-rule", "nonzero"),
+    ("fill-opacity", "1"),
+    ("stroke", "none"),
+    ("stroke-width", "1"),
+    ("stroke-opacity", "1"),
+];
+
+/// Containers whose content is rendered where it is referenced, so it
+/// inherits from the referencing element rather than its ancestors
+const REFERENCED_CONTAINERS: &[&str] = &["defs", "symbol", "clipPath", "mask", "pattern", "marker"];
+
+/// Elements whose `x`/`y` default to 0
+const ZERO_POSITION_ELEMENTS: &[&str] = &["use", "rect", "image"];
+
+/// Minify an SVG, keeping every link overlay
+pub fn minify_svg(svg: &str) -> String {
+    minify_svg_with_options(svg, &MinifyOptions::default())
+}
+
+/// Minify an SVG using the given options
+///
+/// Collapses whitespace between tags, drops comments and attributes that
+/// restate a default or inherited value, and optionally removes edit
+/// overlays. Input that does not parse is returned unminified, though edit
+/// overlays are still removed when asked.
+pub fn minify_svg_with_options(svg: &str, options: &MinifyOptions) -> String {
+    minify(svg, options).unwrap_or_else(|| {
+        if options.remove_edit_overlays {
+            strip_edit_overlays(svg)
+        } else {
+            svg.to_string()
+        }
+    })
+}
+
+/// Remove `<a href="cmd://...">...</a>` overlays without parsing the SVG
+///
+/// Overlays never nest, so each one ends at the next `</a>`; one that is
+/// cut off runs to the end of the input.
+fn strip_edit_overlays(svg: &str) -> String {
+    const EDIT_LINK: &str = r#"<a href="cmd://"#;
+    const CLOSE: &str = "</a>";
+
+    let mut result = String::with_capacity(svg.len());
+    let mut rest = svg;
+    while let Some(start) = rest.find(EDIT_LINK) {
+        result.push_str(&rest[..start]);
+        rest = rest[start..]
+            .find(CLOSE)
+            .map_or("", |end| &rest[start + end + CLOSE.len()..]);
+    }
+    result.push_str(rest);
+    result
+}
+
+fn minify(svg: &str, options: &MinifyOptions) -> Option<String> {
+    let mut output = String::with_capacity(svg.len());
+    // Start tag being read, written out once its attributes have been seen
+    let mut pending: Option<PendingTag> = None;
+    // Open elements that were written out, innermost last
+    let mut open: Vec<OpenElement> = Vec::new();
+    // Depth inside a removed overlay (0 when copying)
+    let mut skip_depth = 0_usize;
+    // A stylesheet can set any inherited value, so none of them are known.
+    // Classes alone don't count: Typst puts one on every group.
+    let has_stylesheet = Tokenizer::from(svg).any(
+        |token| matches!(token, Ok(Token::ElementStart { local, .. }) if local.as_str() == "style"),
+    );
+
+    for token in Tokenizer::from(svg) {
+        match token.ok()? {
+            Token::ElementStart { local, span, .. } => {
+                if skip_depth > 0 {
+                    skip_depth += 1;
+                } else {
+                    pending = Some(PendingTag::new(
+                        local.as_str(),
+                        span.as_str(),
+                        has_stylesheet,
+                    ));
+                }
+            }
+            Token::Attribute {
+                prefix,
+                local,
+                value,
+                span,
+            } => {
+                if let Some(tag) = pending.as_mut() {
+                    tag.attribute(
+                        prefix.as_str(),
+                        local.as_str(),
+                        value.as_str(),
+                        span.as_str(),
+                        open.last(),
+                    );
+                }
+            }
+            Token::ElementEnd { end, span } => {
+                if let Some(tag) = pending.take() {
+                    if options.remove_edit_overlays && tag.is_edit_overlay {
+                        // An empty element ends here; an open one at its close tag
+                        skip_depth = usize::from(matches!(end, ElementEnd::Open));
+                        continue;
+                    }
+                    output.push_str(&tag.markup);
+                    output.push_str(span.as_str());
+                    if matches!(end, ElementEnd::Open) {
+                        open.push(tag.into_open(open.last()));
+                    }
+                } else if skip_depth > 0 {
+                    if matches!(end, ElementEnd::Empty | ElementEnd::Close(..)) {
+                        skip_depth -= 1;
+                    }
+                } else {
+                    output.push_str(span.as_str());
+                    open.pop();
+                }
+            }
+            Token::Text { text } if skip_depth == 0 => {
+                let in_text = open.last().is_some_and(|element| element.in_text);
+                if in_text || !text.as_str().trim().is_empty() {
+                    output.push_str(text.as_str());
+                }
+            }
+            Token::Cdata { span, .. } if skip_depth == 0 => output.push_str(span.as_str()),
+            Token::Comment { .. } => {}
+            Token::Declaration { span, .. }
+            | Token::ProcessingInstruction { span, .. }
+            | Token::DtdStart { span, .. }
+            | Token::EmptyDtd { span, .. }
+            | Token::EntityDeclaration { span, .. }
+            | Token::DtdEnd { span } => output.push_str(span.as_str()),
+            _ => {}
+        }
+    }
+
+    // A truncated document ends inside a tag or with elements still open
+    (pending.is_none() && open.is_empty() && skip_depth == 0).then_some(output)
+}
+
+/// An element that has been written out and not yet closed
+struct OpenElement {
+    /// Whether text inside is rendered (inside `<text>`, `<title>`, ...)
+    in_text: bool,
+    /// Inherited presentation values in effect for children, or `None` when
+    /// CSS or a referenced container makes them unknown
+    inherited: Option<Vec<(&'static str, String)>>,
+}
+
+impl OpenElement {
+    /// The value a child inherits for a tracked attribute, if known
+    fn inherited_value(&self, name: &str) -> Option<&str> {
+        let inherited = self.inherited.as_ref()?;
+        inherited
+            .iter()
+            .find(|(known, _)| *known == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// A start tag with the attributes that survived minification
+struct PendingTag {
+    name: String,
+    /// `<name` followed by the kept attributes
+    markup: String,
+    /// Tracked inherited attributes set on this element
+    set: Vec<(&'static str, String)>,
+    has_style: bool,
+    /// Whether the document has a `<style>` element
+    has_stylesheet: bool,
+    is_edit_overlay: bool,
+}
+
+impl PendingTag {
+    fn new(local: &str, start: &str, has_stylesheet: bool) -> Self {
+        Self {
+            name: local.to_string(),
+            markup: start.to_string(),
+            set: Vec::new(),
+            has_style: false,
+            has_stylesheet,
+            is_edit_overlay: false,
+        }
+    }
+
+    fn attribute(
+        &mut self,
+        prefix: &str,
+        local: &str,
+        value: &str,
+        span: &str,
+        parent: Option<&OpenElement>,
+    ) {
+        if prefix.is_empty() {
+            if self.name == "a" && local == "href" && value.starts_with("cmd://") {
+                self.is_edit_overlay = true;
+            }
+            if local == "style" {
+                self.has_style = true;
+            }
+            if let Some(&(name, initial)) =
+                INHERITED_DEFAULTS.iter().find(|(name, _)| *name == local)
+            {
+                let inherited = match parent {
+                    _ if self.has_stylesheet => None,
+                    Some(parent) => parent.inherited_value(name),
+                    None => Some(initial),
+                };
+                self.set.push((name, value.to_string()));
+                if inherited == Some(value) {
+                    return;
+                }
+            }
+            if (local == "x" || local == "y")
+                && value == "0"
+                && ZERO_POSITION_ELEMENTS.contains(&self.name.as_str())
+            {
+                return;
+            }
+        }
+        self.markup.push(' ');
+        self.markup.push_str(span);
+    }
+
+    /// Track this element as open, with the values its children inherit
+    fn into_open(self, parent: Option<&OpenElement>) -> OpenElement {
+        let in_text = TEXT_ELEMENTS.contains(&self.name.as_str())
+            || parent.is_some_and(|parent| parent.in_text);
+        let inherited = match parent {
+            _ if self.has_style
+                || self.has_stylesheet
+                || REFERENCED_CONTAINERS.contains(&self.name.as_str()) =>
+            {
+                None
+            }
+            Some(parent) => parent.inherited.clone(),
+            None => Some(
+                INHERITED_DEFAULTS
+                    .iter()
+                    .map(|&(name, initial)| (name, initial.to_string()))
+                    .collect(),
+            ),
+        };
+        let inherited = inherited.map(|mut values| {
+            for (name, value) in self.set {
+                match values.iter_mut().find(|(known, _)| *known == name) {
+                    Some(entry) => entry.1 = value,
+                    None => values.push((name, value)),
+                }
+            }
+            values
+        });
+        OpenElement { in_text, inherited }
+    }
+}
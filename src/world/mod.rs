@@ -3,24 +3,37 @@
 //! This module implements the `typst::World` trait to enable Typst compilation
 //! in the browser environment.
 
+mod budget;
+mod cache;
 mod fonts;
+mod incremental;
+mod minify;
 
 #[cfg(test)]
 mod tests;
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::OnceLock;
+use std::time::Duration;
 
 use typst::diag::{FileError, FileResult, SourceDiagnostic};
-use typst::foundations::{Bytes, Datetime};
-use typst::layout::{Abs, Frame, FrameItem, Point, Size, Transform};
-use typst::model::Destination;
-use typst::syntax::{FileId, Source, VirtualPath};
+use typst::foundations::{Bytes, Datetime, NativeElement, Selector, StyleChain};
+use typst::layout::{Frame, FrameItem, Point, Size, Transform};
+use typst::model::{Destination, Document, HeadingElem};
+use typst::syntax::{FileId, Source, Span, VirtualPath};
 use typst::text::{Font, FontBook};
 use typst::utils::LazyHash;
 use typst::{Library, World};
 
+use crate::template::PLACEHOLDER_IMAGE_PATH;
+
+use budget::Deadline;
+pub use cache::{
+    image_version_keys, referenced_image_ids, CompileCache, DEFAULT_COMPILE_CACHE_CAPACITY,
+};
 use fonts::FontLoader;
+pub use incremental::IncrementalCompiler;
+pub use minify::{minify_svg, minify_svg_with_options, MinifyOptions};
 
 /// Static library instance
 static LIBRARY: OnceLock<LazyHash<Library>> = OnceLock::new();
@@ -30,7 +43,59 @@ fn library() -> &'static LazyHash<Library> {
     LIBRARY.get_or_init(|| LazyHash::new(Library::default()))
 }
 
+/// Error reported when a document compiles to nothing visible
+pub const EMPTY_DOCUMENT_MESSAGE: &str = "Document is empty \u{2014} add some content";
+
+/// UTF-8 encoding of the byte order mark some editors prepend to files
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Strip a leading byte order mark from source text
+///
+/// Typst treats the mark as content, so a BOM would otherwise stop a
+/// heading or rule on the first line from being recognised.
+pub fn strip_bom(source: &str) -> &str {
+    source.strip_prefix('\u{FEFF}').unwrap_or(source)
+}
+
+/// Decode Typst source bytes read from a file
+///
+/// A leading byte order mark is dropped. Invalid UTF-8 is reported with the
+/// file name and the byte offset of the first bad byte.
+pub fn decode_source(name: &str, bytes: &[u8]) -> Result<String, String> {
+    let body = bytes.strip_prefix(UTF8_BOM).unwrap_or(bytes);
+    std::str::from_utf8(body).map(str::to_string).map_err(|e| {
+        let offset = bytes.len() - body.len() + e.valid_up_to();
+        format!(
+            "{} is not valid UTF-8 (invalid byte at offset {})",
+            name, offset
+        )
+    })
+}
+
+/// First page of a compiled document, for the editor preview
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompiledPreview {
+    /// SVG of the first page, with link overlays
+    pub svg: String,
+    /// Total number of pages, so overflow onto later pages can be shown
+    pub page_count: usize,
+}
+
+/// Number of pages in a compiled document
+pub fn page_count(doc: &Document) -> usize {
+    doc.pages.len()
+}
+
+/// Grey crossed-out frame served for `PLACEHOLDER_IMAGE_PATH`
+const PLACEHOLDER_IMAGE_SVG: &[u8] = br##"<svg xmlns="http://www.w3.org/2000/svg" width="160" height="120" viewBox="0 0 160 120"><rect x="1" y="1" width="158" height="118" fill="#eeeeee" stroke="#999999" stroke-width="2" stroke-dasharray="6 4"/><path d="M56 36 L104 84 M104 36 L56 84" stroke="#999999" stroke-width="4" stroke-linecap="round"/></svg>"##;
+
+/// Default time budget for a single compilation in the browser
+///
+/// Native worlds have no budget unless one is set with `with_timeout`.
+pub const DEFAULT_COMPILE_TIMEOUT: Duration = Duration::from_secs(10);
+
 /// VirtualWorld implements typst::World for in-browser Typst compilation
+#[derive(Clone)]
 pub struct VirtualWorld {
     /// The main source file
     main: Source,
@@ -40,13 +105,17 @@ pub struct VirtualWorld {
     font_loader: FontLoader,
     /// Font book
     font_book: LazyHash<FontBook>,
+    /// Time budget for `compile` (None disables the deadline)
+    timeout: Option<Duration>,
+    /// Deadline of the compile in progress, checked on world accesses
+    deadline: Deadline,
 }
 
 impl VirtualWorld {
     /// Create a new VirtualWorld with the given main source content
     pub fn new(source: &str) -> Self {
         let main_id = FileId::new(None, VirtualPath::new("main.typ"));
-        let main = Source::new(main_id, source.to_string());
+        let main = Source::new(main_id, strip_bom(source).to_string());
 
         let font_loader = FontLoader::new();
         let font_book = LazyHash::new(font_loader.font_book());
@@ -56,60 +125,234 @@ impl VirtualWorld {
             files: HashMap::new(),
             font_loader,
             font_book,
+            timeout: cfg!(target_arch = "wasm32").then_some(DEFAULT_COMPILE_TIMEOUT),
+            deadline: Deadline::default(),
         }
     }
 
+    /// Set the compilation time budget (None disables the deadline)
+    ///
+    /// Typst has no way to interrupt evaluation, so the budget stops a
+    /// compile only at its next source, file or font access. Native builds
+    /// also compile on a separate thread and return once the budget is spent,
+    /// but a compile stuck in pure evaluation keeps that thread running in
+    /// the background until it finishes. In the browser, such a compile
+    /// blocks the UI thread until it finishes.
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
     /// Set the main source content
-    #[allow(dead_code)]
+    ///
+    /// Only the changed region is reparsed, so untouched syntax nodes keep
+    /// their identity and Typst's memoized results stay valid.
     pub fn set_source(&mut self, source: &str) {
-        let main_id = FileId::new(None, VirtualPath::new("main.typ"));
-        self.main = Source::new(main_id, source.to_string());
+        self.main.replace(strip_bom(source));
     }
 
     /// Add a file to the virtual file system
-    #[allow(dead_code)]
     pub fn add_file(&mut self, path: &str, content: impl Into<Bytes>) {
         let id = FileId::new(None, VirtualPath::new(path));
         self.files.insert(id, content.into());
     }
 
+    /// Register project partials (extra `.typ` files keyed by path)
+    ///
+    /// The main source can then `#include` or `#import` them, e.g.
+    /// `#include "partials/header.typ"`.
+    pub fn add_partials(&mut self, partials: &BTreeMap<String, String>) {
+        for (path, content) in partials {
+            self.add_file(path, content.as_bytes().to_vec());
+        }
+    }
+
+    /// Register a TTF/OTF font so the source can use it by family name
+    ///
+    /// Returns the family names that were added.
+    pub fn add_font(&mut self, data: impl Into<Bytes>) -> Result<Vec<String>, String> {
+        let families = self.font_loader.add_font(data)?;
+        self.font_book = LazyHash::new(self.font_loader.font_book());
+        Ok(families)
+    }
+
     /// Compile the current source to a Document
+    ///
+    /// Fails with "Compilation timed out after Ns" when the time budget is
+    /// exceeded.
     pub fn compile(&self) -> Result<typst::model::Document, Vec<SourceDiagnostic>> {
+        match self.timeout {
+            Some(timeout) => self.compile_with_timeout(timeout),
+            None => self.compile_unbounded(),
+        }
+    }
+
+    fn compile_unbounded(&self) -> Result<typst::model::Document, Vec<SourceDiagnostic>> {
         let result = typst::compile(self);
         result.output.map_err(|errs| errs.into_iter().collect())
     }
 
+    /// Compile, stopping at the next world access once `timeout` has passed
+    ///
+    /// A compile that finishes late but without errors keeps its result;
+    /// only errors caused by the expired deadline become a timeout error.
+    fn compile_cooperatively(
+        &self,
+        timeout: Duration,
+    ) -> Result<typst::model::Document, Vec<SourceDiagnostic>> {
+        self.deadline.start(timeout);
+        let result = self.compile_unbounded();
+        let expired = self.deadline.is_expired();
+        self.deadline.clear();
+        match result {
+            Err(_) if expired => Err(vec![timeout_error(timeout)]),
+            result => result,
+        }
+    }
+
+    /// Compile on a watchdog thread, giving up once the deadline passes
+    ///
+    /// The compile thread also checks the deadline cooperatively, and is told
+    /// to stop when the watchdog gives up. It can't be stopped between world
+    /// accesses, so the thread is left to finish on its own (see
+    /// `with_timeout`).
+    #[cfg(not(target_arch = "wasm32"))]
+    fn compile_with_timeout(
+        &self,
+        timeout: Duration,
+    ) -> Result<typst::model::Document, Vec<SourceDiagnostic>> {
+        use std::sync::mpsc::{self, RecvTimeoutError};
+
+        let world = Self {
+            deadline: Deadline::default(),
+            ..self.clone()
+        };
+        let deadline = world.deadline.clone();
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = sender.send(world.compile_cooperatively(timeout));
+        });
+
+        match receiver.recv_timeout(timeout) {
+            Ok(result) => result,
+            Err(RecvTimeoutError::Timeout) => {
+                deadline.expire();
+                Err(vec![timeout_error(timeout)])
+            }
+            Err(RecvTimeoutError::Disconnected) => Err(vec![SourceDiagnostic::error(
+                Span::detached(),
+                "Compilation failed unexpectedly",
+            )]),
+        }
+    }
+
+    /// Compile on the UI thread with a cooperative deadline
+    ///
+    /// The WASM UI thread cannot be preempted, so the budget is only checked
+    /// when Typst loads a source, file or font; pure evaluation in between
+    /// still runs to completion.
+    #[cfg(target_arch = "wasm32")]
+    fn compile_with_timeout(
+        &self,
+        timeout: Duration,
+    ) -> Result<typst::model::Document, Vec<SourceDiagnostic>> {
+        self.compile_cooperatively(timeout)
+    }
+
+    /// "Error likely in field: <field>" lines for errors inside editable fields
+    ///
+    /// Each error span in the main source is matched to the click-to-edit
+    /// link enclosing it (see `field_at_offset`); each field is listed once.
+    fn field_hints(&self, diagnostics: &[SourceDiagnostic]) -> Vec<String> {
+        let mut fields: Vec<String> = Vec::new();
+        for diagnostic in diagnostics {
+            if diagnostic.severity != typst::diag::Severity::Error {
+                continue;
+            }
+            let Some(range) = self.main.range(diagnostic.span) else {
+                continue;
+            };
+            if let Some(field) = field_at_offset(self.main.text(), range.start) {
+                if !fields.contains(&field) {
+                    fields.push(field);
+                }
+            }
+        }
+        fields
+            .into_iter()
+            .map(|field| format!("Error likely in field: {}", field))
+            .collect()
+    }
+
     /// Compile source text to SVG string (convenience method)
     pub fn compile_to_svg(source: &str) -> Result<String, Vec<String>> {
-        let world = Self::new(source);
+        Self::new(source).compile_first_page_svg()
+    }
 
-        match world.compile() {
-            Ok(doc) => {
-                if let Some(page) = doc.pages.first() {
-                    let svg = typst_svg::svg(page);
+    /// Compile the world and render its first page to SVG
+    ///
+    /// Errors are formatted as "Error: message" / "Warning: message", with
+    /// " (line N)" appended when they point into the main source. A
+    /// document without pages or visible content fails with just
+    /// `EMPTY_DOCUMENT_MESSAGE` (see `is_empty_document`).
+    pub fn compile_first_page_svg(&self) -> Result<String, Vec<String>> {
+        self.compile_preview().map(|preview| preview.svg)
+    }
 
-                    // Extract links from the frame and add them to SVG
-                    let links = extract_links_from_frame(&page.frame, Point::zero());
+    /// Compile source text to an SVG without link overlays, returning the
+    /// link regions separately (convenience method)
+    ///
+    /// For embedders that handle clicks themselves instead of relying on
+    /// the `<a>` overlays `compile_to_svg` injects.
+    pub fn compile_to_svg_and_links(
+        source: &str,
+    ) -> Result<(String, Vec<LinkRegion>), Vec<String>> {
+        Self::new(source)
+            .compile_first_page()
+            .map(|(svg, links, _)| (svg, links))
+    }
 
-                    // Post-process SVG to add link overlays
-                    let svg_with_links = add_links_to_svg(&svg, &links, page.frame.size());
+    /// Like `compile_first_page_svg`, also reporting the page count
+    pub fn compile_preview(&self) -> Result<CompiledPreview, Vec<String>> {
+        self.compile_first_page()
+            .map(|(svg, links, page_count)| CompiledPreview {
+                svg: add_links_to_svg(&svg, &links),
+                page_count,
+            })
+    }
 
-                    Ok(svg_with_links)
-                } else {
-                    Err(vec!["Document has no pages".to_string()])
+    /// Compile and render the first page as a plain SVG, its link regions,
+    /// and the total page count
+    fn compile_first_page(&self) -> Result<(String, Vec<LinkRegion>, usize), Vec<String>> {
+        match self.compile() {
+            Ok(doc) => match doc.pages.first() {
+                Some(page) if !document_is_empty(&doc) => {
+                    let (svg, links) = page_svg_and_links(page);
+                    Ok((svg, links, page_count(&doc)))
                 }
-            }
+                _ => Err(vec![EMPTY_DOCUMENT_MESSAGE.to_string()]),
+            },
             Err(diagnostics) => {
-                let errors: Vec<String> = diagnostics
+                let mut errors: Vec<String> = diagnostics
                     .iter()
                     .map(|d| {
                         let severity = match d.severity {
                             typst::diag::Severity::Error => "Error",
                             typst::diag::Severity::Warning => "Warning",
                         };
-                        format!("{}: {}", severity, d.message)
+                        let line = self
+                            .main
+                            .range(d.span)
+                            .and_then(|range| self.main.byte_to_line(range.start));
+                        match line {
+                            Some(line) => {
+                                format!("{}: {} (line {})", severity, d.message, line + 1)
+                            }
+                            None => format!("{}: {}", severity, d.message),
+                        }
                     })
                     .collect();
+                errors.extend(self.field_hints(&diagnostics));
                 Err(errors)
             }
         }
@@ -124,48 +367,113 @@ impl VirtualWorld {
         source: &str,
         cache: &crate::images::ImageCache,
     ) -> Result<String, Vec<String>> {
-        let mut world = Self::new(source);
-
-        // Add images from cache to virtual file system
-        cache.populate_world(&mut world);
-
-        match world.compile() {
-            Ok(doc) => {
-                if let Some(page) = doc.pages.first() {
-                    let svg = typst_svg::svg(page);
+        Self::compile_to_svg_with_assets(
+            source,
+            &BTreeMap::new(),
+            cache,
+            &crate::fonts::FontCache::new(),
+        )
+    }
 
-                    // Extract links from the frame and add them to SVG
-                    let links = extract_links_from_frame(&page.frame, Point::zero());
+    /// Compile source text to SVG string with project partials, cached images
+    /// and uploaded fonts
+    #[cfg(any(target_arch = "wasm32", test))]
+    pub fn compile_to_svg_with_assets(
+        source: &str,
+        partials: &BTreeMap<String, String>,
+        images: &crate::images::ImageCache,
+        fonts: &crate::fonts::FontCache,
+    ) -> Result<String, Vec<String>> {
+        let mut world = Self::new(source);
 
-                    // Post-process SVG to add link overlays
-                    let svg_with_links = add_links_to_svg(&svg, &links, page.frame.size());
+        // Add partials, images and fonts before compiling
+        world.add_partials(partials);
+        images.populate_world(&mut world);
+        fonts.populate_world(&mut world);
 
-                    Ok(svg_with_links)
-                } else {
-                    Err(vec!["Document has no pages".to_string()])
-                }
-            }
-            Err(diagnostics) => {
-                let errors: Vec<String> = diagnostics
-                    .iter()
-                    .map(|d| {
-                        let severity = match d.severity {
-                            typst::diag::Severity::Error => "Error",
-                            typst::diag::Severity::Warning => "Warning",
-                        };
-                        format!("{}: {}", severity, d.message)
-                    })
-                    .collect();
-                Err(errors)
-            }
-        }
+        world.compile_first_page_svg()
     }
 
     /// Get all available fonts
-    #[allow(dead_code)]
     pub fn fonts(&self) -> &[Font] {
         self.font_loader.fonts()
     }
+
+    /// Names of all available font families, sorted and deduplicated
+    pub fn font_families(&self) -> Vec<String> {
+        let mut families: Vec<String> = self
+            .fonts()
+            .iter()
+            .map(|font| font.info().family.clone())
+            .collect();
+        families.sort();
+        families.dedup();
+        families
+    }
+
+    /// Compile the current source and list its headings in document order
+    ///
+    /// Returns an empty outline if compilation fails.
+    pub fn outline(&self) -> Vec<OutlineEntry> {
+        self.compile()
+            .map(|doc| document_outline(&doc))
+            .unwrap_or_default()
+    }
+}
+
+/// Whether a compiled document has no pages, or only pages with no content
+///
+/// Typst always lays out at least one page, so a blank source (or one whose
+/// content all evaluates to nothing) yields a single empty page.
+pub fn document_is_empty(doc: &Document) -> bool {
+    !doc.pages.iter().any(|page| frame_has_content(&page.frame))
+}
+
+/// Whether a frame (or a nested group) draws text, shapes or images
+///
+/// Introspection tags and link areas are invisible on their own.
+fn frame_has_content(frame: &Frame) -> bool {
+    frame.items().any(|(_, item)| match item {
+        FrameItem::Group(group) => frame_has_content(&group.frame),
+        FrameItem::Text(_) | FrameItem::Shape(..) | FrameItem::Image(..) => true,
+        FrameItem::Link(..) | FrameItem::Tag(_) => false,
+    })
+}
+
+/// Whether compile errors only report an empty document
+pub fn is_empty_document(errors: &[String]) -> bool {
+    matches!(errors, [message] if message == EMPTY_DOCUMENT_MESSAGE)
+}
+
+/// A heading in a compiled document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutlineEntry {
+    /// Heading level, starting at 1 for `=`
+    pub level: usize,
+    /// Plain-text heading body
+    pub text: String,
+    /// 1-based page number the heading appears on
+    pub page: usize,
+}
+
+/// Collect the headings of an already-compiled document in document order
+pub fn document_outline(doc: &Document) -> Vec<OutlineEntry> {
+    doc.introspector
+        .query(&Selector::Elem(HeadingElem::elem(), None))
+        .iter()
+        .filter_map(|content| {
+            let heading = content.to_packed::<HeadingElem>()?;
+            let page = content
+                .location()
+                .map(|location| doc.introspector.page(location).get())
+                .unwrap_or(1);
+            Some(OutlineEntry {
+                level: heading.resolve_level(StyleChain::default()).get(),
+                text: heading.body().plain_text().to_string(),
+                page,
+            })
+        })
+        .collect()
 }
 
 impl World for VirtualWorld {
@@ -182,6 +490,9 @@ impl World for VirtualWorld {
     }
 
     fn source(&self, id: FileId) -> FileResult<Source> {
+        if self.deadline.is_expired() {
+            return Err(deadline_error());
+        }
         if id == self.main.id() {
             return Ok(self.main.clone());
         }
@@ -190,18 +501,29 @@ impl World for VirtualWorld {
             .files
             .get(&id)
             .ok_or_else(|| FileError::NotFound(id.vpath().as_rootless_path().into()))?;
-        let text = std::str::from_utf8(content).map_err(|_| FileError::InvalidUtf8)?;
-        Ok(Source::new(id, text.to_string()))
+        let path = id.vpath().as_rootless_path().display().to_string();
+        let text = decode_source(&path, content).map_err(|e| FileError::Other(Some(e.into())))?;
+        Ok(Source::new(id, text))
     }
 
     fn file(&self, id: FileId) -> FileResult<Bytes> {
-        self.files
-            .get(&id)
-            .cloned()
-            .ok_or_else(|| FileError::NotFound(id.vpath().as_rootless_path().into()))
+        if self.deadline.is_expired() {
+            return Err(deadline_error());
+        }
+        if let Some(bytes) = self.files.get(&id) {
+            return Ok(bytes.clone());
+        }
+        let path = id.vpath().as_rootless_path();
+        if path == std::path::Path::new(PLACEHOLDER_IMAGE_PATH) {
+            return Ok(Bytes::from_static(PLACEHOLDER_IMAGE_SVG));
+        }
+        Err(FileError::NotFound(path.into()))
     }
 
     fn font(&self, index: usize) -> Option<Font> {
+        if self.deadline.is_expired() {
+            return None;
+        }
         self.font_loader.font(index)
     }
 
@@ -216,40 +538,153 @@ impl World for VirtualWorld {
     }
 }
 
-/// A link extracted from the document with position and URL
-#[derive(Debug)]
-struct LinkInfo {
-    /// Position relative to page origin
-    x: f64,
-    y: f64,
-    /// Size of the link area
-    width: f64,
-    height: f64,
-    /// Destination URL
-    url: String,
+/// Diagnostic reported when compilation exceeds its time budget
+fn timeout_error(timeout: Duration) -> SourceDiagnostic {
+    SourceDiagnostic::error(
+        Span::detached(),
+        format!("Compilation timed out after {}s", timeout.as_secs_f64()),
+    )
+}
+
+/// File error returned by world accesses after the deadline has passed
+fn deadline_error() -> FileError {
+    FileError::Other(Some("compilation deadline exceeded".into()))
+}
+
+/// Find the click-to-edit field whose content encloses a source offset
+///
+/// Walks back through `#link("cmd://edit/<field>")[...]` calls and returns
+/// the nearest one whose content block is still open at `offset`. This is a
+/// best-effort guess: fields rendered without an edit link are not found.
+pub fn field_at_offset(source: &str, offset: usize) -> Option<String> {
+    const MARKER: &str = "\"cmd://edit/";
+
+    let before = source.get(..offset)?;
+    for (index, _) in before.rmatch_indices(MARKER) {
+        let field_start = index + MARKER.len();
+        let Some(field_len) = source[field_start..].find('"') else {
+            continue;
+        };
+        let field = &source[field_start..field_start + field_len];
+        if field.is_empty() {
+            continue;
+        }
+
+        // The error may sit in the link call itself, before the content block
+        let after_url = field_start + field_len + 1;
+        let Some(args_end) = source[after_url..].find(')').map(|i| after_url + i + 1) else {
+            continue;
+        };
+        if offset < args_end {
+            return Some(field.to_string());
+        }
+        let rest = &source[args_end..];
+        let trimmed = rest.trim_start();
+        if !trimmed.starts_with('[') {
+            continue;
+        }
+        let content_start = args_end + (rest.len() - trimmed.len()) + 1;
+        if offset < content_start || content_is_open(&source[content_start..offset]) {
+            return Some(field.to_string());
+        }
+    }
+    None
+}
+
+/// Whether a content block starting just after its `[` is still open
+///
+/// Backslash escapes are skipped, so `\]` in field text does not close it.
+fn content_is_open(content: &str) -> bool {
+    let mut depth = 1usize;
+    let mut chars = content.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return false;
+                }
+            }
+            _ => {}
+        }
+    }
+    true
+}
+
+/// A clickable link area on a page, in points from the page's top-left corner
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinkRegion {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    /// Destination URL, e.g. `cmd://edit/title`
+    pub url: String,
+}
+
+/// Human-readable label for a link destination
+///
+/// `cmd://edit/...` targets become "Edit <field>"; anything else is labelled
+/// with its URL.
+fn link_label(url: &str) -> String {
+    match url.strip_prefix("cmd://edit/") {
+        Some(field) if !field.is_empty() => {
+            let field = field
+                .split('/')
+                .filter(|part| !part.is_empty())
+                .map(|part| part.replace(['_', '-'], " "))
+                .collect::<Vec<_>>()
+                .join(" ");
+            format!("Edit {}", field)
+        }
+        _ => url.to_string(),
+    }
+}
+
+/// Render a page to SVG with clickable link overlays
+pub fn page_to_svg(page: &typst::layout::Page) -> String {
+    let (svg, links) = page_svg_and_links(page);
+
+    // Post-process SVG to add link overlays
+    add_links_to_svg(&svg, &links)
+}
+
+/// Render a page to SVG without overlays, alongside its link regions
+fn page_svg_and_links(page: &typst::layout::Page) -> (String, Vec<LinkRegion>) {
+    let svg = typst_svg::svg(page);
+    let links = extract_links_from_frame(&page.frame, Transform::identity());
+    (svg, links)
 }
 
 /// Extract all links from a frame recursively
-fn extract_links_from_frame(frame: &Frame, offset: Point) -> Vec<LinkInfo> {
+///
+/// `ts` maps the frame's local coordinates to page coordinates and is
+/// composed with each nested group's transform during recursion.
+fn extract_links_from_frame(frame: &Frame, ts: Transform) -> Vec<LinkRegion> {
     let mut links = Vec::new();
 
     for (pos, item) in frame.items() {
-        let abs_pos = Point::new(offset.x + pos.x, offset.y + pos.y);
-
         match item {
             FrameItem::Link(Destination::Url(url), size) => {
-                links.push(LinkInfo {
-                    x: abs_pos.x.to_pt(),
-                    y: abs_pos.y.to_pt(),
-                    width: size.x.to_pt(),
-                    height: size.y.to_pt(),
+                let (x, y, width, height) = transform_rect(*pos, *size, ts);
+                links.push(LinkRegion {
+                    x,
+                    y,
+                    width,
+                    height,
                     url: url.as_str().to_string(),
                 });
             }
             FrameItem::Group(group) => {
                 // Recursively extract links from nested groups
-                let group_offset = apply_transform(abs_pos, group.transform);
-                let nested_links = extract_links_from_frame(&group.frame, group_offset);
+                let group_ts = ts
+                    .pre_concat(Transform::translate(pos.x, pos.y))
+                    .pre_concat(group.transform);
+                let nested_links = extract_links_from_frame(&group.frame, group_ts);
                 links.extend(nested_links);
             }
             _ => {}
@@ -259,17 +694,41 @@ fn extract_links_from_frame(frame: &Frame, offset: Point) -> Vec<LinkInfo> {
     links
 }
 
-/// Apply a transform to a point (simplified - only handles translation)
-fn apply_transform(pos: Point, transform: Transform) -> Point {
-    // Transform matrix is [[a, b, c], [d, e, f]] where c and f are translations
-    Point::new(
-        Abs::pt(pos.x.to_pt() + transform.tx.to_pt()),
-        Abs::pt(pos.y.to_pt() + transform.ty.to_pt()),
-    )
+/// Apply a full affine transform to a rectangle
+///
+/// Returns the axis-aligned bounding box `(x, y, width, height)` in points,
+/// so rotated or skewed links still get a clickable area covering them.
+fn transform_rect(pos: Point, size: Size, ts: Transform) -> (f64, f64, f64, f64) {
+    let corners = [
+        pos,
+        Point::new(pos.x + size.x, pos.y),
+        Point::new(pos.x, pos.y + size.y),
+        Point::new(pos.x + size.x, pos.y + size.y),
+    ]
+    .map(|corner| corner.transform(ts));
+
+    let min_x = corners
+        .iter()
+        .map(|p| p.x.to_pt())
+        .fold(f64::INFINITY, f64::min);
+    let min_y = corners
+        .iter()
+        .map(|p| p.y.to_pt())
+        .fold(f64::INFINITY, f64::min);
+    let max_x = corners
+        .iter()
+        .map(|p| p.x.to_pt())
+        .fold(f64::NEG_INFINITY, f64::max);
+    let max_y = corners
+        .iter()
+        .map(|p| p.y.to_pt())
+        .fold(f64::NEG_INFINITY, f64::max);
+
+    (min_x, min_y, max_x - min_x, max_y - min_y)
 }
 
 /// Add link overlay elements to the SVG
-fn add_links_to_svg(svg: &str, links: &[LinkInfo], _page_size: Size) -> String {
+fn add_links_to_svg(svg: &str, links: &[LinkRegion]) -> String {
     if links.is_empty() {
         return svg.to_string();
     }
@@ -277,10 +736,22 @@ fn add_links_to_svg(svg: &str, links: &[LinkInfo], _page_size: Size) -> String {
     // Build link elements as SVG <a> tags with transparent rectangles
     let mut link_elements = String::new();
     for link in links {
-        // Create a clickable rectangle for each link
+        // Edit commands act as buttons; external URLs keep plain link semantics
+        let role = if link.url.starts_with("cmd://") {
+            r#" role="button""#
+        } else {
+            ""
+        };
+        let label = escape_xml(&link_label(&link.url));
+
+        // Create a focusable, labelled clickable rectangle for each link
         link_elements.push_str(&format!(
-            r#"<a href="{}" target="_self"><rect x="{}" y="{}" width="{}" height="{}" fill="transparent" style="cursor: pointer;" /></a>"#,
+            r#"<a href="{}" {}{} tabindex="0" aria-label="{}"><title>{}</title><rect x="{}" y="{}" width="{}" height="{}" fill="transparent" style="cursor: pointer;" /></a>"#,
             escape_xml(&link.url),
+            link_target(&link.url),
+            role,
+            label,
+            label,
             link.x,
             link.y,
             link.width,
@@ -300,6 +771,20 @@ fn add_links_to_svg(svg: &str, links: &[LinkInfo], _page_size: Size) -> String {
     }
 }
 
+/// Target attributes for a link overlay
+///
+/// Web URLs open in a new tab so following them never navigates the editor
+/// away; `cmd://` and other links stay in place for the click interceptor
+/// or the browser to handle.
+fn link_target(url: &str) -> &'static str {
+    let lower = url.to_ascii_lowercase();
+    if lower.starts_with("http://") || lower.starts_with("https://") {
+        r#"target="_blank" rel="noopener""#
+    } else {
+        r#"target="_self""#
+    }
+}
+
 /// Escape XML special characters in a string
 fn escape_xml(s: &str) -> String {
     s.replace('&', "&amp;")
@@ -148,3 +148,693 @@ fn test_compile_with_multiple_links() {
         "SVG should contain body link"
     );
 }
+
+/// Compile a document and extract the links from its first page
+fn links_for(source: &str) -> Vec<LinkRegion> {
+    let world = VirtualWorld::new(source);
+    let doc = world.compile().expect("compilation should succeed");
+    let page = doc.pages.first().expect("document should have a page");
+    extract_links_from_frame(&page.frame, Transform::identity())
+}
+
+#[test]
+fn test_link_extraction_applies_scale_transform() {
+    let body = r#"#pad(left: 100pt, top: 40pt)[#link("cmd://edit/title")[Title]]"#;
+    let unscaled = links_for(&format!("#set page(margin: 0pt)\n{}", body));
+    let scaled = links_for(&format!(
+        "#set page(margin: 0pt)\n#scale(50%, origin: top + left)[{}]",
+        body
+    ));
+
+    assert_eq!(unscaled.len(), 1);
+    assert_eq!(scaled.len(), 1);
+
+    let (full, half) = (&unscaled[0], &scaled[0]);
+    assert_eq!(half.url, "cmd://edit/title");
+    assert!(
+        (half.x - full.x / 2.0).abs() < 0.01,
+        "{:?} vs {:?}",
+        half,
+        full
+    );
+    assert!(
+        (half.y - full.y / 2.0).abs() < 0.01,
+        "{:?} vs {:?}",
+        half,
+        full
+    );
+    assert!((half.width - full.width / 2.0).abs() < 0.01);
+    assert!((half.height - full.height / 2.0).abs() < 0.01);
+}
+
+#[test]
+fn test_link_extraction_applies_rotate_transform() {
+    let body = r#"#link("cmd://edit/title")[A fairly long title]"#;
+    let plain = links_for(body);
+    let rotated = links_for(&format!("#rotate(90deg)[{}]", body));
+
+    assert_eq!(rotated.len(), 1);
+    // A quarter turn swaps the bounding box dimensions
+    assert!((rotated[0].width - plain[0].height).abs() < 0.01);
+    assert!((rotated[0].height - plain[0].width).abs() < 0.01);
+}
+
+#[test]
+fn test_compile_to_svg_and_links_returns_regions_without_overlays() {
+    let gallery = crate::templates::TemplateGallery::new();
+    let template = gallery
+        .get("minimal")
+        .expect("minimal template should exist");
+
+    let (svg, links) = VirtualWorld::compile_to_svg_and_links(template.source)
+        .expect("compilation should succeed");
+
+    let title = links
+        .iter()
+        .find(|link| link.url == "cmd://edit/title")
+        .expect("title link region should be returned");
+    assert!(title.width > 0.0 && title.height > 0.0);
+    assert!(!svg.contains("cmd://edit/"), "SVG should have no overlays");
+}
+
+#[test]
+fn test_link_overlay_has_accessible_label() {
+    let svg = VirtualWorld::compile_to_svg(r#"#link("cmd://edit/title")[Title]"#)
+        .expect("compilation should succeed");
+
+    assert!(
+        svg.contains(r#"aria-label="Edit title""#),
+        "overlay should be labelled: {}",
+        svg
+    );
+    assert!(svg.contains(r#"role="button""#));
+    assert!(svg.contains(r#"tabindex="0""#));
+}
+
+#[test]
+fn test_external_links_open_in_new_tab() {
+    let svg = VirtualWorld::compile_to_svg(
+        r#"#link("cmd://edit/title")[Title]
+
+#link("https://example.com")[Website]"#,
+    )
+    .expect("compilation should succeed");
+
+    assert!(
+        svg.contains(r#"href="https://example.com" target="_blank" rel="noopener""#),
+        "external link should open in a new tab: {}",
+        svg
+    );
+    assert!(svg.contains(r#"href="cmd://edit/title" target="_self""#));
+}
+
+#[test]
+fn test_link_label() {
+    assert_eq!(link_label("cmd://edit/title"), "Edit title");
+    assert_eq!(link_label("cmd://edit/features/1"), "Edit features 1");
+    assert_eq!(
+        link_label("cmd://edit/meta/contact_email"),
+        "Edit meta contact email"
+    );
+    assert_eq!(link_label("https://example.com"), "https://example.com");
+}
+
+#[test]
+fn test_outline_lists_headings_in_order() {
+    let world = VirtualWorld::new("= A\nIntro\n== B\nDetails\n#pagebreak()\n= C *bold*");
+    let outline = world.outline();
+
+    let summary: Vec<(usize, &str, usize)> = outline
+        .iter()
+        .map(|entry| (entry.level, entry.text.as_str(), entry.page))
+        .collect();
+    assert_eq!(summary, vec![(1, "A", 1), (2, "B", 1), (1, "C bold", 2)]);
+}
+
+#[test]
+fn test_outline_empty_on_compile_error() {
+    let world = VirtualWorld::new("= A\n#unknown_function()");
+    assert!(world.outline().is_empty());
+}
+
+#[test]
+fn test_include_registered_partial() {
+    let mut partials = std::collections::BTreeMap::new();
+    partials.insert(
+        "partials/header.typ".to_string(),
+        "= Shared Header".to_string(),
+    );
+    partials.insert(
+        "lib/brand.typ".to_string(),
+        "#let tagline = [Built with partials]".to_string(),
+    );
+
+    let mut world = VirtualWorld::new(
+        r#"#include "partials/header.typ"
+#import "lib/brand.typ": tagline
+#tagline"#,
+    );
+    world.add_partials(&partials);
+
+    let outline = world.outline();
+    assert_eq!(outline.len(), 1);
+    assert_eq!(outline[0].text, "Shared Header");
+    assert!(world.compile_first_page_svg().is_ok());
+}
+
+#[test]
+fn test_include_missing_partial_fails() {
+    let world = VirtualWorld::new(r#"#include "partials/missing.typ""#);
+    let errors = world.compile_first_page_svg().unwrap_err();
+    assert!(
+        errors.iter().any(|e| e.contains("not found")),
+        "{:?}",
+        errors
+    );
+}
+
+#[test]
+fn test_add_font_from_fixture() {
+    let mut world = VirtualWorld::new(
+        r#"#set text(font: "Tuffy")
+Hello"#,
+    );
+    let before = world.fonts().len();
+
+    let families = world
+        .add_font(include_bytes!("../../tests/fixtures/fonts/Tuffy.ttf").to_vec())
+        .expect("fixture font should parse");
+
+    assert_eq!(families, vec!["Tuffy".to_string()]);
+    assert_eq!(world.fonts().len(), before + 1);
+    assert!(world.fonts().iter().any(|f| f.info().family == "Tuffy"));
+    assert!(world.book().select_family("tuffy").next().is_some());
+    assert!(world.compile().is_ok());
+}
+
+#[test]
+fn test_add_font_rejects_unparseable_bytes() {
+    let mut world = VirtualWorld::new("Hello");
+    let before = world.fonts().len();
+
+    let result = world.add_font(b"definitely not a font".to_vec());
+
+    assert!(result.unwrap_err().contains("Unparseable font file"));
+    assert_eq!(world.fonts().len(), before);
+}
+
+#[test]
+fn test_compile_times_out_on_expensive_source() {
+    // Seconds of pure evaluation, far beyond the budget
+    let source = r#"#let total = 0
+#for i in range(1000000) { total += i }
+#total"#;
+    let budget = std::time::Duration::from_millis(100);
+    let world = VirtualWorld::new(source).with_timeout(Some(budget));
+
+    let started = std::time::Instant::now();
+    let errors = world
+        .compile()
+        .expect_err("expensive source should time out");
+
+    assert_eq!(
+        errors[0].message.as_str(),
+        "Compilation timed out after 0.1s"
+    );
+    assert!(started.elapsed() < std::time::Duration::from_secs(1));
+}
+
+#[test]
+fn test_cooperative_deadline_stops_at_world_access() {
+    // The loop outlasts the budget; the file read after it is where it stops
+    let source = r#"#let total = 0
+#for i in range(300000) { total += i }
+#read("data.txt")"#;
+    let mut world = VirtualWorld::new(source);
+    world.add_file("data.txt", "contents".as_bytes().to_vec());
+
+    let errors = world
+        .compile_cooperatively(std::time::Duration::from_millis(50))
+        .expect_err("an expired deadline should stop the compile");
+    assert_eq!(
+        errors[0].message.as_str(),
+        "Compilation timed out after 0.05s"
+    );
+
+    // The deadline is cleared afterwards
+    assert!(world.compile().is_ok());
+}
+
+#[test]
+fn test_native_world_has_no_default_timeout() {
+    assert!(VirtualWorld::new("Hello").timeout.is_none());
+}
+
+#[test]
+fn test_compile_within_timeout_succeeds() {
+    let world = VirtualWorld::new("Hello").with_timeout(Some(std::time::Duration::from_secs(30)));
+    assert!(world.compile().is_ok());
+    assert!(VirtualWorld::new("Hello")
+        .with_timeout(None)
+        .compile()
+        .is_ok());
+}
+
+#[test]
+fn test_compile_cache_reuses_output() {
+    let mut cache = CompileCache::default();
+    let mut compiles = 0;
+    let source = "Hello cache";
+
+    for _ in 0..2 {
+        let svg = cache
+            .get_or_compile(source, &["img_1", "img_2"], || {
+                compiles += 1;
+                VirtualWorld::compile_to_svg(source)
+            })
+            .unwrap();
+        assert!(svg.contains("<svg"));
+    }
+    // Asset order does not change the key
+    cache
+        .get_or_compile(source, &["img_2", "img_1"], || {
+            compiles += 1;
+            VirtualWorld::compile_to_svg(source)
+        })
+        .unwrap();
+
+    assert_eq!(compiles, 1);
+    assert_eq!(cache.len(), 1);
+}
+
+#[test]
+fn test_compile_cache_keys_on_assets_and_skips_errors() {
+    let mut cache = CompileCache::default();
+    let mut compiles = 0;
+    let mut compile = |ids: &[&str]| {
+        cache.get_or_compile("Same", ids, || {
+            compiles += 1;
+            Ok(format!("svg-{}", ids.len()))
+        })
+    };
+    compile(&[]).unwrap();
+    compile(&["img_1"]).unwrap();
+    assert_eq!(compiles, 2);
+
+    let mut failures = 0;
+    for _ in 0..2 {
+        let result = cache.get_or_compile("#bad(", &[] as &[&str], || {
+            failures += 1;
+            Err(vec!["Error".to_string()])
+        });
+        assert!(result.is_err());
+    }
+    assert_eq!(failures, 2);
+}
+
+#[test]
+fn test_compile_cache_evicts_least_recently_used() {
+    let mut cache = CompileCache::new(2);
+    let mut compiles = 0;
+    let mut get = |cache: &mut CompileCache, source: &str| {
+        cache
+            .get_or_compile(source, &[] as &[&str], || {
+                compiles += 1;
+                Ok(source.to_string())
+            })
+            .unwrap()
+    };
+
+    get(&mut cache, "a");
+    get(&mut cache, "b");
+    get(&mut cache, "a"); // refresh "a"
+    get(&mut cache, "c"); // evicts "b"
+    get(&mut cache, "a"); // still cached
+    get(&mut cache, "b"); // recompiled
+
+    assert_eq!(compiles, 4);
+    assert_eq!(cache.len(), 2);
+}
+
+#[test]
+fn test_referenced_image_ids() {
+    let source = r#"#set page(background: image("img_bg.png", width: 100%))
+#image("img_logo.svg", width: 2in)
+#figure(image( "img_logo.svg"))
+#myimage("img_other.png")
+#image(path)"#;
+
+    assert_eq!(referenced_image_ids(source), vec!["img_bg", "img_logo"]);
+    assert!(referenced_image_ids("No images here").is_empty());
+}
+
+#[test]
+fn test_compile_cache_misses_after_image_bytes_change() {
+    let source = r#"#image("img_photo.svg", width: 1in)"#;
+    let svg = |fill: &str| {
+        format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10"><rect width="10" height="10" fill="{}"/></svg>"#,
+            fill
+        )
+    };
+    let mut images = crate::images::ImageCache::new();
+    let mut cache = CompileCache::default();
+    let mut compiles = 0;
+    let mut compile = |images: &crate::images::ImageCache| {
+        let keys = image_version_keys(source, images);
+        cache
+            .get_or_compile(source, &keys, || {
+                compiles += 1;
+                VirtualWorld::compile_to_svg_with_images(source, images)
+            })
+            .unwrap()
+    };
+
+    images.add(
+        "img_photo".to_string(),
+        svg("red").into_bytes(),
+        "svg".to_string(),
+    );
+    let first = compile(&images);
+    assert_eq!(compile(&images), first);
+
+    // Same ID, new bytes
+    images.add(
+        "img_photo".to_string(),
+        svg("blue").into_bytes(),
+        "svg".to_string(),
+    );
+    let second = compile(&images);
+
+    assert_eq!(compiles, 2);
+    assert_ne!(first, second);
+}
+
+// ============================================================================
+// SVG Minification Tests
+// ============================================================================
+
+/// Source with an external link and an edit link, compiled with overlays
+fn linked_svg() -> String {
+    let source = r#"#set page(width: 3in, height: 2in)
+= Hello
+#link("https://example.com")[Site] and #link("cmd://edit/title")[Edit]
+"#;
+    VirtualWorld::compile_to_svg(source).unwrap()
+}
+
+/// Assert that an SVG tokenizes and its elements nest properly
+fn assert_well_formed(svg: &str) {
+    let mut open: Vec<String> = Vec::new();
+    let mut pending = None;
+    for token in xmlparser::Tokenizer::from(svg) {
+        match token.unwrap_or_else(|e| panic!("{}: {}", e, svg)) {
+            xmlparser::Token::ElementStart { local, .. } => pending = Some(local.to_string()),
+            xmlparser::Token::ElementEnd { end, .. } => match end {
+                xmlparser::ElementEnd::Open => open.push(pending.take().unwrap()),
+                xmlparser::ElementEnd::Empty => pending = None,
+                xmlparser::ElementEnd::Close(_, local) => {
+                    assert_eq!(open.pop().as_deref(), Some(local.as_str()));
+                }
+            },
+            _ => {}
+        }
+    }
+    assert!(open.is_empty(), "unclosed elements: {:?}", open);
+}
+
+#[test]
+fn test_minify_svg_shrinks_and_stays_well_formed() {
+    let svg = linked_svg();
+    let minified = minify_svg(&svg);
+
+    assert!(
+        minified.len() < svg.len(),
+        "{} vs {}",
+        minified.len(),
+        svg.len()
+    );
+    assert!(!minified.contains(">\n"));
+    assert!(!minified.contains(r#"fill-rule="nonzero""#));
+    assert_well_formed(&minified);
+    // Minifying again changes nothing
+    assert_eq!(minify_svg(&minified), minified);
+}
+
+#[test]
+fn test_minify_svg_keeps_link_overlays() {
+    let minified = minify_svg(&linked_svg());
+
+    assert!(
+        minified.contains(r#"<a href="https://example.com""#),
+        "{}",
+        minified
+    );
+    assert!(
+        minified.contains(r#"<a href="cmd://edit/title""#),
+        "{}",
+        minified
+    );
+    assert!(minified.contains(r#"fill="transparent""#));
+    assert!(
+        minified.contains("<title>Edit title</title>"),
+        "{}",
+        minified
+    );
+}
+
+#[test]
+fn test_minify_svg_can_remove_edit_overlays() {
+    let options = MinifyOptions {
+        remove_edit_overlays: true,
+    };
+    let minified = minify_svg_with_options(&linked_svg(), &options);
+
+    assert!(!minified.contains("cmd://"), "{}", minified);
+    assert!(minified.contains(r#"<a href="https://example.com""#));
+    assert_well_formed(&minified);
+
+    // Overlays go even when the SVG doesn't parse
+    let svg = linked_svg();
+    let truncated = &svg[..svg.rfind("</svg>").unwrap()];
+    let stripped = minify_svg_with_options(truncated, &options);
+    assert!(!stripped.contains("cmd://"), "{}", stripped);
+    assert!(stripped.contains(r#"<a href="https://example.com""#));
+}
+
+#[test]
+fn test_minify_svg_keeps_values_that_override_parents() {
+    let svg = r##"<svg xmlns="http://www.w3.org/2000/svg">
+  <!-- comment -->
+  <g fill="#ff0000">
+    <rect x="0" y="2" width="5" height="5" fill="#000000"/>
+    <rect width="5" height="5" fill="#ff0000"/>
+  </g>
+  <defs><path id="p" fill="#000000" d="M0 0"/></defs>
+  <text x="0"> spaced  text </text>
+</svg>"##;
+
+    assert_eq!(
+        minify_svg(svg),
+        r##"<svg xmlns="http://www.w3.org/2000/svg"><g fill="#ff0000"><rect y="2" width="5" height="5" fill="#000000"/><rect width="5" height="5"/></g><defs><path id="p" fill="#000000" d="M0 0"/></defs><text x="0"> spaced  text </text></svg>"##
+    );
+    // Input that does not parse is left alone
+    assert_eq!(minify_svg("<svg><g"), "<svg><g");
+}
+
+#[test]
+fn test_minify_svg_keeps_inherited_values_under_css() {
+    // A stylesheet can target any element, so nothing is assumed inherited
+    let styled = r##"<svg><style>rect { fill: red }</style><g><rect fill="#000000"/></g></svg>"##;
+    assert_eq!(minify_svg(styled), styled);
+
+    // Without one, classes don't stop defaults from being dropped
+    let classed = r##"<svg><g class="typst-text"><rect fill="#000000"/></g></svg>"##;
+    assert_eq!(
+        minify_svg(classed),
+        r#"<svg><g class="typst-text"><rect/></g></svg>"#
+    );
+}
+
+// ============================================================================
+// Incremental Compilation Tests
+// ============================================================================
+
+/// A long document whose paragraph `edited` says "changed"
+fn long_document(edited: Option<usize>) -> String {
+    let mut source = String::from("#set page(height: auto)\n= Report\n");
+    for i in 0..200 {
+        if Some(i) == edited {
+            source.push_str(&format!("Paragraph {} was changed.\n\n", i));
+        } else {
+            source.push_str(&format!("Paragraph {} of the report body text.\n\n", i));
+        }
+    }
+    source
+}
+
+#[test]
+fn test_incremental_compile_matches_fresh_compile() {
+    let partials = BTreeMap::new();
+    let images = crate::images::ImageCache::new();
+    let fonts = crate::fonts::FontCache::new();
+    let mut compiler = IncrementalCompiler::new();
+
+    let first = compiler
+        .compile_to_svg(&long_document(None), &partials, &images, &fonts)
+        .unwrap();
+    assert_eq!(
+        first,
+        VirtualWorld::compile_to_svg(&long_document(None)).unwrap()
+    );
+
+    let edited = long_document(Some(120));
+    let second = compiler
+        .compile_to_svg(&edited, &partials, &images, &fonts)
+        .unwrap();
+    assert_ne!(first, second);
+    assert_eq!(second, VirtualWorld::compile_to_svg(&edited).unwrap());
+}
+
+#[test]
+fn test_incremental_compile_refreshes_assets() {
+    let images = crate::images::ImageCache::new();
+    let fonts = crate::fonts::FontCache::new();
+    let mut compiler = IncrementalCompiler::new();
+    let source = "#include \"partials/footer.typ\"";
+
+    let mut partials = BTreeMap::new();
+    partials.insert(
+        "partials/footer.typ".to_string(),
+        "First footer".to_string(),
+    );
+    let first = compiler.compile_to_svg(source, &partials, &images, &fonts);
+    assert!(first.is_ok());
+
+    partials.insert(
+        "partials/footer.typ".to_string(),
+        "Second footer".to_string(),
+    );
+    let second = compiler.compile_to_svg(source, &partials, &images, &fonts);
+    assert_eq!(
+        second,
+        VirtualWorld::compile_to_svg_with_assets(source, &partials, &images, &fonts)
+    );
+    assert_ne!(first, second);
+
+    // Removed partials are no longer visible
+    let third = compiler.compile_to_svg(source, &BTreeMap::new(), &images, &fonts);
+    assert!(third.is_err());
+}
+
+// ============================================================================
+// Empty Document Tests
+// ============================================================================
+
+#[test]
+fn test_empty_document_is_reported() {
+    for source in [
+        "",
+        "  \n",
+        "#context none",
+        "#set page(fill: rgb(\"#eeeeee\"))",
+    ] {
+        let errors = VirtualWorld::compile_to_svg(source).unwrap_err();
+        assert!(
+            is_empty_document(&errors),
+            "{:?} should be empty, got {:?}",
+            source,
+            errors
+        );
+        assert_eq!(errors, vec![EMPTY_DOCUMENT_MESSAGE.to_string()]);
+    }
+}
+
+#[test]
+fn test_document_with_content_is_not_empty() {
+    let doc = VirtualWorld::new("#context [Hello]").compile().unwrap();
+    assert!(!document_is_empty(&doc));
+    assert!(VirtualWorld::compile_to_svg("#rect(width: 1cm)").is_ok());
+
+    let errors = VirtualWorld::compile_to_svg("#invalid_function()").unwrap_err();
+    assert!(!is_empty_document(&errors));
+}
+
+#[test]
+fn test_font_families_lists_embedded_fonts() {
+    let families = VirtualWorld::new("").font_families();
+
+    assert!(families.iter().any(|family| family == "Inter"));
+    let mut sorted = families.clone();
+    sorted.sort();
+    sorted.dedup();
+    assert_eq!(families, sorted);
+}
+
+#[test]
+fn test_bom_prefixed_source_compiles() {
+    let source = decode_source("main.typ", b"\xEF\xBB\xBF= Title\nBody").unwrap();
+    assert_eq!(source, "= Title\nBody");
+
+    // The heading on the first line is still recognised
+    let world = VirtualWorld::new("\u{FEFF}= Title\nBody");
+    assert_eq!(world.outline().len(), 1);
+    assert!(world.compile_first_page_svg().is_ok());
+}
+
+#[test]
+fn test_invalid_utf8_source_reports_file_and_offset() {
+    let error = decode_source("sheet.typ", b"\xEF\xBB\xBF= Ti\xFFtle").unwrap_err();
+    assert_eq!(
+        error,
+        "sheet.typ is not valid UTF-8 (invalid byte at offset 7)"
+    );
+
+    let mut world = VirtualWorld::new(r#"#include "bad.typ""#);
+    world.add_file("bad.typ", b"= Ti\xFFtle".to_vec());
+    let errors = world.compile_first_page_svg().unwrap_err();
+    assert!(
+        errors
+            .iter()
+            .any(|e| e.contains("bad.typ is not valid UTF-8 (invalid byte at offset 4)")),
+        "{:?}",
+        errors
+    );
+}
+
+#[test]
+fn test_compile_preview_reports_page_count() {
+    let single = VirtualWorld::new("= One page").compile_preview().unwrap();
+    assert_eq!(single.page_count, 1);
+
+    let world = VirtualWorld::new("Page one\n#pagebreak()\nPage two\n#pagebreak()\nThree");
+    let doc = world.compile().unwrap();
+    assert_eq!(page_count(&doc), 3);
+    let preview = world.compile_preview().unwrap();
+    assert_eq!(preview.page_count, 3);
+    assert_eq!(Ok(preview.svg), world.compile_first_page_svg());
+}
+
+#[test]
+fn test_compile_error_names_enclosing_field() {
+    let source = concat!(
+        "#link(\"cmd://edit/title\")[Launch]\n",
+        "#link(\"cmd://edit/body\")[Ships #undefined-thing soon]\n",
+        "#link(\"cmd://edit/subtitle\")[Fine]\n",
+    );
+
+    let errors = VirtualWorld::compile_to_svg(source).unwrap_err();
+    assert!(errors[0].starts_with("Error: unknown variable"));
+    assert!(errors[0].ends_with("(line 2)"), "{}", errors[0]);
+    assert!(errors.contains(&"Error likely in field: body".to_string()));
+    assert!(!errors.iter().any(|e| e.contains("field: title")));
+
+    // Text after a closed link is not attributed to it
+    let after_link = "#link(\"cmd://edit/title\")[Launch] #oops";
+    assert_eq!(field_at_offset(after_link, after_link.len() - 4), None);
+    let escaped = "#link(\"cmd://edit/title\")[a \\] b #oops]";
+    assert_eq!(
+        field_at_offset(escaped, escaped.len() - 5),
+        Some("title".to_string())
+    );
+}